@@ -0,0 +1,127 @@
+//! `ccx` configuration, a TOML file naming the cluster, the signing keypair
+//! and the x25519 key pair confidential inputs are encrypted under.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anchor_client::Cluster;
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+pub const DEFAULT_CONFIG_PATH: &str = "~/.config/ccx/config.toml";
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// `localnet`, `devnet`, `mainnet` or an RPC URL
+    pub cluster: String,
+    /// Solana keypair file that signs and pays
+    pub keypair: String,
+    /// Hex x25519 secret, written by `ccx keygen`
+    pub x25519_keypair: String,
+    /// Hex MXE x25519 public key, read from the MXE account when unset
+    pub mxe_public_key: Option<String>,
+    /// Arcium cluster offset, read from the MXE account when unset
+    pub cluster_offset: Option<u32>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            cluster: "localnet".to_string(),
+            keypair: "~/.config/solana/id.json".to_string(),
+            x25519_keypair: "~/.config/ccx/x25519.key".to_string(),
+            mxe_public_key: None,
+            cluster_offset: None,
+        }
+    }
+}
+
+impl Config {
+    /// Loads `path`, falling back to localnet defaults when the default
+    /// config file does not exist
+    pub fn load(path: &str) -> Result<Self> {
+        let resolved = expand_home(path);
+        if !resolved.exists() && path == DEFAULT_CONFIG_PATH {
+            return Ok(Config::default());
+        }
+        let text = fs::read_to_string(&resolved)
+            .with_context(|| format!("reading config {}", resolved.display()))?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        toml::from_str(text).context("parsing config")
+    }
+
+    pub fn cluster(&self) -> Result<Cluster> {
+        Cluster::from_str(&self.cluster).map_err(|e| anyhow!("invalid cluster {}: {e}", self.cluster))
+    }
+
+    pub fn keypair_path(&self) -> PathBuf {
+        expand_home(&self.keypair)
+    }
+
+    pub fn x25519_keypair_path(&self) -> PathBuf {
+        expand_home(&self.x25519_keypair)
+    }
+
+    pub fn mxe_public_key(&self) -> Result<Option<[u8; 32]>> {
+        self.mxe_public_key
+            .as_deref()
+            .map(|key| parse_hex(key).context("mxe_public_key"))
+            .transpose()
+    }
+}
+
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Parses hex, with or without a `0x` prefix, into exactly `N` bytes
+pub fn parse_hex<const N: usize>(s: &str) -> Result<[u8; N]> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if s.len() != 2 * N || !s.is_ascii() {
+        return Err(anyhow!("expected {N} hex-encoded bytes"));
+    }
+    let mut bytes = [0u8; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16)?;
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_localnet() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config, Config::default());
+        assert_eq!(config.cluster().unwrap(), Cluster::Localnet);
+    }
+
+    #[test]
+    fn parses_overrides() {
+        let config = Config::parse(
+            r#"
+            cluster = "http://127.0.0.1:8899"
+            keypair = "/keys/maker.json"
+            mxe_public_key = "0x0101010101010101010101010101010101010101010101010101010101010101"
+            cluster_offset = 1116522165
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.keypair_path(), PathBuf::from("/keys/maker.json"));
+        assert_eq!(config.mxe_public_key().unwrap(), Some([1; 32]));
+        assert_eq!(config.cluster_offset, Some(1116522165));
+        assert!(Config::parse("rpc = \"x\"").is_err());
+    }
+}
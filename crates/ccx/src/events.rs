@@ -0,0 +1,85 @@
+//! Encrypted outputs emitted by callbacks, read back from transaction logs.
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use confidential_exchange_client::program::{ConfidentialDepositNativeEvent, EventHeader, SumEvent};
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+/// A callback output encrypted to the client that queued the computation
+#[derive(Clone, Debug)]
+pub struct EncryptedOutput {
+    pub event: &'static str,
+    pub header: EventHeader,
+    pub ciphertext: [u8; 32],
+    pub nonce: u128,
+}
+
+fn decode<T: AnchorDeserialize + Discriminator>(data: &[u8]) -> Option<T> {
+    let body = data.strip_prefix(T::DISCRIMINATOR)?;
+    T::deserialize(&mut &body[..]).ok()
+}
+
+/// Every ciphertext-carrying event in `logs`
+pub fn encrypted_outputs(logs: &[String]) -> Vec<EncryptedOutput> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix(PROGRAM_DATA))
+        .filter_map(|data| STANDARD.decode(data).ok())
+        .filter_map(|data| {
+            if let Some(event) = decode::<SumEvent>(&data) {
+                Some(EncryptedOutput {
+                    event: "SumEvent",
+                    header: event.header,
+                    ciphertext: event.sum,
+                    nonce: u128::from_le_bytes(event.nonce),
+                })
+            } else {
+                decode::<ConfidentialDepositNativeEvent>(&data).map(|event| EncryptedOutput {
+                    event: "ConfidentialDepositNativeEvent",
+                    header: event.header,
+                    ciphertext: event.processed_amount,
+                    nonce: u128::from_le_bytes(event.nonce),
+                })
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{prelude::Pubkey, Event};
+    use confidential_exchange_client::program::OfferKind;
+
+    use super::*;
+
+    #[test]
+    fn reads_encrypted_outputs_from_logs() {
+        let event = SumEvent {
+            header: EventHeader {
+                schema_version: 1,
+                offer_kind: OfferKind::Standalone,
+                offer_id: 0,
+                offer: Pubkey::default(),
+                maker: Pubkey::default(),
+                computation_offset: 7,
+                status_before: None,
+                status_after: None,
+                slot: 1,
+                unix_timestamp: 0,
+            },
+            sum: [9; 32],
+            nonce: 5u128.to_le_bytes(),
+        };
+        let logs = vec![
+            "Program log: Instruction: AddTogetherCallback".to_string(),
+            format!("{PROGRAM_DATA}{}", STANDARD.encode(event.data())),
+            format!("{PROGRAM_DATA}{}", STANDARD.encode([0u8; 12])),
+        ];
+        let outputs = encrypted_outputs(&logs);
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].event, "SumEvent");
+        assert_eq!(outputs[0].header.computation_offset, 7);
+        assert_eq!(outputs[0].ciphertext, [9; 32]);
+        assert_eq!(outputs[0].nonce, 5);
+    }
+}
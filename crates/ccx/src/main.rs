@@ -0,0 +1,485 @@
+//! `ccx`, a command line client for makers and takers of the confidential
+//! cross-chain exchange.
+//!
+//! Confidential inputs are encrypted under the x25519 key pair from the
+//! config, so `ccx decrypt-events` can read back outputs encrypted to it.
+
+mod config;
+mod events;
+mod session;
+
+use std::{
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anchor_client::solana_sdk::{
+    signature::{read_keypair_file, Keypair, Signature},
+    signer::Signer,
+};
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, bail, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use confidential_exchange_client::{
+    crypto::{identity_hash, X25519Keypair},
+    instructions,
+    pda,
+    program::{instruction, ChainConfig, InterchainOffer, IntraChainOffer, OfferKind},
+    Fe,
+};
+
+use crate::{
+    config::{expand_home, parse_hex, Config, DEFAULT_CONFIG_PATH},
+    session::{computation_offset, Session},
+};
+
+#[derive(Parser)]
+#[command(name = "ccx", version, about)]
+struct Cli {
+    /// Config file; localnet defaults are used when the default one is missing
+    #[arg(long, env = "CCX_CONFIG", default_value = DEFAULT_CONFIG_PATH)]
+    config: String,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate the x25519 key pair confidential inputs are encrypted under
+    Keygen {
+        /// Replace an existing key pair
+        #[arg(long)]
+        force: bool,
+    },
+    /// Create an intrachain offer, verifying the seller identity via MPC
+    CreateIntrachain {
+        #[arg(long)]
+        id: u64,
+        /// Lamports offered
+        #[arg(long)]
+        offered: u64,
+        /// Lamports wanted in return
+        #[arg(long)]
+        wanted: u64,
+        /// Identity whose hash the buyer must match
+        #[arg(long)]
+        identity: String,
+        #[arg(long, default_value_t = 3600)]
+        expires_in: i64,
+        #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+        taker_native: bool,
+        /// Use the SPL deposit circuit
+        #[arg(long)]
+        spl: bool,
+    },
+    /// Create an interchain offer filled on an EVM chain
+    CreateInterchain {
+        #[arg(long)]
+        id: u64,
+        #[arg(long)]
+        chain_id: u64,
+        /// Hex address of the EVM token wanted
+        #[arg(long)]
+        evm_token: String,
+        /// Lamports offered
+        #[arg(long)]
+        offered: u64,
+        /// EVM token base units wanted
+        #[arg(long)]
+        wanted: u128,
+        #[arg(long)]
+        identity: String,
+        #[arg(long, default_value_t = 3600)]
+        expires_in: i64,
+        #[arg(long, default_value_t = false, action = clap::ArgAction::Set)]
+        taker_native: bool,
+    },
+    /// Deposit lamports into the seller or buyer vault of an offer
+    Deposit {
+        #[arg(value_enum)]
+        side: Side,
+        #[arg(value_enum)]
+        kind: OfferKindArg,
+        #[arg(long)]
+        id: u64,
+        /// The offer's maker, needed for buyer deposits
+        #[arg(long)]
+        maker: Option<Pubkey>,
+        #[arg(long)]
+        amount: u64,
+    },
+    /// Prove the buyer identity against an offer via MPC
+    Finalize {
+        #[arg(value_enum)]
+        kind: OfferKindArg,
+        #[arg(long)]
+        id: u64,
+        /// The offer's maker
+        #[arg(long)]
+        maker: Pubkey,
+        #[arg(long)]
+        identity: String,
+    },
+    /// Execute a finalized intrachain swap as the buyer, co-signed by the seller
+    ExecuteIntrachain {
+        #[arg(long)]
+        id: u64,
+        #[arg(long)]
+        seller_keypair: String,
+    },
+    /// Execute a finalized interchain swap as the buyer, co-signed by the maker
+    ExecuteInterchain {
+        #[arg(long)]
+        id: u64,
+        #[arg(long)]
+        maker_keypair: String,
+        /// Hex commitment to the EVM payout address
+        #[arg(long)]
+        payout_commitment: String,
+    },
+    /// Cancel one of your interchain offers and refund its vault
+    Cancel {
+        #[arg(long)]
+        id: u64,
+    },
+    /// List offers
+    Offers {
+        #[arg(long, value_enum)]
+        kind: Option<OfferKindArg>,
+        #[arg(long)]
+        maker: Option<Pubkey>,
+    },
+    /// Decrypt the encrypted outputs a callback transaction emitted
+    DecryptEvents { signature: Signature },
+    /// Upgrade program accounts left in an older layout, as the exchange
+    /// admin or their owner
+    Migrate {
+        #[arg(required = true)]
+        accounts: Vec<Pubkey>,
+        /// Maker of the first program's offers among `accounts`
+        #[arg(long)]
+        maker: Option<Pubkey>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Side {
+    Seller,
+    Buyer,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OfferKindArg {
+    Intrachain,
+    Interchain,
+}
+
+impl From<OfferKindArg> for OfferKind {
+    fn from(kind: OfferKindArg) -> Self {
+        match kind {
+            OfferKindArg::Intrachain => OfferKind::Intrachain,
+            OfferKindArg::Interchain => OfferKind::Interchain,
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let config = Config::load(&cli.config)?;
+    if let Command::Keygen { force } = cli.command {
+        return keygen(&config, force);
+    }
+    let session = Session::open(&config)?;
+    match cli.command {
+        Command::Keygen { .. } => unreachable!("handled before connecting"),
+        Command::CreateIntrachain {
+            id,
+            offered,
+            wanted,
+            identity,
+            expires_in,
+            taker_native,
+            spl,
+        } => {
+            let encrypted = session.cipher()?.encrypt(
+                [Fe::from(identity_hash(identity.as_bytes()))],
+                &mut rand::thread_rng(),
+            );
+            let offset = computation_offset();
+            let deadline = deadline(expires_in)?;
+            macro_rules! args {
+                ($ix:ident) => {
+                    instruction::$ix {
+                        id,
+                        token_b_wanted_amount: wanted,
+                        token_a_offered_amount: offered,
+                        is_taker_native: taker_native,
+                        deadline,
+                        ciphertext_seller_identity_hash: encrypted.ciphertexts[0],
+                        pub_key: encrypted.pub_key,
+                        nonce: encrypted.nonce,
+                        computation_offset: offset,
+                    }
+                };
+            }
+            let cluster_offset = session.cluster_offset()?;
+            let ix = if spl {
+                instructions::deposit_seller_spl(session.payer(), cluster_offset, args!(DepositSellerSpl))
+            } else {
+                instructions::deposit_seller_native(
+                    session.payer(),
+                    cluster_offset,
+                    args!(DepositSellerNative),
+                )
+            };
+            session.queue(ix, offset)?;
+            print_intrachain(&session, pda::intrachain_offer(&session.payer(), id))
+        }
+        Command::CreateInterchain {
+            id,
+            chain_id,
+            evm_token,
+            offered,
+            wanted,
+            identity,
+            expires_in,
+            taker_native,
+        } => {
+            let encrypted = session.cipher()?.encrypt(
+                [Fe::from(identity_hash(identity.as_bytes()))],
+                &mut rand::thread_rng(),
+            );
+            let offset = computation_offset();
+            let mut token_b_wanted_amount = [0u8; 32];
+            token_b_wanted_amount[16..].copy_from_slice(&wanted.to_be_bytes());
+            let ix = instructions::interchain_origin_evm_deposit_seller_spl(
+                session.payer(),
+                session.cluster_offset()?,
+                parse_hex(&evm_token)?,
+                instruction::InterchainOriginEvmDepositSellerSpl {
+                    id,
+                    token_b_wanted_amount,
+                    token_a_offered_amount: offered,
+                    is_taker_native: taker_native,
+                    chain_id,
+                    deadline: deadline(expires_in)?,
+                    ciphertext_seller_identity_hash: encrypted.ciphertexts[0],
+                    pub_key: encrypted.pub_key,
+                    nonce: encrypted.nonce,
+                    computation_offset: offset,
+                },
+            );
+            session.queue(ix, offset)?;
+            print_interchain(&session, pda::interchain_offer(&session.payer(), id))
+        }
+        Command::Deposit { side, kind, id, maker, amount } => {
+            let ix = match side {
+                Side::Seller => {
+                    instructions::deposit_to_seller_vault(session.payer(), kind.into(), id, amount)
+                }
+                Side::Buyer => {
+                    let maker = maker.ok_or_else(|| anyhow!("buyer deposits need --maker"))?;
+                    instructions::deposit_to_buyer_vault(
+                        session.payer(),
+                        maker,
+                        kind.into(),
+                        id,
+                        amount,
+                    )
+                }
+            };
+            println!("{}", session.send(ix, &[])?);
+            Ok(())
+        }
+        Command::Finalize { kind, id, maker, identity } => {
+            let encrypted = session.cipher()?.encrypt(
+                [Fe::from(identity_hash(identity.as_bytes()))],
+                &mut rand::thread_rng(),
+            );
+            let offset = computation_offset();
+            let cluster_offset = session.cluster_offset()?;
+            let ix = match kind {
+                OfferKindArg::Intrachain => instructions::finalize_intrachain_offer(
+                    session.payer(),
+                    maker,
+                    cluster_offset,
+                    instruction::FinalizeIntrachainOffer {
+                        id,
+                        ciphertext_buyer_identity_hash: encrypted.ciphertexts[0],
+                        pub_key: encrypted.pub_key,
+                        nonce: encrypted.nonce,
+                        computation_offset: offset,
+                    },
+                ),
+                OfferKindArg::Interchain => instructions::finalize_interchain_origin_evm_offer(
+                    session.payer(),
+                    maker,
+                    cluster_offset,
+                    instruction::FinalizeInterchainOriginEvmOffer {
+                        id,
+                        ciphertext_buyer_identity_hash: encrypted.ciphertexts[0],
+                        pub_key: encrypted.pub_key,
+                        nonce: encrypted.nonce,
+                        computation_offset: offset,
+                    },
+                ),
+            };
+            session.queue(ix, offset)?;
+            Ok(())
+        }
+        Command::ExecuteIntrachain { id, seller_keypair } => {
+            let seller = read_keypair(&seller_keypair)?;
+            let price_feed =
+                session.price_band_feed(pda::intrachain_offer(&seller.pubkey(), id))?;
+            let ix = instructions::execute_intrachain_swap(
+                seller.pubkey(),
+                session.payer(),
+                id,
+                price_feed,
+            );
+            println!("{}", session.send(ix, &[&seller])?);
+            Ok(())
+        }
+        Command::ExecuteInterchain {
+            id,
+            maker_keypair,
+            payout_commitment,
+        } => {
+            let maker = read_keypair(&maker_keypair)?;
+            let offer: InterchainOffer =
+                session.account(pda::interchain_offer(&maker.pubkey(), id))?;
+            let chain: ChainConfig = session.account(pda::chain_config(offer.chain_id))?;
+            let price_feed =
+                session.price_band_feed(pda::interchain_offer(&maker.pubkey(), id))?;
+            let ix = instructions::execute_interchain_swap(
+                session.payer(),
+                &offer,
+                chain.outbound_sequence,
+                parse_hex(&payout_commitment)?,
+                price_feed,
+            );
+            println!("{}", session.send(ix, &[&maker])?);
+            Ok(())
+        }
+        Command::Cancel { id } => {
+            let offer: InterchainOffer =
+                session.account(pda::interchain_offer(&session.payer(), id))?;
+            let chain: ChainConfig = session.account(pda::chain_config(offer.chain_id))?;
+            let ix = instructions::cancel_interchain_offer(
+                session.payer(),
+                id,
+                offer.chain_id,
+                chain.outbound_sequence,
+            );
+            println!("{}", session.send(ix, &[])?);
+            Ok(())
+        }
+        Command::Offers { kind, maker } => {
+            let by_maker = |offer_maker: &Pubkey| maker.is_none_or(|m| m == *offer_maker);
+            if kind != Some(OfferKindArg::Interchain) {
+                for (address, offer) in session.accounts::<IntraChainOffer>()? {
+                    if by_maker(&offer.maker) {
+                        println!("{}", describe_intrachain(&address, &offer));
+                    }
+                }
+            }
+            if kind != Some(OfferKindArg::Intrachain) {
+                for (address, offer) in session.accounts::<InterchainOffer>()? {
+                    if by_maker(&offer.maker) {
+                        println!("{}", describe_interchain(&address, &offer));
+                    }
+                }
+            }
+            Ok(())
+        }
+        Command::DecryptEvents { signature } => {
+            let outputs = events::encrypted_outputs(&session.transaction_logs(&signature)?);
+            if outputs.is_empty() {
+                bail!("{signature} emitted no encrypted outputs");
+            }
+            let cipher = session.cipher()?;
+            for output in outputs {
+                let value = cipher.decrypt(&[output.ciphertext], output.nonce).remove(0);
+                println!(
+                    "{} computation={} offer_id={} value={}",
+                    output.event,
+                    output.header.computation_offset,
+                    output.header.offer_id,
+                    value.to_biguint()
+                );
+            }
+            Ok(())
+        }
+        Command::Migrate { accounts, maker } => {
+            for account in accounts {
+                let ix = instructions::migrate_account(session.payer(), account, maker);
+                println!("{account}: {}", session.send(ix, &[])?);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn keygen(config: &Config, force: bool) -> Result<()> {
+    let path = config.x25519_keypair_path();
+    if path.exists() && !force {
+        bail!("{} exists, pass --force to replace it", path.display());
+    }
+    let keypair = X25519Keypair::generate(&mut rand::rngs::OsRng);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, hex(&keypair.secret_key()))?;
+    println!("wrote {}, public key {}", path.display(), hex(&keypair.public_key()));
+    Ok(())
+}
+
+fn read_keypair(path: &str) -> Result<Keypair> {
+    read_keypair_file(expand_home(path)).map_err(|e| anyhow!("reading keypair {path}: {e}"))
+}
+
+fn deadline(expires_in: i64) -> Result<i64> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    Ok(now + expires_in)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn describe_intrachain(address: &Pubkey, offer: &IntraChainOffer) -> String {
+    format!(
+        "intrachain {address} id={} maker={} offered={} wanted={} status={:?} deadline={}",
+        offer.id,
+        offer.maker,
+        offer.token_a_offered_amount,
+        offer.token_b_wanted_amount,
+        offer.status,
+        offer.deadline
+    )
+}
+
+fn describe_interchain(address: &Pubkey, offer: &InterchainOffer) -> String {
+    format!(
+        "interchain {address} id={} maker={} chain={} offered={} wanted=0x{} status={:?} deadline={}",
+        offer.id,
+        offer.maker,
+        offer.chain_id,
+        offer.token_a_offered_amount,
+        hex(&offer.token_b_wanted_amount),
+        offer.status,
+        offer.deadline
+    )
+}
+
+fn print_intrachain(session: &Session, address: Pubkey) -> Result<()> {
+    let offer: IntraChainOffer = session.account(address)?;
+    println!("{}", describe_intrachain(&address, &offer));
+    Ok(())
+}
+
+fn print_interchain(session: &Session, address: Pubkey) -> Result<()> {
+    let offer: InterchainOffer = session.account(address)?;
+    println!("{}", describe_interchain(&address, &offer));
+    Ok(())
+}
@@ -0,0 +1,207 @@
+//! An RPC connection plus the keys a command signs and encrypts with.
+
+use std::{fs, sync::Arc, thread, time::Duration};
+
+use anchor_client::{
+    solana_account_decoder::UiAccountEncoding,
+    solana_client::{
+        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig},
+        rpc_filter::{Memcmp, RpcFilterType},
+    },
+    solana_sdk::{
+        commitment_config::CommitmentConfig,
+        instruction::Instruction,
+        signature::{read_keypair_file, Keypair, Signature},
+        signer::Signer,
+    },
+    Client,
+    Program,
+};
+use anchor_lang::{prelude::Pubkey, AccountDeserialize};
+use anyhow::{anyhow, bail, Context, Result};
+use arcium_client::idl::arcium::accounts::MXEAccount;
+use confidential_exchange_client::{
+    accounts,
+    crypto::{self, SharedCipher, X25519Keypair},
+    pda,
+    program::versioning::VersionedAccount,
+    PROGRAM_ID,
+};
+
+use crate::config::{parse_hex, Config};
+
+/// How long to wait for the cluster to run a queued computation
+const COMPUTATION_TIMEOUT: Duration = Duration::from_secs(180);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct Session {
+    pub program: Program<Arc<Keypair>>,
+    pub payer: Arc<Keypair>,
+    x25519: Option<X25519Keypair>,
+    config: Config,
+}
+
+impl Session {
+    pub fn open(config: &Config) -> Result<Self> {
+        let payer = Arc::new(
+            read_keypair_file(config.keypair_path())
+                .map_err(|e| anyhow!("reading keypair {}: {e}", config.keypair_path().display()))?,
+        );
+        let client =
+            Client::new_with_options(config.cluster()?, payer.clone(), CommitmentConfig::confirmed());
+        let x25519 = match fs::read_to_string(config.x25519_keypair_path()) {
+            Ok(secret) => Some(X25519Keypair::from_secret(parse_hex(secret.trim())?)),
+            Err(_) => None,
+        };
+        Ok(Session {
+            program: client.program(PROGRAM_ID)?,
+            payer,
+            x25519,
+            config: config.clone(),
+        })
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    /// Fetches a program account in any of its supported layouts
+    pub fn account<T: VersionedAccount>(&self, address: Pubkey) -> Result<T> {
+        let data = self
+            .program
+            .rpc()
+            .get_account_data(&address)
+            .with_context(|| format!("fetching account {address}"))?;
+        Ok(accounts::decode(&data)?)
+    }
+
+    /// Feed of the price band guarding `offer`, if it has one
+    pub fn price_band_feed(&self, offer: Pubkey) -> Result<Option<Pubkey>> {
+        let band = self
+            .program
+            .rpc()
+            .get_account_with_commitment(&pda::price_band(&offer), CommitmentConfig::confirmed())
+            .with_context(|| format!("fetching the price band of {offer}"))?
+            .value;
+        band.map(|account| Ok(accounts::decode_price_band(&account.data)?.price_feed))
+            .transpose()
+    }
+
+    /// Every program account of type `T`, in any of its supported layouts
+    pub fn accounts<T: VersionedAccount>(&self) -> Result<Vec<(Pubkey, T)>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                0,
+                T::DISCRIMINATOR,
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        self.program
+            .rpc()
+            .get_program_accounts_with_config(&PROGRAM_ID, config)?
+            .into_iter()
+            .map(|(address, account)| Ok((address, accounts::decode(&account.data)?)))
+            .collect()
+    }
+
+    fn mxe_account(&self) -> Result<MXEAccount> {
+        let data = self.program.rpc().get_account_data(&pda::mxe_account())?;
+        Ok(MXEAccount::try_deserialize(&mut &data[..])?)
+    }
+
+    pub fn cluster_offset(&self) -> Result<u32> {
+        match self.config.cluster_offset {
+            Some(offset) => Ok(offset),
+            None => self
+                .mxe_account()?
+                .cluster
+                .ok_or_else(|| anyhow!("MXE is not assigned to a cluster yet")),
+        }
+    }
+
+    fn mxe_public_key(&self) -> Result<[u8; 32]> {
+        if let Some(key) = self.config.mxe_public_key()? {
+            return Ok(key);
+        }
+        let data = self.program.rpc().get_account_data(&pda::mxe_account())?;
+        crypto::mxe_public_key(&data)?.ok_or_else(|| anyhow!("MXE key generation has not finished"))
+    }
+
+    /// Cipher shared with the MXE under the configured x25519 key pair
+    pub fn cipher(&self) -> Result<SharedCipher> {
+        let keypair = self.x25519.as_ref().ok_or_else(|| {
+            anyhow!(
+                "no x25519 key pair at {}, run `ccx keygen`",
+                self.config.x25519_keypair_path().display()
+            )
+        })?;
+        Ok(SharedCipher::new(keypair, &self.mxe_public_key()?))
+    }
+
+    pub fn send(&self, instruction: Instruction, co_signers: &[&Keypair]) -> Result<Signature> {
+        let mut request = self.program.request().instruction(instruction);
+        for &signer in co_signers {
+            request = request.signer(signer);
+        }
+        Ok(request.send()?)
+    }
+
+    /// Sends a confidential instruction and waits for its callback,
+    /// returning the callback's transaction
+    pub fn queue(&self, instruction: Instruction, computation_offset: u64) -> Result<Signature> {
+        let signature = self.send(instruction, &[])?;
+        println!("queued computation {computation_offset} in {signature}");
+        let callback = self.await_computation(computation_offset)?;
+        println!("callback {callback}");
+        Ok(callback)
+    }
+
+    pub fn transaction_logs(&self, signature: &Signature) -> Result<Vec<String>> {
+        let transaction = self.program.rpc().get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+                ..RpcTransactionConfig::default()
+            },
+        )?;
+        let logs: Option<Vec<String>> = transaction
+            .transaction
+            .meta
+            .and_then(|meta| meta.log_messages.into());
+        Ok(logs.unwrap_or_default())
+    }
+
+    /// Polls until the callback closes the computation's `PendingComputation`
+    pub fn await_computation(&self, computation_offset: u64) -> Result<Signature> {
+        let rpc = self.program.rpc();
+        let pending = pda::pending_computation(computation_offset);
+        let mut waited = Duration::ZERO;
+        while rpc
+            .get_account_with_commitment(&pending, rpc.commitment())?
+            .value
+            .is_some()
+        {
+            if waited >= COMPUTATION_TIMEOUT {
+                bail!("computation {computation_offset} did not finish in {COMPUTATION_TIMEOUT:?}");
+            }
+            thread::sleep(POLL_INTERVAL);
+            waited += POLL_INTERVAL;
+        }
+        let latest = rpc
+            .get_signatures_for_address(&pending)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("no transactions touched computation {computation_offset}"))?;
+        Ok(latest.signature.parse()?)
+    }
+}
+
+/// Fresh random computation offset
+pub fn computation_offset() -> u64 {
+    rand::random()
+}
@@ -0,0 +1,77 @@
+//! Decoders for the program's account data as fetched over RPC.
+
+use anchor_lang::prelude::*;
+use confidential_cross_chain_exchange::{
+    versioning::{self, VersionedAccount},
+    BasketOffer,
+    CounterOffer,
+    Dispute,
+    FiatOffer,
+    IdentityExchange,
+    InterchainOffer,
+    IntraChainOffer,
+    PendingComputation,
+    PriceBand,
+    PriceFeed,
+    PriceTrigger,
+    RfqRequest,
+    SolanaOriginOffer,
+};
+
+/// Decodes any program account, checking its discriminator. Accounts still
+/// in an older layout have to be migrated first.
+pub fn decode<T: VersionedAccount>(data: &[u8]) -> Result<T> {
+    versioning::decode_current(data)
+}
+
+pub fn decode_intrachain_offer(data: &[u8]) -> Result<IntraChainOffer> {
+    decode(data)
+}
+
+pub fn decode_interchain_offer(data: &[u8]) -> Result<InterchainOffer> {
+    decode(data)
+}
+
+pub fn decode_solana_origin_offer(data: &[u8]) -> Result<SolanaOriginOffer> {
+    decode(data)
+}
+
+pub fn decode_basket_offer(data: &[u8]) -> Result<BasketOffer> {
+    decode(data)
+}
+
+pub fn decode_rfq(data: &[u8]) -> Result<RfqRequest> {
+    decode(data)
+}
+
+pub fn decode_counter_offer(data: &[u8]) -> Result<CounterOffer> {
+    decode(data)
+}
+
+pub fn decode_dispute(data: &[u8]) -> Result<Dispute> {
+    decode(data)
+}
+
+pub fn decode_fiat_offer(data: &[u8]) -> Result<FiatOffer> {
+    decode(data)
+}
+
+pub fn decode_identity_exchange(data: &[u8]) -> Result<IdentityExchange> {
+    decode(data)
+}
+
+pub fn decode_price_feed(data: &[u8]) -> Result<PriceFeed> {
+    decode(data)
+}
+
+pub fn decode_price_band(data: &[u8]) -> Result<PriceBand> {
+    decode(data)
+}
+
+pub fn decode_price_trigger(data: &[u8]) -> Result<PriceTrigger> {
+    decode(data)
+}
+
+pub fn decode_pending_computation(data: &[u8]) -> Result<PendingComputation> {
+    decode(data)
+}
@@ -0,0 +1,250 @@
+//! x25519 key agreement with the MXE and the ciphertext/pub_key/nonce
+//! triples the confidential instructions take.
+
+use anchor_lang::prelude::*;
+use arcium_client::idl::arcium::{accounts::MXEAccount, types::X25519Pubkey};
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use rand::{CryptoRng, RngCore};
+use sha3::{Digest, Sha3_256};
+
+use crate::rescue::{Fe, RescueCipher};
+
+/// A client's x25519 key pair, one per session is enough
+#[derive(Clone)]
+pub struct X25519Keypair {
+    secret: [u8; 32],
+    public: [u8; 32],
+}
+
+impl X25519Keypair {
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let mut secret = [0u8; 32];
+        rng.fill_bytes(&mut secret);
+        Self::from_secret(secret)
+    }
+
+    pub fn from_secret(secret: [u8; 32]) -> Self {
+        let public = MontgomeryPoint::mul_base_clamped(secret).to_bytes();
+        X25519Keypair { secret, public }
+    }
+
+    pub fn secret_key(&self) -> [u8; 32] {
+        self.secret
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.public
+    }
+
+    pub fn shared_secret(&self, peer_public_key: &[u8; 32]) -> [u8; 32] {
+        MontgomeryPoint(*peer_public_key)
+            .mul_clamped(self.secret)
+            .to_bytes()
+    }
+}
+
+/// Encrypted struct input for a confidential instruction, one ciphertext per
+/// plaintext field
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Encrypted<const N: usize> {
+    pub ciphertexts: [[u8; 32]; N],
+    pub pub_key: [u8; 32],
+    pub nonce: u128,
+}
+
+/// Rescue cipher shared between a client key pair and the MXE
+#[derive(Clone, Debug)]
+pub struct SharedCipher {
+    pub_key: [u8; 32],
+    cipher: RescueCipher,
+}
+
+impl SharedCipher {
+    pub fn new(keypair: &X25519Keypair, mxe_public_key: &[u8; 32]) -> Self {
+        SharedCipher {
+            pub_key: keypair.public_key(),
+            cipher: RescueCipher::new(&keypair.shared_secret(mxe_public_key)),
+        }
+    }
+
+    /// Encrypts under a fresh random nonce; nonces must never be reused
+    pub fn encrypt<const N: usize, R: RngCore + CryptoRng>(
+        &self,
+        plaintext: [Fe; N],
+        rng: &mut R,
+    ) -> Encrypted<N> {
+        let mut nonce = [0u8; 16];
+        rng.fill_bytes(&mut nonce);
+        self.encrypt_with_nonce(plaintext, u128::from_le_bytes(nonce))
+    }
+
+    pub fn encrypt_with_nonce<const N: usize>(&self, plaintext: [Fe; N], nonce: u128) -> Encrypted<N> {
+        let ciphertexts = self
+            .cipher
+            .encrypt(&plaintext, nonce)
+            .try_into()
+            .expect("one ciphertext per plaintext element");
+        Encrypted {
+            ciphertexts,
+            pub_key: self.pub_key,
+            nonce,
+        }
+    }
+
+    /// Decrypts a shared output, e.g. a callback's `SharedEncryptedStruct`
+    pub fn decrypt(&self, ciphertexts: &[[u8; 32]], nonce: u128) -> Vec<Fe> {
+        self.cipher.decrypt(ciphertexts, nonce)
+    }
+}
+
+/// The MXE's x25519 public key from its `MXEAccount` data, `None` while the
+/// cluster has not finished key generation
+pub fn mxe_public_key(mxe_account_data: &[u8]) -> Result<Option<[u8; 32]>> {
+    let mxe = MXEAccount::try_deserialize(&mut &mxe_account_data[..])?;
+    Ok(match mxe.x25519_pubkey {
+        X25519Pubkey::Set(key) => Some(key),
+        X25519Pubkey::Unset(..) => None,
+    })
+}
+
+/// Identity hash the circuits take: first 8 bytes of SHA3-256, little endian
+pub fn identity_hash(data: &[u8]) -> u64 {
+    let digest = Sha3_256::digest(data);
+    u64::from_le_bytes(digest[..8].try_into().expect("digest is 32 bytes"))
+}
+
+/// Splits an EVM address into the `EvmPayoutAddress { hi, lo }` circuit
+/// input, its first 16 and last 4 bytes read big endian
+pub fn split_evm_address(address: &[u8; 20]) -> [Fe; 2] {
+    let hi = u128::from_be_bytes(address[..16].try_into().expect("16 bytes"));
+    let lo = u32::from_be_bytes(address[16..].try_into().expect("4 bytes"));
+    [Fe::from(hi), Fe::from(lo as u64)]
+}
+
+/// Most bytes of payment details a fiat offer stores
+pub const FIAT_PAYMENT_DETAILS_LEN: usize = 64;
+
+/// Most bytes of identity a party shares after a trade
+pub const TRADE_IDENTITY_LEN: usize = 64;
+
+/// Packs up to 64 bytes into four circuit fields, 16 zero-padded bytes each
+/// read little endian
+fn pack_parts(bytes: &[u8]) -> Option<[Fe; 4]> {
+    if bytes.len() > 64 {
+        return None;
+    }
+    let mut padded = [0u8; 64];
+    padded[..bytes.len()].copy_from_slice(bytes);
+    Some(std::array::from_fn(|i| {
+        let part = padded[16 * i..16 * (i + 1)].try_into().expect("16 bytes");
+        Fe::from(u128::from_le_bytes(part))
+    }))
+}
+
+/// Inverse of [`pack_parts`], without the zero padding
+fn unpack_parts(fields: &[Fe]) -> Vec<u8> {
+    let mut bytes: Vec<u8> = fields
+        .iter()
+        .flat_map(|field| field.to_le_bytes()[..16].to_vec())
+        .collect();
+    while bytes.last() == Some(&0) {
+        bytes.pop();
+    }
+    bytes
+}
+
+/// Packs a fiat seller's payment instructions into the four
+/// `FiatPaymentDetails` circuit fields; `None` when longer than
+/// [`FIAT_PAYMENT_DETAILS_LEN`]
+pub fn pack_fiat_payment_details(details: &[u8]) -> Option<[Fe; 4]> {
+    pack_parts(details)
+}
+
+/// Payment details as decrypted from a `FiatPaymentDetailsRevealedEvent`,
+/// without their zero padding
+pub fn unpack_fiat_payment_details(fields: &[Fe]) -> Vec<u8> {
+    unpack_parts(fields)
+}
+
+/// Packs a party's identity, e.g. a name and delivery address, into the
+/// four `TradeIdentity` circuit fields; `None` when longer than
+/// [`TRADE_IDENTITY_LEN`]
+pub fn pack_trade_identity(identity: &[u8]) -> Option<[Fe; 4]> {
+    pack_parts(identity)
+}
+
+/// Counterparty identity as decrypted from a
+/// `CounterpartyIdentityRevealedEvent`, without its zero padding
+pub fn unpack_trade_identity(fields: &[Fe]) -> Vec<u8> {
+    unpack_parts(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex32(s: &str) -> [u8; 32] {
+        let bytes: Vec<u8> = (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect();
+        bytes.try_into().unwrap()
+    }
+
+    #[test]
+    fn matches_arcis_rescue_cipher() {
+        // Client secret [1; 32], MXE secret [2; 32], vector from arcis 0.3.0
+        let mxe = X25519Keypair::from_secret([2; 32]);
+        assert_eq!(
+            mxe.public_key(),
+            hex32("ce8d3ad1ccb633ec7b70c17814a5c76ecd029685050d344745ba05870e587d59")
+        );
+        let cipher = SharedCipher::new(&X25519Keypair::from_secret([1; 32]), &mxe.public_key());
+        let encrypted = cipher.encrypt_with_nonce([Fe::from(1u64), Fe::from(2u64), Fe::from(3u64)], 42);
+        assert_eq!(
+            encrypted.ciphertexts,
+            [
+                hex32("1a7f8d6317d1a61c2cd733e9ab73952e78061ec10c7f6fc3a0f651e2efbeec4a"),
+                hex32("c4c280da2e06de14a6c5220636617fadd7568a5b4d75042c55c92ee8ae673b54"),
+                hex32("0c3847efb568b2b17a5db7bd8608fd5da8b6a8cc60e6cc9171b5f4c6f94ac418"),
+            ]
+        );
+    }
+
+    #[test]
+    fn mxe_decrypts_client_ciphertexts() {
+        let client = X25519Keypair::from_secret([3; 32]);
+        let mxe = X25519Keypair::from_secret([4; 32]);
+        let plaintext: [Fe; 7] = std::array::from_fn(|i| Fe::from(u128::MAX - i as u128));
+        let encrypted = SharedCipher::new(&client, &mxe.public_key()).encrypt_with_nonce(plaintext.clone(), 7);
+        let decrypted = SharedCipher::new(&mxe, &encrypted.pub_key).decrypt(&encrypted.ciphertexts, encrypted.nonce);
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn splits_evm_address() {
+        let mut address = [0u8; 20];
+        address[15] = 1;
+        address[19] = 2;
+        assert_eq!(split_evm_address(&address), [Fe::from(1u64), Fe::from(2u64)]);
+    }
+
+    #[test]
+    fn fiat_payment_details_round_trip() {
+        let details = b"IBAN DE89 3704 0044 0532 0130 00, ref 4711";
+        let fields = pack_fiat_payment_details(details).unwrap();
+        assert_eq!(fields[3], Fe::zero());
+        assert_eq!(unpack_fiat_payment_details(&fields), details);
+        let full = [b'x'; FIAT_PAYMENT_DETAILS_LEN];
+        assert_eq!(unpack_fiat_payment_details(&pack_fiat_payment_details(&full).unwrap()), full);
+        assert!(pack_fiat_payment_details(&[b'x'; FIAT_PAYMENT_DETAILS_LEN + 1]).is_none());
+    }
+
+    #[test]
+    fn trade_identity_round_trip() {
+        let identity = b"Jane Doe, 1 Main St, Springfield";
+        let fields = pack_trade_identity(identity).unwrap();
+        assert_eq!(unpack_trade_identity(&fields), identity);
+        assert!(pack_trade_identity(&[b'x'; TRADE_IDENTITY_LEN + 1]).is_none());
+    }
+}
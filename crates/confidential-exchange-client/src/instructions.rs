@@ -0,0 +1,1797 @@
+//! Instruction builders for every client-facing instruction of the program.
+//!
+//! Builders derive every PDA an instruction needs. Arguments are the
+//! program's own `instruction::*` structs, so ciphertexts from
+//! [`crate::crypto::SharedCipher`] are passed the same way the program reads
+//! them. Confidential instructions also take the MXE's `cluster_offset`.
+//! Callbacks are invoked by the Arcium cluster and have no builders.
+
+use anchor_lang::{
+    prelude::{AccountMeta, Pubkey},
+    solana_program::instruction::Instruction,
+    system_program,
+    InstructionData,
+    ToAccountMetas,
+};
+use anchor_spl::{associated_token, associated_token::get_associated_token_address, token};
+use arcium_client::ARCIUM_PROGRAM_ID;
+use confidential_cross_chain_exchange::{
+    accounts,
+    instruction,
+    BasketOffer,
+    ComputationOperation,
+    CounterOffer,
+    Dispute,
+    InterchainOffer,
+    OfferKind,
+    PendingComputation,
+    PriceTrigger,
+    RfqRequest,
+    SolanaOriginOffer,
+    VaultSide,
+    ID as PROGRAM_ID,
+};
+
+use crate::pda;
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Fills in the accounts every queued computation shares
+macro_rules! queue_accounts {
+    (
+        $accounts:ident {
+            $($field:ident: $value:expr,)*
+        },
+        $payer:expr, $operation:expr, $computation_offset:expr, $cluster_offset:expr
+    ) => {
+        accounts::$accounts {
+            $($field: $value,)*
+            payer: $payer,
+            pending_computation: pda::pending_computation($computation_offset),
+            sign_pda_account: pda::sign_pda(),
+            mxe_account: pda::mxe_account(),
+            mempool_account: pda::mempool_account(),
+            executing_pool: pda::executing_pool(),
+            computation_account: pda::computation_account($computation_offset),
+            comp_def_account: pda::comp_def_account($operation.comp_def_offset()),
+            cluster_account: pda::cluster_account($cluster_offset),
+            pool_account: pda::fee_pool_account(),
+            clock_account: pda::clock_account(),
+            system_program: system_program::ID,
+            arcium_program: ARCIUM_PROGRAM_ID,
+        }
+    };
+}
+
+/// Fills in the accounts every comp def initialization shares
+macro_rules! init_comp_def {
+    ($accounts:ident, $payer:expr, $operation:expr) => {
+        build(
+            accounts::$accounts {
+                payer: $payer,
+                mxe_account: pda::mxe_account(),
+                comp_def_account: pda::comp_def_account($operation.comp_def_offset()),
+                arcium_program: ARCIUM_PROGRAM_ID,
+                system_program: system_program::ID,
+            },
+            instruction::$accounts {},
+        )
+    };
+}
+
+// === COMPUTATION DEFINITIONS ===
+
+/// Initializes the comp def of the circuit `operation` runs
+pub fn init_comp_def(payer: Pubkey, operation: ComputationOperation) -> Instruction {
+    use ComputationOperation::*;
+    match operation {
+        AddTogether => init_comp_def!(InitAddTogetherCompDef, payer, operation),
+        RelayOfferClone => init_comp_def!(InitRelayOfferCloneCompDef, payer, operation),
+        ConfidentialDepositNative => {
+            init_comp_def!(InitConfidentialDepositNativeCompDef, payer, operation)
+        }
+        InterchainOriginEvmDepositSellerSpl => {
+            init_comp_def!(InitInterchainOriginEvmDepositSellerSplCompDef, payer, operation)
+        }
+        FinalizeInterchainOriginEvmOffer => {
+            init_comp_def!(InitFinalizeInterchainOriginEvmOfferCompDef, payer, operation)
+        }
+        DepositSellerNative => init_comp_def!(InitDepositSellerNativeCompDef, payer, operation),
+        DepositSellerSpl => init_comp_def!(InitDepositSellerSplCompDef, payer, operation),
+        FinalizeIntrachainOffer => {
+            init_comp_def!(InitFinalizeIntrachainOfferCompDef, payer, operation)
+        }
+        StoreEvmPayoutAddress => {
+            init_comp_def!(InitStoreEvmPayoutAddressCompDef, payer, operation)
+        }
+        CreateBasketOffer => init_comp_def!(InitCreateBasketOfferCompDef, payer, operation),
+        FinalizeBasketOffer => init_comp_def!(InitFinalizeBasketOfferCompDef, payer, operation),
+        RankRfqQuote => init_comp_def!(InitRankRfqQuoteCompDef, payer, operation),
+        SelectRfqQuote => init_comp_def!(InitSelectRfqQuoteCompDef, payer, operation),
+        ProposeCounterOffer => {
+            init_comp_def!(InitProposeCounterOfferCompDef, payer, operation)
+        }
+        StoreFiatPaymentDetails => {
+            init_comp_def!(InitStoreFiatPaymentDetailsCompDef, payer, operation)
+        }
+        RevealFiatPaymentDetails => {
+            init_comp_def!(InitRevealFiatPaymentDetailsCompDef, payer, operation)
+        }
+        StoreTradeIdentity => init_comp_def!(InitStoreTradeIdentityCompDef, payer, operation),
+        RevealTradeIdentities => {
+            init_comp_def!(InitRevealTradeIdentitiesCompDef, payer, operation)
+        }
+        StorePriceTrigger => init_comp_def!(InitStorePriceTriggerCompDef, payer, operation),
+        CheckPriceTrigger => init_comp_def!(InitCheckPriceTriggerCompDef, payer, operation),
+        RevealEvmPayoutAddress => {
+            init_comp_def!(InitRevealEvmPayoutAddressCompDef, payer, operation)
+        }
+    }
+}
+
+// === CONFIDENTIAL INSTRUCTIONS ===
+
+pub fn add_together(payer: Pubkey, cluster_offset: u32, args: instruction::AddTogether) -> Instruction {
+    build(
+        queue_accounts!(
+            AddTogether {},
+            payer,
+            ComputationOperation::AddTogether,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+/// Relays an EVM-origin offer; `relayer` must be bonded and allowed on the chain
+pub fn relay_offer_clone(
+    relayer: Pubkey,
+    cluster_offset: u32,
+    evm_token: [u8; 20],
+    args: instruction::RelayOfferClone,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            RelayOfferClone {
+                interchain_offer: pda::interchain_offer(&relayer, args.id),
+                chain_config: pda::chain_config(args.chain_id),
+                token_mapping: pda::token_mapping(args.chain_id, &evm_token),
+                exchange_config: pda::exchange_config(),
+                relayer_bond: pda::relayer_bond(&relayer),
+            },
+            relayer,
+            ComputationOperation::RelayOfferClone,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+pub fn confidential_deposit_native(
+    payer: Pubkey,
+    cluster_offset: u32,
+    args: instruction::ConfidentialDepositNative,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            ConfidentialDepositNative {},
+            payer,
+            ComputationOperation::ConfidentialDepositNative,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+pub fn interchain_origin_evm_deposit_seller_spl(
+    payer: Pubkey,
+    cluster_offset: u32,
+    evm_token: [u8; 20],
+    args: instruction::InterchainOriginEvmDepositSellerSpl,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            InterchainOriginEvmDepositSellerSpl {
+                interchain_offer: pda::interchain_offer(&payer, args.id),
+                chain_config: pda::chain_config(args.chain_id),
+                token_mapping: pda::token_mapping(args.chain_id, &evm_token),
+            },
+            payer,
+            ComputationOperation::InterchainOriginEvmDepositSellerSpl,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+pub fn finalize_interchain_origin_evm_offer(
+    payer: Pubkey,
+    maker: Pubkey,
+    cluster_offset: u32,
+    args: instruction::FinalizeInterchainOriginEvmOffer,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            FinalizeInterchainOriginEvmOffer {
+                interchain_offer: pda::interchain_offer(&maker, args.id),
+            },
+            payer,
+            ComputationOperation::FinalizeInterchainOriginEvmOffer,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+pub fn deposit_seller_native(
+    payer: Pubkey,
+    cluster_offset: u32,
+    args: instruction::DepositSellerNative,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            DepositSellerNative {
+                intrachain_offer: pda::intrachain_offer(&payer, args.id),
+            },
+            payer,
+            ComputationOperation::DepositSellerNative,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+pub fn deposit_seller_spl(
+    payer: Pubkey,
+    cluster_offset: u32,
+    args: instruction::DepositSellerSpl,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            DepositSellerSpl {
+                intrachain_offer: pda::intrachain_offer(&payer, args.id),
+            },
+            payer,
+            ComputationOperation::DepositSellerSpl,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+pub fn finalize_intrachain_offer(
+    payer: Pubkey,
+    maker: Pubkey,
+    cluster_offset: u32,
+    args: instruction::FinalizeIntrachainOffer,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            FinalizeIntrachainOffer {
+                intrachain_offer: pda::intrachain_offer(&maker, args.id),
+            },
+            payer,
+            ComputationOperation::FinalizeIntrachainOffer,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+pub fn create_solana_origin_offer_native(
+    maker: Pubkey,
+    cluster_offset: u32,
+    evm_token: [u8; 20],
+    args: instruction::CreateSolanaOriginOfferNative,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            CreateSolanaOriginOfferNative {
+                solana_origin_offer: pda::solana_origin_offer(&maker, args.id),
+                chain_config: pda::chain_config(args.chain_id),
+                token_mapping: pda::token_mapping(args.chain_id, &evm_token),
+            },
+            maker,
+            ComputationOperation::StoreEvmPayoutAddress,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+/// Escrows `mint` from the maker's associated token account into one owned
+/// by the offer
+pub fn create_solana_origin_offer_spl(
+    maker: Pubkey,
+    cluster_offset: u32,
+    evm_token: [u8; 20],
+    mint: Pubkey,
+    args: instruction::CreateSolanaOriginOfferSpl,
+) -> Instruction {
+    let solana_origin_offer = pda::solana_origin_offer(&maker, args.id);
+    build(
+        queue_accounts!(
+            CreateSolanaOriginOfferSpl {
+                solana_origin_offer: solana_origin_offer,
+                chain_config: pda::chain_config(args.chain_id),
+                token_mapping: pda::token_mapping(args.chain_id, &evm_token),
+                mint: mint,
+                maker_token_account: get_associated_token_address(&maker, &mint),
+                escrow_token_account: get_associated_token_address(&solana_origin_offer, &mint),
+                token_program: token::ID,
+                associated_token_program: associated_token::ID,
+            },
+            maker,
+            ComputationOperation::StoreEvmPayoutAddress,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+pub fn create_basket_offer(
+    maker: Pubkey,
+    cluster_offset: u32,
+    args: instruction::CreateBasketOffer,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            CreateBasketOffer {
+                basket_offer: pda::basket_offer(&maker, args.id),
+            },
+            maker,
+            ComputationOperation::CreateBasketOffer,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+/// Verifies `taker`'s identity for `maker`'s basket offer
+pub fn finalize_basket_offer(
+    taker: Pubkey,
+    maker: Pubkey,
+    cluster_offset: u32,
+    args: instruction::FinalizeBasketOffer,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            FinalizeBasketOffer {
+                basket_offer: pda::basket_offer(&maker, args.offer_id),
+            },
+            taker,
+            ComputationOperation::FinalizeBasketOffer,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+/// Quotes `taker`'s RFQ as `maker`; the quote is ranked in MPC
+pub fn submit_rfq_quote(
+    maker: Pubkey,
+    taker: Pubkey,
+    cluster_offset: u32,
+    args: instruction::SubmitRfqQuote,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            SubmitRfqQuote {
+                rfq: pda::rfq(&taker, args.rfq_id),
+            },
+            maker,
+            ComputationOperation::RankRfqQuote,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+/// Awards `taker`'s RFQ to its best quote; anyone may pay for it
+pub fn select_rfq_quote(
+    payer: Pubkey,
+    taker: Pubkey,
+    cluster_offset: u32,
+    args: instruction::SelectRfqQuote,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            SelectRfqQuote {
+                rfq: pda::rfq(&taker, args.rfq_id),
+            },
+            payer,
+            ComputationOperation::SelectRfqQuote,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+/// Proposes new terms for `maker`'s open intrachain offer, escrowing the
+/// proposed price from `proposer`; the proposer is verified in MPC
+pub fn propose_counter_offer(
+    proposer: Pubkey,
+    maker: Pubkey,
+    cluster_offset: u32,
+    args: instruction::ProposeCounterOffer,
+) -> Instruction {
+    let intrachain_offer = pda::intrachain_offer(&maker, args.offer_id);
+    let counter_offer = pda::counter_offer(&intrachain_offer, &proposer);
+    build(
+        queue_accounts!(
+            ProposeCounterOffer {
+                intrachain_offer: intrachain_offer,
+                counter_offer: counter_offer,
+                counter_vault: pda::counter_vault(&counter_offer),
+            },
+            proposer,
+            ComputationOperation::ProposeCounterOffer,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+/// Lists a fiat offer, escrowing its lamports from `seller`; the payment
+/// details are stored encrypted to the MXE
+pub fn create_fiat_offer(
+    seller: Pubkey,
+    cluster_offset: u32,
+    args: instruction::CreateFiatOffer,
+) -> Instruction {
+    let fiat_offer = pda::fiat_offer(&seller, args.id);
+    build(
+        queue_accounts!(
+            CreateFiatOffer {
+                fiat_offer: fiat_offer,
+                fiat_vault: pda::fiat_vault(&fiat_offer),
+            },
+            seller,
+            ComputationOperation::StoreFiatPaymentDetails,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+/// Locks `seller`'s fiat offer for `buyer`, whose `args.pub_key` the payment
+/// details are re-encrypted to
+pub fn lock_fiat_offer(
+    buyer: Pubkey,
+    seller: Pubkey,
+    cluster_offset: u32,
+    args: instruction::LockFiatOffer,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            LockFiatOffer {
+                fiat_offer: pda::fiat_offer(&seller, args.offer_id),
+            },
+            buyer,
+            ComputationOperation::RevealFiatPaymentDetails,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+/// `party` shares their identity in the exchange about the settled offer
+/// at `trade`
+pub fn share_trade_identity(
+    party: Pubkey,
+    trade: Pubkey,
+    cluster_offset: u32,
+    args: instruction::ShareTradeIdentity,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            ShareTradeIdentity {
+                identity_exchange: pda::identity_exchange(&trade),
+            },
+            party,
+            ComputationOperation::StoreTradeIdentity,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+/// `party` has both shared identities revealed to each other
+pub fn reveal_trade_identities(
+    party: Pubkey,
+    trade: Pubkey,
+    cluster_offset: u32,
+    args: instruction::RevealTradeIdentities,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            RevealTradeIdentities {
+                identity_exchange: pda::identity_exchange(&trade),
+            },
+            party,
+            ComputationOperation::RevealTradeIdentities,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+/// `maker` holds back their offer `args.offer`, an intrachain or interchain
+/// offer as `offer_kind` says, until `price_feed` crosses the encrypted
+/// trigger
+pub fn set_price_trigger(
+    maker: Pubkey,
+    offer_kind: OfferKind,
+    price_feed: Pubkey,
+    cluster_offset: u32,
+    args: instruction::SetPriceTrigger,
+) -> Instruction {
+    let offer_of = |kind: OfferKind| (offer_kind == kind).then_some(args.offer);
+    build(
+        queue_accounts!(
+            SetPriceTrigger {
+                intrachain_offer: offer_of(OfferKind::Intrachain),
+                interchain_offer: offer_of(OfferKind::Interchain),
+                price_feed: price_feed,
+                price_trigger: pda::price_trigger(&args.offer),
+            },
+            maker,
+            ComputationOperation::StorePriceTrigger,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+/// Permissionless: checks `trigger`'s feed price against it
+pub fn check_price_trigger(
+    cranker: Pubkey,
+    trigger: &PriceTrigger,
+    cluster_offset: u32,
+    args: instruction::CheckPriceTrigger,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            CheckPriceTrigger {
+                price_trigger: pda::price_trigger(&trigger.offer),
+                price_feed: trigger.price_feed,
+            },
+            cranker,
+            ComputationOperation::CheckPriceTrigger,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+// === RETRIES ===
+
+pub fn retry_relay_offer_clone(
+    relayer: Pubkey,
+    cluster_offset: u32,
+    args: instruction::RetryRelayOfferClone,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            RetryRelayOfferClone {
+                interchain_offer: pda::interchain_offer(&relayer, args.offer_id),
+            },
+            relayer,
+            ComputationOperation::RelayOfferClone,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+pub fn retry_interchain_origin_evm_deposit_seller_spl(
+    payer: Pubkey,
+    cluster_offset: u32,
+    args: instruction::RetryInterchainOriginEvmDepositSellerSpl,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            RetryInterchainOriginEvmDepositSellerSpl {
+                interchain_offer: pda::interchain_offer(&payer, args.offer_id),
+            },
+            payer,
+            ComputationOperation::InterchainOriginEvmDepositSellerSpl,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+pub fn retry_deposit_seller_native(
+    payer: Pubkey,
+    cluster_offset: u32,
+    args: instruction::RetryDepositSellerNative,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            RetryDepositSellerNative {
+                intrachain_offer: pda::intrachain_offer(&payer, args.offer_id),
+            },
+            payer,
+            ComputationOperation::DepositSellerNative,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+pub fn retry_deposit_seller_spl(
+    payer: Pubkey,
+    cluster_offset: u32,
+    args: instruction::RetryDepositSellerSpl,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            RetryDepositSellerSpl {
+                intrachain_offer: pda::intrachain_offer(&payer, args.offer_id),
+            },
+            payer,
+            ComputationOperation::DepositSellerSpl,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+pub fn retry_create_basket_offer(
+    maker: Pubkey,
+    cluster_offset: u32,
+    args: instruction::RetryCreateBasketOffer,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            RetryCreateBasketOffer {
+                basket_offer: pda::basket_offer(&maker, args.offer_id),
+            },
+            maker,
+            ComputationOperation::CreateBasketOffer,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+pub fn retry_store_evm_payout_address(
+    maker: Pubkey,
+    cluster_offset: u32,
+    args: instruction::RetryStoreEvmPayoutAddress,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            RetryStoreEvmPayoutAddress {
+                solana_origin_offer: pda::solana_origin_offer(&maker, args.offer_id),
+            },
+            maker,
+            ComputationOperation::StoreEvmPayoutAddress,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+/// Marks `pending` stale, passing the offer it was queued for and, for a
+/// finalize, the buyer escrow it refunds
+pub fn mark_computation_stale(cranker: Pubkey, pending: &PendingComputation) -> Instruction {
+    let offer = (pending.offer != Pubkey::default()).then_some(pending.offer);
+    let offer_of = |kind: OfferKind| offer.filter(|_| pending.offer_kind == kind);
+    let finalizes = matches!(
+        pending.operation,
+        ComputationOperation::FinalizeInterchainOriginEvmOffer
+            | ComputationOperation::FinalizeIntrachainOffer
+    );
+    build(
+        accounts::MarkComputationStale {
+            cranker,
+            pending_computation: pda::pending_computation(pending.computation_offset),
+            requester: pending.requester,
+            computation_timeout: pda::computation_timeout(pending.operation.comp_def_offset()),
+            interchain_offer: offer_of(OfferKind::Interchain),
+            intrachain_offer: offer_of(OfferKind::Intrachain),
+            solana_origin_offer: offer_of(OfferKind::SolanaOrigin),
+            basket_offer: offer_of(OfferKind::Basket),
+            rfq: offer_of(OfferKind::Rfq),
+            counter_offer: offer_of(OfferKind::CounterOffer),
+            fiat_offer: offer_of(OfferKind::Fiat),
+            identity_exchange: offer_of(OfferKind::IdentityExchange),
+            price_trigger: offer_of(OfferKind::PriceTrigger),
+            buyer_vault: finalizes
+                .then(|| pda::buyer_vault(&pending.requester, pending.offer_id)),
+        },
+        instruction::MarkComputationStale {
+            computation_offset: pending.computation_offset,
+        },
+    )
+}
+
+// === INTRACHAIN SETTLEMENT ===
+
+/// `seller` escrows `amount` lamports for their offer `offer_id`, an
+/// intrachain or interchain offer as `offer_kind` says
+pub fn deposit_to_seller_vault(
+    seller: Pubkey,
+    offer_kind: OfferKind,
+    offer_id: u64,
+    amount: u64,
+) -> Instruction {
+    let offer = match offer_kind {
+        OfferKind::Intrachain => pda::intrachain_offer(&seller, offer_id),
+        _ => pda::interchain_offer(&seller, offer_id),
+    };
+    let offer_of = |kind: OfferKind| (offer_kind == kind).then_some(offer);
+    build(
+        accounts::DepositToSellerVault {
+            seller,
+            intrachain_offer: offer_of(OfferKind::Intrachain),
+            interchain_offer: offer_of(OfferKind::Interchain),
+            seller_vault: pda::seller_vault(&seller, offer_id),
+            system_program: system_program::ID,
+        },
+        instruction::DepositToSellerVault { offer_id, amount },
+    )
+}
+
+/// `buyer` escrows `amount` lamports for `maker`'s offer `offer_id`
+pub fn deposit_to_buyer_vault(
+    buyer: Pubkey,
+    maker: Pubkey,
+    offer_kind: OfferKind,
+    offer_id: u64,
+    amount: u64,
+) -> Instruction {
+    let offer = match offer_kind {
+        OfferKind::Intrachain => pda::intrachain_offer(&maker, offer_id),
+        _ => pda::interchain_offer(&maker, offer_id),
+    };
+    let offer_of = |kind: OfferKind| (offer_kind == kind).then_some(offer);
+    build(
+        accounts::DepositToBuyerVault {
+            buyer,
+            maker,
+            intrachain_offer: offer_of(OfferKind::Intrachain),
+            interchain_offer: offer_of(OfferKind::Interchain),
+            buyer_vault: pda::buyer_vault(&buyer, offer_id),
+            system_program: system_program::ID,
+        },
+        instruction::DepositToBuyerVault { offer_id, amount },
+    )
+}
+
+pub fn refund_intrachain_buyer_vault(buyer: Pubkey, maker: Pubkey, offer_id: u64) -> Instruction {
+    build(
+        accounts::RefundIntrachainBuyerVault {
+            buyer,
+            maker,
+            intrachain_offer: pda::intrachain_offer(&maker, offer_id),
+            buyer_vault: pda::buyer_vault(&buyer, offer_id),
+        },
+        instruction::RefundIntrachainBuyerVault { offer_id },
+    )
+}
+
+/// `price_feed` is the feed of the offer's price band, if it has one
+pub fn execute_intrachain_swap(
+    seller: Pubkey,
+    buyer: Pubkey,
+    offer_id: u64,
+    price_feed: Option<Pubkey>,
+) -> Instruction {
+    let intrachain_offer = pda::intrachain_offer(&seller, offer_id);
+    build(
+        accounts::ExecuteIntrachainSwap {
+            intrachain_offer,
+            seller,
+            buyer,
+            seller_vault: pda::seller_vault(&seller, offer_id),
+            buyer_vault: pda::buyer_vault(&buyer, offer_id),
+            price_trigger: pda::price_trigger(&intrachain_offer),
+            price_band: pda::price_band(&intrachain_offer),
+            price_feed,
+            system_program: system_program::ID,
+        },
+        instruction::ExecuteIntrachainSwap { offer_id },
+    )
+}
+
+/// Permissionless once the offer's deadline passed or its computation failed
+pub fn refund_intrachain_offer(maker: Pubkey, offer_id: u64) -> Instruction {
+    build(
+        accounts::RefundIntrachainOffer {
+            maker,
+            intrachain_offer: pda::intrachain_offer(&maker, offer_id),
+            seller_vault: pda::seller_vault(&maker, offer_id),
+        },
+        instruction::RefundIntrachainOffer { offer_id },
+    )
+}
+
+// === BASKET SETTLEMENT ===
+
+/// Escrows whatever the leg at `leg_index` of `side` is missing, SOL from
+/// `depositor` or tokens from its associated token account
+pub fn deposit_basket_leg(
+    depositor: Pubkey,
+    offer: &BasketOffer,
+    side: VaultSide,
+    leg_index: u8,
+) -> Instruction {
+    let basket_offer = pda::basket_offer(&offer.maker, offer.id);
+    let leg_vault = pda::basket_vault(&basket_offer, &depositor, side, leg_index);
+    match offer.legs(side)[usize::from(leg_index)].mint {
+        None => build(
+            accounts::DepositBasketLegNative {
+                depositor,
+                basket_offer,
+                leg_vault,
+                system_program: system_program::ID,
+            },
+            instruction::DepositBasketLegNative {
+                offer_id: offer.id,
+                side,
+                leg_index,
+            },
+        ),
+        Some(mint) => build(
+            accounts::DepositBasketLegSpl {
+                depositor,
+                basket_offer,
+                mint,
+                depositor_token_account: get_associated_token_address(&depositor, &mint),
+                leg_vault,
+                token_program: token::ID,
+                system_program: system_program::ID,
+            },
+            instruction::DepositBasketLegSpl {
+                offer_id: offer.id,
+                side,
+                leg_index,
+            },
+        ),
+    }
+}
+
+/// Vault and destination of every leg of `side`; SPL legs are paid to the
+/// recipient's associated token account
+fn basket_leg_accounts(
+    offer: &BasketOffer,
+    side: VaultSide,
+    depositor: &Pubkey,
+    recipient: &Pubkey,
+) -> Vec<AccountMeta> {
+    let basket_offer = pda::basket_offer(&offer.maker, offer.id);
+    offer
+        .legs(side)
+        .iter()
+        .enumerate()
+        .flat_map(|(leg_index, leg)| {
+            let vault = pda::basket_vault(&basket_offer, depositor, side, leg_index as u8);
+            let destination = match leg.mint {
+                None => *recipient,
+                Some(mint) => get_associated_token_address(recipient, &mint),
+            };
+            [AccountMeta::new(vault, false), AccountMeta::new(destination, false)]
+        })
+        .collect()
+}
+
+/// Settles every leg between the maker and `taker`, both signing
+pub fn execute_basket_swap(offer: &BasketOffer, taker: Pubkey) -> Instruction {
+    let mut ix = build(
+        accounts::ExecuteBasketSwap {
+            basket_offer: pda::basket_offer(&offer.maker, offer.id),
+            maker: offer.maker,
+            taker,
+            token_program: token::ID,
+        },
+        instruction::ExecuteBasketSwap { offer_id: offer.id },
+    );
+    ix.accounts
+        .extend(basket_leg_accounts(offer, VaultSide::Seller, &offer.maker, &taker));
+    ix.accounts
+        .extend(basket_leg_accounts(offer, VaultSide::Buyer, &taker, &offer.maker));
+    ix
+}
+
+/// Permissionless once the offer is no longer live; SPL legs are returned to
+/// the depositor's associated token account
+pub fn refund_basket_leg(
+    offer: &BasketOffer,
+    depositor: Pubkey,
+    side: VaultSide,
+    leg_index: u8,
+) -> Instruction {
+    let basket_offer = pda::basket_offer(&offer.maker, offer.id);
+    let mint = offer.legs(side)[usize::from(leg_index)].mint;
+    build(
+        accounts::RefundBasketLeg {
+            depositor,
+            basket_offer,
+            leg_vault: pda::basket_vault(&basket_offer, &depositor, side, leg_index),
+            depositor_token_account: mint.map(|mint| get_associated_token_address(&depositor, &mint)),
+            token_program: mint.map(|_| token::ID),
+        },
+        instruction::RefundBasketLeg {
+            offer_id: offer.id,
+            side,
+            leg_index,
+        },
+    )
+}
+
+// === RFQ SETTLEMENT ===
+
+pub fn create_rfq(taker: Pubkey, args: instruction::CreateRfq) -> Instruction {
+    build(
+        accounts::CreateRfq {
+            taker,
+            rfq: pda::rfq(&taker, args.id),
+            system_program: system_program::ID,
+        },
+        args,
+    )
+}
+
+pub fn cancel_rfq(taker: Pubkey, rfq_id: u64) -> Instruction {
+    build(
+        accounts::CancelRfq {
+            taker,
+            rfq: pda::rfq(&taker, rfq_id),
+        },
+        instruction::CancelRfq { rfq_id },
+    )
+}
+
+/// Escrows whatever the awarded RFQ's `side` is missing, SOL from
+/// `depositor` or tokens from its associated token account
+pub fn deposit_rfq_leg(depositor: Pubkey, rfq: &RfqRequest, side: VaultSide) -> Instruction {
+    let rfq_address = pda::rfq(&rfq.taker, rfq.id);
+    let leg_vault = pda::rfq_vault(&rfq_address, side);
+    match rfq.leg(side).mint {
+        None => build(
+            accounts::DepositRfqLegNative {
+                depositor,
+                rfq: rfq_address,
+                leg_vault,
+                system_program: system_program::ID,
+            },
+            instruction::DepositRfqLegNative {
+                rfq_id: rfq.id,
+                side,
+            },
+        ),
+        Some(mint) => build(
+            accounts::DepositRfqLegSpl {
+                depositor,
+                rfq: rfq_address,
+                mint,
+                depositor_token_account: get_associated_token_address(&depositor, &mint),
+                leg_vault,
+                token_program: token::ID,
+                system_program: system_program::ID,
+            },
+            instruction::DepositRfqLegSpl {
+                rfq_id: rfq.id,
+                side,
+            },
+        ),
+    }
+}
+
+/// Swaps both legs of an awarded RFQ; SPL legs are paid to associated
+/// token accounts
+pub fn settle_rfq(rfq: &RfqRequest) -> Instruction {
+    let rfq_address = pda::rfq(&rfq.taker, rfq.id);
+    let mut ix = build(
+        accounts::SettleRfq {
+            rfq: rfq_address,
+            token_program: token::ID,
+        },
+        instruction::SettleRfq { rfq_id: rfq.id },
+    );
+    let recipients = [
+        (VaultSide::Seller, rfq.taker),
+        (VaultSide::Buyer, rfq.winner.unwrap_or_default()),
+    ];
+    for (side, recipient) in recipients {
+        let destination = match rfq.leg(side).mint {
+            None => recipient,
+            Some(mint) => get_associated_token_address(&recipient, &mint),
+        };
+        ix.accounts.extend([
+            AccountMeta::new(pda::rfq_vault(&rfq_address, side), false),
+            AccountMeta::new(destination, false),
+        ]);
+    }
+    ix
+}
+
+/// Permissionless once the RFQ is no longer live; SPL legs are returned to
+/// the depositor's associated token account
+pub fn refund_rfq_leg(rfq: &RfqRequest, side: VaultSide) -> Instruction {
+    let rfq_address = pda::rfq(&rfq.taker, rfq.id);
+    let depositor = rfq.depositor(side).unwrap_or_default();
+    let mint = rfq.leg(side).mint;
+    build(
+        accounts::RefundRfqLeg {
+            depositor,
+            rfq: rfq_address,
+            leg_vault: pda::rfq_vault(&rfq_address, side),
+            depositor_token_account: mint.map(|mint| get_associated_token_address(&depositor, &mint)),
+            token_program: mint.map(|_| token::ID),
+        },
+        instruction::RefundRfqLeg {
+            rfq_id: rfq.id,
+            side,
+        },
+    )
+}
+
+// === COUNTER-OFFER SETTLEMENT ===
+
+/// Accepts `counter`, settling the maker's offer on its terms
+/// `price_feed` is the feed of the offer's price band, if it has one
+pub fn accept_counter_offer(counter: &CounterOffer, price_feed: Option<Pubkey>) -> Instruction {
+    let counter_offer = pda::counter_offer(&counter.offer, &counter.proposer);
+    build(
+        accounts::AcceptCounterOffer {
+            maker: counter.maker,
+            intrachain_offer: counter.offer,
+            proposer: counter.proposer,
+            counter_offer,
+            seller_vault: pda::seller_vault(&counter.maker, counter.offer_id),
+            counter_vault: pda::counter_vault(&counter_offer),
+            price_trigger: pda::price_trigger(&counter.offer),
+            price_band: pda::price_band(&counter.offer),
+            price_feed,
+        },
+        instruction::AcceptCounterOffer {
+            offer_id: counter.offer_id,
+            proposer: counter.proposer,
+        },
+    )
+}
+
+pub fn reject_counter_offer(counter: &CounterOffer) -> Instruction {
+    let counter_offer = pda::counter_offer(&counter.offer, &counter.proposer);
+    build(
+        accounts::RejectCounterOffer {
+            maker: counter.maker,
+            intrachain_offer: counter.offer,
+            proposer: counter.proposer,
+            counter_offer,
+            counter_vault: pda::counter_vault(&counter_offer),
+        },
+        instruction::RejectCounterOffer {
+            offer_id: counter.offer_id,
+            proposer: counter.proposer,
+        },
+    )
+}
+
+/// Permissionless once `counter` expired, its offer closed or its
+/// verification aborted
+pub fn refund_counter_offer(counter: &CounterOffer) -> Instruction {
+    let counter_offer = pda::counter_offer(&counter.offer, &counter.proposer);
+    build(
+        accounts::RefundCounterOffer {
+            proposer: counter.proposer,
+            intrachain_offer: counter.offer,
+            counter_offer,
+            counter_vault: pda::counter_vault(&counter_offer),
+        },
+        instruction::RefundCounterOffer {
+            offer_id: counter.offer_id,
+            proposer: counter.proposer,
+        },
+    )
+}
+
+// === FIAT SETTLEMENT ===
+
+pub fn mark_fiat_offer_paid(buyer: Pubkey, seller: Pubkey, offer_id: u64) -> Instruction {
+    build(
+        accounts::MarkFiatOfferPaid {
+            buyer,
+            fiat_offer: pda::fiat_offer(&seller, offer_id),
+        },
+        instruction::MarkFiatOfferPaid { offer_id },
+    )
+}
+
+pub fn release_fiat_offer(seller: Pubkey, buyer: Pubkey, offer_id: u64) -> Instruction {
+    let fiat_offer = pda::fiat_offer(&seller, offer_id);
+    build(
+        accounts::ReleaseFiatOffer {
+            maker: seller,
+            buyer,
+            fiat_offer,
+            fiat_vault: pda::fiat_vault(&fiat_offer),
+        },
+        instruction::ReleaseFiatOffer { offer_id },
+    )
+}
+
+pub fn cancel_fiat_offer(seller: Pubkey, offer_id: u64) -> Instruction {
+    let fiat_offer = pda::fiat_offer(&seller, offer_id);
+    build(
+        accounts::CancelFiatOffer {
+            maker: seller,
+            fiat_offer,
+            fiat_vault: pda::fiat_vault(&fiat_offer),
+        },
+        instruction::CancelFiatOffer { offer_id },
+    )
+}
+
+/// `opener` is the seller or the buyer, posting the dispute bond
+pub fn escalate_fiat_offer(opener: Pubkey, seller: Pubkey, offer_id: u64) -> Instruction {
+    let fiat_offer = pda::fiat_offer(&seller, offer_id);
+    build(
+        accounts::EscalateFiatOffer {
+            opener,
+            fiat_offer,
+            arbiter_registry: pda::arbiter_registry(),
+            dispute: pda::dispute(&fiat_offer),
+            system_program: system_program::ID,
+        },
+        instruction::EscalateFiatOffer { offer_id },
+    )
+}
+
+pub fn rule_fiat_dispute(
+    arbiter: Pubkey,
+    dispute: &Dispute,
+    maker_share_bps: u16,
+    refund_bond: bool,
+) -> Instruction {
+    build(
+        accounts::RuleFiatDispute {
+            arbiter,
+            arbiter_registry: pda::arbiter_registry(),
+            maker: dispute.maker,
+            buyer: dispute.buyer,
+            fiat_offer: dispute.offer,
+            dispute: pda::dispute(&dispute.offer),
+            fiat_vault: pda::fiat_vault(&dispute.offer),
+        },
+        instruction::RuleFiatDispute {
+            offer_id: dispute.offer_id,
+            maker_share_bps,
+            refund_bond,
+        },
+    )
+}
+
+/// Permissionless once `dispute`'s ruling window elapsed unruled
+pub fn expire_fiat_dispute(dispute: &Dispute) -> Instruction {
+    build(
+        accounts::ExpireFiatDispute {
+            opened_by: dispute.opened_by,
+            fiat_offer: dispute.offer,
+            dispute: pda::dispute(&dispute.offer),
+        },
+        instruction::ExpireFiatDispute {
+            offer_id: dispute.offer_id,
+        },
+    )
+}
+
+// === IDENTITY EXCHANGE ===
+
+/// `seller` opens an identity exchange with `args.buyer` about their settled
+/// offer `args.trade`, an intrachain or fiat offer as `trade_kind` says
+pub fn open_identity_exchange(
+    seller: Pubkey,
+    trade_kind: OfferKind,
+    args: instruction::OpenIdentityExchange,
+) -> Instruction {
+    let trade_of = |kind: OfferKind| (trade_kind == kind).then_some(args.trade);
+    build(
+        accounts::OpenIdentityExchange {
+            seller,
+            intrachain_offer: trade_of(OfferKind::Intrachain),
+            fiat_offer: trade_of(OfferKind::Fiat),
+            identity_exchange: pda::identity_exchange(&args.trade),
+            system_program: system_program::ID,
+        },
+        args,
+    )
+}
+
+// === PRICE BANDS ===
+
+/// `publisher` prices its feed `args.feed_id`, creating it on first use
+pub fn publish_price(publisher: Pubkey, args: instruction::PublishPrice) -> Instruction {
+    build(
+        accounts::PublishPrice {
+            publisher,
+            price_feed: pda::price_feed(&publisher, args.feed_id),
+            system_program: system_program::ID,
+        },
+        args,
+    )
+}
+
+/// `maker` guards their offer `args.offer`, an intrachain or interchain
+/// offer as `offer_kind` says, with a band around `price_feed`
+pub fn set_price_band(
+    maker: Pubkey,
+    offer_kind: OfferKind,
+    price_feed: Pubkey,
+    args: instruction::SetPriceBand,
+) -> Instruction {
+    let offer_of = |kind: OfferKind| (offer_kind == kind).then_some(args.offer);
+    build(
+        accounts::SetPriceBand {
+            maker,
+            intrachain_offer: offer_of(OfferKind::Intrachain),
+            interchain_offer: offer_of(OfferKind::Interchain),
+            price_feed,
+            price_band: pda::price_band(&args.offer),
+            system_program: system_program::ID,
+        },
+        args,
+    )
+}
+
+// === INTERCHAIN SETTLEMENT ===
+
+/// `outbound_sequence` is the chain config's current `outbound_sequence`;
+/// `price_feed` is the feed of the offer's price band, if it has one
+pub fn execute_interchain_swap(
+    buyer: Pubkey,
+    offer: &InterchainOffer,
+    outbound_sequence: u64,
+    payout_commitment: [u8; 32],
+    price_feed: Option<Pubkey>,
+) -> Instruction {
+    let relayer = (offer.relayer != Pubkey::default()).then_some(offer.relayer);
+    let interchain_offer = pda::interchain_offer(&offer.maker, offer.id);
+    build(
+        accounts::ExecuteInterchainSwap {
+            interchain_offer,
+            token_mapping: offer.token_mapping,
+            seller: offer.maker,
+            buyer,
+            seller_vault: pda::seller_vault(&offer.maker, offer.id),
+            buyer_vault: pda::buyer_vault(&buyer, offer.id),
+            chain_config: pda::chain_config(offer.chain_id),
+            outbound_message: pda::outbound_message(offer.chain_id, outbound_sequence),
+            exchange_config: pda::exchange_config(),
+            fee_treasury: pda::fee_treasury(),
+            relayer_bond: relayer.map(|r| pda::relayer_bond(&r)),
+            price_trigger: pda::price_trigger(&interchain_offer),
+            price_band: pda::price_band(&interchain_offer),
+            price_feed,
+            system_program: system_program::ID,
+        },
+        instruction::ExecuteInterchainSwap {
+            offer_id: offer.id,
+            payout_commitment,
+        },
+    )
+}
+
+pub fn cancel_interchain_offer(
+    maker: Pubkey,
+    offer_id: u64,
+    chain_id: u64,
+    outbound_sequence: u64,
+) -> Instruction {
+    build(
+        accounts::CancelInterchainOffer {
+            maker,
+            interchain_offer: pda::interchain_offer(&maker, offer_id),
+            seller_vault: pda::seller_vault(&maker, offer_id),
+            chain_config: pda::chain_config(chain_id),
+            outbound_message: pda::outbound_message(chain_id, outbound_sequence),
+            system_program: system_program::ID,
+        },
+        instruction::CancelInterchainOffer { offer_id },
+    )
+}
+
+pub fn refund_interchain_offer(
+    payer: Pubkey,
+    maker: Pubkey,
+    offer_id: u64,
+    chain_id: u64,
+    outbound_sequence: u64,
+) -> Instruction {
+    build(
+        accounts::RefundInterchainOffer {
+            payer,
+            maker,
+            interchain_offer: pda::interchain_offer(&maker, offer_id),
+            seller_vault: pda::seller_vault(&maker, offer_id),
+            chain_config: pda::chain_config(chain_id),
+            outbound_message: pda::outbound_message(chain_id, outbound_sequence),
+            system_program: system_program::ID,
+        },
+        instruction::RefundInterchainOffer { offer_id },
+    )
+}
+
+// === DISPUTES ===
+
+/// Opens a dispute on `maker`'s interchain offer as its maker or as
+/// `args.buyer`, posting the registry's dispute bond
+pub fn open_dispute(opener: Pubkey, maker: Pubkey, args: instruction::OpenDispute) -> Instruction {
+    let interchain_offer = pda::interchain_offer(&maker, args.offer_id);
+    build(
+        accounts::OpenDispute {
+            opener,
+            maker,
+            interchain_offer,
+            arbiter_registry: pda::arbiter_registry(),
+            buyer_vault: pda::buyer_vault(&args.buyer, args.offer_id),
+            dispute: pda::dispute(&interchain_offer),
+            system_program: system_program::ID,
+        },
+        args,
+    )
+}
+
+/// `outbound_sequence` is the chain config's current `outbound_sequence`
+pub fn rule_dispute(
+    arbiter: Pubkey,
+    offer: &InterchainOffer,
+    dispute: &Dispute,
+    outbound_sequence: u64,
+    maker_share_bps: u16,
+    refund_bond: bool,
+) -> Instruction {
+    build(
+        accounts::RuleDispute {
+            arbiter,
+            arbiter_registry: pda::arbiter_registry(),
+            maker: dispute.maker,
+            buyer: dispute.buyer,
+            opened_by: dispute.opened_by,
+            interchain_offer: dispute.offer,
+            dispute: pda::dispute(&dispute.offer),
+            seller_vault: pda::seller_vault(&dispute.maker, dispute.offer_id),
+            buyer_vault: pda::buyer_vault(&dispute.buyer, dispute.offer_id),
+            chain_config: pda::chain_config(offer.chain_id),
+            outbound_message: pda::outbound_message(offer.chain_id, outbound_sequence),
+            system_program: system_program::ID,
+        },
+        instruction::RuleDispute {
+            offer_id: dispute.offer_id,
+            maker_share_bps,
+            refund_bond,
+        },
+    )
+}
+
+/// Permissionless once `dispute`'s ruling window elapsed unruled
+pub fn expire_dispute(dispute: &Dispute) -> Instruction {
+    build(
+        accounts::ExpireDispute {
+            opened_by: dispute.opened_by,
+            maker: dispute.maker,
+            interchain_offer: dispute.offer,
+            dispute: pda::dispute(&dispute.offer),
+        },
+        instruction::ExpireDispute {
+            offer_id: dispute.offer_id,
+        },
+    )
+}
+
+// === SOLANA-ORIGIN SETTLEMENT ===
+
+/// `taker` reserves `maker`'s offer; the payout address is revealed to the
+/// x25519 key in `args`
+pub fn reserve_solana_origin_offer(
+    taker: Pubkey,
+    maker: Pubkey,
+    cluster_offset: u32,
+    args: instruction::ReserveSolanaOriginOffer,
+) -> Instruction {
+    build(
+        queue_accounts!(
+            ReserveSolanaOriginOffer {
+                maker: maker,
+                solana_origin_offer: pda::solana_origin_offer(&maker, args.offer_id),
+            },
+            taker,
+            ComputationOperation::RevealEvmPayoutAddress,
+            args.computation_offset,
+            cluster_offset
+        ),
+        args,
+    )
+}
+
+/// Token accounts moving an SPL escrow, `None` for SOL offers
+fn spl_escrow(
+    offer: &SolanaOriginOffer,
+    recipient_token_account: Option<Pubkey>,
+) -> (Option<Pubkey>, Option<Pubkey>, Option<Pubkey>) {
+    match offer.escrow_token_account {
+        Some(escrow) => (Some(escrow), recipient_token_account, Some(token::ID)),
+        None => (None, None, None),
+    }
+}
+
+/// Relayer-attested settlement to the offer's reserved `taker`;
+/// `taker_token_account` receives an SPL escrow
+pub fn settle_solana_origin_offer(
+    relayer: Pubkey,
+    offer: &SolanaOriginOffer,
+    taker: Pubkey,
+    taker_token_account: Option<Pubkey>,
+    args: instruction::SettleSolanaOriginOffer,
+) -> Instruction {
+    let (escrow_token_account, taker_token_account, token_program) =
+        spl_escrow(offer, taker_token_account);
+    build(
+        accounts::SettleSolanaOriginOffer {
+            relayer,
+            maker: offer.maker,
+            solana_origin_offer: pda::solana_origin_offer(&offer.maker, args.offer_id),
+            chain_config: pda::chain_config(offer.chain_id),
+            processed_evm_tx: pda::processed_evm_tx(offer.chain_id, &args.evm_tx_hash),
+            taker,
+            escrow_token_account,
+            taker_token_account,
+            token_program,
+            system_program: system_program::ID,
+        },
+        args,
+    )
+}
+
+/// Trustless settlement against a relayed block header, final once the
+/// header `finality_blocks` above it is posted; the offer must be reserved,
+/// and its taker receives the escrow
+pub fn settle_solana_origin_offer_with_receipt_proof(
+    payer: Pubkey,
+    offer: &SolanaOriginOffer,
+    finality_blocks: u64,
+    taker_token_account: Option<Pubkey>,
+    args: instruction::SettleSolanaOriginOfferWithReceiptProof,
+) -> Instruction {
+    let (escrow_token_account, taker_token_account, token_program) =
+        spl_escrow(offer, taker_token_account);
+    build(
+        accounts::SettleSolanaOriginOfferWithReceiptProof {
+            payer,
+            maker: offer.maker,
+            solana_origin_offer: pda::solana_origin_offer(&offer.maker, args.offer_id),
+            token_mapping: offer.token_mapping,
+            chain_config: pda::chain_config(offer.chain_id),
+            evm_block_header: pda::evm_block_header(offer.chain_id, args.block_number),
+            finality_block_header: pda::evm_block_header(
+                offer.chain_id,
+                args.block_number.saturating_add(finality_blocks),
+            ),
+            processed_evm_log: pda::processed_evm_log(
+                offer.chain_id,
+                args.block_number,
+                args.tx_index,
+                args.log_index,
+            ),
+            taker: offer.taker.unwrap_or_default(),
+            escrow_token_account,
+            taker_token_account,
+            token_program,
+            system_program: system_program::ID,
+        },
+        args,
+    )
+}
+
+pub fn refund_solana_origin_offer(
+    offer: &SolanaOriginOffer,
+    maker_token_account: Option<Pubkey>,
+) -> Instruction {
+    let (escrow_token_account, maker_token_account, token_program) =
+        spl_escrow(offer, maker_token_account);
+    build(
+        accounts::RefundSolanaOriginOffer {
+            maker: offer.maker,
+            solana_origin_offer: pda::solana_origin_offer(&offer.maker, offer.id),
+            escrow_token_account,
+            maker_token_account,
+            token_program,
+        },
+        instruction::RefundSolanaOriginOffer { offer_id: offer.id },
+    )
+}
+
+pub fn post_evm_block_header(
+    relayer: Pubkey,
+    chain_id: u64,
+    block_number: u64,
+    header_rlp: Vec<u8>,
+) -> Instruction {
+    build(
+        accounts::PostEvmBlockHeader {
+            relayer,
+            chain_config: pda::chain_config(chain_id),
+            evm_block_header: pda::evm_block_header(chain_id, block_number),
+            parent_block_header: pda::evm_block_header(chain_id, block_number.saturating_sub(1)),
+            system_program: system_program::ID,
+        },
+        instruction::PostEvmBlockHeader {
+            chain_id,
+            block_number,
+            header_rlp,
+        },
+    )
+}
+
+// === ADMIN ===
+
+pub fn initialize_exchange_config(admin: Pubkey) -> Instruction {
+    build(
+        accounts::InitializeExchangeConfig {
+            admin,
+            program_data: pda::program_data(),
+            exchange_config: pda::exchange_config(),
+            system_program: system_program::ID,
+        },
+        instruction::InitializeExchangeConfig {},
+    )
+}
+
+pub fn set_protocol_params(admin: Pubkey, args: instruction::SetProtocolParams) -> Instruction {
+    build(
+        accounts::SetProtocolParams {
+            admin,
+            exchange_config: pda::exchange_config(),
+        },
+        args,
+    )
+}
+
+pub fn withdraw_protocol_fees(admin: Pubkey, recipient: Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::WithdrawProtocolFees {
+            admin,
+            exchange_config: pda::exchange_config(),
+            fee_treasury: pda::fee_treasury(),
+            recipient,
+        },
+        instruction::WithdrawProtocolFees { amount },
+    )
+}
+
+pub fn set_chain_config(admin: Pubkey, args: instruction::SetChainConfig) -> Instruction {
+    build(
+        accounts::SetChainConfig {
+            admin,
+            exchange_config: pda::exchange_config(),
+            chain_config: pda::chain_config(args.chain_id),
+            system_program: system_program::ID,
+        },
+        args,
+    )
+}
+
+pub fn set_token_mapping(admin: Pubkey, args: instruction::SetTokenMapping) -> Instruction {
+    build(
+        accounts::SetTokenMapping {
+            admin,
+            exchange_config: pda::exchange_config(),
+            chain_config: pda::chain_config(args.chain_id),
+            token_mapping: pda::token_mapping(args.chain_id, &args.evm_token),
+            system_program: system_program::ID,
+        },
+        args,
+    )
+}
+
+pub fn set_computation_timeout(admin: Pubkey, comp_def_offset: u32, timeout_slots: u64) -> Instruction {
+    build(
+        accounts::SetComputationTimeout {
+            admin,
+            exchange_config: pda::exchange_config(),
+            computation_timeout: pda::computation_timeout(comp_def_offset),
+            system_program: system_program::ID,
+        },
+        instruction::SetComputationTimeout {
+            comp_def_offset,
+            timeout_slots,
+        },
+    )
+}
+
+/// Slashes the bond of the relayer that relayed `offer`
+pub fn slash_relayer(admin: Pubkey, offer: &InterchainOffer, amount: u64) -> Instruction {
+    build(
+        accounts::SlashRelayer {
+            admin,
+            exchange_config: pda::exchange_config(),
+            maker: offer.maker,
+            interchain_offer: pda::interchain_offer(&offer.maker, offer.id),
+            relayer_bond: pda::relayer_bond(&offer.relayer),
+            fee_treasury: pda::fee_treasury(),
+            system_program: system_program::ID,
+        },
+        instruction::SlashRelayer {
+            offer_id: offer.id,
+            amount,
+        },
+    )
+}
+
+pub fn set_arbiter_registry(admin: Pubkey, args: instruction::SetArbiterRegistry) -> Instruction {
+    build(
+        accounts::SetArbiterRegistry {
+            admin,
+            exchange_config: pda::exchange_config(),
+            arbiter_registry: pda::arbiter_registry(),
+            system_program: system_program::ID,
+        },
+        args,
+    )
+}
+
+/// Upgrades a program account left in an older layout; `authority` is the
+/// admin or the account's owner and pays for the reallocation. `maker` is
+/// needed for offers of the first program, which did not record it.
+pub fn migrate_account(authority: Pubkey, account: Pubkey, maker: Option<Pubkey>) -> Instruction {
+    build(
+        accounts::MigrateAccount {
+            authority,
+            account,
+            maker,
+            exchange_config: pda::exchange_config(),
+            system_program: system_program::ID,
+        },
+        instruction::MigrateAccount {},
+    )
+}
+
+// === RELAYERS ===
+
+pub fn bond_relayer(relayer: Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::BondRelayer {
+            relayer,
+            relayer_bond: pda::relayer_bond(&relayer),
+            system_program: system_program::ID,
+        },
+        instruction::BondRelayer { amount },
+    )
+}
+
+pub fn begin_relayer_unbonding(relayer: Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::UpdateRelayerBond {
+            relayer,
+            relayer_bond: pda::relayer_bond(&relayer),
+        },
+        instruction::BeginRelayerUnbonding { amount },
+    )
+}
+
+pub fn withdraw_relayer_bond(relayer: Pubkey) -> Instruction {
+    build(
+        accounts::WithdrawRelayerBond {
+            relayer,
+            relayer_bond: pda::relayer_bond(&relayer),
+            exchange_config: pda::exchange_config(),
+        },
+        instruction::WithdrawRelayerBond {},
+    )
+}
+
+pub fn claim_relayer_fees(relayer: Pubkey) -> Instruction {
+    build(
+        accounts::UpdateRelayerBond {
+            relayer,
+            relayer_bond: pda::relayer_bond(&relayer),
+        },
+        instruction::ClaimRelayerFees {},
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue_accounts_derive_arcium_pdas() {
+        let payer = Pubkey::new_unique();
+        let ix = retry_deposit_seller_native(
+            payer,
+            1,
+            instruction::RetryDepositSellerNative {
+                offer_id: 3,
+                ciphertext_seller_identity_hash: [0; 32],
+                pub_key: [0; 32],
+                nonce: 0,
+                computation_offset: 9,
+            },
+        );
+        let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(ix.program_id, PROGRAM_ID);
+        assert!(keys.contains(&pda::intrachain_offer(&payer, 3)));
+        assert!(keys.contains(&pda::pending_computation(9)));
+        assert!(keys.contains(&pda::computation_account(9)));
+        assert!(keys.contains(&pda::comp_def_account(
+            ComputationOperation::DepositSellerNative.comp_def_offset()
+        )));
+        assert!(keys.contains(&pda::cluster_account(1)));
+    }
+
+    #[test]
+    fn omits_relayer_bond_for_unrelayed_offers() {
+        let offer = unrelayed_offer();
+        let ix = execute_interchain_swap(Pubkey::new_unique(), &offer, 0, [0; 32], None);
+        let bond = pda::relayer_bond(&Pubkey::default());
+        assert!(!ix.accounts.iter().any(|meta| meta.pubkey == bond));
+    }
+
+    #[test]
+    fn passes_the_price_band_and_its_feed() {
+        let offer = unrelayed_offer();
+        let feed = pda::price_feed(&Pubkey::new_unique(), 1);
+        let ix = execute_interchain_swap(Pubkey::new_unique(), &offer, 0, [0; 32], Some(feed));
+        let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        let band = pda::price_band(&pda::interchain_offer(&offer.maker, offer.id));
+        assert!(keys.contains(&band));
+        assert!(keys.contains(&feed));
+    }
+
+    fn unrelayed_offer() -> InterchainOffer {
+        InterchainOffer {
+            layout_version: 2,
+            id: 1,
+            token_a_offered_amount: 10,
+            token_b_wanted_amount: [0; 32],
+            is_taker_native: true,
+            chain_id: 1,
+            deadline: 0,
+            token_mapping: Pubkey::new_unique(),
+            maker: Pubkey::new_unique(),
+            buyer: None,
+            relayer: Pubkey::default(),
+            slashed: false,
+            status: confidential_cross_chain_exchange::OfferStatus::Open,
+            computation_offset: 0,
+            bump: 0,
+        }
+    }
+}
@@ -0,0 +1,16 @@
+//! Rust client for the confidential cross-chain exchange.
+//!
+//! Mirrors what `tests/confidential_cross_chain_exchange.ts` does by hand:
+//! [`pda`] derives every account address, [`instructions`] builds each
+//! client-facing instruction, [`accounts`] decodes offer accounts, and
+//! [`crypto`] produces the ciphertext/pub_key/nonce triples confidential
+//! instructions take.
+
+pub mod accounts;
+pub mod crypto;
+pub mod instructions;
+pub mod pda;
+pub mod rescue;
+
+pub use confidential_cross_chain_exchange::{self as program, ID as PROGRAM_ID};
+pub use rescue::Fe;
@@ -0,0 +1,217 @@
+//! Program derived addresses of the exchange and of the Arcium accounts its
+//! confidential instructions queue computations through.
+
+use anchor_lang::{
+    prelude::{ProgramData, Pubkey},
+    Owner,
+};
+use arcium_anchor::{
+    ARCIUM_CLOCK_ACCOUNT_ADDRESS,
+    ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    CLUSTER_PDA_SEED,
+    COMP_DEF_PDA_SEED,
+    COMP_PDA_SEED,
+    EXECPOOL_PDA_SEED,
+    MEMPOOL_PDA_SEED,
+    MXE_PDA_SEED,
+    SIGN_PDA_SEED,
+};
+use arcium_client::ARCIUM_PROGRAM_ID;
+use confidential_cross_chain_exchange::{VaultSide, ID as PROGRAM_ID};
+
+fn program_address(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &PROGRAM_ID).0
+}
+
+fn arcium_address(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &ARCIUM_PROGRAM_ID).0
+}
+
+pub fn interchain_offer(maker: &Pubkey, offer_id: u64) -> Pubkey {
+    program_address(&[b"InterChainoffer", maker.as_ref(), &offer_id.to_le_bytes()])
+}
+
+pub fn intrachain_offer(maker: &Pubkey, offer_id: u64) -> Pubkey {
+    program_address(&[b"IntraChainoffer", maker.as_ref(), &offer_id.to_le_bytes()])
+}
+
+pub fn solana_origin_offer(maker: &Pubkey, offer_id: u64) -> Pubkey {
+    program_address(&[b"SolanaOriginOffer", maker.as_ref(), &offer_id.to_le_bytes()])
+}
+
+pub fn basket_offer(maker: &Pubkey, offer_id: u64) -> Pubkey {
+    program_address(&[b"BasketOffer", maker.as_ref(), &offer_id.to_le_bytes()])
+}
+
+/// Escrow vault of the leg at `leg_index` of `side`, funded by `depositor`
+pub fn basket_vault(offer: &Pubkey, depositor: &Pubkey, side: VaultSide, leg_index: u8) -> Pubkey {
+    program_address(&[
+        b"BasketVault",
+        offer.as_ref(),
+        depositor.as_ref(),
+        &[side as u8],
+        &[leg_index],
+    ])
+}
+
+pub fn rfq(taker: &Pubkey, rfq_id: u64) -> Pubkey {
+    program_address(&[b"Rfq", taker.as_ref(), &rfq_id.to_le_bytes()])
+}
+
+/// Escrow vault of an awarded RFQ's `side`: the winner's base leg or the
+/// taker's quote leg
+pub fn rfq_vault(rfq: &Pubkey, side: VaultSide) -> Pubkey {
+    program_address(&[b"RfqVault", rfq.as_ref(), &[side as u8]])
+}
+
+/// `proposer`'s counter-offer on the intrachain offer at `offer`
+pub fn counter_offer(offer: &Pubkey, proposer: &Pubkey) -> Pubkey {
+    program_address(&[b"CounterOffer", offer.as_ref(), proposer.as_ref()])
+}
+
+/// Escrow vault of the lamports a counter-offer proposes
+pub fn counter_vault(counter_offer: &Pubkey) -> Pubkey {
+    program_address(&[b"CounterVault", counter_offer.as_ref()])
+}
+
+pub fn fiat_offer(seller: &Pubkey, offer_id: u64) -> Pubkey {
+    program_address(&[b"FiatOffer", seller.as_ref(), &offer_id.to_le_bytes()])
+}
+
+/// Escrow vault of the lamports a fiat offer sells
+pub fn fiat_vault(fiat_offer: &Pubkey) -> Pubkey {
+    program_address(&[b"FiatVault", fiat_offer.as_ref()])
+}
+
+/// Identity exchange between the parties of the settled offer at `trade`
+pub fn identity_exchange(trade: &Pubkey) -> Pubkey {
+    program_address(&[b"IdentityExchange", trade.as_ref()])
+}
+
+/// Price feed `feed_id` published by `publisher`
+pub fn price_feed(publisher: &Pubkey, feed_id: u64) -> Pubkey {
+    program_address(&[b"PriceFeed", publisher.as_ref(), &feed_id.to_le_bytes()])
+}
+
+/// Price band guarding the offer at `offer`
+pub fn price_band(offer: &Pubkey) -> Pubkey {
+    program_address(&[b"PriceBand", offer.as_ref()])
+}
+
+/// Price trigger holding back the offer at `offer`
+pub fn price_trigger(offer: &Pubkey) -> Pubkey {
+    program_address(&[b"PriceTrigger", offer.as_ref()])
+}
+
+pub fn seller_vault(seller: &Pubkey, offer_id: u64) -> Pubkey {
+    program_address(&[b"seller_vault", seller.as_ref(), &offer_id.to_le_bytes()])
+}
+
+pub fn buyer_vault(buyer: &Pubkey, offer_id: u64) -> Pubkey {
+    program_address(&[b"buyer_vault", buyer.as_ref(), &offer_id.to_le_bytes()])
+}
+
+pub fn exchange_config() -> Pubkey {
+    program_address(&[b"ExchangeConfig"])
+}
+
+/// The upgradeable loader's record of the exchange, holding its upgrade
+/// authority
+pub fn program_data() -> Pubkey {
+    Pubkey::find_program_address(&[PROGRAM_ID.as_ref()], &ProgramData::owner()).0
+}
+
+pub fn fee_treasury() -> Pubkey {
+    program_address(&[b"FeeTreasury"])
+}
+
+pub fn chain_config(chain_id: u64) -> Pubkey {
+    program_address(&[b"ChainConfig", &chain_id.to_le_bytes()])
+}
+
+pub fn token_mapping(chain_id: u64, evm_token: &[u8; 20]) -> Pubkey {
+    program_address(&[b"TokenMapping", &chain_id.to_le_bytes(), evm_token])
+}
+
+/// Outbound message posted at `sequence`, the chain config's
+/// `outbound_sequence` when the message is posted
+pub fn outbound_message(chain_id: u64, sequence: u64) -> Pubkey {
+    program_address(&[b"OutboundMessage", &chain_id.to_le_bytes(), &sequence.to_le_bytes()])
+}
+
+pub fn relayer_bond(relayer: &Pubkey) -> Pubkey {
+    program_address(&[b"RelayerBond", relayer.as_ref()])
+}
+
+pub fn arbiter_registry() -> Pubkey {
+    program_address(&[b"ArbiterRegistry"])
+}
+
+/// Dispute on the interchain or fiat offer at `offer`
+pub fn dispute(offer: &Pubkey) -> Pubkey {
+    program_address(&[b"Dispute", offer.as_ref()])
+}
+
+pub fn evm_block_header(chain_id: u64, block_number: u64) -> Pubkey {
+    program_address(&[b"EvmBlockHeader", &chain_id.to_le_bytes(), &block_number.to_le_bytes()])
+}
+
+pub fn processed_evm_tx(chain_id: u64, evm_tx_hash: &[u8; 32]) -> Pubkey {
+    program_address(&[b"ProcessedEvmTx", &chain_id.to_le_bytes(), evm_tx_hash])
+}
+
+pub fn processed_evm_log(chain_id: u64, block_number: u64, tx_index: u64, log_index: u64) -> Pubkey {
+    program_address(&[
+        b"ProcessedEvmLog",
+        &chain_id.to_le_bytes(),
+        &block_number.to_le_bytes(),
+        &tx_index.to_le_bytes(),
+        &log_index.to_le_bytes(),
+    ])
+}
+
+pub fn pending_computation(computation_offset: u64) -> Pubkey {
+    program_address(&[b"PendingComputation", &computation_offset.to_le_bytes()])
+}
+
+pub fn computation_timeout(comp_def_offset: u32) -> Pubkey {
+    program_address(&[b"ComputationTimeout", &comp_def_offset.to_le_bytes()])
+}
+
+/// The program's signer PDA that queues computations
+pub fn sign_pda() -> Pubkey {
+    program_address(&[SIGN_PDA_SEED])
+}
+
+pub fn mxe_account() -> Pubkey {
+    arcium_address(&[MXE_PDA_SEED, PROGRAM_ID.as_ref()])
+}
+
+pub fn mempool_account() -> Pubkey {
+    arcium_address(&[MEMPOOL_PDA_SEED, PROGRAM_ID.as_ref()])
+}
+
+pub fn executing_pool() -> Pubkey {
+    arcium_address(&[EXECPOOL_PDA_SEED, PROGRAM_ID.as_ref()])
+}
+
+pub fn computation_account(computation_offset: u64) -> Pubkey {
+    arcium_address(&[COMP_PDA_SEED, PROGRAM_ID.as_ref(), &computation_offset.to_le_bytes()])
+}
+
+pub fn comp_def_account(comp_def_offset: u32) -> Pubkey {
+    arcium_address(&[COMP_DEF_PDA_SEED, PROGRAM_ID.as_ref(), &comp_def_offset.to_le_bytes()])
+}
+
+/// Cluster at `cluster_offset`, the MXE account's `cluster`
+pub fn cluster_account(cluster_offset: u32) -> Pubkey {
+    arcium_address(&[CLUSTER_PDA_SEED, &cluster_offset.to_le_bytes()])
+}
+
+pub fn fee_pool_account() -> Pubkey {
+    ARCIUM_FEE_POOL_ACCOUNT_ADDRESS
+}
+
+pub fn clock_account() -> Pubkey {
+    ARCIUM_CLOCK_ACCOUNT_ADDRESS
+}
@@ -0,0 +1,482 @@
+//! Plaintext port of the Arcis Rescue cipher and Rescue-Prime hash over the
+//! field with p = 2^255 - 19, matching what the MXE uses to decrypt client
+//! inputs and encrypt shared outputs.
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{One, ToPrimitive, Zero};
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+use std::{
+    ops::{Add, Mul, Neg, Sub},
+    sync::OnceLock,
+};
+
+// hardcoded security level of 128 bits
+const SECURITY_LEVEL: usize = 128;
+/// Number of field elements in a client's Rescue key
+pub const RESCUE_KEY_COUNT: usize = 5;
+/// Rescue-Prime hash width and capacity, hence a rate of 5
+const HASH_WIDTH: usize = 6;
+const HASH_CAPACITY: usize = 1;
+const HASH_RATE: usize = HASH_WIDTH - HASH_CAPACITY;
+
+fn modulus() -> &'static BigUint {
+    static P: OnceLock<BigUint> = OnceLock::new();
+    P.get_or_init(|| (BigUint::one() << 255u32) - BigUint::from(19u32))
+}
+
+/// Element of the field with 2^255 - 19 elements
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Fe(BigUint);
+
+impl Fe {
+    pub fn zero() -> Self {
+        Fe(BigUint::zero())
+    }
+
+    pub fn one() -> Self {
+        Fe(BigUint::one())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Reduces any little-endian integer modulo p
+    pub fn from_le_bytes(bytes: &[u8]) -> Self {
+        Fe(BigUint::from_bytes_le(bytes) % modulus())
+    }
+
+    pub fn to_le_bytes(&self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        let bytes = self.0.to_bytes_le();
+        out[..bytes.len()].copy_from_slice(&bytes);
+        out
+    }
+
+    /// Reads a canonical encoding, rejecting values that are not below p
+    pub fn from_canonical_le_bytes(bytes: &[u8; 32]) -> Option<Self> {
+        let value = BigUint::from_bytes_le(bytes);
+        (&value < modulus()).then_some(Fe(value))
+    }
+
+    pub fn pow(&self, exponent: &BigUint) -> Self {
+        Fe(self.0.modpow(exponent, modulus()))
+    }
+
+    pub fn invert(&self) -> Self {
+        self.pow(&(modulus() - BigUint::from(2u32)))
+    }
+
+    /// The value as an unsigned integer below p
+    pub fn to_biguint(&self) -> BigUint {
+        self.0.clone()
+    }
+}
+
+impl From<u128> for Fe {
+    fn from(value: u128) -> Self {
+        Fe(BigUint::from(value))
+    }
+}
+
+impl From<u64> for Fe {
+    fn from(value: u64) -> Self {
+        Fe(BigUint::from(value))
+    }
+}
+
+impl From<i128> for Fe {
+    fn from(value: i128) -> Self {
+        let magnitude = Fe(BigUint::from(value.unsigned_abs()));
+        if value < 0 {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl From<bool> for Fe {
+    fn from(value: bool) -> Self {
+        if value {
+            Fe::one()
+        } else {
+            Fe::zero()
+        }
+    }
+}
+
+impl From<&BigInt> for Fe {
+    fn from(value: &BigInt) -> Self {
+        let magnitude = Fe(value.magnitude() % modulus());
+        match value.sign() {
+            Sign::Minus => -magnitude,
+            _ => magnitude,
+        }
+    }
+}
+
+impl Add for &Fe {
+    type Output = Fe;
+    fn add(self, rhs: &Fe) -> Fe {
+        Fe((&self.0 + &rhs.0) % modulus())
+    }
+}
+
+impl Sub for &Fe {
+    type Output = Fe;
+    fn sub(self, rhs: &Fe) -> Fe {
+        Fe((&self.0 + modulus() - &rhs.0) % modulus())
+    }
+}
+
+impl Mul for &Fe {
+    type Output = Fe;
+    fn mul(self, rhs: &Fe) -> Fe {
+        Fe((&self.0 * &rhs.0) % modulus())
+    }
+}
+
+impl Neg for Fe {
+    type Output = Fe;
+    fn neg(self) -> Fe {
+        &Fe::zero() - &self
+    }
+}
+
+/// Row-major square matrix
+#[derive(Clone, Debug)]
+struct Matrix {
+    n: usize,
+    data: Vec<Fe>,
+}
+
+impl Matrix {
+    fn get(&self, i: usize, j: usize) -> &Fe {
+        &self.data[i * self.n + j]
+    }
+
+    fn mul_vec(&self, v: &[Fe]) -> Vec<Fe> {
+        (0..self.n)
+            .map(|i| {
+                (0..self.n).fold(Fe::zero(), |acc, k| &acc + &(&v[k] * self.get(i, k)))
+            })
+            .collect()
+    }
+
+    fn is_singular(&self) -> bool {
+        // Gaussian elimination, the matrix is singular iff a column has no pivot
+        let n = self.n;
+        let mut rows: Vec<Vec<Fe>> = self.data.chunks(n).map(|r| r.to_vec()).collect();
+        for col in 0..n {
+            let Some(pivot) = (col..n).find(|&r| !rows[r][col].is_zero()) else {
+                return true;
+            };
+            rows.swap(col, pivot);
+            let inverse = rows[col][col].invert();
+            let (done, rest) = rows.split_at_mut(col + 1);
+            let pivot_row = &done[col];
+            for row in rest {
+                let factor = &row[col] * &inverse;
+                for (x, p) in row.iter_mut().zip(pivot_row).skip(col) {
+                    *x = &*x - &(&factor * p);
+                }
+            }
+        }
+        false
+    }
+}
+
+fn add_vec(a: &[Fe], b: &[Fe]) -> Vec<Fe> {
+    a.iter().zip(b).map(|(x, y)| x + y).collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RescueMode {
+    BlockCipher,
+    HashFunction { capacity: usize },
+}
+
+/// The smallest prime alpha not dividing p - 1, and its inverse modulo p - 1
+fn alphas() -> &'static (BigUint, BigUint) {
+    static ALPHAS: OnceLock<(BigUint, BigUint)> = OnceLock::new();
+    ALPHAS.get_or_init(|| {
+        let p_minus_one = modulus() - BigUint::one();
+        let alpha = [2u32, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47]
+            .into_iter()
+            .map(BigUint::from)
+            .find(|a| !(&p_minus_one % a).is_zero())
+            .expect("no prime alpha coprime to p - 1");
+        let inverse = BigInt::from(alpha.clone())
+            .modinv(&BigInt::from(p_minus_one))
+            .and_then(|i| i.to_biguint())
+            .expect("alpha is invertible modulo p - 1");
+        (alpha, inverse)
+    })
+}
+
+/// Cauchy MDS matrix with x_i = i and y_j = -j
+fn mds_matrix(m: usize) -> Matrix {
+    let data = (1..=m as u64)
+        .flat_map(|i| (1..=m as u64).map(move |j| Fe::from(i + j).invert()))
+        .collect();
+    Matrix { n: m, data }
+}
+
+/// Rescue permutation description, see https://tosc.iacr.org/index.php/ToSC/article/view/8695/8287
+#[derive(Clone, Debug)]
+struct RescueDesc {
+    mode: RescueMode,
+    m: usize,
+    mds: Matrix,
+    round_keys: Vec<Vec<Fe>>,
+}
+
+impl RescueDesc {
+    fn new_cipher(key: &[Fe]) -> Self {
+        let m = key.len();
+        assert!(m >= 2, "a Rescue key has at least two elements");
+        let mode = RescueMode::BlockCipher;
+        let n_rounds = n_rounds(mode, m);
+        let mds = mds_matrix(m);
+        let constants = sample_constants(mode, n_rounds, m);
+        // the key schedule is the permutation of the key under the round constants
+        let round_keys = permutation_states(mode, &mds, &constants, key);
+        RescueDesc { mode, m, mds, round_keys }
+    }
+
+    fn new_hash(m: usize, capacity: usize) -> Self {
+        let mode = RescueMode::HashFunction { capacity };
+        let n_rounds = n_rounds(mode, m);
+        RescueDesc {
+            mode,
+            m,
+            mds: mds_matrix(m),
+            round_keys: sample_constants(mode, n_rounds, m),
+        }
+    }
+
+    fn permute(&self, state: &[Fe]) -> Vec<Fe> {
+        permutation_states(self.mode, &self.mds, &self.round_keys, state)
+            .pop()
+            .expect("permutation has at least one state")
+    }
+}
+
+fn n_rounds(mode: RescueMode, m: usize) -> usize {
+    let alpha = alphas().0.to_usize().expect("alpha is small");
+    match mode {
+        RescueMode::BlockCipher => {
+            let log2_p = modulus().to_f64().expect("p fits in f64").log2();
+            let l_0 = (SECURITY_LEVEL as f64 * 2.0
+                / ((m + 1) as f64 * (log2_p - ((alpha - 1) as f64).log2())))
+            .ceil() as usize;
+            let l_1 = if alpha == 3 {
+                ((SECURITY_LEVEL + 2) as f64 / (4 * m) as f64).ceil() as usize
+            } else {
+                ((SECURITY_LEVEL + 3) as f64 / (m as f64 * 5.5)).ceil() as usize
+            };
+            2 * l_0.max(l_1).max(5)
+        }
+        RescueMode::HashFunction { capacity } => {
+            // rounds needed against a Groebner basis attack
+            let rate = m - capacity;
+            let dcon = |n: usize| (0.5 * ((alpha - 1) * m * (n - 1)) as f64 + 2.0).floor() as usize;
+            let v = |n: usize| m * (n - 1) + rate;
+            let binomial = |n: usize, k: usize| -> BigUint {
+                (1..=k).fold(BigUint::one(), |acc, i| acc * BigUint::from(n + 1 - i) / BigUint::from(i))
+            };
+            let target = BigUint::one() << SECURITY_LEVEL;
+            let mut l_1 = 1;
+            let mut tmp = binomial(v(l_1) + dcon(l_1), v(l_1));
+            while &tmp * &tmp <= target && l_1 <= 23 {
+                l_1 += 1;
+                tmp = binomial(v(l_1) + dcon(l_1), v(l_1));
+            }
+            // a minimum for sanity plus 50%
+            (1.5 * l_1.max(5) as f64).ceil() as usize
+        }
+    }
+}
+
+/// Round constants drawn from SHAKE256, read as unsigned little-endian
+/// integers 16 bytes longer than p so they are close to uniform
+fn sample_constants(mode: RescueMode, n_rounds: usize, m: usize) -> Vec<Vec<Fe>> {
+    const BUFFER_LEN: usize = 32 + 16;
+    let mut hasher = Shake256::default();
+    match mode {
+        RescueMode::BlockCipher => {
+            hasher.update(b"encrypt everything, compute anything");
+            let mut reader = hasher.finalize_xof();
+            let mut sample = || {
+                let mut buffer = [0u8; BUFFER_LEN];
+                reader.read(&mut buffer);
+                Fe::from_le_bytes(&buffer)
+            };
+
+            let mut round_constant_mat = Matrix {
+                n: m,
+                data: (0..m * m).map(|_| sample()).collect(),
+            };
+            let initial: Vec<Fe> = (0..m).map(|_| sample()).collect();
+            let affine: Vec<Fe> = (0..m).map(|_| sample()).collect();
+            while round_constant_mat.is_singular() {
+                round_constant_mat.data = (0..m * m).map(|_| sample()).collect();
+            }
+
+            let mut constants = vec![initial];
+            for _ in 0..2 * n_rounds {
+                let last = constants.last().expect("constants start non-empty");
+                let next = add_vec(&round_constant_mat.mul_vec(last), &affine);
+                constants.push(next);
+            }
+            constants
+        }
+        RescueMode::HashFunction { capacity } => {
+            let seed = format!(
+                "Rescue-XLIX({},{},{},{})",
+                modulus(),
+                m,
+                capacity,
+                SECURITY_LEVEL
+            );
+            hasher.update(seed.as_bytes());
+            let mut reader = hasher.finalize_xof();
+            let elements: Vec<Fe> = (0..2 * m * n_rounds)
+                .map(|_| {
+                    let mut buffer = [0u8; BUFFER_LEN];
+                    reader.read(&mut buffer);
+                    Fe::from_le_bytes(&buffer)
+                })
+                .collect();
+            // an odd number of round keys is needed, a leading zero key makes this
+            // Algorithm 3 of https://eprint.iacr.org/2020/1143.pdf
+            std::iter::once(vec![Fe::zero(); m])
+                .chain(elements.chunks(m).map(|c| c.to_vec()))
+                .collect()
+        }
+    }
+}
+
+/// Every intermediate state of the Rescue permutation of `state`
+fn permutation_states(
+    mode: RescueMode,
+    mds: &Matrix,
+    subkeys: &[Vec<Fe>],
+    state: &[Fe],
+) -> Vec<Vec<Fe>> {
+    let (alpha, alpha_inverse) = alphas();
+    let (exponent_even, exponent_odd) = match mode {
+        RescueMode::BlockCipher => (alpha_inverse, alpha),
+        RescueMode::HashFunction { .. } => (alpha, alpha_inverse),
+    };
+    let mut states = vec![add_vec(state, &subkeys[0])];
+    for (r, key) in subkeys[1..].iter().enumerate() {
+        let exponent = if r % 2 == 0 { exponent_even } else { exponent_odd };
+        let s: Vec<Fe> = states
+            .last()
+            .expect("states start non-empty")
+            .iter()
+            .map(|x| x.pow(exponent))
+            .collect();
+        states.push(add_vec(&mds.mul_vec(&s), key));
+    }
+    states
+}
+
+/// Rescue-Prime hash with width 6 and capacity 1, see https://eprint.iacr.org/2020/1143.pdf
+struct RescuePrimeHash {
+    desc: RescueDesc,
+}
+
+impl RescuePrimeHash {
+    fn new() -> Self {
+        RescuePrimeHash {
+            desc: RescueDesc::new_hash(HASH_WIDTH, HASH_CAPACITY),
+        }
+    }
+
+    fn digest(&self, mut message: Vec<Fe>) -> Vec<Fe> {
+        message.push(Fe::one());
+        while !message.len().is_multiple_of(HASH_RATE) {
+            message.push(Fe::zero());
+        }
+        let mut state = vec![Fe::zero(); self.desc.m];
+        for chunk in message.chunks(HASH_RATE) {
+            for (s, x) in state.iter_mut().zip(chunk) {
+                *s = &*s + x;
+            }
+            state = self.desc.permute(&state);
+        }
+        state.truncate(HASH_RATE);
+        state
+    }
+
+    /// HMAC as in RFC 2104 with B = L = the hash rate
+    fn hmac(&self, mut key: Vec<Fe>, message: Vec<Fe>) -> Vec<Fe> {
+        assert!(key.len() <= HASH_RATE, "HMAC key longer than the hash rate");
+        let ipad = Fe::from_le_bytes(&[0x36; 32]);
+        let opad = Fe::from_le_bytes(&[0x5c; 32]);
+        key.resize(HASH_RATE, Fe::zero());
+        let mut inner: Vec<Fe> = key.iter().map(|k| k + &ipad).collect();
+        inner.extend(message);
+        let mut outer: Vec<Fe> = key.iter().map(|k| k + &opad).collect();
+        outer.extend(self.digest(inner));
+        self.digest(outer)
+    }
+
+    /// HKDF as in RFC 5869 with an empty salt and info, and L = HashLen
+    fn hkdf(&self, ikm: Vec<Fe>) -> Vec<Fe> {
+        let prk = self.hmac(vec![Fe::zero(); HASH_RATE], ikm);
+        self.hmac(prk, vec![Fe::one()])
+    }
+}
+
+/// Rescue block cipher in counter mode, keyed from an x25519 shared secret
+#[derive(Clone, Debug)]
+pub struct RescueCipher {
+    desc: RescueDesc,
+}
+
+impl RescueCipher {
+    pub fn new(shared_secret: &[u8; 32]) -> Self {
+        let key = RescuePrimeHash::new().hkdf(vec![Fe::from_le_bytes(shared_secret)]);
+        RescueCipher {
+            desc: RescueDesc::new_cipher(&key),
+        }
+    }
+
+    /// Counter-mode keystream for `len` elements, counter blocks are
+    /// `[nonce, i, 0, ..]`
+    fn keystream(&self, nonce: u128, len: usize) -> Vec<Fe> {
+        let m = self.desc.m;
+        (0..len.div_ceil(m))
+            .flat_map(|i| {
+                let mut counter = vec![Fe::zero(); m];
+                counter[0] = Fe::from(nonce);
+                counter[1] = Fe::from(i as u64);
+                self.desc.permute(&counter)
+            })
+            .take(len)
+            .collect()
+    }
+
+    pub fn encrypt(&self, plaintext: &[Fe], nonce: u128) -> Vec<[u8; 32]> {
+        plaintext
+            .iter()
+            .zip(self.keystream(nonce, plaintext.len()))
+            .map(|(p, k)| (p + &k).to_le_bytes())
+            .collect()
+    }
+
+    pub fn decrypt(&self, ciphertext: &[[u8; 32]], nonce: u128) -> Vec<Fe> {
+        ciphertext
+            .iter()
+            .zip(self.keystream(nonce, ciphertext.len()))
+            .map(|(c, k)| &Fe::from_le_bytes(c) - &k)
+            .collect()
+    }
+}
@@ -0,0 +1,283 @@
+//! Decoders for the program's events and the accounts the indexer tracks.
+
+use anchor_lang::{prelude::*, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use confidential_exchange_client::{
+    accounts,
+    program::{
+        AccountMigratedEvent,
+        BasketLegRefundedEvent,
+        BasketOfferCreatedEvent,
+        BasketSwapExecutedEvent,
+        BuyerVaultRefundedEvent,
+        ComputationAbortedEvent,
+        ComputationRetriedEvent,
+        ComputationStaleEvent,
+        ConfidentialDepositNativeEvent,
+        CounterOfferAcceptedEvent,
+        CounterOfferProposedEvent,
+        CounterOfferRefundedEvent,
+        CounterOfferRejectedEvent,
+        CounterpartyIdentityRevealedEvent,
+        CreateBasketOfferEvent,
+        DepositSellerNativeEvent,
+        DepositSellerSplEvent,
+        DisputeExpiredEvent,
+        DisputeOpenedEvent,
+        DisputeRuledEvent,
+        EventHeader,
+        EvmBlockHeaderPostedEvent,
+        EvmPayoutAddressRevealedEvent,
+        EvmPayoutAddressStoredEvent,
+        FiatOfferCreatedEvent,
+        FiatOfferLockedEvent,
+        FiatOfferPaidEvent,
+        FiatOfferReleasedEvent,
+        FiatPaymentDetailsRevealedEvent,
+        FiatPaymentDetailsStoredEvent,
+        FinalizeBasketOfferEvent,
+        FinalizeInterchainOriginEvmOfferEvent,
+        FinalizeIntrachainOfferEvent,
+        IdentityExchangeOpenedEvent,
+        InterchainOffer,
+        InterchainOfferCreatedEvent,
+        InterchainOriginEvmDepositSellerSplEvent,
+        InterchainSwapExecutedEvent,
+        IntraChainOffer,
+        IntrachainOfferCreatedEvent,
+        IntrachainSwapExecutedEvent,
+        OfferCancelledEvent,
+        OfferRefundedEvent,
+        OutboundMessagePostedEvent,
+        PriceBandSetEvent,
+        PricePublishedEvent,
+        PriceTriggerArmedEvent,
+        PriceTriggerCheckedEvent,
+        ProposeCounterOfferEvent,
+        ProtocolFeeChargedEvent,
+        RelayOfferClonedEvent,
+        RelayerBondUpdatedEvent,
+        RelayerSlashedEvent,
+        RfqAwardedEvent,
+        RfqCancelledEvent,
+        RfqCreatedEvent,
+        RfqLegRefundedEvent,
+        RfqQuoteRankedEvent,
+        RfqSettledEvent,
+        SolanaOriginOfferCreatedEvent,
+        SolanaOriginOfferProvenEvent,
+        SolanaOriginOfferReservedEvent,
+        SolanaOriginOfferSettledEvent,
+        SumEvent,
+        TradeIdentityStoredEvent,
+        VaultDepositedEvent,
+    },
+    PROGRAM_ID,
+};
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+macro_rules! program_events {
+    ($($name:ident),* $(,)?) => {
+        /// Every event the program emits
+        pub enum ProgramEvent {
+            $($name($name),)*
+        }
+
+        impl ProgramEvent {
+            /// Decodes an `emit!` payload: the event's discriminator then its
+            /// Borsh body. `None` for data that is not one of the program's
+            /// events.
+            pub fn decode(data: &[u8]) -> Option<Self> {
+                $(
+                    if let Some(body) = data.strip_prefix($name::DISCRIMINATOR) {
+                        return $name::deserialize(&mut &body[..]).ok().map(Self::$name);
+                    }
+                )*
+                None
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$name(_) => stringify!($name),)*
+                }
+            }
+
+            pub fn header(&self) -> &EventHeader {
+                match self {
+                    $(Self::$name(event) => &event.header,)*
+                }
+            }
+        }
+    };
+}
+
+program_events! {
+    SumEvent,
+    InterchainOfferCreatedEvent,
+    RelayOfferClonedEvent,
+    InterchainOriginEvmDepositSellerSplEvent,
+    FinalizeInterchainOriginEvmOfferEvent,
+    InterchainSwapExecutedEvent,
+    IntrachainOfferCreatedEvent,
+    DepositSellerNativeEvent,
+    DepositSellerSplEvent,
+    FinalizeIntrachainOfferEvent,
+    IntrachainSwapExecutedEvent,
+    ConfidentialDepositNativeEvent,
+    VaultDepositedEvent,
+    OfferCancelledEvent,
+    OfferRefundedEvent,
+    BuyerVaultRefundedEvent,
+    OutboundMessagePostedEvent,
+    SolanaOriginOfferCreatedEvent,
+    EvmPayoutAddressStoredEvent,
+    SolanaOriginOfferReservedEvent,
+    EvmPayoutAddressRevealedEvent,
+    SolanaOriginOfferSettledEvent,
+    SolanaOriginOfferProvenEvent,
+    BasketOfferCreatedEvent,
+    CreateBasketOfferEvent,
+    FinalizeBasketOfferEvent,
+    BasketSwapExecutedEvent,
+    BasketLegRefundedEvent,
+    RfqCreatedEvent,
+    RfqQuoteRankedEvent,
+    RfqAwardedEvent,
+    RfqCancelledEvent,
+    RfqSettledEvent,
+    RfqLegRefundedEvent,
+    CounterOfferProposedEvent,
+    ProposeCounterOfferEvent,
+    CounterOfferAcceptedEvent,
+    CounterOfferRejectedEvent,
+    CounterOfferRefundedEvent,
+    FiatOfferCreatedEvent,
+    FiatPaymentDetailsStoredEvent,
+    FiatOfferLockedEvent,
+    FiatPaymentDetailsRevealedEvent,
+    FiatOfferPaidEvent,
+    FiatOfferReleasedEvent,
+    IdentityExchangeOpenedEvent,
+    TradeIdentityStoredEvent,
+    CounterpartyIdentityRevealedEvent,
+    PricePublishedEvent,
+    PriceBandSetEvent,
+    PriceTriggerArmedEvent,
+    PriceTriggerCheckedEvent,
+    DisputeOpenedEvent,
+    DisputeRuledEvent,
+    DisputeExpiredEvent,
+    ComputationAbortedEvent,
+    ComputationRetriedEvent,
+    ComputationStaleEvent,
+    RelayerBondUpdatedEvent,
+    RelayerSlashedEvent,
+    ProtocolFeeChargedEvent,
+    EvmBlockHeaderPostedEvent,
+    AccountMigratedEvent,
+}
+
+/// The raw payload of every `Program data:` line in `logs` that decodes
+/// as one of the program's events, with the event, in log order
+pub fn program_events(logs: &[String]) -> Vec<(Vec<u8>, ProgramEvent)> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix(PROGRAM_DATA))
+        .filter_map(|data| STANDARD.decode(data).ok())
+        .filter_map(|data| ProgramEvent::decode(&data).map(|event| (data, event)))
+        .collect()
+}
+
+/// An offer account's state
+pub enum OfferAccount {
+    Intrachain(IntraChainOffer),
+    Interchain(InterchainOffer),
+}
+
+impl OfferAccount {
+    /// Decodes a program-owned account, `None` for anything but an offer
+    pub fn decode(owner: &Pubkey, data: &[u8]) -> Option<Self> {
+        if *owner != PROGRAM_ID {
+            return None;
+        }
+        if let Ok(offer) = accounts::decode_intrachain_offer(data) {
+            Some(OfferAccount::Intrachain(offer))
+        } else {
+            accounts::decode_interchain_offer(data).ok().map(OfferAccount::Interchain)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{AccountSerialize, Event};
+    use confidential_exchange_client::program::{OfferKind, OfferStatus, VaultSide};
+
+    use super::*;
+
+    fn header() -> EventHeader {
+        EventHeader {
+            schema_version: 1,
+            offer_kind: OfferKind::Intrachain,
+            offer_id: 3,
+            offer: Pubkey::new_unique(),
+            maker: Pubkey::new_unique(),
+            computation_offset: 0,
+            status_before: Some(OfferStatus::Open),
+            status_after: Some(OfferStatus::Open),
+            slot: 5,
+            unix_timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn decodes_events_from_logs() {
+        let deposit = VaultDepositedEvent {
+            header: header(),
+            vault: Pubkey::new_unique(),
+            side: VaultSide::Seller,
+            amount: 40,
+        };
+        let logs = vec![
+            "Program log: Instruction: DepositSellerNative".to_string(),
+            format!("{PROGRAM_DATA}{}", STANDARD.encode(deposit.data())),
+            format!("{PROGRAM_DATA}{}", STANDARD.encode([0u8; 12])),
+        ];
+        let events = program_events(&logs);
+        assert_eq!(events.len(), 1);
+        let (data, event) = &events[0];
+        assert_eq!(*data, deposit.data());
+        assert_eq!(event.name(), "VaultDepositedEvent");
+        assert_eq!(event.header().offer_id, 3);
+        let ProgramEvent::VaultDepositedEvent(decoded) = event else {
+            panic!("decoded as {}", event.name());
+        };
+        assert_eq!(decoded.vault, deposit.vault);
+        assert_eq!(decoded.amount, 40);
+    }
+
+    #[test]
+    fn decodes_only_program_owned_offers() {
+        let offer = IntraChainOffer {
+            layout_version: 2,
+            id: 1,
+            token_a_offered_amount: 10,
+            token_b_wanted_amount: 20,
+            is_taker_native: true,
+            deadline: 100,
+            maker: Pubkey::new_unique(),
+            buyer: None,
+            status: OfferStatus::Open,
+            computation_offset: 0,
+            bump: 255,
+        };
+        let mut data = Vec::new();
+        offer.try_serialize(&mut data).unwrap();
+        assert!(matches!(
+            OfferAccount::decode(&PROGRAM_ID, &data),
+            Some(OfferAccount::Intrachain(decoded)) if decoded.token_b_wanted_amount == 20
+        ));
+        assert!(OfferAccount::decode(&Pubkey::default(), &data).is_none());
+        assert!(OfferAccount::decode(&PROGRAM_ID, &[0; 8]).is_none());
+    }
+}
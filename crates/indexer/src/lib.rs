@@ -0,0 +1,14 @@
+//! Offer and trade history for the confidential cross-chain exchange.
+//!
+//! A [`source`] yields the program's transactions, from a local validator or
+//! a replay file, as their logs plus the state of the accounts they touched.
+//! [`decode`] turns those into Anchor events and `IntraChainOffer`,
+//! `InterchainOffer` and vault snapshots, and [`store`] keeps them in SQLite
+//! behind a small query API over offers, fills and per-maker history.
+
+pub mod decode;
+pub mod source;
+pub mod store;
+
+pub use source::{AccountRecord, TransactionRecord};
+pub use store::Store;
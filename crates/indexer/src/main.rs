@@ -0,0 +1,184 @@
+//! `exchange-indexer`, which fills the exchange's SQLite history from a
+//! validator or replay files and answers queries over it.
+
+use std::{path::PathBuf, thread, time::Duration};
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use confidential_exchange_client::program::{OfferKind, OfferStatus};
+use exchange_indexer::{
+    source::{self, RpcSource},
+    store::{self, OfferFilter},
+    Store,
+};
+
+#[derive(Parser)]
+#[command(name = "exchange-indexer", version, about)]
+struct Cli {
+    /// SQLite database holding the history
+    #[arg(long, env = "EXCHANGE_INDEXER_DB", default_value = "exchange-history.db")]
+    db: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Index the transactions in replay files, one JSON record per line
+    Replay {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Follow the program's transactions on a validator
+    Follow {
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+        /// Also append every fetched transaction to this replay file
+        #[arg(long)]
+        record: Option<PathBuf>,
+        /// Index what the validator has and exit
+        #[arg(long)]
+        once: bool,
+    },
+    /// List offers with their latest status
+    Offers {
+        #[arg(long, value_parser = kind_arg)]
+        kind: Option<OfferKind>,
+        #[arg(long, value_parser = status_arg)]
+        status: Option<OfferStatus>,
+        #[arg(long)]
+        maker: Option<Pubkey>,
+    },
+    /// List swaps executed against one offer, or all of them
+    Fills {
+        #[arg(long)]
+        offer: Option<Pubkey>,
+    },
+    /// Every event about a maker's offers
+    History { maker: Pubkey },
+}
+
+fn kind_arg(name: &str) -> std::result::Result<OfferKind, String> {
+    store::parse_kind(name)
+        .ok_or_else(|| {
+            "expected Intrachain, Interchain, SolanaOrigin, Basket, Rfq, CounterOffer, Fiat, \
+             IdentityExchange or PriceTrigger"
+                .to_string()
+        })
+}
+
+fn status_arg(name: &str) -> std::result::Result<OfferStatus, String> {
+    store::parse_status(name).ok_or_else(|| {
+        "expected Pending, Open, Settled, Refunded, Cancelled, VerificationFailed or Disputed"
+            .to_string()
+    })
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let mut store = Store::open(&cli.db)?;
+    match cli.command {
+        Command::Replay { files } => {
+            for file in files {
+                let mut indexed = 0;
+                for record in source::read_replay(&file)? {
+                    indexed += usize::from(store.ingest(&record)?);
+                }
+                println!("{}: indexed {indexed} transactions", file.display());
+            }
+            Ok(())
+        }
+        Command::Follow {
+            url,
+            interval_ms,
+            record,
+            once,
+        } => {
+            let mut rpc = RpcSource::new(&url, store.last_signature()?.as_deref())?;
+            loop {
+                let records = rpc.poll()?;
+                if let Some(path) = &record {
+                    source::append_replay(path, &records)?;
+                }
+                let mut indexed = 0;
+                for record in &records {
+                    indexed += usize::from(store.ingest(record)?);
+                }
+                if indexed > 0 {
+                    println!("indexed {indexed} transactions");
+                }
+                if once {
+                    return Ok(());
+                }
+                thread::sleep(Duration::from_millis(interval_ms));
+            }
+        }
+        Command::Offers {
+            kind,
+            status,
+            maker,
+        } => {
+            for offer in store.offers(&OfferFilter {
+                kind,
+                status,
+                maker,
+            })? {
+                println!(
+                    "{:?} {} id={} maker={} status={:?} offered={} deadline={} fills={}{}",
+                    offer.kind,
+                    offer.address,
+                    offer.id,
+                    offer.maker,
+                    offer.status,
+                    or_dash(offer.token_a_offered_amount),
+                    or_dash(offer.deadline),
+                    offer.fills,
+                    offer
+                        .chain_id
+                        .map(|chain| format!(" chain={chain}"))
+                        .unwrap_or_default()
+                );
+            }
+            Ok(())
+        }
+        Command::Fills { offer } => {
+            for fill in store.fills(offer.as_ref())? {
+                println!(
+                    "{} {:?} offer={} buyer={} seller={} token_a={} token_b={} fee={} slot={}",
+                    fill.signature,
+                    fill.offer_kind,
+                    fill.offer,
+                    fill.buyer,
+                    fill.seller,
+                    fill.token_a_amount,
+                    fill.token_b_amount,
+                    fill.fee,
+                    fill.slot
+                );
+            }
+            Ok(())
+        }
+        Command::History { maker } => {
+            for event in store.maker_history(&maker)? {
+                println!(
+                    "{} {} {} {:?} offer_id={} status={}->{}",
+                    event.slot,
+                    event.signature,
+                    event.name,
+                    event.offer_kind,
+                    event.offer_id,
+                    or_dash(event.status_before.map(store::status_name)),
+                    or_dash(event.status_after.map(store::status_name))
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+fn or_dash<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| value.to_string())
+}
@@ -0,0 +1,251 @@
+//! Where indexed transactions come from: replay files, written as JSON
+//! lines, or a validator's RPC.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    str::FromStr,
+};
+
+use anchor_client::{
+    solana_client::{
+        rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+        rpc_request::RpcRequest,
+    },
+    solana_sdk::{account::Account, commitment_config::CommitmentConfig, signature::Signature},
+};
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Context, Result};
+use confidential_exchange_client::PROGRAM_ID;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// `getSignaturesForAddress` returns at most this many per page
+const SIGNATURE_PAGE: usize = 1000;
+
+/// A program transaction: its logs, and the accounts it touched as they were
+/// read after it ran
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub logs: Vec<String>,
+    #[serde(default)]
+    pub accounts: Vec<AccountRecord>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountRecord {
+    #[serde(with = "base58")]
+    pub address: Pubkey,
+    #[serde(with = "base58")]
+    pub owner: Pubkey,
+    pub lamports: u64,
+    #[serde(with = "base64_bytes")]
+    pub data: Vec<u8>,
+}
+
+impl AccountRecord {
+    pub fn new(address: Pubkey, account: Account) -> Self {
+        AccountRecord {
+            address,
+            owner: account.owner,
+            lamports: account.lamports,
+            data: account.data,
+        }
+    }
+}
+
+mod base58 {
+    use std::str::FromStr;
+
+    use anchor_lang::prelude::Pubkey;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(key: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(key)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        Pubkey::from_str(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+mod base64_bytes {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(data))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        STANDARD
+            .decode(String::deserialize(deserializer)?)
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Reads a replay file, one [`TransactionRecord`] per line, oldest first
+pub fn read_replay(path: &Path) -> Result<Vec<TransactionRecord>> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).with_context(|| {
+                format!("{}:{}: bad transaction record", path.display(), index + 1)
+            })
+        })
+        .collect()
+}
+
+/// Appends `records` to a replay file, creating it if needed
+pub fn append_replay(path: &Path, records: &[TransactionRecord]) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("opening {}", path.display()))?;
+    for record in records {
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
+    }
+    Ok(())
+}
+
+/// Follows the program's transactions over RPC
+pub struct RpcSource {
+    client: RpcClient,
+    /// Newest transaction already returned
+    until: Option<Signature>,
+}
+
+impl RpcSource {
+    /// Starts after `until`, or at the program's oldest retained transaction
+    pub fn new(url: &str, until: Option<&str>) -> Result<Self> {
+        Ok(RpcSource {
+            client: RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed()),
+            until: until.map(Signature::from_str).transpose()?,
+        })
+    }
+
+    /// Successful program transactions since the last poll, oldest first.
+    /// Account states are read when the transaction is fetched, so they can
+    /// be newer than its slot.
+    pub fn poll(&mut self) -> Result<Vec<TransactionRecord>> {
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self.client.get_signatures_for_address_with_config(
+                &PROGRAM_ID,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until: self.until,
+                    limit: Some(SIGNATURE_PAGE),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )?;
+            let full = page.len() == SIGNATURE_PAGE;
+            before = match page.last() {
+                Some(oldest) => Some(Signature::from_str(&oldest.signature)?),
+                None => None,
+            };
+            signatures.extend(page);
+            if !full {
+                break;
+            }
+        }
+
+        let mut records = Vec::new();
+        for status in signatures.iter().rev() {
+            if status.err.is_none() {
+                records.push(self.transaction(&status.signature)?);
+            }
+        }
+        if let Some(newest) = signatures.first() {
+            self.until = Some(Signature::from_str(&newest.signature)?);
+        }
+        Ok(records)
+    }
+
+    fn transaction(&self, signature: &str) -> Result<TransactionRecord> {
+        let transaction: Value = self.client.send(
+            RpcRequest::GetTransaction,
+            json!([
+                signature,
+                {
+                    "encoding": "json",
+                    "commitment": "confirmed",
+                    "maxSupportedTransactionVersion": 0,
+                },
+            ]),
+        )?;
+        let strings = |value: &Value| -> Vec<String> {
+            value
+                .as_array()
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|item| item.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let meta = &transaction["meta"];
+        let mut keys = strings(&transaction["transaction"]["message"]["accountKeys"]);
+        keys.extend(strings(&meta["loadedAddresses"]["writable"]));
+        keys.extend(strings(&meta["loadedAddresses"]["readonly"]));
+        let keys = keys
+            .iter()
+            .map(|key| Pubkey::from_str(key))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let accounts = self
+            .client
+            .get_multiple_accounts(&keys)?
+            .into_iter()
+            .zip(&keys)
+            .filter_map(|(account, address)| Some(AccountRecord::new(*address, account?)))
+            .collect();
+        Ok(TransactionRecord {
+            signature: signature.to_string(),
+            slot: transaction["slot"]
+                .as_u64()
+                .ok_or_else(|| anyhow!("transaction {signature} has no slot"))?,
+            logs: strings(&meta["logMessages"]),
+            accounts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_files_round_trip() {
+        let record = TransactionRecord {
+            signature: "5ig".to_string(),
+            slot: 9,
+            logs: vec!["Program log: Instruction: CancelOffer".to_string()],
+            accounts: vec![AccountRecord {
+                address: Pubkey::new_unique(),
+                owner: PROGRAM_ID,
+                lamports: 1_000,
+                data: vec![1, 2, 3],
+            }],
+        };
+        let path = std::env::temp_dir().join(format!("replay-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        append_replay(&path, std::slice::from_ref(&record)).unwrap();
+        append_replay(&path, std::slice::from_ref(&record)).unwrap();
+        let line = fs::read_to_string(&path).unwrap();
+        assert!(line.contains(&PROGRAM_ID.to_string()));
+        assert!(line.contains("\"AQID\""));
+        assert_eq!(read_replay(&path).unwrap(), vec![record.clone(), record]);
+        fs::remove_file(&path).unwrap();
+    }
+}
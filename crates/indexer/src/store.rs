@@ -0,0 +1,810 @@
+//! SQLite storage for indexed transactions and the queries over it.
+//!
+//! Events are kept verbatim alongside their header columns. `offers` tracks
+//! every offer's latest status from event headers and account snapshots,
+//! whichever is newer; amounts and deadlines come from the snapshots in
+//! `intrachain_offers` and `interchain_offers`. The `offer_status` view
+//! joins the three. SQLite integers are signed, so `u64` columns hold the
+//! value's bit pattern.
+
+use std::{path::Path, str::FromStr};
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use confidential_exchange_client::program::{OfferKind, OfferStatus};
+use rusqlite::{params, types::Type, Connection, OptionalExtension, Row};
+
+use crate::{
+    decode::{self, OfferAccount, ProgramEvent},
+    source::{AccountRecord, TransactionRecord},
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    seq INTEGER PRIMARY KEY,
+    signature TEXT NOT NULL UNIQUE,
+    slot INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS events (
+    seq INTEGER PRIMARY KEY,
+    signature TEXT NOT NULL REFERENCES transactions (signature),
+    name TEXT NOT NULL,
+    offer_kind TEXT NOT NULL,
+    offer_id INTEGER NOT NULL,
+    offer TEXT,
+    maker TEXT,
+    computation_offset INTEGER NOT NULL,
+    status_before TEXT,
+    status_after TEXT,
+    slot INTEGER NOT NULL,
+    unix_timestamp INTEGER NOT NULL,
+    data BLOB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS events_offer ON events (offer);
+CREATE INDEX IF NOT EXISTS events_maker ON events (maker);
+CREATE TABLE IF NOT EXISTS offers (
+    address TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    id INTEGER NOT NULL,
+    maker TEXT NOT NULL,
+    status TEXT NOT NULL,
+    computation_offset INTEGER NOT NULL,
+    created_slot INTEGER NOT NULL,
+    updated_slot INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS intrachain_offers (
+    address TEXT PRIMARY KEY,
+    token_a_offered_amount INTEGER NOT NULL,
+    token_b_wanted_amount INTEGER NOT NULL,
+    is_taker_native INTEGER NOT NULL,
+    deadline INTEGER NOT NULL,
+    slot INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS interchain_offers (
+    address TEXT PRIMARY KEY,
+    chain_id INTEGER NOT NULL,
+    token_a_offered_amount INTEGER NOT NULL,
+    token_b_wanted_amount BLOB NOT NULL,
+    is_taker_native INTEGER NOT NULL,
+    deadline INTEGER NOT NULL,
+    token_mapping TEXT NOT NULL,
+    relayer TEXT NOT NULL,
+    slashed INTEGER NOT NULL,
+    slot INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS vaults (
+    address TEXT PRIMARY KEY,
+    offer TEXT NOT NULL,
+    side TEXT NOT NULL,
+    deposited INTEGER NOT NULL,
+    lamports INTEGER,
+    slot INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS fills (
+    event INTEGER PRIMARY KEY REFERENCES events (seq),
+    signature TEXT NOT NULL,
+    offer TEXT NOT NULL,
+    offer_kind TEXT NOT NULL,
+    maker TEXT NOT NULL,
+    buyer TEXT NOT NULL,
+    seller TEXT NOT NULL,
+    token_a_amount INTEGER NOT NULL,
+    token_b_amount INTEGER NOT NULL,
+    fee INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    unix_timestamp INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS fills_offer ON fills (offer);
+CREATE VIEW IF NOT EXISTS offer_status AS
+SELECT
+    o.address,
+    o.kind,
+    o.id,
+    o.maker,
+    o.status,
+    COALESCE(i.token_a_offered_amount, x.token_a_offered_amount) AS token_a_offered_amount,
+    COALESCE(i.deadline, x.deadline) AS deadline,
+    x.chain_id,
+    (SELECT COUNT(*) FROM fills f WHERE f.offer = o.address) AS fills,
+    o.created_slot,
+    o.updated_slot
+FROM offers o
+LEFT JOIN intrachain_offers i ON i.address = o.address
+LEFT JOIN interchain_offers x ON x.address = o.address;
+";
+
+/// An offer's row in the `offer_status` view
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OfferRow {
+    pub address: Pubkey,
+    pub kind: OfferKind,
+    pub id: u64,
+    pub maker: Pubkey,
+    pub status: OfferStatus,
+    /// `None` until the offer's account has been indexed
+    pub token_a_offered_amount: Option<u64>,
+    pub deadline: Option<i64>,
+    /// Set for interchain offers
+    pub chain_id: Option<u64>,
+    pub fills: u64,
+    pub created_slot: u64,
+    pub updated_slot: u64,
+}
+
+/// A swap executed against an offer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FillRow {
+    pub signature: String,
+    pub offer: Pubkey,
+    pub offer_kind: OfferKind,
+    pub maker: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    /// Protocol fee withheld from `token_a_amount`, 0 for intrachain fills
+    pub fee: u64,
+    pub slot: u64,
+    pub unix_timestamp: i64,
+}
+
+/// An indexed event
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventRow {
+    pub signature: String,
+    pub name: String,
+    pub offer_kind: OfferKind,
+    pub offer_id: u64,
+    pub offer: Option<Pubkey>,
+    pub computation_offset: u64,
+    pub status_before: Option<OfferStatus>,
+    pub status_after: Option<OfferStatus>,
+    pub slot: u64,
+    pub unix_timestamp: i64,
+    /// The event as emitted, discriminator first
+    pub data: Vec<u8>,
+}
+
+impl EventRow {
+    pub fn event(&self) -> Option<ProgramEvent> {
+        ProgramEvent::decode(&self.data)
+    }
+}
+
+/// A vault an offer's funds were deposited to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultRow {
+    pub address: Pubkey,
+    pub offer: Pubkey,
+    pub side: String,
+    /// Sum of the deposits reported by `VaultDepositedEvent`s
+    pub deposited: u64,
+    /// Balance from the latest account snapshot, if any
+    pub lamports: Option<u64>,
+}
+
+/// Restricts [`Store::offers`]; `None` fields match everything
+#[derive(Clone, Debug, Default)]
+pub struct OfferFilter {
+    pub kind: Option<OfferKind>,
+    pub status: Option<OfferStatus>,
+    pub maker: Option<Pubkey>,
+}
+
+pub fn kind_name(kind: OfferKind) -> String {
+    format!("{kind:?}")
+}
+
+pub fn parse_kind(name: &str) -> Option<OfferKind> {
+    match name {
+        "Standalone" => Some(OfferKind::Standalone),
+        "Interchain" => Some(OfferKind::Interchain),
+        "Intrachain" => Some(OfferKind::Intrachain),
+        "SolanaOrigin" => Some(OfferKind::SolanaOrigin),
+        "Basket" => Some(OfferKind::Basket),
+        "Rfq" => Some(OfferKind::Rfq),
+        "CounterOffer" => Some(OfferKind::CounterOffer),
+        "Fiat" => Some(OfferKind::Fiat),
+        "IdentityExchange" => Some(OfferKind::IdentityExchange),
+        "PriceTrigger" => Some(OfferKind::PriceTrigger),
+        _ => None,
+    }
+}
+
+pub fn status_name(status: OfferStatus) -> String {
+    format!("{status:?}")
+}
+
+pub fn parse_status(name: &str) -> Option<OfferStatus> {
+    match name {
+        "Pending" => Some(OfferStatus::Pending),
+        "Open" => Some(OfferStatus::Open),
+        "Settled" => Some(OfferStatus::Settled),
+        "Refunded" => Some(OfferStatus::Refunded),
+        "Cancelled" => Some(OfferStatus::Cancelled),
+        "VerificationFailed" => Some(OfferStatus::VerificationFailed),
+        "Disputed" => Some(OfferStatus::Disputed),
+        _ => None,
+    }
+}
+
+fn int(value: u64) -> i64 {
+    value as i64
+}
+
+fn column<T>(
+    row: &Row,
+    index: usize,
+    parse: impl FnOnce(&str) -> Option<T>,
+) -> rusqlite::Result<T> {
+    let text: String = row.get(index)?;
+    parse(&text).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(
+            index,
+            Type::Text,
+            format!("unexpected value {text:?}").into(),
+        )
+    })
+}
+
+fn optional_column<T>(
+    row: &Row,
+    index: usize,
+    parse: impl FnOnce(&str) -> Option<T>,
+) -> rusqlite::Result<Option<T>> {
+    match row.get::<_, Option<String>>(index)? {
+        Some(_) => column(row, index, parse).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn pubkey(name: &str) -> Option<Pubkey> {
+    Pubkey::from_str(name).ok()
+}
+
+const OFFER_COLUMNS: &str = "address, kind, id, maker, status, token_a_offered_amount, deadline,
+    chain_id, fills, created_slot, updated_slot";
+
+fn offer_row(row: &Row) -> rusqlite::Result<OfferRow> {
+    Ok(OfferRow {
+        address: column(row, 0, pubkey)?,
+        kind: column(row, 1, parse_kind)?,
+        id: row.get::<_, i64>(2)? as u64,
+        maker: column(row, 3, pubkey)?,
+        status: column(row, 4, parse_status)?,
+        token_a_offered_amount: row.get::<_, Option<i64>>(5)?.map(|v| v as u64),
+        deadline: row.get(6)?,
+        chain_id: row.get::<_, Option<i64>>(7)?.map(|v| v as u64),
+        fills: row.get::<_, i64>(8)? as u64,
+        created_slot: row.get::<_, i64>(9)? as u64,
+        updated_slot: row.get::<_, i64>(10)? as u64,
+    })
+}
+
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Store { conn })
+    }
+
+    /// Indexes `record`'s events and account snapshots in one SQLite
+    /// transaction. Returns false when it was indexed before.
+    pub fn ingest(&mut self, record: &TransactionRecord) -> Result<bool> {
+        let tx = self.conn.transaction()?;
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO transactions (signature, slot) VALUES (?1, ?2)",
+            params![record.signature, int(record.slot)],
+        )?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+        for (data, event) in decode::program_events(&record.logs) {
+            index_event(&tx, &record.signature, &data, &event)?;
+        }
+        for account in &record.accounts {
+            index_account(&tx, record.slot, account)?;
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// The newest indexed transaction, where following should resume
+    pub fn last_signature(&self) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT signature FROM transactions ORDER BY slot DESC, seq DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Offers matching `filter`, oldest first
+    pub fn offers(&self, filter: &OfferFilter) -> Result<Vec<OfferRow>> {
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT {OFFER_COLUMNS} FROM offer_status
+             WHERE (?1 IS NULL OR kind = ?1)
+               AND (?2 IS NULL OR status = ?2)
+               AND (?3 IS NULL OR maker = ?3)
+             ORDER BY created_slot, address"
+        ))?;
+        let rows = statement.query_map(
+            params![
+                filter.kind.map(kind_name),
+                filter.status.map(status_name),
+                filter.maker.map(|maker| maker.to_string()),
+            ],
+            offer_row,
+        )?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn offer(&self, address: &Pubkey) -> Result<Option<OfferRow>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("SELECT {OFFER_COLUMNS} FROM offer_status WHERE address = ?1"),
+                params![address.to_string()],
+                offer_row,
+            )
+            .optional()?)
+    }
+
+    /// Fills of `offer`, or of every offer, oldest first
+    pub fn fills(&self, offer: Option<&Pubkey>) -> Result<Vec<FillRow>> {
+        let mut statement = self.conn.prepare(
+            "SELECT signature, offer, offer_kind, maker, buyer, seller, token_a_amount,
+                    token_b_amount, fee, slot, unix_timestamp
+             FROM fills
+             WHERE ?1 IS NULL OR offer = ?1
+             ORDER BY event",
+        )?;
+        let rows = statement.query_map(params![offer.map(|offer| offer.to_string())], |row| {
+            Ok(FillRow {
+                signature: row.get(0)?,
+                offer: column(row, 1, pubkey)?,
+                offer_kind: column(row, 2, parse_kind)?,
+                maker: column(row, 3, pubkey)?,
+                buyer: column(row, 4, pubkey)?,
+                seller: column(row, 5, pubkey)?,
+                token_a_amount: row.get::<_, i64>(6)? as u64,
+                token_b_amount: row.get::<_, i64>(7)? as u64,
+                fee: row.get::<_, i64>(8)? as u64,
+                slot: row.get::<_, i64>(9)? as u64,
+                unix_timestamp: row.get(10)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Every event about `maker`'s offers, oldest first
+    pub fn maker_history(&self, maker: &Pubkey) -> Result<Vec<EventRow>> {
+        let mut statement = self.conn.prepare(
+            "SELECT signature, name, offer_kind, offer_id, offer, computation_offset,
+                    status_before, status_after, slot, unix_timestamp, data
+             FROM events
+             WHERE maker = ?1
+             ORDER BY seq",
+        )?;
+        let rows = statement.query_map(params![maker.to_string()], |row| {
+            Ok(EventRow {
+                signature: row.get(0)?,
+                name: row.get(1)?,
+                offer_kind: column(row, 2, parse_kind)?,
+                offer_id: row.get::<_, i64>(3)? as u64,
+                offer: optional_column(row, 4, pubkey)?,
+                computation_offset: row.get::<_, i64>(5)? as u64,
+                status_before: optional_column(row, 6, parse_status)?,
+                status_after: optional_column(row, 7, parse_status)?,
+                slot: row.get::<_, i64>(8)? as u64,
+                unix_timestamp: row.get(9)?,
+                data: row.get(10)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Vaults `offer`'s funds were deposited to
+    pub fn vaults(&self, offer: &Pubkey) -> Result<Vec<VaultRow>> {
+        let mut statement = self.conn.prepare(
+            "SELECT address, offer, side, deposited, lamports FROM vaults
+             WHERE offer = ?1 ORDER BY address",
+        )?;
+        let rows = statement.query_map(params![offer.to_string()], |row| {
+            Ok(VaultRow {
+                address: column(row, 0, pubkey)?,
+                offer: column(row, 1, pubkey)?,
+                side: row.get(2)?,
+                deposited: row.get::<_, i64>(3)? as u64,
+                lamports: row.get::<_, Option<i64>>(4)?.map(|v| v as u64),
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
+
+/// An offer's status as of `slot`, from an event header or its account
+struct OfferUpdate<'a> {
+    address: &'a Pubkey,
+    kind: OfferKind,
+    id: u64,
+    maker: &'a Pubkey,
+    status: OfferStatus,
+    /// 0 keeps the offset already recorded
+    computation_offset: u64,
+    slot: u64,
+}
+
+impl OfferUpdate<'_> {
+    /// Records the update unless a newer slot already did
+    fn apply(&self, conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute(
+            "INSERT INTO offers
+                 (address, kind, id, maker, status, computation_offset, created_slot,
+                  updated_slot)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+             ON CONFLICT (address) DO UPDATE SET
+                 status = excluded.status,
+                 computation_offset = CASE excluded.computation_offset
+                     WHEN 0 THEN offers.computation_offset
+                     ELSE excluded.computation_offset
+                 END,
+                 updated_slot = excluded.updated_slot
+             WHERE excluded.updated_slot >= offers.updated_slot",
+            params![
+                self.address.to_string(),
+                kind_name(self.kind),
+                int(self.id),
+                self.maker.to_string(),
+                status_name(self.status),
+                int(self.computation_offset),
+                int(self.slot),
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+fn index_event(
+    conn: &Connection,
+    signature: &str,
+    data: &[u8],
+    event: &ProgramEvent,
+) -> rusqlite::Result<()> {
+    let header = event.header();
+    let offer = (header.offer != Pubkey::default()).then_some(header.offer);
+    conn.execute(
+        "INSERT INTO events
+             (signature, name, offer_kind, offer_id, offer, maker, computation_offset,
+              status_before, status_after, slot, unix_timestamp, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            signature,
+            event.name(),
+            kind_name(header.offer_kind),
+            int(header.offer_id),
+            offer.map(|offer| offer.to_string()),
+            offer.map(|_| header.maker.to_string()),
+            int(header.computation_offset),
+            header.status_before.map(status_name),
+            header.status_after.map(status_name),
+            int(header.slot),
+            header.unix_timestamp,
+            data,
+        ],
+    )?;
+    let seq = conn.last_insert_rowid();
+
+    let Some(offer) = offer else {
+        return Ok(());
+    };
+    if let Some(status) = header.status_after {
+        OfferUpdate {
+            address: &offer,
+            kind: header.offer_kind,
+            id: header.offer_id,
+            maker: &header.maker,
+            status,
+            computation_offset: header.computation_offset,
+            slot: header.slot,
+        }
+        .apply(conn)?;
+    }
+
+    let fill = |buyer: &Pubkey, seller: &Pubkey, token_a: u64, token_b: u64, fee: u64| {
+        conn.execute(
+            "INSERT INTO fills
+                 (event, signature, offer, offer_kind, maker, buyer, seller, token_a_amount,
+                  token_b_amount, fee, slot, unix_timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                seq,
+                signature,
+                offer.to_string(),
+                kind_name(header.offer_kind),
+                header.maker.to_string(),
+                buyer.to_string(),
+                seller.to_string(),
+                int(token_a),
+                int(token_b),
+                int(fee),
+                int(header.slot),
+                header.unix_timestamp,
+            ],
+        )
+    };
+    match event {
+        ProgramEvent::IntrachainSwapExecutedEvent(swap) => {
+            fill(&swap.buyer, &swap.seller, swap.token_a_amount, swap.token_b_amount, 0)?;
+        }
+        ProgramEvent::InterchainSwapExecutedEvent(swap) => {
+            fill(
+                &swap.buyer,
+                &swap.seller,
+                swap.token_a_amount,
+                swap.token_b_amount,
+                swap.fee,
+            )?;
+        }
+        // The winner sold the RFQ's size for its price
+        ProgramEvent::RfqSettledEvent(settled) => {
+            fill(&settled.taker, &settled.winner, settled.size, settled.price, 0)?;
+        }
+        ProgramEvent::VaultDepositedEvent(deposit) => {
+            conn.execute(
+                "INSERT INTO vaults (address, offer, side, deposited, slot)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT (address) DO UPDATE SET deposited = deposited + excluded.deposited",
+                params![
+                    deposit.vault.to_string(),
+                    offer.to_string(),
+                    format!("{:?}", deposit.side),
+                    int(deposit.amount),
+                    int(header.slot),
+                ],
+            )?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn index_account(conn: &Connection, slot: u64, account: &AccountRecord) -> rusqlite::Result<()> {
+    let address = account.address.to_string();
+    match OfferAccount::decode(&account.owner, &account.data) {
+        Some(OfferAccount::Intrachain(offer)) => {
+            OfferUpdate {
+                address: &account.address,
+                kind: OfferKind::Intrachain,
+                id: offer.id,
+                maker: &offer.maker,
+                status: offer.status,
+                computation_offset: offer.computation_offset,
+                slot,
+            }
+            .apply(conn)?;
+            conn.execute(
+                "INSERT OR REPLACE INTO intrachain_offers
+                     (address, token_a_offered_amount, token_b_wanted_amount, is_taker_native,
+                      deadline, slot)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    address,
+                    int(offer.token_a_offered_amount),
+                    int(offer.token_b_wanted_amount),
+                    offer.is_taker_native,
+                    offer.deadline,
+                    int(slot),
+                ],
+            )?;
+        }
+        Some(OfferAccount::Interchain(offer)) => {
+            OfferUpdate {
+                address: &account.address,
+                kind: OfferKind::Interchain,
+                id: offer.id,
+                maker: &offer.maker,
+                status: offer.status,
+                computation_offset: offer.computation_offset,
+                slot,
+            }
+            .apply(conn)?;
+            conn.execute(
+                "INSERT OR REPLACE INTO interchain_offers
+                     (address, chain_id, token_a_offered_amount, token_b_wanted_amount,
+                      is_taker_native, deadline, token_mapping, relayer, slashed, slot)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    address,
+                    int(offer.chain_id),
+                    int(offer.token_a_offered_amount),
+                    offer.token_b_wanted_amount.as_slice(),
+                    offer.is_taker_native,
+                    offer.deadline,
+                    offer.token_mapping.to_string(),
+                    offer.relayer.to_string(),
+                    offer.slashed,
+                    int(slot),
+                ],
+            )?;
+        }
+        None => {
+            conn.execute(
+                "UPDATE vaults SET lamports = ?2, slot = ?3 WHERE address = ?1 AND slot <= ?3",
+                params![address, int(account.lamports), int(slot)],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{AccountSerialize, Event};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use confidential_exchange_client::{
+        pda,
+        program::{
+            EventHeader,
+            IntraChainOffer,
+            IntrachainOfferCreatedEvent,
+            IntrachainSwapExecutedEvent,
+        },
+        PROGRAM_ID,
+    };
+
+    use super::*;
+
+    fn header(maker: Pubkey, before: Option<OfferStatus>, after: OfferStatus) -> EventHeader {
+        EventHeader {
+            schema_version: 1,
+            offer_kind: OfferKind::Intrachain,
+            offer_id: 1,
+            offer: pda::intrachain_offer(&maker, 1),
+            maker,
+            computation_offset: 0,
+            status_before: before,
+            status_after: Some(after),
+            slot: 10,
+            unix_timestamp: 1_700_000_000,
+        }
+    }
+
+    fn record(signature: &str, slot: u64, events: &[Vec<u8>]) -> TransactionRecord {
+        TransactionRecord {
+            signature: signature.to_string(),
+            slot,
+            logs: events
+                .iter()
+                .map(|data| format!("Program data: {}", STANDARD.encode(data)))
+                .collect(),
+            accounts: vec![],
+        }
+    }
+
+    #[test]
+    fn event_status_yields_to_newer_account_snapshot() {
+        let maker = Pubkey::new_unique();
+        let created = IntrachainOfferCreatedEvent {
+            header: header(maker, None, OfferStatus::Pending),
+            token_a_offered_amount: 5,
+            token_b_wanted_amount: 7,
+            is_taker_native: true,
+            deadline: 99,
+        };
+        let mut store = Store::open_in_memory().unwrap();
+        assert!(store.ingest(&record("a", 10, &[created.data()])).unwrap());
+        assert!(!store.ingest(&record("a", 10, &[created.data()])).unwrap());
+
+        let address = pda::intrachain_offer(&maker, 1);
+        let offer = store.offer(&address).unwrap().unwrap();
+        assert_eq!(offer.status, OfferStatus::Pending);
+        assert_eq!(offer.token_a_offered_amount, None);
+
+        let mut data = Vec::new();
+        IntraChainOffer {
+            layout_version: 2,
+            id: 1,
+            token_a_offered_amount: 5,
+            token_b_wanted_amount: 7,
+            is_taker_native: true,
+            deadline: 99,
+            maker,
+            buyer: None,
+            status: OfferStatus::Open,
+            computation_offset: 42,
+            bump: 255,
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        let mut snapshot = record("b", 11, &[]);
+        snapshot.accounts.push(AccountRecord {
+            address,
+            owner: PROGRAM_ID,
+            lamports: 1,
+            data,
+        });
+        store.ingest(&snapshot).unwrap();
+
+        let offer = store.offer(&address).unwrap().unwrap();
+        assert_eq!(offer.status, OfferStatus::Open);
+        assert_eq!(offer.token_a_offered_amount, Some(5));
+        assert_eq!(offer.deadline, Some(99));
+        assert_eq!(offer.updated_slot, 11);
+
+        // An event from an older slot does not roll the status back
+        let stale = IntrachainOfferCreatedEvent {
+            header: header(maker, None, OfferStatus::Pending),
+            token_a_offered_amount: 5,
+            token_b_wanted_amount: 7,
+            is_taker_native: true,
+            deadline: 99,
+        };
+        store.ingest(&record("c", 12, &[stale.data()])).unwrap();
+        assert_eq!(store.offer(&address).unwrap().unwrap().status, OfferStatus::Open);
+        assert_eq!(store.last_signature().unwrap().as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn queries_fills_and_maker_history() {
+        let maker = Pubkey::new_unique();
+        let buyer = Pubkey::new_unique();
+        let mut swap_header = header(maker, Some(OfferStatus::Open), OfferStatus::Settled);
+        swap_header.slot = 20;
+        let swap = IntrachainSwapExecutedEvent {
+            header: swap_header,
+            buyer,
+            seller: maker,
+            token_a_amount: 5,
+            token_b_amount: 7,
+        };
+        let created = IntrachainOfferCreatedEvent {
+            header: header(maker, None, OfferStatus::Open),
+            token_a_offered_amount: 5,
+            token_b_wanted_amount: 7,
+            is_taker_native: true,
+            deadline: 99,
+        };
+        let mut store = Store::open_in_memory().unwrap();
+        store.ingest(&record("a", 10, &[created.data()])).unwrap();
+        store.ingest(&record("b", 20, &[swap.data()])).unwrap();
+
+        let fills = store.fills(None).unwrap();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].buyer, buyer);
+        assert_eq!(fills[0].token_b_amount, 7);
+        assert_eq!(fills[0].signature, "b");
+        assert!(store.fills(Some(&Pubkey::new_unique())).unwrap().is_empty());
+
+        let settled = store
+            .offers(&OfferFilter {
+                status: Some(OfferStatus::Settled),
+                ..OfferFilter::default()
+            })
+            .unwrap();
+        assert_eq!(settled.len(), 1);
+        assert_eq!(settled[0].fills, 1);
+
+        let history = store.maker_history(&maker).unwrap();
+        let names: Vec<_> = history.iter().map(|event| event.name.as_str()).collect();
+        assert_eq!(names, ["IntrachainOfferCreatedEvent", "IntrachainSwapExecutedEvent"]);
+        assert!(matches!(
+            history[1].event(),
+            Some(ProgramEvent::IntrachainSwapExecutedEvent(event)) if event.buyer == buyer
+        ));
+        assert!(store.maker_history(&buyer).unwrap().is_empty());
+    }
+}
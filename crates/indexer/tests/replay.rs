@@ -0,0 +1,274 @@
+//! Indexes a replay file covering an intrachain offer from creation to
+//! settlement next to an open interchain offer, then queries it.
+
+use std::fs;
+
+use anchor_lang::{prelude::Pubkey, AccountSerialize, Event};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use confidential_exchange_client::{
+    pda,
+    program::{
+        EventHeader,
+        InterchainOffer,
+        InterchainOfferCreatedEvent,
+        IntraChainOffer,
+        IntrachainOfferCreatedEvent,
+        IntrachainSwapExecutedEvent,
+        OfferKind,
+        OfferStatus,
+        VaultDepositedEvent,
+        VaultSide,
+    },
+    PROGRAM_ID,
+};
+use exchange_indexer::{
+    source::{append_replay, read_replay},
+    store::OfferFilter,
+    AccountRecord,
+    Store,
+    TransactionRecord,
+};
+
+const OFFER_ID: u64 = 4;
+
+fn header(
+    kind: OfferKind,
+    offer: Pubkey,
+    maker: Pubkey,
+    before: Option<OfferStatus>,
+    after: OfferStatus,
+    slot: u64,
+) -> EventHeader {
+    EventHeader {
+        schema_version: 1,
+        offer_kind: kind,
+        offer_id: OFFER_ID,
+        offer,
+        maker,
+        computation_offset: 0,
+        status_before: before,
+        status_after: Some(after),
+        slot,
+        unix_timestamp: 1_700_000_000 + slot as i64,
+    }
+}
+
+fn transaction(slot: u64, events: &[Vec<u8>], accounts: Vec<AccountRecord>) -> TransactionRecord {
+    let mut logs = vec![format!("Program {PROGRAM_ID} invoke [1]")];
+    logs.extend(
+        events
+            .iter()
+            .map(|data| format!("Program data: {}", STANDARD.encode(data))),
+    );
+    logs.push(format!("Program {PROGRAM_ID} success"));
+    TransactionRecord {
+        signature: format!("sig{slot}"),
+        slot,
+        logs,
+        accounts,
+    }
+}
+
+fn program_account(address: Pubkey, state: &impl AccountSerialize) -> AccountRecord {
+    let mut data = Vec::new();
+    state.try_serialize(&mut data).unwrap();
+    AccountRecord {
+        address,
+        owner: PROGRAM_ID,
+        lamports: 2_000_000,
+        data,
+    }
+}
+
+fn intrachain_offer(maker: Pubkey, status: OfferStatus) -> IntraChainOffer {
+    IntraChainOffer {
+        layout_version: 2,
+        id: OFFER_ID,
+        token_a_offered_amount: 1_000,
+        token_b_wanted_amount: 2_000,
+        is_taker_native: true,
+        deadline: 1_800_000_000,
+        maker,
+        buyer: None,
+        status,
+        computation_offset: 77,
+        bump: 255,
+    }
+}
+
+fn replay(maker: Pubkey, buyer: Pubkey) -> Vec<TransactionRecord> {
+    let offer = pda::intrachain_offer(&maker, OFFER_ID);
+    let vault = pda::seller_vault(&maker, OFFER_ID);
+    let interchain = pda::interchain_offer(&maker, OFFER_ID);
+    let created = IntrachainOfferCreatedEvent {
+        header: header(OfferKind::Intrachain, offer, maker, None, OfferStatus::Pending, 10),
+        token_a_offered_amount: 1_000,
+        token_b_wanted_amount: 2_000,
+        is_taker_native: true,
+        deadline: 1_800_000_000,
+    };
+    let deposited = VaultDepositedEvent {
+        header: header(
+            OfferKind::Intrachain,
+            offer,
+            maker,
+            Some(OfferStatus::Open),
+            OfferStatus::Open,
+            12,
+        ),
+        vault,
+        side: VaultSide::Seller,
+        amount: 1_000,
+    };
+    let swapped = IntrachainSwapExecutedEvent {
+        header: header(
+            OfferKind::Intrachain,
+            offer,
+            maker,
+            Some(OfferStatus::Open),
+            OfferStatus::Settled,
+            14,
+        ),
+        buyer,
+        seller: maker,
+        token_a_amount: 1_000,
+        token_b_amount: 2_000,
+    };
+    let interchain_created = InterchainOfferCreatedEvent {
+        header: header(OfferKind::Interchain, interchain, maker, None, OfferStatus::Open, 15),
+        chain_id: 1,
+        token_a_offered_amount: 500,
+        token_b_wanted_amount: [0; 32],
+        token_mapping: Pubkey::new_unique(),
+        relayer: Pubkey::default(),
+        deadline: 1_800_000_000,
+    };
+    let vault_account = |lamports| AccountRecord {
+        address: vault,
+        owner: Pubkey::default(),
+        lamports,
+        data: vec![],
+    };
+
+    vec![
+        transaction(
+            10,
+            &[created.data()],
+            vec![program_account(offer, &intrachain_offer(maker, OfferStatus::Pending))],
+        ),
+        transaction(
+            12,
+            &[deposited.data()],
+            vec![
+                program_account(offer, &intrachain_offer(maker, OfferStatus::Open)),
+                vault_account(1_000),
+            ],
+        ),
+        transaction(
+            14,
+            &[swapped.data()],
+            vec![
+                program_account(offer, &intrachain_offer(maker, OfferStatus::Settled)),
+                vault_account(0),
+            ],
+        ),
+        transaction(
+            15,
+            &[interchain_created.data()],
+            vec![program_account(
+                interchain,
+                &InterchainOffer {
+                    layout_version: 2,
+                    id: OFFER_ID,
+                    token_a_offered_amount: 500,
+                    token_b_wanted_amount: [0; 32],
+                    is_taker_native: true,
+                    chain_id: 1,
+                    deadline: 1_800_000_000,
+                    token_mapping: interchain_created.token_mapping,
+                    maker,
+                    buyer: None,
+                    relayer: Pubkey::default(),
+                    slashed: false,
+                    status: OfferStatus::Open,
+                    computation_offset: 0,
+                    bump: 255,
+                },
+            )],
+        ),
+    ]
+}
+
+#[test]
+fn indexes_offer_lifecycle_from_replay_file() {
+    let maker = Pubkey::new_unique();
+    let buyer = Pubkey::new_unique();
+    let path = std::env::temp_dir().join(format!("indexer-replay-{}.jsonl", std::process::id()));
+    let _ = fs::remove_file(&path);
+    append_replay(&path, &replay(maker, buyer)).unwrap();
+
+    let mut store = Store::open_in_memory().unwrap();
+    let records = read_replay(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    for record in &records {
+        assert!(store.ingest(record).unwrap());
+    }
+    for record in &records {
+        assert!(!store.ingest(record).unwrap());
+    }
+    assert_eq!(store.last_signature().unwrap().as_deref(), Some("sig15"));
+
+    let offers = store
+        .offers(&OfferFilter {
+            maker: Some(maker),
+            ..OfferFilter::default()
+        })
+        .unwrap();
+    assert_eq!(offers.len(), 2);
+    let intrachain = &offers[0];
+    assert_eq!(intrachain.address, pda::intrachain_offer(&maker, OFFER_ID));
+    assert_eq!(intrachain.kind, OfferKind::Intrachain);
+    assert_eq!(intrachain.status, OfferStatus::Settled);
+    assert_eq!(intrachain.token_a_offered_amount, Some(1_000));
+    assert_eq!(intrachain.fills, 1);
+    assert_eq!(intrachain.created_slot, 10);
+    assert_eq!(offers[1].kind, OfferKind::Interchain);
+    assert_eq!(offers[1].chain_id, Some(1));
+
+    let open = store
+        .offers(&OfferFilter {
+            status: Some(OfferStatus::Open),
+            ..OfferFilter::default()
+        })
+        .unwrap();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].address, pda::interchain_offer(&maker, OFFER_ID));
+
+    let fills = store.fills(Some(&intrachain.address)).unwrap();
+    assert_eq!(fills.len(), 1);
+    assert_eq!((fills[0].buyer, fills[0].seller), (buyer, maker));
+    assert_eq!((fills[0].token_a_amount, fills[0].token_b_amount), (1_000, 2_000));
+    assert_eq!(fills[0].signature, "sig14");
+
+    let vaults = store.vaults(&intrachain.address).unwrap();
+    assert_eq!(vaults.len(), 1);
+    assert_eq!(vaults[0].address, pda::seller_vault(&maker, OFFER_ID));
+    assert_eq!(vaults[0].deposited, 1_000);
+    assert_eq!(vaults[0].lamports, Some(0));
+
+    let history: Vec<_> = store
+        .maker_history(&maker)
+        .unwrap()
+        .into_iter()
+        .map(|event| (event.slot, event.name, event.status_after))
+        .collect();
+    assert_eq!(
+        history,
+        [
+            (10, "IntrachainOfferCreatedEvent".to_string(), Some(OfferStatus::Pending)),
+            (12, "VaultDepositedEvent".to_string(), Some(OfferStatus::Open)),
+            (14, "IntrachainSwapExecutedEvent".to_string(), Some(OfferStatus::Settled)),
+            (15, "InterchainOfferCreatedEvent".to_string(), Some(OfferStatus::Open)),
+        ]
+    );
+}
@@ -0,0 +1,149 @@
+//! Plaintext models of the `encrypted-ixs` circuits.
+//!
+//! Each model takes the decrypted input fields in declaration order and
+//! returns the output fields the circuit re-encrypts, so a callback sees the
+//! same plaintext it would from the cluster.
+
+use confidential_cross_chain_exchange::ComputationOperation;
+
+/// Who a circuit's output is encrypted to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Owner {
+    /// `Enc<Shared, _>`, the client that supplied the input
+    Shared,
+    /// A tuple of `Enc<Shared, _>`, each with its field count and which of
+    /// the circuit's `Shared` inputs, in order, it is encrypted to
+    SharedTuple(&'static [(usize, usize)]),
+    /// `Enc<Mxe, _>`, only the MXE can decrypt it
+    Mxe,
+    /// Plaintext outputs of `.reveal()`, with their byte widths
+    Revealed(&'static [usize]),
+}
+
+/// Every circuit the program queues
+pub const OPERATIONS: [ComputationOperation; 21] = [
+    ComputationOperation::AddTogether,
+    ComputationOperation::RelayOfferClone,
+    ComputationOperation::ConfidentialDepositNative,
+    ComputationOperation::InterchainOriginEvmDepositSellerSpl,
+    ComputationOperation::FinalizeInterchainOriginEvmOffer,
+    ComputationOperation::DepositSellerNative,
+    ComputationOperation::DepositSellerSpl,
+    ComputationOperation::FinalizeIntrachainOffer,
+    ComputationOperation::StoreEvmPayoutAddress,
+    ComputationOperation::CreateBasketOffer,
+    ComputationOperation::FinalizeBasketOffer,
+    ComputationOperation::RankRfqQuote,
+    ComputationOperation::SelectRfqQuote,
+    ComputationOperation::ProposeCounterOffer,
+    ComputationOperation::StoreFiatPaymentDetails,
+    ComputationOperation::RevealFiatPaymentDetails,
+    ComputationOperation::StoreTradeIdentity,
+    ComputationOperation::RevealTradeIdentities,
+    ComputationOperation::StorePriceTrigger,
+    ComputationOperation::CheckPriceTrigger,
+    ComputationOperation::RevealEvmPayoutAddress,
+];
+
+pub fn operation(comp_def_offset: u32) -> Option<ComputationOperation> {
+    OPERATIONS
+        .into_iter()
+        .find(|operation| operation.comp_def_offset() == comp_def_offset)
+}
+
+pub fn output_owner(operation: ComputationOperation) -> Owner {
+    match operation {
+        ComputationOperation::StoreEvmPayoutAddress
+        | ComputationOperation::RankRfqQuote
+        | ComputationOperation::StoreFiatPaymentDetails
+        | ComputationOperation::StoreTradeIdentity
+        | ComputationOperation::StorePriceTrigger => Owner::Mxe,
+        // `(buyer.from_arcis(seller_identity), seller.from_arcis(buyer_identity))`
+        ComputationOperation::RevealTradeIdentities => Owner::SharedTuple(&[(1, 4), (0, 4)]),
+        // `(best.price.reveal(), best.index.reveal())`
+        ComputationOperation::SelectRfqQuote => Owner::Revealed(&[8, 1]),
+        // `crossed.reveal()`
+        ComputationOperation::CheckPriceTrigger => Owner::Revealed(&[1]),
+        _ => Owner::Shared,
+    }
+}
+
+/// Runs `operation`'s circuit on its decrypted `inputs`
+pub fn evaluate(operation: ComputationOperation, inputs: &[u128]) -> Vec<u128> {
+    match operation {
+        // `input.v1 as u16 + input.v2 as u16`
+        ComputationOperation::AddTogether => {
+            vec![inputs[0] as u8 as u128 + inputs[1] as u8 as u128]
+        }
+        // `EvmPayoutAddress { hi, lo }` re-encrypted to the MXE, then to the
+        // taker, unchanged
+        ComputationOperation::StoreEvmPayoutAddress
+        | ComputationOperation::RevealEvmPayoutAddress => vec![inputs[0], inputs[1] as u32 as u128],
+        // `FiatPaymentDetails` re-encrypted to the MXE, then to the buyer,
+        // unchanged
+        ComputationOperation::StoreFiatPaymentDetails
+        | ComputationOperation::RevealFiatPaymentDetails => inputs.to_vec(),
+        // A `TradeIdentity` re-encrypted to the MXE unchanged; then the
+        // seller's and the buyer's, each for the other party
+        ComputationOperation::StoreTradeIdentity
+        | ComputationOperation::RevealTradeIdentities => inputs.to_vec(),
+        // The quote (price, identity) at `index` replaces the best (price,
+        // identity, index) if it is the first or strictly cheaper
+        ComputationOperation::RankRfqQuote => {
+            let index = inputs[5] as u8;
+            if index == 0 || (inputs[0] as u64) < (inputs[2] as u64) {
+                vec![inputs[0] as u64 as u128, inputs[1] as u64 as u128, u128::from(index)]
+            } else {
+                vec![inputs[2] as u64 as u128, inputs[3] as u64 as u128, inputs[4] as u8 as u128]
+            }
+        }
+        // Reveals the best price and index, never the identity
+        ComputationOperation::SelectRfqQuote => {
+            vec![inputs[0] as u64 as u128, inputs[2] as u8 as u128]
+        }
+        // `PriceTrigger { threshold, above }` re-encrypted to the MXE unchanged
+        ComputationOperation::StorePriceTrigger => {
+            vec![inputs[0] as u64 as u128, u128::from(inputs[1] != 0)]
+        }
+        // Whether the price reached the threshold from the trigger's side
+        ComputationOperation::CheckPriceTrigger => {
+            let (threshold, above, price) = (inputs[0] as u64, inputs[1] != 0, inputs[2] as u64);
+            let crossed = if above { price >= threshold } else { price <= threshold };
+            vec![u128::from(crossed)]
+        }
+        // Identity hashes are acknowledged and deposit amounts processed as is
+        _ => vec![inputs[0] as u64 as u128],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_every_comp_def_offset() {
+        for operation in OPERATIONS {
+            assert_eq!(self::operation(operation.comp_def_offset()), Some(operation));
+        }
+        assert_eq!(self::operation(0), None);
+    }
+
+    #[test]
+    fn add_together_widens_to_u16() {
+        assert_eq!(evaluate(ComputationOperation::AddTogether, &[255, 255]), vec![510]);
+    }
+
+    #[test]
+    fn rank_rfq_quote_keeps_the_earlier_of_equal_prices() {
+        let rank = |quote: [u128; 2], best: [u128; 3], index| {
+            evaluate(
+                ComputationOperation::RankRfqQuote,
+                &[quote[0], quote[1], best[0], best[1], best[2], index],
+            )
+        };
+        // The zeroed state never beats the first quote
+        assert_eq!(rank([100, 7], [0, 0, 0], 0), vec![100, 7, 0]);
+        assert_eq!(rank([100, 8], [100, 7, 0], 1), vec![100, 7, 0]);
+        assert_eq!(rank([99, 8], [100, 7, 0], 1), vec![99, 8, 1]);
+    }
+}
@@ -0,0 +1,655 @@
+//! A `solana-program-test` bank running the exchange natively next to the
+//! mock Arcium program, and the stand-in cluster that answers its queued
+//! computations.
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{entrypoint::ProgramResult, instruction::Instruction, sysvar::clock::Clock},
+    AccountDeserialize,
+    InstructionData,
+    Owner as _,
+};
+use anchor_spl::{
+    associated_token::{
+        get_associated_token_address,
+        spl_associated_token_account::instruction::create_associated_token_account,
+    },
+    token::{
+        spl_token::{self, state::Mint},
+        TokenAccount,
+    },
+};
+use arcium_client::{
+    idl::arcium::{
+        client::args::{FinalizeComputation, QueueComputation},
+        types::{Argument, CallbackInstruction},
+    },
+    ARCIUM_PROGRAM_ID,
+};
+use confidential_cross_chain_exchange::{
+    instruction::SetChainConfig,
+    versioning::VersionedAccount,
+    ComputationOperation,
+    ComputationTimeout,
+    IntraChainOffer,
+    OfferStatus,
+    PendingComputation,
+};
+use confidential_exchange_client::{
+    crypto::{SharedCipher, X25519Keypair},
+    instructions,
+    pda,
+    rescue::RescueCipher,
+    Fe,
+    PROGRAM_ID,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    native_token::LAMPORTS_PER_SOL,
+    program_pack::Pack,
+    signature::Keypair,
+    signer::Signer,
+    transaction::Transaction,
+};
+use solana_system_interface::instruction as system_instruction;
+
+use crate::{
+    circuits::{self, Owner},
+    mock_arcium,
+};
+
+/// The mock MXE's x25519 secret
+const MXE_SECRET: [u8; 32] = [7; 32];
+
+/// Anchor's entry wants accounts that live as long as their data; the bank
+/// keeps both alive for the whole instruction
+fn exchange_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    confidential_cross_chain_exchange::entry(program_id, accounts, data)
+}
+
+/// A `queue_computation` the exchange made that has not been answered yet
+#[derive(Clone, Debug)]
+pub struct QueuedComputation {
+    pub computation_offset: u64,
+    pub operation: ComputationOperation,
+    pub args: Vec<Argument>,
+    pub callback: CallbackInstruction,
+}
+
+impl QueuedComputation {
+    fn new(queued: QueueComputation) -> Self {
+        QueuedComputation {
+            computation_offset: queued.comp_offset,
+            operation: circuits::operation(queued.computation_definition_offset)
+                .expect("the exchange only queues its own circuits"),
+            args: queued.args,
+            callback: queued
+                .custom_callback_instructions
+                .into_iter()
+                .next()
+                .expect("every computation has a callback"),
+        }
+    }
+}
+
+/// The ciphertexts of one `Enc<_, _>` input and the nonce they were
+/// encrypted under
+struct Segment {
+    /// The client's pub_key, `None` when encrypted by the MXE
+    key: Option<[u8; 32]>,
+    nonce: u128,
+    ciphertexts: Vec<[u8; 32]>,
+}
+
+enum Field {
+    Plaintext(u128),
+    /// Segment index and byte width
+    Encrypted(usize, usize),
+}
+
+fn to_fields(outputs: Vec<u128>) -> Vec<Fe> {
+    outputs.into_iter().map(Fe::from).collect()
+}
+
+pub struct Harness {
+    context: ProgramTestContext,
+    mxe: X25519Keypair,
+    queued: Vec<QueuedComputation>,
+}
+
+impl Harness {
+    pub async fn start() -> Self {
+        let mut program_test = ProgramTest::default();
+        program_test.prefer_bpf(false);
+        program_test.add_program(
+            "confidential_cross_chain_exchange",
+            PROGRAM_ID,
+            processor!(exchange_entry),
+        );
+        program_test.add_program(
+            "arcium",
+            ARCIUM_PROGRAM_ID,
+            processor!(mock_arcium::process_instruction),
+        );
+        let mxe = X25519Keypair::from_secret(MXE_SECRET);
+        for (address, account) in mock_arcium::accounts(mxe.public_key()) {
+            program_test.add_account(address, account);
+        }
+        let mut context = program_test.start_with_context().await;
+        // The payer deployed the exchange, so it holds the upgrade authority
+        let program_data = UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(context.payer.pubkey()),
+        };
+        let program_data =
+            Account::new_data(LAMPORTS_PER_SOL, &program_data, &ProgramData::owner()).unwrap();
+        context.set_account(&pda::program_data(), &program_data.into());
+        Harness {
+            context,
+            mxe,
+            queued: Vec::new(),
+        }
+    }
+
+    /// Pays for every transaction the harness sends
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    pub fn context(&mut self) -> &mut ProgramTestContext {
+        &mut self.context
+    }
+
+    pub fn mxe_public_key(&self) -> [u8; 32] {
+        self.mxe.public_key()
+    }
+
+    /// Cipher a client shares with the mock MXE
+    pub fn cipher(&self, client: &X25519Keypair) -> SharedCipher {
+        SharedCipher::new(client, &self.mxe_public_key())
+    }
+
+    /// Cipher of `Enc<Mxe, _>` outputs, keyed from the mock MXE's secret
+    pub fn mxe_cipher(&self) -> RescueCipher {
+        RescueCipher::new(&self.mxe.secret_key())
+    }
+
+    /// Sends `instructions` in one transaction paid by [`Harness::payer`],
+    /// recording the computations they queue
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> std::result::Result<(), BanksClientError> {
+        let logs = self.send(instructions, signers).await?;
+        self.queued.extend(
+            mock_arcium::queued_computations(&logs)
+                .into_iter()
+                .map(QueuedComputation::new),
+        );
+        Ok(())
+    }
+
+    async fn send(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> std::result::Result<Vec<String>, BanksClientError> {
+        let blockhash = self.context.banks_client.get_latest_blockhash().await?;
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        let processed = self
+            .context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await?;
+        processed.result.map_err(BanksClientError::TransactionError)?;
+        Ok(processed
+            .metadata
+            .map(|metadata| metadata.log_messages)
+            .unwrap_or_default())
+    }
+
+    /// Computations queued and not yet answered, oldest first
+    pub fn queued(&self) -> &[QueuedComputation] {
+        &self.queued
+    }
+
+    /// Runs every queued computation's circuit and invokes its callback
+    /// with the encrypted result
+    pub async fn run_computations(&mut self) -> std::result::Result<(), BanksClientError> {
+        self.finish_computations(true).await
+    }
+
+    /// Invokes every queued computation's callback with
+    /// `ComputationOutputs::Failure`, as when the cluster aborts
+    pub async fn abort_computations(&mut self) -> std::result::Result<(), BanksClientError> {
+        self.finish_computations(false).await
+    }
+
+    /// Lets every queued computation time out without a callback, as when
+    /// the cluster never answers, and marks it stale as a cranker would. The
+    /// comp defs get a one-slot `ComputationTimeout` rather than the bank
+    /// warping past the default one.
+    pub async fn stall_computations(&mut self) -> std::result::Result<(), BanksClientError> {
+        for computation in &self.queued.clone() {
+            let comp_def_offset = computation.operation.comp_def_offset();
+            let (address, bump) = Pubkey::find_program_address(
+                &[b"ComputationTimeout", &comp_def_offset.to_le_bytes()],
+                &PROGRAM_ID,
+            );
+            let timeout = ComputationTimeout {
+                layout_version: ComputationTimeout::LAYOUT_VERSION,
+                comp_def_offset,
+                timeout_slots: 1,
+                bump,
+            };
+            self.store_account(address, &timeout).await;
+        }
+        // One slot at a time: longer warps make the bank hash every account
+        for _ in 0..2 {
+            self.next_slot().await;
+        }
+        while !self.queued.is_empty() {
+            let computation = self.queued.remove(0);
+            let pending: PendingComputation = self
+                .account(pda::pending_computation(computation.computation_offset))
+                .await
+                .expect("queued computations are pending");
+            let stale = instructions::mark_computation_stale(self.payer(), &pending);
+            self.send(&[stale], &[]).await?;
+        }
+        Ok(())
+    }
+
+    /// Answers computations in queue order; on an error the failed one is
+    /// dropped and the rest stay queued
+    async fn finish_computations(
+        &mut self,
+        succeed: bool,
+    ) -> std::result::Result<(), BanksClientError> {
+        while !self.queued.is_empty() {
+            let computation = self.queued.remove(0);
+            let outputs = if succeed {
+                self.evaluate(&computation).await
+            } else {
+                vec![1]
+            };
+            self.callback(&computation, outputs).await?;
+        }
+        Ok(())
+    }
+
+    /// Borsh of `ComputationOutputs::Success(..)` for `computation`: a
+    /// `SharedEncryptedStruct` re-encrypted to a client under its `nonce +
+    /// 1`, an `MXEEncryptedStruct` under the MXE nonce plus one, or the
+    /// revealed plaintext
+    async fn evaluate(&mut self, computation: &QueuedComputation) -> Vec<u8> {
+        let mut pub_key = None;
+        let mut segments: Vec<Segment> = Vec::new();
+        // Each input field in declaration order: a plaintext value, or the
+        // width of the next ciphertext of a segment
+        let mut fields = Vec::new();
+        for argument in &computation.args {
+            let (ciphertext, width) = match argument {
+                Argument::ArcisPubkey(key) => {
+                    pub_key = Some(*key);
+                    continue;
+                }
+                // A nonce starts the fields of one `Enc<_, _>`: a client's
+                // if it follows its pub_key, the MXE's otherwise
+                Argument::PlaintextU128(nonce) => {
+                    segments.push(Segment {
+                        key: pub_key.take(),
+                        nonce: *nonce,
+                        ciphertexts: Vec::new(),
+                    });
+                    continue;
+                }
+                Argument::PlaintextBool(value) => {
+                    fields.push(Field::Plaintext(u128::from(*value)));
+                    continue;
+                }
+                Argument::PlaintextU8(value) => {
+                    fields.push(Field::Plaintext(u128::from(*value)));
+                    continue;
+                }
+                Argument::PlaintextU64(value) => {
+                    fields.push(Field::Plaintext(u128::from(*value)));
+                    continue;
+                }
+                // MXE state the program stored from an earlier output, one
+                // ciphertext per 32 bytes
+                Argument::Account(address, offset, length) => {
+                    let data = self
+                        .context
+                        .banks_client
+                        .get_account(*address)
+                        .await
+                        .expect("bank is running")
+                        .expect("computations read existing accounts")
+                        .data;
+                    let stored = &data[*offset as usize..(*offset + *length) as usize];
+                    let segment = segments.len() - 1;
+                    for ciphertext in stored.chunks_exact(32) {
+                        segments[segment].ciphertexts.push(ciphertext.try_into().unwrap());
+                        fields.push(Field::Encrypted(segment, 16));
+                    }
+                    continue;
+                }
+                Argument::EncryptedBool(c) | Argument::EncryptedU8(c) => (c, 1),
+                Argument::EncryptedU16(c) => (c, 2),
+                Argument::EncryptedU32(c) => (c, 4),
+                Argument::EncryptedU64(c) => (c, 8),
+                Argument::EncryptedU128(c) => (c, 16),
+                other => panic!("mock cluster does not support {other:?}"),
+            };
+            let segment = segments.len().checked_sub(1).expect("ciphertexts follow a nonce");
+            segments[segment].ciphertexts.push(*ciphertext);
+            fields.push(Field::Encrypted(segment, width));
+        }
+
+        let mxe = self.mxe_cipher();
+        let cipher = |segment: &Segment| match segment.key {
+            Some(key) => RescueCipher::new(&self.mxe.shared_secret(&key)),
+            None => self.mxe_cipher(),
+        };
+        let mut decrypted: Vec<_> = segments
+            .iter()
+            .map(|segment| {
+                cipher(segment)
+                    .decrypt(&segment.ciphertexts, segment.nonce)
+                    .into_iter()
+            })
+            .collect();
+        let inputs: Vec<u128> = fields
+            .into_iter()
+            .map(|field| match field {
+                Field::Plaintext(value) => value,
+                Field::Encrypted(segment, width) => {
+                    let field = decrypted[segment].next().expect("one field per ciphertext");
+                    let mut bytes = [0u8; 16];
+                    bytes[..width].copy_from_slice(&field.to_le_bytes()[..width]);
+                    u128::from_le_bytes(bytes)
+                }
+            })
+            .collect();
+        let outputs = circuits::evaluate(computation.operation, &inputs);
+
+        // `SharedEncryptedStruct` of `outputs` for the `index`th client
+        let shared = |index: usize, outputs: Vec<u128>| {
+            let segment = segments
+                .iter()
+                .filter(|segment| segment.key.is_some())
+                .nth(index)
+                .expect("shared outputs go to a client's pub_key");
+            let nonce = segment.nonce.wrapping_add(1);
+            let mut data = segment.key.unwrap().to_vec();
+            data.extend_from_slice(&nonce.to_le_bytes());
+            data.extend(cipher(segment).encrypt(&to_fields(outputs), nonce).concat());
+            data
+        };
+        let mut data = vec![0];
+        match circuits::output_owner(computation.operation) {
+            Owner::Shared => data.extend(shared(0, outputs)),
+            Owner::SharedTuple(elements) => {
+                let mut outputs = outputs.into_iter();
+                for &(client, len) in elements {
+                    data.extend(shared(client, outputs.by_ref().take(len).collect()));
+                }
+            }
+            Owner::Mxe => {
+                let nonce = segments
+                    .iter()
+                    .rev()
+                    .find(|segment| segment.key.is_none())
+                    .expect("MXE outputs take an MXE nonce")
+                    .nonce
+                    .wrapping_add(1);
+                data.extend_from_slice(&nonce.to_le_bytes());
+                data.extend(mxe.encrypt(&to_fields(outputs), nonce).concat());
+            }
+            Owner::Revealed(widths) => {
+                for (output, width) in outputs.into_iter().zip(widths) {
+                    data.extend_from_slice(&output.to_le_bytes()[..*width]);
+                }
+            }
+        }
+        data
+    }
+
+    /// Sends the callback transaction the cluster would: Arcium's
+    /// `finalize_computation` followed by the program's callback
+    async fn callback(
+        &mut self,
+        computation: &QueuedComputation,
+        outputs: Vec<u8>,
+    ) -> std::result::Result<(), BanksClientError> {
+        let finalize = Instruction {
+            program_id: ARCIUM_PROGRAM_ID,
+            accounts: vec![],
+            data: FinalizeComputation {
+                comp_offset: computation.computation_offset,
+                comp_def_offset: computation.operation.comp_def_offset(),
+                mxe_program: PROGRAM_ID,
+            }
+            .data(),
+        };
+        let callback = Instruction {
+            program_id: computation.callback.program_id,
+            accounts: computation
+                .callback
+                .accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: false,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: [computation.callback.discriminator.as_slice(), &outputs].concat(),
+        };
+        self.send(&[finalize, callback], &[]).await?;
+        Ok(())
+    }
+
+    pub async fn account<T: AccountDeserialize>(&mut self, address: Pubkey) -> Option<T> {
+        let account = self
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .expect("bank is running")?;
+        T::try_deserialize(&mut &account.data[..]).ok()
+    }
+
+    pub async fn balance(&mut self, address: Pubkey) -> u64 {
+        self.context
+            .banks_client
+            .get_balance(address)
+            .await
+            .expect("bank is running")
+    }
+
+    /// Transfers `lamports` from [`Harness::payer`]
+    pub async fn fund(&mut self, to: &Pubkey, lamports: u64) {
+        let transfer = system_instruction::transfer(&self.payer(), to, lamports);
+        self.send(&[transfer], &[])
+            .await
+            .expect("payer can fund accounts");
+    }
+
+    /// A fresh keypair holding 10 SOL
+    pub async fn funded_keypair(&mut self) -> Keypair {
+        let keypair = Keypair::new();
+        self.fund(&keypair.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+        keypair
+    }
+
+    /// Initializes the exchange config as [`Harness::payer`], registers
+    /// `chain` and sends the rest of the admin `setup`
+    pub async fn register_chain(&mut self, chain: SetChainConfig, setup: Vec<Instruction>) {
+        let admin = self.payer();
+        let register = [
+            instructions::initialize_exchange_config(admin),
+            instructions::set_chain_config(admin, chain),
+        ];
+        let setup: Vec<_> = register.into_iter().chain(setup).collect();
+        self.send(&setup, &[])
+            .await
+            .expect("payer is the exchange admin");
+    }
+
+    /// Writes `account` at `address`, owned by the exchange, rent exempt and
+    /// as large as `init` allocates it, as if an instruction had left it there
+    pub async fn store_account<T: VersionedAccount>(&mut self, address: Pubkey, account: &T) {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).expect("account serializes");
+        data.resize(T::SPACE, 0);
+        let rent = self
+            .context
+            .banks_client
+            .get_rent()
+            .await
+            .expect("bank is running");
+        let account = Account {
+            lamports: rent.minimum_balance(data.len()),
+            data,
+            owner: PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.context.set_account(&address, &account.into());
+    }
+
+    /// Stores `maker`'s intrachain offer `id` of 1 SOL for 2 SOL with
+    /// `status` and `buyer`, skipping its verification, and returns its
+    /// address
+    pub async fn store_intrachain_offer(
+        &mut self,
+        maker: &Pubkey,
+        id: u64,
+        status: OfferStatus,
+        buyer: Option<Pubkey>,
+    ) -> Pubkey {
+        let (address, bump) = Pubkey::find_program_address(
+            &[b"IntraChainoffer", maker.as_ref(), &id.to_le_bytes()],
+            &PROGRAM_ID,
+        );
+        let offer = IntraChainOffer {
+            layout_version: IntraChainOffer::LAYOUT_VERSION,
+            id,
+            token_a_offered_amount: LAMPORTS_PER_SOL,
+            token_b_wanted_amount: 2 * LAMPORTS_PER_SOL,
+            is_taker_native: true,
+            deadline: i64::MAX,
+            maker: *maker,
+            buyer,
+            status,
+            computation_offset: 0,
+            bump,
+        };
+        self.store_account(address, &offer).await;
+        address
+    }
+
+    /// Creates a mint whose authority is [`Harness::payer`]
+    pub async fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        let mint = Keypair::new();
+        let rent = self
+            .context
+            .banks_client
+            .get_rent()
+            .await
+            .expect("bank is running");
+        let create = system_instruction::create_account(
+            &self.payer(),
+            &mint.pubkey(),
+            rent.minimum_balance(Mint::LEN),
+            Mint::LEN as u64,
+            &spl_token::ID,
+        );
+        let initialize = spl_token::instruction::initialize_mint2(
+            &spl_token::ID,
+            &mint.pubkey(),
+            &self.payer(),
+            None,
+            decimals,
+        )
+        .expect("valid mint");
+        self.send(&[create, initialize], &[&mint])
+            .await
+            .expect("payer can create mints");
+        mint.pubkey()
+    }
+
+    /// Creates `owner`'s associated token account for `mint` holding `amount`
+    pub async fn create_token_account(
+        &mut self,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Pubkey {
+        let address = get_associated_token_address(owner, mint);
+        let create = create_associated_token_account(&self.payer(), owner, mint, &spl_token::ID);
+        let mint_to = spl_token::instruction::mint_to(
+            &spl_token::ID,
+            mint,
+            &address,
+            &self.payer(),
+            &[],
+            amount,
+        )
+        .expect("valid mint_to");
+        self.send(&[create, mint_to], &[])
+            .await
+            .expect("payer is the mint authority");
+        address
+    }
+
+    /// Token amount of `address`, 0 when it does not exist
+    pub async fn token_balance(&mut self, address: Pubkey) -> u64 {
+        self.account::<TokenAccount>(address)
+            .await
+            .map_or(0, |account| account.amount)
+    }
+
+    pub async fn clock(&mut self) -> Clock {
+        self.context
+            .banks_client
+            .get_sysvar()
+            .await
+            .expect("bank is running")
+    }
+
+    /// Moves the clock's `unix_timestamp`, e.g. past an offer's deadline
+    pub async fn set_unix_timestamp(&mut self, unix_timestamp: i64) {
+        let clock = Clock {
+            unix_timestamp,
+            ..self.clock().await
+        };
+        self.context.set_sysvar(&clock);
+    }
+
+    /// Advances the bank to `slot`, e.g. past a computation's timeout
+    pub fn warp_to_slot(&mut self, slot: u64) {
+        self.context
+            .warp_to_slot(slot)
+            .expect("slot is ahead of the bank");
+    }
+
+    /// Moves to a fresh blockhash, so a retried transaction is not a replay
+    /// of the one that failed
+    pub async fn next_slot(&mut self) {
+        let slot = self.clock().await.slot;
+        self.warp_to_slot(slot + 1);
+    }
+}
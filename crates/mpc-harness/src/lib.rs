@@ -0,0 +1,16 @@
+//! Offline stand-in for the Arcium network, for testing the exchange's
+//! callback paths without an Arcium localnet.
+//!
+//! [`Harness`] runs the program natively in `solana-program-test` next to a
+//! mock Arcium program ([`mock_arcium`]) that accepts `queue_computation`.
+//! [`Harness::run_computations`] then evaluates the queued `encrypted-ixs`
+//! circuits in plaintext ([`circuits`]) and invokes each `*_callback` with
+//! correctly encrypted `ComputationOutputs`, so escrow and lifecycle logic
+//! can be tested deterministically.
+
+pub mod circuits;
+pub mod harness;
+pub mod mock_arcium;
+
+pub use harness::{Harness, QueuedComputation};
+pub use mock_arcium::CLUSTER_OFFSET;
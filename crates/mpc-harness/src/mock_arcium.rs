@@ -0,0 +1,148 @@
+//! Native stand-in for the Arcium program and the accounts it owns.
+//!
+//! The program's queue contexts only check the Arcium accounts' addresses,
+//! owners and discriminators, so [`accounts`] preloads minimal ones. The
+//! mock program logs each `queue_computation` CPI for the harness to pick up
+//! and accepts the `finalize_computation` instruction a callback transaction
+//! must start with.
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{entrypoint::ProgramResult, program_error::ProgramError, program_stubs},
+    AccountSerialize,
+    Discriminator,
+};
+use arcium_client::idl::arcium::{
+    accounts::{ClockAccount, Cluster, ComputationDefinitionAccount, FeePool, MXEAccount},
+    client::args::{FinalizeComputation, QueueComputation},
+    types::{
+        Activation,
+        CircuitSource,
+        ComputationDefinitionMeta,
+        ComputationSignature,
+        Epoch,
+        OffChainCircuitSource,
+        Timestamp,
+        X25519Pubkey,
+    },
+};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use confidential_exchange_client::pda;
+use solana_sdk::{account::Account, rent::Rent};
+
+use crate::circuits::OPERATIONS;
+
+/// Cluster the mock MXE is assigned to
+pub const CLUSTER_OFFSET: u32 = 1;
+
+/// Prefix of the log line carrying a queued computation's base64 args
+pub const QUEUE_LOG_PREFIX: &str = "mock-arcium queue_computation ";
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let (discriminator, args) = data
+        .split_at_checked(8)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if discriminator == QueueComputation::DISCRIMINATOR {
+        // `msg!` prints to stdout off-chain; the stubs log to the transaction
+        program_stubs::sol_log(&format!("{QUEUE_LOG_PREFIX}{}", BASE64_STANDARD.encode(args)));
+        Ok(())
+    } else if discriminator == FinalizeComputation::DISCRIMINATOR {
+        Ok(())
+    } else {
+        Err(ProgramError::InvalidInstructionData)
+    }
+}
+
+/// Parses the `queue_computation` args logged by [`process_instruction`]
+pub fn queued_computations(logs: &[String]) -> Vec<QueueComputation> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix("Program log: ")?.strip_prefix(QUEUE_LOG_PREFIX))
+        .filter_map(|encoded| BASE64_STANDARD.decode(encoded).ok())
+        .filter_map(|args| QueueComputation::deserialize(&mut &args[..]).ok())
+        .collect()
+}
+
+fn arcium_account(state: &impl AccountSerialize) -> Account {
+    let mut data = Vec::new();
+    state
+        .try_serialize(&mut data)
+        .expect("serializing into a Vec cannot fail");
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: arcium_client::ARCIUM_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// The MXE, its cluster, the fee pool, the clock and a comp def for every
+/// circuit, with `mxe_public_key` as the MXE's x25519 key
+pub fn accounts(mxe_public_key: [u8; 32]) -> Vec<(Pubkey, Account)> {
+    let mxe = MXEAccount {
+        authority: None,
+        cluster: Some(CLUSTER_OFFSET),
+        x25519_pubkey: X25519Pubkey::Set(mxe_public_key),
+        fallback_clusters: vec![],
+        rejected_clusters: vec![],
+        computation_definitions: OPERATIONS.iter().map(|op| op.comp_def_offset()).collect(),
+        bump: 0,
+    };
+    let cluster = Cluster {
+        authority: None,
+        max_size: 1,
+        activation: Activation {
+            activation_epoch: Epoch(0),
+            deactivation_epoch: Epoch(u64::MAX),
+        },
+        max_capacity: u64::MAX,
+        cu_price: 0,
+        cu_price_proposals: [0; 32],
+        last_updated_epoch: Epoch(0),
+        mxes: vec![confidential_exchange_client::PROGRAM_ID],
+        nodes: vec![],
+        pending_nodes: vec![],
+        bump: 0,
+    };
+    let clock = ClockAccount {
+        start_epoch: Epoch(0),
+        current_epoch: Epoch(0),
+        start_epoch_timestamp: Timestamp { timestamp: 0 },
+        bump: 0,
+    };
+
+    let mut accounts = vec![
+        (pda::mxe_account(), arcium_account(&mxe)),
+        (pda::cluster_account(CLUSTER_OFFSET), arcium_account(&cluster)),
+        (pda::fee_pool_account(), arcium_account(&FeePool { bump: 0 })),
+        (pda::clock_account(), arcium_account(&clock)),
+    ];
+    accounts.extend(OPERATIONS.iter().map(|operation| {
+        let comp_def = ComputationDefinitionAccount {
+            finalization_authority: None,
+            finalize_during_callback: true,
+            cu_amount: 0,
+            definition: ComputationDefinitionMeta {
+                circuit_len: 0,
+                signature: ComputationSignature {
+                    parameters: vec![],
+                    outputs: vec![],
+                },
+            },
+            circuit_source: CircuitSource::OffChain(OffChainCircuitSource {
+                source: String::new(),
+                hash: [0; 32],
+            }),
+            bump: 0,
+        };
+        (
+            pda::comp_def_account(operation.comp_def_offset()),
+            arcium_account(&comp_def),
+        )
+    }));
+    accounts
+}
@@ -0,0 +1,258 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+use confidential_exchange_client::{
+    crypto::{identity_hash, X25519Keypair},
+    instructions,
+    pda,
+    program::{instruction, BasketLeg, BasketOffer, OfferStatus, VaultSide},
+    Fe,
+};
+use mpc_harness::{Harness, CLUSTER_OFFSET};
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Keypair, signer::Signer};
+
+const OFFER_ID: u64 = 1;
+const SOL_LEG: u64 = LAMPORTS_PER_SOL;
+const TOKEN_A_LEG: u64 = 500;
+const TOKEN_B_LEG: u64 = 300;
+
+/// A maker offering SOL and token A for a taker's token B
+struct Desk {
+    maker: Keypair,
+    taker: Keypair,
+    token_a: Pubkey,
+    token_b: Pubkey,
+}
+
+async fn desk(harness: &mut Harness) -> Desk {
+    let maker = Keypair::new();
+    let taker = Keypair::new();
+    harness.fund(&maker.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+    harness.fund(&taker.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+    let token_a = harness.create_mint(6).await;
+    let token_b = harness.create_mint(6).await;
+    harness.create_token_account(&maker.pubkey(), &token_a, TOKEN_A_LEG).await;
+    harness.create_token_account(&maker.pubkey(), &token_b, 0).await;
+    harness.create_token_account(&taker.pubkey(), &token_a, 0).await;
+    harness.create_token_account(&taker.pubkey(), &token_b, TOKEN_B_LEG).await;
+    Desk {
+        maker,
+        taker,
+        token_a,
+        token_b,
+    }
+}
+
+async fn basket(harness: &mut Harness, desk: &Desk) -> BasketOffer {
+    harness
+        .account(pda::basket_offer(&desk.maker.pubkey(), OFFER_ID))
+        .await
+        .unwrap()
+}
+
+async fn token_balance(harness: &mut Harness, owner: &Keypair, mint: &Pubkey) -> u64 {
+    harness
+        .token_balance(get_associated_token_address(&owner.pubkey(), mint))
+        .await
+}
+
+/// Queues `create_basket_offer` under computation 1 and escrows the maker's legs
+async fn create_offer(harness: &mut Harness, desk: &Desk, deadline: i64) -> BasketOffer {
+    let maker = &desk.maker;
+    let cipher = harness.cipher(&X25519Keypair::from_secret([1; 32]));
+    let identity = cipher.encrypt_with_nonce([Fe::from(identity_hash(maker.pubkey().as_ref()))], 1);
+    let create = instructions::create_basket_offer(
+        maker.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::CreateBasketOffer {
+            id: OFFER_ID,
+            offered_legs: vec![
+                BasketLeg {
+                    mint: None,
+                    amount: SOL_LEG,
+                },
+                BasketLeg {
+                    mint: Some(desk.token_a),
+                    amount: TOKEN_A_LEG,
+                },
+            ],
+            wanted_legs: vec![BasketLeg {
+                mint: Some(desk.token_b),
+                amount: TOKEN_B_LEG,
+            }],
+            deadline,
+            ciphertext_seller_identity_hash: identity.ciphertexts[0],
+            pub_key: identity.pub_key,
+            nonce: identity.nonce,
+            computation_offset: 1,
+        },
+    );
+    harness.process(&[create], &[maker]).await.unwrap();
+
+    let offer = basket(harness, desk).await;
+    let deposits: Vec<_> = (0..2)
+        .map(|leg| instructions::deposit_basket_leg(maker.pubkey(), &offer, VaultSide::Seller, leg))
+        .collect();
+    harness.process(&deposits, &[maker]).await.unwrap();
+    offer
+}
+
+/// Queues `finalize_basket_offer` for the taker under `computation_offset`
+async fn finalize(harness: &mut Harness, desk: &Desk, computation_offset: u64) {
+    let taker = &desk.taker;
+    let cipher = harness.cipher(&X25519Keypair::from_secret([2; 32]));
+    let identity = cipher.encrypt_with_nonce([Fe::from(identity_hash(taker.pubkey().as_ref()))], 1);
+    let finalize = instructions::finalize_basket_offer(
+        taker.pubkey(),
+        desk.maker.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::FinalizeBasketOffer {
+            offer_id: OFFER_ID,
+            ciphertext_buyer_identity_hash: identity.ciphertexts[0],
+            pub_key: identity.pub_key,
+            nonce: identity.nonce,
+            computation_offset,
+        },
+    );
+    harness.process(&[finalize], &[taker]).await.unwrap();
+}
+
+#[tokio::test]
+async fn settles_every_leg_at_once() {
+    let mut harness = Harness::start().await;
+    let desk = desk(&mut harness).await;
+
+    let offer = create_offer(&mut harness, &desk, i64::MAX).await;
+    assert_eq!(basket(&mut harness, &desk).await.status, OfferStatus::Pending);
+    harness.run_computations().await.unwrap();
+    assert_eq!(basket(&mut harness, &desk).await.status, OfferStatus::Open);
+
+    let deposit =
+        instructions::deposit_basket_leg(desk.taker.pubkey(), &offer, VaultSide::Buyer, 0);
+    harness.process(&[deposit], &[&desk.taker]).await.unwrap();
+
+    // Only a taker whose identity was verified can settle
+    let execute = instructions::execute_basket_swap(&offer, desk.taker.pubkey());
+    let signers = [&desk.maker, &desk.taker];
+    assert!(harness.process(std::slice::from_ref(&execute), &signers).await.is_err());
+    finalize(&mut harness, &desk, 2).await;
+    harness.run_computations().await.unwrap();
+    assert_eq!(basket(&mut harness, &desk).await.taker, Some(desk.taker.pubkey()));
+
+    let taker_before = harness.balance(desk.taker.pubkey()).await;
+    harness.process(&[execute], &signers).await.unwrap();
+
+    assert_eq!(basket(&mut harness, &desk).await.status, OfferStatus::Settled);
+    assert_eq!(harness.balance(desk.taker.pubkey()).await, taker_before + SOL_LEG);
+    assert_eq!(token_balance(&mut harness, &desk.taker, &desk.token_a).await, TOKEN_A_LEG);
+    assert_eq!(token_balance(&mut harness, &desk.maker, &desk.token_b).await, TOKEN_B_LEG);
+    assert_eq!(token_balance(&mut harness, &desk.taker, &desk.token_b).await, 0);
+}
+
+#[tokio::test]
+async fn unfunded_leg_fails_the_whole_swap() {
+    let mut harness = Harness::start().await;
+    let desk = desk(&mut harness).await;
+
+    let offer = create_offer(&mut harness, &desk, i64::MAX).await;
+    harness.run_computations().await.unwrap();
+    finalize(&mut harness, &desk, 2).await;
+    harness.run_computations().await.unwrap();
+
+    // The taker never escrowed token B
+    let execute = instructions::execute_basket_swap(&offer, desk.taker.pubkey());
+    assert!(harness.process(&[execute], &[&desk.maker, &desk.taker]).await.is_err());
+
+    assert_eq!(basket(&mut harness, &desk).await.status, OfferStatus::Open);
+    assert_eq!(token_balance(&mut harness, &desk.taker, &desk.token_a).await, 0);
+    let offer_address = pda::basket_offer(&desk.maker.pubkey(), OFFER_ID);
+    let token_vault = pda::basket_vault(&offer_address, &desk.maker.pubkey(), VaultSide::Seller, 1);
+    assert_eq!(harness.token_balance(token_vault).await, TOKEN_A_LEG);
+}
+
+#[tokio::test]
+async fn refunds_legs_after_aborted_verification() {
+    let mut harness = Harness::start().await;
+    let desk = desk(&mut harness).await;
+
+    let offer = create_offer(&mut harness, &desk, i64::MAX).await;
+    harness.abort_computations().await.unwrap();
+    assert_eq!(
+        basket(&mut harness, &desk).await.status,
+        OfferStatus::VerificationFailed
+    );
+    let deposit =
+        instructions::deposit_basket_leg(desk.taker.pubkey(), &offer, VaultSide::Buyer, 0);
+    assert!(harness.process(&[deposit], &[&desk.taker]).await.is_err());
+
+    let maker_before = harness.balance(desk.maker.pubkey()).await;
+    let refunds: Vec<_> = (0..2)
+        .map(|leg| {
+            instructions::refund_basket_leg(&offer, desk.maker.pubkey(), VaultSide::Seller, leg)
+        })
+        .collect();
+    harness.process(&refunds, &[]).await.unwrap();
+
+    assert_eq!(basket(&mut harness, &desk).await.status, OfferStatus::Refunded);
+    assert_eq!(harness.balance(desk.maker.pubkey()).await, maker_before + SOL_LEG);
+    assert_eq!(token_balance(&mut harness, &desk.maker, &desk.token_a).await, TOKEN_A_LEG);
+
+    let again = instructions::refund_basket_leg(&offer, desk.maker.pubkey(), VaultSide::Seller, 0);
+    assert!(harness.process(&[again], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn refunds_legs_after_stale_verification() {
+    let mut harness = Harness::start().await;
+    let desk = desk(&mut harness).await;
+
+    let offer = create_offer(&mut harness, &desk, i64::MAX).await;
+    harness.stall_computations().await.unwrap();
+    assert_eq!(
+        basket(&mut harness, &desk).await.status,
+        OfferStatus::VerificationFailed
+    );
+    let deposit =
+        instructions::deposit_basket_leg(desk.taker.pubkey(), &offer, VaultSide::Buyer, 0);
+    assert!(harness.process(&[deposit], &[&desk.taker]).await.is_err());
+
+    let maker_before = harness.balance(desk.maker.pubkey()).await;
+    let refunds: Vec<_> = (0..2)
+        .map(|leg| {
+            instructions::refund_basket_leg(&offer, desk.maker.pubkey(), VaultSide::Seller, leg)
+        })
+        .collect();
+    harness.process(&refunds, &[]).await.unwrap();
+
+    assert_eq!(basket(&mut harness, &desk).await.status, OfferStatus::Refunded);
+    assert_eq!(harness.balance(desk.maker.pubkey()).await, maker_before + SOL_LEG);
+    assert_eq!(token_balance(&mut harness, &desk.maker, &desk.token_a).await, TOKEN_A_LEG);
+
+    let again = instructions::refund_basket_leg(&offer, desk.maker.pubkey(), VaultSide::Seller, 0);
+    assert!(harness.process(&[again], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn escrow_is_locked_until_the_deadline() {
+    let mut harness = Harness::start().await;
+    let desk = desk(&mut harness).await;
+
+    let deadline = harness.clock().await.unix_timestamp + 3_600;
+    let offer = create_offer(&mut harness, &desk, deadline).await;
+    harness.run_computations().await.unwrap();
+    let deposit =
+        instructions::deposit_basket_leg(desk.taker.pubkey(), &offer, VaultSide::Buyer, 0);
+    harness.process(&[deposit], &[&desk.taker]).await.unwrap();
+
+    let refund_taker =
+        instructions::refund_basket_leg(&offer, desk.taker.pubkey(), VaultSide::Buyer, 0);
+    assert!(harness.process(std::slice::from_ref(&refund_taker), &[]).await.is_err());
+
+    harness.set_unix_timestamp(deadline + 1).await;
+    let refund_maker =
+        instructions::refund_basket_leg(&offer, desk.maker.pubkey(), VaultSide::Seller, 1);
+    harness.process(&[refund_taker, refund_maker], &[]).await.unwrap();
+
+    assert_eq!(token_balance(&mut harness, &desk.taker, &desk.token_b).await, TOKEN_B_LEG);
+    assert_eq!(token_balance(&mut harness, &desk.maker, &desk.token_a).await, TOKEN_A_LEG);
+    assert_eq!(basket(&mut harness, &desk).await.status, OfferStatus::Refunded);
+}
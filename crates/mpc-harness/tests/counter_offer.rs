@@ -0,0 +1,427 @@
+use confidential_exchange_client::{
+    crypto::{identity_hash, X25519Keypair},
+    instructions,
+    pda,
+    program::{instruction, CounterOffer, IntraChainOffer, OfferKind, OfferStatus, PriceTrigger},
+    Fe,
+};
+use mpc_harness::{Harness, CLUSTER_OFFSET};
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+};
+
+const OFFER_ID: u64 = 1;
+const TOKEN_A: u64 = LAMPORTS_PER_SOL;
+const TOKEN_B: u64 = 2 * LAMPORTS_PER_SOL;
+
+/// Opens `maker`'s offer of `TOKEN_A` for `TOKEN_B`, verified under
+/// computation 1
+async fn open_offer(harness: &mut Harness, maker: &Keypair) {
+    let cipher = harness.cipher(&X25519Keypair::from_secret([1; 32]));
+    let identity = cipher.encrypt_with_nonce([Fe::from(identity_hash(maker.pubkey().as_ref()))], 1);
+    let create = instructions::deposit_seller_native(
+        maker.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::DepositSellerNative {
+            id: OFFER_ID,
+            token_b_wanted_amount: TOKEN_B,
+            token_a_offered_amount: TOKEN_A,
+            is_taker_native: true,
+            deadline: i64::MAX,
+            ciphertext_seller_identity_hash: identity.ciphertexts[0],
+            pub_key: identity.pub_key,
+            nonce: identity.nonce,
+            computation_offset: 1,
+        },
+    );
+    let deposit = instructions::deposit_to_seller_vault(
+        maker.pubkey(),
+        OfferKind::Intrachain,
+        OFFER_ID,
+        TOKEN_A,
+    );
+    harness.process(&[create, deposit], &[maker]).await.unwrap();
+    harness.run_computations().await.unwrap();
+}
+
+/// Queues `propose_counter_offer` for half the offer at `price`, under
+/// `computation_offset`
+async fn propose(
+    harness: &mut Harness,
+    maker: &Keypair,
+    proposer: &Keypair,
+    price: u64,
+    deadline: i64,
+    computation_offset: u64,
+) -> Result<(), solana_program_test::BanksClientError> {
+    let cipher = harness.cipher(&X25519Keypair::from_secret([computation_offset as u8; 32]));
+    let identity =
+        cipher.encrypt_with_nonce([Fe::from(identity_hash(proposer.pubkey().as_ref()))], 1);
+    let propose = instructions::propose_counter_offer(
+        proposer.pubkey(),
+        maker.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::ProposeCounterOffer {
+            offer_id: OFFER_ID,
+            token_a_amount: TOKEN_A / 2,
+            token_b_amount: price,
+            deadline,
+            ciphertext_proposer_identity_hash: identity.ciphertexts[0],
+            pub_key: identity.pub_key,
+            nonce: identity.nonce,
+            computation_offset,
+        },
+    );
+    harness.process(&[propose], &[proposer]).await
+}
+
+fn counter_address(maker: &Keypair, proposer: &Keypair) -> Pubkey {
+    pda::counter_offer(&pda::intrachain_offer(&maker.pubkey(), OFFER_ID), &proposer.pubkey())
+}
+
+async fn counter(harness: &mut Harness, maker: &Keypair, proposer: &Keypair) -> CounterOffer {
+    harness.account(counter_address(maker, proposer)).await.unwrap()
+}
+
+async fn offer(harness: &mut Harness, maker: &Keypair) -> IntraChainOffer {
+    harness
+        .account(pda::intrachain_offer(&maker.pubkey(), OFFER_ID))
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn accepted_counter_offer_settles_on_its_terms() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+    let proposer = harness.funded_keypair().await;
+    open_offer(&mut harness, &maker).await;
+
+    // More than the offer's size, or past its deadline, is not a counter-offer
+    let cipher = harness.cipher(&X25519Keypair::from_secret([3; 32]));
+    let identity = cipher.encrypt_with_nonce([Fe::from(7u64)], 1);
+    let oversized = instructions::propose_counter_offer(
+        proposer.pubkey(),
+        maker.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::ProposeCounterOffer {
+            offer_id: OFFER_ID,
+            token_a_amount: TOKEN_A + 1,
+            token_b_amount: TOKEN_B,
+            deadline: i64::MAX,
+            ciphertext_proposer_identity_hash: identity.ciphertexts[0],
+            pub_key: identity.pub_key,
+            nonce: identity.nonce,
+            computation_offset: 3,
+        },
+    );
+    assert!(harness.process(&[oversized], &[&proposer]).await.is_err());
+
+    let price = 3 * LAMPORTS_PER_SOL / 4;
+    propose(&mut harness, &maker, &proposer, price, i64::MAX, 2).await.unwrap();
+    let proposed = counter(&mut harness, &maker, &proposer).await;
+    assert_eq!(proposed.status, OfferStatus::Pending);
+
+    // Not acceptable until the proposer is verified
+    let accept = instructions::accept_counter_offer(&proposed, None);
+    assert!(harness.process(std::slice::from_ref(&accept), &[&maker]).await.is_err());
+    harness.run_computations().await.unwrap();
+    assert_eq!(counter(&mut harness, &maker, &proposer).await.status, OfferStatus::Open);
+
+    let counter_rent = harness.balance(counter_address(&maker, &proposer)).await;
+    let maker_before = harness.balance(maker.pubkey()).await;
+    let proposer_before = harness.balance(proposer.pubkey()).await;
+    harness.next_slot().await;
+    harness.process(&[accept], &[&maker]).await.unwrap();
+
+    let settled = offer(&mut harness, &maker).await;
+    assert_eq!(settled.status, OfferStatus::Settled);
+    assert_eq!(
+        (settled.token_a_offered_amount, settled.token_b_wanted_amount),
+        (TOKEN_A / 2, price)
+    );
+    // The maker is paid the price and gets back the half not sold
+    assert_eq!(
+        harness.balance(maker.pubkey()).await,
+        maker_before + price + TOKEN_A / 2
+    );
+    assert_eq!(
+        harness.balance(proposer.pubkey()).await,
+        proposer_before + TOKEN_A / 2 + counter_rent
+    );
+    assert!(harness
+        .account::<CounterOffer>(counter_address(&maker, &proposer))
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn rejected_counter_offer_refunds_the_proposer() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+    let proposer = harness.funded_keypair().await;
+    open_offer(&mut harness, &maker).await;
+
+    let proposer_before = harness.balance(proposer.pubkey()).await;
+    propose(&mut harness, &maker, &proposer, LAMPORTS_PER_SOL, i64::MAX, 2).await.unwrap();
+    harness.run_computations().await.unwrap();
+    let proposed = counter(&mut harness, &maker, &proposer).await;
+
+    // Only the maker rejects
+    let reject = instructions::reject_counter_offer(&proposed);
+    let mut forged = reject.clone();
+    forged.accounts[0].pubkey = proposer.pubkey();
+    assert!(harness.process(&[forged], &[&proposer]).await.is_err());
+    harness.process(&[reject], &[&maker]).await.unwrap();
+
+    // Everything but the vault's rent comes back
+    let vault_rent = harness
+        .balance(pda::counter_vault(&counter_address(&maker, &proposer)))
+        .await;
+    assert_eq!(harness.balance(proposer.pubkey()).await, proposer_before - vault_rent);
+    assert_eq!(offer(&mut harness, &maker).await.status, OfferStatus::Open);
+
+    // The proposer may counter again, reusing the vault
+    propose(&mut harness, &maker, &proposer, TOKEN_B, i64::MAX, 3).await.unwrap();
+    harness.run_computations().await.unwrap();
+    assert_eq!(counter(&mut harness, &maker, &proposer).await.token_b_amount, TOKEN_B);
+}
+
+#[tokio::test]
+async fn expired_counter_offer_is_refunded_to_anyone() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+    let proposer = harness.funded_keypair().await;
+    open_offer(&mut harness, &maker).await;
+
+    let deadline = harness.clock().await.unix_timestamp + 60;
+    propose(&mut harness, &maker, &proposer, LAMPORTS_PER_SOL, deadline, 2).await.unwrap();
+    harness.run_computations().await.unwrap();
+    let proposed = counter(&mut harness, &maker, &proposer).await;
+
+    let refund = instructions::refund_counter_offer(&proposed);
+    assert!(harness.process(std::slice::from_ref(&refund), &[]).await.is_err());
+
+    harness.next_slot().await;
+    harness.set_unix_timestamp(deadline + 1).await;
+    let accept = instructions::accept_counter_offer(&proposed, None);
+    assert!(harness.process(&[accept], &[&maker]).await.is_err());
+    let proposer_before = harness.balance(proposer.pubkey()).await;
+    harness.process(&[refund], &[]).await.unwrap();
+    assert!(harness.balance(proposer.pubkey()).await > proposer_before + LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn aborted_verification_refunds_the_proposer() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+    let proposer = harness.funded_keypair().await;
+    open_offer(&mut harness, &maker).await;
+
+    propose(&mut harness, &maker, &proposer, LAMPORTS_PER_SOL, i64::MAX, 2).await.unwrap();
+    harness.abort_computations().await.unwrap();
+    let failed = counter(&mut harness, &maker, &proposer).await;
+    assert_eq!(failed.status, OfferStatus::VerificationFailed);
+
+    let accept = instructions::accept_counter_offer(&failed, None);
+    assert!(harness.process(&[accept], &[&maker]).await.is_err());
+    let proposer_before = harness.balance(proposer.pubkey()).await;
+    harness
+        .process(&[instructions::refund_counter_offer(&failed)], &[])
+        .await
+        .unwrap();
+    assert!(harness.balance(proposer.pubkey()).await > proposer_before + LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn stale_verification_refunds_the_proposer() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+    let proposer = harness.funded_keypair().await;
+    open_offer(&mut harness, &maker).await;
+
+    propose(&mut harness, &maker, &proposer, LAMPORTS_PER_SOL, i64::MAX, 2).await.unwrap();
+    harness.stall_computations().await.unwrap();
+    let failed = counter(&mut harness, &maker, &proposer).await;
+    assert_eq!(failed.status, OfferStatus::VerificationFailed);
+
+    let accept = instructions::accept_counter_offer(&failed, None);
+    assert!(harness.process(&[accept], &[&maker]).await.is_err());
+    let proposer_before = harness.balance(proposer.pubkey()).await;
+    harness
+        .process(&[instructions::refund_counter_offer(&failed)], &[])
+        .await
+        .unwrap();
+    assert!(harness.balance(proposer.pubkey()).await > proposer_before + LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn accepted_counter_offer_stays_within_the_price_band() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+    let lowballer = harness.funded_keypair().await;
+    let proposer = harness.funded_keypair().await;
+    let publisher = harness.funded_keypair().await;
+    open_offer(&mut harness, &maker).await;
+
+    // The feed prices token A at the offer's 2 token B, give or take 1%
+    let publish = instructions::publish_price(
+        publisher.pubkey(),
+        instruction::PublishPrice {
+            feed_id: 1,
+            price: 2,
+            expo: 0,
+        },
+    );
+    let feed = pda::price_feed(&publisher.pubkey(), 1);
+    let band = instructions::set_price_band(
+        maker.pubkey(),
+        OfferKind::Intrachain,
+        feed,
+        instruction::SetPriceBand {
+            offer: pda::intrachain_offer(&maker.pubkey(), OFFER_ID),
+            max_deviation_bps: 100,
+            max_staleness_secs: 60,
+        },
+    );
+    harness.process(&[publish], &[&publisher]).await.unwrap();
+    harness.process(&[band], &[&maker]).await.unwrap();
+
+    let lowball = 3 * LAMPORTS_PER_SOL / 4;
+    propose(&mut harness, &maker, &lowballer, lowball, i64::MAX, 2).await.unwrap();
+    propose(&mut harness, &maker, &proposer, TOKEN_B / 2, i64::MAX, 3).await.unwrap();
+    harness.run_computations().await.unwrap();
+
+    // Half the offer for 0.75 token B is a quarter under the feed
+    let lowballed = counter(&mut harness, &maker, &lowballer).await;
+    let off_market = instructions::accept_counter_offer(&lowballed, Some(feed));
+    assert!(harness.process(&[off_market], &[&maker]).await.is_err());
+    let fair = counter(&mut harness, &maker, &proposer).await;
+    let without_feed = instructions::accept_counter_offer(&fair, None);
+    assert!(harness.process(&[without_feed], &[&maker]).await.is_err());
+    assert_eq!(offer(&mut harness, &maker).await.status, OfferStatus::Open);
+
+    let accept = instructions::accept_counter_offer(&fair, Some(feed));
+    harness.process(&[accept], &[&maker]).await.unwrap();
+    assert_eq!(offer(&mut harness, &maker).await.status, OfferStatus::Settled);
+}
+
+#[tokio::test]
+async fn dormant_offer_accepts_no_counter_offer_until_triggered() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+    let proposer = harness.funded_keypair().await;
+    let publisher = harness.funded_keypair().await;
+    open_offer(&mut harness, &maker).await;
+    let offer_address = pda::intrachain_offer(&maker.pubkey(), OFFER_ID);
+
+    // A stop at 1.50 token B per token A, with the feed at 2.00
+    let publish = |price| {
+        instructions::publish_price(
+            publisher.pubkey(),
+            instruction::PublishPrice {
+                feed_id: 1,
+                price,
+                expo: -2,
+            },
+        )
+    };
+    harness.process(&[publish(200)], &[&publisher]).await.unwrap();
+    let feed = pda::price_feed(&publisher.pubkey(), 1);
+    let cipher = harness.cipher(&X25519Keypair::from_secret([4; 32]));
+    let stop = cipher.encrypt_with_nonce([Fe::from(150u64), Fe::from(0u64)], 1);
+    let set_stop = instructions::set_price_trigger(
+        maker.pubkey(),
+        OfferKind::Intrachain,
+        feed,
+        CLUSTER_OFFSET,
+        instruction::SetPriceTrigger {
+            offer: offer_address,
+            ciphertext_trigger: stop.ciphertexts,
+            pub_key: stop.pub_key,
+            nonce: stop.nonce,
+            mxe_nonce: 5,
+            computation_offset: 2,
+        },
+    );
+    harness.process(&[set_stop], &[&maker]).await.unwrap();
+    propose(&mut harness, &maker, &proposer, TOKEN_B / 2, i64::MAX, 3).await.unwrap();
+    harness.run_computations().await.unwrap();
+
+    let proposed = counter(&mut harness, &maker, &proposer).await;
+    let accept = instructions::accept_counter_offer(&proposed, None);
+    assert!(harness.process(std::slice::from_ref(&accept), &[&maker]).await.is_err());
+    assert_eq!(offer(&mut harness, &maker).await.status, OfferStatus::Open);
+
+    let now = harness.clock().await.unix_timestamp;
+    harness.set_unix_timestamp(now + 1).await;
+    harness.process(&[publish(149)], &[&publisher]).await.unwrap();
+    let armed: PriceTrigger = harness.account(pda::price_trigger(&offer_address)).await.unwrap();
+    let check = instructions::check_price_trigger(
+        publisher.pubkey(),
+        &armed,
+        CLUSTER_OFFSET,
+        instruction::CheckPriceTrigger {
+            computation_offset: 4,
+        },
+    );
+    harness.process(&[check], &[&publisher]).await.unwrap();
+    harness.run_computations().await.unwrap();
+
+    harness.next_slot().await;
+    harness.process(&[accept], &[&maker]).await.unwrap();
+    assert_eq!(offer(&mut harness, &maker).await.status, OfferStatus::Settled);
+}
+
+#[tokio::test]
+async fn buyer_escrow_is_refunded_once_a_counter_offer_settles() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let proposer = harness.funded_keypair().await;
+    open_offer(&mut harness, &maker).await;
+
+    // A buyer finalizes and escrows the full price...
+    let cipher = harness.cipher(&X25519Keypair::from_secret([2; 32]));
+    let identity = cipher.encrypt_with_nonce([Fe::from(identity_hash(buyer.pubkey().as_ref()))], 1);
+    let finalize = instructions::finalize_intrachain_offer(
+        buyer.pubkey(),
+        maker.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::FinalizeIntrachainOffer {
+            id: OFFER_ID,
+            ciphertext_buyer_identity_hash: identity.ciphertexts[0],
+            pub_key: identity.pub_key,
+            nonce: identity.nonce,
+            computation_offset: 2,
+        },
+    );
+    let deposit = instructions::deposit_to_buyer_vault(
+        buyer.pubkey(),
+        maker.pubkey(),
+        OfferKind::Intrachain,
+        OFFER_ID,
+        TOKEN_B,
+    );
+    harness.process(&[finalize, deposit], &[&buyer]).await.unwrap();
+    harness.run_computations().await.unwrap();
+    let refund =
+        instructions::refund_intrachain_buyer_vault(buyer.pubkey(), maker.pubkey(), OFFER_ID);
+    assert!(harness.process(std::slice::from_ref(&refund), &[&buyer]).await.is_err());
+
+    // ...but the maker takes a counter-offer instead
+    propose(&mut harness, &maker, &proposer, TOKEN_B / 2, i64::MAX, 3).await.unwrap();
+    harness.run_computations().await.unwrap();
+    let proposed = counter(&mut harness, &maker, &proposer).await;
+    let accept = instructions::accept_counter_offer(&proposed, None);
+    harness.process(&[accept], &[&maker]).await.unwrap();
+    assert_eq!(offer(&mut harness, &maker).await.status, OfferStatus::Settled);
+
+    let buyer_before = harness.balance(buyer.pubkey()).await;
+    harness.next_slot().await;
+    harness.process(&[refund], &[&buyer]).await.unwrap();
+    assert_eq!(harness.balance(buyer.pubkey()).await, buyer_before + TOKEN_B);
+}
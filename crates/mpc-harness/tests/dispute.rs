@@ -0,0 +1,373 @@
+use anchor_lang::prelude::Pubkey;
+use confidential_exchange_client::{
+    crypto::{identity_hash, X25519Keypair},
+    instructions,
+    pda,
+    program::{
+        instruction,
+        versioning::VersionedAccount,
+        ChainConfig,
+        Dispute,
+        InterchainOffer,
+        OfferKind,
+        OfferStatus,
+        OutboundMessage,
+        OutboundMessageKind,
+        PendingComputation,
+    },
+    Fe,
+    PROGRAM_ID,
+};
+use mpc_harness::{Harness, CLUSTER_OFFSET};
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    signature::Keypair,
+    signer::Signer,
+};
+
+const CHAIN_ID: u64 = 1;
+const OFFER_ID: u64 = 1;
+const TOKEN_A: u64 = LAMPORTS_PER_SOL;
+const TOKEN_B: u64 = 2 * LAMPORTS_PER_SOL;
+const DISPUTE_BOND: u64 = LAMPORTS_PER_SOL / 10;
+const DISPUTE_WINDOW_SECS: i64 = 3_600;
+
+/// An interchain offer open until `deadline`, with both vaults funded
+struct Trade {
+    maker: Keypair,
+    buyer: Keypair,
+    arbiter: Keypair,
+    deadline: i64,
+}
+
+/// Registers the chain and one arbiter, then stores `maker`'s offer as a
+/// relayer would have left it, finalized by the buyer, and funds both vaults
+async fn trade(harness: &mut Harness) -> Trade {
+    let maker = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let arbiter = harness.funded_keypair().await;
+    let chain = instruction::SetChainConfig {
+        chain_id: CHAIN_ID,
+        enabled: true,
+        finality_blocks: 12,
+        min_deadline_secs: 60,
+        max_deadline_secs: 86_400,
+        relayers: vec![],
+        allowed_tokens: vec![],
+    };
+    let registry = instructions::set_arbiter_registry(
+        harness.payer(),
+        instruction::SetArbiterRegistry {
+            arbiters: vec![arbiter.pubkey()],
+            dispute_bond: DISPUTE_BOND,
+            dispute_window_secs: DISPUTE_WINDOW_SECS,
+        },
+    );
+    harness.register_chain(chain, vec![registry]).await;
+
+    let deadline = harness.clock().await.unix_timestamp + 600;
+    let (address, bump) = Pubkey::find_program_address(
+        &[b"InterChainoffer", maker.pubkey().as_ref(), &OFFER_ID.to_le_bytes()],
+        &PROGRAM_ID,
+    );
+    let offer = InterchainOffer {
+        layout_version: InterchainOffer::LAYOUT_VERSION,
+        id: OFFER_ID,
+        token_a_offered_amount: TOKEN_A,
+        token_b_wanted_amount: [0; 32],
+        is_taker_native: true,
+        chain_id: CHAIN_ID,
+        deadline,
+        token_mapping: Pubkey::default(),
+        maker: maker.pubkey(),
+        buyer: Some(buyer.pubkey()),
+        relayer: Pubkey::default(),
+        slashed: false,
+        status: OfferStatus::Open,
+        computation_offset: 0,
+        bump,
+    };
+    harness.store_account(address, &offer).await;
+
+    let deposits = [
+        instructions::deposit_to_seller_vault(
+            maker.pubkey(),
+            OfferKind::Interchain,
+            OFFER_ID,
+            TOKEN_A,
+        ),
+        instructions::deposit_to_buyer_vault(
+            buyer.pubkey(),
+            maker.pubkey(),
+            OfferKind::Interchain,
+            OFFER_ID,
+            TOKEN_B,
+        ),
+    ];
+    harness.process(&deposits, &[&maker, &buyer]).await.unwrap();
+    Trade {
+        maker,
+        buyer,
+        arbiter,
+        deadline,
+    }
+}
+
+fn open_dispute(trade: &Trade, opener: &Keypair) -> solana_sdk::instruction::Instruction {
+    instructions::open_dispute(
+        opener.pubkey(),
+        trade.maker.pubkey(),
+        instruction::OpenDispute {
+            offer_id: OFFER_ID,
+            buyer: trade.buyer.pubkey(),
+        },
+    )
+}
+
+fn offer_address(trade: &Trade) -> Pubkey {
+    pda::interchain_offer(&trade.maker.pubkey(), OFFER_ID)
+}
+
+async fn offer(harness: &mut Harness, trade: &Trade) -> InterchainOffer {
+    harness.account(offer_address(trade)).await.unwrap()
+}
+
+async fn dispute(harness: &mut Harness, trade: &Trade) -> Option<Dispute> {
+    harness.account(pda::dispute(&offer_address(trade))).await
+}
+
+/// Rewrites the buyer recorded on the trade's offer
+async fn record_buyer(harness: &mut Harness, trade: &Trade, buyer: Option<Pubkey>) {
+    let mut offer = offer(harness, trade).await;
+    offer.buyer = buyer;
+    harness.store_account(offer_address(trade), &offer).await;
+}
+
+/// `buyer` finalizes the trade's offer under `computation_offset`
+fn finalize(
+    harness: &Harness,
+    trade: &Trade,
+    buyer: &Keypair,
+    computation_offset: u64,
+) -> solana_sdk::instruction::Instruction {
+    let cipher = harness.cipher(&X25519Keypair::from_secret([2; 32]));
+    let identity = cipher.encrypt_with_nonce([Fe::from(identity_hash(buyer.pubkey().as_ref()))], 1);
+    instructions::finalize_interchain_origin_evm_offer(
+        buyer.pubkey(),
+        trade.maker.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::FinalizeInterchainOriginEvmOffer {
+            id: OFFER_ID,
+            ciphertext_buyer_identity_hash: identity.ciphertexts[0],
+            pub_key: identity.pub_key,
+            nonce: identity.nonce,
+            computation_offset,
+        },
+    )
+}
+
+#[tokio::test]
+async fn arbiter_splits_the_frozen_escrow() {
+    let mut harness = Harness::start().await;
+    let trade = trade(&mut harness).await;
+
+    let open = open_dispute(&trade, &trade.buyer);
+    harness.process(&[open], &[&trade.buyer]).await.unwrap();
+    assert_eq!(offer(&mut harness, &trade).await.status, OfferStatus::Disputed);
+    let opened = dispute(&mut harness, &trade).await.unwrap();
+    assert_eq!((opened.opened_by, opened.bond), (trade.buyer.pubkey(), DISPUTE_BOND));
+
+    // Escrow is frozen
+    let cancel =
+        instructions::cancel_interchain_offer(trade.maker.pubkey(), OFFER_ID, CHAIN_ID, 0);
+    assert!(harness.process(&[cancel], &[&trade.maker]).await.is_err());
+
+    // Only a registered arbiter rules, and never beyond the whole escrow
+    let disputed = offer(&mut harness, &trade).await;
+    let rule = |arbiter: &Keypair, maker_share_bps| {
+        instructions::rule_dispute(arbiter.pubkey(), &disputed, &opened, 0, maker_share_bps, false)
+    };
+    let by_maker = rule(&trade.maker, 10_000);
+    assert!(harness.process(&[by_maker], &[&trade.maker]).await.is_err());
+    let too_much = rule(&trade.arbiter, 10_001);
+    assert!(harness.process(&[too_much], &[&trade.arbiter]).await.is_err());
+
+    let maker_before = harness.balance(trade.maker.pubkey()).await;
+    let buyer_before = harness.balance(trade.buyer.pubkey()).await;
+    let dispute_rent = harness.balance(pda::dispute(&offer_address(&trade))).await - DISPUTE_BOND;
+    // A quarter of the escrow to the maker, and the dismissed dispute's bond too
+    harness.process(&[rule(&trade.arbiter, 2_500)], &[&trade.arbiter]).await.unwrap();
+
+    let escrow = TOKEN_A + TOKEN_B;
+    assert_eq!(
+        harness.balance(trade.maker.pubkey()).await,
+        maker_before + escrow / 4 + DISPUTE_BOND
+    );
+    // The opener gets the closed dispute's rent back
+    assert_eq!(
+        harness.balance(trade.buyer.pubkey()).await,
+        buyer_before + escrow * 3 / 4 + dispute_rent
+    );
+    assert_eq!(offer(&mut harness, &trade).await.status, OfferStatus::Settled);
+    assert!(dispute(&mut harness, &trade).await.is_none());
+
+    let message: OutboundMessage = harness
+        .account(pda::outbound_message(CHAIN_ID, 0))
+        .await
+        .unwrap();
+    assert_eq!(message.kind, OutboundMessageKind::Ruling);
+    let chain: ChainConfig = harness.account(pda::chain_config(CHAIN_ID)).await.unwrap();
+    assert_eq!(chain.outbound_sequence, 1);
+}
+
+#[tokio::test]
+async fn only_parties_open_disputes_before_the_deadline() {
+    let mut harness = Harness::start().await;
+    let trade = trade(&mut harness).await;
+    let stranger = harness.funded_keypair().await;
+
+    let by_stranger = open_dispute(&trade, &stranger);
+    assert!(harness.process(&[by_stranger], &[&stranger]).await.is_err());
+    // Funding a buyer vault does not make the stranger the offer's buyer
+    let deposit = instructions::deposit_to_buyer_vault(
+        stranger.pubkey(),
+        trade.maker.pubkey(),
+        OfferKind::Interchain,
+        OFFER_ID,
+        TOKEN_B,
+    );
+    harness.process(&[deposit], &[&stranger]).await.unwrap();
+    let as_buyer = instructions::open_dispute(
+        stranger.pubkey(),
+        trade.maker.pubkey(),
+        instruction::OpenDispute {
+            offer_id: OFFER_ID,
+            buyer: stranger.pubkey(),
+        },
+    );
+    assert!(harness.process(&[as_buyer], &[&stranger]).await.is_err());
+    // The buyer has to have escrowed something
+    let unfunded_buyer = harness.funded_keypair().await;
+    record_buyer(&mut harness, &trade, Some(unfunded_buyer.pubkey())).await;
+    let unfunded = instructions::open_dispute(
+        trade.maker.pubkey(),
+        trade.maker.pubkey(),
+        instruction::OpenDispute {
+            offer_id: OFFER_ID,
+            buyer: unfunded_buyer.pubkey(),
+        },
+    );
+    assert!(harness.process(&[unfunded], &[&trade.maker]).await.is_err());
+    record_buyer(&mut harness, &trade, Some(trade.buyer.pubkey())).await;
+
+    harness.set_unix_timestamp(trade.deadline + 1).await;
+    let late = open_dispute(&trade, &trade.maker);
+    assert!(harness.process(&[late], &[&trade.maker]).await.is_err());
+    assert_eq!(offer(&mut harness, &trade).await.status, OfferStatus::Open);
+}
+
+#[tokio::test]
+async fn unruled_dispute_expires_and_the_offer_reopens() {
+    let mut harness = Harness::start().await;
+    let trade = trade(&mut harness).await;
+
+    let maker_before = harness.balance(trade.maker.pubkey()).await;
+    let open = open_dispute(&trade, &trade.maker);
+    harness.process(&[open], &[&trade.maker]).await.unwrap();
+    let opened = dispute(&mut harness, &trade).await.unwrap();
+
+    let expire = instructions::expire_dispute(&opened);
+    assert!(harness.process(std::slice::from_ref(&expire), &[]).await.is_err());
+
+    let slot = harness.clock().await.slot;
+    harness.warp_to_slot(slot + 1);
+    harness.set_unix_timestamp(opened.ruling_deadline + 1).await;
+    let disputed = offer(&mut harness, &trade).await;
+    let rule =
+        instructions::rule_dispute(trade.arbiter.pubkey(), &disputed, &opened, 0, 0, true);
+    assert!(harness.process(&[rule], &[&trade.arbiter]).await.is_err());
+    harness.process(&[expire], &[]).await.unwrap();
+
+    assert_eq!(offer(&mut harness, &trade).await.status, OfferStatus::Open);
+    // The dispute is closed, its bond and rent back with the opener
+    assert!(dispute(&mut harness, &trade).await.is_none());
+    assert_eq!(harness.balance(trade.maker.pubkey()).await, maker_before);
+
+    // Past its deadline, the reopened offer is refunded as usual
+    let refund = instructions::refund_interchain_offer(
+        harness.payer(),
+        trade.maker.pubkey(),
+        OFFER_ID,
+        CHAIN_ID,
+        0,
+    );
+    harness.process(&[refund], &[]).await.unwrap();
+    assert_eq!(offer(&mut harness, &trade).await.status, OfferStatus::Refunded);
+}
+
+#[tokio::test]
+async fn only_the_finalized_buyer_disputes() {
+    let mut harness = Harness::start().await;
+    let trade = trade(&mut harness).await;
+    record_buyer(&mut harness, &trade, None).await;
+
+    // A funded vault alone does not make a buyer
+    let before_finalize = open_dispute(&trade, &trade.buyer);
+    assert!(harness.process(&[before_finalize], &[&trade.buyer]).await.is_err());
+
+    let finalize_ix = finalize(&harness, &trade, &trade.buyer, 1);
+    harness.process(&[finalize_ix], &[&trade.buyer]).await.unwrap();
+    harness.run_computations().await.unwrap();
+    assert_eq!(offer(&mut harness, &trade).await.buyer, Some(trade.buyer.pubkey()));
+
+    // Nobody else finalizes after the buyer
+    let rival = harness.funded_keypair().await;
+    let by_rival = finalize(&harness, &trade, &rival, 2);
+    assert!(harness.process(&[by_rival], &[&rival]).await.is_err());
+
+    let open = open_dispute(&trade, &trade.buyer);
+    harness.process(&[open], &[&trade.buyer]).await.unwrap();
+    assert_eq!(offer(&mut harness, &trade).await.status, OfferStatus::Disputed);
+}
+
+#[tokio::test]
+async fn competing_finalize_keeps_the_first_verified_buyer() {
+    let mut harness = Harness::start().await;
+    let trade = trade(&mut harness).await;
+    record_buyer(&mut harness, &trade, None).await;
+
+    // Both are queued before either is verified
+    let rival = harness.funded_keypair().await;
+    let by_buyer = finalize(&harness, &trade, &trade.buyer, 1);
+    let by_rival = finalize(&harness, &trade, &rival, 2);
+    harness.process(&[by_buyer], &[&trade.buyer]).await.unwrap();
+    harness.process(&[by_rival], &[&rival]).await.unwrap();
+    harness.run_computations().await.unwrap();
+
+    assert_eq!(offer(&mut harness, &trade).await.buyer, Some(trade.buyer.pubkey()));
+    assert!(harness.account::<PendingComputation>(pda::pending_computation(2)).await.is_none());
+    let open = open_dispute(&trade, &trade.buyer);
+    harness.process(&[open], &[&trade.buyer]).await.unwrap();
+}
+
+#[tokio::test]
+async fn expired_dispute_can_be_opened_again() {
+    let mut harness = Harness::start().await;
+    let trade = trade(&mut harness).await;
+    let open = open_dispute(&trade, &trade.buyer);
+    harness.process(std::slice::from_ref(&open), &[&trade.buyer]).await.unwrap();
+    let opened = dispute(&mut harness, &trade).await.unwrap();
+
+    // Ruling windows shorter than the offer's leave time to dispute again
+    let mut disputed = offer(&mut harness, &trade).await;
+    disputed.deadline = opened.ruling_deadline + 600;
+    harness.store_account(offer_address(&trade), &disputed).await;
+    harness.next_slot().await;
+    harness.set_unix_timestamp(opened.ruling_deadline + 1).await;
+    harness.process(&[instructions::expire_dispute(&opened)], &[]).await.unwrap();
+
+    harness.next_slot().await;
+    harness.process(&[open], &[&trade.buyer]).await.unwrap();
+    assert_eq!(offer(&mut harness, &trade).await.status, OfferStatus::Disputed);
+    assert_eq!(dispute(&mut harness, &trade).await.unwrap().bond, DISPUTE_BOND);
+}
@@ -0,0 +1,222 @@
+use confidential_exchange_client::{
+    crypto::{pack_fiat_payment_details, unpack_fiat_payment_details, X25519Keypair},
+    instructions,
+    pda,
+    program::{instruction, Dispute, DisputeStatus, FiatOffer, FiatStage, OfferStatus},
+};
+use mpc_harness::{Harness, CLUSTER_OFFSET};
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    signature::Keypair,
+    signer::Signer,
+};
+
+const OFFER_ID: u64 = 1;
+const AMOUNT: u64 = LAMPORTS_PER_SOL;
+const PAYMENT_WINDOW_SECS: i64 = 1_800;
+const RELEASE_WINDOW_SECS: i64 = 3_600;
+const DISPUTE_BOND: u64 = LAMPORTS_PER_SOL / 10;
+const PAYMENT_DETAILS: &[u8] = b"IBAN DE89 3704 0044 0532 0130 00, ref 4711";
+
+/// Lists `seller`'s fiat offer of `AMOUNT` with `PAYMENT_DETAILS`, stored
+/// under computation 1
+async fn list(harness: &mut Harness, seller: &Keypair) {
+    let cipher = harness.cipher(&X25519Keypair::from_secret([1; 32]));
+    let details = cipher.encrypt_with_nonce(pack_fiat_payment_details(PAYMENT_DETAILS).unwrap(), 1);
+    let create = instructions::create_fiat_offer(
+        seller.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::CreateFiatOffer {
+            id: OFFER_ID,
+            amount: AMOUNT,
+            fiat_currency: *b"EUR",
+            fiat_amount: 95_00,
+            payment_window_secs: PAYMENT_WINDOW_SECS,
+            release_window_secs: RELEASE_WINDOW_SECS,
+            ciphertext_payment_details: details.ciphertexts,
+            pub_key: details.pub_key,
+            nonce: details.nonce,
+            mxe_nonce: 7,
+            computation_offset: 1,
+        },
+    );
+    harness.process(&[create], &[seller]).await.unwrap();
+    harness.run_computations().await.unwrap();
+}
+
+/// Locks `seller`'s offer for `buyer` under `computation_offset`
+async fn lock(
+    harness: &mut Harness,
+    seller: &Keypair,
+    buyer: &Keypair,
+    computation_offset: u64,
+) -> Result<(), solana_program_test::BanksClientError> {
+    let keypair = X25519Keypair::from_secret([computation_offset as u8; 32]);
+    let lock = instructions::lock_fiat_offer(
+        buyer.pubkey(),
+        seller.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::LockFiatOffer {
+            offer_id: OFFER_ID,
+            pub_key: keypair.public_key(),
+            nonce: 11,
+            computation_offset,
+        },
+    );
+    harness.process(&[lock], &[buyer]).await
+}
+
+async fn offer(harness: &mut Harness, seller: &Keypair) -> FiatOffer {
+    harness
+        .account(pda::fiat_offer(&seller.pubkey(), OFFER_ID))
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn paid_offer_is_released_to_the_buyer() {
+    let mut harness = Harness::start().await;
+    let seller = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    list(&mut harness, &seller).await;
+
+    // Only the MXE can read the stored details
+    let listed = offer(&mut harness, &seller).await;
+    assert_eq!((listed.status, listed.stage), (OfferStatus::Open, FiatStage::Listed));
+    let stored = harness
+        .mxe_cipher()
+        .decrypt(&listed.payment_details, listed.payment_details_nonce);
+    assert_eq!(unpack_fiat_payment_details(&stored), PAYMENT_DETAILS);
+
+    // The seller cannot buy their own offer
+    assert!(lock(&mut harness, &seller, &seller, 2).await.is_err());
+    lock(&mut harness, &seller, &buyer, 2).await.unwrap();
+    let locked = offer(&mut harness, &seller).await;
+    assert_eq!((locked.status, locked.buyer), (OfferStatus::Pending, Some(buyer.pubkey())));
+    let cancel = instructions::cancel_fiat_offer(seller.pubkey(), OFFER_ID);
+    assert!(harness.process(&[cancel], &[&seller]).await.is_err());
+
+    // The payment window starts once the details reach the buyer
+    harness.run_computations().await.unwrap();
+    let revealed = offer(&mut harness, &seller).await;
+    assert_eq!(revealed.status, OfferStatus::Open);
+    assert_eq!(
+        revealed.stage_deadline,
+        harness.clock().await.unix_timestamp + PAYMENT_WINDOW_SECS
+    );
+
+    let stranger = harness.funded_keypair().await;
+    let forged = instructions::mark_fiat_offer_paid(stranger.pubkey(), seller.pubkey(), OFFER_ID);
+    assert!(harness.process(&[forged], &[&stranger]).await.is_err());
+    let paid = instructions::mark_fiat_offer_paid(buyer.pubkey(), seller.pubkey(), OFFER_ID);
+    harness.process(&[paid], &[&buyer]).await.unwrap();
+    assert_eq!(offer(&mut harness, &seller).await.stage, FiatStage::Paid);
+
+    let buyer_before = harness.balance(buyer.pubkey()).await;
+    let release = instructions::release_fiat_offer(seller.pubkey(), buyer.pubkey(), OFFER_ID);
+    harness.process(&[release], &[&seller]).await.unwrap();
+    assert_eq!(harness.balance(buyer.pubkey()).await, buyer_before + AMOUNT);
+    assert_eq!(offer(&mut harness, &seller).await.status, OfferStatus::Settled);
+}
+
+#[tokio::test]
+async fn unreleased_payment_is_escalated_to_an_arbiter() {
+    let mut harness = Harness::start().await;
+    let seller = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let arbiter = harness.funded_keypair().await;
+    let admin = harness.payer();
+    let setup = [
+        instructions::initialize_exchange_config(admin),
+        instructions::set_arbiter_registry(
+            admin,
+            instruction::SetArbiterRegistry {
+                arbiters: vec![arbiter.pubkey()],
+                dispute_bond: DISPUTE_BOND,
+                dispute_window_secs: 3_600,
+            },
+        ),
+    ];
+    harness.process(&setup, &[]).await.unwrap();
+    list(&mut harness, &seller).await;
+    lock(&mut harness, &seller, &buyer, 2).await.unwrap();
+    harness.run_computations().await.unwrap();
+    let paid = instructions::mark_fiat_offer_paid(buyer.pubkey(), seller.pubkey(), OFFER_ID);
+    harness.process(&[paid], &[&buyer]).await.unwrap();
+
+    // The seller still has time to release
+    let escalate = instructions::escalate_fiat_offer(buyer.pubkey(), seller.pubkey(), OFFER_ID);
+    assert!(harness.process(std::slice::from_ref(&escalate), &[&buyer]).await.is_err());
+
+    harness.next_slot().await;
+    let release_deadline = offer(&mut harness, &seller).await.stage_deadline;
+    harness.set_unix_timestamp(release_deadline + 1).await;
+    harness.process(&[escalate], &[&buyer]).await.unwrap();
+    assert_eq!(offer(&mut harness, &seller).await.status, OfferStatus::Disputed);
+    let opened: Dispute = harness
+        .account(pda::dispute(&pda::fiat_offer(&seller.pubkey(), OFFER_ID)))
+        .await
+        .unwrap();
+    assert_eq!((opened.buyer, opened.bond), (buyer.pubkey(), DISPUTE_BOND));
+
+    // Escrow is frozen
+    let release = instructions::release_fiat_offer(seller.pubkey(), buyer.pubkey(), OFFER_ID);
+    assert!(harness.process(&[release], &[&seller]).await.is_err());
+
+    // The buyer proved the payment: all of the escrow and their bond back
+    let buyer_before = harness.balance(buyer.pubkey()).await;
+    let by_seller = instructions::rule_fiat_dispute(seller.pubkey(), &opened, 10_000, false);
+    assert!(harness.process(&[by_seller], &[&seller]).await.is_err());
+    let rule = instructions::rule_fiat_dispute(arbiter.pubkey(), &opened, 0, true);
+    harness.process(&[rule], &[&arbiter]).await.unwrap();
+
+    assert_eq!(
+        harness.balance(buyer.pubkey()).await,
+        buyer_before + AMOUNT + DISPUTE_BOND
+    );
+    assert_eq!(offer(&mut harness, &seller).await.status, OfferStatus::Settled);
+    let ruled: Dispute = harness.account(pda::dispute(&opened.offer)).await.unwrap();
+    assert_eq!(ruled.status, DisputeStatus::Ruled);
+}
+
+#[tokio::test]
+async fn aborted_reveal_relists_the_offer() {
+    let mut harness = Harness::start().await;
+    let seller = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    list(&mut harness, &seller).await;
+
+    lock(&mut harness, &seller, &buyer, 2).await.unwrap();
+    harness.abort_computations().await.unwrap();
+    let relisted = offer(&mut harness, &seller).await;
+    assert_eq!((relisted.status, relisted.stage), (OfferStatus::Open, FiatStage::Listed));
+    assert_eq!(relisted.buyer, None);
+
+    // Listed again, so the seller may withdraw it
+    let seller_before = harness.balance(seller.pubkey()).await;
+    let cancel = instructions::cancel_fiat_offer(seller.pubkey(), OFFER_ID);
+    harness.process(&[cancel], &[&seller]).await.unwrap();
+    assert_eq!(harness.balance(seller.pubkey()).await, seller_before + AMOUNT);
+    assert_eq!(offer(&mut harness, &seller).await.status, OfferStatus::Cancelled);
+}
+
+#[tokio::test]
+async fn stale_reveal_relists_the_offer() {
+    let mut harness = Harness::start().await;
+    let seller = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    list(&mut harness, &seller).await;
+
+    lock(&mut harness, &seller, &buyer, 2).await.unwrap();
+    harness.stall_computations().await.unwrap();
+    let relisted = offer(&mut harness, &seller).await;
+    assert_eq!((relisted.status, relisted.stage), (OfferStatus::Open, FiatStage::Listed));
+    assert_eq!(relisted.buyer, None);
+
+    // Listed again, so the seller may withdraw it
+    let seller_before = harness.balance(seller.pubkey()).await;
+    let cancel = instructions::cancel_fiat_offer(seller.pubkey(), OFFER_ID);
+    harness.process(&[cancel], &[&seller]).await.unwrap();
+    assert_eq!(harness.balance(seller.pubkey()).await, seller_before + AMOUNT);
+    assert_eq!(offer(&mut harness, &seller).await.status, OfferStatus::Cancelled);
+}
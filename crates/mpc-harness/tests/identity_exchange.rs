@@ -0,0 +1,189 @@
+use anchor_lang::prelude::Pubkey;
+use confidential_exchange_client::{
+    crypto::{pack_trade_identity, unpack_trade_identity, X25519Keypair},
+    instructions,
+    pda,
+    program::{instruction, IdentityExchange, OfferKind, OfferStatus},
+};
+use mpc_harness::{Harness, CLUSTER_OFFSET};
+use solana_sdk::{instruction::Instruction, signature::Keypair, signer::Signer};
+
+const OFFER_ID: u64 = 1;
+const SELLER_IDENTITY: &[u8] = b"Seller GmbH, Hauptstrasse 1, Berlin";
+const BUYER_IDENTITY: &[u8] = b"Jane Buyer, 1 Main St, Springfield";
+
+fn open(seller: &Keypair, trade: Pubkey, buyer: Pubkey) -> Instruction {
+    instructions::open_identity_exchange(
+        seller.pubkey(),
+        OfferKind::Intrachain,
+        instruction::OpenIdentityExchange { trade, buyer },
+    )
+}
+
+/// Shares `identity` as `party` under `computation_offset`, which also
+/// seeds the party's x25519 key
+async fn share(
+    harness: &mut Harness,
+    party: &Keypair,
+    trade: Pubkey,
+    identity: &[u8],
+    computation_offset: u64,
+) -> Result<(), solana_program_test::BanksClientError> {
+    let cipher = harness.cipher(&X25519Keypair::from_secret([computation_offset as u8; 32]));
+    let identity = cipher.encrypt_with_nonce(pack_trade_identity(identity).unwrap(), 1);
+    let share = instructions::share_trade_identity(
+        party.pubkey(),
+        trade,
+        CLUSTER_OFFSET,
+        instruction::ShareTradeIdentity {
+            ciphertext_identity: identity.ciphertexts,
+            pub_key: identity.pub_key,
+            nonce: identity.nonce,
+            reveal_nonce: 3,
+            mxe_nonce: 5,
+            computation_offset,
+        },
+    );
+    harness.process(&[share], &[party]).await
+}
+
+fn reveal(party: &Keypair, trade: Pubkey, computation_offset: u64) -> Instruction {
+    instructions::reveal_trade_identities(
+        party.pubkey(),
+        trade,
+        CLUSTER_OFFSET,
+        instruction::RevealTradeIdentities { computation_offset },
+    )
+}
+
+async fn exchange(harness: &mut Harness, trade: Pubkey) -> IdentityExchange {
+    harness.account(pda::identity_exchange(&trade)).await.unwrap()
+}
+
+#[tokio::test]
+async fn parties_exchange_identities_after_settlement() {
+    let mut harness = Harness::start().await;
+    let seller = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let stranger = harness.funded_keypair().await;
+    let trade = harness
+        .store_intrachain_offer(
+            &seller.pubkey(),
+            OFFER_ID,
+            OfferStatus::Settled,
+            Some(buyer.pubkey()),
+        )
+        .await;
+
+    // Only the seller opens it, and only with the offer's buyer
+    let by_stranger = open(&stranger, trade, buyer.pubkey());
+    assert!(harness.process(&[by_stranger], &[&stranger]).await.is_err());
+    let with_self = open(&seller, trade, seller.pubkey());
+    assert!(harness.process(&[with_self], &[&seller]).await.is_err());
+    let with_stranger = open(&seller, trade, stranger.pubkey());
+    assert!(harness.process(&[with_stranger], &[&seller]).await.is_err());
+    harness.process(&[open(&seller, trade, buyer.pubkey())], &[&seller]).await.unwrap();
+    let opened = exchange(&mut harness, trade).await;
+    assert_eq!((opened.seller, opened.buyer), (seller.pubkey(), buyer.pubkey()));
+    assert_eq!((opened.trade_kind, opened.trade_id), (OfferKind::Intrachain, OFFER_ID));
+
+    assert!(share(&mut harness, &stranger, trade, b"nobody", 1).await.is_err());
+    share(&mut harness, &seller, trade, SELLER_IDENTITY, 2).await.unwrap();
+    harness.run_computations().await.unwrap();
+    // Nothing is revealed until both shared
+    let early = reveal(&buyer, trade, 3);
+    assert!(harness.process(&[early], &[&buyer]).await.is_err());
+    assert!(share(&mut harness, &seller, trade, SELLER_IDENTITY, 4).await.is_err());
+    share(&mut harness, &buyer, trade, BUYER_IDENTITY, 5).await.unwrap();
+    harness.run_computations().await.unwrap();
+
+    // Stored identities are only readable by the MXE
+    let shared = exchange(&mut harness, trade).await;
+    assert!(shared.seller_shared && shared.buyer_shared);
+    let mxe = harness.mxe_cipher();
+    let seller_identity = mxe.decrypt(&shared.seller_identity, shared.seller_identity_nonce);
+    let buyer_identity = mxe.decrypt(&shared.buyer_identity, shared.buyer_identity_nonce);
+    assert_eq!(unpack_trade_identity(&seller_identity), SELLER_IDENTITY);
+    assert_eq!(unpack_trade_identity(&buyer_identity), BUYER_IDENTITY);
+
+    let by_stranger = reveal(&stranger, trade, 6);
+    assert!(harness.process(&[by_stranger], &[&stranger]).await.is_err());
+    harness.process(&[reveal(&buyer, trade, 7)], &[&buyer]).await.unwrap();
+    assert_eq!(exchange(&mut harness, trade).await.status, OfferStatus::Pending);
+    harness.run_computations().await.unwrap();
+    assert_eq!(exchange(&mut harness, trade).await.status, OfferStatus::Settled);
+}
+
+#[tokio::test]
+async fn only_settled_trades_exchange_identities() {
+    let mut harness = Harness::start().await;
+    let seller = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let trade = harness
+        .store_intrachain_offer(
+            &seller.pubkey(),
+            OFFER_ID,
+            OfferStatus::Open,
+            Some(buyer.pubkey()),
+        )
+        .await;
+
+    let open = open(&seller, trade, buyer.pubkey());
+    assert!(harness.process(&[open], &[&seller]).await.is_err());
+    assert!(harness
+        .account::<IdentityExchange>(pda::identity_exchange(&trade))
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn aborted_share_can_be_retried() {
+    let mut harness = Harness::start().await;
+    let seller = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let trade = harness
+        .store_intrachain_offer(
+            &seller.pubkey(),
+            OFFER_ID,
+            OfferStatus::Settled,
+            Some(buyer.pubkey()),
+        )
+        .await;
+    harness.process(&[open(&seller, trade, buyer.pubkey())], &[&seller]).await.unwrap();
+
+    share(&mut harness, &buyer, trade, BUYER_IDENTITY, 1).await.unwrap();
+    harness.abort_computations().await.unwrap();
+    let aborted = exchange(&mut harness, trade).await;
+    assert_eq!(aborted.status, OfferStatus::Open);
+    assert!(!aborted.buyer_shared);
+
+    share(&mut harness, &buyer, trade, BUYER_IDENTITY, 2).await.unwrap();
+    harness.run_computations().await.unwrap();
+    assert!(exchange(&mut harness, trade).await.buyer_shared);
+}
+
+#[tokio::test]
+async fn stale_share_can_be_retried() {
+    let mut harness = Harness::start().await;
+    let seller = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let trade = harness
+        .store_intrachain_offer(
+            &seller.pubkey(),
+            OFFER_ID,
+            OfferStatus::Settled,
+            Some(buyer.pubkey()),
+        )
+        .await;
+    harness.process(&[open(&seller, trade, buyer.pubkey())], &[&seller]).await.unwrap();
+
+    share(&mut harness, &buyer, trade, BUYER_IDENTITY, 1).await.unwrap();
+    harness.stall_computations().await.unwrap();
+    let aborted = exchange(&mut harness, trade).await;
+    assert_eq!(aborted.status, OfferStatus::Open);
+    assert!(!aborted.buyer_shared);
+
+    share(&mut harness, &buyer, trade, BUYER_IDENTITY, 2).await.unwrap();
+    harness.run_computations().await.unwrap();
+    assert!(exchange(&mut harness, trade).await.buyer_shared);
+}
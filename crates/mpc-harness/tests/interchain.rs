@@ -0,0 +1,200 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token::spl_token::native_mint;
+use confidential_exchange_client::{
+    instructions,
+    pda,
+    program::{
+        instruction,
+        versioning::VersionedAccount,
+        ChainConfig,
+        InterchainOffer,
+        OfferKind,
+        OfferStatus,
+    },
+    PROGRAM_ID,
+};
+use mpc_harness::Harness;
+use solana_sdk::{
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    signature::Keypair,
+    signer::Signer,
+};
+
+const CHAIN_ID: u64 = 1;
+const OFFER_ID: u64 = 1;
+const TOKEN_A: u64 = LAMPORTS_PER_SOL;
+const TOKEN_B: u64 = 2 * LAMPORTS_PER_SOL;
+/// Bridged SOL on the EVM chain, at 18 decimals
+const WRAPPED_SOL: [u8; 20] = [0x50; 20];
+/// A token whose Solana side is an SPL mint, which lamport vaults cannot pay
+const STABLECOIN: [u8; 20] = [0xdc; 20];
+
+fn set_mapping(
+    admin: Pubkey,
+    evm_token: [u8; 20],
+    solana_mint: Pubkey,
+    solana_decimals: u8,
+) -> Instruction {
+    instructions::set_token_mapping(
+        admin,
+        instruction::SetTokenMapping {
+            chain_id: CHAIN_ID,
+            evm_token,
+            asset_id: 1,
+            evm_decimals: 18,
+            solana_mint: Some(solana_mint),
+            solana_decimals,
+        },
+    )
+}
+
+/// Registers the chain with both tokens mapped
+async fn register_chain(harness: &mut Harness) {
+    let admin = harness.payer();
+    let chain = instruction::SetChainConfig {
+        chain_id: CHAIN_ID,
+        enabled: true,
+        finality_blocks: 12,
+        min_deadline_secs: 60,
+        max_deadline_secs: 86_400,
+        relayers: vec![],
+        allowed_tokens: vec![WRAPPED_SOL, STABLECOIN],
+    };
+    let mappings = vec![
+        set_mapping(admin, WRAPPED_SOL, native_mint::ID, 9),
+        set_mapping(admin, STABLECOIN, Pubkey::new_unique(), 6),
+    ];
+    harness.register_chain(chain, mappings).await;
+}
+
+/// Stores `maker`'s open offer of `TOKEN_A` for `TOKEN_B` worth of
+/// `evm_token`, as a relayer would have left it, and funds both vaults
+async fn open_offer(
+    harness: &mut Harness,
+    maker: &Keypair,
+    buyer: &Keypair,
+    evm_token: [u8; 20],
+) -> InterchainOffer {
+    let (address, bump) = Pubkey::find_program_address(
+        &[b"InterChainoffer", maker.pubkey().as_ref(), &OFFER_ID.to_le_bytes()],
+        &PROGRAM_ID,
+    );
+    let mut token_b_wanted_amount = [0; 32];
+    let wei = u128::from(TOKEN_B) * 1_000_000_000;
+    token_b_wanted_amount[16..].copy_from_slice(&wei.to_be_bytes());
+    let offer = InterchainOffer {
+        layout_version: InterchainOffer::LAYOUT_VERSION,
+        id: OFFER_ID,
+        token_a_offered_amount: TOKEN_A,
+        token_b_wanted_amount,
+        is_taker_native: true,
+        chain_id: CHAIN_ID,
+        deadline: harness.clock().await.unix_timestamp + 600,
+        token_mapping: pda::token_mapping(CHAIN_ID, &evm_token),
+        maker: maker.pubkey(),
+        buyer: Some(buyer.pubkey()),
+        relayer: Pubkey::default(),
+        slashed: false,
+        status: OfferStatus::Open,
+        computation_offset: 0,
+        bump,
+    };
+    harness.store_account(address, &offer).await;
+
+    let deposits = [
+        instructions::deposit_to_seller_vault(
+            maker.pubkey(),
+            OfferKind::Interchain,
+            OFFER_ID,
+            TOKEN_A,
+        ),
+        instructions::deposit_to_buyer_vault(
+            buyer.pubkey(),
+            maker.pubkey(),
+            OfferKind::Interchain,
+            OFFER_ID,
+            TOKEN_B,
+        ),
+    ];
+    harness.process(&deposits, &[maker, buyer]).await.unwrap();
+    offer
+}
+
+async fn execute(
+    harness: &mut Harness,
+    maker: &Keypair,
+    buyer: &Keypair,
+    offer: &InterchainOffer,
+) -> Result<(), solana_program_test::BanksClientError> {
+    let chain: ChainConfig = harness.account(pda::chain_config(CHAIN_ID)).await.unwrap();
+    let execute = instructions::execute_interchain_swap(
+        buyer.pubkey(),
+        offer,
+        chain.outbound_sequence,
+        [0; 32],
+        None,
+    );
+    harness.process(&[execute], &[maker, buyer]).await
+}
+
+#[tokio::test]
+async fn settles_token_b_mapped_to_native_sol() {
+    let mut harness = Harness::start().await;
+    register_chain(&mut harness).await;
+    let maker = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let offer = open_offer(&mut harness, &maker, &buyer, WRAPPED_SOL).await;
+
+    let maker_before = harness.balance(maker.pubkey()).await;
+    execute(&mut harness, &maker, &buyer, &offer).await.unwrap();
+    let settled: InterchainOffer = harness
+        .account(pda::interchain_offer(&maker.pubkey(), OFFER_ID))
+        .await
+        .unwrap();
+    assert_eq!(settled.status, OfferStatus::Settled);
+    assert_eq!(harness.balance(maker.pubkey()).await, maker_before + TOKEN_B);
+}
+
+#[tokio::test]
+async fn lamport_vaults_refuse_spl_mapped_token_b() {
+    let mut harness = Harness::start().await;
+    register_chain(&mut harness).await;
+    let maker = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let offer = open_offer(&mut harness, &maker, &buyer, STABLECOIN).await;
+
+    assert!(execute(&mut harness, &maker, &buyer, &offer).await.is_err());
+    let open: InterchainOffer = harness
+        .account(pda::interchain_offer(&maker.pubkey(), OFFER_ID))
+        .await
+        .unwrap();
+    assert_eq!(open.status, OfferStatus::Open);
+
+    // Native SOL has 9 decimals, a mapping cannot claim otherwise
+    let misscaled = set_mapping(harness.payer(), WRAPPED_SOL, native_mint::ID, 6);
+    assert!(harness.process(&[misscaled], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn only_the_verified_buyer_settles() {
+    let mut harness = Harness::start().await;
+    register_chain(&mut harness).await;
+    let maker = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let offer = open_offer(&mut harness, &maker, &buyer, WRAPPED_SOL).await;
+
+    // A funded vault of its own does not let a stranger take the offer
+    let stranger = harness.funded_keypair().await;
+    let deposit = instructions::deposit_to_buyer_vault(
+        stranger.pubkey(),
+        maker.pubkey(),
+        OfferKind::Interchain,
+        OFFER_ID,
+        TOKEN_B,
+    );
+    harness.process(&[deposit], &[&stranger]).await.unwrap();
+    assert!(execute(&mut harness, &maker, &stranger, &offer).await.is_err());
+
+    execute(&mut harness, &maker, &buyer, &offer).await.unwrap();
+}
@@ -0,0 +1,310 @@
+use anchor_lang::prelude::Pubkey;
+use confidential_exchange_client::{
+    crypto::{identity_hash, X25519Keypair},
+    instructions,
+    pda,
+    program::{instruction, IntraChainOffer, OfferKind, OfferStatus, PendingComputation},
+    Fe,
+};
+use mpc_harness::{Harness, CLUSTER_OFFSET};
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Keypair, signer::Signer};
+
+const OFFER_ID: u64 = 1;
+const TOKEN_A: u64 = LAMPORTS_PER_SOL;
+const TOKEN_B: u64 = 2 * LAMPORTS_PER_SOL;
+
+/// Queues `deposit_seller_native` for `maker`'s offer under computation 1
+async fn create_offer(harness: &mut Harness, maker: &Keypair) {
+    let cipher = harness.cipher(&X25519Keypair::from_secret([1; 32]));
+    let identity = cipher.encrypt_with_nonce([Fe::from(identity_hash(maker.pubkey().as_ref()))], 1);
+    let create = instructions::deposit_seller_native(
+        maker.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::DepositSellerNative {
+            id: OFFER_ID,
+            token_b_wanted_amount: TOKEN_B,
+            token_a_offered_amount: TOKEN_A,
+            is_taker_native: true,
+            deadline: i64::MAX,
+            ciphertext_seller_identity_hash: identity.ciphertexts[0],
+            pub_key: identity.pub_key,
+            nonce: identity.nonce,
+            computation_offset: 1,
+        },
+    );
+    let deposit = instructions::deposit_to_seller_vault(
+        maker.pubkey(),
+        OfferKind::Intrachain,
+        OFFER_ID,
+        TOKEN_A,
+    );
+    harness.process(&[create, deposit], &[maker]).await.unwrap();
+}
+
+/// `buyer` finalizes `maker`'s offer with their identity under
+/// `computation_offset`, which also seeds their x25519 key, and escrows
+/// token B
+async fn finalize_and_deposit(
+    harness: &mut Harness,
+    maker: &Keypair,
+    buyer: &Keypair,
+    computation_offset: u64,
+) -> Result<(), solana_program_test::BanksClientError> {
+    let cipher = harness.cipher(&X25519Keypair::from_secret([computation_offset as u8; 32]));
+    let identity = cipher.encrypt_with_nonce([Fe::from(identity_hash(buyer.pubkey().as_ref()))], 1);
+    let finalize = instructions::finalize_intrachain_offer(
+        buyer.pubkey(),
+        maker.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::FinalizeIntrachainOffer {
+            id: OFFER_ID,
+            ciphertext_buyer_identity_hash: identity.ciphertexts[0],
+            pub_key: identity.pub_key,
+            nonce: identity.nonce,
+            computation_offset,
+        },
+    );
+    let deposit = instructions::deposit_to_buyer_vault(
+        buyer.pubkey(),
+        maker.pubkey(),
+        OfferKind::Intrachain,
+        OFFER_ID,
+        TOKEN_B,
+    );
+    harness.process(&[finalize, deposit], &[buyer]).await
+}
+
+async fn offer_status(harness: &mut Harness, maker: &Keypair) -> OfferStatus {
+    harness
+        .account::<IntraChainOffer>(pda::intrachain_offer(&maker.pubkey(), OFFER_ID))
+        .await
+        .unwrap()
+        .status
+}
+
+#[tokio::test]
+async fn settles_verified_offer() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+
+    create_offer(&mut harness, &maker).await;
+    assert_eq!(offer_status(&mut harness, &maker).await, OfferStatus::Pending);
+    assert_eq!(harness.queued().len(), 1);
+
+    harness.run_computations().await.unwrap();
+    assert_eq!(offer_status(&mut harness, &maker).await, OfferStatus::Open);
+    assert!(harness.queued().is_empty());
+    assert!(harness.account::<PendingComputation>(pda::pending_computation(1)).await.is_none());
+
+    finalize_and_deposit(&mut harness, &maker, &buyer, 2).await.unwrap();
+    let pending: PendingComputation =
+        harness.account(pda::pending_computation(2)).await.unwrap();
+    assert_eq!(pending.offer, pda::intrachain_offer(&maker.pubkey(), OFFER_ID));
+    harness.run_computations().await.unwrap();
+
+    let maker_before = harness.balance(maker.pubkey()).await;
+    let buyer_before = harness.balance(buyer.pubkey()).await;
+    let execute =
+        instructions::execute_intrachain_swap(maker.pubkey(), buyer.pubkey(), OFFER_ID, None);
+    harness.process(&[execute], &[&maker, &buyer]).await.unwrap();
+
+    assert_eq!(offer_status(&mut harness, &maker).await, OfferStatus::Settled);
+    assert_eq!(harness.balance(maker.pubkey()).await, maker_before + TOKEN_B);
+    assert_eq!(harness.balance(buyer.pubkey()).await, buyer_before + TOKEN_A);
+}
+
+#[tokio::test]
+async fn only_the_verified_buyer_settles() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let stranger = harness.funded_keypair().await;
+
+    create_offer(&mut harness, &maker).await;
+    harness.run_computations().await.unwrap();
+    // Both finalize before either is verified; the first verified is the buyer
+    finalize_and_deposit(&mut harness, &maker, &buyer, 2).await.unwrap();
+    finalize_and_deposit(&mut harness, &maker, &stranger, 3).await.unwrap();
+    harness.run_computations().await.unwrap();
+    let address = pda::intrachain_offer(&maker.pubkey(), OFFER_ID);
+    let offer: IntraChainOffer = harness.account(address).await.unwrap();
+    assert_eq!(offer.buyer, Some(buyer.pubkey()));
+
+    // Once verified, the offer is no one else's to finalize or settle
+    let cipher = harness.cipher(&X25519Keypair::from_secret([4; 32]));
+    let identity =
+        cipher.encrypt_with_nonce([Fe::from(identity_hash(stranger.pubkey().as_ref()))], 1);
+    let again = instructions::finalize_intrachain_offer(
+        stranger.pubkey(),
+        maker.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::FinalizeIntrachainOffer {
+            id: OFFER_ID,
+            ciphertext_buyer_identity_hash: identity.ciphertexts[0],
+            pub_key: identity.pub_key,
+            nonce: identity.nonce,
+            computation_offset: 4,
+        },
+    );
+    assert!(harness.process(&[again], &[&stranger]).await.is_err());
+    let by_stranger =
+        instructions::execute_intrachain_swap(maker.pubkey(), stranger.pubkey(), OFFER_ID, None);
+    assert!(harness.process(&[by_stranger], &[&maker, &stranger]).await.is_err());
+
+    let execute =
+        instructions::execute_intrachain_swap(maker.pubkey(), buyer.pubkey(), OFFER_ID, None);
+    harness.process(&[execute], &[&maker, &buyer]).await.unwrap();
+    assert_eq!(offer_status(&mut harness, &maker).await, OfferStatus::Settled);
+}
+
+#[tokio::test]
+async fn refunds_offer_whose_verification_aborted() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+
+    create_offer(&mut harness, &maker).await;
+    harness.abort_computations().await.unwrap();
+    assert_eq!(
+        offer_status(&mut harness, &maker).await,
+        OfferStatus::VerificationFailed
+    );
+
+    let maker_before = harness.balance(maker.pubkey()).await;
+    let refund = instructions::refund_intrachain_offer(maker.pubkey(), OFFER_ID);
+    harness.process(&[refund], &[]).await.unwrap();
+
+    assert_eq!(offer_status(&mut harness, &maker).await, OfferStatus::Refunded);
+    assert_eq!(harness.balance(maker.pubkey()).await, maker_before + TOKEN_A);
+}
+
+#[tokio::test]
+async fn aborted_verification_can_be_retried() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+
+    create_offer(&mut harness, &maker).await;
+    harness.abort_computations().await.unwrap();
+
+    let cipher = harness.cipher(&X25519Keypair::from_secret([2; 32]));
+    let identity = cipher.encrypt_with_nonce([Fe::from(identity_hash(maker.pubkey().as_ref()))], 1);
+    let retry = |computation_offset| {
+        instructions::retry_deposit_seller_native(
+            maker.pubkey(),
+            CLUSTER_OFFSET,
+            instruction::RetryDepositSellerNative {
+                offer_id: OFFER_ID,
+                ciphertext_seller_identity_hash: identity.ciphertexts[0],
+                pub_key: identity.pub_key,
+                nonce: identity.nonce,
+                computation_offset,
+            },
+        )
+    };
+    harness.process(&[retry(2)], &[&maker]).await.unwrap();
+    let offer: IntraChainOffer =
+        harness.account(pda::intrachain_offer(&maker.pubkey(), OFFER_ID)).await.unwrap();
+    assert_eq!((offer.status, offer.computation_offset), (OfferStatus::Pending, 2));
+    let pending: PendingComputation =
+        harness.account(pda::pending_computation(2)).await.unwrap();
+    assert_eq!(pending.offer, pda::intrachain_offer(&maker.pubkey(), OFFER_ID));
+
+    // Only an aborted verification is re-queued
+    assert!(harness.process(&[retry(3)], &[&maker]).await.is_err());
+    harness.run_computations().await.unwrap();
+    assert_eq!(offer_status(&mut harness, &maker).await, OfferStatus::Open);
+    assert!(harness.process(&[retry(4)], &[&maker]).await.is_err());
+}
+
+#[tokio::test]
+async fn aborted_finalize_leaves_the_offer_open() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+
+    create_offer(&mut harness, &maker).await;
+    harness.run_computations().await.unwrap();
+
+    let cipher = harness.cipher(&X25519Keypair::from_secret([2; 32]));
+    let identity = cipher.encrypt_with_nonce([Fe::from(7u64)], 1);
+    let finalize = instructions::finalize_intrachain_offer(
+        maker.pubkey(),
+        maker.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::FinalizeIntrachainOffer {
+            id: OFFER_ID,
+            ciphertext_buyer_identity_hash: identity.ciphertexts[0],
+            pub_key: identity.pub_key,
+            nonce: identity.nonce,
+            computation_offset: 2,
+        },
+    );
+    harness.process(&[finalize], &[&maker]).await.unwrap();
+    harness.abort_computations().await.unwrap();
+    assert!(harness.account::<PendingComputation>(pda::pending_computation(2)).await.is_none());
+    assert_eq!(offer_status(&mut harness, &maker).await, OfferStatus::Open);
+}
+
+#[tokio::test]
+async fn vault_deposits_name_an_existing_offer() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+
+    let early = instructions::deposit_to_seller_vault(
+        maker.pubkey(),
+        OfferKind::Intrachain,
+        OFFER_ID,
+        TOKEN_A,
+    );
+    assert!(harness.process(&[early], &[&maker]).await.is_err());
+    create_offer(&mut harness, &maker).await;
+
+    let deposit = |maker: Pubkey, kind: OfferKind| {
+        instructions::deposit_to_buyer_vault(buyer.pubkey(), maker, kind, OFFER_ID, TOKEN_B)
+    };
+    let wrong_maker = deposit(buyer.pubkey(), OfferKind::Intrachain);
+    assert!(harness.process(&[wrong_maker], &[&buyer]).await.is_err());
+    let wrong_kind = deposit(maker.pubkey(), OfferKind::Interchain);
+    assert!(harness.process(&[wrong_kind], &[&buyer]).await.is_err());
+    let vault = pda::buyer_vault(&buyer.pubkey(), OFFER_ID);
+    harness.process(&[deposit(maker.pubkey(), OfferKind::Intrachain)], &[&buyer]).await.unwrap();
+    assert!(harness.balance(vault).await >= TOKEN_B);
+}
+
+#[tokio::test]
+async fn stale_verification_releases_the_escrow() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+
+    create_offer(&mut harness, &maker).await;
+    harness.stall_computations().await.unwrap();
+    assert!(harness.account::<PendingComputation>(pda::pending_computation(1)).await.is_none());
+    assert_eq!(
+        offer_status(&mut harness, &maker).await,
+        OfferStatus::VerificationFailed
+    );
+
+    let maker_before = harness.balance(maker.pubkey()).await;
+    let refund = instructions::refund_intrachain_offer(maker.pubkey(), OFFER_ID);
+    harness.process(&[refund], &[]).await.unwrap();
+    assert_eq!(offer_status(&mut harness, &maker).await, OfferStatus::Refunded);
+    assert_eq!(harness.balance(maker.pubkey()).await, maker_before + TOKEN_A);
+}
+
+#[tokio::test]
+async fn stale_finalize_returns_the_buyer_escrow() {
+    let mut harness = Harness::start().await;
+    let maker = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+
+    create_offer(&mut harness, &maker).await;
+    harness.run_computations().await.unwrap();
+
+    finalize_and_deposit(&mut harness, &maker, &buyer, 2).await.unwrap();
+
+    let buyer_before = harness.balance(buyer.pubkey()).await;
+    harness.stall_computations().await.unwrap();
+    assert_eq!(offer_status(&mut harness, &maker).await, OfferStatus::Open);
+    // The pending computation's rent comes back with the escrow
+    assert!(harness.balance(buyer.pubkey()).await > buyer_before + TOKEN_B);
+}
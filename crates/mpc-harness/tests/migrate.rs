@@ -0,0 +1,139 @@
+use anchor_lang::{prelude::Pubkey, AccountDeserialize, AnchorSerialize, Discriminator};
+use confidential_exchange_client::{
+    instructions,
+    pda,
+    program::{
+        versioning::{InterchainOfferV0, IntraChainOfferV0, LegacyOffer, VersionedAccount},
+        ExchangeConfig,
+        InterchainOffer,
+        IntraChainOffer,
+        OfferStatus,
+    },
+    PROGRAM_ID,
+};
+use mpc_harness::Harness;
+use solana_sdk::{account::Account, signer::Signer};
+
+/// Stores `legacy` at `address` as the first program wrote it, with just
+/// enough lamports to be rent exempt at that size
+async fn store_legacy<L: LegacyOffer + AnchorSerialize>(
+    harness: &mut Harness,
+    address: Pubkey,
+    legacy: &L,
+) {
+    let mut data = L::Current::DISCRIMINATOR.to_vec();
+    legacy.serialize(&mut data).unwrap();
+    assert_eq!(data.len(), L::SPACE);
+    let rent = harness.context().banks_client.get_rent().await.unwrap();
+    let account = Account {
+        lamports: rent.minimum_balance(data.len()),
+        data,
+        owner: PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    harness.context().set_account(&address, &account.into());
+}
+
+async fn raw_account(harness: &mut Harness, address: Pubkey) -> Account {
+    harness
+        .context()
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn maker_migrates_legacy_offer() {
+    let mut harness = Harness::start().await;
+    let initialize = instructions::initialize_exchange_config(harness.payer());
+    harness.process(&[initialize], &[]).await.unwrap();
+    let maker = harness.funded_keypair().await;
+    let stranger = harness.funded_keypair().await;
+
+    let address = pda::intrachain_offer(&maker.pubkey(), 3);
+    let (_, bump) = Pubkey::find_program_address(
+        &[b"IntraChainoffer", maker.pubkey().as_ref(), &3u64.to_le_bytes()],
+        &PROGRAM_ID,
+    );
+    let legacy = IntraChainOfferV0 {
+        id: 3,
+        token_a_offered_amount: 1_000,
+        token_b_wanted_amount: 2_000,
+        is_taker_native: true,
+        deadline: 1_800_000_000,
+        bump,
+    };
+    store_legacy(&mut harness, address, &legacy).await;
+    assert!(harness.account::<IntraChainOffer>(address).await.is_none());
+
+    // Only the maker the offer's address was derived from owns it
+    let by_stranger = instructions::migrate_account(stranger.pubkey(), address, None);
+    assert!(harness.process(&[by_stranger], &[&stranger]).await.is_err());
+    let as_stranger =
+        instructions::migrate_account(stranger.pubkey(), address, Some(stranger.pubkey()));
+    assert!(harness.process(&[as_stranger], &[&stranger]).await.is_err());
+    let without_maker = instructions::migrate_account(maker.pubkey(), address, None);
+    assert!(harness.process(&[without_maker], &[&maker]).await.is_err());
+
+    let migrate = instructions::migrate_account(maker.pubkey(), address, Some(maker.pubkey()));
+    harness.process(&[migrate], &[&maker]).await.unwrap();
+
+    let account = raw_account(&mut harness, address).await;
+    let rent = harness.context().banks_client.get_rent().await.unwrap();
+    assert_eq!(account.data.len(), IntraChainOffer::SPACE);
+    assert!(rent.is_exempt(account.lamports, account.data.len()));
+    let offer = IntraChainOffer::try_deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(offer.layout_version, IntraChainOffer::LAYOUT_VERSION);
+    assert_eq!(offer.maker, maker.pubkey());
+    assert_eq!(offer.token_b_wanted_amount, 2_000);
+    assert_eq!(offer.status, OfferStatus::Open);
+    assert_eq!((offer.computation_offset, offer.bump), (0, bump));
+
+    // Already in the current layout, even for the admin
+    let again = instructions::migrate_account(harness.payer(), address, None);
+    assert!(harness.process(&[again], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn admin_migrates_legacy_interchain_offer() {
+    let mut harness = Harness::start().await;
+    let admin = harness.payer();
+    let initialize = instructions::initialize_exchange_config(admin);
+    harness.process(&[initialize], &[]).await.unwrap();
+    let maker = harness.funded_keypair().await;
+
+    let address = pda::interchain_offer(&maker.pubkey(), 5);
+    let (_, bump) = Pubkey::find_program_address(
+        &[b"InterChainoffer", maker.pubkey().as_ref(), &5u64.to_le_bytes()],
+        &PROGRAM_ID,
+    );
+    let legacy = InterchainOfferV0 {
+        id: 5,
+        token_a_offered_amount: 1_000,
+        token_b_wanted_amount: 2_000,
+        is_taker_native: true,
+        chain_id: 1,
+        deadline: 1_800_000_000,
+        bump,
+    };
+    store_legacy(&mut harness, address, &legacy).await;
+
+    let migrate = instructions::migrate_account(admin, address, Some(maker.pubkey()));
+    harness.process(&[migrate], &[]).await.unwrap();
+
+    let offer: InterchainOffer = harness.account(address).await.unwrap();
+    assert_eq!(offer.layout_version, InterchainOffer::LAYOUT_VERSION);
+    assert_eq!((offer.maker, offer.chain_id, offer.bump), (maker.pubkey(), 1, bump));
+    assert_eq!(offer.token_b_wanted_amount[24..], 2_000u64.to_be_bytes());
+    assert_eq!(offer.token_mapping, Pubkey::default());
+    assert_eq!(offer.status, OfferStatus::Open);
+
+    // Accounts introduced with versioning have no older layout
+    let config = instructions::migrate_account(admin, pda::exchange_config(), None);
+    assert!(harness.process(&[config], &[]).await.is_err());
+    let migrated: ExchangeConfig = harness.account(pda::exchange_config()).await.unwrap();
+    assert_eq!(migrated.admin, admin);
+}
@@ -0,0 +1,162 @@
+use anchor_lang::prelude::Pubkey;
+use confidential_exchange_client::{
+    instructions,
+    pda,
+    program::{
+        instruction,
+        IntraChainOffer,
+        OfferKind,
+        OfferStatus,
+        PriceBand,
+    },
+};
+use mpc_harness::Harness;
+use solana_sdk::{
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    signature::Keypair,
+    signer::Signer,
+};
+
+const OFFER_ID: u64 = 1;
+const FEED_ID: u64 = 7;
+const TOKEN_A: u64 = LAMPORTS_PER_SOL;
+const TOKEN_B: u64 = 2 * LAMPORTS_PER_SOL;
+const MAX_DEVIATION_BPS: u16 = 100;
+const MAX_STALENESS_SECS: i64 = 60;
+
+/// Stores `seller`'s open intrachain offer of `TOKEN_A` for `TOKEN_B`, as
+/// finalizing it would have left it, and escrows both sides
+async fn open_offer(harness: &mut Harness, seller: &Keypair, buyer: &Keypair) -> Pubkey {
+    let address = harness
+        .store_intrachain_offer(&seller.pubkey(), OFFER_ID, OfferStatus::Open, Some(buyer.pubkey()))
+        .await;
+
+    let seller_deposit = instructions::deposit_to_seller_vault(
+        seller.pubkey(),
+        OfferKind::Intrachain,
+        OFFER_ID,
+        TOKEN_A,
+    );
+    harness.process(&[seller_deposit], &[seller]).await.unwrap();
+    let buyer_deposit = instructions::deposit_to_buyer_vault(
+        buyer.pubkey(),
+        seller.pubkey(),
+        OfferKind::Intrachain,
+        OFFER_ID,
+        TOKEN_B,
+    );
+    harness.process(&[buyer_deposit], &[buyer]).await.unwrap();
+    address
+}
+
+/// `publisher` prices its feed at `price * 10^expo` token B per token A
+async fn publish(harness: &mut Harness, publisher: &Keypair, price: i64, expo: i32) -> Pubkey {
+    let publish = instructions::publish_price(
+        publisher.pubkey(),
+        instruction::PublishPrice {
+            feed_id: FEED_ID,
+            price,
+            expo,
+        },
+    );
+    harness.process(&[publish], &[publisher]).await.unwrap();
+    pda::price_feed(&publisher.pubkey(), FEED_ID)
+}
+
+fn set_band(maker: &Keypair, offer: Pubkey, price_feed: Pubkey) -> Instruction {
+    instructions::set_price_band(
+        maker.pubkey(),
+        OfferKind::Intrachain,
+        price_feed,
+        instruction::SetPriceBand {
+            offer,
+            max_deviation_bps: MAX_DEVIATION_BPS,
+            max_staleness_secs: MAX_STALENESS_SECS,
+        },
+    )
+}
+
+fn execute(seller: &Keypair, buyer: &Keypair, price_feed: Option<Pubkey>) -> Instruction {
+    instructions::execute_intrachain_swap(seller.pubkey(), buyer.pubkey(), OFFER_ID, price_feed)
+}
+
+async fn status(harness: &mut Harness, offer: Pubkey) -> OfferStatus {
+    harness.account::<IntraChainOffer>(offer).await.unwrap().status
+}
+
+#[tokio::test]
+async fn fills_only_within_the_band() {
+    let mut harness = Harness::start().await;
+    let seller = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let publisher = harness.funded_keypair().await;
+    let offer = open_offer(&mut harness, &seller, &buyer).await;
+
+    // 2.1 token B per token A, the offer's 2.0 is 4.8% below
+    let feed = publish(&mut harness, &publisher, 21, -1).await;
+    let by_buyer = set_band(&buyer, offer, feed);
+    assert!(harness.process(&[by_buyer], &[&buyer]).await.is_err());
+    harness.process(&[set_band(&seller, offer, feed)], &[&seller]).await.unwrap();
+    let band: PriceBand = harness.account(pda::price_band(&offer)).await.unwrap();
+    assert_eq!((band.price_feed, band.max_deviation_bps), (feed, MAX_DEVIATION_BPS));
+
+    let off_market = execute(&seller, &buyer, Some(feed));
+    assert!(harness.process(&[off_market], &[&seller, &buyer]).await.is_err());
+    assert_eq!(status(&mut harness, offer).await, OfferStatus::Open);
+
+    // 2.01 is within 1%
+    publish(&mut harness, &publisher, 2_010, -3).await;
+    let seller_before = harness.balance(seller.pubkey()).await;
+    let buyer_before = harness.balance(buyer.pubkey()).await;
+    harness.next_slot().await;
+    harness.process(&[execute(&seller, &buyer, Some(feed))], &[&seller, &buyer]).await.unwrap();
+    assert_eq!(status(&mut harness, offer).await, OfferStatus::Settled);
+    assert_eq!(harness.balance(seller.pubkey()).await, seller_before + TOKEN_B);
+    assert_eq!(harness.balance(buyer.pubkey()).await, buyer_before + TOKEN_A);
+}
+
+#[tokio::test]
+async fn stale_price_blocks_the_fill() {
+    let mut harness = Harness::start().await;
+    let seller = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let publisher = harness.funded_keypair().await;
+    let offer = open_offer(&mut harness, &seller, &buyer).await;
+    let feed = publish(&mut harness, &publisher, 2, 0).await;
+    harness.process(&[set_band(&seller, offer, feed)], &[&seller]).await.unwrap();
+
+    let now = harness.clock().await.unix_timestamp;
+    harness.set_unix_timestamp(now + MAX_STALENESS_SECS + 1).await;
+    let stale = execute(&seller, &buyer, Some(feed));
+    assert!(harness.process(&[stale], &[&seller, &buyer]).await.is_err());
+
+    // Republishing the same price needs a fresh blockhash to count as a new update
+    harness.next_slot().await;
+    publish(&mut harness, &publisher, 2, 0).await;
+    harness.next_slot().await;
+    harness.process(&[execute(&seller, &buyer, Some(feed))], &[&seller, &buyer]).await.unwrap();
+    assert_eq!(status(&mut harness, offer).await, OfferStatus::Settled);
+}
+
+#[tokio::test]
+async fn banded_offer_needs_its_own_feed() {
+    let mut harness = Harness::start().await;
+    let seller = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let publisher = harness.funded_keypair().await;
+    let impostor = harness.funded_keypair().await;
+    let offer = open_offer(&mut harness, &seller, &buyer).await;
+    let feed = publish(&mut harness, &publisher, 2, 0).await;
+    let other_feed = publish(&mut harness, &impostor, 2, 0).await;
+    harness.process(&[set_band(&seller, offer, feed)], &[&seller]).await.unwrap();
+
+    let without_feed = execute(&seller, &buyer, None);
+    assert!(harness.process(&[without_feed], &[&seller, &buyer]).await.is_err());
+    let with_other_feed = execute(&seller, &buyer, Some(other_feed));
+    assert!(harness.process(&[with_other_feed], &[&seller, &buyer]).await.is_err());
+    assert_eq!(status(&mut harness, offer).await, OfferStatus::Open);
+
+    harness.process(&[execute(&seller, &buyer, Some(feed))], &[&seller, &buyer]).await.unwrap();
+    assert_eq!(status(&mut harness, offer).await, OfferStatus::Settled);
+}
@@ -0,0 +1,213 @@
+use anchor_lang::prelude::Pubkey;
+use confidential_exchange_client::{
+    crypto::X25519Keypair,
+    instructions,
+    pda,
+    program::{
+        instruction,
+        IntraChainOffer,
+        OfferKind,
+        OfferStatus,
+        PriceTrigger,
+    },
+    Fe,
+};
+use mpc_harness::{Harness, CLUSTER_OFFSET};
+use solana_sdk::{
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    signature::Keypair,
+    signer::Signer,
+};
+
+const OFFER_ID: u64 = 1;
+const FEED_ID: u64 = 7;
+const TOKEN_A: u64 = LAMPORTS_PER_SOL;
+const TOKEN_B: u64 = 2 * LAMPORTS_PER_SOL;
+/// 1.50 token B per token A, at the feed's exponent of -2
+const STOP_PRICE: u64 = 150;
+
+/// Stores `seller`'s open intrachain offer of `TOKEN_A` for `TOKEN_B`, as
+/// finalizing it would have left it, and escrows both sides
+async fn open_offer(harness: &mut Harness, seller: &Keypair, buyer: &Keypair) -> Pubkey {
+    let address = harness
+        .store_intrachain_offer(&seller.pubkey(), OFFER_ID, OfferStatus::Open, Some(buyer.pubkey()))
+        .await;
+
+    let seller_deposit = instructions::deposit_to_seller_vault(
+        seller.pubkey(),
+        OfferKind::Intrachain,
+        OFFER_ID,
+        TOKEN_A,
+    );
+    harness.process(&[seller_deposit], &[seller]).await.unwrap();
+    let buyer_deposit = instructions::deposit_to_buyer_vault(
+        buyer.pubkey(),
+        seller.pubkey(),
+        OfferKind::Intrachain,
+        OFFER_ID,
+        TOKEN_B,
+    );
+    harness.process(&[buyer_deposit], &[buyer]).await.unwrap();
+    address
+}
+
+/// `publisher` prices its feed at `price` hundredths of token B per token A
+async fn publish(harness: &mut Harness, publisher: &Keypair, price: i64) -> Pubkey {
+    let publish = instructions::publish_price(
+        publisher.pubkey(),
+        instruction::PublishPrice {
+            feed_id: FEED_ID,
+            price,
+            expo: -2,
+        },
+    );
+    harness.process(&[publish], &[publisher]).await.unwrap();
+    pda::price_feed(&publisher.pubkey(), FEED_ID)
+}
+
+/// `maker` holds `offer` back until the feed drops to `STOP_PRICE`, under
+/// `computation_offset`, which also seeds the maker's x25519 key
+fn set_stop(
+    harness: &Harness,
+    maker: &Keypair,
+    offer: Pubkey,
+    price_feed: Pubkey,
+    computation_offset: u64,
+) -> Instruction {
+    let cipher = harness.cipher(&X25519Keypair::from_secret([computation_offset as u8; 32]));
+    let trigger = cipher.encrypt_with_nonce([Fe::from(STOP_PRICE), Fe::from(0u64)], 1);
+    instructions::set_price_trigger(
+        maker.pubkey(),
+        OfferKind::Intrachain,
+        price_feed,
+        CLUSTER_OFFSET,
+        instruction::SetPriceTrigger {
+            offer,
+            ciphertext_trigger: trigger.ciphertexts,
+            pub_key: trigger.pub_key,
+            nonce: trigger.nonce,
+            mxe_nonce: 5,
+            computation_offset,
+        },
+    )
+}
+
+async fn trigger(harness: &mut Harness, offer: Pubkey) -> PriceTrigger {
+    harness.account(pda::price_trigger(&offer)).await.unwrap()
+}
+
+/// `cranker` checks the trigger of `offer` against its feed's price
+async fn check(
+    harness: &mut Harness,
+    cranker: &Keypair,
+    offer: Pubkey,
+    computation_offset: u64,
+) -> Result<(), solana_program_test::BanksClientError> {
+    let armed = trigger(harness, offer).await;
+    let check = instructions::check_price_trigger(
+        cranker.pubkey(),
+        &armed,
+        CLUSTER_OFFSET,
+        instruction::CheckPriceTrigger { computation_offset },
+    );
+    harness.process(&[check], &[cranker]).await
+}
+
+fn execute(seller: &Keypair, buyer: &Keypair) -> Instruction {
+    instructions::execute_intrachain_swap(seller.pubkey(), buyer.pubkey(), OFFER_ID, None)
+}
+
+#[tokio::test]
+async fn stop_offer_activates_once_the_price_drops() {
+    let mut harness = Harness::start().await;
+    let seller = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let publisher = harness.funded_keypair().await;
+    let cranker = harness.funded_keypair().await;
+    let offer = open_offer(&mut harness, &seller, &buyer).await;
+    let feed = publish(&mut harness, &publisher, 200).await;
+
+    let by_buyer = set_stop(&harness, &buyer, offer, feed, 1);
+    assert!(harness.process(&[by_buyer], &[&buyer]).await.is_err());
+    let set = set_stop(&harness, &seller, offer, feed, 2);
+    harness.process(&[set], &[&seller]).await.unwrap();
+    harness.run_computations().await.unwrap();
+
+    // Only the MXE can read the stored trigger
+    let armed = trigger(&mut harness, offer).await;
+    assert_eq!((armed.status, armed.price_feed, armed.expo), (OfferStatus::Open, feed, -2));
+    let stored = harness.mxe_cipher().decrypt(&armed.trigger, armed.trigger_nonce);
+    assert_eq!(stored, vec![Fe::from(STOP_PRICE), Fe::from(0u64)]);
+    assert!(harness.process(&[execute(&seller, &buyer)], &[&seller, &buyer]).await.is_err());
+
+    // 2.00 is above the stop, so the offer stays dormant
+    check(&mut harness, &cranker, offer, 3).await.unwrap();
+    harness.run_computations().await.unwrap();
+    let checked = trigger(&mut harness, offer).await;
+    assert_eq!((checked.status, checked.checked_price), (OfferStatus::Open, 200));
+    assert!(check(&mut harness, &cranker, offer, 4).await.is_err());
+
+    // A price is only checked once, so the next one needs a later publish time
+    let now = harness.clock().await.unix_timestamp;
+    harness.set_unix_timestamp(now + 1).await;
+    publish(&mut harness, &publisher, 149).await;
+    check(&mut harness, &cranker, offer, 5).await.unwrap();
+    harness.run_computations().await.unwrap();
+    assert_eq!(trigger(&mut harness, offer).await.status, OfferStatus::Settled);
+    let reset = set_stop(&harness, &seller, offer, feed, 6);
+    assert!(harness.process(&[reset], &[&seller]).await.is_err());
+
+    let seller_before = harness.balance(seller.pubkey()).await;
+    harness.next_slot().await;
+    harness.process(&[execute(&seller, &buyer)], &[&seller, &buyer]).await.unwrap();
+    assert_eq!(harness.balance(seller.pubkey()).await, seller_before + TOKEN_B);
+    let settled: IntraChainOffer = harness.account(offer).await.unwrap();
+    assert_eq!(settled.status, OfferStatus::Settled);
+}
+
+#[tokio::test]
+async fn aborted_trigger_keeps_the_offer_dormant_until_set_again() {
+    let mut harness = Harness::start().await;
+    let seller = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let publisher = harness.funded_keypair().await;
+    let offer = open_offer(&mut harness, &seller, &buyer).await;
+    let feed = publish(&mut harness, &publisher, 100).await;
+
+    harness.process(&[set_stop(&harness, &seller, offer, feed, 1)], &[&seller]).await.unwrap();
+    harness.abort_computations().await.unwrap();
+    let failed = trigger(&mut harness, offer).await;
+    assert_eq!(failed.status, OfferStatus::VerificationFailed);
+    assert!(harness.process(&[execute(&seller, &buyer)], &[&seller, &buyer]).await.is_err());
+    assert!(check(&mut harness, &seller, offer, 2).await.is_err());
+
+    harness.process(&[set_stop(&harness, &seller, offer, feed, 3)], &[&seller]).await.unwrap();
+    harness.run_computations().await.unwrap();
+    check(&mut harness, &seller, offer, 4).await.unwrap();
+    harness.run_computations().await.unwrap();
+    assert_eq!(trigger(&mut harness, offer).await.status, OfferStatus::Settled);
+}
+
+#[tokio::test]
+async fn stale_trigger_keeps_the_offer_dormant_until_set_again() {
+    let mut harness = Harness::start().await;
+    let seller = harness.funded_keypair().await;
+    let buyer = harness.funded_keypair().await;
+    let publisher = harness.funded_keypair().await;
+    let offer = open_offer(&mut harness, &seller, &buyer).await;
+    let feed = publish(&mut harness, &publisher, 100).await;
+
+    harness.process(&[set_stop(&harness, &seller, offer, feed, 1)], &[&seller]).await.unwrap();
+    harness.stall_computations().await.unwrap();
+    let failed = trigger(&mut harness, offer).await;
+    assert_eq!(failed.status, OfferStatus::VerificationFailed);
+    assert!(harness.process(&[execute(&seller, &buyer)], &[&seller, &buyer]).await.is_err());
+    assert!(check(&mut harness, &seller, offer, 2).await.is_err());
+
+    harness.process(&[set_stop(&harness, &seller, offer, feed, 3)], &[&seller]).await.unwrap();
+    harness.run_computations().await.unwrap();
+    check(&mut harness, &seller, offer, 4).await.unwrap();
+    harness.run_computations().await.unwrap();
+    assert_eq!(trigger(&mut harness, offer).await.status, OfferStatus::Settled);
+}
@@ -0,0 +1,266 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+use confidential_exchange_client::{
+    crypto::{identity_hash, X25519Keypair},
+    instructions,
+    pda,
+    program::{instruction, OfferStatus, RfqRequest, VaultSide},
+    Fe,
+};
+use mpc_harness::{Harness, CLUSTER_OFFSET};
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Keypair, signer::Signer};
+
+const RFQ_ID: u64 = 1;
+const SIZE: u64 = 500;
+
+/// A taker asking for `SIZE` of token A, paid in SOL, and three makers
+/// holding token A
+struct Desk {
+    taker: Keypair,
+    makers: [Keypair; 3],
+    token_a: Pubkey,
+}
+
+async fn desk(harness: &mut Harness) -> Desk {
+    let taker = Keypair::new();
+    let makers = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let token_a = harness.create_mint(6).await;
+    harness.fund(&taker.pubkey(), 10 * LAMPORTS_PER_SOL).await;
+    harness.create_token_account(&taker.pubkey(), &token_a, 0).await;
+    for maker in &makers {
+        harness.fund(&maker.pubkey(), LAMPORTS_PER_SOL).await;
+        harness.create_token_account(&maker.pubkey(), &token_a, SIZE).await;
+    }
+    Desk {
+        taker,
+        makers,
+        token_a,
+    }
+}
+
+async fn rfq(harness: &mut Harness, desk: &Desk) -> RfqRequest {
+    harness
+        .account(pda::rfq(&desk.taker.pubkey(), RFQ_ID))
+        .await
+        .unwrap()
+}
+
+async fn token_balance(harness: &mut Harness, owner: &Keypair, mint: &Pubkey) -> u64 {
+    harness
+        .token_balance(get_associated_token_address(&owner.pubkey(), mint))
+        .await
+}
+
+/// Opens the RFQ, returning its quote and settlement deadlines
+async fn create_rfq(harness: &mut Harness, desk: &Desk) -> (i64, i64) {
+    let now = harness.clock().await.unix_timestamp;
+    let (quote_deadline, settle_deadline) = (now + 60, now + 3_600);
+    let create = instructions::create_rfq(
+        desk.taker.pubkey(),
+        instruction::CreateRfq {
+            id: RFQ_ID,
+            base_mint: Some(desk.token_a),
+            quote_mint: None,
+            size: SIZE,
+            quote_deadline,
+            settle_deadline,
+        },
+    );
+    harness.process(&[create], &[&desk.taker]).await.unwrap();
+    (quote_deadline, settle_deadline)
+}
+
+/// Queues `rank_rfq_quote` for `maker`'s `price` under `computation_offset`
+async fn quote(
+    harness: &mut Harness,
+    desk: &Desk,
+    maker: &Keypair,
+    price: u64,
+    computation_offset: u64,
+) -> Result<(), solana_program_test::BanksClientError> {
+    let cipher = harness.cipher(&X25519Keypair::from_secret([computation_offset as u8; 32]));
+    let identity = identity_hash(maker.pubkey().as_ref());
+    let quote = cipher.encrypt_with_nonce([Fe::from(price), Fe::from(identity)], 1);
+    let submit = instructions::submit_rfq_quote(
+        maker.pubkey(),
+        desk.taker.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::SubmitRfqQuote {
+            rfq_id: RFQ_ID,
+            ciphertext_price: quote.ciphertexts[0],
+            ciphertext_maker_identity_hash: quote.ciphertexts[1],
+            pub_key: quote.pub_key,
+            nonce: quote.nonce,
+            computation_offset,
+        },
+    );
+    harness.process(&[submit], &[maker]).await
+}
+
+/// Queues `select_rfq_quote`, paid by the harness, under `computation_offset`
+async fn select(
+    harness: &mut Harness,
+    desk: &Desk,
+    computation_offset: u64,
+) -> Result<(), solana_program_test::BanksClientError> {
+    let select = instructions::select_rfq_quote(
+        harness.payer(),
+        desk.taker.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::SelectRfqQuote {
+            rfq_id: RFQ_ID,
+            computation_offset,
+        },
+    );
+    harness.process(&[select], &[]).await
+}
+
+/// Ranks the makers' quotes at `prices`, in order, then awards the RFQ
+async fn award(harness: &mut Harness, desk: &Desk, prices: [u64; 3]) -> RfqRequest {
+    let (quote_deadline, _) = create_rfq(harness, desk).await;
+    for (index, (maker, price)) in desk.makers.iter().zip(prices).enumerate() {
+        quote(harness, desk, maker, price, index as u64 + 1).await.unwrap();
+        harness.run_computations().await.unwrap();
+    }
+    harness.set_unix_timestamp(quote_deadline + 1).await;
+    select(harness, desk, 10).await.unwrap();
+    harness.run_computations().await.unwrap();
+    rfq(harness, desk).await
+}
+
+#[tokio::test]
+async fn awards_the_earliest_lowest_quote_and_settles() {
+    let mut harness = Harness::start().await;
+    let desk = desk(&mut harness).await;
+
+    let awarded = award(
+        &mut harness,
+        &desk,
+        [3 * LAMPORTS_PER_SOL, 2 * LAMPORTS_PER_SOL, 2 * LAMPORTS_PER_SOL],
+    )
+    .await;
+    let winner = &desk.makers[1];
+    assert_eq!(awarded.winner, Some(winner.pubkey()));
+    assert_eq!(awarded.price, 2 * LAMPORTS_PER_SOL);
+    assert_eq!(awarded.status, OfferStatus::Open);
+
+    // Only the winner escrows the base leg
+    let loser_deposit =
+        instructions::deposit_rfq_leg(desk.makers[0].pubkey(), &awarded, VaultSide::Seller);
+    assert!(harness.process(&[loser_deposit], &[&desk.makers[0]]).await.is_err());
+    let deposits = [
+        instructions::deposit_rfq_leg(winner.pubkey(), &awarded, VaultSide::Seller),
+        instructions::deposit_rfq_leg(desk.taker.pubkey(), &awarded, VaultSide::Buyer),
+    ];
+    harness.process(&deposits, &[winner, &desk.taker]).await.unwrap();
+
+    let winner_before = harness.balance(winner.pubkey()).await;
+    harness.process(&[instructions::settle_rfq(&awarded)], &[]).await.unwrap();
+
+    assert_eq!(rfq(&mut harness, &desk).await.status, OfferStatus::Settled);
+    assert_eq!(token_balance(&mut harness, &desk.taker, &desk.token_a).await, SIZE);
+    assert_eq!(token_balance(&mut harness, winner, &desk.token_a).await, 0);
+    assert_eq!(
+        harness.balance(winner.pubkey()).await,
+        winner_before + 2 * LAMPORTS_PER_SOL
+    );
+}
+
+#[tokio::test]
+async fn quotes_and_selection_follow_the_quote_window() {
+    let mut harness = Harness::start().await;
+    let desk = desk(&mut harness).await;
+
+    let (quote_deadline, _) = create_rfq(&mut harness, &desk).await;
+    // Nothing to select yet, and the window is still open once quoted
+    assert!(select(&mut harness, &desk, 10).await.is_err());
+    quote(&mut harness, &desk, &desk.makers[0], 100, 1).await.unwrap();
+    // Quotes are ranked one at a time
+    assert!(quote(&mut harness, &desk, &desk.makers[1], 90, 2).await.is_err());
+    harness.run_computations().await.unwrap();
+    assert!(select(&mut harness, &desk, 11).await.is_err());
+    // A maker quotes once
+    assert!(quote(&mut harness, &desk, &desk.makers[0], 80, 3).await.is_err());
+
+    harness.set_unix_timestamp(quote_deadline + 1).await;
+    assert!(quote(&mut harness, &desk, &desk.makers[1], 90, 4).await.is_err());
+    select(&mut harness, &desk, 12).await.unwrap();
+    harness.run_computations().await.unwrap();
+    let awarded = rfq(&mut harness, &desk).await;
+    assert_eq!(awarded.winner, Some(desk.makers[0].pubkey()));
+    assert_eq!(awarded.price, 100);
+
+    // Awarded RFQs can no longer be cancelled
+    let cancel = instructions::cancel_rfq(desk.taker.pubkey(), RFQ_ID);
+    assert!(harness.process(&[cancel], &[&desk.taker]).await.is_err());
+}
+
+#[tokio::test]
+async fn aborted_ranking_drops_the_quote() {
+    let mut harness = Harness::start().await;
+    let desk = desk(&mut harness).await;
+
+    create_rfq(&mut harness, &desk).await;
+    let maker = &desk.makers[0];
+    quote(&mut harness, &desk, maker, 100, 1).await.unwrap();
+    assert_eq!(rfq(&mut harness, &desk).await.status, OfferStatus::Pending);
+    harness.abort_computations().await.unwrap();
+
+    let dropped = rfq(&mut harness, &desk).await;
+    assert_eq!(dropped.status, OfferStatus::Open);
+    assert!(dropped.makers.is_empty());
+
+    // The maker may quote again, and is ranked first
+    quote(&mut harness, &desk, maker, 100, 2).await.unwrap();
+    harness.run_computations().await.unwrap();
+    assert_eq!(rfq(&mut harness, &desk).await.makers, vec![maker.pubkey()]);
+}
+
+#[tokio::test]
+async fn stale_ranking_drops_the_quote() {
+    let mut harness = Harness::start().await;
+    let desk = desk(&mut harness).await;
+
+    create_rfq(&mut harness, &desk).await;
+    let maker = &desk.makers[0];
+    quote(&mut harness, &desk, maker, 100, 1).await.unwrap();
+    assert_eq!(rfq(&mut harness, &desk).await.status, OfferStatus::Pending);
+    harness.stall_computations().await.unwrap();
+
+    let dropped = rfq(&mut harness, &desk).await;
+    assert_eq!(dropped.status, OfferStatus::Open);
+    assert!(dropped.makers.is_empty());
+
+    // The maker may quote again, and is ranked first
+    quote(&mut harness, &desk, maker, 100, 2).await.unwrap();
+    harness.run_computations().await.unwrap();
+    assert_eq!(rfq(&mut harness, &desk).await.makers, vec![maker.pubkey()]);
+}
+
+#[tokio::test]
+async fn refunds_a_funded_leg_after_the_settlement_deadline() {
+    let mut harness = Harness::start().await;
+    let desk = desk(&mut harness).await;
+
+    let awarded = award(&mut harness, &desk, [300, 200, 100]).await;
+    let winner = &desk.makers[2];
+    assert_eq!(awarded.winner, Some(winner.pubkey()));
+    let deposit = instructions::deposit_rfq_leg(winner.pubkey(), &awarded, VaultSide::Seller);
+    harness.process(&[deposit], &[winner]).await.unwrap();
+
+    // The taker never paid
+    let settle = instructions::settle_rfq(&awarded);
+    assert!(harness.process(std::slice::from_ref(&settle), &[]).await.is_err());
+    let refund = instructions::refund_rfq_leg(&awarded, VaultSide::Seller);
+    assert!(harness.process(std::slice::from_ref(&refund), &[]).await.is_err());
+
+    // On a fresh blockhash, so the refund is not a replay of the one that failed
+    let slot = harness.clock().await.slot;
+    harness.warp_to_slot(slot + 1);
+    harness.set_unix_timestamp(awarded.settle_deadline + 1).await;
+    harness.process(&[refund], &[]).await.unwrap();
+
+    assert_eq!(rfq(&mut harness, &desk).await.status, OfferStatus::Refunded);
+    assert_eq!(token_balance(&mut harness, winner, &desk.token_a).await, SIZE);
+    assert!(harness.process(&[settle], &[]).await.is_err());
+}
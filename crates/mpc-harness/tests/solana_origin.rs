@@ -0,0 +1,430 @@
+use anchor_lang::solana_program::keccak;
+use confidential_exchange_client::{
+    crypto::{split_evm_address, X25519Keypair},
+    instructions,
+    pda,
+    program::{
+        evm_proof::{payout_commitment, TRANSFER_TOPIC},
+        instruction,
+        EvmBlockHeader,
+        OfferStatus,
+        SolanaOriginOffer,
+    },
+};
+use mpc_harness::{Harness, CLUSTER_OFFSET};
+use solana_sdk::{
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+};
+
+const CHAIN_ID: u64 = 1;
+const OFFER_ID: u64 = 1;
+const EVM_TOKEN: [u8; 20] = [0xee; 20];
+const TAKER_EVM_ADDRESS: [u8; 20] = [0x7a; 20];
+const PAYOUT_SALT: [u8; 32] = [0x5a; 32];
+
+async fn register_chain(harness: &mut Harness, relayers: Vec<Pubkey>) {
+    let chain = instruction::SetChainConfig {
+        chain_id: CHAIN_ID,
+        enabled: true,
+        finality_blocks: 12,
+        min_deadline_secs: 0,
+        max_deadline_secs: 86_400,
+        relayers,
+        allowed_tokens: vec![EVM_TOKEN],
+    };
+    let mapping = instructions::set_token_mapping(
+        harness.payer(),
+        instruction::SetTokenMapping {
+            chain_id: CHAIN_ID,
+            evm_token: EVM_TOKEN,
+            asset_id: 1,
+            evm_decimals: 18,
+            solana_mint: None,
+            solana_decimals: 9,
+        },
+    );
+    harness.register_chain(chain, vec![mapping]).await;
+}
+
+/// `maker` offers 1 SOL, paid out on the EVM chain to `payout_address`
+/// committed with `PAYOUT_SALT`, and queues storing that address under
+/// computation 1
+async fn queue_create(harness: &mut Harness, maker: &Keypair, payout_address: &[u8; 20]) {
+    let cipher = harness.cipher(&X25519Keypair::from_secret([1; 32]));
+    let encrypted = cipher.encrypt_with_nonce(split_evm_address(payout_address), 5);
+    let deadline = harness.clock().await.unix_timestamp + 3_600;
+    let create = instructions::create_solana_origin_offer_native(
+        maker.pubkey(),
+        CLUSTER_OFFSET,
+        EVM_TOKEN,
+        instruction::CreateSolanaOriginOfferNative {
+            id: OFFER_ID,
+            chain_id: CHAIN_ID,
+            token_a_offered_amount: LAMPORTS_PER_SOL,
+            evm_wanted_amount: [0; 32],
+            deadline,
+            payout_commitment: payout_commitment(payout_address, &PAYOUT_SALT),
+            ciphertext_payout_address_hi: encrypted.ciphertexts[0],
+            ciphertext_payout_address_lo: encrypted.ciphertexts[1],
+            pub_key: encrypted.pub_key,
+            nonce: encrypted.nonce,
+            mxe_nonce: 9,
+            computation_offset: 1,
+        },
+    );
+    harness.process(&[create], &[maker]).await.unwrap();
+}
+
+async fn create(harness: &mut Harness, maker: &Keypair, payout_address: &[u8; 20]) {
+    queue_create(harness, maker, payout_address).await;
+    harness.run_computations().await.unwrap();
+}
+
+async fn offer(harness: &mut Harness, maker: &Keypair) -> SolanaOriginOffer {
+    harness
+        .account(pda::solana_origin_offer(&maker.pubkey(), OFFER_ID))
+        .await
+        .unwrap()
+}
+
+/// `taker` reserves `maker`'s offer under `computation_offset`, which also
+/// seeds the taker's x25519 key
+async fn reserve(
+    harness: &mut Harness,
+    maker: &Keypair,
+    taker: &Keypair,
+    computation_offset: u64,
+) -> Result<(), solana_program_test::BanksClientError> {
+    let keypair = X25519Keypair::from_secret([computation_offset as u8; 32]);
+    let reserve = instructions::reserve_solana_origin_offer(
+        taker.pubkey(),
+        maker.pubkey(),
+        CLUSTER_OFFSET,
+        instruction::ReserveSolanaOriginOffer {
+            offer_id: OFFER_ID,
+            taker_evm_address: TAKER_EVM_ADDRESS,
+            pub_key: keypair.public_key(),
+            nonce: 3,
+            computation_offset,
+        },
+    );
+    harness.process(&[reserve], &[taker]).await
+}
+
+/// `relayer` attests `evm_sender` paid the offer in EVM tx `evm_tx_hash`,
+/// paying out to `taker`
+fn settle(
+    relayer: &Keypair,
+    offer: &SolanaOriginOffer,
+    taker: Pubkey,
+    evm_tx_hash: u8,
+    evm_sender: [u8; 20],
+) -> Instruction {
+    instructions::settle_solana_origin_offer(
+        relayer.pubkey(),
+        offer,
+        taker,
+        None,
+        instruction::SettleSolanaOriginOffer {
+            offer_id: OFFER_ID,
+            evm_tx_hash: [evm_tx_hash; 32],
+            evm_sender,
+            evm_paid_amount: [0; 32],
+            payout_commitment: offer.payout_commitment,
+        },
+    )
+}
+
+#[tokio::test]
+async fn stores_payout_address_for_the_mxe() {
+    let mut harness = Harness::start().await;
+    register_chain(&mut harness, vec![]).await;
+    let maker = harness.funded_keypair().await;
+
+    let payout_address: [u8; 20] = core::array::from_fn(|i| i as u8 + 1);
+    create(&mut harness, &maker, &payout_address).await;
+
+    let offer = offer(&mut harness, &maker).await;
+    assert_eq!(offer.status, OfferStatus::Open);
+    assert_eq!(offer.payout_address_nonce, 10);
+    assert_eq!(
+        harness
+            .mxe_cipher()
+            .decrypt(&offer.encrypted_payout_address, offer.payout_address_nonce),
+        split_evm_address(&payout_address)
+    );
+}
+
+#[tokio::test]
+async fn only_the_upgrade_authority_initializes_the_config() {
+    let mut harness = Harness::start().await;
+    let squatter = Keypair::new();
+    harness.fund(&squatter.pubkey(), LAMPORTS_PER_SOL).await;
+
+    let by_squatter = instructions::initialize_exchange_config(squatter.pubkey());
+    assert!(harness.process(&[by_squatter], &[&squatter]).await.is_err());
+    register_chain(&mut harness, vec![]).await;
+}
+
+#[tokio::test]
+async fn settlement_pays_only_the_reserved_taker() {
+    let mut harness = Harness::start().await;
+    let relayer = harness.funded_keypair().await;
+    register_chain(&mut harness, vec![relayer.pubkey()]).await;
+    let maker = harness.funded_keypair().await;
+    let taker = harness.funded_keypair().await;
+    let stranger = harness.funded_keypair().await;
+    create(&mut harness, &maker, &[0x11; 20]).await;
+
+    // Nobody was told where to pay, so nobody can be paid out
+    let open = offer(&mut harness, &maker).await;
+    let unreserved = settle(&relayer, &open, taker.pubkey(), 1, TAKER_EVM_ADDRESS);
+    assert!(harness.process(&[unreserved], &[&relayer]).await.is_err());
+
+    reserve(&mut harness, &maker, &taker, 2).await.unwrap();
+    let reserved = offer(&mut harness, &maker).await;
+    assert_eq!((reserved.status, reserved.taker), (OfferStatus::Pending, Some(taker.pubkey())));
+    assert!(reserve(&mut harness, &maker, &stranger, 3).await.is_err());
+    harness.run_computations().await.unwrap();
+    let revealed = offer(&mut harness, &maker).await;
+    assert_eq!(revealed.status, OfferStatus::Open);
+    assert_eq!(revealed.taker_evm_address, TAKER_EVM_ADDRESS);
+
+    let to_stranger = settle(&relayer, &revealed, stranger.pubkey(), 4, TAKER_EVM_ADDRESS);
+    assert!(harness.process(&[to_stranger], &[&relayer]).await.is_err());
+    let wrong_sender = settle(&relayer, &revealed, taker.pubkey(), 5, [0x99; 20]);
+    assert!(harness.process(&[wrong_sender], &[&relayer]).await.is_err());
+
+    let taker_before = harness.balance(taker.pubkey()).await;
+    let paid = settle(&relayer, &revealed, taker.pubkey(), 6, TAKER_EVM_ADDRESS);
+    harness.process(&[paid], &[&relayer]).await.unwrap();
+    assert_eq!(harness.balance(taker.pubkey()).await, taker_before + LAMPORTS_PER_SOL);
+    assert_eq!(offer(&mut harness, &maker).await.status, OfferStatus::Settled);
+}
+
+#[tokio::test]
+async fn aborted_reveal_releases_the_reservation() {
+    let mut harness = Harness::start().await;
+    register_chain(&mut harness, vec![]).await;
+    let maker = harness.funded_keypair().await;
+    let taker = harness.funded_keypair().await;
+    let other = harness.funded_keypair().await;
+    create(&mut harness, &maker, &[0x11; 20]).await;
+
+    reserve(&mut harness, &maker, &taker, 2).await.unwrap();
+    harness.abort_computations().await.unwrap();
+    let released = offer(&mut harness, &maker).await;
+    assert_eq!((released.status, released.taker), (OfferStatus::Open, None));
+
+    reserve(&mut harness, &maker, &other, 3).await.unwrap();
+    harness.run_computations().await.unwrap();
+    let reserved = offer(&mut harness, &maker).await;
+    assert_eq!((reserved.status, reserved.taker), (OfferStatus::Open, Some(other.pubkey())));
+}
+
+#[tokio::test]
+async fn pending_offer_is_refunded_only_once_released() {
+    let mut harness = Harness::start().await;
+    register_chain(&mut harness, vec![]).await;
+    let maker = harness.funded_keypair().await;
+    let taker = harness.funded_keypair().await;
+    create(&mut harness, &maker, &[0x11; 20]).await;
+    reserve(&mut harness, &maker, &taker, 2).await.unwrap();
+
+    let reserved = offer(&mut harness, &maker).await;
+    harness.set_unix_timestamp(reserved.deadline + 1).await;
+    let refund = || instructions::refund_solana_origin_offer(&reserved, None);
+    assert!(harness.process(&[refund()], &[]).await.is_err());
+
+    harness.abort_computations().await.unwrap();
+    let slot = harness.clock().await.slot;
+    harness.warp_to_slot(slot + 1);
+    let maker_before = harness.balance(maker.pubkey()).await;
+    harness.process(&[refund()], &[]).await.unwrap();
+    assert_eq!(harness.balance(maker.pubkey()).await, maker_before + LAMPORTS_PER_SOL);
+    assert_eq!(offer(&mut harness, &maker).await.status, OfferStatus::Refunded);
+}
+
+#[tokio::test]
+async fn stale_store_leaves_the_escrow_refundable() {
+    let mut harness = Harness::start().await;
+    register_chain(&mut harness, vec![]).await;
+    let maker = harness.funded_keypair().await;
+    queue_create(&mut harness, &maker, &[0x11; 20]).await;
+    harness.stall_computations().await.unwrap();
+
+    let failed = offer(&mut harness, &maker).await;
+    assert_eq!(failed.status, OfferStatus::VerificationFailed);
+    let maker_before = harness.balance(maker.pubkey()).await;
+    let refund = instructions::refund_solana_origin_offer(&failed, None);
+    harness.process(&[refund], &[]).await.unwrap();
+    assert_eq!(harness.balance(maker.pubkey()).await, maker_before + LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn stale_reveal_releases_the_reservation() {
+    let mut harness = Harness::start().await;
+    register_chain(&mut harness, vec![]).await;
+    let maker = harness.funded_keypair().await;
+    let taker = harness.funded_keypair().await;
+    let other = harness.funded_keypair().await;
+    create(&mut harness, &maker, &[0x11; 20]).await;
+    reserve(&mut harness, &maker, &taker, 2).await.unwrap();
+    harness.stall_computations().await.unwrap();
+
+    let released = offer(&mut harness, &maker).await;
+    assert_eq!((released.status, released.taker), (OfferStatus::Open, None));
+    reserve(&mut harness, &maker, &other, 3).await.unwrap();
+}
+
+fn rlp_prefix(offset: u8, len: usize) -> Vec<u8> {
+    if len < 56 {
+        return vec![offset + len as u8];
+    }
+    let len = len.to_be_bytes();
+    let skip = len.iter().take_while(|&&b| b == 0).count();
+    [&[offset + 55 + (len.len() - skip) as u8][..], &len[skip..]].concat()
+}
+
+fn rlp_string(bytes: &[u8]) -> Vec<u8> {
+    match bytes {
+        [byte] if *byte < 0x80 => vec![*byte],
+        _ => [rlp_prefix(0x80, bytes.len()), bytes.to_vec()].concat(),
+    }
+}
+
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload = items.concat();
+    [rlp_prefix(0xc0, payload.len()), payload].concat()
+}
+
+fn rlp_u64(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take_while(|&&b| b == 0).count();
+    rlp_string(&bytes[skip..])
+}
+
+/// RLP of a block header with `parent_hash`, `receipts_root`, `number` and
+/// `timestamp` at their positions and every other field empty
+fn header_rlp(
+    parent_hash: [u8; 32],
+    number: u64,
+    receipts_root: [u8; 32],
+    timestamp: u64,
+) -> Vec<u8> {
+    let items = (0..15)
+        .map(|i| match i {
+            0 => rlp_string(&parent_hash),
+            5 => rlp_string(&receipts_root),
+            8 => rlp_u64(number),
+            11 => rlp_u64(timestamp),
+            _ => rlp_string(&[]),
+        })
+        .collect::<Vec<_>>();
+    rlp_list(&items)
+}
+
+/// A successful receipt whose only log is an `EVM_TOKEN` transfer from
+/// `from` to `to`
+fn transfer_receipt(from: [u8; 20], to: [u8; 20]) -> Vec<u8> {
+    let topic = |address: [u8; 20]| rlp_string(&[&[0; 12][..], &address].concat());
+    let topics = rlp_list(&[rlp_string(&TRANSFER_TOPIC), topic(from), topic(to)]);
+    let log = rlp_list(&[rlp_string(&EVM_TOKEN), topics, rlp_string(&[0; 32])]);
+    rlp_list(&[rlp_string(&[1]), rlp_string(&[]), rlp_string(&[]), rlp_list(&[log])])
+}
+
+/// Root of a receipt trie holding only `receipt`, as tx 0, and the proof of it
+fn single_receipt_trie(receipt: &[u8]) -> ([u8; 32], Vec<Vec<u8>>) {
+    // Key rlp(0) = 0x80, stored in a single leaf with an even-length path
+    let leaf = rlp_list(&[rlp_string(&[0x20, 0x80]), rlp_string(receipt)]);
+    (keccak::hash(&leaf).to_bytes(), vec![leaf])
+}
+
+#[tokio::test]
+async fn block_headers_extend_their_posted_parent() {
+    let mut harness = Harness::start().await;
+    let relayer = harness.funded_keypair().await;
+    register_chain(&mut harness, vec![relayer.pubkey()]).await;
+    let post = |number, parent_hash| {
+        let header_rlp = header_rlp(parent_hash, number, [0; 32], 0);
+        instructions::post_evm_block_header(relayer.pubkey(), CHAIN_ID, number, header_rlp)
+    };
+    let header = |number| pda::evm_block_header(CHAIN_ID, number);
+
+    harness.process(&[post(100, [1; 32])], &[&relayer]).await.unwrap();
+    let anchor: EvmBlockHeader = harness.account(header(100)).await.unwrap();
+    assert_eq!(anchor.linked_from, 100);
+
+    assert!(harness.process(&[post(101, [2; 32])], &[&relayer]).await.is_err());
+    harness.process(&[post(101, anchor.block_hash)], &[&relayer]).await.unwrap();
+    let child: EvmBlockHeader = harness.account(header(101)).await.unwrap();
+    assert_eq!(child.linked_from, 100);
+
+    // With block 102 missing, block 103 starts a run of its own
+    harness.process(&[post(103, [3; 32])], &[&relayer]).await.unwrap();
+    let detached: EvmBlockHeader = harness.account(header(103)).await.unwrap();
+    assert_eq!(detached.linked_from, 103);
+}
+
+#[tokio::test]
+async fn receipt_proof_settles_only_a_live_reservation() {
+    let mut harness = Harness::start().await;
+    let relayer = harness.funded_keypair().await;
+    register_chain(&mut harness, vec![relayer.pubkey()]).await;
+    let maker = harness.funded_keypair().await;
+    let taker = harness.funded_keypair().await;
+    let payout_address = [0x22; 20];
+    create(&mut harness, &maker, &payout_address).await;
+    reserve(&mut harness, &maker, &taker, 2).await.unwrap();
+    harness.run_computations().await.unwrap();
+
+    // Block 100 carries the taker's payment and block 112 makes it final;
+    // its timestamp is after both reservations below
+    let receipt = transfer_receipt(TAKER_EVM_ADDRESS, payout_address);
+    let (receipts_root, receipt_proof) = single_receipt_trie(&receipt);
+    let timestamp = harness.clock().await.unix_timestamp as u64 + 3_600;
+    let mut parent_hash = [1; 32];
+    for number in 100..=112 {
+        let header_rlp = header_rlp(parent_hash, number, receipts_root, timestamp);
+        parent_hash = keccak::hash(&header_rlp).to_bytes();
+        let post =
+            instructions::post_evm_block_header(relayer.pubkey(), CHAIN_ID, number, header_rlp);
+        harness.process(&[post], &[&relayer]).await.unwrap();
+    }
+    let payer = harness.payer();
+    let prove = |offer: &SolanaOriginOffer| {
+        instructions::settle_solana_origin_offer_with_receipt_proof(
+            payer,
+            offer,
+            12,
+            None,
+            instruction::SettleSolanaOriginOfferWithReceiptProof {
+                offer_id: OFFER_ID,
+                block_number: 100,
+                tx_index: 0,
+                log_index: 0,
+                receipt_proof: receipt_proof.clone(),
+                payout_address,
+                payout_salt: PAYOUT_SALT,
+            },
+        )
+    };
+
+    // The 30 minute reservation lapses before the proof comes in
+    let lapsed = offer(&mut harness, &maker).await;
+    harness.set_unix_timestamp(lapsed.reserved_at + 30 * 60).await;
+    assert!(harness.process(&[prove(&lapsed)], &[]).await.is_err());
+
+    reserve(&mut harness, &maker, &taker, 3).await.unwrap();
+    harness.run_computations().await.unwrap();
+    harness.next_slot().await;
+    let reserved = offer(&mut harness, &maker).await;
+    let taker_before = harness.balance(taker.pubkey()).await;
+    harness.process(&[prove(&reserved)], &[]).await.unwrap();
+    assert_eq!(harness.balance(taker.pubkey()).await, taker_before + LAMPORTS_PER_SOL);
+    assert_eq!(offer(&mut harness, &maker).await.status, OfferStatus::Settled);
+}
@@ -1,5 +1,7 @@
 use arcis_imports::*;
 
+pub mod reference;
+
 #[encrypted]
 mod circuits {
     use arcis_imports::*;
@@ -149,4 +151,212 @@ mod circuits {
             .owner
             .from_arcis(FinalizeIntrachainOutput { ack })
     }
-}
\ No newline at end of file
+
+    pub struct CreateBasketInput {
+        seller_identity_hash: u64,
+    }
+
+    pub struct CreateBasketOutput {
+        ack: u64,
+    }
+
+    #[instruction]
+    pub fn create_basket_offer(
+        input_ctxt: Enc<Shared, CreateBasketInput>,
+    ) -> Enc<Shared, CreateBasketOutput> {
+        let input = input_ctxt.to_arcis();
+        let ack = input.seller_identity_hash;
+        input_ctxt
+            .owner
+            .from_arcis(CreateBasketOutput { ack })
+    }
+
+    pub struct FinalizeBasketInput {
+        buyer_identity_hash: u64,
+    }
+
+    pub struct FinalizeBasketOutput {
+        ack: u64,
+    }
+
+    #[instruction]
+    pub fn finalize_basket_offer(
+        input_ctxt: Enc<Shared, FinalizeBasketInput>,
+    ) -> Enc<Shared, FinalizeBasketOutput> {
+        let input = input_ctxt.to_arcis();
+        let ack = input.buyer_identity_hash;
+        input_ctxt
+            .owner
+            .from_arcis(FinalizeBasketOutput { ack })
+    }
+
+    pub struct ProposeCounterOfferInput {
+        proposer_identity_hash: u64,
+    }
+
+    pub struct ProposeCounterOfferOutput {
+        ack: u64,
+    }
+
+    #[instruction]
+    pub fn propose_counter_offer(
+        input_ctxt: Enc<Shared, ProposeCounterOfferInput>,
+    ) -> Enc<Shared, ProposeCounterOfferOutput> {
+        let input = input_ctxt.to_arcis();
+        let ack = input.proposer_identity_hash;
+        input_ctxt
+            .owner
+            .from_arcis(ProposeCounterOfferOutput { ack })
+    }
+
+    /// A maker's price for the whole RFQ size, in the quote asset
+    pub struct RfqQuote {
+        price: u64,
+        maker_identity_hash: u64,
+    }
+
+    /// Lowest quote ranked so far and the index of the maker who sent it
+    pub struct RfqBest {
+        price: u64,
+        maker_identity_hash: u64,
+        index: u8,
+    }
+
+    #[instruction]
+    pub fn rank_rfq_quote(
+        quote_ctxt: Enc<Shared, RfqQuote>,
+        best_ctxt: Enc<Mxe, RfqBest>,
+        index: u8,
+    ) -> Enc<Mxe, RfqBest> {
+        let quote = quote_ctxt.to_arcis();
+        let best = best_ctxt.to_arcis();
+        // The first quote replaces the RFQ's still zeroed state; ties keep
+        // the earlier quote
+        let next = if index == 0 || quote.price < best.price {
+            RfqBest {
+                price: quote.price,
+                maker_identity_hash: quote.maker_identity_hash,
+                index,
+            }
+        } else {
+            best
+        };
+        best_ctxt.owner.from_arcis(next)
+    }
+
+    #[instruction]
+    pub fn select_rfq_quote(best_ctxt: Enc<Mxe, RfqBest>) -> (u64, u8) {
+        let best = best_ctxt.to_arcis();
+        (best.price.reveal(), best.index.reveal())
+    }
+
+    /// EVM address split into its first 16 and last 4 bytes (big-endian)
+    pub struct EvmPayoutAddress {
+        hi: u128,
+        lo: u32,
+    }
+
+    #[instruction]
+    pub fn store_evm_payout_address(
+        input_ctxt: Enc<Shared, EvmPayoutAddress>,
+        mxe: Mxe,
+    ) -> Enc<Mxe, EvmPayoutAddress> {
+        let input = input_ctxt.to_arcis();
+        mxe.from_arcis(input)
+    }
+
+    /// Tells the taker who reserved a Solana-origin offer where to pay
+    #[instruction]
+    pub fn reveal_evm_payout_address(
+        taker: Shared,
+        payout_ctxt: Enc<Mxe, EvmPayoutAddress>,
+    ) -> Enc<Shared, EvmPayoutAddress> {
+        let payout = payout_ctxt.to_arcis();
+        taker.from_arcis(payout)
+    }
+
+    /// A fiat seller's payment instructions, up to 64 bytes of text packed
+    /// little endian into four fields
+    pub struct FiatPaymentDetails {
+        part_0: u128,
+        part_1: u128,
+        part_2: u128,
+        part_3: u128,
+    }
+
+    #[instruction]
+    pub fn store_fiat_payment_details(
+        input_ctxt: Enc<Shared, FiatPaymentDetails>,
+        mxe: Mxe,
+    ) -> Enc<Mxe, FiatPaymentDetails> {
+        let input = input_ctxt.to_arcis();
+        mxe.from_arcis(input)
+    }
+
+    #[instruction]
+    pub fn reveal_fiat_payment_details(
+        buyer: Shared,
+        details_ctxt: Enc<Mxe, FiatPaymentDetails>,
+    ) -> Enc<Shared, FiatPaymentDetails> {
+        let details = details_ctxt.to_arcis();
+        buyer.from_arcis(details)
+    }
+
+    pub struct TradeIdentity {
+        part_0: u128,
+        part_1: u128,
+        part_2: u128,
+        part_3: u128,
+    }
+
+    #[instruction]
+    pub fn store_trade_identity(
+        input_ctxt: Enc<Shared, TradeIdentity>,
+        mxe: Mxe,
+    ) -> Enc<Mxe, TradeIdentity> {
+        let input = input_ctxt.to_arcis();
+        mxe.from_arcis(input)
+    }
+
+    /// Each party's identity goes to the other one: the seller's to the
+    /// buyer, the buyer's to the seller
+    #[instruction]
+    pub fn reveal_trade_identities(
+        seller: Shared,
+        buyer: Shared,
+        seller_identity_ctxt: Enc<Mxe, TradeIdentity>,
+        buyer_identity_ctxt: Enc<Mxe, TradeIdentity>,
+    ) -> (Enc<Shared, TradeIdentity>, Enc<Shared, TradeIdentity>) {
+        let seller_identity = seller_identity_ctxt.to_arcis();
+        let buyer_identity = buyer_identity_ctxt.to_arcis();
+        (buyer.from_arcis(seller_identity), seller.from_arcis(buyer_identity))
+    }
+
+    /// Price, in a feed's units, at which a conditional offer activates:
+    /// once the feed is at or above it if `above`, at or below otherwise
+    pub struct PriceTrigger {
+        threshold: u64,
+        above: bool,
+    }
+
+    #[instruction]
+    pub fn store_price_trigger(
+        input_ctxt: Enc<Shared, PriceTrigger>,
+        mxe: Mxe,
+    ) -> Enc<Mxe, PriceTrigger> {
+        let input = input_ctxt.to_arcis();
+        mxe.from_arcis(input)
+    }
+
+    /// Reveals only whether `price` crossed the trigger, never the trigger
+    #[instruction]
+    pub fn check_price_trigger(trigger_ctxt: Enc<Mxe, PriceTrigger>, price: u64) -> bool {
+        let trigger = trigger_ctxt.to_arcis();
+        let crossed = if trigger.above {
+            price >= trigger.threshold
+        } else {
+            price <= trigger.threshold
+        };
+        crossed.reveal()
+    }
+}
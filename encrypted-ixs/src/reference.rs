@@ -0,0 +1,516 @@
+//! Plaintext reference models of the circuits.
+//!
+//! Each model takes the decrypted input fields in declaration order and
+//! returns the fields the circuit re-encrypts. The tests run every circuit
+//! on random inputs and check its decrypted output against its model, so a
+//! circuit change that alters what callbacks receive fails here first.
+
+/// `add_together`: both inputs widened before adding, so it never wraps
+pub fn add_together(v1: u8, v2: u8) -> u16 {
+    u16::from(v1) + u16::from(v2)
+}
+
+/// `relay_offer_clone`: acknowledges the external seller's identity hash
+pub fn relay_offer_clone(external_seller_identity_hash: u64) -> u64 {
+    external_seller_identity_hash
+}
+
+/// `confidential_deposit_native`: the deposit amount is processed as is
+pub fn confidential_deposit_native(amount: u64) -> u64 {
+    amount
+}
+
+/// `interchain_origin_evm_deposit_seller_spl`: acknowledges the seller
+pub fn interchain_origin_evm_deposit_seller_spl(seller_identity_hash: u64) -> u64 {
+    seller_identity_hash
+}
+
+/// `deposit_seller_native`: acknowledges the seller
+pub fn deposit_seller_native(seller_identity_hash: u64) -> u64 {
+    seller_identity_hash
+}
+
+/// `deposit_seller_spl`: acknowledges the seller
+pub fn deposit_seller_spl(seller_identity_hash: u64) -> u64 {
+    seller_identity_hash
+}
+
+/// `finalize_interchain_origin_evm_offer`: acknowledges the buyer
+pub fn finalize_interchain_origin_evm_offer(buyer_identity_hash: u64) -> u64 {
+    buyer_identity_hash
+}
+
+/// `finalize_intrachain_offer`: acknowledges the buyer
+pub fn finalize_intrachain_offer(buyer_identity_hash: u64) -> u64 {
+    buyer_identity_hash
+}
+
+/// `create_basket_offer`: acknowledges the seller
+pub fn create_basket_offer(seller_identity_hash: u64) -> u64 {
+    seller_identity_hash
+}
+
+/// `finalize_basket_offer`: acknowledges the buyer
+pub fn finalize_basket_offer(buyer_identity_hash: u64) -> u64 {
+    buyer_identity_hash
+}
+
+/// `propose_counter_offer`: acknowledges the proposer
+pub fn propose_counter_offer(proposer_identity_hash: u64) -> u64 {
+    proposer_identity_hash
+}
+
+/// `rank_rfq_quote`: the quote `(price, maker_identity_hash)` at `index`
+/// replaces the `(price, maker_identity_hash, index)` best so far if it is
+/// the first or strictly cheaper
+pub fn rank_rfq_quote(quote: (u64, u64), best: (u64, u64, u8), index: u8) -> (u64, u64, u8) {
+    if index == 0 || quote.0 < best.0 {
+        (quote.0, quote.1, index)
+    } else {
+        best
+    }
+}
+
+/// `select_rfq_quote`: reveals the best price and its index, never the
+/// maker's identity
+pub fn select_rfq_quote(best: (u64, u64, u8)) -> (u64, u8) {
+    (best.0, best.2)
+}
+
+/// `store_evm_payout_address`: the address halves re-encrypted to the MXE
+/// unchanged
+pub fn store_evm_payout_address(hi: u128, lo: u32) -> (u128, u32) {
+    (hi, lo)
+}
+
+/// `reveal_evm_payout_address`: the stored address halves re-encrypted to
+/// the taker unchanged
+pub fn reveal_evm_payout_address(hi: u128, lo: u32) -> (u128, u32) {
+    (hi, lo)
+}
+
+/// `store_fiat_payment_details`: the payment details re-encrypted to the
+/// MXE unchanged
+pub fn store_fiat_payment_details(parts: [u128; 4]) -> [u128; 4] {
+    parts
+}
+
+/// `reveal_fiat_payment_details`: the stored payment details re-encrypted
+/// to the buyer unchanged
+pub fn reveal_fiat_payment_details(parts: [u128; 4]) -> [u128; 4] {
+    parts
+}
+
+/// `store_trade_identity`: a party's identity re-encrypted to the MXE
+/// unchanged
+pub fn store_trade_identity(parts: [u128; 4]) -> [u128; 4] {
+    parts
+}
+
+/// `reveal_trade_identities`: the seller's identity for the buyer and the
+/// buyer's for the seller, in that order
+pub fn reveal_trade_identities(
+    seller_identity: [u128; 4],
+    buyer_identity: [u128; 4],
+) -> ([u128; 4], [u128; 4]) {
+    (seller_identity, buyer_identity)
+}
+
+/// `store_price_trigger`: a conditional offer's trigger re-encrypted to
+/// the MXE unchanged
+pub fn store_price_trigger(threshold: u64, above: bool) -> (u64, bool) {
+    (threshold, above)
+}
+
+/// `check_price_trigger`: whether `price` reached the threshold from the
+/// trigger's side, inclusive
+pub fn check_price_trigger((threshold, above): (u64, bool), price: u64) -> bool {
+    if above {
+        price >= threshold
+    } else {
+        price <= threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arcis_imports::*;
+    use proptest::prelude::*;
+
+    use crate::circuits;
+
+    /// A valid test key, as the generated circuit tests use
+    const CLIENT_KEY: u64 = 16;
+
+    fn client() -> Shared {
+        Shared::new(ArcisPublicKey::from_values(&[Number::from(CLIENT_KEY)]))
+    }
+
+    fn encrypt<T: ArcisType>(fields: &[Number]) -> Enc<Shared, T> {
+        client().from_arcis(T::from_values(fields))
+    }
+
+    fn decrypt<C: Cipher, T: ArcisType>(output: Enc<C, T>) -> Vec<Number> {
+        let mut fields = Vec::new();
+        output.to_arcis().handle_outputs(&mut fields);
+        fields
+    }
+
+    /// The RFQ state `rank_rfq_quote` reads, encrypted to the MXE
+    fn rfq_best(
+        (price, maker_identity_hash, index): (u64, u64, u8),
+    ) -> Enc<Mxe, circuits::RfqBest> {
+        Mxe::get().from_arcis(circuits::RfqBest::from_values(&[
+            Number::from(price),
+            Number::from(maker_identity_hash),
+            Number::from(index),
+        ]))
+    }
+
+    /// The payout address `reveal_evm_payout_address` reads, encrypted to
+    /// the MXE
+    fn evm_payout_address((hi, lo): (u128, u32)) -> Enc<Mxe, circuits::EvmPayoutAddress> {
+        Mxe::get().from_arcis(circuits::EvmPayoutAddress::from_values(&[
+            Number::from(hi),
+            Number::from(lo),
+        ]))
+    }
+
+    /// Payment details `reveal_fiat_payment_details` reads, encrypted to the
+    /// MXE
+    fn fiat_payment_details(parts: [u128; 4]) -> Enc<Mxe, circuits::FiatPaymentDetails> {
+        Mxe::get().from_arcis(circuits::FiatPaymentDetails::from_values(&parts.map(Number::from)))
+    }
+
+    /// A party's identity `reveal_trade_identities` reads, encrypted to the
+    /// MXE
+    fn trade_identity(parts: [u128; 4]) -> Enc<Mxe, circuits::TradeIdentity> {
+        Mxe::get().from_arcis(circuits::TradeIdentity::from_values(&parts.map(Number::from)))
+    }
+
+    /// The trigger `check_price_trigger` reads, encrypted to the MXE
+    fn price_trigger((threshold, above): (u64, bool)) -> Enc<Mxe, circuits::PriceTrigger> {
+        Mxe::get().from_arcis(circuits::PriceTrigger::from_values(&[
+            Number::from(threshold),
+            Number::from(above),
+        ]))
+    }
+
+    /// Runs a one-field `u64` circuit, checking it answers the same client
+    fn run_u64<I: ArcisType, O: ArcisType>(
+        circuit: fn(Enc<Shared, I>) -> Enc<Shared, O>,
+        input: u64,
+    ) -> Vec<Number> {
+        let output = circuit(encrypt(&[Number::from(input)]));
+        assert_eq!(output.owner.public_key, client().public_key);
+        decrypt(output)
+    }
+
+    #[test]
+    fn add_together_widens_instead_of_wrapping() {
+        assert_eq!(super::add_together(u8::MAX, u8::MAX), 510);
+        let output = circuits::add_together(encrypt(&[
+            Number::from(u8::MAX),
+            Number::from(u8::MAX),
+        ]));
+        assert_eq!(decrypt(output), vec![Number::from(510u16)]);
+    }
+
+    #[test]
+    fn acks_pass_u64_max_through() {
+        assert_eq!(run_u64(circuits::relay_offer_clone, u64::MAX), vec![Number::from(u64::MAX)]);
+        assert_eq!(
+            run_u64(circuits::confidential_deposit_native, u64::MAX),
+            vec![Number::from(u64::MAX)]
+        );
+    }
+
+    #[test]
+    fn store_evm_payout_address_keeps_full_width_halves() {
+        let output = circuits::store_evm_payout_address(
+            encrypt(&[Number::from(u128::MAX), Number::from(u32::MAX)]),
+            Mxe::get(),
+        );
+        assert_eq!(decrypt(output), vec![Number::from(u128::MAX), Number::from(u32::MAX)]);
+    }
+
+    #[test]
+    fn reveal_evm_payout_address_answers_the_taker() {
+        let taker = || Shared::new(ArcisPublicKey::from_values(&[Number::from(CLIENT_KEY + 1)]));
+        let output =
+            circuits::reveal_evm_payout_address(taker(), evm_payout_address((u128::MAX, u32::MAX)));
+        assert_eq!(output.owner.public_key, taker().public_key);
+        assert_eq!(decrypt(output), vec![Number::from(u128::MAX), Number::from(u32::MAX)]);
+    }
+
+    #[test]
+    fn reveal_fiat_payment_details_answers_the_buyer() {
+        let buyer = || Shared::new(ArcisPublicKey::from_values(&[Number::from(CLIENT_KEY + 1)]));
+        let parts = [u128::MAX, 0, 1, u128::MAX - 1];
+        let output = circuits::reveal_fiat_payment_details(buyer(), fiat_payment_details(parts));
+        assert_eq!(output.owner.public_key, buyer().public_key);
+        assert_ne!(output.owner.public_key, client().public_key);
+        assert_eq!(decrypt(output), parts.map(Number::from).to_vec());
+    }
+
+    #[test]
+    fn reveal_trade_identities_crosses_the_parties() {
+        let buyer = || Shared::new(ArcisPublicKey::from_values(&[Number::from(CLIENT_KEY + 1)]));
+        let seller_identity = [1, 2, 3, u128::MAX];
+        let buyer_identity = [u128::MAX, 6, 7, 8];
+        let (for_buyer, for_seller) = circuits::reveal_trade_identities(
+            client(),
+            buyer(),
+            trade_identity(seller_identity),
+            trade_identity(buyer_identity),
+        );
+        assert_eq!(for_buyer.owner.public_key, buyer().public_key);
+        assert_eq!(for_seller.owner.public_key, client().public_key);
+        assert_eq!(decrypt(for_buyer), seller_identity.map(Number::from).to_vec());
+        assert_eq!(decrypt(for_seller), buyer_identity.map(Number::from).to_vec());
+    }
+
+    #[test]
+    fn check_price_trigger_includes_the_threshold() {
+        assert!(circuits::check_price_trigger(price_trigger((100, true)), 100));
+        assert!(!circuits::check_price_trigger(price_trigger((100, true)), 99));
+        assert!(circuits::check_price_trigger(price_trigger((100, false)), 100));
+        assert!(!circuits::check_price_trigger(price_trigger((100, false)), 101));
+    }
+
+    #[test]
+    fn rank_rfq_quote_keeps_the_earlier_of_equal_prices() {
+        let best = (100, 1, 0);
+        let output = circuits::rank_rfq_quote(
+            encrypt(&[Number::from(100u64), Number::from(2u64)]),
+            rfq_best(best),
+            1,
+        );
+        assert_eq!(
+            decrypt(output),
+            vec![Number::from(100u64), Number::from(1u64), Number::from(0u8)]
+        );
+    }
+
+    proptest! {
+        // Each circuit run derives the Rescue key in plaintext field
+        // arithmetic, about a second per case in a debug build
+        #![proptest_config(ProptestConfig::with_cases(4))]
+
+        #[test]
+        fn add_together_matches_model(v1: u8, v2: u8) {
+            let output = circuits::add_together(encrypt(&[Number::from(v1), Number::from(v2)]));
+            prop_assert_eq!(decrypt(output), vec![Number::from(super::add_together(v1, v2))]);
+        }
+
+        #[test]
+        fn acks_match_models(input: u64) {
+            let expected = |model: fn(u64) -> u64| vec![Number::from(model(input))];
+            prop_assert_eq!(
+                run_u64(circuits::relay_offer_clone, input),
+                expected(super::relay_offer_clone)
+            );
+            prop_assert_eq!(
+                run_u64(circuits::confidential_deposit_native, input),
+                expected(super::confidential_deposit_native)
+            );
+            prop_assert_eq!(
+                run_u64(circuits::interchain_origin_evm_deposit_seller_spl, input),
+                expected(super::interchain_origin_evm_deposit_seller_spl)
+            );
+            prop_assert_eq!(
+                run_u64(circuits::deposit_seller_native, input),
+                expected(super::deposit_seller_native)
+            );
+            prop_assert_eq!(
+                run_u64(circuits::deposit_seller_spl, input),
+                expected(super::deposit_seller_spl)
+            );
+            prop_assert_eq!(
+                run_u64(circuits::finalize_interchain_origin_evm_offer, input),
+                expected(super::finalize_interchain_origin_evm_offer)
+            );
+            prop_assert_eq!(
+                run_u64(circuits::finalize_intrachain_offer, input),
+                expected(super::finalize_intrachain_offer)
+            );
+            prop_assert_eq!(
+                run_u64(circuits::create_basket_offer, input),
+                expected(super::create_basket_offer)
+            );
+            prop_assert_eq!(
+                run_u64(circuits::finalize_basket_offer, input),
+                expected(super::finalize_basket_offer)
+            );
+            prop_assert_eq!(
+                run_u64(circuits::propose_counter_offer, input),
+                expected(super::propose_counter_offer)
+            );
+        }
+
+        #[test]
+        fn store_evm_payout_address_matches_model(hi: u128, lo: u32) {
+            let output = circuits::store_evm_payout_address(
+                encrypt(&[Number::from(hi), Number::from(lo)]),
+                Mxe::get(),
+            );
+            let (hi, lo) = super::store_evm_payout_address(hi, lo);
+            prop_assert_eq!(decrypt(output), vec![Number::from(hi), Number::from(lo)]);
+        }
+
+        #[test]
+        fn reveal_evm_payout_address_matches_model(hi: u128, lo: u32) {
+            let output =
+                circuits::reveal_evm_payout_address(client(), evm_payout_address((hi, lo)));
+            let (hi, lo) = super::reveal_evm_payout_address(hi, lo);
+            prop_assert_eq!(decrypt(output), vec![Number::from(hi), Number::from(lo)]);
+        }
+
+        #[test]
+        fn store_fiat_payment_details_matches_model(parts: [u128; 4]) {
+            let output = circuits::store_fiat_payment_details(
+                encrypt(&parts.map(Number::from)),
+                Mxe::get(),
+            );
+            let expected = super::store_fiat_payment_details(parts);
+            prop_assert_eq!(decrypt(output), expected.map(Number::from).to_vec());
+        }
+
+        #[test]
+        fn reveal_fiat_payment_details_matches_model(parts: [u128; 4]) {
+            let output = circuits::reveal_fiat_payment_details(
+                client(),
+                fiat_payment_details(parts),
+            );
+            let expected = super::reveal_fiat_payment_details(parts);
+            prop_assert_eq!(decrypt(output), expected.map(Number::from).to_vec());
+        }
+
+        #[test]
+        fn store_trade_identity_matches_model(parts: [u128; 4]) {
+            let output =
+                circuits::store_trade_identity(encrypt(&parts.map(Number::from)), Mxe::get());
+            let expected = super::store_trade_identity(parts);
+            prop_assert_eq!(decrypt(output), expected.map(Number::from).to_vec());
+        }
+
+        #[test]
+        fn reveal_trade_identities_matches_model(seller: [u128; 4], buyer: [u128; 4]) {
+            let (for_buyer, for_seller) = circuits::reveal_trade_identities(
+                client(),
+                client(),
+                trade_identity(seller),
+                trade_identity(buyer),
+            );
+            let (expected_for_buyer, expected_for_seller) =
+                super::reveal_trade_identities(seller, buyer);
+            prop_assert_eq!(decrypt(for_buyer), expected_for_buyer.map(Number::from).to_vec());
+            prop_assert_eq!(decrypt(for_seller), expected_for_seller.map(Number::from).to_vec());
+        }
+
+        #[test]
+        fn rank_rfq_quote_matches_model(quote: (u64, u64), best: (u64, u64, u8), index: u8) {
+            let output = circuits::rank_rfq_quote(
+                encrypt(&[Number::from(quote.0), Number::from(quote.1)]),
+                rfq_best(best),
+                index,
+            );
+            let (price, maker_identity_hash, index) = super::rank_rfq_quote(quote, best, index);
+            prop_assert_eq!(
+                decrypt(output),
+                vec![Number::from(price), Number::from(maker_identity_hash), Number::from(index)]
+            );
+        }
+
+        #[test]
+        fn select_rfq_quote_matches_model(best: (u64, u64, u8)) {
+            prop_assert_eq!(
+                circuits::select_rfq_quote(rfq_best(best)),
+                super::select_rfq_quote(best)
+            );
+        }
+
+        #[test]
+        fn store_price_trigger_matches_model(threshold: u64, above: bool) {
+            let output = circuits::store_price_trigger(
+                encrypt(&[Number::from(threshold), Number::from(above)]),
+                Mxe::get(),
+            );
+            let (threshold, above) = super::store_price_trigger(threshold, above);
+            prop_assert_eq!(decrypt(output), vec![Number::from(threshold), Number::from(above)]);
+        }
+
+        #[test]
+        fn check_price_trigger_matches_model(trigger: (u64, bool), price: u64) {
+            prop_assert_eq!(
+                circuits::check_price_trigger(price_trigger(trigger), price),
+                super::check_price_trigger(trigger, price)
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn add_together_is_commutative(v1: u8, v2: u8) {
+            prop_assert_eq!(super::add_together(v1, v2), super::add_together(v2, v1));
+        }
+
+        #[test]
+        fn acks_echo_their_input(input: u64) {
+            prop_assert_eq!(super::relay_offer_clone(input), input);
+            prop_assert_eq!(super::confidential_deposit_native(input), input);
+            prop_assert_eq!(super::interchain_origin_evm_deposit_seller_spl(input), input);
+            prop_assert_eq!(super::deposit_seller_native(input), input);
+            prop_assert_eq!(super::deposit_seller_spl(input), input);
+            prop_assert_eq!(super::finalize_interchain_origin_evm_offer(input), input);
+            prop_assert_eq!(super::finalize_intrachain_offer(input), input);
+            prop_assert_eq!(super::create_basket_offer(input), input);
+            prop_assert_eq!(super::finalize_basket_offer(input), input);
+            prop_assert_eq!(super::propose_counter_offer(input), input);
+        }
+
+        #[test]
+        fn rank_rfq_quote_keeps_the_lowest_price(prices: Vec<u64>) {
+            let best = prices
+                .iter()
+                .take(usize::from(u8::MAX))
+                .enumerate()
+                .fold((0, 0, 0), |best, (index, &price)| {
+                    super::rank_rfq_quote((price, index as u64), best, index as u8)
+                });
+            if let Some(&lowest) = prices.iter().take(usize::from(u8::MAX)).min() {
+                prop_assert_eq!(best.0, lowest);
+                // The earliest of equally low quotes wins
+                let earliest = prices.iter().position(|&price| price == lowest);
+                prop_assert_eq!(earliest, Some(best.1 as usize));
+            }
+        }
+
+        #[test]
+        fn evm_payout_address_round_trips(hi: u128, lo: u32) {
+            let (stored_hi, stored_lo) = super::store_evm_payout_address(hi, lo);
+            prop_assert_eq!(super::reveal_evm_payout_address(stored_hi, stored_lo), (hi, lo));
+        }
+
+        #[test]
+        fn fiat_payment_details_round_trip(parts: [u128; 4]) {
+            let stored = super::store_fiat_payment_details(parts);
+            prop_assert_eq!(super::reveal_fiat_payment_details(stored), parts);
+        }
+
+        #[test]
+        fn trade_identities_round_trip(seller: [u128; 4], buyer: [u128; 4]) {
+            let stored = (super::store_trade_identity(seller), super::store_trade_identity(buyer));
+            prop_assert_eq!(super::reveal_trade_identities(stored.0, stored.1), (seller, buyer));
+        }
+
+        #[test]
+        fn price_trigger_fires_on_one_side_only(threshold: u64, price: u64) {
+            let above = super::check_price_trigger((threshold, true), price);
+            let below = super::check_price_trigger((threshold, false), price);
+            // Both sides only hold at the threshold itself
+            prop_assert!(above || below);
+            prop_assert_eq!(above && below, price == threshold);
+        }
+    }
+}
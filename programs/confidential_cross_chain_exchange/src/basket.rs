@@ -0,0 +1,1022 @@
+//! Basket offers.
+//!
+//! A maker trades several assets for several others, up to
+//! [`MAX_BASKET_LEGS`] a side, settled at once. MPC verifies the seller's
+//! identity when the offer is created and the taker's before it settles.
+//! Every leg is escrowed in its own `BasketVault` PDA.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer as SplTransfer};
+use arcium_anchor::prelude::*;
+use arcium_client::idl::arcium::types::CallbackAccount;
+
+use crate::versioning::VersionedAccount;
+use crate::{
+    COMP_DEF_OFFSET_CREATE_BASKET_OFFER,
+    COMP_DEF_OFFSET_FINALIZE_BASKET_OFFER,
+    ComputationOperation,
+    ErrorCode,
+    EventHeader,
+    ID,
+    ID_CONST,
+    OfferAccount,
+    OfferKind,
+    OfferStatus,
+    PendingComputation,
+    RetryableOffer,
+    SignerAccount,
+    VaultDepositedEvent,
+    VaultSide,
+    abort_offer_computation,
+    leave_pending,
+    leg_releases,
+    refund_leg,
+    release_leg,
+    retry_offer_computation,
+    vault_lamports,
+};
+
+/// Most legs a basket offer has on each side
+pub const MAX_BASKET_LEGS: usize = 4;
+
+/// One escrowed asset of a basket offer or an awarded RFQ
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct BasketLeg {
+    /// SPL mint, `None` for SOL
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+/// Offer trading several assets for several others, all settled at once.
+/// Every leg is escrowed in its own vault, a `BasketVault` PDA of the offer,
+/// the depositor, the side and the leg's index.
+#[account]
+#[derive(InitSpace)]
+pub struct BasketOffer {
+    pub layout_version: u8,
+    pub id: u64,
+    pub maker: Pubkey,
+    /// Legs the maker escrows and the taker receives
+    #[max_len(MAX_BASKET_LEGS)]
+    pub offered_legs: Vec<BasketLeg>,
+    /// Legs the taker escrows and the maker receives
+    #[max_len(MAX_BASKET_LEGS)]
+    pub wanted_legs: Vec<BasketLeg>,
+    pub deadline: i64,
+    /// Taker whose identity the latest `finalize_basket_offer` verified
+    pub taker: Option<Pubkey>,
+    pub status: OfferStatus,
+    /// Offset of the latest MPC computation queued for this offer
+    pub computation_offset: u64,
+    pub bump: u8,
+}
+
+impl BasketOffer {
+    pub fn legs(&self, side: VaultSide) -> &[BasketLeg] {
+        match side {
+            VaultSide::Seller => &self.offered_legs,
+            VaultSide::Buyer => &self.wanted_legs,
+        }
+    }
+
+    /// Leg `depositor` may escrow at `leg_index` of `side` while the offer
+    /// is live; the maker funds the offered legs and takers the wanted ones
+    fn depositable_leg(&self, side: VaultSide, leg_index: u8, depositor: &Pubkey) -> Result<BasketLeg> {
+        require!(
+            matches!(self.status, OfferStatus::Pending | OfferStatus::Open),
+            ErrorCode::OfferNotOpen
+        );
+        require!(Clock::get()?.unix_timestamp <= self.deadline, ErrorCode::OfferExpired);
+        require!(
+            (side == VaultSide::Seller) == (*depositor == self.maker),
+            ErrorCode::LegMismatch
+        );
+        self.legs(side)
+            .get(usize::from(leg_index))
+            .copied()
+            .ok_or(ErrorCode::LegMismatch.into())
+    }
+}
+
+impl OfferAccount for BasketOffer {
+    const KIND: OfferKind = OfferKind::Basket;
+
+    fn offer_id(&self) -> u64 {
+        self.id
+    }
+    fn offer_maker(&self) -> Pubkey {
+        self.maker
+    }
+    fn offer_status(&self) -> OfferStatus {
+        self.status
+    }
+    fn offer_computation_offset(&self) -> u64 {
+        self.computation_offset
+    }
+    fn abort_computation(&mut self, operation: ComputationOperation) -> Result<OfferStatus> {
+        match operation {
+            ComputationOperation::CreateBasketOffer => {
+                leave_pending(&mut self.status, OfferStatus::VerificationFailed)
+            }
+            // The offer stays open for this or another taker to finalize again
+            _ => Ok(self.status),
+        }
+    }
+}
+
+impl RetryableOffer for BasketOffer {
+    fn offer_deadline(&self) -> i64 {
+        self.deadline
+    }
+    fn requeue(&mut self, computation_offset: u64) {
+        self.status = OfferStatus::Pending;
+        self.computation_offset = computation_offset;
+    }
+}
+
+fn validate_basket_legs(legs: &[BasketLeg]) -> Result<()> {
+    require!(
+        (1..=MAX_BASKET_LEGS).contains(&legs.len()),
+        ErrorCode::InvalidBasketLegs
+    );
+    for (index, leg) in legs.iter().enumerate() {
+        require!(leg.amount > 0, ErrorCode::InvalidBasketLegs);
+        require!(
+            legs[..index].iter().all(|other| other.mint != leg.mint),
+            ErrorCode::InvalidBasketLegs
+        );
+    }
+    Ok(())
+}
+
+pub fn basket_vault_address(offer: &Pubkey, depositor: &Pubkey, side: VaultSide, leg_index: u8) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"BasketVault", offer.as_ref(), depositor.as_ref(), &[side as u8], &[leg_index]],
+        &crate::ID,
+    )
+    .0
+}
+
+// === INSTRUCTIONS ===
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn create_basket_offer(
+    ctx: Context<CreateBasketOffer>,
+    // Public business fields
+    id: u64,
+    offered_legs: Vec<BasketLeg>,
+    wanted_legs: Vec<BasketLeg>,
+    deadline: i64,
+    // Confidential identity
+    ciphertext_seller_identity_hash: [u8; 32],
+    // Arcium handshake
+    pub_key: [u8; 32],
+    nonce: u128,
+    computation_offset: u64,
+) -> Result<()> {
+    validate_basket_legs(&offered_legs)?;
+    validate_basket_legs(&wanted_legs)?;
+    require!(deadline > Clock::get()?.unix_timestamp, ErrorCode::OfferExpired);
+
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+    ctx.accounts.pending_computation.record(
+        computation_offset,
+        OfferKind::Basket,
+        id,
+        ctx.accounts.basket_offer.key(),
+        ComputationOperation::CreateBasketOffer,
+        ctx.accounts.payer.key(),
+        ctx.bumps.pending_computation,
+    )?;
+
+    let offer = &mut ctx.accounts.basket_offer;
+    offer.id = id;
+    offer.maker = ctx.accounts.payer.key();
+    offer.offered_legs = offered_legs;
+    offer.wanted_legs = wanted_legs;
+    offer.deadline = deadline;
+    offer.taker = None;
+    offer.status = OfferStatus::Pending;
+    offer.computation_offset = computation_offset;
+    offer.layout_version = BasketOffer::LAYOUT_VERSION;
+    offer.bump = ctx.bumps.basket_offer;
+
+    emit!(BasketOfferCreatedEvent {
+        header: offer.event_header(offer.key(), None)?,
+        offered_legs: offer.offered_legs.clone(),
+        wanted_legs: offer.wanted_legs.clone(),
+        deadline,
+    });
+
+    let args = vec![
+        Argument::ArcisPubkey(pub_key),
+        Argument::PlaintextU128(nonce),
+        Argument::EncryptedU64(ciphertext_seller_identity_hash),
+    ];
+    let callback_accounts = [
+        CallbackAccount {
+            pubkey: ctx.accounts.basket_offer.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.pending_computation.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.payer.key(),
+            is_writable: true,
+        },
+    ];
+    queue_computation(
+        ctx.accounts,
+        computation_offset,
+        args,
+        None,
+        vec![CreateBasketOfferCallback::callback_ix(&callback_accounts)],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn finalize_basket_offer(
+    ctx: Context<FinalizeBasketOffer>,
+    offer_id: u64,
+    // Confidential buyer identity
+    ciphertext_buyer_identity_hash: [u8; 32],
+    // Arcium handshake
+    pub_key: [u8; 32],
+    nonce: u128,
+    computation_offset: u64,
+) -> Result<()> {
+    let offer = &ctx.accounts.basket_offer;
+    require!(offer.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    require!(Clock::get()?.unix_timestamp <= offer.deadline, ErrorCode::OfferExpired);
+
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+    ctx.accounts.pending_computation.record(
+        computation_offset,
+        OfferKind::Basket,
+        offer_id,
+        ctx.accounts.basket_offer.key(),
+        ComputationOperation::FinalizeBasketOffer,
+        ctx.accounts.payer.key(),
+        ctx.bumps.pending_computation,
+    )?;
+
+    let args = vec![
+        Argument::ArcisPubkey(pub_key),
+        Argument::PlaintextU128(nonce),
+        Argument::EncryptedU64(ciphertext_buyer_identity_hash),
+    ];
+    let callback_accounts = [
+        CallbackAccount {
+            pubkey: ctx.accounts.basket_offer.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.pending_computation.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.payer.key(),
+            is_writable: true,
+        },
+    ];
+    queue_computation(
+        ctx.accounts,
+        computation_offset,
+        args,
+        None,
+        vec![FinalizeBasketOfferCallback::callback_ix(&callback_accounts)],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn deposit_basket_leg_native(
+    ctx: Context<DepositBasketLegNative>,
+    offer_id: u64,
+    side: VaultSide,
+    leg_index: u8,
+) -> Result<()> {
+    let offer = &ctx.accounts.basket_offer;
+    let leg = offer.depositable_leg(side, leg_index, &ctx.accounts.depositor.key())?;
+    require!(leg.mint.is_none(), ErrorCode::LegMismatch);
+
+    let vault = ctx.accounts.leg_vault.to_account_info();
+    let missing = leg.amount.saturating_sub(vault_lamports(&vault)?);
+    require!(missing > 0, ErrorCode::LegFunded);
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.depositor.to_account_info(),
+                to: vault,
+            },
+        ),
+        missing,
+    )?;
+
+    msg!("💰 Basket offer {} leg {} funded with {} lamports", offer_id, leg_index, missing);
+    emit!(VaultDepositedEvent {
+        header: offer.event_header(offer.key(), Some(offer.status))?,
+        vault: ctx.accounts.leg_vault.key(),
+        side,
+        amount: missing,
+    });
+    Ok(())
+}
+
+pub(crate) fn deposit_basket_leg_spl(
+    ctx: Context<DepositBasketLegSpl>,
+    offer_id: u64,
+    side: VaultSide,
+    leg_index: u8,
+) -> Result<()> {
+    let offer = &ctx.accounts.basket_offer;
+    let leg = offer.depositable_leg(side, leg_index, &ctx.accounts.depositor.key())?;
+    require!(leg.mint == Some(ctx.accounts.mint.key()), ErrorCode::LegMismatch);
+
+    let missing = leg.amount.saturating_sub(ctx.accounts.leg_vault.amount);
+    require!(missing > 0, ErrorCode::LegFunded);
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            SplTransfer {
+                from: ctx.accounts.depositor_token_account.to_account_info(),
+                to: ctx.accounts.leg_vault.to_account_info(),
+                authority: ctx.accounts.depositor.to_account_info(),
+            },
+        ),
+        missing,
+    )?;
+
+    msg!("💰 Basket offer {} leg {} funded with {} tokens", offer_id, leg_index, missing);
+    emit!(VaultDepositedEvent {
+        header: offer.event_header(offer.key(), Some(offer.status))?,
+        vault: ctx.accounts.leg_vault.key(),
+        side,
+        amount: missing,
+    });
+    Ok(())
+}
+
+pub(crate) fn execute_basket_swap<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteBasketSwap<'info>>,
+    offer_id: u64,
+) -> Result<()> {
+    let offer = &ctx.accounts.basket_offer;
+    require!(offer.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    require!(Clock::get()?.unix_timestamp <= offer.deadline, ErrorCode::OfferExpired);
+    let maker = ctx.accounts.maker.key();
+    let taker = ctx.accounts.taker.key();
+    require!(offer.taker == Some(taker), ErrorCode::TakerNotVerified);
+
+    let offered_pairs = 2 * offer.offered_legs.len();
+    require!(
+        ctx.remaining_accounts.len() == offered_pairs + 2 * offer.wanted_legs.len(),
+        ErrorCode::EscrowMismatch
+    );
+    let (offered_accounts, wanted_accounts) = ctx.remaining_accounts.split_at(offered_pairs);
+
+    // Every leg is checked before any moves, so a missing or short leg
+    // fails the whole swap
+    let offer_key = offer.key();
+    let offered = leg_releases(&offer.offered_legs, offered_accounts, &taker, |leg_index| {
+        basket_vault_address(&offer_key, &maker, VaultSide::Seller, leg_index)
+    })?;
+    let wanted = leg_releases(&offer.wanted_legs, wanted_accounts, &maker, |leg_index| {
+        basket_vault_address(&offer_key, &taker, VaultSide::Buyer, leg_index)
+    })?;
+
+    msg!("🔄 Executing basket swap for offer ID: {}", offer_id);
+    let id_bytes = offer.id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[b"BasketOffer", maker.as_ref(), &id_bytes, &[offer.bump]]];
+    for release in offered.iter().chain(&wanted) {
+        release_leg(
+            &offer.to_account_info(),
+            signer_seeds,
+            release.vault,
+            release.destination,
+            release.leg.mint,
+            release.leg.amount,
+            Some(&ctx.accounts.token_program),
+        )?;
+    }
+
+    let offer = &mut ctx.accounts.basket_offer;
+    offer.status = OfferStatus::Settled;
+    emit!(BasketSwapExecutedEvent {
+        header: offer.event_header(offer.key(), Some(OfferStatus::Open))?,
+        maker,
+        taker,
+        offered_legs: offer.offered_legs.clone(),
+        wanted_legs: offer.wanted_legs.clone(),
+    });
+    msg!("✅ Basket swap completed successfully");
+    Ok(())
+}
+
+pub(crate) fn refund_basket_leg(
+    ctx: Context<RefundBasketLeg>,
+    offer_id: u64,
+    side: VaultSide,
+    leg_index: u8,
+) -> Result<()> {
+    let offer = &ctx.accounts.basket_offer;
+    let status_before = offer.status;
+    let leg = *offer
+        .legs(side)
+        .get(usize::from(leg_index))
+        .ok_or(ErrorCode::LegMismatch)?;
+    if matches!(offer.status, OfferStatus::Pending | OfferStatus::Open) {
+        require!(
+            Clock::get()?.unix_timestamp > offer.deadline,
+            ErrorCode::DeadlineNotReached
+        );
+    }
+
+    let vault = ctx.accounts.leg_vault.to_account_info();
+    let depositor = ctx.accounts.depositor.to_account_info();
+    let id_bytes = offer.id.to_le_bytes();
+    let refunded = refund_leg(
+        &offer.to_account_info(),
+        &[&[b"BasketOffer", offer.maker.as_ref(), &id_bytes, &[offer.bump]]],
+        &vault,
+        &depositor,
+        ctx.accounts.depositor_token_account.as_deref(),
+        leg.mint,
+        ctx.accounts.token_program.as_ref(),
+    )?;
+
+    let offer = &mut ctx.accounts.basket_offer;
+    if side == VaultSide::Seller && offer.status != OfferStatus::Settled {
+        offer.status = OfferStatus::Refunded;
+    }
+    msg!("↩️ Basket offer {} leg {} refunded {}", offer_id, leg_index, refunded);
+    emit!(BasketLegRefundedEvent {
+        header: offer.event_header(offer.key(), Some(status_before))?,
+        depositor: depositor.key(),
+        side,
+        leg_index,
+        vault: vault.key(),
+        refunded,
+    });
+    Ok(())
+}
+
+pub(crate) fn retry_create_basket_offer(
+    ctx: Context<RetryCreateBasketOffer>,
+    offer_id: u64,
+    ciphertext_seller_identity_hash: [u8; 32],
+    pub_key: [u8; 32],
+    nonce: u128,
+    computation_offset: u64,
+) -> Result<()> {
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+    let callback_accounts = retry_offer_computation(
+        &mut ctx.accounts.basket_offer,
+        &mut ctx.accounts.pending_computation,
+        ctx.bumps.pending_computation,
+        ctx.accounts.payer.key(),
+        offer_id,
+        ComputationOperation::CreateBasketOffer,
+        computation_offset,
+    )?;
+
+    let args = vec![
+        Argument::ArcisPubkey(pub_key),
+        Argument::PlaintextU128(nonce),
+        Argument::EncryptedU64(ciphertext_seller_identity_hash),
+    ];
+    queue_computation(
+        ctx.accounts,
+        computation_offset,
+        args,
+        None,
+        vec![CreateBasketOfferCallback::callback_ix(&callback_accounts)],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn create_basket_offer_callback(
+    ctx: Context<CreateBasketOfferCallback>,
+    output: ComputationOutputs<CreateBasketOfferOutput>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.basket_offer.status == OfferStatus::Pending,
+        ErrorCode::OfferNotPending
+    );
+    let _o = match output {
+        ComputationOutputs::Success(CreateBasketOfferOutput { field_0 }) => field_0,
+        _ => {
+            let operation = ComputationOperation::CreateBasketOffer;
+            return abort_offer_computation(&mut ctx.accounts.basket_offer, operation);
+        }
+    };
+
+    let offer = &mut ctx.accounts.basket_offer;
+    offer.status = OfferStatus::Open;
+    emit!(CreateBasketOfferEvent {
+        header: offer.event_header(offer.key(), Some(OfferStatus::Pending))?,
+    });
+    Ok(())
+}
+
+pub(crate) fn finalize_basket_offer_callback(
+    ctx: Context<FinalizeBasketOfferCallback>,
+    output: ComputationOutputs<FinalizeBasketOfferOutput>,
+) -> Result<()> {
+    let offer = &mut ctx.accounts.basket_offer;
+    let _o = match output {
+        ComputationOutputs::Success(FinalizeBasketOfferOutput { field_0 }) => field_0,
+        _ => {
+            let operation = ComputationOperation::FinalizeBasketOffer;
+            return abort_offer_computation(offer, operation);
+        }
+    };
+
+    let taker = ctx.accounts.pending_computation.requester;
+    offer.taker = Some(taker);
+    msg!("✅ Basket offer {} taker identity verified via MPC", offer.id);
+    emit!(FinalizeBasketOfferEvent {
+        header: offer.event_header(offer.key(), Some(offer.status))?,
+        taker,
+    });
+    Ok(())
+}
+
+// === ACCOUNT CONTEXTS ===
+
+#[queue_computation_accounts("create_basket_offer", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, offered_legs: Vec<BasketLeg>, wanted_legs: Vec<BasketLeg>, deadline: i64, ciphertext_seller_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct CreateBasketOffer<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = BasketOffer::SPACE,
+        seeds = [b"BasketOffer", payer.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub basket_offer: Box<Account<'info, BasketOffer>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CREATE_BASKET_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("finalize_basket_offer", payer)]
+#[derive(Accounts)]
+#[instruction(offer_id: u64, ciphertext_buyer_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct FinalizeBasketOffer<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"BasketOffer", basket_offer.maker.as_ref(), &offer_id.to_le_bytes()],
+        bump = basket_offer.bump,
+    )]
+    pub basket_offer: Box<Account<'info, BasketOffer>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_BASKET_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64, side: VaultSide, leg_index: u8)]
+pub struct DepositBasketLegNative<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        seeds = [b"BasketOffer", basket_offer.maker.as_ref(), &offer_id.to_le_bytes()],
+        bump = basket_offer.bump,
+    )]
+    pub basket_offer: Box<Account<'info, BasketOffer>>,
+
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8,
+        seeds = [
+            b"BasketVault",
+            basket_offer.key().as_ref(),
+            depositor.key().as_ref(),
+            &[side as u8],
+            &[leg_index],
+        ],
+        bump,
+    )]
+    /// CHECK: Escrow vault PDA holding the leg's lamports
+    pub leg_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64, side: VaultSide, leg_index: u8)]
+pub struct DepositBasketLegSpl<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        seeds = [b"BasketOffer", basket_offer.maker.as_ref(), &offer_id.to_le_bytes()],
+        bump = basket_offer.bump,
+    )]
+    pub basket_offer: Box<Account<'info, BasketOffer>>,
+
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = depositor,
+    )]
+    pub depositor_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [
+            b"BasketVault",
+            basket_offer.key().as_ref(),
+            depositor.key().as_ref(),
+            &[side as u8],
+            &[leg_index],
+        ],
+        bump,
+        token::mint = mint,
+        token::authority = basket_offer,
+    )]
+    pub leg_vault: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct ExecuteBasketSwap<'info> {
+    #[account(
+        mut,
+        seeds = [b"BasketOffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = basket_offer.bump,
+        has_one = maker,
+    )]
+    pub basket_offer: Box<Account<'info, BasketOffer>>,
+
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64, side: VaultSide, leg_index: u8)]
+pub struct RefundBasketLeg<'info> {
+    #[account(mut)]
+    /// CHECK: refund destination, the vault's seeds tie it to the deposit
+    pub depositor: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"BasketOffer", basket_offer.maker.as_ref(), &offer_id.to_le_bytes()],
+        bump = basket_offer.bump,
+    )]
+    pub basket_offer: Box<Account<'info, BasketOffer>>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"BasketVault",
+            basket_offer.key().as_ref(),
+            depositor.key().as_ref(),
+            &[side as u8],
+            &[leg_index],
+        ],
+        bump,
+    )]
+    /// CHECK: the leg's escrow vault, holding lamports or, for SPL legs, a token account
+    pub leg_vault: UncheckedAccount<'info>,
+
+    /// Receives an SPL leg
+    #[account(mut)]
+    pub depositor_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+#[queue_computation_accounts("create_basket_offer", payer)]
+#[derive(Accounts)]
+#[instruction(offer_id: u64, ciphertext_seller_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct RetryCreateBasketOffer<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"BasketOffer", payer.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = basket_offer.bump,
+    )]
+    pub basket_offer: Box<Account<'info, BasketOffer>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CREATE_BASKET_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("create_basket_offer")]
+#[derive(Accounts)]
+pub struct CreateBasketOfferCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CREATE_BASKET_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub basket_offer: Box<Account<'info, BasketOffer>>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::CreateBasketOffer @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == basket_offer.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[callback_accounts("finalize_basket_offer")]
+#[derive(Accounts)]
+pub struct FinalizeBasketOfferCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_BASKET_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub basket_offer: Box<Account<'info, BasketOffer>>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::FinalizeBasketOffer @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == basket_offer.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[init_computation_definition_accounts("create_basket_offer", payer)]
+#[derive(Accounts)]
+pub struct InitCreateBasketOfferCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("finalize_basket_offer", payer)]
+#[derive(Accounts)]
+pub struct InitFinalizeBasketOfferCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+// === EVENTS ===
+
+#[event]
+pub struct BasketOfferCreatedEvent {
+    pub header: EventHeader,
+    pub offered_legs: Vec<BasketLeg>,
+    pub wanted_legs: Vec<BasketLeg>,
+    pub deadline: i64,
+}
+
+#[event]
+pub struct CreateBasketOfferEvent {
+    pub header: EventHeader,
+}
+
+#[event]
+pub struct FinalizeBasketOfferEvent {
+    pub header: EventHeader,
+    pub taker: Pubkey,
+}
+
+#[event]
+pub struct BasketSwapExecutedEvent {
+    pub header: EventHeader,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub offered_legs: Vec<BasketLeg>,
+    pub wanted_legs: Vec<BasketLeg>,
+}
+
+#[event]
+pub struct BasketLegRefundedEvent {
+    pub header: EventHeader,
+    pub depositor: Pubkey,
+    pub side: VaultSide,
+    pub leg_index: u8,
+    pub vault: Pubkey,
+    pub refunded: u64,
+}
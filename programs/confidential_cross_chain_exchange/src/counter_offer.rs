@@ -0,0 +1,612 @@
+//! Counter-offers.
+//!
+//! A taker proposes new terms for an open intrachain offer and escrows the
+//! lamports they offer in a `CounterVault` PDA. MPC verifies the proposer's
+//! identity, then the maker accepts, which settles the offer on the new
+//! terms at once, or rejects them.
+
+use anchor_lang::prelude::*;
+use arcium_anchor::prelude::*;
+use arcium_client::idl::arcium::types::CallbackAccount;
+
+use crate::versioning::VersionedAccount;
+use crate::{
+    COMP_DEF_OFFSET_PROPOSE_COUNTER_OFFER,
+    ComputationOperation,
+    ErrorCode,
+    EventHeader,
+    ID,
+    ID_CONST,
+    IntraChainOffer,
+    IntrachainSwapExecutedEvent,
+    OfferAccount,
+    OfferKind,
+    OfferStatus,
+    PendingComputation,
+    PriceFeed,
+    SignerAccount,
+    abort_offer_computation,
+    enforce_price_band,
+    enforce_price_trigger,
+    leave_pending,
+    refund_vault,
+    release_leg,
+    vault_lamports,
+};
+
+/// New terms a taker proposes for an open `IntraChainOffer`, with the
+/// proposed lamports escrowed in its `CounterVault` PDA. One per offer and
+/// proposer at a time; it is closed once accepted, rejected or refunded.
+#[account]
+#[derive(InitSpace)]
+pub struct CounterOffer {
+    pub layout_version: u8,
+    /// The `IntraChainOffer` it counters
+    pub offer: Pubkey,
+    pub offer_id: u64,
+    pub maker: Pubkey,
+    pub proposer: Pubkey,
+    /// Proposed size, at most the offer's `token_a_offered_amount`
+    pub token_a_amount: u64,
+    /// Proposed price in lamports, escrowed by the proposer
+    pub token_b_amount: u64,
+    /// Last timestamp the maker can accept at
+    pub deadline: i64,
+    pub status: OfferStatus,
+    /// Offset of the MPC computation verifying the proposer
+    pub computation_offset: u64,
+    pub bump: u8,
+}
+
+impl OfferAccount for CounterOffer {
+    const KIND: OfferKind = OfferKind::CounterOffer;
+
+    fn offer_id(&self) -> u64 {
+        self.offer_id
+    }
+    fn offer_maker(&self) -> Pubkey {
+        self.maker
+    }
+    fn offer_status(&self) -> OfferStatus {
+        self.status
+    }
+    fn offer_computation_offset(&self) -> u64 {
+        self.computation_offset
+    }
+    fn abort_computation(&mut self, _operation: ComputationOperation) -> Result<OfferStatus> {
+        leave_pending(&mut self.status, OfferStatus::VerificationFailed)
+    }
+}
+
+// === INSTRUCTIONS ===
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn propose_counter_offer(
+    ctx: Context<ProposeCounterOffer>,
+    offer_id: u64,
+    token_a_amount: u64,
+    token_b_amount: u64,
+    deadline: i64,
+    // Confidential proposer identity
+    ciphertext_proposer_identity_hash: [u8; 32],
+    // Arcium handshake
+    pub_key: [u8; 32],
+    nonce: u128,
+    computation_offset: u64,
+) -> Result<()> {
+    let offer = &ctx.accounts.intrachain_offer;
+    require!(offer.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    require!(
+        (1..=offer.token_a_offered_amount).contains(&token_a_amount)
+            && token_b_amount > 0
+            && Clock::get()?.unix_timestamp < deadline
+            && deadline <= offer.deadline,
+        ErrorCode::InvalidCounterOffer
+    );
+    require_keys_neq!(ctx.accounts.payer.key(), offer.maker, ErrorCode::InvalidCounterOffer);
+
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+    ctx.accounts.pending_computation.record(
+        computation_offset,
+        OfferKind::CounterOffer,
+        offer_id,
+        ctx.accounts.counter_offer.key(),
+        ComputationOperation::ProposeCounterOffer,
+        ctx.accounts.payer.key(),
+        ctx.bumps.pending_computation,
+    )?;
+
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ctx.accounts.counter_vault.to_account_info(),
+            },
+        ),
+        token_b_amount,
+    )?;
+
+    let counter = &mut ctx.accounts.counter_offer;
+    counter.offer = ctx.accounts.intrachain_offer.key();
+    counter.offer_id = offer_id;
+    counter.maker = ctx.accounts.intrachain_offer.maker;
+    counter.proposer = ctx.accounts.payer.key();
+    counter.token_a_amount = token_a_amount;
+    counter.token_b_amount = token_b_amount;
+    counter.deadline = deadline;
+    counter.status = OfferStatus::Pending;
+    counter.computation_offset = computation_offset;
+    counter.layout_version = CounterOffer::LAYOUT_VERSION;
+    counter.bump = ctx.bumps.counter_offer;
+
+    emit!(CounterOfferProposedEvent {
+        header: counter.event_header(counter.key(), None)?,
+        proposer: counter.proposer,
+        token_a_amount,
+        token_b_amount,
+        deadline,
+    });
+
+    let args = vec![
+        Argument::ArcisPubkey(pub_key),
+        Argument::PlaintextU128(nonce),
+        Argument::EncryptedU64(ciphertext_proposer_identity_hash),
+    ];
+    let callback_accounts = [
+        CallbackAccount {
+            pubkey: ctx.accounts.counter_offer.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.pending_computation.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.payer.key(),
+            is_writable: true,
+        },
+    ];
+    queue_computation(
+        ctx.accounts,
+        computation_offset,
+        args,
+        None,
+        vec![ProposeCounterOfferCallback::callback_ix(&callback_accounts)],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn accept_counter_offer(
+    ctx: Context<AcceptCounterOffer>,
+    offer_id: u64,
+    proposer: Pubkey,
+) -> Result<()> {
+    let counter = &ctx.accounts.counter_offer;
+    require!(ctx.accounts.intrachain_offer.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    require!(counter.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    require!(Clock::get()?.unix_timestamp <= counter.deadline, ErrorCode::OfferExpired);
+    let (token_a_amount, token_b_amount) = (counter.token_a_amount, counter.token_b_amount);
+    enforce_price_trigger(&ctx.accounts.price_trigger)?;
+    enforce_price_band(
+        &ctx.accounts.price_band,
+        ctx.accounts.price_feed.as_deref(),
+        token_a_amount,
+        token_b_amount,
+    )?;
+
+    let seller_vault = ctx.accounts.seller_vault.to_account_info();
+    let counter_vault = ctx.accounts.counter_vault.to_account_info();
+    require!(
+        vault_lamports(&seller_vault)? >= token_a_amount
+            && vault_lamports(&counter_vault)? >= token_b_amount,
+        ErrorCode::LegUnfunded
+    );
+    msg!("🤝 Accepting counter-offer from {} on offer ID: {}", proposer, offer_id);
+    let proposer_info = ctx.accounts.proposer.to_account_info();
+    let maker_info = ctx.accounts.maker.to_account_info();
+    release_leg(&seller_vault, &[], &seller_vault, &proposer_info, None, token_a_amount, None)?;
+    release_leg(&counter_vault, &[], &counter_vault, &maker_info, None, token_b_amount, None)?;
+    let returned = refund_vault(&seller_vault, &maker_info)?;
+
+    let offer = &mut ctx.accounts.intrachain_offer;
+    offer.token_a_offered_amount = token_a_amount;
+    offer.token_b_wanted_amount = token_b_amount;
+    // The proposer settled the offer, whoever finalize verified before
+    offer.buyer = Some(proposer);
+    offer.status = OfferStatus::Settled;
+    emit!(IntrachainSwapExecutedEvent {
+        header: offer.event_header(offer.key(), Some(OfferStatus::Open))?,
+        buyer: proposer,
+        seller: offer.maker,
+        token_a_amount,
+        token_b_amount,
+    });
+
+    let counter = &mut ctx.accounts.counter_offer;
+    counter.status = OfferStatus::Settled;
+    emit!(CounterOfferAcceptedEvent {
+        header: counter.event_header(counter.key(), Some(OfferStatus::Open))?,
+        proposer,
+        token_a_amount,
+        token_b_amount,
+        returned_to_maker: returned,
+    });
+    msg!("✅ Counter-offer settled");
+    Ok(())
+}
+
+pub(crate) fn reject_counter_offer(
+    ctx: Context<RejectCounterOffer>,
+    offer_id: u64,
+    proposer: Pubkey,
+) -> Result<()> {
+    let counter = &ctx.accounts.counter_offer;
+    require!(counter.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+
+    let refunded = refund_vault(
+        &ctx.accounts.counter_vault.to_account_info(),
+        &ctx.accounts.proposer.to_account_info(),
+    )?;
+    let counter = &mut ctx.accounts.counter_offer;
+    counter.status = OfferStatus::Cancelled;
+    msg!("🚫 Counter-offer from {} on offer {} rejected", proposer, offer_id);
+    emit!(CounterOfferRejectedEvent {
+        header: counter.event_header(counter.key(), Some(OfferStatus::Open))?,
+        proposer,
+        refunded,
+    });
+    Ok(())
+}
+
+pub(crate) fn refund_counter_offer(
+    ctx: Context<RefundCounterOffer>,
+    offer_id: u64,
+    proposer: Pubkey,
+) -> Result<()> {
+    let counter = &ctx.accounts.counter_offer;
+    let status_before = counter.status;
+    match counter.status {
+        OfferStatus::VerificationFailed => {}
+        OfferStatus::Open => require!(
+            Clock::get()?.unix_timestamp > counter.deadline
+                || ctx.accounts.intrachain_offer.status != OfferStatus::Open,
+            ErrorCode::DeadlineNotReached
+        ),
+        _ => return err!(ErrorCode::OfferNotOpen),
+    }
+
+    let refunded = refund_vault(
+        &ctx.accounts.counter_vault.to_account_info(),
+        &ctx.accounts.proposer.to_account_info(),
+    )?;
+    let counter = &mut ctx.accounts.counter_offer;
+    counter.status = OfferStatus::Refunded;
+    msg!("↩️ Counter-offer from {} on offer {} refunded {}", proposer, offer_id, refunded);
+    emit!(CounterOfferRefundedEvent {
+        header: counter.event_header(counter.key(), Some(status_before))?,
+        proposer,
+        refunded,
+    });
+    Ok(())
+}
+
+pub(crate) fn propose_counter_offer_callback(
+    ctx: Context<ProposeCounterOfferCallback>,
+    output: ComputationOutputs<ProposeCounterOfferOutput>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.counter_offer.status == OfferStatus::Pending,
+        ErrorCode::OfferNotPending
+    );
+    let _o = match output {
+        ComputationOutputs::Success(ProposeCounterOfferOutput { field_0 }) => field_0,
+        _ => {
+            let operation = ComputationOperation::ProposeCounterOffer;
+            return abort_offer_computation(&mut ctx.accounts.counter_offer, operation);
+        }
+    };
+
+    let counter = &mut ctx.accounts.counter_offer;
+    counter.status = OfferStatus::Open;
+    emit!(ProposeCounterOfferEvent {
+        header: counter.event_header(counter.key(), Some(OfferStatus::Pending))?,
+    });
+    Ok(())
+}
+
+// === ACCOUNT CONTEXTS ===
+
+#[queue_computation_accounts("propose_counter_offer", payer)]
+#[derive(Accounts)]
+#[instruction(offer_id: u64, token_a_amount: u64, token_b_amount: u64, deadline: i64, ciphertext_proposer_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct ProposeCounterOffer<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"IntraChainoffer", intrachain_offer.maker.as_ref(), &offer_id.to_le_bytes()],
+        bump = intrachain_offer.bump,
+    )]
+    pub intrachain_offer: Box<Account<'info, IntraChainOffer>>,
+    #[account(
+        init,
+        payer = payer,
+        space = CounterOffer::SPACE,
+        seeds = [b"CounterOffer", intrachain_offer.key().as_ref(), payer.key().as_ref()],
+        bump
+    )]
+    pub counter_offer: Box<Account<'info, CounterOffer>>,
+    /// Outlives its counter-offer with just its rent, so it is reused when
+    /// the proposer counters again
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8,
+        seeds = [b"CounterVault", counter_offer.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault PDA holding the proposer's lamports
+    pub counter_vault: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_PROPOSE_COUNTER_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64, proposer: Pubkey)]
+pub struct AcceptCounterOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"IntraChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = intrachain_offer.bump,
+        has_one = maker,
+    )]
+    pub intrachain_offer: Box<Account<'info, IntraChainOffer>>,
+
+    #[account(mut)]
+    /// CHECK: receives the accepted size, checked by the counter-offer's `has_one`
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"CounterOffer", intrachain_offer.key().as_ref(), proposer.key().as_ref()],
+        bump = counter_offer.bump,
+        has_one = proposer,
+        close = proposer,
+    )]
+    pub counter_offer: Box<Account<'info, CounterOffer>>,
+
+    #[account(
+        mut,
+        seeds = [b"seller_vault", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the maker's token A
+    pub seller_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"CounterVault", counter_offer.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the proposer's lamports
+    pub counter_vault: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"PriceTrigger", intrachain_offer.key().as_ref()], bump)]
+    /// CHECK: the offer's price trigger, uninitialized when it has none
+    pub price_trigger: UncheckedAccount<'info>,
+    #[account(seeds = [b"PriceBand", intrachain_offer.key().as_ref()], bump)]
+    /// CHECK: the offer's price band, uninitialized when it has none
+    pub price_band: UncheckedAccount<'info>,
+    /// The band's price feed, only needed when the offer has a band
+    pub price_feed: Option<Box<Account<'info, PriceFeed>>>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64, proposer: Pubkey)]
+pub struct RejectCounterOffer<'info> {
+    pub maker: Signer<'info>,
+
+    #[account(
+        seeds = [b"IntraChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = intrachain_offer.bump,
+        has_one = maker,
+    )]
+    pub intrachain_offer: Box<Account<'info, IntraChainOffer>>,
+
+    #[account(mut)]
+    /// CHECK: refund destination, checked by the counter-offer's `has_one`
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"CounterOffer", intrachain_offer.key().as_ref(), proposer.key().as_ref()],
+        bump = counter_offer.bump,
+        has_one = proposer,
+        close = proposer,
+    )]
+    pub counter_offer: Box<Account<'info, CounterOffer>>,
+
+    #[account(
+        mut,
+        seeds = [b"CounterVault", counter_offer.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the proposer's lamports
+    pub counter_vault: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64, proposer: Pubkey)]
+pub struct RefundCounterOffer<'info> {
+    #[account(mut)]
+    /// CHECK: refund destination, checked by the counter-offer's `has_one`
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"IntraChainoffer", intrachain_offer.maker.as_ref(), &offer_id.to_le_bytes()],
+        bump = intrachain_offer.bump,
+    )]
+    pub intrachain_offer: Box<Account<'info, IntraChainOffer>>,
+
+    #[account(
+        mut,
+        seeds = [b"CounterOffer", intrachain_offer.key().as_ref(), proposer.key().as_ref()],
+        bump = counter_offer.bump,
+        has_one = proposer,
+        close = proposer,
+    )]
+    pub counter_offer: Box<Account<'info, CounterOffer>>,
+
+    #[account(
+        mut,
+        seeds = [b"CounterVault", counter_offer.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the proposer's lamports
+    pub counter_vault: UncheckedAccount<'info>,
+}
+
+#[callback_accounts("propose_counter_offer")]
+#[derive(Accounts)]
+pub struct ProposeCounterOfferCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_PROPOSE_COUNTER_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub counter_offer: Box<Account<'info, CounterOffer>>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::ProposeCounterOffer @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == counter_offer.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[init_computation_definition_accounts("propose_counter_offer", payer)]
+#[derive(Accounts)]
+pub struct InitProposeCounterOfferCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+// === EVENTS ===
+
+#[event]
+pub struct CounterOfferProposedEvent {
+    pub header: EventHeader,
+    pub proposer: Pubkey,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    pub deadline: i64,
+}
+
+#[event]
+pub struct ProposeCounterOfferEvent {
+    pub header: EventHeader,
+}
+
+#[event]
+pub struct CounterOfferAcceptedEvent {
+    pub header: EventHeader,
+    pub proposer: Pubkey,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    /// Seller vault lamports beyond the accepted size, back to the maker
+    pub returned_to_maker: u64,
+}
+
+#[event]
+pub struct CounterOfferRejectedEvent {
+    pub header: EventHeader,
+    pub proposer: Pubkey,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct CounterOfferRefundedEvent {
+    pub header: EventHeader,
+    pub proposer: Pubkey,
+    pub refunded: u64,
+}
@@ -0,0 +1,431 @@
+//! EVM receipt inclusion proofs.
+//!
+//! Decodes block headers and receipts (RLP) and verifies Merkle-Patricia trie
+//! proofs against a header's `receiptsRoot`, so a Solana-origin offer can be
+//! settled by proving the taker's ERC-20 `Transfer` instead of trusting a
+//! relayer's word for it.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+
+use crate::ErrorCode;
+
+#[cfg(test)]
+mod fixtures;
+
+/// keccak256("Transfer(address,address,uint256)")
+pub const TRANSFER_TOPIC: [u8; 32] = [
+    0xdd, 0xf2, 0x52, 0xad, 0x1b, 0xe2, 0xc8, 0x9b, 0x69, 0xc2, 0xb0, 0x68, 0xfc, 0x37, 0x8d, 0xaa,
+    0x95, 0x2b, 0xa7, 0xf1, 0x63, 0xc4, 0xa1, 0x16, 0x28, 0xf5, 0x5a, 0x4d, 0xf5, 0x23, 0xb3, 0xef,
+];
+
+/// Header list positions (unchanged since genesis; later forks only append)
+const HEADER_PARENT_HASH_INDEX: usize = 0;
+const HEADER_RECEIPTS_ROOT_INDEX: usize = 5;
+const HEADER_NUMBER_INDEX: usize = 8;
+const HEADER_TIMESTAMP_INDEX: usize = 11;
+const HEADER_MIN_ITEMS: usize = 15;
+
+/// A single RLP item borrowed from its encoding
+#[derive(Clone, Copy, Debug)]
+pub struct Rlp<'a> {
+    raw: &'a [u8],
+    payload: &'a [u8],
+    is_list: bool,
+}
+
+impl<'a> Rlp<'a> {
+    /// Decode `data`, which must hold exactly one item
+    pub fn new(data: &'a [u8]) -> Result<Self> {
+        let (item, rest) = Self::decode_prefix(data)?;
+        require!(rest.is_empty(), ErrorCode::InvalidRlp);
+        Ok(item)
+    }
+
+    /// Decode the item at the start of `data`, returning it and the remainder
+    fn decode_prefix(data: &'a [u8]) -> Result<(Self, &'a [u8])> {
+        let prefix = *data.first().ok_or(ErrorCode::InvalidRlp)?;
+        let (header_len, payload_len, is_list) = match prefix {
+            0x00..=0x7f => (0, 1, false),
+            0x80..=0xb7 => (1, usize::from(prefix - 0x80), false),
+            0xb8..=0xbf => {
+                let len_of_len = usize::from(prefix - 0xb7);
+                (1 + len_of_len, read_long_length(&data[1..], len_of_len)?, false)
+            }
+            0xc0..=0xf7 => (1, usize::from(prefix - 0xc0), true),
+            0xf8..=0xff => {
+                let len_of_len = usize::from(prefix - 0xf7);
+                (1 + len_of_len, read_long_length(&data[1..], len_of_len)?, true)
+            }
+        };
+        let end = header_len
+            .checked_add(payload_len)
+            .ok_or(ErrorCode::InvalidRlp)?;
+        require!(data.len() >= end, ErrorCode::InvalidRlp);
+
+        let item = Rlp {
+            raw: &data[..end],
+            payload: &data[header_len..end],
+            is_list,
+        };
+        Ok((item, &data[end..]))
+    }
+
+    /// Full encoding of this item, prefix included
+    pub fn raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    pub fn is_list(&self) -> bool {
+        self.is_list
+    }
+
+    /// Payload of a byte-string item
+    pub fn bytes(&self) -> Result<&'a [u8]> {
+        require!(!self.is_list, ErrorCode::InvalidRlp);
+        Ok(self.payload)
+    }
+
+    /// Elements of a list item
+    pub fn items(&self) -> Result<Vec<Rlp<'a>>> {
+        require!(self.is_list, ErrorCode::InvalidRlp);
+        let mut items = Vec::new();
+        let mut rest = self.payload;
+        while !rest.is_empty() {
+            let (item, next) = Self::decode_prefix(rest)?;
+            items.push(item);
+            rest = next;
+        }
+        Ok(items)
+    }
+
+    /// Byte string interpreted as a big-endian integer of at most 8 bytes
+    pub fn as_u64(&self) -> Result<u64> {
+        let bytes = self.bytes()?;
+        require!(bytes.len() <= 8, ErrorCode::InvalidRlp);
+        Ok(bytes.iter().fold(0u64, |acc, &b| (acc << 8) | u64::from(b)))
+    }
+
+    /// Byte string of exactly `N` bytes
+    pub fn as_array<const N: usize>(&self) -> Result<[u8; N]> {
+        self.bytes()?
+            .try_into()
+            .map_err(|_| error!(ErrorCode::InvalidRlp))
+    }
+}
+
+fn read_long_length(data: &[u8], len_of_len: usize) -> Result<usize> {
+    require!(
+        len_of_len <= std::mem::size_of::<usize>() && data.len() >= len_of_len,
+        ErrorCode::InvalidRlp
+    );
+    let bytes = &data[..len_of_len];
+    // Canonical encodings have no leading zeros and use the long form only
+    // for payloads longer than 55 bytes
+    require!(bytes[0] != 0, ErrorCode::InvalidRlp);
+    let len = bytes.iter().fold(0usize, |acc, &b| (acc << 8) | usize::from(b));
+    require!(len > 55, ErrorCode::InvalidRlp);
+    Ok(len)
+}
+
+/// RLP encoding of an unsigned integer, as used for receipt trie keys
+pub fn rlp_encode_u64(value: u64) -> Vec<u8> {
+    match value {
+        0 => vec![0x80],
+        1..=0x7f => vec![value as u8],
+        _ => {
+            let bytes = value.to_be_bytes();
+            let skip = bytes.iter().take_while(|&&b| b == 0).count();
+            let mut out = vec![0x80 + (8 - skip) as u8];
+            out.extend_from_slice(&bytes[skip..]);
+            out
+        }
+    }
+}
+
+/// Fields of an EVM block header needed to check receipt proofs
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedHeader {
+    pub block_hash: [u8; 32],
+    pub parent_hash: [u8; 32],
+    pub number: u64,
+    pub timestamp: u64,
+    pub receipts_root: [u8; 32],
+}
+
+/// Decode an RLP block header; its hash is keccak256 of the encoding
+pub fn decode_block_header(header_rlp: &[u8]) -> Result<DecodedHeader> {
+    let items = Rlp::new(header_rlp)
+        .and_then(|header| header.items())
+        .map_err(|_| error!(ErrorCode::InvalidBlockHeader))?;
+    require!(items.len() >= HEADER_MIN_ITEMS, ErrorCode::InvalidBlockHeader);
+
+    Ok(DecodedHeader {
+        block_hash: keccak::hash(header_rlp).to_bytes(),
+        parent_hash: items[HEADER_PARENT_HASH_INDEX].as_array()?,
+        number: items[HEADER_NUMBER_INDEX].as_u64()?,
+        timestamp: items[HEADER_TIMESTAMP_INDEX].as_u64()?,
+        receipts_root: items[HEADER_RECEIPTS_ROOT_INDEX].as_array()?,
+    })
+}
+
+/// Verify that `proof` (root node first) proves `key` in the trie committed
+/// to by `root`, returning the stored value
+pub fn verify_mpt_proof<'a>(root: &[u8; 32], key: &[u8], proof: &'a [Vec<u8>]) -> Result<&'a [u8]> {
+    let path: Vec<u8> = key.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect();
+    let mut consumed = 0;
+    let mut proof_nodes = proof.iter();
+    let mut node = next_proof_node(&mut proof_nodes, root)?;
+
+    let value = loop {
+        let items = node.items()?;
+        match items.len() {
+            // Branch node: 16 children and a value slot
+            17 => {
+                if consumed == path.len() {
+                    break items[16].bytes()?;
+                }
+                let child = items[usize::from(path[consumed])];
+                consumed += 1;
+                node = resolve_child(child, &mut proof_nodes)?;
+            }
+            // Leaf or extension node: hex-prefix encoded partial path
+            2 => {
+                let (partial, is_leaf) = decode_hex_prefix(items[0].bytes()?)?;
+                require!(
+                    path[consumed..].starts_with(&partial),
+                    ErrorCode::InvalidReceiptProof
+                );
+                consumed += partial.len();
+                if is_leaf {
+                    require!(consumed == path.len(), ErrorCode::InvalidReceiptProof);
+                    break items[1].bytes()?;
+                }
+                node = resolve_child(items[1], &mut proof_nodes)?;
+            }
+            _ => return err!(ErrorCode::InvalidReceiptProof),
+        }
+    };
+
+    require!(!value.is_empty(), ErrorCode::InvalidReceiptProof);
+    require!(proof_nodes.next().is_none(), ErrorCode::InvalidReceiptProof);
+    Ok(value)
+}
+
+/// Take the next proof node and check it hashes to `expected`
+fn next_proof_node<'a>(
+    proof_nodes: &mut std::slice::Iter<'a, Vec<u8>>,
+    expected: &[u8; 32],
+) -> Result<Rlp<'a>> {
+    let node = proof_nodes.next().ok_or(ErrorCode::InvalidReceiptProof)?;
+    require!(
+        keccak::hash(node).to_bytes() == *expected,
+        ErrorCode::InvalidReceiptProof
+    );
+    Rlp::new(node)
+}
+
+/// Follow a child reference: nodes shorter than 32 bytes are embedded in
+/// their parent, longer ones are referenced by hash
+fn resolve_child<'a>(
+    child: Rlp<'a>,
+    proof_nodes: &mut std::slice::Iter<'a, Vec<u8>>,
+) -> Result<Rlp<'a>> {
+    if child.is_list() {
+        return Ok(child);
+    }
+    let hash: [u8; 32] = child
+        .as_array()
+        .map_err(|_| error!(ErrorCode::InvalidReceiptProof))?;
+    next_proof_node(proof_nodes, &hash)
+}
+
+/// Returns the nibbles of a hex-prefix encoded path and whether it ends in a leaf
+fn decode_hex_prefix(encoded: &[u8]) -> Result<(Vec<u8>, bool)> {
+    let first = *encoded.first().ok_or(ErrorCode::InvalidReceiptProof)?;
+    let flag = first >> 4;
+    require!(flag <= 3, ErrorCode::InvalidReceiptProof);
+    let is_leaf = flag >= 2;
+    let is_odd = flag & 1 == 1;
+
+    let mut nibbles = Vec::with_capacity(encoded.len() * 2);
+    if is_odd {
+        nibbles.push(first & 0x0f);
+    }
+    for b in &encoded[1..] {
+        nibbles.push(b >> 4);
+        nibbles.push(b & 0x0f);
+    }
+    Ok((nibbles, is_leaf))
+}
+
+/// An ERC-20 `Transfer(from, to, amount)` log
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferLog {
+    pub token: [u8; 20],
+    pub from: [u8; 20],
+    pub to: [u8; 20],
+    /// Big-endian u256
+    pub amount: [u8; 32],
+}
+
+/// Extract the `Transfer` log at `log_index` from a successful receipt,
+/// legacy or EIP-2718 typed
+pub fn receipt_transfer_log(receipt: &[u8], log_index: u64) -> Result<TransferLog> {
+    let body = match receipt.first() {
+        Some(&tx_type) if tx_type <= 0x7f => &receipt[1..],
+        _ => receipt,
+    };
+    let items = Rlp::new(body)
+        .and_then(|r| r.items())
+        .map_err(|_| error!(ErrorCode::InvalidReceipt))?;
+    require!(items.len() == 4, ErrorCode::InvalidReceipt);
+    require!(items[0].bytes()? == [1u8], ErrorCode::InvalidReceipt);
+
+    let logs = items[3].items()?;
+    let log = usize::try_from(log_index)
+        .ok()
+        .and_then(|i| logs.get(i))
+        .ok_or(ErrorCode::TransferLogNotFound)?;
+    let fields = log.items()?;
+    require!(fields.len() == 3, ErrorCode::InvalidReceipt);
+
+    let topics = fields[1].items()?;
+    require!(topics.len() == 3, ErrorCode::TransferLogNotFound);
+    require!(
+        topics[0].as_array::<32>()? == TRANSFER_TOPIC,
+        ErrorCode::TransferLogNotFound
+    );
+
+    Ok(TransferLog {
+        token: fields[0].as_array()?,
+        from: topic_address(&topics[1])?,
+        to: topic_address(&topics[2])?,
+        amount: fields[2].as_array()?,
+    })
+}
+
+/// An indexed `address` topic: 12 zero bytes then the address
+fn topic_address(topic: &Rlp) -> Result<[u8; 20]> {
+    let word: [u8; 32] = topic.as_array()?;
+    require!(word[..12].iter().all(|&b| b == 0), ErrorCode::InvalidReceipt);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&word[12..]);
+    Ok(address)
+}
+
+/// keccak256(address || salt), the commitment stored on Solana-origin offers
+pub fn payout_commitment(address: &[u8; 20], salt: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[address.as_ref(), salt.as_ref()]).to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::*;
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn hex32(s: &str) -> [u8; 32] {
+        hex(s).try_into().unwrap()
+    }
+
+    fn proof(nodes: &[&str]) -> Vec<Vec<u8>> {
+        nodes.iter().map(|n| hex(n)).collect()
+    }
+
+    #[test]
+    fn decodes_block_header() {
+        let header = decode_block_header(&hex(HEADER_RLP)).unwrap();
+        assert_eq!(header.block_hash, hex32(BLOCK_HASH));
+        assert_eq!(header.parent_hash, hex32(PARENT_HASH));
+        assert_eq!(header.receipts_root, hex32(RECEIPTS_ROOT));
+        assert_eq!(header.number, 19_000_000);
+        assert_eq!(header.timestamp, 1_700_000_000);
+    }
+
+    #[test]
+    fn rejects_truncated_header() {
+        let raw = hex(HEADER_RLP);
+        assert!(decode_block_header(&raw[..raw.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn verifies_receipt_proofs() {
+        let root = hex32(RECEIPTS_ROOT);
+
+        let tx_1 = proof(RECEIPT_PROOF_TX_1);
+        let value = verify_mpt_proof(&root, &rlp_encode_u64(1), &tx_1).unwrap();
+        assert_eq!(value, hex(RECEIPT_TX_1).as_slice());
+
+        let tx_0 = proof(RECEIPT_PROOF_TX_0);
+        let value = verify_mpt_proof(&root, &rlp_encode_u64(0), &tx_0).unwrap();
+        assert_eq!(value, hex(RECEIPT_TX_0).as_slice());
+    }
+
+    #[test]
+    fn rejects_wrong_key_or_root() {
+        let root = hex32(RECEIPTS_ROOT);
+        let tx_1 = proof(RECEIPT_PROOF_TX_1);
+        assert!(verify_mpt_proof(&root, &rlp_encode_u64(0), &tx_1).is_err());
+        assert!(verify_mpt_proof(&root, &rlp_encode_u64(2), &tx_1).is_err());
+        assert!(verify_mpt_proof(&[0u8; 32], &rlp_encode_u64(1), &tx_1).is_err());
+    }
+
+    #[test]
+    fn rejects_tampered_or_padded_proof() {
+        let root = hex32(RECEIPTS_ROOT);
+
+        let mut tampered = proof(RECEIPT_PROOF_TX_1);
+        let leaf = tampered.last_mut().unwrap();
+        let last = leaf.len() - 1;
+        leaf[last] ^= 1;
+        assert!(verify_mpt_proof(&root, &rlp_encode_u64(1), &tampered).is_err());
+
+        let mut padded = proof(RECEIPT_PROOF_TX_0);
+        padded.push(hex(RECEIPT_PROOF_TX_1[2]));
+        assert!(verify_mpt_proof(&root, &rlp_encode_u64(0), &padded).is_err());
+
+        let truncated = &proof(RECEIPT_PROOF_TX_1)[..2];
+        assert!(verify_mpt_proof(&root, &rlp_encode_u64(1), truncated).is_err());
+    }
+
+    #[test]
+    fn extracts_transfer_log() {
+        let log = receipt_transfer_log(&hex(RECEIPT_TX_1), 1).unwrap();
+        assert_eq!(log.token.to_vec(), hex("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"));
+        assert_eq!(log.from, [0x11; 20]);
+        assert_eq!(log.to, [0x22; 20]);
+        let mut amount = [0u8; 32];
+        amount[24..].copy_from_slice(&500_000_000u64.to_be_bytes());
+        assert_eq!(log.amount, amount);
+    }
+
+    #[test]
+    fn rejects_non_transfer_or_missing_log() {
+        let receipt = hex(RECEIPT_TX_1);
+        assert!(receipt_transfer_log(&receipt, 0).is_err());
+        assert!(receipt_transfer_log(&receipt, 2).is_err());
+        assert!(receipt_transfer_log(&hex(RECEIPT_TX_0), 0).is_err());
+    }
+
+    #[test]
+    fn encodes_trie_keys() {
+        assert_eq!(rlp_encode_u64(0), vec![0x80]);
+        assert_eq!(rlp_encode_u64(0x7f), vec![0x7f]);
+        assert_eq!(rlp_encode_u64(0x80), vec![0x81, 0x80]);
+        assert_eq!(rlp_encode_u64(0x0400), vec![0x82, 0x04, 0x00]);
+    }
+
+    #[test]
+    fn matches_payout_commitment() {
+        let salt = hex32(PAYOUT_SALT);
+        assert_eq!(payout_commitment(&[0x22; 20], &salt), hex32(PAYOUT_COMMITMENT));
+        assert_ne!(payout_commitment(&[0x11; 20], &salt), hex32(PAYOUT_COMMITMENT));
+    }
+}
@@ -0,0 +1,25 @@
+//! Receipt-trie fixtures for the verifier tests.
+//!
+//! Block with three receipts: tx 0 is a legacy receipt without logs, tx 1 an
+//! EIP-1559 receipt whose second log is a USDC `Transfer` of 500_000_000 from
+//! 0x1111…1111 to 0x2222…2222, and tx 2 a failed receipt. `HEADER_RLP` is a
+//! block header (number 19_000_000) committing to that receipts root.
+//! `PAYOUT_COMMITMENT` is keccak256(0x2222…2222 || `PAYOUT_SALT`).
+
+pub const HEADER_RLP: &str = "f90204a0ff483e972a04a9a62bb4b7d04ae403c615604e4090521ecc5bb7af67f71be09ca01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347943333333333333333333333333333333333333333a069e39af32bd0cc2d5f8ad822a3afcd7fe8d7211e4ca7c42654cdbda7a9b74516a0306ee5f79df3868527ca0e28dabeabb1269f92497c02721a269672b6ee362b2ca0d9610e5b64e001c617ca01bd23109ad53ee914b180323e0e7e3368c215d835f1b901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000080840121eac08401c9c380830324b0846553f1008766697874757265a0ae8df219bf308945ea5dfc66cefa89433ee04132e9e17361a03ae901cf9a547c88000000000000000007";
+pub const PARENT_HASH: &str = "ff483e972a04a9a62bb4b7d04ae403c615604e4090521ecc5bb7af67f71be09c";
+pub const BLOCK_HASH: &str = "1e214c77eaed6ecb8bb0613b6233b7d32667089f991d32eae4c950fc54c2e85e";
+pub const RECEIPTS_ROOT: &str = "d9610e5b64e001c617ca01bd23109ad53ee914b180323e0e7e3368c215d835f1";
+pub const RECEIPT_PROOF_TX_1: &[&str] = &[
+    "f851a0363eb6ad53f56d8bcc25d790e73b41a65ee540092d2712132dd1229f6bdeea6480808080808080a0e58215be848c1293dd381210359d84485553000a82b67410406d183b42adbbdd8080808080808080",
+    "f85180a098143e4d0fc787deb33d3fdf7a78426957dcfebdf17c1fe3b7bf440c0056641aa054668f7d33b96c5a9539a1a27994e952e69031fc8788abb6b9d43074342bc1bd8080808080808080808080808080",
+    "f9024d20b9024902f902450183014ff0b9010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000f9013af89b94a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48f863a08c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925a00000000000000000000000001111111111111111111111111111111111111111a00000000000000000000000003333333333333333333333333333333333333333a00000000000000000000000000000000000000000000000000000000000000001f89b94a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48f863a0ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3efa00000000000000000000000001111111111111111111111111111111111111111a00000000000000000000000002222222222222222222222222222222222222222a0000000000000000000000000000000000000000000000000000000001dcd6500",
+];
+pub const RECEIPT_PROOF_TX_0: &[&str] = &[
+    "f851a0363eb6ad53f56d8bcc25d790e73b41a65ee540092d2712132dd1229f6bdeea6480808080808080a0e58215be848c1293dd381210359d84485553000a82b67410406d183b42adbbdd8080808080808080",
+    "f9010f30b9010bf9010801825208b9010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000c0",
+];
+pub const RECEIPT_TX_1: &str = "02f902450183014ff0b9010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000f9013af89b94a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48f863a08c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925a00000000000000000000000001111111111111111111111111111111111111111a00000000000000000000000003333333333333333333333333333333333333333a00000000000000000000000000000000000000000000000000000000000000001f89b94a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48f863a0ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3efa00000000000000000000000001111111111111111111111111111111111111111a00000000000000000000000002222222222222222222222222222222222222222a0000000000000000000000000000000000000000000000000000000001dcd6500";
+pub const RECEIPT_TX_0: &str = "f9010801825208b9010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000c0";
+pub const PAYOUT_SALT: &str = "a05e334153147e75f3f416139b5109d1179cb56fef6a4ecb4c4cbc92a7c37b70";
+pub const PAYOUT_COMMITMENT: &str = "b4754427e65e9a8aede89e5ced084890aab0fa604d5b193f248460bf12b70582";
@@ -0,0 +1,1013 @@
+//! Fiat offers.
+//!
+//! A seller escrows lamports for fiat paid off-chain. Their payment details
+//! are encrypted to the MXE and only re-encrypted to the buyer who locks the
+//! offer. The buyer reports paying and the seller releases the lamports;
+//! if they disagree, an arbiter of the registry rules on the trade.
+
+use anchor_lang::prelude::*;
+use arcium_anchor::prelude::*;
+use arcium_client::idl::arcium::types::CallbackAccount;
+
+use crate::versioning::VersionedAccount;
+use crate::{
+    ArbiterRegistry,
+    BPS_DENOMINATOR,
+    COMP_DEF_OFFSET_REVEAL_FIAT_PAYMENT_DETAILS,
+    COMP_DEF_OFFSET_STORE_FIAT_PAYMENT_DETAILS,
+    ComputationOperation,
+    Dispute,
+    DisputeExpiredEvent,
+    DisputeOpenedEvent,
+    DisputeRuledEvent,
+    DisputeStatus,
+    ErrorCode,
+    EventHeader,
+    ID,
+    ID_CONST,
+    OfferAccount,
+    OfferCancelledEvent,
+    OfferKind,
+    OfferStatus,
+    PendingComputation,
+    SignerAccount,
+    abort_offer_computation,
+    bps_of,
+    leave_pending,
+    refund_vault,
+    release_leg,
+    vault_lamports,
+};
+
+/// Where `FiatOffer::payment_details` starts, after the discriminator and
+/// the layout version; `reveal_fiat_payment_details` reads it from there
+const FIAT_PAYMENT_DETAILS_OFFSET: u32 = 8 + 1;
+
+/// Where a fiat trade stands while its offer is open
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum FiatStage {
+    /// Waiting for a buyer to lock it
+    Listed,
+    /// Locked by a buyer, who has until `stage_deadline` to pay
+    Locked,
+    /// The buyer reported paying; the seller has until `stage_deadline` to
+    /// release
+    Paid,
+}
+
+/// P2P offer of lamports for fiat paid off-chain. The lamports are escrowed
+/// in the offer's `FiatVault` PDA; the seller's payment details are
+/// encrypted to the MXE and only ever re-encrypted to the locking buyer.
+#[account]
+#[derive(InitSpace)]
+pub struct FiatOffer {
+    pub layout_version: u8,
+    /// Seller's payment instructions encrypted to the MXE; must stay right
+    /// after `layout_version`, the circuits read it at
+    /// `FIAT_PAYMENT_DETAILS_OFFSET`
+    pub payment_details: [[u8; 32]; 4],
+    pub payment_details_nonce: u128,
+    pub id: u64,
+    /// The seller
+    pub maker: Pubkey,
+    /// Escrowed lamports
+    pub amount: u64,
+    /// ISO 4217 code of the fiat currency, e.g. `*b"EUR"`
+    pub fiat_currency: [u8; 3],
+    /// Price in the currency's minor units
+    pub fiat_amount: u64,
+    /// Time the buyer has to pay once the payment details are revealed
+    pub payment_window_secs: i64,
+    /// Time the seller has to release once the buyer reports paying
+    pub release_window_secs: i64,
+    pub buyer: Option<Pubkey>,
+    pub stage: FiatStage,
+    /// Deadline of the current stage, 0 while listed
+    pub stage_deadline: i64,
+    pub status: OfferStatus,
+    /// Offset of the latest MPC computation queued for this offer
+    pub computation_offset: u64,
+    pub bump: u8,
+}
+
+impl OfferAccount for FiatOffer {
+    const KIND: OfferKind = OfferKind::Fiat;
+
+    fn offer_id(&self) -> u64 {
+        self.id
+    }
+    fn offer_maker(&self) -> Pubkey {
+        self.maker
+    }
+    fn offer_status(&self) -> OfferStatus {
+        self.status
+    }
+    fn offer_computation_offset(&self) -> u64 {
+        self.computation_offset
+    }
+    fn abort_computation(&mut self, operation: ComputationOperation) -> Result<OfferStatus> {
+        match operation {
+            ComputationOperation::StoreFiatPaymentDetails => {
+                leave_pending(&mut self.status, OfferStatus::VerificationFailed)
+            }
+            // Nothing was revealed; the offer is listed again
+            _ => {
+                let status_before = leave_pending(&mut self.status, OfferStatus::Open)?;
+                self.buyer = None;
+                self.stage = FiatStage::Listed;
+                Ok(status_before)
+            }
+        }
+    }
+}
+
+// === INSTRUCTIONS ===
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn create_fiat_offer(
+    ctx: Context<CreateFiatOffer>,
+    id: u64,
+    amount: u64,
+    fiat_currency: [u8; 3],
+    fiat_amount: u64,
+    payment_window_secs: i64,
+    release_window_secs: i64,
+    // Confidential payment instructions
+    ciphertext_payment_details: [[u8; 32]; 4],
+    // Arcium handshake
+    pub_key: [u8; 32],
+    nonce: u128,
+    mxe_nonce: u128,
+    computation_offset: u64,
+) -> Result<()> {
+    require!(
+        amount > 0 && fiat_amount > 0 && payment_window_secs > 0 && release_window_secs > 0,
+        ErrorCode::InvalidFiatOffer
+    );
+
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+    ctx.accounts.pending_computation.record(
+        computation_offset,
+        OfferKind::Fiat,
+        id,
+        ctx.accounts.fiat_offer.key(),
+        ComputationOperation::StoreFiatPaymentDetails,
+        ctx.accounts.payer.key(),
+        ctx.bumps.pending_computation,
+    )?;
+
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ctx.accounts.fiat_vault.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let offer = &mut ctx.accounts.fiat_offer;
+    offer.payment_details = [[0; 32]; 4];
+    offer.payment_details_nonce = 0;
+    offer.id = id;
+    offer.maker = ctx.accounts.payer.key();
+    offer.amount = amount;
+    offer.fiat_currency = fiat_currency;
+    offer.fiat_amount = fiat_amount;
+    offer.payment_window_secs = payment_window_secs;
+    offer.release_window_secs = release_window_secs;
+    offer.buyer = None;
+    offer.stage = FiatStage::Listed;
+    offer.stage_deadline = 0;
+    offer.status = OfferStatus::Pending;
+    offer.computation_offset = computation_offset;
+    offer.layout_version = FiatOffer::LAYOUT_VERSION;
+    offer.bump = ctx.bumps.fiat_offer;
+
+    emit!(FiatOfferCreatedEvent {
+        header: offer.event_header(offer.key(), None)?,
+        amount,
+        fiat_currency,
+        fiat_amount,
+        payment_window_secs,
+        release_window_secs,
+    });
+
+    let mut args = vec![Argument::ArcisPubkey(pub_key), Argument::PlaintextU128(nonce)];
+    args.extend(ciphertext_payment_details.map(Argument::EncryptedU128));
+    args.push(Argument::PlaintextU128(mxe_nonce));
+    // The callback writes the encrypted payment details back to the offer
+    let callback_accounts = [
+        CallbackAccount {
+            pubkey: ctx.accounts.fiat_offer.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.pending_computation.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.payer.key(),
+            is_writable: true,
+        },
+    ];
+    queue_computation(
+        ctx.accounts,
+        computation_offset,
+        args,
+        None,
+        vec![StoreFiatPaymentDetailsCallback::callback_ix(&callback_accounts)],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn lock_fiat_offer(
+    ctx: Context<LockFiatOffer>,
+    offer_id: u64,
+    // Arcium handshake, keyed to the buyer
+    pub_key: [u8; 32],
+    nonce: u128,
+    computation_offset: u64,
+) -> Result<()> {
+    let offer = &ctx.accounts.fiat_offer;
+    let buyer = ctx.accounts.payer.key();
+    require!(offer.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    require!(offer.stage == FiatStage::Listed, ErrorCode::FiatStageMismatch);
+    require_keys_neq!(buyer, offer.maker, ErrorCode::InvalidFiatOffer);
+
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+    ctx.accounts.pending_computation.record(
+        computation_offset,
+        OfferKind::Fiat,
+        offer_id,
+        ctx.accounts.fiat_offer.key(),
+        ComputationOperation::RevealFiatPaymentDetails,
+        buyer,
+        ctx.bumps.pending_computation,
+    )?;
+
+    let offer = &mut ctx.accounts.fiat_offer;
+    offer.buyer = Some(buyer);
+    offer.stage = FiatStage::Locked;
+    offer.status = OfferStatus::Pending;
+    offer.computation_offset = computation_offset;
+    msg!("🔒 Fiat offer {} locked by {}", offer_id, buyer);
+    emit!(FiatOfferLockedEvent {
+        header: offer.event_header(offer.key(), Some(OfferStatus::Open))?,
+        buyer,
+    });
+
+    let args = vec![
+        Argument::ArcisPubkey(pub_key),
+        Argument::PlaintextU128(nonce),
+        Argument::PlaintextU128(offer.payment_details_nonce),
+        Argument::Account(offer.key(), FIAT_PAYMENT_DETAILS_OFFSET, 32 * 4),
+    ];
+    let callback_accounts = [
+        CallbackAccount {
+            pubkey: ctx.accounts.fiat_offer.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.pending_computation.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.payer.key(),
+            is_writable: true,
+        },
+    ];
+    queue_computation(
+        ctx.accounts,
+        computation_offset,
+        args,
+        None,
+        vec![RevealFiatPaymentDetailsCallback::callback_ix(&callback_accounts)],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn mark_fiat_offer_paid(ctx: Context<MarkFiatOfferPaid>, offer_id: u64) -> Result<()> {
+    let offer = &ctx.accounts.fiat_offer;
+    require!(offer.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    require!(offer.stage == FiatStage::Locked, ErrorCode::FiatStageMismatch);
+    let now = Clock::get()?.unix_timestamp;
+    require!(now <= offer.stage_deadline, ErrorCode::OfferExpired);
+    let release_deadline = now
+        .checked_add(offer.release_window_secs)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let offer = &mut ctx.accounts.fiat_offer;
+    offer.stage = FiatStage::Paid;
+    offer.stage_deadline = release_deadline;
+    msg!("💸 Fiat offer {} marked paid", offer_id);
+    emit!(FiatOfferPaidEvent {
+        header: offer.event_header(offer.key(), Some(OfferStatus::Open))?,
+        buyer: ctx.accounts.buyer.key(),
+        release_deadline,
+    });
+    Ok(())
+}
+
+pub(crate) fn release_fiat_offer(ctx: Context<ReleaseFiatOffer>, offer_id: u64) -> Result<()> {
+    let offer = &ctx.accounts.fiat_offer;
+    require!(offer.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    require!(offer.stage != FiatStage::Listed, ErrorCode::FiatStageMismatch);
+
+    let amount = refund_vault(
+        &ctx.accounts.fiat_vault.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+    )?;
+    let offer = &mut ctx.accounts.fiat_offer;
+    offer.status = OfferStatus::Settled;
+    msg!("✅ Fiat offer {} released {} lamports", offer_id, amount);
+    emit!(FiatOfferReleasedEvent {
+        header: offer.event_header(offer.key(), Some(OfferStatus::Open))?,
+        buyer: ctx.accounts.buyer.key(),
+        amount,
+    });
+    Ok(())
+}
+
+pub(crate) fn cancel_fiat_offer(ctx: Context<CancelFiatOffer>, offer_id: u64) -> Result<()> {
+    let offer = &ctx.accounts.fiat_offer;
+    let status_before = offer.status;
+    match offer.status {
+        OfferStatus::VerificationFailed => {}
+        OfferStatus::Open => {
+            require!(offer.stage == FiatStage::Listed, ErrorCode::FiatStageMismatch)
+        }
+        _ => return err!(ErrorCode::OfferNotOpen),
+    }
+
+    let refunded = refund_vault(
+        &ctx.accounts.fiat_vault.to_account_info(),
+        &ctx.accounts.maker.to_account_info(),
+    )?;
+    let offer = &mut ctx.accounts.fiat_offer;
+    offer.status = OfferStatus::Cancelled;
+    msg!("🚫 Fiat offer {} cancelled", offer_id);
+    emit!(OfferCancelledEvent {
+        header: offer.event_header(offer.key(), Some(status_before))?,
+        refunded,
+    });
+    Ok(())
+}
+
+pub(crate) fn escalate_fiat_offer(ctx: Context<EscalateFiatOffer>, offer_id: u64) -> Result<()> {
+    let offer = &ctx.accounts.fiat_offer;
+    require!(offer.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    let buyer = match (offer.stage, offer.buyer) {
+        (FiatStage::Locked | FiatStage::Paid, Some(buyer)) => buyer,
+        _ => return err!(ErrorCode::FiatStageMismatch),
+    };
+    let now = Clock::get()?.unix_timestamp;
+    require!(now > offer.stage_deadline, ErrorCode::DeadlineNotReached);
+    let opener = ctx.accounts.opener.key();
+    require!(opener == offer.maker || opener == buyer, ErrorCode::NotADisputeParty);
+
+    let registry = &ctx.accounts.arbiter_registry;
+    let bond = registry.dispute_bond;
+    let ruling_deadline = now
+        .checked_add(registry.dispute_window_secs)
+        .ok_or(ErrorCode::MathOverflow)?;
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.opener.to_account_info(),
+                to: ctx.accounts.dispute.to_account_info(),
+            },
+        ),
+        bond,
+    )?;
+
+    let dispute = &mut ctx.accounts.dispute;
+    dispute.offer = ctx.accounts.fiat_offer.key();
+    dispute.offer_id = offer_id;
+    dispute.maker = ctx.accounts.fiat_offer.maker;
+    dispute.buyer = buyer;
+    dispute.opened_by = opener;
+    dispute.bond = bond;
+    dispute.opened_at = now;
+    dispute.ruling_deadline = ruling_deadline;
+    dispute.arbiter = Pubkey::default();
+    dispute.maker_share_bps = 0;
+    dispute.status = DisputeStatus::Open;
+    dispute.layout_version = Dispute::LAYOUT_VERSION;
+    dispute.bump = ctx.bumps.dispute;
+
+    let offer = &mut ctx.accounts.fiat_offer;
+    offer.status = OfferStatus::Disputed;
+    msg!("⚠️ Fiat offer {} escalated by {}", offer_id, opener);
+    emit!(DisputeOpenedEvent {
+        header: offer.event_header(offer.key(), Some(OfferStatus::Open))?,
+        dispute: ctx.accounts.dispute.key(),
+        opened_by: opener,
+        buyer,
+        bond,
+        ruling_deadline,
+    });
+    Ok(())
+}
+
+pub(crate) fn rule_fiat_dispute(
+    ctx: Context<RuleFiatDispute>,
+    offer_id: u64,
+    maker_share_bps: u16,
+    refund_bond: bool,
+) -> Result<()> {
+    let dispute = &ctx.accounts.dispute;
+    require!(dispute.status == DisputeStatus::Open, ErrorCode::DisputeNotOpen);
+    require!(
+        Clock::get()?.unix_timestamp <= dispute.ruling_deadline,
+        ErrorCode::DisputeWindowElapsed
+    );
+    require!(u64::from(maker_share_bps) <= BPS_DENOMINATOR, ErrorCode::InvalidRuling);
+
+    let vault = ctx.accounts.fiat_vault.to_account_info();
+    let maker = ctx.accounts.maker.to_account_info();
+    let buyer = ctx.accounts.buyer.to_account_info();
+    let maker_amount = bps_of(vault_lamports(&vault)?, maker_share_bps)?;
+    release_leg(&vault, &[], &vault, &maker, None, maker_amount, None)?;
+    let buyer_amount = refund_vault(&vault, &buyer)?;
+
+    let bond_to_maker = (dispute.opened_by == maker.key()) == refund_bond;
+    let bond_recipient = if bond_to_maker { &maker } else { &buyer };
+    let bond = refund_vault(&ctx.accounts.dispute.to_account_info(), bond_recipient)?;
+    msg!(
+        "⚖️ Fiat offer {} ruled: {} lamports to the seller, {} to the buyer",
+        offer_id,
+        maker_amount,
+        buyer_amount
+    );
+
+    let dispute = &mut ctx.accounts.dispute;
+    dispute.status = DisputeStatus::Ruled;
+    dispute.arbiter = ctx.accounts.arbiter.key();
+    dispute.maker_share_bps = maker_share_bps;
+
+    let offer = &mut ctx.accounts.fiat_offer;
+    offer.status = OfferStatus::Settled;
+    emit!(DisputeRuledEvent {
+        header: offer.event_header(offer.key(), Some(OfferStatus::Disputed))?,
+        dispute: dispute.key(),
+        arbiter: dispute.arbiter,
+        maker_amount,
+        buyer_amount,
+        bond_recipient: bond_recipient.key(),
+        bond,
+    });
+    Ok(())
+}
+
+pub(crate) fn expire_fiat_dispute(ctx: Context<ExpireFiatDispute>, offer_id: u64) -> Result<()> {
+    let dispute = &ctx.accounts.dispute;
+    require!(dispute.status == DisputeStatus::Open, ErrorCode::DisputeNotOpen);
+    require!(
+        Clock::get()?.unix_timestamp > dispute.ruling_deadline,
+        ErrorCode::DeadlineNotReached
+    );
+
+    let bond_refunded = refund_vault(
+        &ctx.accounts.dispute.to_account_info(),
+        &ctx.accounts.opened_by.to_account_info(),
+    )?;
+    ctx.accounts.dispute.status = DisputeStatus::Expired;
+    msg!("⌛ Dispute on fiat offer {} expired unruled", offer_id);
+
+    let offer = &mut ctx.accounts.fiat_offer;
+    offer.status = OfferStatus::Open;
+    emit!(DisputeExpiredEvent {
+        header: offer.event_header(offer.key(), Some(OfferStatus::Disputed))?,
+        dispute: ctx.accounts.dispute.key(),
+        bond_refunded,
+    });
+    Ok(())
+}
+
+pub(crate) fn store_fiat_payment_details_callback(
+    ctx: Context<StoreFiatPaymentDetailsCallback>,
+    output: ComputationOutputs<StoreFiatPaymentDetailsOutput>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.fiat_offer.status == OfferStatus::Pending,
+        ErrorCode::OfferNotPending
+    );
+    let o = match output {
+        ComputationOutputs::Success(StoreFiatPaymentDetailsOutput { field_0 }) => field_0,
+        _ => {
+            let operation = ComputationOperation::StoreFiatPaymentDetails;
+            return abort_offer_computation(&mut ctx.accounts.fiat_offer, operation);
+        }
+    };
+
+    // Payment details are now only readable by the MXE
+    let offer = &mut ctx.accounts.fiat_offer;
+    offer.payment_details = o.ciphertexts;
+    offer.payment_details_nonce = o.nonce;
+    offer.status = OfferStatus::Open;
+    emit!(FiatPaymentDetailsStoredEvent {
+        header: offer.event_header(offer.key(), Some(OfferStatus::Pending))?,
+    });
+    Ok(())
+}
+
+pub(crate) fn reveal_fiat_payment_details_callback(
+    ctx: Context<RevealFiatPaymentDetailsCallback>,
+    output: ComputationOutputs<RevealFiatPaymentDetailsOutput>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.fiat_offer.status == OfferStatus::Pending,
+        ErrorCode::OfferNotPending
+    );
+    let o = match output {
+        ComputationOutputs::Success(RevealFiatPaymentDetailsOutput { field_0 }) => field_0,
+        _ => {
+            let operation = ComputationOperation::RevealFiatPaymentDetails;
+            return abort_offer_computation(&mut ctx.accounts.fiat_offer, operation);
+        }
+    };
+
+    let offer = &mut ctx.accounts.fiat_offer;
+    let payment_deadline = Clock::get()?
+        .unix_timestamp
+        .checked_add(offer.payment_window_secs)
+        .ok_or(ErrorCode::MathOverflow)?;
+    offer.stage_deadline = payment_deadline;
+    offer.status = OfferStatus::Open;
+    emit!(FiatPaymentDetailsRevealedEvent {
+        header: offer.event_header(offer.key(), Some(OfferStatus::Pending))?,
+        buyer: ctx.accounts.pending_computation.requester,
+        encryption_key: o.encryption_key,
+        nonce: o.nonce,
+        ciphertexts: o.ciphertexts,
+        payment_deadline,
+    });
+    Ok(())
+}
+
+// === ACCOUNT CONTEXTS ===
+
+#[queue_computation_accounts("store_fiat_payment_details", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, amount: u64, fiat_currency: [u8; 3], fiat_amount: u64, payment_window_secs: i64, release_window_secs: i64, ciphertext_payment_details: [[u8; 32]; 4], pub_key: [u8; 32], nonce: u128, mxe_nonce: u128, computation_offset: u64)]
+pub struct CreateFiatOffer<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = FiatOffer::SPACE,
+        seeds = [b"FiatOffer", payer.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub fiat_offer: Box<Account<'info, FiatOffer>>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8,
+        seeds = [b"FiatVault", fiat_offer.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault PDA holding the seller's lamports
+    pub fiat_vault: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_STORE_FIAT_PAYMENT_DETAILS)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("reveal_fiat_payment_details", payer)]
+#[derive(Accounts)]
+#[instruction(offer_id: u64, pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct LockFiatOffer<'info> {
+    /// The buyer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"FiatOffer", fiat_offer.maker.as_ref(), &offer_id.to_le_bytes()],
+        bump = fiat_offer.bump,
+    )]
+    pub fiat_offer: Box<Account<'info, FiatOffer>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_FIAT_PAYMENT_DETAILS)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct MarkFiatOfferPaid<'info> {
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"FiatOffer", fiat_offer.maker.as_ref(), &offer_id.to_le_bytes()],
+        bump = fiat_offer.bump,
+        constraint = fiat_offer.buyer == Some(buyer.key()) @ ErrorCode::NotFiatBuyer,
+    )]
+    pub fiat_offer: Box<Account<'info, FiatOffer>>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct ReleaseFiatOffer<'info> {
+    pub maker: Signer<'info>,
+
+    #[account(mut)]
+    /// CHECK: receives the escrow, checked against the offer's buyer
+    pub buyer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"FiatOffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = fiat_offer.bump,
+        has_one = maker,
+        constraint = fiat_offer.buyer == Some(buyer.key()) @ ErrorCode::NotFiatBuyer,
+    )]
+    pub fiat_offer: Box<Account<'info, FiatOffer>>,
+
+    #[account(
+        mut,
+        seeds = [b"FiatVault", fiat_offer.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the seller's lamports
+    pub fiat_vault: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct CancelFiatOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"FiatOffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = fiat_offer.bump,
+        has_one = maker,
+    )]
+    pub fiat_offer: Box<Account<'info, FiatOffer>>,
+
+    #[account(
+        mut,
+        seeds = [b"FiatVault", fiat_offer.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the seller's lamports
+    pub fiat_vault: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct EscalateFiatOffer<'info> {
+    #[account(mut)]
+    pub opener: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"FiatOffer", fiat_offer.maker.as_ref(), &offer_id.to_le_bytes()],
+        bump = fiat_offer.bump,
+    )]
+    pub fiat_offer: Box<Account<'info, FiatOffer>>,
+
+    #[account(
+        seeds = [b"ArbiterRegistry"],
+        bump = arbiter_registry.bump,
+    )]
+    pub arbiter_registry: Account<'info, ArbiterRegistry>,
+
+    /// Reused when a trade is escalated again after its dispute expired
+    #[account(
+        init_if_needed,
+        payer = opener,
+        space = Dispute::SPACE,
+        seeds = [b"Dispute", fiat_offer.key().as_ref()],
+        bump,
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct RuleFiatDispute<'info> {
+    pub arbiter: Signer<'info>,
+
+    #[account(
+        seeds = [b"ArbiterRegistry"],
+        bump = arbiter_registry.bump,
+        constraint = arbiter_registry.is_arbiter(&arbiter.key()) @ ErrorCode::UnauthorizedArbiter,
+    )]
+    pub arbiter_registry: Account<'info, ArbiterRegistry>,
+
+    #[account(mut)]
+    /// CHECK: receives the seller's share, checked by the dispute's `has_one`
+    pub maker: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: receives the buyer's share, checked by the dispute's `has_one`
+    pub buyer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"FiatOffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = fiat_offer.bump,
+        has_one = maker,
+    )]
+    pub fiat_offer: Box<Account<'info, FiatOffer>>,
+
+    #[account(
+        mut,
+        seeds = [b"Dispute", fiat_offer.key().as_ref()],
+        bump = dispute.bump,
+        has_one = maker,
+        has_one = buyer,
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    #[account(
+        mut,
+        seeds = [b"FiatVault", fiat_offer.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the seller's lamports
+    pub fiat_vault: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct ExpireFiatDispute<'info> {
+    #[account(mut)]
+    /// CHECK: gets the bond back, checked by the dispute's `has_one`
+    pub opened_by: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"FiatOffer", fiat_offer.maker.as_ref(), &offer_id.to_le_bytes()],
+        bump = fiat_offer.bump,
+    )]
+    pub fiat_offer: Box<Account<'info, FiatOffer>>,
+
+    #[account(
+        mut,
+        seeds = [b"Dispute", fiat_offer.key().as_ref()],
+        bump = dispute.bump,
+        has_one = opened_by,
+    )]
+    pub dispute: Account<'info, Dispute>,
+}
+
+#[callback_accounts("store_fiat_payment_details")]
+#[derive(Accounts)]
+pub struct StoreFiatPaymentDetailsCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_STORE_FIAT_PAYMENT_DETAILS)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub fiat_offer: Box<Account<'info, FiatOffer>>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::StoreFiatPaymentDetails @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == fiat_offer.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[callback_accounts("reveal_fiat_payment_details")]
+#[derive(Accounts)]
+pub struct RevealFiatPaymentDetailsCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_FIAT_PAYMENT_DETAILS)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub fiat_offer: Box<Account<'info, FiatOffer>>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::RevealFiatPaymentDetails @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == fiat_offer.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[init_computation_definition_accounts("store_fiat_payment_details", payer)]
+#[derive(Accounts)]
+pub struct InitStoreFiatPaymentDetailsCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("reveal_fiat_payment_details", payer)]
+#[derive(Accounts)]
+pub struct InitRevealFiatPaymentDetailsCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+// === EVENTS ===
+
+#[event]
+pub struct FiatOfferCreatedEvent {
+    pub header: EventHeader,
+    pub amount: u64,
+    pub fiat_currency: [u8; 3],
+    pub fiat_amount: u64,
+    pub payment_window_secs: i64,
+    pub release_window_secs: i64,
+}
+
+#[event]
+pub struct FiatPaymentDetailsStoredEvent {
+    pub header: EventHeader,
+}
+
+#[event]
+pub struct FiatOfferLockedEvent {
+    pub header: EventHeader,
+    pub buyer: Pubkey,
+}
+
+/// The seller's payment details, encrypted to the x25519 key the buyer
+/// locked the offer with
+#[event]
+pub struct FiatPaymentDetailsRevealedEvent {
+    pub header: EventHeader,
+    pub buyer: Pubkey,
+    pub encryption_key: [u8; 32],
+    pub nonce: u128,
+    pub ciphertexts: [[u8; 32]; 4],
+    pub payment_deadline: i64,
+}
+
+#[event]
+pub struct FiatOfferPaidEvent {
+    pub header: EventHeader,
+    pub buyer: Pubkey,
+    pub release_deadline: i64,
+}
+
+#[event]
+pub struct FiatOfferReleasedEvent {
+    pub header: EventHeader,
+    pub buyer: Pubkey,
+    pub amount: u64,
+}
@@ -0,0 +1,659 @@
+//! Identity exchanges.
+//!
+//! Once a trade settles, its seller and buyer may disclose their identities
+//! to each other. Each stores theirs encrypted to the MXE, and when both
+//! have, one computation re-encrypts each identity to the other party.
+
+use anchor_lang::prelude::*;
+use arcium_anchor::prelude::*;
+use arcium_client::idl::arcium::types::CallbackAccount;
+
+use crate::versioning::VersionedAccount;
+use crate::{
+    COMP_DEF_OFFSET_REVEAL_TRADE_IDENTITIES,
+    COMP_DEF_OFFSET_STORE_TRADE_IDENTITY,
+    ComputationOperation,
+    ErrorCode,
+    EventHeader,
+    FiatOffer,
+    ID,
+    ID_CONST,
+    IntraChainOffer,
+    OfferAccount,
+    OfferKind,
+    OfferStatus,
+    PendingComputation,
+    SignerAccount,
+    abort_offer_computation,
+    leave_pending,
+};
+
+/// Where `IdentityExchange::seller_identity` starts, after the
+/// discriminator and the layout version, with `buyer_identity` right after
+/// it; `reveal_trade_identities` reads both from there
+const SELLER_IDENTITY_OFFSET: u32 = 8 + 1;
+const BUYER_IDENTITY_OFFSET: u32 = SELLER_IDENTITY_OFFSET + 32 * 4;
+
+/// Identities the two parties of a settled trade disclose to each other.
+/// Each party's identity is stored encrypted to the MXE, and once both are,
+/// one computation re-encrypts each to the other party's x25519 key.
+#[account]
+#[derive(InitSpace)]
+pub struct IdentityExchange {
+    pub layout_version: u8,
+    /// Identities encrypted to the MXE; must stay right after
+    /// `layout_version`, the circuits read them at `SELLER_IDENTITY_OFFSET`
+    /// and `BUYER_IDENTITY_OFFSET`
+    pub seller_identity: [[u8; 32]; 4],
+    pub buyer_identity: [[u8; 32]; 4],
+    pub seller_identity_nonce: u128,
+    pub buyer_identity_nonce: u128,
+    /// The settled offer
+    pub trade: Pubkey,
+    pub trade_kind: OfferKind,
+    pub trade_id: u64,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    /// x25519 key and nonce the buyer's identity is revealed to the seller
+    /// under, set when the seller shares
+    pub seller_key: [u8; 32],
+    pub seller_nonce: u128,
+    /// Same for the seller's identity revealed to the buyer
+    pub buyer_key: [u8; 32],
+    pub buyer_nonce: u128,
+    pub seller_shared: bool,
+    pub buyer_shared: bool,
+    /// `Pending` while a computation runs, `Settled` once revealed
+    pub status: OfferStatus,
+    /// Offset of the latest MPC computation queued for this exchange
+    pub computation_offset: u64,
+    pub bump: u8,
+}
+
+impl IdentityExchange {
+    /// Whether `party` is the seller rather than the buyer; fails for
+    /// anyone else
+    fn is_seller(&self, party: Pubkey) -> Result<bool> {
+        if party == self.seller {
+            Ok(true)
+        } else if party == self.buyer {
+            Ok(false)
+        } else {
+            err!(ErrorCode::NotTradeParty)
+        }
+    }
+}
+
+impl OfferAccount for IdentityExchange {
+    const KIND: OfferKind = OfferKind::IdentityExchange;
+
+    fn offer_id(&self) -> u64 {
+        self.trade_id
+    }
+    fn offer_maker(&self) -> Pubkey {
+        self.seller
+    }
+    fn offer_status(&self) -> OfferStatus {
+        self.status
+    }
+    fn offer_computation_offset(&self) -> u64 {
+        self.computation_offset
+    }
+    /// The party has not shared, or nothing was revealed; they may try again
+    fn abort_computation(&mut self, _operation: ComputationOperation) -> Result<OfferStatus> {
+        leave_pending(&mut self.status, OfferStatus::Open)
+    }
+}
+
+// === INSTRUCTIONS ===
+
+pub(crate) fn open_identity_exchange(
+    ctx: Context<OpenIdentityExchange>,
+    trade: Pubkey,
+    buyer: Pubkey,
+) -> Result<()> {
+    let seller = ctx.accounts.seller.key();
+    let (trade_kind, trade_id, maker, status) =
+        match (&ctx.accounts.intrachain_offer, &ctx.accounts.fiat_offer) {
+            (Some(offer), None) => {
+                require!(offer.buyer == Some(buyer), ErrorCode::NotTradeParty);
+                (OfferKind::Intrachain, offer.id, offer.maker, offer.status)
+            }
+            (None, Some(offer)) => {
+                require!(offer.buyer == Some(buyer), ErrorCode::NotTradeParty);
+                (OfferKind::Fiat, offer.id, offer.maker, offer.status)
+            }
+            _ => return err!(ErrorCode::IdentityExchangeUnsupported),
+        };
+    require!(status == OfferStatus::Settled, ErrorCode::IdentityExchangeUnsupported);
+    require_keys_eq!(maker, seller, ErrorCode::NotTradeParty);
+    require_keys_neq!(buyer, seller, ErrorCode::NotTradeParty);
+
+    let exchange = &mut ctx.accounts.identity_exchange;
+    exchange.layout_version = IdentityExchange::LAYOUT_VERSION;
+    exchange.seller_identity = [[0; 32]; 4];
+    exchange.buyer_identity = [[0; 32]; 4];
+    exchange.seller_identity_nonce = 0;
+    exchange.buyer_identity_nonce = 0;
+    exchange.trade = trade;
+    exchange.trade_kind = trade_kind;
+    exchange.trade_id = trade_id;
+    exchange.seller = seller;
+    exchange.buyer = buyer;
+    exchange.seller_key = [0; 32];
+    exchange.seller_nonce = 0;
+    exchange.buyer_key = [0; 32];
+    exchange.buyer_nonce = 0;
+    exchange.seller_shared = false;
+    exchange.buyer_shared = false;
+    exchange.status = OfferStatus::Open;
+    exchange.computation_offset = 0;
+    exchange.bump = ctx.bumps.identity_exchange;
+    msg!("🪪 Identity exchange opened for trade {} with {}", trade_id, buyer);
+    emit!(IdentityExchangeOpenedEvent {
+        header: exchange.event_header(exchange.key(), None)?,
+        trade,
+        trade_kind,
+        buyer,
+    });
+    Ok(())
+}
+
+pub(crate) fn share_trade_identity(
+    ctx: Context<ShareTradeIdentity>,
+    // Confidential identity
+    ciphertext_identity: [[u8; 32]; 4],
+    // Arcium handshake
+    pub_key: [u8; 32],
+    nonce: u128,
+    reveal_nonce: u128,
+    mxe_nonce: u128,
+    computation_offset: u64,
+) -> Result<()> {
+    let exchange = &ctx.accounts.identity_exchange;
+    let party = ctx.accounts.payer.key();
+    require!(exchange.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    let is_seller = exchange.is_seller(party)?;
+    let shared = if is_seller { exchange.seller_shared } else { exchange.buyer_shared };
+    require!(!shared, ErrorCode::IdentityAlreadyShared);
+
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+    ctx.accounts.pending_computation.record(
+        computation_offset,
+        OfferKind::IdentityExchange,
+        exchange.trade_id,
+        exchange.key(),
+        ComputationOperation::StoreTradeIdentity,
+        party,
+        ctx.bumps.pending_computation,
+    )?;
+
+    let exchange = &mut ctx.accounts.identity_exchange;
+    if is_seller {
+        exchange.seller_key = pub_key;
+        exchange.seller_nonce = reveal_nonce;
+    } else {
+        exchange.buyer_key = pub_key;
+        exchange.buyer_nonce = reveal_nonce;
+    }
+    exchange.status = OfferStatus::Pending;
+    exchange.computation_offset = computation_offset;
+
+    let mut args = vec![Argument::ArcisPubkey(pub_key), Argument::PlaintextU128(nonce)];
+    args.extend(ciphertext_identity.map(Argument::EncryptedU128));
+    args.push(Argument::PlaintextU128(mxe_nonce));
+    // The callback writes the encrypted identity back to the exchange
+    let callback_accounts = [
+        CallbackAccount {
+            pubkey: ctx.accounts.identity_exchange.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.pending_computation.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.payer.key(),
+            is_writable: true,
+        },
+    ];
+    queue_computation(
+        ctx.accounts,
+        computation_offset,
+        args,
+        None,
+        vec![StoreTradeIdentityCallback::callback_ix(&callback_accounts)],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn reveal_trade_identities(
+    ctx: Context<RevealTradeIdentities>,
+    computation_offset: u64,
+) -> Result<()> {
+    let exchange = &ctx.accounts.identity_exchange;
+    let payer = ctx.accounts.payer.key();
+    require!(exchange.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    exchange.is_seller(payer)?;
+    require!(
+        exchange.seller_shared && exchange.buyer_shared,
+        ErrorCode::IdentitiesNotShared
+    );
+
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+    ctx.accounts.pending_computation.record(
+        computation_offset,
+        OfferKind::IdentityExchange,
+        exchange.trade_id,
+        exchange.key(),
+        ComputationOperation::RevealTradeIdentities,
+        payer,
+        ctx.bumps.pending_computation,
+    )?;
+
+    let exchange = &mut ctx.accounts.identity_exchange;
+    exchange.status = OfferStatus::Pending;
+    exchange.computation_offset = computation_offset;
+
+    let args = vec![
+        Argument::ArcisPubkey(exchange.seller_key),
+        Argument::PlaintextU128(exchange.seller_nonce),
+        Argument::ArcisPubkey(exchange.buyer_key),
+        Argument::PlaintextU128(exchange.buyer_nonce),
+        Argument::PlaintextU128(exchange.seller_identity_nonce),
+        Argument::Account(exchange.key(), SELLER_IDENTITY_OFFSET, 32 * 4),
+        Argument::PlaintextU128(exchange.buyer_identity_nonce),
+        Argument::Account(exchange.key(), BUYER_IDENTITY_OFFSET, 32 * 4),
+    ];
+    let callback_accounts = [
+        CallbackAccount {
+            pubkey: ctx.accounts.identity_exchange.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.pending_computation.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.payer.key(),
+            is_writable: true,
+        },
+    ];
+    queue_computation(
+        ctx.accounts,
+        computation_offset,
+        args,
+        None,
+        vec![RevealTradeIdentitiesCallback::callback_ix(&callback_accounts)],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn store_trade_identity_callback(
+    ctx: Context<StoreTradeIdentityCallback>,
+    output: ComputationOutputs<StoreTradeIdentityOutput>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.identity_exchange.status == OfferStatus::Pending,
+        ErrorCode::OfferNotPending
+    );
+    let o = match output {
+        ComputationOutputs::Success(StoreTradeIdentityOutput { field_0 }) => field_0,
+        _ => {
+            let operation = ComputationOperation::StoreTradeIdentity;
+            return abort_offer_computation(&mut ctx.accounts.identity_exchange, operation);
+        }
+    };
+
+    let party = ctx.accounts.pending_computation.requester;
+    let exchange = &mut ctx.accounts.identity_exchange;
+    if exchange.is_seller(party)? {
+        exchange.seller_identity = o.ciphertexts;
+        exchange.seller_identity_nonce = o.nonce;
+        exchange.seller_shared = true;
+    } else {
+        exchange.buyer_identity = o.ciphertexts;
+        exchange.buyer_identity_nonce = o.nonce;
+        exchange.buyer_shared = true;
+    }
+    exchange.status = OfferStatus::Open;
+    emit!(TradeIdentityStoredEvent {
+        header: exchange.event_header(exchange.key(), Some(OfferStatus::Pending))?,
+        party,
+    });
+    Ok(())
+}
+
+pub(crate) fn reveal_trade_identities_callback(
+    ctx: Context<RevealTradeIdentitiesCallback>,
+    output: ComputationOutputs<RevealTradeIdentitiesOutput>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.identity_exchange.status == OfferStatus::Pending,
+        ErrorCode::OfferNotPending
+    );
+    let (for_buyer, for_seller) = match output {
+        ComputationOutputs::Success(RevealTradeIdentitiesOutput {
+            field_0: RevealTradeIdentitiesOutputStruct0 {
+                field_0: for_buyer,
+                field_1: for_seller,
+            },
+        }) => (for_buyer, for_seller),
+        _ => {
+            let operation = ComputationOperation::RevealTradeIdentities;
+            return abort_offer_computation(&mut ctx.accounts.identity_exchange, operation);
+        }
+    };
+
+    let exchange = &mut ctx.accounts.identity_exchange;
+    exchange.status = OfferStatus::Settled;
+    let header = exchange.event_header(exchange.key(), Some(OfferStatus::Pending))?;
+    msg!("🪪 Identities of trade {} revealed to each other", exchange.trade_id);
+    emit!(CounterpartyIdentityRevealedEvent {
+        header: header.clone(),
+        recipient: exchange.buyer,
+        encryption_key: for_buyer.encryption_key,
+        nonce: for_buyer.nonce,
+        ciphertexts: for_buyer.ciphertexts,
+    });
+    emit!(CounterpartyIdentityRevealedEvent {
+        header,
+        recipient: exchange.seller,
+        encryption_key: for_seller.encryption_key,
+        nonce: for_seller.nonce,
+        ciphertexts: for_seller.ciphertexts,
+    });
+    Ok(())
+}
+
+// === ACCOUNT CONTEXTS ===
+
+#[derive(Accounts)]
+#[instruction(trade: Pubkey)]
+pub struct OpenIdentityExchange<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// The settled trade, exactly one of these
+    #[account(address = trade @ ErrorCode::IdentityExchangeUnsupported)]
+    pub intrachain_offer: Option<Box<Account<'info, IntraChainOffer>>>,
+    #[account(address = trade @ ErrorCode::IdentityExchangeUnsupported)]
+    pub fiat_offer: Option<Box<Account<'info, FiatOffer>>>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = IdentityExchange::SPACE,
+        seeds = [b"IdentityExchange", trade.as_ref()],
+        bump,
+    )]
+    pub identity_exchange: Box<Account<'info, IdentityExchange>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[queue_computation_accounts("store_trade_identity", payer)]
+#[derive(Accounts)]
+#[instruction(ciphertext_identity: [[u8; 32]; 4], pub_key: [u8; 32], nonce: u128, reveal_nonce: u128, mxe_nonce: u128, computation_offset: u64)]
+pub struct ShareTradeIdentity<'info> {
+    /// The seller or the buyer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"IdentityExchange", identity_exchange.trade.as_ref()],
+        bump = identity_exchange.bump,
+    )]
+    pub identity_exchange: Box<Account<'info, IdentityExchange>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_STORE_TRADE_IDENTITY)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("reveal_trade_identities", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RevealTradeIdentities<'info> {
+    /// The seller or the buyer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"IdentityExchange", identity_exchange.trade.as_ref()],
+        bump = identity_exchange.bump,
+    )]
+    pub identity_exchange: Box<Account<'info, IdentityExchange>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_TRADE_IDENTITIES)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("store_trade_identity")]
+#[derive(Accounts)]
+pub struct StoreTradeIdentityCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_STORE_TRADE_IDENTITY)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub identity_exchange: Box<Account<'info, IdentityExchange>>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::StoreTradeIdentity @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == identity_exchange.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[callback_accounts("reveal_trade_identities")]
+#[derive(Accounts)]
+pub struct RevealTradeIdentitiesCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_TRADE_IDENTITIES)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub identity_exchange: Box<Account<'info, IdentityExchange>>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::RevealTradeIdentities @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == identity_exchange.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[init_computation_definition_accounts("store_trade_identity", payer)]
+#[derive(Accounts)]
+pub struct InitStoreTradeIdentityCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("reveal_trade_identities", payer)]
+#[derive(Accounts)]
+pub struct InitRevealTradeIdentitiesCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+// === EVENTS ===
+
+#[event]
+pub struct IdentityExchangeOpenedEvent {
+    pub header: EventHeader,
+    /// The settled offer
+    pub trade: Pubkey,
+    pub trade_kind: OfferKind,
+    pub buyer: Pubkey,
+}
+
+#[event]
+pub struct TradeIdentityStoredEvent {
+    pub header: EventHeader,
+    pub party: Pubkey,
+}
+
+/// The counterparty's identity, encrypted to the x25519 key `recipient`
+/// shared their own identity with
+#[event]
+pub struct CounterpartyIdentityRevealedEvent {
+    pub header: EventHeader,
+    pub recipient: Pubkey,
+    pub encryption_key: [u8; 32],
+    pub nonce: u128,
+    pub ciphertexts: [[u8; 32]; 4],
+}
@@ -1,7 +1,34 @@
+// `#[arcium_program]` expands, beside the program module, to IDL handlers
+// calling the deprecated `AccountInfo::realloc`; no item-level allow reaches them
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Mint, Transfer as SplTransfer};
-use anchor_spl::associated_token::{self, AssociatedToken};
+use anchor_spl::associated_token::AssociatedToken;
 use arcium_anchor::prelude::*;
+use arcium_client::idl::arcium::types::CallbackAccount;
+
+pub mod basket;
+pub mod counter_offer;
+pub mod evm_proof;
+pub mod fiat;
+pub mod identity_exchange;
+pub mod price_band;
+pub mod price_trigger;
+pub mod rfq;
+pub mod u256;
+pub mod versioning;
+
+pub use basket::*;
+pub use counter_offer::*;
+pub use fiat::*;
+pub use identity_exchange::*;
+pub use price_band::*;
+pub use price_trigger::*;
+pub use rfq::*;
+
+use u256::U256;
+use versioning::VersionedAccount;
 
 const COMP_DEF_OFFSET_ADD_TOGETHER: u32 = comp_def_offset("add_together");
 const COMP_DEF_OFFSET_RELAY_OFFER_CLONE: u32 = comp_def_offset("relay_offer_clone");
@@ -11,6 +38,34 @@ const COMP_DEF_OFFSET_FINALIZE_INTERCHAIN_ORIGIN_EVM_OFFER: u32 = comp_def_offse
 const COMP_DEF_OFFSET_DEPOSIT_SELLER_NATIVE: u32 = comp_def_offset("deposit_seller_native");
 const COMP_DEF_OFFSET_DEPOSIT_SELLER_SPL: u32 = comp_def_offset("deposit_seller_spl");
 const COMP_DEF_OFFSET_FINALIZE_INTRACHAIN_OFFER: u32 = comp_def_offset("finalize_intrachain_offer");
+const COMP_DEF_OFFSET_STORE_EVM_PAYOUT_ADDRESS: u32 = comp_def_offset("store_evm_payout_address");
+const COMP_DEF_OFFSET_CREATE_BASKET_OFFER: u32 = comp_def_offset("create_basket_offer");
+const COMP_DEF_OFFSET_FINALIZE_BASKET_OFFER: u32 = comp_def_offset("finalize_basket_offer");
+const COMP_DEF_OFFSET_RANK_RFQ_QUOTE: u32 = comp_def_offset("rank_rfq_quote");
+const COMP_DEF_OFFSET_SELECT_RFQ_QUOTE: u32 = comp_def_offset("select_rfq_quote");
+const COMP_DEF_OFFSET_PROPOSE_COUNTER_OFFER: u32 = comp_def_offset("propose_counter_offer");
+const COMP_DEF_OFFSET_STORE_FIAT_PAYMENT_DETAILS: u32 = comp_def_offset("store_fiat_payment_details");
+const COMP_DEF_OFFSET_REVEAL_FIAT_PAYMENT_DETAILS: u32 = comp_def_offset("reveal_fiat_payment_details");
+const COMP_DEF_OFFSET_STORE_TRADE_IDENTITY: u32 = comp_def_offset("store_trade_identity");
+const COMP_DEF_OFFSET_REVEAL_TRADE_IDENTITIES: u32 = comp_def_offset("reveal_trade_identities");
+const COMP_DEF_OFFSET_STORE_PRICE_TRIGGER: u32 = comp_def_offset("store_price_trigger");
+const COMP_DEF_OFFSET_CHECK_PRICE_TRIGGER: u32 = comp_def_offset("check_price_trigger");
+const COMP_DEF_OFFSET_REVEAL_EVM_PAYOUT_ADDRESS: u32 = comp_def_offset("reveal_evm_payout_address");
+
+const MAX_CHAIN_RELAYERS: usize = 8;
+const MAX_CHAIN_TOKENS: usize = 16;
+const MAX_ARBITERS: usize = 8;
+/// How long a taker's reservation of a Solana-origin offer lasts
+const RESERVATION_WINDOW_SECS: i64 = 30 * 60;
+/// Fees and shares are expressed in basis points
+const BPS_DENOMINATOR: u64 = 10_000;
+const MAX_PROTOCOL_FEE_BPS: u16 = 1_000;
+/// Escrow vaults hold lamports, so token B mapped to native SOL settles at its decimals
+const NATIVE_SOL_DECIMALS: u8 = 9;
+const DEFAULT_RELAYER_UNBONDING_SECS: i64 = 7 * 24 * 3600;
+/// Slots a computation may stay queued before anyone can mark it stale,
+/// unless its comp def has a `ComputationTimeout` (~10 minutes)
+const DEFAULT_COMPUTATION_TIMEOUT_SLOTS: u64 = 1_500;
 
 
 declare_id!("DzueqW4xsJRhv5pQdcwTsWgeKcV2xfEoKRALN4Ma8dHd");
@@ -33,6 +88,15 @@ pub mod confidential_cross_chain_exchange {
         nonce: u128,
     ) -> Result<()> {
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        ctx.accounts.pending_computation.record(
+            computation_offset,
+            OfferKind::Standalone,
+            0,
+            Pubkey::default(),
+            ComputationOperation::AddTogether,
+            ctx.accounts.payer.key(),
+            ctx.bumps.pending_computation,
+        )?;
         let args = vec![
             Argument::ArcisPubkey(pub_key),
             Argument::PlaintextU128(nonce),
@@ -40,12 +104,22 @@ pub mod confidential_cross_chain_exchange {
             Argument::EncryptedU8(ciphertext_1),
         ];
 
+        let callback_accounts = [
+            CallbackAccount {
+                pubkey: ctx.accounts.pending_computation.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.payer.key(),
+                is_writable: true,
+            },
+        ];
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![AddTogetherCallback::callback_ix(&[])],
+            vec![AddTogetherCallback::callback_ix(&callback_accounts)],
         )?;
 
         Ok(())
@@ -86,12 +160,78 @@ pub mod confidential_cross_chain_exchange {
         Ok(())
     }
 
+    pub fn init_store_evm_payout_address_comp_def(ctx: Context<InitStoreEvmPayoutAddressCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_create_basket_offer_comp_def(ctx: Context<InitCreateBasketOfferCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_finalize_basket_offer_comp_def(ctx: Context<InitFinalizeBasketOfferCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_rank_rfq_quote_comp_def(ctx: Context<InitRankRfqQuoteCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_select_rfq_quote_comp_def(ctx: Context<InitSelectRfqQuoteCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_propose_counter_offer_comp_def(ctx: Context<InitProposeCounterOfferCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_store_fiat_payment_details_comp_def(ctx: Context<InitStoreFiatPaymentDetailsCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_reveal_fiat_payment_details_comp_def(ctx: Context<InitRevealFiatPaymentDetailsCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_store_trade_identity_comp_def(ctx: Context<InitStoreTradeIdentityCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_reveal_trade_identities_comp_def(ctx: Context<InitRevealTradeIdentitiesCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_store_price_trigger_comp_def(ctx: Context<InitStorePriceTriggerCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_check_price_trigger_comp_def(ctx: Context<InitCheckPriceTriggerCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_reveal_evm_payout_address_comp_def(ctx: Context<InitRevealEvmPayoutAddressCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        Ok(())
+    }
+
 
+    #[allow(clippy::too_many_arguments)]
     pub fn relay_offer_clone(
         ctx: Context<RelayOfferClone>,
         // Public business fields (matching original program)
         id: u64,
-        token_b_wanted_amount: u64,
+        token_b_wanted_amount: [u8; 32],
         token_a_offered_amount: u64,
         is_taker_native: bool,
         chain_id: u64,
@@ -104,7 +244,25 @@ pub mod confidential_cross_chain_exchange {
         computation_offset: u64,
     ) -> Result<()> {
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
-        
+        ctx.accounts.pending_computation.record(
+            computation_offset,
+            OfferKind::Interchain,
+            id,
+            ctx.accounts.interchain_offer.key(),
+            ComputationOperation::RelayOfferClone,
+            ctx.accounts.payer.key(),
+            ctx.bumps.pending_computation,
+        )?;
+
+        // Only the chain's trusted relayer set may mirror offers from it
+        let chain_config = &ctx.accounts.chain_config;
+        require!(
+            chain_config.is_relayer(&ctx.accounts.payer.key()),
+            ErrorCode::UnauthorizedRelayer
+        );
+        chain_config.validate_deadline(deadline, Clock::get()?.unix_timestamp)?;
+        chain_config.validate_token_mapping(&ctx.accounts.token_mapping)?;
+
         // Store public metadata in PDA
         let offer = &mut ctx.accounts.interchain_offer;
         offer.id = id;
@@ -113,8 +271,27 @@ pub mod confidential_cross_chain_exchange {
         offer.is_taker_native = is_taker_native;
         offer.chain_id = chain_id;
         offer.deadline = deadline;
+        offer.token_mapping = ctx.accounts.token_mapping.key();
+        offer.maker = ctx.accounts.payer.key();
+        offer.buyer = None;
+        offer.relayer = ctx.accounts.payer.key();
+        offer.slashed = false;
+        // Open once the MPC callback confirms the encrypted identity
+        offer.status = OfferStatus::Pending;
+        offer.computation_offset = computation_offset;
+        offer.layout_version = InterchainOffer::LAYOUT_VERSION;
         offer.bump = ctx.bumps.interchain_offer;
 
+        emit!(InterchainOfferCreatedEvent {
+            header: offer.event_header(offer.key(), None)?,
+            chain_id,
+            token_a_offered_amount,
+            token_b_wanted_amount,
+            token_mapping: offer.token_mapping,
+            relayer: offer.relayer,
+            deadline,
+        });
+
         // Only pass encrypted inputs expected by the circuit (handshake + encrypted identity)
         let args = vec![
             Argument::ArcisPubkey(pub_key),
@@ -122,12 +299,26 @@ pub mod confidential_cross_chain_exchange {
             Argument::EncryptedU64(ciphertext_external_seller_identity_hash),
         ];
 
+        let callback_accounts = [
+            CallbackAccount {
+                pubkey: ctx.accounts.interchain_offer.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.pending_computation.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.payer.key(),
+                is_writable: true,
+            },
+        ];
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![RelayOfferCloneCallback::callback_ix(&[])],
+            vec![RelayOfferCloneCallback::callback_ix(&callback_accounts)],
         )?;
 
         Ok(())
@@ -141,28 +332,48 @@ pub mod confidential_cross_chain_exchange {
         nonce: u128,
     ) -> Result<()> {
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        ctx.accounts.pending_computation.record(
+            computation_offset,
+            OfferKind::Standalone,
+            0,
+            Pubkey::default(),
+            ComputationOperation::ConfidentialDepositNative,
+            ctx.accounts.payer.key(),
+            ctx.bumps.pending_computation,
+        )?;
         let args = vec![
             Argument::ArcisPubkey(pub_key),
             Argument::PlaintextU128(nonce),
             Argument::EncryptedU64(ciphertext_amount),
         ];
 
+        let callback_accounts = [
+            CallbackAccount {
+                pubkey: ctx.accounts.pending_computation.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.payer.key(),
+                is_writable: true,
+            },
+        ];
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![ConfidentialDepositNativeCallback::callback_ix(&[])],
+            vec![ConfidentialDepositNativeCallback::callback_ix(&callback_accounts)],
         )?;
 
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn interchain_origin_evm_deposit_seller_spl(
         ctx: Context<InterchainOriginEvmDepositSellerSpl>,
         // Public business fields
         id: u64,
-        token_b_wanted_amount: u64,
+        token_b_wanted_amount: [u8; 32],
         token_a_offered_amount: u64,
         is_taker_native: bool,
         chain_id: u64,
@@ -175,7 +386,20 @@ pub mod confidential_cross_chain_exchange {
         computation_offset: u64,
     ) -> Result<()> {
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
-        
+        ctx.accounts.pending_computation.record(
+            computation_offset,
+            OfferKind::Interchain,
+            id,
+            ctx.accounts.interchain_offer.key(),
+            ComputationOperation::InterchainOriginEvmDepositSellerSpl,
+            ctx.accounts.payer.key(),
+            ctx.bumps.pending_computation,
+        )?;
+
+        let chain_config = &ctx.accounts.chain_config;
+        chain_config.validate_deadline(deadline, Clock::get()?.unix_timestamp)?;
+        chain_config.validate_token_mapping(&ctx.accounts.token_mapping)?;
+
         // Store public metadata in PDA
         let offer = &mut ctx.accounts.interchain_offer;
         offer.id = id;
@@ -184,8 +408,28 @@ pub mod confidential_cross_chain_exchange {
         offer.is_taker_native = is_taker_native;
         offer.chain_id = chain_id;
         offer.deadline = deadline;
+        offer.token_mapping = ctx.accounts.token_mapping.key();
+        offer.maker = ctx.accounts.payer.key();
+        offer.buyer = None;
+        // Deposited by the seller directly, no relayer involved
+        offer.relayer = Pubkey::default();
+        offer.slashed = false;
+        // Open once the MPC callback confirms the encrypted identity
+        offer.status = OfferStatus::Pending;
+        offer.computation_offset = computation_offset;
+        offer.layout_version = InterchainOffer::LAYOUT_VERSION;
         offer.bump = ctx.bumps.interchain_offer;
 
+        emit!(InterchainOfferCreatedEvent {
+            header: offer.event_header(offer.key(), None)?,
+            chain_id,
+            token_a_offered_amount,
+            token_b_wanted_amount,
+            token_mapping: offer.token_mapping,
+            relayer: offer.relayer,
+            deadline,
+        });
+
         // Only pass encrypted inputs expected by the circuit
         let args = vec![
             Argument::ArcisPubkey(pub_key),
@@ -193,12 +437,26 @@ pub mod confidential_cross_chain_exchange {
             Argument::EncryptedU64(ciphertext_seller_identity_hash),
         ];
 
+        let callback_accounts = [
+            CallbackAccount {
+                pubkey: ctx.accounts.interchain_offer.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.pending_computation.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.payer.key(),
+                is_writable: true,
+            },
+        ];
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![InterchainOriginEvmDepositSellerSplCallback::callback_ix(&[])],
+            vec![InterchainOriginEvmDepositSellerSplCallback::callback_ix(&callback_accounts)],
         )?;
 
         Ok(())
@@ -215,7 +473,23 @@ pub mod confidential_cross_chain_exchange {
         nonce: u128,
         computation_offset: u64,
     ) -> Result<()> {
+        // Once a buyer is verified, the offer is theirs to settle or dispute
+        let payer = ctx.accounts.payer.key();
+        require!(
+            ctx.accounts.interchain_offer.buyer.is_none_or(|buyer| buyer == payer),
+            ErrorCode::OfferReserved
+        );
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        ctx.accounts.pending_computation.record(
+            computation_offset,
+            OfferKind::Interchain,
+            id,
+            ctx.accounts.interchain_offer.key(),
+            ComputationOperation::FinalizeInterchainOriginEvmOffer,
+            ctx.accounts.payer.key(),
+            ctx.bumps.pending_computation,
+        )?;
+        ctx.accounts.interchain_offer.computation_offset = computation_offset;
         // Circuit expects only encrypted buyer identity (plus handshake)
         let args = vec![
             Argument::ArcisPubkey(pub_key),
@@ -223,17 +497,32 @@ pub mod confidential_cross_chain_exchange {
             Argument::EncryptedU64(ciphertext_buyer_identity_hash),
         ];
 
+        let callback_accounts = [
+            CallbackAccount {
+                pubkey: ctx.accounts.interchain_offer.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.pending_computation.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.payer.key(),
+                is_writable: true,
+            },
+        ];
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![FinalizeInterchainOriginEvmOfferCallback::callback_ix(&[])],
+            vec![FinalizeInterchainOriginEvmOfferCallback::callback_ix(&callback_accounts)],
         )?;
 
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn deposit_seller_native(
         ctx: Context<DepositSellerNative>,
         // Public business fields (matching original program)
@@ -250,6 +539,15 @@ pub mod confidential_cross_chain_exchange {
         computation_offset: u64,
     ) -> Result<()> {
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        ctx.accounts.pending_computation.record(
+            computation_offset,
+            OfferKind::Intrachain,
+            id,
+            ctx.accounts.intrachain_offer.key(),
+            ComputationOperation::DepositSellerNative,
+            ctx.accounts.payer.key(),
+            ctx.bumps.pending_computation,
+        )?;
         
         // Store public metadata in PDA
         let offer = &mut ctx.accounts.intrachain_offer;
@@ -258,8 +556,21 @@ pub mod confidential_cross_chain_exchange {
         offer.token_b_wanted_amount = token_b_wanted_amount;
         offer.is_taker_native = is_taker_native;
         offer.deadline = deadline;
+        offer.maker = ctx.accounts.payer.key();
+        offer.buyer = None;
+        offer.status = OfferStatus::Pending;
+        offer.computation_offset = computation_offset;
+        offer.layout_version = IntraChainOffer::LAYOUT_VERSION;
         offer.bump = ctx.bumps.intrachain_offer;
 
+        emit!(IntrachainOfferCreatedEvent {
+            header: offer.event_header(offer.key(), None)?,
+            token_a_offered_amount,
+            token_b_wanted_amount,
+            is_taker_native,
+            deadline,
+        });
+
         // Only pass encrypted inputs expected by the circuit
         let args = vec![
             Argument::ArcisPubkey(pub_key),
@@ -267,17 +578,32 @@ pub mod confidential_cross_chain_exchange {
             Argument::EncryptedU64(ciphertext_seller_identity_hash),
         ];
 
+        let callback_accounts = [
+            CallbackAccount {
+                pubkey: ctx.accounts.intrachain_offer.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.pending_computation.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.payer.key(),
+                is_writable: true,
+            },
+        ];
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![DepositSellerNativeCallback::callback_ix(&[])],
+            vec![DepositSellerNativeCallback::callback_ix(&callback_accounts)],
         )?;
 
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn deposit_seller_spl(
         ctx: Context<DepositSellerSpl>,
         // Public business fields (matching original program)
@@ -294,6 +620,15 @@ pub mod confidential_cross_chain_exchange {
         computation_offset: u64,
     ) -> Result<()> {
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        ctx.accounts.pending_computation.record(
+            computation_offset,
+            OfferKind::Intrachain,
+            id,
+            ctx.accounts.intrachain_offer.key(),
+            ComputationOperation::DepositSellerSpl,
+            ctx.accounts.payer.key(),
+            ctx.bumps.pending_computation,
+        )?;
         
         // Store public metadata in PDA
         let offer = &mut ctx.accounts.intrachain_offer;
@@ -302,8 +637,21 @@ pub mod confidential_cross_chain_exchange {
         offer.token_b_wanted_amount = token_b_wanted_amount;
         offer.is_taker_native = is_taker_native;
         offer.deadline = deadline;
+        offer.maker = ctx.accounts.payer.key();
+        offer.buyer = None;
+        offer.status = OfferStatus::Pending;
+        offer.computation_offset = computation_offset;
+        offer.layout_version = IntraChainOffer::LAYOUT_VERSION;
         offer.bump = ctx.bumps.intrachain_offer;
 
+        emit!(IntrachainOfferCreatedEvent {
+            header: offer.event_header(offer.key(), None)?,
+            token_a_offered_amount,
+            token_b_wanted_amount,
+            is_taker_native,
+            deadline,
+        });
+
         // Only pass encrypted inputs expected by the circuit
         let args = vec![
             Argument::ArcisPubkey(pub_key),
@@ -311,12 +659,26 @@ pub mod confidential_cross_chain_exchange {
             Argument::EncryptedU64(ciphertext_seller_identity_hash),
         ];
 
+        let callback_accounts = [
+            CallbackAccount {
+                pubkey: ctx.accounts.intrachain_offer.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.pending_computation.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.payer.key(),
+                is_writable: true,
+            },
+        ];
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![DepositSellerSplCallback::callback_ix(&[])],
+            vec![DepositSellerSplCallback::callback_ix(&callback_accounts)],
         )?;
 
         Ok(())
@@ -333,7 +695,23 @@ pub mod confidential_cross_chain_exchange {
         nonce: u128,
         computation_offset: u64,
     ) -> Result<()> {
+        // Once a buyer is verified, the offer is theirs to settle
+        let payer = ctx.accounts.payer.key();
+        require!(
+            ctx.accounts.intrachain_offer.buyer.is_none_or(|buyer| buyer == payer),
+            ErrorCode::OfferReserved
+        );
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        ctx.accounts.pending_computation.record(
+            computation_offset,
+            OfferKind::Intrachain,
+            id,
+            ctx.accounts.intrachain_offer.key(),
+            ComputationOperation::FinalizeIntrachainOffer,
+            ctx.accounts.payer.key(),
+            ctx.bumps.pending_computation,
+        )?;
+        ctx.accounts.intrachain_offer.computation_offset = computation_offset;
         // Circuit expects only encrypted buyer identity (plus handshake)
         let args = vec![
             Argument::ArcisPubkey(pub_key),
@@ -341,12 +719,26 @@ pub mod confidential_cross_chain_exchange {
             Argument::EncryptedU64(ciphertext_buyer_identity_hash),
         ];
 
+        let callback_accounts = [
+            CallbackAccount {
+                pubkey: ctx.accounts.intrachain_offer.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.pending_computation.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.payer.key(),
+                is_writable: true,
+            },
+        ];
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![FinalizeIntrachainOfferCallback::callback_ix(&[])],
+            vec![FinalizeIntrachainOfferCallback::callback_ix(&callback_accounts)],
         )?;
 
         Ok(())
@@ -354,12 +746,21 @@ pub mod confidential_cross_chain_exchange {
 
     // === ASSET TRANSFER INSTRUCTIONS ===
     
-    /// Execute atomic swap after both identities verified via MPC
+    /// Execute atomic swap with the buyer whose identity the offer's
+    /// finalize verified
     pub fn execute_intrachain_swap(
         ctx: Context<ExecuteIntrachainSwap>,
         offer_id: u64,
     ) -> Result<()> {
         let offer = &ctx.accounts.intrachain_offer;
+        require!(offer.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+        enforce_price_trigger(&ctx.accounts.price_trigger)?;
+        enforce_price_band(
+            &ctx.accounts.price_band,
+            ctx.accounts.price_feed.as_deref(),
+            offer.token_a_offered_amount,
+            offer.token_b_wanted_amount,
+        )?;
         
         msg!("🔄 Executing intrachain swap for offer ID: {}", offer_id);
         msg!("  Seller vault → Buyer: {} lamports (token A)", offer.token_a_offered_amount);
@@ -373,40 +774,185 @@ pub mod confidential_cross_chain_exchange {
         **ctx.accounts.buyer_vault.to_account_info().try_borrow_mut_lamports()? -= offer.token_b_wanted_amount;
         **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += offer.token_b_wanted_amount;
 
+        let offer = &mut ctx.accounts.intrachain_offer;
+        offer.status = OfferStatus::Settled;
+        emit!(IntrachainSwapExecutedEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::Open))?,
+            buyer: ctx.accounts.buyer.key(),
+            seller: ctx.accounts.seller.key(),
+            token_a_amount: offer.token_a_offered_amount,
+            token_b_amount: offer.token_b_wanted_amount,
+        });
         msg!("✅ Swap completed successfully");
         Ok(())
     }
 
-    /// Execute atomic swap for interchain offers after identities verified
+    /// Execute atomic swap for interchain offers with the buyer whose
+    /// identity the offer's finalize verified
     pub fn execute_interchain_swap(
         ctx: Context<ExecuteInterchainSwap>,
         offer_id: u64,
+        payout_commitment: [u8; 32],
     ) -> Result<()> {
         let offer = &ctx.accounts.interchain_offer;
-        
+        require!(offer.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+        require!(!offer.slashed, ErrorCode::OfferSlashed);
+
+        // token B is quoted in the EVM token's decimals; the buyer vault pays it out in lamports
+        let token_b_amount = ctx
+            .accounts
+            .token_mapping
+            .to_lamports(&offer.token_b_wanted_amount)?;
+        enforce_price_trigger(&ctx.accounts.price_trigger)?;
+        enforce_price_band(
+            &ctx.accounts.price_band,
+            ctx.accounts.price_feed.as_deref(),
+            offer.token_a_offered_amount,
+            token_b_amount,
+        )?;
+
         msg!("🔄 Executing interchain swap for offer ID: {}", offer_id);
         msg!("  Seller vault → Buyer: {} lamports (token A)", offer.token_a_offered_amount);
-        msg!("  Buyer vault → Seller: {} lamports (token B)", offer.token_b_wanted_amount);
+        msg!("  Buyer vault → Seller: {} lamports (token B)", token_b_amount);
 
-        // Transfer token A from seller vault to buyer
+        // Protocol fee comes out of token A; the relayer's share accrues in its bond PDA
+        let config = &ctx.accounts.exchange_config;
+        let fee = bps_of(offer.token_a_offered_amount, config.protocol_fee_bps)?;
+        let relayer_share = match &ctx.accounts.relayer_bond {
+            Some(_) => bps_of(fee, config.relayer_fee_share_bps)?,
+            None => {
+                require_keys_eq!(offer.relayer, Pubkey::default(), ErrorCode::RelayerBondMismatch);
+                0
+            }
+        };
+
+        // Transfer token A from seller vault to buyer, less the fee
         **ctx.accounts.seller_vault.to_account_info().try_borrow_mut_lamports()? -= offer.token_a_offered_amount;
-        **ctx.accounts.buyer.to_account_info().try_borrow_mut_lamports()? += offer.token_a_offered_amount;
+        **ctx.accounts.buyer.to_account_info().try_borrow_mut_lamports()? += offer.token_a_offered_amount - fee;
+        **ctx.accounts.fee_treasury.to_account_info().try_borrow_mut_lamports()? += fee - relayer_share;
+        if let Some(relayer_bond) = &mut ctx.accounts.relayer_bond {
+            **relayer_bond.to_account_info().try_borrow_mut_lamports()? += relayer_share;
+            relayer_bond.fees_earned = relayer_bond
+                .fees_earned
+                .checked_add(relayer_share)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        if fee > 0 {
+            emit!(ProtocolFeeChargedEvent {
+                header: offer.event_header(offer.key(), Some(offer.status))?,
+                fee,
+                relayer: offer.relayer,
+                relayer_share,
+            });
+        }
 
         // Transfer token B from buyer vault to seller
-        **ctx.accounts.buyer_vault.to_account_info().try_borrow_mut_lamports()? -= offer.token_b_wanted_amount;
-        **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += offer.token_b_wanted_amount;
+        **ctx.accounts.buyer_vault.to_account_info().try_borrow_mut_lamports()? -= token_b_amount;
+        **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += token_b_amount;
+
+        let offer = &mut ctx.accounts.interchain_offer;
+        offer.status = OfferStatus::Settled;
+        emit!(InterchainSwapExecutedEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::Open))?,
+            buyer: ctx.accounts.buyer.key(),
+            seller: ctx.accounts.seller.key(),
+            token_a_amount: offer.token_a_offered_amount - fee,
+            token_b_amount,
+            fee,
+        });
+        post_outbound_message(
+            &mut ctx.accounts.chain_config,
+            &mut ctx.accounts.outbound_message,
+            ctx.bumps.outbound_message,
+            OutboundMessageKind::Settle,
+            offer,
+            payout_commitment,
+        )?;
 
         msg!("✅ Swap completed successfully");
         Ok(())
     }
 
+    /// Maker withdraws an open interchain offer and reclaims its escrow
+    pub fn cancel_interchain_offer(
+        ctx: Context<CancelInterchainOffer>,
+        offer_id: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.interchain_offer.status == OfferStatus::Open,
+            ErrorCode::OfferNotOpen
+        );
+
+        let refunded = refund_vault(
+            &ctx.accounts.seller_vault.to_account_info(),
+            &ctx.accounts.maker.to_account_info(),
+        )?;
+        msg!("🚫 Interchain offer {} cancelled, {} lamports returned", offer_id, refunded);
+
+        let offer = &mut ctx.accounts.interchain_offer;
+        offer.status = OfferStatus::Cancelled;
+        emit!(OfferCancelledEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::Open))?,
+            refunded,
+        });
+        post_outbound_message(
+            &mut ctx.accounts.chain_config,
+            &mut ctx.accounts.outbound_message,
+            ctx.bumps.outbound_message,
+            OutboundMessageKind::Cancel,
+            offer,
+            [0u8; 32],
+        )?;
+        Ok(())
+    }
+
+    /// Permissionless refund of an interchain offer that expired unsettled
+    pub fn refund_interchain_offer(
+        ctx: Context<RefundInterchainOffer>,
+        offer_id: u64,
+    ) -> Result<()> {
+        let offer = &ctx.accounts.interchain_offer;
+        let status_before = offer.status;
+        // Offers whose verification aborted are refundable right away
+        match offer.status {
+            OfferStatus::Open => require!(
+                Clock::get()?.unix_timestamp > offer.deadline,
+                ErrorCode::DeadlineNotReached
+            ),
+            OfferStatus::VerificationFailed => {}
+            _ => return err!(ErrorCode::OfferNotOpen),
+        }
+
+        let refunded = refund_vault(
+            &ctx.accounts.seller_vault.to_account_info(),
+            &ctx.accounts.maker.to_account_info(),
+        )?;
+        msg!("↩️ Interchain offer {} expired, {} lamports refunded", offer_id, refunded);
+
+        let offer = &mut ctx.accounts.interchain_offer;
+        offer.status = OfferStatus::Refunded;
+        emit!(OfferRefundedEvent {
+            header: offer.event_header(offer.key(), Some(status_before))?,
+            refunded,
+        });
+        post_outbound_message(
+            &mut ctx.accounts.chain_config,
+            &mut ctx.accounts.outbound_message,
+            ctx.bumps.outbound_message,
+            OutboundMessageKind::Refund,
+            offer,
+            [0u8; 32],
+        )?;
+        Ok(())
+    }
+
     /// Deposit seller assets into escrow vault
     pub fn deposit_to_seller_vault(
         ctx: Context<DepositToSellerVault>,
         offer_id: u64,
         amount: u64,
     ) -> Result<()> {
-        msg!("💰 Seller depositing {} lamports to vault", amount);
+        msg!("💰 Seller depositing {} lamports to the vault of offer {}", amount, offer_id);
         
         // Transfer from seller to vault
         anchor_lang::system_program::transfer(
@@ -421,6 +967,17 @@ pub mod confidential_cross_chain_exchange {
         )?;
 
         msg!("✅ Deposit successful");
+        let header = match (&ctx.accounts.intrachain_offer, &ctx.accounts.interchain_offer) {
+            (Some(offer), None) => offer.event_header(offer.key(), Some(offer.status))?,
+            (None, Some(offer)) => offer.event_header(offer.key(), Some(offer.status))?,
+            _ => return err!(ErrorCode::VaultOfferMissing),
+        };
+        emit!(VaultDepositedEvent {
+            header,
+            vault: ctx.accounts.seller_vault.key(),
+            side: VaultSide::Seller,
+            amount,
+        });
         Ok(())
     }
 
@@ -430,7 +987,7 @@ pub mod confidential_cross_chain_exchange {
         offer_id: u64,
         amount: u64,
     ) -> Result<()> {
-        msg!("💰 Buyer depositing {} lamports to vault", amount);
+        msg!("💰 Buyer depositing {} lamports to the vault of offer {}", amount, offer_id);
         
         // Transfer from buyer to vault
         anchor_lang::system_program::transfer(
@@ -445,170 +1002,3382 @@ pub mod confidential_cross_chain_exchange {
         )?;
 
         msg!("✅ Deposit successful");
+        let header = match (&ctx.accounts.intrachain_offer, &ctx.accounts.interchain_offer) {
+            (Some(offer), None) => offer.event_header(offer.key(), Some(offer.status))?,
+            (None, Some(offer)) => offer.event_header(offer.key(), Some(offer.status))?,
+            _ => return err!(ErrorCode::VaultOfferMissing),
+        };
+        emit!(VaultDepositedEvent {
+            header,
+            vault: ctx.accounts.buyer_vault.key(),
+            side: VaultSide::Buyer,
+            amount,
+        });
         Ok(())
     }
 
-
-    #[arcium_callback(encrypted_ix = "add_together")]
-    pub fn add_together_callback(
-        ctx: Context<AddTogetherCallback>,
-        output: ComputationOutputs<AddTogetherOutput>,
+    /// Buyer takes back their escrow for an intrachain offer that can no
+    /// longer settle with it, such as one that settled through a
+    /// counter-offer or was refunded
+    pub fn refund_intrachain_buyer_vault(
+        ctx: Context<RefundIntrachainBuyerVault>,
+        offer_id: u64,
     ) -> Result<()> {
-        let o = match output {
-            ComputationOutputs::Success(AddTogetherOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+        let offer = &ctx.accounts.intrachain_offer;
+        require!(
+            !matches!(offer.status, OfferStatus::Open | OfferStatus::Pending),
+            ErrorCode::OfferStillOpen
+        );
 
-        emit!(SumEvent {
-            sum: o.ciphertexts[0],
-            nonce: o.nonce.to_le_bytes(),
+        let refunded = refund_vault(
+            &ctx.accounts.buyer_vault.to_account_info(),
+            &ctx.accounts.buyer.to_account_info(),
+        )?;
+        msg!("↩️ Buyer vault of offer {} refunded {} lamports", offer_id, refunded);
+        emit!(BuyerVaultRefundedEvent {
+            header: offer.event_header(offer.key(), Some(offer.status))?,
+            buyer: ctx.accounts.buyer.key(),
+            refunded,
         });
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "relay_offer_clone")]
-    pub fn relay_offer_clone_callback(
-        ctx: Context<RelayOfferCloneCallback>,
-        output: ComputationOutputs<RelayOfferCloneOutput>,
-    ) -> Result<()> {
-        let _o = match output {
-            ComputationOutputs::Success(RelayOfferCloneOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+    // === CHAIN REGISTRY INSTRUCTIONS ===
 
-        // Public data already stored in PDA during relay_offer_clone call
-        // Just emit acknowledgment
-        emit!(RelayOfferClonedEvent {
-            acknowledged: 1,
-        });
+    /// Create the global exchange config; only the program's upgrade authority
+    /// can, and it becomes the admin
+    pub fn initialize_exchange_config(ctx: Context<InitializeExchangeConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.exchange_config;
+        config.admin = ctx.accounts.admin.key();
+        config.protocol_fee_bps = 0;
+        config.relayer_fee_share_bps = 0;
+        config.min_relayer_bond = 0;
+        config.relayer_unbonding_secs = DEFAULT_RELAYER_UNBONDING_SECS;
+        config.layout_version = ExchangeConfig::LAYOUT_VERSION;
+        config.bump = ctx.bumps.exchange_config;
+
+        msg!("⚙️ Exchange config initialized, admin: {}", config.admin);
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "confidential_deposit_native")]
-    pub fn confidential_deposit_native_callback(
-        ctx: Context<ConfidentialDepositNativeCallback>,
-        output: ComputationOutputs<ConfidentialDepositNativeOutput>,
+    /// Set the protocol fee, the relayers' share of it and the bonding rules
+    pub fn set_protocol_params(
+        ctx: Context<SetProtocolParams>,
+        protocol_fee_bps: u16,
+        relayer_fee_share_bps: u16,
+        min_relayer_bond: u64,
+        relayer_unbonding_secs: i64,
     ) -> Result<()> {
-        let o = match output {
-            ComputationOutputs::Success(ConfidentialDepositNativeOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+        require!(
+            protocol_fee_bps <= MAX_PROTOCOL_FEE_BPS
+                && u64::from(relayer_fee_share_bps) <= BPS_DENOMINATOR
+                && relayer_unbonding_secs >= 0,
+            ErrorCode::InvalidProtocolParams
+        );
 
-        emit!(ConfidentialDepositNativeEvent {
-            processed_amount: o.ciphertexts[0],
-            nonce: o.nonce.to_le_bytes(),
-        });
+        let config = &mut ctx.accounts.exchange_config;
+        config.protocol_fee_bps = protocol_fee_bps;
+        config.relayer_fee_share_bps = relayer_fee_share_bps;
+        config.min_relayer_bond = min_relayer_bond;
+        config.relayer_unbonding_secs = relayer_unbonding_secs;
+
+        msg!(
+            "⚙️ Protocol fee {} bps, relayer share {} bps, min bond {} lamports",
+            protocol_fee_bps,
+            relayer_fee_share_bps,
+            min_relayer_bond
+        );
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "interchain_origin_evm_deposit_seller_spl")]
-    pub fn interchain_origin_evm_deposit_seller_spl_callback(
-        ctx: Context<InterchainOriginEvmDepositSellerSplCallback>,
-        output: ComputationOutputs<InterchainOriginEvmDepositSellerSplOutput>,
-    ) -> Result<()> {
-        let _o = match output {
-            ComputationOutputs::Success(InterchainOriginEvmDepositSellerSplOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+    /// Move protocol fees (and slashed bonds) out of the fee treasury
+    pub fn withdraw_protocol_fees(ctx: Context<WithdrawProtocolFees>, amount: u64) -> Result<()> {
+        let treasury = ctx.accounts.fee_treasury.to_account_info();
+        let rent_floor = Rent::get()?.minimum_balance(treasury.data_len());
+        let available = treasury.lamports().saturating_sub(rent_floor);
+        require!(amount <= available, ErrorCode::NothingToWithdraw);
 
-        // Public data already stored in PDA during interchain_origin_evm_deposit_seller_spl call
-        emit!(InterchainOriginEvmDepositSellerSplEvent {
-            acknowledged: 1,
-        });
+        **treasury.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.recipient.to_account_info().try_borrow_mut_lamports()? += amount;
+
+        msg!("🏦 {} lamports withdrawn from the fee treasury", amount);
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "finalize_interchain_origin_evm_offer")]
-    pub fn finalize_interchain_origin_evm_offer_callback(
-        ctx: Context<FinalizeInterchainOriginEvmOfferCallback>,
-        output: ComputationOutputs<FinalizeInterchainOriginEvmOfferOutput>,
+    /// Register or update the parameters of a supported external chain
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_chain_config(
+        ctx: Context<SetChainConfig>,
+        chain_id: u64,
+        enabled: bool,
+        finality_blocks: u64,
+        min_deadline_secs: i64,
+        max_deadline_secs: i64,
+        relayers: Vec<Pubkey>,
+        allowed_tokens: Vec<[u8; 20]>,
     ) -> Result<()> {
-        let _o = match output {
-            ComputationOutputs::Success(FinalizeInterchainOriginEvmOfferOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+        require!(
+            min_deadline_secs >= 0 && min_deadline_secs <= max_deadline_secs,
+            ErrorCode::InvalidDeadlineBounds
+        );
+        require!(relayers.len() <= MAX_CHAIN_RELAYERS, ErrorCode::TooManyRelayers);
+        require!(allowed_tokens.len() <= MAX_CHAIN_TOKENS, ErrorCode::TooManyTokens);
 
-        // TODO: Asset transfers require vault accounts to be passed to callback
-        // For now, just emit acknowledgment event
-        // In production, you would:
-        // 1. Pass seller/buyer vault accounts to this callback
-        // 2. Deserialize the interchain_offer PDA to read amounts
-        // 3. Execute SOL or SPL token transfers based on is_taker_native flag
-        
-        msg!("✅ Finalize interchain offer callback executed - identity verified via MPC");
+        let chain_config = &mut ctx.accounts.chain_config;
+        chain_config.chain_id = chain_id;
+        chain_config.enabled = enabled;
+        chain_config.finality_blocks = finality_blocks;
+        chain_config.min_deadline_secs = min_deadline_secs;
+        chain_config.max_deadline_secs = max_deadline_secs;
+        chain_config.relayers = relayers;
+        chain_config.allowed_tokens = allowed_tokens;
+        chain_config.layout_version = ChainConfig::LAYOUT_VERSION;
+        chain_config.bump = ctx.bumps.chain_config;
 
-        emit!(FinalizeInterchainOriginEvmOfferEvent {
-            acknowledged: 1,
-        });
+        msg!("🌉 Chain {} config updated (enabled: {})", chain_id, enabled);
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "deposit_seller_native")]
-    pub fn deposit_seller_native_callback(
-        ctx: Context<DepositSellerNativeCallback>,
-        output: ComputationOutputs<DepositSellerNativeOutput>,
+    /// Map an EVM token on `chain_id` to a canonical asset and its Solana mint
+    pub fn set_token_mapping(
+        ctx: Context<SetTokenMapping>,
+        chain_id: u64,
+        evm_token: [u8; 20],
+        asset_id: u64,
+        evm_decimals: u8,
+        solana_mint: Option<Pubkey>,
+        solana_decimals: u8,
     ) -> Result<()> {
-        let _o = match output {
-            ComputationOutputs::Success(DepositSellerNativeOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+        require!(
+            ctx.accounts.chain_config.allowed_tokens.contains(&evm_token),
+            ErrorCode::TokenNotAllowed
+        );
+        if solana_mint == Some(token::spl_token::native_mint::ID) {
+            require!(solana_decimals == NATIVE_SOL_DECIMALS, ErrorCode::InvalidNativeMapping);
+        }
 
-        // Public data already stored in PDA during deposit_seller_native call
-        emit!(DepositSellerNativeEvent {
-            acknowledged: 1,
-        });
+        let mapping = &mut ctx.accounts.token_mapping;
+        mapping.chain_id = chain_id;
+        mapping.evm_token = evm_token;
+        mapping.asset_id = asset_id;
+        mapping.evm_decimals = evm_decimals;
+        mapping.solana_mint = solana_mint;
+        mapping.solana_decimals = solana_decimals;
+        mapping.layout_version = TokenMapping::LAYOUT_VERSION;
+        mapping.bump = ctx.bumps.token_mapping;
+
+        msg!("🪙 Token mapping set for chain {} → asset {}", chain_id, asset_id);
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "deposit_seller_spl")]
-    pub fn deposit_seller_spl_callback(
-        ctx: Context<DepositSellerSplCallback>,
-        output: ComputationOutputs<DepositSellerSplOutput>,
-    ) -> Result<()> {
-        let _o = match output {
-            ComputationOutputs::Success(DepositSellerSplOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+    // === RELAYER BOND INSTRUCTIONS ===
 
-        // Public data already stored in PDA during deposit_seller_spl call
-        emit!(DepositSellerSplEvent {
-            acknowledged: 1,
-        });
+    /// Stake SOL in the relayer's bond PDA; relaying requires at least
+    /// `ExchangeConfig::min_relayer_bond`
+    pub fn bond_relayer(ctx: Context<BondRelayer>, amount: u64) -> Result<()> {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.relayer.to_account_info(),
+                    to: ctx.accounts.relayer_bond.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let bond = &mut ctx.accounts.relayer_bond;
+        bond.relayer = ctx.accounts.relayer.key();
+        bond.bonded = bond.bonded.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        bond.layout_version = RelayerBond::LAYOUT_VERSION;
+        bond.bump = ctx.bumps.relayer_bond;
+
+        msg!("🔒 Relayer {} bonded {} lamports", bond.relayer, amount);
+        emit_bond_updated(bond)?;
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "finalize_intrachain_offer")]
-    pub fn finalize_intrachain_offer_callback(
-            ctx: Context<FinalizeIntrachainOfferCallback>,
-        output: ComputationOutputs<FinalizeIntrachainOfferOutput>,
-    ) -> Result<()> {
-            let _o = match output {
-            ComputationOutputs::Success(FinalizeIntrachainOfferOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+    /// Start unbonding part of the stake; it stays slashable until withdrawn
+    pub fn begin_relayer_unbonding(ctx: Context<UpdateRelayerBond>, amount: u64) -> Result<()> {
+        let bond = &mut ctx.accounts.relayer_bond;
+        require!(amount > 0 && amount <= bond.bonded, ErrorCode::InsufficientRelayerBond);
 
-        // TODO: Asset transfers require vault accounts to be passed to callback
-        // For now, just emit acknowledgment event
-        // In production, you would:
-        // 1. Pass seller/buyer vault accounts to this callback
-        // 2. Deserialize the intrachain_offer PDA to read amounts
-        // 3. Execute SOL or SPL token transfers based on is_taker_native flag
-        
-        msg!("✅ Finalize intrachain offer callback executed - identity verified via MPC");
+        bond.bonded -= amount;
+        bond.unbonding = bond.unbonding.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        bond.unbonding_started_at = Clock::get()?.unix_timestamp;
 
-        emit!(FinalizeIntrachainOfferEvent {
-                acknowledged: 1,
-        });
+        msg!("⏳ Relayer {} unbonding {} lamports", bond.relayer, amount);
+        emit_bond_updated(bond)?;
         Ok(())
     }
-}
 
-#[queue_computation_accounts("add_together", payer)]
-#[derive(Accounts)]
-#[instruction(computation_offset: u64)]
-pub struct AddTogether<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
+    /// Withdraw stake whose unbonding period has elapsed
+    pub fn withdraw_relayer_bond(ctx: Context<WithdrawRelayerBond>) -> Result<()> {
+        let bond = &ctx.accounts.relayer_bond;
+        require!(bond.unbonding > 0, ErrorCode::NothingToWithdraw);
+        let unlocks_at = bond
+            .unbonding_started_at
+            .saturating_add(ctx.accounts.exchange_config.relayer_unbonding_secs);
+        require!(
+            Clock::get()?.unix_timestamp >= unlocks_at,
+            ErrorCode::UnbondingNotElapsed
+        );
+
+        let amount = bond.unbonding;
+        **ctx.accounts.relayer_bond.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.relayer.to_account_info().try_borrow_mut_lamports()? += amount;
+
+        let bond = &mut ctx.accounts.relayer_bond;
+        bond.unbonding = 0;
+
+        msg!("🔓 Relayer {} withdrew {} lamports", bond.relayer, amount);
+        emit_bond_updated(bond)?;
+        Ok(())
+    }
+
+    /// Claim the relayer's accrued share of protocol fees
+    pub fn claim_relayer_fees(ctx: Context<UpdateRelayerBond>) -> Result<()> {
+        let amount = ctx.accounts.relayer_bond.fees_earned;
+        require!(amount > 0, ErrorCode::NothingToWithdraw);
+
+        **ctx.accounts.relayer_bond.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.relayer.to_account_info().try_borrow_mut_lamports()? += amount;
+        ctx.accounts.relayer_bond.fees_earned = 0;
+
+        msg!("💸 Relayer {} claimed {} lamports of fees", ctx.accounts.relayer.key(), amount);
+        Ok(())
+    }
+
+    /// Slash the bond of the relayer that relayed an offer proven fraudulent;
+    /// the slashed stake goes to the fee treasury
+    pub fn slash_relayer(ctx: Context<SlashRelayer>, offer_id: u64, amount: u64) -> Result<()> {
+        require!(!ctx.accounts.interchain_offer.slashed, ErrorCode::OfferSlashed);
+
+        let slashed = slash_bond(
+            &mut ctx.accounts.relayer_bond,
+            &ctx.accounts.fee_treasury.to_account_info(),
+            amount,
+        )?;
+        ctx.accounts.interchain_offer.slashed = true;
+
+        msg!("⚔️ Relayer slashed {} lamports for offer {}", slashed, offer_id);
+        let offer = &ctx.accounts.interchain_offer;
+        emit!(RelayerSlashedEvent {
+            header: offer.event_header(offer.key(), Some(offer.status))?,
+            relayer: ctx.accounts.relayer_bond.relayer,
+            amount: slashed,
+            remaining_bond: ctx.accounts.relayer_bond.bonded,
+        });
+        Ok(())
+    }
+
+    // === DISPUTE INSTRUCTIONS ===
+
+    /// Set who may rule on disputes, the bond a party posts to open one and
+    /// how long arbiters have to rule
+    pub fn set_arbiter_registry(
+        ctx: Context<SetArbiterRegistry>,
+        arbiters: Vec<Pubkey>,
+        dispute_bond: u64,
+        dispute_window_secs: i64,
+    ) -> Result<()> {
+        require!(arbiters.len() <= MAX_ARBITERS, ErrorCode::TooManyArbiters);
+        require!(dispute_window_secs > 0, ErrorCode::InvalidDisputeParams);
+
+        let registry = &mut ctx.accounts.arbiter_registry;
+        registry.arbiters = arbiters;
+        registry.dispute_bond = dispute_bond;
+        registry.dispute_window_secs = dispute_window_secs;
+        registry.layout_version = ArbiterRegistry::LAYOUT_VERSION;
+        registry.bump = ctx.bumps.arbiter_registry;
+
+        msg!(
+            "⚖️ {} arbiters registered, dispute bond {} lamports, window {}s",
+            registry.arbiters.len(),
+            dispute_bond,
+            dispute_window_secs
+        );
+        Ok(())
+    }
+
+    /// Contest an open interchain offer before its deadline, freezing both
+    /// escrow vaults until an arbiter rules. Either the maker or `buyer`, the
+    /// buyer a finalize verified and whose vault must be funded, can open it
+    /// by posting the dispute bond.
+    pub fn open_dispute(ctx: Context<OpenDispute>, offer_id: u64, buyer: Pubkey) -> Result<()> {
+        let offer = &ctx.accounts.interchain_offer;
+        require!(offer.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= offer.deadline, ErrorCode::OfferExpired);
+        require!(offer.buyer == Some(buyer), ErrorCode::NotADisputeParty);
+        let opener = ctx.accounts.opener.key();
+        require!(opener == offer.maker || opener == buyer, ErrorCode::NotADisputeParty);
+        require!(
+            vault_lamports(&ctx.accounts.buyer_vault.to_account_info())? > 0,
+            ErrorCode::LegUnfunded
+        );
+
+        let registry = &ctx.accounts.arbiter_registry;
+        let bond = registry.dispute_bond;
+        let ruling_deadline = now
+            .checked_add(registry.dispute_window_secs)
+            .ok_or(ErrorCode::MathOverflow)?;
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.opener.to_account_info(),
+                    to: ctx.accounts.dispute.to_account_info(),
+                },
+            ),
+            bond,
+        )?;
+
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.offer = ctx.accounts.interchain_offer.key();
+        dispute.offer_id = offer_id;
+        dispute.maker = ctx.accounts.interchain_offer.maker;
+        dispute.buyer = buyer;
+        dispute.opened_by = opener;
+        dispute.bond = bond;
+        dispute.opened_at = now;
+        dispute.ruling_deadline = ruling_deadline;
+        dispute.arbiter = Pubkey::default();
+        dispute.maker_share_bps = 0;
+        dispute.status = DisputeStatus::Open;
+        dispute.layout_version = Dispute::LAYOUT_VERSION;
+        dispute.bump = ctx.bumps.dispute;
+
+        let offer = &mut ctx.accounts.interchain_offer;
+        offer.status = OfferStatus::Disputed;
+        msg!("⚠️ Dispute opened on interchain offer {} by {}", offer_id, opener);
+        emit!(DisputeOpenedEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::Open))?,
+            dispute: ctx.accounts.dispute.key(),
+            opened_by: opener,
+            buyer,
+            bond,
+            ruling_deadline,
+        });
+        Ok(())
+    }
+
+    /// A registered arbiter splits the frozen escrow of both vaults between
+    /// the parties, `maker_share_bps` of it to the maker and the rest to the
+    /// buyer. The bond goes back to the opener when `refund_bond` is set and
+    /// to the other party otherwise; the closed dispute's rent goes back to
+    /// the opener.
+    pub fn rule_dispute(
+        ctx: Context<RuleDispute>,
+        offer_id: u64,
+        maker_share_bps: u16,
+        refund_bond: bool,
+    ) -> Result<()> {
+        let dispute = &ctx.accounts.dispute;
+        require!(dispute.status == DisputeStatus::Open, ErrorCode::DisputeNotOpen);
+        require!(
+            Clock::get()?.unix_timestamp <= dispute.ruling_deadline,
+            ErrorCode::DisputeWindowElapsed
+        );
+        require!(u64::from(maker_share_bps) <= BPS_DENOMINATOR, ErrorCode::InvalidRuling);
+
+        let seller_vault = ctx.accounts.seller_vault.to_account_info();
+        let buyer_vault = ctx.accounts.buyer_vault.to_account_info();
+        let maker = ctx.accounts.maker.to_account_info();
+        let buyer = ctx.accounts.buyer.to_account_info();
+        let seller_escrow = vault_lamports(&seller_vault)?;
+        let escrow = seller_escrow
+            .checked_add(vault_lamports(&buyer_vault)?)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let maker_amount = bps_of(escrow, maker_share_bps)?;
+        // The maker's share comes out of its own vault first
+        let from_seller_vault = maker_amount.min(seller_escrow);
+        release_leg(&seller_vault, &[], &seller_vault, &maker, None, from_seller_vault, None)?;
+        release_leg(&buyer_vault, &[], &buyer_vault, &maker, None, maker_amount - from_seller_vault, None)?;
+        let buyer_amount = refund_vault(&seller_vault, &buyer)? + refund_vault(&buyer_vault, &buyer)?;
+
+        let bond_to_maker = (dispute.opened_by == maker.key()) == refund_bond;
+        let bond_recipient = if bond_to_maker { &maker } else { &buyer };
+        let bond = refund_vault(&ctx.accounts.dispute.to_account_info(), bond_recipient)?;
+        msg!(
+            "⚖️ Dispute on offer {} ruled: {} lamports to the maker, {} to the buyer",
+            offer_id,
+            maker_amount,
+            buyer_amount
+        );
+
+        let offer = &mut ctx.accounts.interchain_offer;
+        offer.status = OfferStatus::Settled;
+        emit!(DisputeRuledEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::Disputed))?,
+            dispute: ctx.accounts.dispute.key(),
+            arbiter: ctx.accounts.arbiter.key(),
+            maker_amount,
+            buyer_amount,
+            bond_recipient: bond_recipient.key(),
+            bond,
+        });
+        post_outbound_message(
+            &mut ctx.accounts.chain_config,
+            &mut ctx.accounts.outbound_message,
+            ctx.bumps.outbound_message,
+            OutboundMessageKind::Ruling,
+            offer,
+            [0u8; 32],
+        )?;
+        Ok(())
+    }
+
+    /// Permissionless once the ruling window elapsed without a ruling: the
+    /// opener gets the bond and the closed dispute's rent back, and the offer
+    /// reopens, refundable or disputable again as usual
+    pub fn expire_dispute(ctx: Context<ExpireDispute>, offer_id: u64) -> Result<()> {
+        let dispute = &ctx.accounts.dispute;
+        require!(dispute.status == DisputeStatus::Open, ErrorCode::DisputeNotOpen);
+        require!(
+            Clock::get()?.unix_timestamp > dispute.ruling_deadline,
+            ErrorCode::DeadlineNotReached
+        );
+
+        let bond_refunded = refund_vault(
+            &ctx.accounts.dispute.to_account_info(),
+            &ctx.accounts.opened_by.to_account_info(),
+        )?;
+        msg!("⌛ Dispute on offer {} expired unruled", offer_id);
+
+        let offer = &mut ctx.accounts.interchain_offer;
+        offer.status = OfferStatus::Open;
+        emit!(DisputeExpiredEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::Disputed))?,
+            dispute: ctx.accounts.dispute.key(),
+            bond_refunded,
+        });
+        Ok(())
+    }
+
+    // === ACCOUNT MIGRATION ===
+
+    /// Rewrite an account created by an older program in its current layout,
+    /// reallocating it; callable by the admin or the account's owner. Offers
+    /// of the first program did not record their maker, who is passed along.
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        let config = &ctx.accounts.exchange_config;
+        let admin = if config.owner == &crate::ID {
+            Some(versioning::decode_current::<ExchangeConfig>(&config.try_borrow_data()?)?.admin)
+        } else {
+            None
+        };
+
+        let account = ctx.accounts.account.to_account_info();
+        let (from_version, to_version) = versioning::migrate(
+            &account,
+            &ctx.accounts.authority.to_account_info(),
+            admin,
+            ctx.accounts.maker.as_ref().map(|maker| maker.key()),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        msg!("📦 Account {} migrated from layout {} to {}", account.key(), from_version, to_version);
+        emit!(AccountMigratedEvent {
+            header: EventHeader::standalone(0)?,
+            account: account.key(),
+            from_version,
+            to_version,
+            migrated_by: ctx.accounts.authority.key(),
+        });
+        Ok(())
+    }
+
+    // === ABORTED COMPUTATION RECOVERY ===
+
+    /// Refund the seller vault of an intrachain offer whose verification aborted
+    pub fn refund_intrachain_offer(
+        ctx: Context<RefundIntrachainOffer>,
+        offer_id: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.intrachain_offer.status == OfferStatus::VerificationFailed,
+            ErrorCode::OfferNotRetryable
+        );
+
+        let refunded = refund_vault(
+            &ctx.accounts.seller_vault.to_account_info(),
+            &ctx.accounts.maker.to_account_info(),
+        )?;
+        let offer = &mut ctx.accounts.intrachain_offer;
+        offer.status = OfferStatus::Refunded;
+        emit!(OfferRefundedEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::VerificationFailed))?,
+            refunded,
+        });
+
+        msg!("↩️ Intrachain offer {} refunded {} lamports (verification aborted)", offer_id, refunded);
+        Ok(())
+    }
+
+    /// Re-queue the relay verification of an offer whose computation aborted
+    pub fn retry_relay_offer_clone(
+        ctx: Context<RetryRelayOfferClone>,
+        offer_id: u64,
+        ciphertext_external_seller_identity_hash: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        let callback_accounts = retry_offer_computation(
+            &mut ctx.accounts.interchain_offer,
+            &mut ctx.accounts.pending_computation,
+            ctx.bumps.pending_computation,
+            ctx.accounts.payer.key(),
+            offer_id,
+            ComputationOperation::RelayOfferClone,
+            computation_offset,
+        )?;
+
+        let args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedU64(ciphertext_external_seller_identity_hash),
+        ];
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![RelayOfferCloneCallback::callback_ix(&callback_accounts)],
+        )?;
+        Ok(())
+    }
+
+    /// Re-queue the seller verification of an EVM-origin SPL offer whose computation aborted
+    pub fn retry_interchain_origin_evm_deposit_seller_spl(
+        ctx: Context<RetryInterchainOriginEvmDepositSellerSpl>,
+        offer_id: u64,
+        ciphertext_seller_identity_hash: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        let callback_accounts = retry_offer_computation(
+            &mut ctx.accounts.interchain_offer,
+            &mut ctx.accounts.pending_computation,
+            ctx.bumps.pending_computation,
+            ctx.accounts.payer.key(),
+            offer_id,
+            ComputationOperation::InterchainOriginEvmDepositSellerSpl,
+            computation_offset,
+        )?;
+
+        let args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedU64(ciphertext_seller_identity_hash),
+        ];
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![InterchainOriginEvmDepositSellerSplCallback::callback_ix(&callback_accounts)],
+        )?;
+        Ok(())
+    }
+
+    /// Re-queue the seller verification of a native intrachain offer whose computation aborted
+    pub fn retry_deposit_seller_native(
+        ctx: Context<RetryDepositSellerNative>,
+        offer_id: u64,
+        ciphertext_seller_identity_hash: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        let callback_accounts = retry_offer_computation(
+            &mut ctx.accounts.intrachain_offer,
+            &mut ctx.accounts.pending_computation,
+            ctx.bumps.pending_computation,
+            ctx.accounts.payer.key(),
+            offer_id,
+            ComputationOperation::DepositSellerNative,
+            computation_offset,
+        )?;
+
+        let args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedU64(ciphertext_seller_identity_hash),
+        ];
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![DepositSellerNativeCallback::callback_ix(&callback_accounts)],
+        )?;
+        Ok(())
+    }
+
+    /// Re-queue the seller verification of an SPL intrachain offer whose computation aborted
+    pub fn retry_deposit_seller_spl(
+        ctx: Context<RetryDepositSellerSpl>,
+        offer_id: u64,
+        ciphertext_seller_identity_hash: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        let callback_accounts = retry_offer_computation(
+            &mut ctx.accounts.intrachain_offer,
+            &mut ctx.accounts.pending_computation,
+            ctx.bumps.pending_computation,
+            ctx.accounts.payer.key(),
+            offer_id,
+            ComputationOperation::DepositSellerSpl,
+            computation_offset,
+        )?;
+
+        let args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedU64(ciphertext_seller_identity_hash),
+        ];
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![DepositSellerSplCallback::callback_ix(&callback_accounts)],
+        )?;
+        Ok(())
+    }
+
+    /// Re-queue storing the EVM payout address of a Solana-origin offer whose computation aborted
+    #[allow(clippy::too_many_arguments)]
+    pub fn retry_store_evm_payout_address(
+        ctx: Context<RetryStoreEvmPayoutAddress>,
+        offer_id: u64,
+        ciphertext_payout_address_hi: [u8; 32],
+        ciphertext_payout_address_lo: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+        mxe_nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        let callback_accounts = retry_offer_computation(
+            &mut ctx.accounts.solana_origin_offer,
+            &mut ctx.accounts.pending_computation,
+            ctx.bumps.pending_computation,
+            ctx.accounts.payer.key(),
+            offer_id,
+            ComputationOperation::StoreEvmPayoutAddress,
+            computation_offset,
+        )?;
+
+        let args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedU128(ciphertext_payout_address_hi),
+            Argument::EncryptedU32(ciphertext_payout_address_lo),
+            Argument::PlaintextU128(mxe_nonce),
+        ];
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![StoreEvmPayoutAddressCallback::callback_ix(&callback_accounts)],
+        )?;
+        Ok(())
+    }
+
+    /// Re-queue the seller verification of a basket offer whose computation aborted
+    pub fn retry_create_basket_offer(
+        ctx: Context<RetryCreateBasketOffer>,
+        offer_id: u64,
+        ciphertext_seller_identity_hash: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        basket::retry_create_basket_offer(
+            ctx,
+            offer_id,
+            ciphertext_seller_identity_hash,
+            pub_key,
+            nonce,
+            computation_offset,
+        )
+    }
+
+    /// Set how many slots computations of a comp def may stay queued before
+    /// they can be marked stale
+    pub fn set_computation_timeout(
+        ctx: Context<SetComputationTimeout>,
+        comp_def_offset: u32,
+        timeout_slots: u64,
+    ) -> Result<()> {
+        require!(timeout_slots > 0, ErrorCode::InvalidComputationTimeout);
+
+        let timeout = &mut ctx.accounts.computation_timeout;
+        timeout.comp_def_offset = comp_def_offset;
+        timeout.timeout_slots = timeout_slots;
+        timeout.layout_version = ComputationTimeout::LAYOUT_VERSION;
+        timeout.bump = ctx.bumps.computation_timeout;
+
+        msg!("⏱️ Comp def {} times out after {} slots", comp_def_offset, timeout_slots);
+        Ok(())
+    }
+
+    /// Permissionless: give up on a computation the cluster never called back
+    /// for. Its offer moves as if the computation aborted: a failed
+    /// verification leaves it VerificationFailed, so the escrow can be
+    /// refunded or the computation re-queued with a fresh offset. A stale
+    /// finalize returns the buyer's escrow right away. A late callback is
+    /// rejected because the pending computation is gone.
+    pub fn mark_computation_stale(
+        ctx: Context<MarkComputationStale>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let pending = &ctx.accounts.pending_computation;
+        let comp_def_offset = pending.operation.comp_def_offset();
+
+        let timeout_info = ctx.accounts.computation_timeout.to_account_info();
+        let timeout_slots = if timeout_info.data_is_empty() {
+            DEFAULT_COMPUTATION_TIMEOUT_SLOTS
+        } else {
+            ComputationTimeout::try_deserialize(&mut &timeout_info.data.borrow()[..])?.timeout_slots
+        };
+        let now = Clock::get()?.slot;
+        require!(
+            now > pending.queued_at_slot.saturating_add(timeout_slots),
+            ErrorCode::ComputationNotStale
+        );
+
+        // A stale finalize also gives the buyer their escrow back
+        let finalizes = matches!(
+            pending.operation,
+            ComputationOperation::FinalizeInterchainOriginEvmOffer
+                | ComputationOperation::FinalizeIntrachainOffer
+        );
+        let refunded = match (&ctx.accounts.buyer_vault, finalizes) {
+            (Some(vault), true) => refund_vault(
+                &vault.to_account_info(),
+                &ctx.accounts.requester.to_account_info(),
+            )?,
+            (None, true) => return err!(ErrorCode::EscrowMismatch),
+            (_, false) => 0,
+        };
+        let (operation, offer_kind) = (pending.operation, pending.offer_kind);
+        let accounts = &mut *ctx.accounts;
+        let header = match offer_kind {
+            OfferKind::Standalone => accounts.pending_computation.event_header()?,
+            OfferKind::Interchain => abort_stale(&mut accounts.interchain_offer, operation)?,
+            OfferKind::Intrachain => abort_stale(&mut accounts.intrachain_offer, operation)?,
+            OfferKind::SolanaOrigin => abort_stale(&mut accounts.solana_origin_offer, operation)?,
+            OfferKind::Basket => abort_stale(&mut accounts.basket_offer, operation)?,
+            OfferKind::Rfq => abort_stale(&mut accounts.rfq, operation)?,
+            OfferKind::CounterOffer => abort_stale(&mut accounts.counter_offer, operation)?,
+            OfferKind::Fiat => abort_stale(&mut accounts.fiat_offer, operation)?,
+            OfferKind::IdentityExchange => abort_stale(&mut accounts.identity_exchange, operation)?,
+            OfferKind::PriceTrigger => abort_stale(&mut accounts.price_trigger, operation)?,
+        };
+
+        let pending = &ctx.accounts.pending_computation;
+        msg!(
+            "⌛ Computation {} queued at slot {} marked stale at slot {}",
+            computation_offset,
+            pending.queued_at_slot,
+            now
+        );
+        emit!(ComputationStaleEvent {
+            header,
+            comp_def_offset,
+            queued_at_slot: pending.queued_at_slot,
+            marked_by: ctx.accounts.cranker.key(),
+            refunded,
+        });
+        Ok(())
+    }
+
+    // === SOLANA-ORIGIN INTERCHAIN INSTRUCTIONS ===
+
+    /// Post a Solana-origin offer paid on an EVM chain, escrowing native SOL
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_solana_origin_offer_native(
+        ctx: Context<CreateSolanaOriginOfferNative>,
+        // Public business fields
+        id: u64,
+        chain_id: u64,
+        token_a_offered_amount: u64,
+        evm_wanted_amount: [u8; 32],
+        deadline: i64,
+        payout_commitment: [u8; 32],
+        // Confidential EVM payout address
+        ciphertext_payout_address_hi: [u8; 32],
+        ciphertext_payout_address_lo: [u8; 32],
+        // Arcium handshake
+        pub_key: [u8; 32],
+        nonce: u128,
+        mxe_nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        ctx.accounts.pending_computation.record(
+            computation_offset,
+            OfferKind::SolanaOrigin,
+            id,
+            ctx.accounts.solana_origin_offer.key(),
+            ComputationOperation::StoreEvmPayoutAddress,
+            ctx.accounts.payer.key(),
+            ctx.bumps.pending_computation,
+        )?;
+
+        let chain_config = &ctx.accounts.chain_config;
+        chain_config.validate_deadline(deadline, Clock::get()?.unix_timestamp)?;
+        chain_config.validate_token_mapping(&ctx.accounts.token_mapping)?;
+
+        let offer = &mut ctx.accounts.solana_origin_offer;
+        offer.id = id;
+        offer.maker = ctx.accounts.payer.key();
+        offer.chain_id = chain_id;
+        offer.token_mapping = ctx.accounts.token_mapping.key();
+        offer.token_a_offered_amount = token_a_offered_amount;
+        offer.escrow_token_account = None;
+        offer.evm_wanted_amount = evm_wanted_amount;
+        offer.payout_commitment = payout_commitment;
+        offer.taker = None;
+        offer.taker_evm_address = [0u8; 20];
+        offer.reserved_at = 0;
+        offer.deadline = deadline;
+        offer.status = OfferStatus::Pending;
+        offer.computation_offset = computation_offset;
+        offer.layout_version = SolanaOriginOffer::LAYOUT_VERSION;
+        offer.bump = ctx.bumps.solana_origin_offer;
+
+        // Escrow token A in the offer PDA itself
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.solana_origin_offer.to_account_info(),
+                },
+            ),
+            token_a_offered_amount,
+        )?;
+
+        let args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedU128(ciphertext_payout_address_hi),
+            Argument::EncryptedU32(ciphertext_payout_address_lo),
+            Argument::PlaintextU128(mxe_nonce),
+        ];
+
+        // The callback writes the encrypted payout address back to the offer
+        let callback_accounts = [
+            CallbackAccount {
+                pubkey: ctx.accounts.solana_origin_offer.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.pending_computation.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.payer.key(),
+                is_writable: true,
+            },
+        ];
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![StoreEvmPayoutAddressCallback::callback_ix(&callback_accounts)],
+        )?;
+
+        let offer = &ctx.accounts.solana_origin_offer;
+        emit!(SolanaOriginOfferCreatedEvent {
+            header: offer.event_header(offer.key(), None)?,
+            chain_id,
+            token_a_offered_amount,
+            escrow_mint: None,
+            evm_wanted_amount,
+            deadline,
+        });
+        Ok(())
+    }
+
+    /// Post a Solana-origin offer paid on an EVM chain, escrowing an SPL token
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_solana_origin_offer_spl(
+        ctx: Context<CreateSolanaOriginOfferSpl>,
+        // Public business fields
+        id: u64,
+        chain_id: u64,
+        token_a_offered_amount: u64,
+        evm_wanted_amount: [u8; 32],
+        deadline: i64,
+        payout_commitment: [u8; 32],
+        // Confidential EVM payout address
+        ciphertext_payout_address_hi: [u8; 32],
+        ciphertext_payout_address_lo: [u8; 32],
+        // Arcium handshake
+        pub_key: [u8; 32],
+        nonce: u128,
+        mxe_nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        ctx.accounts.pending_computation.record(
+            computation_offset,
+            OfferKind::SolanaOrigin,
+            id,
+            ctx.accounts.solana_origin_offer.key(),
+            ComputationOperation::StoreEvmPayoutAddress,
+            ctx.accounts.payer.key(),
+            ctx.bumps.pending_computation,
+        )?;
+
+        let chain_config = &ctx.accounts.chain_config;
+        chain_config.validate_deadline(deadline, Clock::get()?.unix_timestamp)?;
+        chain_config.validate_token_mapping(&ctx.accounts.token_mapping)?;
+
+        let offer = &mut ctx.accounts.solana_origin_offer;
+        offer.id = id;
+        offer.maker = ctx.accounts.payer.key();
+        offer.chain_id = chain_id;
+        offer.token_mapping = ctx.accounts.token_mapping.key();
+        offer.token_a_offered_amount = token_a_offered_amount;
+        offer.escrow_token_account = Some(ctx.accounts.escrow_token_account.key());
+        offer.evm_wanted_amount = evm_wanted_amount;
+        offer.payout_commitment = payout_commitment;
+        offer.taker = None;
+        offer.taker_evm_address = [0u8; 20];
+        offer.reserved_at = 0;
+        offer.deadline = deadline;
+        offer.status = OfferStatus::Pending;
+        offer.computation_offset = computation_offset;
+        offer.layout_version = SolanaOriginOffer::LAYOUT_VERSION;
+        offer.bump = ctx.bumps.solana_origin_offer;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                SplTransfer {
+                    from: ctx.accounts.maker_token_account.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            token_a_offered_amount,
+        )?;
+
+        let args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedU128(ciphertext_payout_address_hi),
+            Argument::EncryptedU32(ciphertext_payout_address_lo),
+            Argument::PlaintextU128(mxe_nonce),
+        ];
+
+        // The callback writes the encrypted payout address back to the offer
+        let callback_accounts = [
+            CallbackAccount {
+                pubkey: ctx.accounts.solana_origin_offer.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.pending_computation.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.payer.key(),
+                is_writable: true,
+            },
+        ];
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![StoreEvmPayoutAddressCallback::callback_ix(&callback_accounts)],
+        )?;
+
+        let offer = &ctx.accounts.solana_origin_offer;
+        emit!(SolanaOriginOfferCreatedEvent {
+            header: offer.event_header(offer.key(), None)?,
+            chain_id,
+            token_a_offered_amount,
+            escrow_mint: Some(ctx.accounts.mint.key()),
+            evm_wanted_amount,
+            deadline,
+        });
+        Ok(())
+    }
+
+    /// Release a Solana-origin offer's escrow to its reserved taker once a
+    /// trusted relayer attests that the taker's EVM payment to the committed
+    /// payout address happened
+    pub fn settle_solana_origin_offer(
+        ctx: Context<SettleSolanaOriginOffer>,
+        offer_id: u64,
+        evm_tx_hash: [u8; 32],
+        evm_sender: [u8; 20],
+        evm_paid_amount: [u8; 32],
+        payout_commitment: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.chain_config.is_relayer(&ctx.accounts.relayer.key()),
+            ErrorCode::UnauthorizedRelayer
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let offer = &ctx.accounts.solana_origin_offer;
+        require!(offer.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+        require!(now <= offer.deadline, ErrorCode::OfferExpired);
+        // Only the taker the payout address was revealed to can be paid out
+        let reserved_taker = offer
+            .taker
+            .filter(|_| offer.is_reserved(now))
+            .ok_or(ErrorCode::OfferNotReserved)?;
+        require_keys_eq!(ctx.accounts.taker.key(), reserved_taker, ErrorCode::OfferNotReserved);
+        require!(
+            evm_sender == offer.taker_evm_address,
+            ErrorCode::TransferSenderMismatch
+        );
+        require!(
+            offer.payout_commitment == payout_commitment,
+            ErrorCode::PayoutMismatch
+        );
+        require!(
+            U256::from_be_bytes(&evm_paid_amount) >= U256::from_be_bytes(&offer.evm_wanted_amount),
+            ErrorCode::InsufficientPayment
+        );
+
+        release_solana_origin_escrow(
+            offer,
+            &ctx.accounts.taker.to_account_info(),
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.taker_token_account,
+            &ctx.accounts.token_program,
+        )?;
+
+        ctx.accounts.processed_evm_tx.layout_version = ProcessedEvmTx::LAYOUT_VERSION;
+        ctx.accounts.processed_evm_tx.bump = ctx.bumps.processed_evm_tx;
+        let offer = &mut ctx.accounts.solana_origin_offer;
+        offer.status = OfferStatus::Settled;
+
+        msg!("✅ Solana-origin offer {} settled by EVM tx", offer_id);
+        emit!(SolanaOriginOfferSettledEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::Open))?,
+            taker: ctx.accounts.taker.key(),
+            token_a_amount: offer.token_a_offered_amount,
+            evm_tx_hash,
+            evm_paid_amount,
+        });
+        Ok(())
+    }
+
+    /// Permissionless refund of a Solana-origin offer that expired unsettled
+    pub fn refund_solana_origin_offer(
+        ctx: Context<RefundSolanaOriginOffer>,
+        offer_id: u64,
+    ) -> Result<()> {
+        let offer = &ctx.accounts.solana_origin_offer;
+        let status_before = offer.status;
+        // Offers whose payout address could not be stored are refundable right
+        // away; pending ones only once `mark_computation_stale` released them
+        match offer.status {
+            OfferStatus::Open => require!(
+                Clock::get()?.unix_timestamp > offer.deadline,
+                ErrorCode::DeadlineNotReached
+            ),
+            OfferStatus::VerificationFailed => {}
+            _ => return err!(ErrorCode::OfferNotOpen),
+        }
+
+        release_solana_origin_escrow(
+            offer,
+            &ctx.accounts.maker.to_account_info(),
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.maker_token_account,
+            &ctx.accounts.token_program,
+        )?;
+        let offer = &mut ctx.accounts.solana_origin_offer;
+        offer.status = OfferStatus::Refunded;
+
+        msg!("↩️ Solana-origin offer {} expired and was refunded", offer_id);
+        emit!(OfferRefundedEvent {
+            header: offer.event_header(offer.key(), Some(status_before))?,
+            refunded: offer.token_a_offered_amount,
+        });
+        Ok(())
+    }
+
+    /// Store an EVM block header posted by a relayer; receipt proofs are
+    /// checked against its receipts root once `finality_blocks` linked
+    /// headers are posted on top of it. A header whose parent is already
+    /// posted must extend it.
+    pub fn post_evm_block_header(
+        ctx: Context<PostEvmBlockHeader>,
+        chain_id: u64,
+        block_number: u64,
+        header_rlp: Vec<u8>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.chain_config.is_relayer(&ctx.accounts.relayer.key()),
+            ErrorCode::UnauthorizedRelayer
+        );
+
+        let decoded = evm_proof::decode_block_header(&header_rlp)?;
+        require!(
+            decoded.number == block_number && block_number > 0,
+            ErrorCode::InvalidBlockHeader
+        );
+
+        let parent_info = ctx.accounts.parent_block_header.to_account_info();
+        let linked_from = if parent_info.data_is_empty() {
+            block_number
+        } else {
+            let parent = EvmBlockHeader::try_deserialize(&mut &parent_info.data.borrow()[..])?;
+            require!(
+                parent.block_hash == decoded.parent_hash,
+                ErrorCode::BlockHeaderNotLinked
+            );
+            parent.linked_from
+        };
+
+        let header = &mut ctx.accounts.evm_block_header;
+        header.chain_id = chain_id;
+        header.number = block_number;
+        header.block_hash = decoded.block_hash;
+        header.receipts_root = decoded.receipts_root;
+        header.timestamp = decoded.timestamp;
+        header.linked_from = linked_from;
+        header.relayer = ctx.accounts.relayer.key();
+        header.layout_version = EvmBlockHeader::LAYOUT_VERSION;
+        header.bump = ctx.bumps.evm_block_header;
+
+        msg!("🧱 EVM block {} on chain {} posted", block_number, chain_id);
+        emit!(EvmBlockHeaderPostedEvent {
+            header: EventHeader::standalone(0)?,
+            chain_id,
+            block_number,
+            block_hash: decoded.block_hash,
+            receipts_root: decoded.receipts_root,
+            relayer: ctx.accounts.relayer.key(),
+        });
+        Ok(())
+    }
+
+    /// Reserve an open Solana-origin offer for a taker, binding the EVM
+    /// address the payment will come from to the Solana account it pays out
+    /// to. The maker's payout address is re-encrypted in MPC to `pub_key`,
+    /// the taker's x25519 key, and emitted in an
+    /// `EvmPayoutAddressRevealedEvent`.
+    pub fn reserve_solana_origin_offer(
+        ctx: Context<ReserveSolanaOriginOffer>,
+        offer_id: u64,
+        taker_evm_address: [u8; 20],
+        // Arcium handshake, keyed to the taker
+        pub_key: [u8; 32],
+        nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let offer = &ctx.accounts.solana_origin_offer;
+        let taker = ctx.accounts.payer.key();
+        require!(offer.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+        require!(now <= offer.deadline, ErrorCode::OfferExpired);
+        require!(!offer.is_reserved(now), ErrorCode::OfferReserved);
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        ctx.accounts.pending_computation.record(
+            computation_offset,
+            OfferKind::SolanaOrigin,
+            offer_id,
+            ctx.accounts.solana_origin_offer.key(),
+            ComputationOperation::RevealEvmPayoutAddress,
+            taker,
+            ctx.bumps.pending_computation,
+        )?;
+
+        let offer = &mut ctx.accounts.solana_origin_offer;
+        offer.taker = Some(taker);
+        offer.taker_evm_address = taker_evm_address;
+        offer.reserved_at = now;
+        offer.status = OfferStatus::Pending;
+        offer.computation_offset = computation_offset;
+
+        msg!("📌 Solana-origin offer {} reserved", offer_id);
+        emit!(SolanaOriginOfferReservedEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::Open))?,
+            taker,
+            taker_evm_address,
+            reserved_until: now + RESERVATION_WINDOW_SECS,
+        });
+
+        let args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::PlaintextU128(offer.payout_address_nonce),
+            // The address moves with the optional escrow account, so it is passed by value
+            Argument::EncryptedU128(offer.encrypted_payout_address[0]),
+            Argument::EncryptedU32(offer.encrypted_payout_address[1]),
+        ];
+        let callback_accounts = [
+            CallbackAccount {
+                pubkey: ctx.accounts.solana_origin_offer.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.pending_computation.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.payer.key(),
+                is_writable: true,
+            },
+        ];
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![RevealEvmPayoutAddressCallback::callback_ix(&callback_accounts)],
+        )?;
+        Ok(())
+    }
+
+    /// Permissionless settlement of a reserved Solana-origin offer by proving
+    /// the taker's ERC-20 `Transfer` to the maker's payout address against a
+    /// posted block header
+    #[allow(clippy::too_many_arguments)]
+    pub fn settle_solana_origin_offer_with_receipt_proof(
+        ctx: Context<SettleSolanaOriginOfferWithReceiptProof>,
+        offer_id: u64,
+        block_number: u64,
+        tx_index: u64,
+        log_index: u64,
+        receipt_proof: Vec<Vec<u8>>,
+        payout_address: [u8; 20],
+        payout_salt: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let offer = &ctx.accounts.solana_origin_offer;
+        require!(offer.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+        require!(now <= offer.deadline, ErrorCode::OfferExpired);
+        // A lapsed reservation may already belong to someone else
+        let taker = offer
+            .taker
+            .filter(|_| offer.is_reserved(now))
+            .ok_or(ErrorCode::OfferNotReserved)?;
+        require_keys_eq!(ctx.accounts.taker.key(), taker, ErrorCode::OfferNotReserved);
+
+        // Only payments made after the reservation count
+        let header = &ctx.accounts.evm_block_header;
+        require!(
+            i64::try_from(header.timestamp).is_ok_and(|t| t >= offer.reserved_at),
+            ErrorCode::TransferBeforeReservation
+        );
+
+        let receipt = evm_proof::verify_mpt_proof(
+            &header.receipts_root,
+            &evm_proof::rlp_encode_u64(tx_index),
+            &receipt_proof,
+        )?;
+        let transfer = evm_proof::receipt_transfer_log(receipt, log_index)?;
+
+        require!(
+            transfer.token == ctx.accounts.token_mapping.evm_token,
+            ErrorCode::TokenMappingMismatch
+        );
+        require!(
+            evm_proof::payout_commitment(&payout_address, &payout_salt) == offer.payout_commitment
+                && transfer.to == payout_address,
+            ErrorCode::PayoutMismatch
+        );
+        require!(
+            transfer.from == offer.taker_evm_address,
+            ErrorCode::TransferSenderMismatch
+        );
+        require!(
+            U256::from_be_bytes(&transfer.amount) >= U256::from_be_bytes(&offer.evm_wanted_amount),
+            ErrorCode::InsufficientPayment
+        );
+
+        release_solana_origin_escrow(
+            offer,
+            &ctx.accounts.taker.to_account_info(),
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.taker_token_account,
+            &ctx.accounts.token_program,
+        )?;
+
+        ctx.accounts.processed_evm_log.layout_version = ProcessedEvmTx::LAYOUT_VERSION;
+        ctx.accounts.processed_evm_log.bump = ctx.bumps.processed_evm_log;
+        let block_hash = header.block_hash;
+        let offer = &mut ctx.accounts.solana_origin_offer;
+        offer.status = OfferStatus::Settled;
+
+        msg!("✅ Solana-origin offer {} settled by receipt proof", offer_id);
+        emit!(SolanaOriginOfferProvenEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::Open))?,
+            taker,
+            token_a_amount: offer.token_a_offered_amount,
+            block_number,
+            block_hash,
+            tx_index,
+            log_index,
+            evm_paid_amount: transfer.amount,
+        });
+        Ok(())
+    }
+
+    // === BASKET OFFER INSTRUCTIONS ===
+
+    /// Create a basket offer trading `offered_legs` for `wanted_legs`. Each
+    /// leg is escrowed in its own vault with `deposit_basket_leg_native` or
+    /// `deposit_basket_leg_spl`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_basket_offer(
+        ctx: Context<CreateBasketOffer>,
+        // Public business fields
+        id: u64,
+        offered_legs: Vec<BasketLeg>,
+        wanted_legs: Vec<BasketLeg>,
+        deadline: i64,
+        // Confidential identity
+        ciphertext_seller_identity_hash: [u8; 32],
+        // Arcium handshake
+        pub_key: [u8; 32],
+        nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        basket::create_basket_offer(
+            ctx,
+            id,
+            offered_legs,
+            wanted_legs,
+            deadline,
+            ciphertext_seller_identity_hash,
+            pub_key,
+            nonce,
+            computation_offset,
+        )
+    }
+
+    /// Verify the taker's identity via MPC; its callback makes the payer the
+    /// taker `execute_basket_swap` settles with
+    pub fn finalize_basket_offer(
+        ctx: Context<FinalizeBasketOffer>,
+        offer_id: u64,
+        // Confidential buyer identity
+        ciphertext_buyer_identity_hash: [u8; 32],
+        // Arcium handshake
+        pub_key: [u8; 32],
+        nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        basket::finalize_basket_offer(
+            ctx,
+            offer_id,
+            ciphertext_buyer_identity_hash,
+            pub_key,
+            nonce,
+            computation_offset,
+        )
+    }
+
+    /// Escrow the missing lamports of a SOL leg; the maker funds offered legs
+    /// and takers fund wanted legs
+    pub fn deposit_basket_leg_native(
+        ctx: Context<DepositBasketLegNative>,
+        offer_id: u64,
+        side: VaultSide,
+        leg_index: u8,
+    ) -> Result<()> {
+        basket::deposit_basket_leg_native(ctx, offer_id, side, leg_index)
+    }
+
+    /// Escrow the missing tokens of an SPL leg in a token account owned by
+    /// the offer
+    pub fn deposit_basket_leg_spl(
+        ctx: Context<DepositBasketLegSpl>,
+        offer_id: u64,
+        side: VaultSide,
+        leg_index: u8,
+    ) -> Result<()> {
+        basket::deposit_basket_leg_spl(ctx, offer_id, side, leg_index)
+    }
+
+    /// Settle every leg of a basket offer at once between its maker and its
+    /// verified taker. Remaining accounts are a (vault, destination) pair per
+    /// leg, offered legs first; the destination is the recipient itself for
+    /// SOL legs and its token account for SPL legs.
+    pub fn execute_basket_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteBasketSwap<'info>>,
+        offer_id: u64,
+    ) -> Result<()> {
+        basket::execute_basket_swap(ctx, offer_id)
+    }
+
+    /// Permissionless refund of one leg vault to whoever funded it. Escrow is
+    /// locked while the offer is live; once it expired, settled or its
+    /// verification aborted every remaining deposit can be returned.
+    pub fn refund_basket_leg(
+        ctx: Context<RefundBasketLeg>,
+        offer_id: u64,
+        side: VaultSide,
+        leg_index: u8,
+    ) -> Result<()> {
+        basket::refund_basket_leg(ctx, offer_id, side, leg_index)
+    }
+
+    // === RFQ INSTRUCTIONS ===
+
+    /// Ask makers to quote `size` of the base asset in the quote asset
+    /// until `quote_deadline`; the winner and the taker settle by
+    /// `settle_deadline`
+    pub fn create_rfq(
+        ctx: Context<CreateRfq>,
+        id: u64,
+        base_mint: Option<Pubkey>,
+        quote_mint: Option<Pubkey>,
+        size: u64,
+        quote_deadline: i64,
+        settle_deadline: i64,
+    ) -> Result<()> {
+        rfq::create_rfq(ctx, id, base_mint, quote_mint, size, quote_deadline, settle_deadline)
+    }
+
+    /// Quote a price for the whole RFQ size. The quote is ranked against the
+    /// best one so far in MPC and never revealed unless it wins; quotes are
+    /// ranked one at a time.
+    pub fn submit_rfq_quote(
+        ctx: Context<SubmitRfqQuote>,
+        rfq_id: u64,
+        // Confidential quote
+        ciphertext_price: [u8; 32],
+        ciphertext_maker_identity_hash: [u8; 32],
+        // Arcium handshake
+        pub_key: [u8; 32],
+        nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        rfq::submit_rfq_quote(
+            ctx,
+            rfq_id,
+            ciphertext_price,
+            ciphertext_maker_identity_hash,
+            pub_key,
+            nonce,
+            computation_offset,
+        )
+    }
+
+    /// Reveal the best quote's price and award the RFQ to its maker, once
+    /// the quote window closed or every quote slot is taken. Anyone may
+    /// request it.
+    pub fn select_rfq_quote(
+        ctx: Context<SelectRfqQuote>,
+        rfq_id: u64,
+        computation_offset: u64,
+    ) -> Result<()> {
+        rfq::select_rfq_quote(ctx, rfq_id, computation_offset)
+    }
+
+    /// Withdraw an RFQ no quote has been awarded yet
+    pub fn cancel_rfq(ctx: Context<CancelRfq>, rfq_id: u64) -> Result<()> {
+        rfq::cancel_rfq(ctx, rfq_id)
+    }
+
+    /// Escrow the missing lamports of an awarded RFQ's SOL leg; the winner
+    /// funds the base side and the taker the quote side
+    pub fn deposit_rfq_leg_native(
+        ctx: Context<DepositRfqLegNative>,
+        rfq_id: u64,
+        side: VaultSide,
+    ) -> Result<()> {
+        rfq::deposit_rfq_leg_native(ctx, rfq_id, side)
+    }
+
+    /// Escrow the missing tokens of an awarded RFQ's SPL leg in a token
+    /// account owned by the RFQ
+    pub fn deposit_rfq_leg_spl(
+        ctx: Context<DepositRfqLegSpl>,
+        rfq_id: u64,
+        side: VaultSide,
+    ) -> Result<()> {
+        rfq::deposit_rfq_leg_spl(ctx, rfq_id, side)
+    }
+
+    /// Swap both escrowed legs of an awarded RFQ. Anyone may settle once both
+    /// are funded. Remaining accounts are the (vault, destination) pairs of
+    /// the base leg, paid to the taker, then of the quote leg, paid to the
+    /// winner.
+    pub fn settle_rfq<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleRfq<'info>>,
+        rfq_id: u64,
+    ) -> Result<()> {
+        rfq::settle_rfq(ctx, rfq_id)
+    }
+
+    /// Permissionless refund of one RFQ leg to whoever funded it, once the
+    /// RFQ was cancelled, settled or missed its settlement deadline
+    pub fn refund_rfq_leg(
+        ctx: Context<RefundRfqLeg>,
+        rfq_id: u64,
+        side: VaultSide,
+    ) -> Result<()> {
+        rfq::refund_rfq_leg(ctx, rfq_id, side)
+    }
+
+    // === COUNTER-OFFER INSTRUCTIONS ===
+
+    /// Propose new terms for an open intrachain offer: `token_a_amount` of
+    /// what it offers, at most its full size, for `token_b_amount` lamports.
+    /// The proposer escrows the lamports now and their identity is verified
+    /// via MPC; the maker can then accept or reject until `deadline`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn propose_counter_offer(
+        ctx: Context<ProposeCounterOffer>,
+        offer_id: u64,
+        token_a_amount: u64,
+        token_b_amount: u64,
+        deadline: i64,
+        // Confidential proposer identity
+        ciphertext_proposer_identity_hash: [u8; 32],
+        // Arcium handshake
+        pub_key: [u8; 32],
+        nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        counter_offer::propose_counter_offer(
+            ctx,
+            offer_id,
+            token_a_amount,
+            token_b_amount,
+            deadline,
+            ciphertext_proposer_identity_hash,
+            pub_key,
+            nonce,
+            computation_offset,
+        )
+    }
+
+    /// Take a verified counter-offer: the offer adopts its terms and settles
+    /// with the proposer at once, within the offer's price band if it has
+    /// one and never while its price trigger holds it dormant. Whatever the
+    /// seller vault holds beyond the accepted size goes back to the maker.
+    pub fn accept_counter_offer(
+        ctx: Context<AcceptCounterOffer>,
+        offer_id: u64,
+        proposer: Pubkey,
+    ) -> Result<()> {
+        counter_offer::accept_counter_offer(ctx, offer_id, proposer)
+    }
+
+    /// Turn down a counter-offer; its escrow goes straight back to the
+    /// proposer, who may propose again
+    pub fn reject_counter_offer(
+        ctx: Context<RejectCounterOffer>,
+        offer_id: u64,
+        proposer: Pubkey,
+    ) -> Result<()> {
+        counter_offer::reject_counter_offer(ctx, offer_id, proposer)
+    }
+
+    /// Permissionless refund of a counter-offer's escrow once it expired,
+    /// its offer is no longer open, or the proposer's verification aborted
+    pub fn refund_counter_offer(
+        ctx: Context<RefundCounterOffer>,
+        offer_id: u64,
+        proposer: Pubkey,
+    ) -> Result<()> {
+        counter_offer::refund_counter_offer(ctx, offer_id, proposer)
+    }
+
+    // === FIAT OFFER INSTRUCTIONS ===
+
+    /// Sell `amount` lamports for `fiat_amount` minor units of
+    /// `fiat_currency`, paid off-chain. The lamports are escrowed in the
+    /// offer's `FiatVault` and the seller's payment instructions are
+    /// re-encrypted to the MXE via MPC, so only the buyer who locks the offer
+    /// ever sees them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_fiat_offer(
+        ctx: Context<CreateFiatOffer>,
+        id: u64,
+        amount: u64,
+        fiat_currency: [u8; 3],
+        fiat_amount: u64,
+        payment_window_secs: i64,
+        release_window_secs: i64,
+        // Confidential payment instructions
+        ciphertext_payment_details: [[u8; 32]; 4],
+        // Arcium handshake
+        pub_key: [u8; 32],
+        nonce: u128,
+        mxe_nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        fiat::create_fiat_offer(
+            ctx,
+            id,
+            amount,
+            fiat_currency,
+            fiat_amount,
+            payment_window_secs,
+            release_window_secs,
+            ciphertext_payment_details,
+            pub_key,
+            nonce,
+            mxe_nonce,
+            computation_offset,
+        )
+    }
+
+    /// Lock a listed fiat offer as its buyer. The seller's payment details
+    /// are re-encrypted in MPC to `pub_key`, the buyer's x25519 key, and
+    /// emitted in a `FiatPaymentDetailsRevealedEvent`; the payment window
+    /// starts once they are.
+    pub fn lock_fiat_offer(
+        ctx: Context<LockFiatOffer>,
+        offer_id: u64,
+        // Arcium handshake, keyed to the buyer
+        pub_key: [u8; 32],
+        nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        fiat::lock_fiat_offer(ctx, offer_id, pub_key, nonce, computation_offset)
+    }
+
+    /// The buyer reports the fiat payment as sent, within the payment
+    /// window; the seller then has the release window to confirm it
+    pub fn mark_fiat_offer_paid(ctx: Context<MarkFiatOfferPaid>, offer_id: u64) -> Result<()> {
+        fiat::mark_fiat_offer_paid(ctx, offer_id)
+    }
+
+    /// The seller confirms the fiat payment arrived and releases the escrow
+    /// to the buyer; allowed as soon as the offer is locked
+    pub fn release_fiat_offer(ctx: Context<ReleaseFiatOffer>, offer_id: u64) -> Result<()> {
+        fiat::release_fiat_offer(ctx, offer_id)
+    }
+
+    /// Withdraw a fiat offer no buyer has locked, or whose payment details
+    /// were never stored, refunding its escrow to the seller
+    pub fn cancel_fiat_offer(ctx: Context<CancelFiatOffer>, offer_id: u64) -> Result<()> {
+        fiat::cancel_fiat_offer(ctx, offer_id)
+    }
+
+    /// Once the current stage's window elapsed, the buyer's to pay or the
+    /// seller's to release, either party can take the trade to arbitration
+    /// by posting the dispute bond. The escrow stays frozen until an arbiter
+    /// rules or the dispute expires.
+    pub fn escalate_fiat_offer(ctx: Context<EscalateFiatOffer>, offer_id: u64) -> Result<()> {
+        fiat::escalate_fiat_offer(ctx, offer_id)
+    }
+
+    /// A registered arbiter splits an escalated fiat offer's escrow,
+    /// `maker_share_bps` of it back to the seller and the rest to the buyer.
+    /// The bond goes back to the opener when `refund_bond` is set and to the
+    /// other party otherwise.
+    pub fn rule_fiat_dispute(
+        ctx: Context<RuleFiatDispute>,
+        offer_id: u64,
+        maker_share_bps: u16,
+        refund_bond: bool,
+    ) -> Result<()> {
+        fiat::rule_fiat_dispute(ctx, offer_id, maker_share_bps, refund_bond)
+    }
+
+    /// Permissionless once the ruling window elapsed without a ruling: the
+    /// opener gets the bond back and the trade resumes where it was, so the
+    /// seller can still release or either party escalate again
+    pub fn expire_fiat_dispute(ctx: Context<ExpireFiatDispute>, offer_id: u64) -> Result<()> {
+        fiat::expire_fiat_dispute(ctx, offer_id)
+    }
+
+    // === IDENTITY EXCHANGE INSTRUCTIONS ===
+
+    /// The seller of a settled intrachain or fiat trade opens an exchange of
+    /// identities with `buyer`, who must be the buyer recorded on the
+    /// offer. Nothing is disclosed unless both parties share their identity.
+    pub fn open_identity_exchange(
+        ctx: Context<OpenIdentityExchange>,
+        trade: Pubkey,
+        buyer: Pubkey,
+    ) -> Result<()> {
+        identity_exchange::open_identity_exchange(ctx, trade, buyer)
+    }
+
+    /// A party shares their identity, encrypted to `pub_key`, their x25519
+    /// key; it is re-encrypted to the MXE via MPC. The counterparty's
+    /// identity is later revealed to the same key under `reveal_nonce`.
+    pub fn share_trade_identity(
+        ctx: Context<ShareTradeIdentity>,
+        // Confidential identity
+        ciphertext_identity: [[u8; 32]; 4],
+        // Arcium handshake
+        pub_key: [u8; 32],
+        nonce: u128,
+        reveal_nonce: u128,
+        mxe_nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        identity_exchange::share_trade_identity(
+            ctx,
+            ciphertext_identity,
+            pub_key,
+            nonce,
+            reveal_nonce,
+            mxe_nonce,
+            computation_offset,
+        )
+    }
+
+    /// Once both parties shared, either one has each identity re-encrypted
+    /// in MPC to the other party's key. Each comes out in its own
+    /// `CounterpartyIdentityRevealedEvent`, so it never appears on-chain in
+    /// the clear.
+    pub fn reveal_trade_identities(
+        ctx: Context<RevealTradeIdentities>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        identity_exchange::reveal_trade_identities(ctx, computation_offset)
+    }
+
+    // === PRICE BAND INSTRUCTIONS ===
+
+    /// Publish the price of a feed the signer owns, creating it on first use.
+    /// Token B per token A, in base units, is `price * 10^expo`; a
+    /// program-owned stand-in for a Pyth price account.
+    pub fn publish_price(
+        ctx: Context<PublishPrice>,
+        feed_id: u64,
+        price: i64,
+        expo: i32,
+    ) -> Result<()> {
+        price_band::publish_price(ctx, feed_id, price, expo)
+    }
+
+    /// Maker guards an unsettled offer with a price band: its swap only
+    /// executes while `price_feed` was updated within `max_staleness_secs`
+    /// and the offer's price is within `max_deviation_bps` of the feed's.
+    /// Setting it again replaces the band.
+    pub fn set_price_band(
+        ctx: Context<SetPriceBand>,
+        offer: Pubkey,
+        max_deviation_bps: u16,
+        max_staleness_secs: i64,
+    ) -> Result<()> {
+        price_band::set_price_band(ctx, offer, max_deviation_bps, max_staleness_secs)
+    }
+
+    // === PRICE TRIGGER INSTRUCTIONS ===
+
+    /// Maker makes an unsettled offer conditional: its swap stays dormant
+    /// until `price_feed` crosses a trigger only the MXE can read. The
+    /// trigger is a threshold in the feed's price units, at its current
+    /// exponent, and whether the offer activates at or above it or at or
+    /// below it. Setting it again, until it fired, replaces the trigger.
+    pub fn set_price_trigger(
+        ctx: Context<SetPriceTrigger>,
+        offer: Pubkey,
+        // Confidential trigger
+        ciphertext_trigger: [[u8; 32]; 2],
+        // Arcium handshake
+        pub_key: [u8; 32],
+        nonce: u128,
+        mxe_nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        price_trigger::set_price_trigger(
+            ctx,
+            offer,
+            ciphertext_trigger,
+            pub_key,
+            nonce,
+            mxe_nonce,
+            computation_offset,
+        )
+    }
+
+    /// Permissionless crank: compare the trigger's feed price with the
+    /// trigger in MPC, revealing only whether it crossed. Each published
+    /// price is checked once.
+    pub fn check_price_trigger(
+        ctx: Context<CheckPriceTrigger>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        price_trigger::check_price_trigger(ctx, computation_offset)
+    }
+
+    #[arcium_callback(encrypted_ix = "add_together")]
+    pub fn add_together_callback(
+        ctx: Context<AddTogetherCallback>,
+        output: ComputationOutputs<AddTogetherOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(AddTogetherOutput { field_0 }) => field_0,
+            _ => {
+                emit_computation_aborted(
+                    ctx.accounts.pending_computation.event_header()?,
+                    COMP_DEF_OFFSET_ADD_TOGETHER,
+                );
+                return Ok(());
+            }
+        };
+
+        emit!(SumEvent {
+            header: ctx.accounts.pending_computation.event_header()?,
+            sum: o.ciphertexts[0],
+            nonce: o.nonce.to_le_bytes(),
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "relay_offer_clone")]
+    pub fn relay_offer_clone_callback(
+        ctx: Context<RelayOfferCloneCallback>,
+        output: ComputationOutputs<RelayOfferCloneOutput>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.interchain_offer.status == OfferStatus::Pending,
+            ErrorCode::OfferNotPending
+        );
+        let _o = match output {
+            ComputationOutputs::Success(RelayOfferCloneOutput { field_0 }) => field_0,
+            _ => {
+                let operation = ComputationOperation::RelayOfferClone;
+                return abort_offer_computation(&mut ctx.accounts.interchain_offer, operation);
+            }
+        };
+
+        let offer = &mut ctx.accounts.interchain_offer;
+        offer.status = OfferStatus::Open;
+
+        // Public data already stored in PDA during relay_offer_clone call
+        emit!(RelayOfferClonedEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::Pending))?,
+            chain_id: offer.chain_id,
+            relayer: offer.relayer,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "confidential_deposit_native")]
+    pub fn confidential_deposit_native_callback(
+        ctx: Context<ConfidentialDepositNativeCallback>,
+        output: ComputationOutputs<ConfidentialDepositNativeOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(ConfidentialDepositNativeOutput { field_0 }) => field_0,
+            _ => {
+                emit_computation_aborted(
+                    ctx.accounts.pending_computation.event_header()?,
+                    COMP_DEF_OFFSET_CONFIDENTIAL_DEPOSIT_NATIVE,
+                );
+                return Ok(());
+            }
+        };
+
+        emit!(ConfidentialDepositNativeEvent {
+            header: ctx.accounts.pending_computation.event_header()?,
+            processed_amount: o.ciphertexts[0],
+            nonce: o.nonce.to_le_bytes(),
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "interchain_origin_evm_deposit_seller_spl")]
+    pub fn interchain_origin_evm_deposit_seller_spl_callback(
+        ctx: Context<InterchainOriginEvmDepositSellerSplCallback>,
+        output: ComputationOutputs<InterchainOriginEvmDepositSellerSplOutput>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.interchain_offer.status == OfferStatus::Pending,
+            ErrorCode::OfferNotPending
+        );
+        let _o = match output {
+            ComputationOutputs::Success(InterchainOriginEvmDepositSellerSplOutput { field_0 }) => field_0,
+            _ => {
+                let operation = ComputationOperation::InterchainOriginEvmDepositSellerSpl;
+                return abort_offer_computation(&mut ctx.accounts.interchain_offer, operation);
+            }
+        };
+
+        let offer = &mut ctx.accounts.interchain_offer;
+        offer.status = OfferStatus::Open;
+
+        // Public data already stored in PDA during interchain_origin_evm_deposit_seller_spl call
+        emit!(InterchainOriginEvmDepositSellerSplEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::Pending))?,
+            chain_id: offer.chain_id,
+            token_a_offered_amount: offer.token_a_offered_amount,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "finalize_interchain_origin_evm_offer")]
+    pub fn finalize_interchain_origin_evm_offer_callback(
+        ctx: Context<FinalizeInterchainOriginEvmOfferCallback>,
+        output: ComputationOutputs<FinalizeInterchainOriginEvmOfferOutput>,
+    ) -> Result<()> {
+        // Finalizes queued by competing buyers all pass the check in
+        // `finalize_interchain_origin_evm_offer`; only the first verified one
+        // becomes the buyer
+        let offer = &ctx.accounts.interchain_offer;
+        let requester = ctx.accounts.requester.key();
+        let claimable = offer.status == OfferStatus::Open
+            && offer.buyer.is_none_or(|buyer| buyer == requester);
+        let _o = match output {
+            ComputationOutputs::Success(FinalizeInterchainOriginEvmOfferOutput { field_0 })
+                if claimable =>
+            {
+                field_0
+            }
+            _ => {
+                let operation = ComputationOperation::FinalizeInterchainOriginEvmOffer;
+                return abort_offer_computation(&mut ctx.accounts.interchain_offer, operation);
+            }
+        };
+
+        // Only verification happens here; both escrows stay in their vaults
+        // until `execute_interchain_swap` moves them
+        msg!("✅ Finalize interchain offer callback executed - identity verified via MPC");
+
+        let offer = &mut ctx.accounts.interchain_offer;
+        offer.buyer = Some(requester);
+        emit!(FinalizeInterchainOriginEvmOfferEvent {
+            header: offer.event_header(offer.key(), Some(offer.status))?,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "deposit_seller_native")]
+    pub fn deposit_seller_native_callback(
+        ctx: Context<DepositSellerNativeCallback>,
+        output: ComputationOutputs<DepositSellerNativeOutput>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.intrachain_offer.status == OfferStatus::Pending,
+            ErrorCode::OfferNotPending
+        );
+        let _o = match output {
+            ComputationOutputs::Success(DepositSellerNativeOutput { field_0 }) => field_0,
+            _ => {
+                let operation = ComputationOperation::DepositSellerNative;
+                return abort_offer_computation(&mut ctx.accounts.intrachain_offer, operation);
+            }
+        };
+
+        let offer = &mut ctx.accounts.intrachain_offer;
+        offer.status = OfferStatus::Open;
+
+        // Public data already stored in PDA during deposit_seller_native call
+        emit!(DepositSellerNativeEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::Pending))?,
+            token_a_offered_amount: offer.token_a_offered_amount,
+            token_b_wanted_amount: offer.token_b_wanted_amount,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "deposit_seller_spl")]
+    pub fn deposit_seller_spl_callback(
+        ctx: Context<DepositSellerSplCallback>,
+        output: ComputationOutputs<DepositSellerSplOutput>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.intrachain_offer.status == OfferStatus::Pending,
+            ErrorCode::OfferNotPending
+        );
+        let _o = match output {
+            ComputationOutputs::Success(DepositSellerSplOutput { field_0 }) => field_0,
+            _ => {
+                let operation = ComputationOperation::DepositSellerSpl;
+                return abort_offer_computation(&mut ctx.accounts.intrachain_offer, operation);
+            }
+        };
+
+        let offer = &mut ctx.accounts.intrachain_offer;
+        offer.status = OfferStatus::Open;
+
+        // Public data already stored in PDA during deposit_seller_spl call
+        emit!(DepositSellerSplEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::Pending))?,
+            token_a_offered_amount: offer.token_a_offered_amount,
+            token_b_wanted_amount: offer.token_b_wanted_amount,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "finalize_intrachain_offer")]
+    pub fn finalize_intrachain_offer_callback(
+            ctx: Context<FinalizeIntrachainOfferCallback>,
+        output: ComputationOutputs<FinalizeIntrachainOfferOutput>,
+    ) -> Result<()> {
+        // As with interchain offers, only the first verified of several
+        // competing buyers becomes the offer's buyer
+        let offer = &ctx.accounts.intrachain_offer;
+        let requester = ctx.accounts.requester.key();
+        let claimable = offer.status == OfferStatus::Open
+            && offer.buyer.is_none_or(|buyer| buyer == requester);
+        let _o = match output {
+            ComputationOutputs::Success(FinalizeIntrachainOfferOutput { field_0 })
+                if claimable =>
+            {
+                field_0
+            }
+            _ => {
+                let operation = ComputationOperation::FinalizeIntrachainOffer;
+                return abort_offer_computation(&mut ctx.accounts.intrachain_offer, operation);
+            }
+        };
+
+        // Only verification happens here; both escrows stay in their vaults
+        // until `execute_intrachain_swap` moves them
+        msg!("✅ Finalize intrachain offer callback executed - identity verified via MPC");
+
+        let offer = &mut ctx.accounts.intrachain_offer;
+        offer.buyer = Some(requester);
+        emit!(FinalizeIntrachainOfferEvent {
+            header: offer.event_header(offer.key(), Some(offer.status))?,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "store_evm_payout_address")]
+    pub fn store_evm_payout_address_callback(
+        ctx: Context<StoreEvmPayoutAddressCallback>,
+        output: ComputationOutputs<StoreEvmPayoutAddressOutput>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.solana_origin_offer.status == OfferStatus::Pending,
+            ErrorCode::OfferNotPending
+        );
+        let o = match output {
+            ComputationOutputs::Success(StoreEvmPayoutAddressOutput { field_0 }) => field_0,
+            _ => {
+                let operation = ComputationOperation::StoreEvmPayoutAddress;
+                return abort_offer_computation(&mut ctx.accounts.solana_origin_offer, operation);
+            }
+        };
+
+        // Payout address is now only readable by the MXE
+        let offer = &mut ctx.accounts.solana_origin_offer;
+        offer.encrypted_payout_address = o.ciphertexts;
+        offer.payout_address_nonce = o.nonce;
+        offer.status = OfferStatus::Open;
+
+        emit!(EvmPayoutAddressStoredEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::Pending))?,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "reveal_evm_payout_address")]
+    pub fn reveal_evm_payout_address_callback(
+        ctx: Context<RevealEvmPayoutAddressCallback>,
+        output: ComputationOutputs<RevealEvmPayoutAddressOutput>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.solana_origin_offer.status == OfferStatus::Pending,
+            ErrorCode::OfferNotPending
+        );
+        let o = match output {
+            ComputationOutputs::Success(RevealEvmPayoutAddressOutput { field_0 }) => field_0,
+            _ => {
+                let operation = ComputationOperation::RevealEvmPayoutAddress;
+                return abort_offer_computation(&mut ctx.accounts.solana_origin_offer, operation);
+            }
+        };
+
+        let offer = &mut ctx.accounts.solana_origin_offer;
+        offer.status = OfferStatus::Open;
+        emit!(EvmPayoutAddressRevealedEvent {
+            header: offer.event_header(offer.key(), Some(OfferStatus::Pending))?,
+            taker: ctx.accounts.pending_computation.requester,
+            encryption_key: o.encryption_key,
+            nonce: o.nonce,
+            ciphertexts: o.ciphertexts,
+            reserved_until: offer.reserved_at + RESERVATION_WINDOW_SECS,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "create_basket_offer")]
+    pub fn create_basket_offer_callback(
+        ctx: Context<CreateBasketOfferCallback>,
+        output: ComputationOutputs<CreateBasketOfferOutput>,
+    ) -> Result<()> {
+        basket::create_basket_offer_callback(ctx, output)
+    }
+
+    #[arcium_callback(encrypted_ix = "finalize_basket_offer")]
+    pub fn finalize_basket_offer_callback(
+        ctx: Context<FinalizeBasketOfferCallback>,
+        output: ComputationOutputs<FinalizeBasketOfferOutput>,
+    ) -> Result<()> {
+        basket::finalize_basket_offer_callback(ctx, output)
+    }
+
+    #[arcium_callback(encrypted_ix = "rank_rfq_quote")]
+    pub fn rank_rfq_quote_callback(
+        ctx: Context<RankRfqQuoteCallback>,
+        output: ComputationOutputs<RankRfqQuoteOutput>,
+    ) -> Result<()> {
+        rfq::rank_rfq_quote_callback(ctx, output)
+    }
+
+    #[arcium_callback(encrypted_ix = "select_rfq_quote")]
+    pub fn select_rfq_quote_callback(
+        ctx: Context<SelectRfqQuoteCallback>,
+        output: ComputationOutputs<SelectRfqQuoteOutput>,
+    ) -> Result<()> {
+        rfq::select_rfq_quote_callback(ctx, output)
+    }
+
+    #[arcium_callback(encrypted_ix = "propose_counter_offer")]
+    pub fn propose_counter_offer_callback(
+        ctx: Context<ProposeCounterOfferCallback>,
+        output: ComputationOutputs<ProposeCounterOfferOutput>,
+    ) -> Result<()> {
+        counter_offer::propose_counter_offer_callback(ctx, output)
+    }
+
+    #[arcium_callback(encrypted_ix = "store_fiat_payment_details")]
+    pub fn store_fiat_payment_details_callback(
+        ctx: Context<StoreFiatPaymentDetailsCallback>,
+        output: ComputationOutputs<StoreFiatPaymentDetailsOutput>,
+    ) -> Result<()> {
+        fiat::store_fiat_payment_details_callback(ctx, output)
+    }
+
+    #[arcium_callback(encrypted_ix = "reveal_fiat_payment_details")]
+    pub fn reveal_fiat_payment_details_callback(
+        ctx: Context<RevealFiatPaymentDetailsCallback>,
+        output: ComputationOutputs<RevealFiatPaymentDetailsOutput>,
+    ) -> Result<()> {
+        fiat::reveal_fiat_payment_details_callback(ctx, output)
+    }
+
+    #[arcium_callback(encrypted_ix = "store_trade_identity")]
+    pub fn store_trade_identity_callback(
+        ctx: Context<StoreTradeIdentityCallback>,
+        output: ComputationOutputs<StoreTradeIdentityOutput>,
+    ) -> Result<()> {
+        identity_exchange::store_trade_identity_callback(ctx, output)
+    }
+
+    #[arcium_callback(encrypted_ix = "reveal_trade_identities")]
+    pub fn reveal_trade_identities_callback(
+        ctx: Context<RevealTradeIdentitiesCallback>,
+        output: ComputationOutputs<RevealTradeIdentitiesOutput>,
+    ) -> Result<()> {
+        identity_exchange::reveal_trade_identities_callback(ctx, output)
+    }
+
+    #[arcium_callback(encrypted_ix = "store_price_trigger")]
+    pub fn store_price_trigger_callback(
+        ctx: Context<StorePriceTriggerCallback>,
+        output: ComputationOutputs<StorePriceTriggerOutput>,
+    ) -> Result<()> {
+        price_trigger::store_price_trigger_callback(ctx, output)
+    }
+
+    #[arcium_callback(encrypted_ix = "check_price_trigger")]
+    pub fn check_price_trigger_callback(
+        ctx: Context<CheckPriceTriggerCallback>,
+        output: ComputationOutputs<CheckPriceTriggerOutput>,
+    ) -> Result<()> {
+        price_trigger::check_price_trigger_callback(ctx, output)
+    }
+}
+
+#[queue_computation_accounts("add_together", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct AddTogether<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ADD_TOGETHER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+
+#[queue_computation_accounts("relay_offer_clone", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, token_b_wanted_amount: [u8; 32], token_a_offered_amount: u64, is_taker_native: bool, chain_id: u64, deadline: i64, ciphertext_external_seller_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct RelayOfferClone<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = InterchainOffer::SPACE,
+        seeds = [b"InterChainoffer", payer.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+    #[account(
+        seeds = [b"ChainConfig", &chain_id.to_le_bytes()],
+        bump = chain_config.bump,
+        constraint = chain_config.enabled @ ErrorCode::ChainDisabled,
+    )]
+    pub chain_config: Box<Account<'info, ChainConfig>>,
+    #[account(
+        constraint = token_mapping.chain_id == chain_id @ ErrorCode::TokenMappingMismatch,
+    )]
+    pub token_mapping: Box<Account<'info, TokenMapping>>,
+    #[account(
+        seeds = [b"ExchangeConfig"],
+        bump = exchange_config.bump,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    /// The relayer must keep at least the minimum bond staked
+    #[account(
+        seeds = [b"RelayerBond", payer.key().as_ref()],
+        bump = relayer_bond.bump,
+        constraint = relayer_bond.bonded >= exchange_config.min_relayer_bond @ ErrorCode::InsufficientRelayerBond,
+    )]
+    pub relayer_bond: Box<Account<'info, RelayerBond>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_RELAY_OFFER_CLONE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("confidential_deposit_native", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct ConfidentialDepositNative<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CONFIDENTIAL_DEPOSIT_NATIVE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("interchain_origin_evm_deposit_seller_spl", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, token_b_wanted_amount: [u8; 32], token_a_offered_amount: u64, is_taker_native: bool, chain_id: u64, deadline: i64, ciphertext_seller_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct InterchainOriginEvmDepositSellerSpl<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = InterchainOffer::SPACE,
+        seeds = [b"InterChainoffer", payer.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+    #[account(
+        seeds = [b"ChainConfig", &chain_id.to_le_bytes()],
+        bump = chain_config.bump,
+        constraint = chain_config.enabled @ ErrorCode::ChainDisabled,
+    )]
+    pub chain_config: Box<Account<'info, ChainConfig>>,
+    #[account(
+        constraint = token_mapping.chain_id == chain_id @ ErrorCode::TokenMappingMismatch,
+    )]
+    pub token_mapping: Box<Account<'info, TokenMapping>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INTERCHAIN_ORIGIN_EVM_DEPOSIT_SELLER_SPL)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("finalize_interchain_origin_evm_offer", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, ciphertext_buyer_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct FinalizeInterchainOriginEvmOffer<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"InterChainoffer", interchain_offer.maker.as_ref(), &id.to_le_bytes()],
+        bump = interchain_offer.bump,
+        constraint = interchain_offer.status == OfferStatus::Open @ ErrorCode::OfferNotOpen,
+    )]
+    pub interchain_offer: Box<Account<'info, InterchainOffer>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTERCHAIN_ORIGIN_EVM_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("deposit_seller_native", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, token_b_wanted_amount: u64, token_a_offered_amount: u64, is_taker_native: bool, deadline: i64, ciphertext_seller_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct DepositSellerNative<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = IntraChainOffer::SPACE,
+        seeds = [b"IntraChainoffer", payer.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub intrachain_offer: Account<'info, IntraChainOffer>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_NATIVE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("deposit_seller_spl", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, token_b_wanted_amount: u64, token_a_offered_amount: u64, is_taker_native: bool, deadline: i64, ciphertext_seller_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct DepositSellerSpl<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = IntraChainOffer::SPACE,
+        seeds = [b"IntraChainoffer", payer.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub intrachain_offer: Account<'info, IntraChainOffer>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_SPL)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("finalize_intrachain_offer", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, ciphertext_buyer_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct FinalizeIntrachainOffer<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"IntraChainoffer", intrachain_offer.maker.as_ref(), &id.to_le_bytes()],
+        bump = intrachain_offer.bump,
+        constraint = intrachain_offer.status == OfferStatus::Open @ ErrorCode::OfferNotOpen,
+    )]
+    pub intrachain_offer: Box<Account<'info, IntraChainOffer>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTRACHAIN_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("store_evm_payout_address", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, chain_id: u64, token_a_offered_amount: u64, evm_wanted_amount: [u8; 32], deadline: i64, payout_commitment: [u8; 32], ciphertext_payout_address_hi: [u8; 32], ciphertext_payout_address_lo: [u8; 32], pub_key: [u8; 32], nonce: u128, mxe_nonce: u128, computation_offset: u64)]
+pub struct CreateSolanaOriginOfferNative<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = SolanaOriginOffer::SPACE,
+        seeds = [b"SolanaOriginOffer", payer.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub solana_origin_offer: Box<Account<'info, SolanaOriginOffer>>,
+    #[account(
+        seeds = [b"ChainConfig", &chain_id.to_le_bytes()],
+        bump = chain_config.bump,
+        constraint = chain_config.enabled @ ErrorCode::ChainDisabled,
+    )]
+    pub chain_config: Box<Account<'info, ChainConfig>>,
+    #[account(
+        constraint = token_mapping.chain_id == chain_id @ ErrorCode::TokenMappingMismatch,
+    )]
+    pub token_mapping: Box<Account<'info, TokenMapping>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_STORE_EVM_PAYOUT_ADDRESS)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("store_evm_payout_address", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, chain_id: u64, token_a_offered_amount: u64, evm_wanted_amount: [u8; 32], deadline: i64, payout_commitment: [u8; 32], ciphertext_payout_address_hi: [u8; 32], ciphertext_payout_address_lo: [u8; 32], pub_key: [u8; 32], nonce: u128, mxe_nonce: u128, computation_offset: u64)]
+pub struct CreateSolanaOriginOfferSpl<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = SolanaOriginOffer::SPACE,
+        seeds = [b"SolanaOriginOffer", payer.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub solana_origin_offer: Box<Account<'info, SolanaOriginOffer>>,
+    #[account(
+        seeds = [b"ChainConfig", &chain_id.to_le_bytes()],
+        bump = chain_config.bump,
+        constraint = chain_config.enabled @ ErrorCode::ChainDisabled,
+    )]
+    pub chain_config: Box<Account<'info, ChainConfig>>,
+    #[account(
+        constraint = token_mapping.chain_id == chain_id @ ErrorCode::TokenMappingMismatch,
+    )]
+    pub token_mapping: Box<Account<'info, TokenMapping>>,
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = payer,
+    )]
+    pub maker_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = solana_origin_offer,
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_STORE_EVM_PAYOUT_ADDRESS)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+// === ESCROW VAULT ACCOUNT CONTEXTS ===
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct ExecuteIntrachainSwap<'info> {
+    #[account(
+        mut,
+        seeds = [b"IntraChainoffer", seller.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = intrachain_offer.bump,
+    )]
+    pub intrachain_offer: Account<'info, IntraChainOffer>,
+    
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    
+    /// The buyer whose identity the offer's finalize verified
+    #[account(
+        mut,
+        constraint = intrachain_offer.buyer == Some(buyer.key()) @ ErrorCode::TakerNotVerified,
+    )]
+    pub buyer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"seller_vault", seller.key().as_ref(), &offer_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding seller's token A
+    pub seller_vault: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"buyer_vault", buyer.key().as_ref(), &offer_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding buyer's token B
+    pub buyer_vault: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"PriceTrigger", intrachain_offer.key().as_ref()], bump)]
+    /// CHECK: the offer's price trigger, uninitialized when it has none
+    pub price_trigger: UncheckedAccount<'info>,
+    #[account(seeds = [b"PriceBand", intrachain_offer.key().as_ref()], bump)]
+    /// CHECK: the offer's price band, uninitialized when it has none
+    pub price_band: UncheckedAccount<'info>,
+    /// The band's price feed, only needed when the offer has a band
+    pub price_feed: Option<Box<Account<'info, PriceFeed>>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct ExecuteInterchainSwap<'info> {
+    #[account(
+        mut,
+        seeds = [b"InterChainoffer", seller.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+
+    #[account(address = interchain_offer.token_mapping @ ErrorCode::TokenMappingMismatch)]
+    pub token_mapping: Account<'info, TokenMapping>,
+    
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    
+    /// The buyer whose identity the offer's finalize verified
+    #[account(
+        mut,
+        constraint = interchain_offer.buyer == Some(buyer.key()) @ ErrorCode::TakerNotVerified,
+    )]
+    pub buyer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"seller_vault", seller.key().as_ref(), &offer_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding seller's token A
+    pub seller_vault: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"buyer_vault", buyer.key().as_ref(), &offer_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding buyer's token B
+    pub buyer_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"ChainConfig", &interchain_offer.chain_id.to_le_bytes()],
+        bump = chain_config.bump,
+    )]
+    pub chain_config: Account<'info, ChainConfig>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = OutboundMessage::SPACE,
+        seeds = [
+            b"OutboundMessage".as_ref(),
+            &interchain_offer.chain_id.to_le_bytes(),
+            &chain_config.outbound_sequence.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub outbound_message: Account<'info, OutboundMessage>,
+
+    #[account(
+        seeds = [b"ExchangeConfig"],
+        bump = exchange_config.bump,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8,
+        seeds = [b"FeeTreasury"],
+        bump,
+    )]
+    /// CHECK: Fee treasury PDA
+    pub fee_treasury: UncheckedAccount<'info>,
+
+    /// Bond of the relayer that relayed the offer; omitted when there was none
+    #[account(
+        mut,
+        seeds = [b"RelayerBond", interchain_offer.relayer.as_ref()],
+        bump = relayer_bond.bump,
+    )]
+    pub relayer_bond: Option<Box<Account<'info, RelayerBond>>>,
+
+    #[account(seeds = [b"PriceTrigger", interchain_offer.key().as_ref()], bump)]
+    /// CHECK: the offer's price trigger, uninitialized when it has none
+    pub price_trigger: UncheckedAccount<'info>,
+    #[account(seeds = [b"PriceBand", interchain_offer.key().as_ref()], bump)]
+    /// CHECK: the offer's price band, uninitialized when it has none
+    pub price_band: UncheckedAccount<'info>,
+    /// The band's price feed, only needed when the offer has a band
+    pub price_feed: Option<Box<Account<'info, PriceFeed>>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct CancelInterchainOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"InterChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+        has_one = maker,
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+
+    #[account(
+        mut,
+        seeds = [b"seller_vault", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the maker's token A
+    pub seller_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"ChainConfig", &interchain_offer.chain_id.to_le_bytes()],
+        bump = chain_config.bump,
+    )]
+    pub chain_config: Account<'info, ChainConfig>,
+
+    #[account(
+        init,
+        payer = maker,
+        space = OutboundMessage::SPACE,
+        seeds = [
+            b"OutboundMessage".as_ref(),
+            &interchain_offer.chain_id.to_le_bytes(),
+            &chain_config.outbound_sequence.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub outbound_message: Account<'info, OutboundMessage>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct RefundInterchainOffer<'info> {
+    /// Anyone may crank an expired refund; they fund the outbound message
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    /// CHECK: refund destination, checked against the offer's maker
+    pub maker: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"InterChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+        has_one = maker,
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+
+    #[account(
+        mut,
+        seeds = [b"seller_vault", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the maker's token A
+    pub seller_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"ChainConfig", &interchain_offer.chain_id.to_le_bytes()],
+        bump = chain_config.bump,
+    )]
+    pub chain_config: Account<'info, ChainConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = OutboundMessage::SPACE,
+        seeds = [
+            b"OutboundMessage".as_ref(),
+            &interchain_offer.chain_id.to_le_bytes(),
+            &chain_config.outbound_sequence.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub outbound_message: Account<'info, OutboundMessage>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct DepositToSellerVault<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// The offer the vault escrows for, exactly one of the two
+    #[account(
+        seeds = [b"IntraChainoffer", seller.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = intrachain_offer.bump,
+    )]
+    pub intrachain_offer: Option<Box<Account<'info, IntraChainOffer>>>,
+    #[account(
+        seeds = [b"InterChainoffer", seller.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+    )]
+    pub interchain_offer: Option<Box<Account<'info, InterchainOffer>>>,
+    
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = 8,
+        seeds = [b"seller_vault", seller.key().as_ref(), &offer_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault PDA
+    pub seller_vault: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct DepositToBuyerVault<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: offer maker, only used to derive the offer PDA
+    pub maker: UncheckedAccount<'info>,
+    /// The offer the vault escrows for, exactly one of the two
+    #[account(
+        seeds = [b"IntraChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = intrachain_offer.bump,
+    )]
+    pub intrachain_offer: Option<Box<Account<'info, IntraChainOffer>>>,
+    #[account(
+        seeds = [b"InterChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+    )]
+    pub interchain_offer: Option<Box<Account<'info, InterchainOffer>>>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8,
+        seeds = [b"buyer_vault", buyer.key().as_ref(), &offer_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault PDA
+    pub buyer_vault: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct RefundIntrachainBuyerVault<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: offer maker, only used to derive the offer PDA
+    pub maker: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"IntraChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = intrachain_offer.bump,
+    )]
+    pub intrachain_offer: Account<'info, IntraChainOffer>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_vault", buyer.key().as_ref(), &offer_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the buyer's token B
+    pub buyer_vault: UncheckedAccount<'info>,
+}
+
+// === ABORTED COMPUTATION RECOVERY ACCOUNT CONTEXTS ===
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct RefundIntrachainOffer<'info> {
+    #[account(mut)]
+    /// CHECK: refund destination, checked against the offer's maker
+    pub maker: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"IntraChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = intrachain_offer.bump,
+        has_one = maker,
+    )]
+    pub intrachain_offer: Account<'info, IntraChainOffer>,
+
+    #[account(
+        mut,
+        seeds = [b"seller_vault", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the maker's token A
+    pub seller_vault: UncheckedAccount<'info>,
+}
+
+#[queue_computation_accounts("relay_offer_clone", payer)]
+#[derive(Accounts)]
+#[instruction(offer_id: u64, ciphertext: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct RetryRelayOfferClone<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"InterChainoffer", payer.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+    )]
+    pub interchain_offer: Box<Account<'info, InterchainOffer>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
     #[account(
         init_if_needed,
         space = 9,
@@ -641,7 +4410,7 @@ pub struct AddTogether<'info> {
     /// CHECK: computation_account, checked by the arcium program.
     pub computation_account: UncheckedAccount<'info>,
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ADD_TOGETHER)
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_RELAY_OFFER_CLONE)
     )]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(
@@ -662,21 +4431,99 @@ pub struct AddTogether<'info> {
     pub arcium_program: Program<'info, Arcium>,
 }
 
+#[queue_computation_accounts("interchain_origin_evm_deposit_seller_spl", payer)]
+#[derive(Accounts)]
+#[instruction(offer_id: u64, ciphertext: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct RetryInterchainOriginEvmDepositSellerSpl<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"InterChainoffer", payer.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+    )]
+    pub interchain_offer: Box<Account<'info, InterchainOffer>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INTERCHAIN_ORIGIN_EVM_DEPOSIT_SELLER_SPL)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
 
-#[queue_computation_accounts("relay_offer_clone", payer)]
+#[queue_computation_accounts("deposit_seller_native", payer)]
 #[derive(Accounts)]
-#[instruction(id: u64, token_b_wanted_amount: u64, token_a_offered_amount: u64, is_taker_native: bool, chain_id: u64, deadline: i64, ciphertext_external_seller_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
-pub struct RelayOfferClone<'info> {
+#[instruction(offer_id: u64, ciphertext: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct RetryDepositSellerNative<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"IntraChainoffer", payer.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = intrachain_offer.bump,
+    )]
+    pub intrachain_offer: Box<Account<'info, IntraChainOffer>>,
     #[account(
         init,
         payer = payer,
-        space = 8 + 8 + 8 + 8 + 1 + 8 + 8 + 1,
-        seeds = [b"InterChainoffer", payer.key().as_ref(), &id.to_le_bytes()],
-        bump
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
     )]
-    pub interchain_offer: Account<'info, InterchainOffer>,
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
     #[account(
         init_if_needed,
         space = 9,
@@ -709,7 +4556,7 @@ pub struct RelayOfferClone<'info> {
     /// CHECK: computation_account, checked by the arcium program.
     pub computation_account: UncheckedAccount<'info>,
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_RELAY_OFFER_CLONE)
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_NATIVE)
     )]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(
@@ -730,12 +4577,26 @@ pub struct RelayOfferClone<'info> {
     pub arcium_program: Program<'info, Arcium>,
 }
 
-#[queue_computation_accounts("confidential_deposit_native", payer)]
+#[queue_computation_accounts("deposit_seller_spl", payer)]
 #[derive(Accounts)]
-#[instruction(computation_offset: u64)]
-pub struct ConfidentialDepositNative<'info> {
+#[instruction(offer_id: u64, ciphertext: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct RetryDepositSellerSpl<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"IntraChainoffer", payer.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = intrachain_offer.bump,
+    )]
+    pub intrachain_offer: Box<Account<'info, IntraChainOffer>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
     #[account(
         init_if_needed,
         space = 9,
@@ -768,7 +4629,7 @@ pub struct ConfidentialDepositNative<'info> {
     /// CHECK: computation_account, checked by the arcium program.
     pub computation_account: UncheckedAccount<'info>,
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CONFIDENTIAL_DEPOSIT_NATIVE)
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_SPL)
     )]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(
@@ -789,20 +4650,26 @@ pub struct ConfidentialDepositNative<'info> {
     pub arcium_program: Program<'info, Arcium>,
 }
 
-#[queue_computation_accounts("interchain_origin_evm_deposit_seller_spl", payer)]
+#[queue_computation_accounts("store_evm_payout_address", payer)]
 #[derive(Accounts)]
-#[instruction(id: u64, token_b_wanted_amount: u64, token_a_offered_amount: u64, is_taker_native: bool, chain_id: u64, deadline: i64, ciphertext_seller_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
-pub struct InterchainOriginEvmDepositSellerSpl<'info> {
+#[instruction(offer_id: u64, ciphertext_hi: [u8; 32], ciphertext_lo: [u8; 32], pub_key: [u8; 32], nonce: u128, mxe_nonce: u128, computation_offset: u64)]
+pub struct RetryStoreEvmPayoutAddress<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"SolanaOriginOffer", payer.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = solana_origin_offer.bump,
+    )]
+    pub solana_origin_offer: Box<Account<'info, SolanaOriginOffer>>,
     #[account(
         init,
         payer = payer,
-        space = 8 + 8 + 8 + 8 + 1 + 8 + 8 + 1,
-        seeds = [b"InterChainoffer", payer.key().as_ref(), &id.to_le_bytes()],
-        bump
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
     )]
-    pub interchain_offer: Account<'info, InterchainOffer>,
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
     #[account(
         init_if_needed,
         space = 9,
@@ -835,7 +4702,7 @@ pub struct InterchainOriginEvmDepositSellerSpl<'info> {
     /// CHECK: computation_account, checked by the arcium program.
     pub computation_account: UncheckedAccount<'info>,
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INTERCHAIN_ORIGIN_EVM_DEPOSIT_SELLER_SPL)
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_STORE_EVM_PAYOUT_ADDRESS)
     )]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(
@@ -847,21 +4714,230 @@ pub struct InterchainOriginEvmDepositSellerSpl<'info> {
         mut,
         address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
     )]
-    pub pool_account: Account<'info, FeePool>,
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[derive(Accounts)]
+#[instruction(comp_def_offset: u32)]
+pub struct SetComputationTimeout<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"ExchangeConfig"],
+        bump = exchange_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Account<'info, ExchangeConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = ComputationTimeout::SPACE,
+        seeds = [b"ComputationTimeout".as_ref(), &comp_def_offset.to_le_bytes()],
+        bump,
+    )]
+    pub computation_timeout: Account<'info, ComputationTimeout>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct MarkComputationStale<'info> {
+    /// Anyone may mark an overdue computation stale
+    pub cranker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"PendingComputation", &computation_offset.to_le_bytes()],
+        bump = pending_computation.bump,
+        close = requester,
+        has_one = requester,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [
+            b"ComputationTimeout",
+            &pending_computation.operation.comp_def_offset().to_le_bytes(),
+        ],
+        bump,
+    )]
+    /// CHECK: the comp def's timeout, may be uninitialized to use the default
+    pub computation_timeout: UncheckedAccount<'info>,
+
+    #[account(mut, address = pending_computation.offer @ ErrorCode::PendingComputationMismatch)]
+    pub interchain_offer: Option<Box<Account<'info, InterchainOffer>>>,
+    #[account(mut, address = pending_computation.offer @ ErrorCode::PendingComputationMismatch)]
+    pub intrachain_offer: Option<Box<Account<'info, IntraChainOffer>>>,
+    #[account(mut, address = pending_computation.offer @ ErrorCode::PendingComputationMismatch)]
+    pub solana_origin_offer: Option<Box<Account<'info, SolanaOriginOffer>>>,
+    #[account(mut, address = pending_computation.offer @ ErrorCode::PendingComputationMismatch)]
+    pub basket_offer: Option<Box<Account<'info, BasketOffer>>>,
+    #[account(mut, address = pending_computation.offer @ ErrorCode::PendingComputationMismatch)]
+    pub rfq: Option<Box<Account<'info, RfqRequest>>>,
+    #[account(mut, address = pending_computation.offer @ ErrorCode::PendingComputationMismatch)]
+    pub counter_offer: Option<Box<Account<'info, CounterOffer>>>,
+    #[account(mut, address = pending_computation.offer @ ErrorCode::PendingComputationMismatch)]
+    pub fiat_offer: Option<Box<Account<'info, FiatOffer>>>,
+    #[account(mut, address = pending_computation.offer @ ErrorCode::PendingComputationMismatch)]
+    pub identity_exchange: Option<Box<Account<'info, IdentityExchange>>>,
+    #[account(mut, address = pending_computation.offer @ ErrorCode::PendingComputationMismatch)]
+    pub price_trigger: Option<Box<Account<'info, PriceTrigger>>>,
+
+    /// The buyer's escrow for the offer, for finalize computations
+    #[account(
+        mut,
+        seeds = [
+            b"buyer_vault",
+            pending_computation.requester.as_ref(),
+            &pending_computation.offer_id.to_le_bytes(),
+        ],
+        bump,
+    )]
+    /// CHECK: Escrow vault PDA
+    pub buyer_vault: Option<UncheckedAccount<'info>>,
+}
+
+// === SOLANA-ORIGIN INTERCHAIN ACCOUNT CONTEXTS ===
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64, evm_tx_hash: [u8; 32])]
+pub struct SettleSolanaOriginOffer<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    /// CHECK: offer maker, only used to derive the offer PDA
+    pub maker: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"SolanaOriginOffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = solana_origin_offer.bump,
+        has_one = maker,
+    )]
+    pub solana_origin_offer: Box<Account<'info, SolanaOriginOffer>>,
+
+    #[account(
+        seeds = [b"ChainConfig", &solana_origin_offer.chain_id.to_le_bytes()],
+        bump = chain_config.bump,
+        constraint = chain_config.enabled @ ErrorCode::ChainDisabled,
+    )]
+    pub chain_config: Box<Account<'info, ChainConfig>>,
+
+    /// Marks the EVM transaction as consumed so it cannot settle twice
+    #[account(
+        init,
+        payer = relayer,
+        space = ProcessedEvmTx::SPACE,
+        seeds = [b"ProcessedEvmTx".as_ref(), &solana_origin_offer.chain_id.to_le_bytes(), evm_tx_hash.as_ref()],
+        bump,
+    )]
+    pub processed_evm_tx: Account<'info, ProcessedEvmTx>,
+
+    #[account(mut)]
+    /// CHECK: taker who paid on the EVM chain; receives token A
+    pub taker: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub taker_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct RefundSolanaOriginOffer<'info> {
+    #[account(mut)]
+    /// CHECK: refund destination, checked against the offer's maker
+    pub maker: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"SolanaOriginOffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = solana_origin_offer.bump,
+        has_one = maker,
+    )]
+    pub solana_origin_offer: Box<Account<'info, SolanaOriginOffer>>,
+
+    #[account(mut)]
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub maker_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+#[derive(Accounts)]
+#[instruction(chain_id: u64, block_number: u64)]
+pub struct PostEvmBlockHeader<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    #[account(
+        seeds = [b"ChainConfig", &chain_id.to_le_bytes()],
+        bump = chain_config.bump,
+        constraint = chain_config.enabled @ ErrorCode::ChainDisabled,
+    )]
+    pub chain_config: Box<Account<'info, ChainConfig>>,
+
+    #[account(
+        init,
+        payer = relayer,
+        space = EvmBlockHeader::SPACE,
+        seeds = [b"EvmBlockHeader".as_ref(), &chain_id.to_le_bytes(), &block_number.to_le_bytes()],
+        bump,
+    )]
+    pub evm_block_header: Account<'info, EvmBlockHeader>,
+
+    /// CHECK: the header of the previous block, empty until it is posted
     #[account(
-        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+        seeds = [b"EvmBlockHeader".as_ref(), &chain_id.to_le_bytes(), &block_number.saturating_sub(1).to_le_bytes()],
+        bump,
     )]
-    pub clock_account: Account<'info, ClockAccount>,
+    pub parent_block_header: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
-    pub arcium_program: Program<'info, Arcium>,
 }
 
-#[queue_computation_accounts("finalize_interchain_origin_evm_offer", payer)]
+#[queue_computation_accounts("reveal_evm_payout_address", payer)]
 #[derive(Accounts)]
-#[instruction(id: u64, ciphertext_buyer_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
-pub struct FinalizeInterchainOriginEvmOffer<'info> {
+#[instruction(offer_id: u64, taker_evm_address: [u8; 20], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct ReserveSolanaOriginOffer<'info> {
+    /// The taker
     #[account(mut)]
     pub payer: Signer<'info>,
+
+    /// CHECK: offer maker, only used to derive the offer PDA
+    pub maker: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"SolanaOriginOffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = solana_origin_offer.bump,
+        has_one = maker,
+    )]
+    pub solana_origin_offer: Box<Account<'info, SolanaOriginOffer>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
     #[account(
         init_if_needed,
         space = 9,
@@ -894,7 +4970,7 @@ pub struct FinalizeInterchainOriginEvmOffer<'info> {
     /// CHECK: computation_account, checked by the arcium program.
     pub computation_account: UncheckedAccount<'info>,
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTERCHAIN_ORIGIN_EVM_OFFER)
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_EVM_PAYOUT_ADDRESS)
     )]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(
@@ -915,304 +4991,500 @@ pub struct FinalizeInterchainOriginEvmOffer<'info> {
     pub arcium_program: Program<'info, Arcium>,
 }
 
-#[queue_computation_accounts("deposit_seller_native", payer)]
 #[derive(Accounts)]
-#[instruction(id: u64, token_b_wanted_amount: u64, token_a_offered_amount: u64, is_taker_native: bool, deadline: i64, ciphertext_seller_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
-pub struct DepositSellerNative<'info> {
+#[instruction(offer_id: u64, block_number: u64, tx_index: u64, log_index: u64)]
+pub struct SettleSolanaOriginOfferWithReceiptProof<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
-    #[account(
-        init,
-        payer = payer,
-        space = 8 + 8 + 8 + 8 + 1 + 8 + 1,
-        seeds = [b"IntraChainoffer", payer.key().as_ref(), &id.to_le_bytes()],
-        bump
-    )]
-    pub intrachain_offer: Account<'info, IntraChainOffer>,
-    #[account(
-        init_if_needed,
-        space = 9,
-        payer = payer,
-        seeds = [&SIGN_PDA_SEED],
-        bump,
-        address = derive_sign_pda!(),
-    )]
-    pub sign_pda_account: Account<'info, SignerAccount>,
-    #[account(
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Account<'info, MXEAccount>,
+
+    /// CHECK: offer maker, only used to derive the offer PDA
+    pub maker: UncheckedAccount<'info>,
+
     #[account(
         mut,
-        address = derive_mempool_pda!()
+        seeds = [b"SolanaOriginOffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = solana_origin_offer.bump,
+        has_one = maker,
+        has_one = token_mapping,
     )]
-    /// CHECK: mempool_account, checked by the arcium program.
-    pub mempool_account: UncheckedAccount<'info>,
+    pub solana_origin_offer: Box<Account<'info, SolanaOriginOffer>>,
+
+    pub token_mapping: Box<Account<'info, TokenMapping>>,
+
     #[account(
-        mut,
-        address = derive_execpool_pda!()
+        seeds = [b"ChainConfig", &solana_origin_offer.chain_id.to_le_bytes()],
+        bump = chain_config.bump,
+        constraint = chain_config.enabled @ ErrorCode::ChainDisabled,
     )]
-    /// CHECK: executing_pool, checked by the arcium program.
-    pub executing_pool: UncheckedAccount<'info>,
+    pub chain_config: Box<Account<'info, ChainConfig>>,
+
     #[account(
-        mut,
-        address = derive_comp_pda!(computation_offset)
+        seeds = [b"EvmBlockHeader", &solana_origin_offer.chain_id.to_le_bytes(), &block_number.to_le_bytes()],
+        bump = evm_block_header.bump,
     )]
-    /// CHECK: computation_account, checked by the arcium program.
-    pub computation_account: UncheckedAccount<'info>,
+    pub evm_block_header: Box<Account<'info, EvmBlockHeader>>,
+
+    /// `finality_blocks` above the proven block, on the same linked run
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_NATIVE)
+        seeds = [
+            b"EvmBlockHeader".as_ref(),
+            &solana_origin_offer.chain_id.to_le_bytes(),
+            &block_number.saturating_add(chain_config.finality_blocks).to_le_bytes(),
+        ],
+        bump = finality_block_header.bump,
+        constraint = finality_block_header.linked_from <= block_number @ ErrorCode::BlockNotFinal,
     )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    pub finality_block_header: Box<Account<'info, EvmBlockHeader>>,
+
+    /// Marks the proven log as consumed so it cannot settle twice
     #[account(
-        mut,
-        address = derive_cluster_pda!(mxe_account)
+        init,
+        payer = payer,
+        space = ProcessedEvmTx::SPACE,
+        seeds = [
+            b"ProcessedEvmLog".as_ref(),
+            &solana_origin_offer.chain_id.to_le_bytes(),
+            &block_number.to_le_bytes(),
+            &tx_index.to_le_bytes(),
+            &log_index.to_le_bytes(),
+        ],
+        bump,
     )]
-    pub cluster_account: Account<'info, Cluster>,
+    pub processed_evm_log: Account<'info, ProcessedEvmTx>,
+
+    #[account(mut)]
+    /// CHECK: checked against the taker recorded by the reservation
+    pub taker: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub escrow_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub taker_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// === CHAIN REGISTRY ACCOUNT CONTEXTS ===
+
+#[derive(Accounts)]
+pub struct InitializeExchangeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// Keeps whoever lands first after a deploy from claiming the admin
     #[account(
-        mut,
-        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = ProgramData::owner(),
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::Unauthorized,
     )]
-    pub pool_account: Account<'info, FeePool>,
+    pub program_data: Account<'info, ProgramData>,
+
     #[account(
-        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+        init,
+        payer = admin,
+        space = ExchangeConfig::SPACE,
+        seeds = [b"ExchangeConfig"],
+        bump,
     )]
-    pub clock_account: Account<'info, ClockAccount>,
+    pub exchange_config: Account<'info, ExchangeConfig>,
+
     pub system_program: Program<'info, System>,
-    pub arcium_program: Program<'info, Arcium>,
 }
 
-#[queue_computation_accounts("deposit_seller_spl", payer)]
 #[derive(Accounts)]
-#[instruction(id: u64, token_b_wanted_amount: u64, token_a_offered_amount: u64, is_taker_native: bool, deadline: i64, ciphertext_seller_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
-pub struct DepositSellerSpl<'info> {
+#[instruction(chain_id: u64)]
+pub struct SetChainConfig<'info> {
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub admin: Signer<'info>,
+
     #[account(
-        init,
-        payer = payer,
-        space = 8 + 8 + 8 + 8 + 1 + 8 + 1,
-        seeds = [b"IntraChainoffer", payer.key().as_ref(), &id.to_le_bytes()],
-        bump
+        seeds = [b"ExchangeConfig"],
+        bump = exchange_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
     )]
-    pub intrachain_offer: Account<'info, IntraChainOffer>,
+    pub exchange_config: Account<'info, ExchangeConfig>,
+
     #[account(
         init_if_needed,
-        space = 9,
-        payer = payer,
-        seeds = [&SIGN_PDA_SEED],
+        payer = admin,
+        space = ChainConfig::SPACE,
+        seeds = [b"ChainConfig".as_ref(), &chain_id.to_le_bytes()],
         bump,
-        address = derive_sign_pda!(),
     )]
-    pub sign_pda_account: Account<'info, SignerAccount>,
+    pub chain_config: Account<'info, ChainConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(chain_id: u64, evm_token: [u8; 20])]
+pub struct SetTokenMapping<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
     #[account(
-        address = derive_mxe_pda!()
+        seeds = [b"ExchangeConfig"],
+        bump = exchange_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
     )]
-    pub mxe_account: Account<'info, MXEAccount>,
+    pub exchange_config: Account<'info, ExchangeConfig>,
+
     #[account(
-        mut,
-        address = derive_mempool_pda!()
+        seeds = [b"ChainConfig", &chain_id.to_le_bytes()],
+        bump = chain_config.bump,
     )]
-    /// CHECK: mempool_account, checked by the arcium program.
-    pub mempool_account: UncheckedAccount<'info>,
+    pub chain_config: Account<'info, ChainConfig>,
+
     #[account(
-        mut,
-        address = derive_execpool_pda!()
+        init_if_needed,
+        payer = admin,
+        space = TokenMapping::SPACE,
+        seeds = [b"TokenMapping".as_ref(), &chain_id.to_le_bytes(), evm_token.as_ref()],
+        bump,
     )]
-    /// CHECK: executing_pool, checked by the arcium program.
-    pub executing_pool: UncheckedAccount<'info>,
+    pub token_mapping: Account<'info, TokenMapping>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolParams<'info> {
+    pub admin: Signer<'info>,
+
     #[account(
         mut,
-        address = derive_comp_pda!(computation_offset)
-    )]
-    /// CHECK: computation_account, checked by the arcium program.
-    pub computation_account: UncheckedAccount<'info>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_SPL)
+        seeds = [b"ExchangeConfig"],
+        bump = exchange_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
     )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    pub exchange_config: Account<'info, ExchangeConfig>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawProtocolFees<'info> {
+    pub admin: Signer<'info>,
+
     #[account(
-        mut,
-        address = derive_cluster_pda!(mxe_account)
+        seeds = [b"ExchangeConfig"],
+        bump = exchange_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
     )]
-    pub cluster_account: Account<'info, Cluster>,
+    pub exchange_config: Account<'info, ExchangeConfig>,
+
     #[account(
         mut,
-        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
-    )]
-    pub pool_account: Account<'info, FeePool>,
-    #[account(
-        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+        seeds = [b"FeeTreasury"],
+        bump,
     )]
-    pub clock_account: Account<'info, ClockAccount>,
-    pub system_program: Program<'info, System>,
-    pub arcium_program: Program<'info, Arcium>,
+    /// CHECK: Fee treasury PDA
+    pub fee_treasury: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: any account chosen by the admin
+    pub recipient: UncheckedAccount<'info>,
 }
 
-#[queue_computation_accounts("finalize_intrachain_offer", payer)]
+// === RELAYER BOND ACCOUNT CONTEXTS ===
+
 #[derive(Accounts)]
-#[instruction(id: u64, ciphertext_buyer_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
-pub struct FinalizeIntrachainOffer<'info> {
+pub struct BondRelayer<'info> {
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub relayer: Signer<'info>,
+
     #[account(
         init_if_needed,
-        space = 9,
-        payer = payer,
-        seeds = [&SIGN_PDA_SEED],
+        payer = relayer,
+        space = RelayerBond::SPACE,
+        seeds = [b"RelayerBond", relayer.key().as_ref()],
         bump,
-        address = derive_sign_pda!(),
-    )]
-    pub sign_pda_account: Account<'info, SignerAccount>,
-    #[account(
-        address = derive_mxe_pda!()
     )]
-    pub mxe_account: Account<'info, MXEAccount>,
+    pub relayer_bond: Account<'info, RelayerBond>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRelayerBond<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
     #[account(
         mut,
-        address = derive_mempool_pda!()
+        seeds = [b"RelayerBond", relayer.key().as_ref()],
+        bump = relayer_bond.bump,
+        has_one = relayer,
     )]
-    /// CHECK: mempool_account, checked by the arcium program.
-    pub mempool_account: UncheckedAccount<'info>,
+    pub relayer_bond: Account<'info, RelayerBond>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawRelayerBond<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
     #[account(
         mut,
-        address = derive_execpool_pda!()
+        seeds = [b"RelayerBond", relayer.key().as_ref()],
+        bump = relayer_bond.bump,
+        has_one = relayer,
     )]
-    /// CHECK: executing_pool, checked by the arcium program.
-    pub executing_pool: UncheckedAccount<'info>,
+    pub relayer_bond: Account<'info, RelayerBond>,
+
     #[account(
-        mut,
-        address = derive_comp_pda!(computation_offset)
+        seeds = [b"ExchangeConfig"],
+        bump = exchange_config.bump,
     )]
-    /// CHECK: computation_account, checked by the arcium program.
-    pub computation_account: UncheckedAccount<'info>,
+    pub exchange_config: Account<'info, ExchangeConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct SlashRelayer<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTRACHAIN_OFFER)
+        seeds = [b"ExchangeConfig"],
+        bump = exchange_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
     )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    pub exchange_config: Account<'info, ExchangeConfig>,
+
+    /// CHECK: offer maker, only used to derive the offer PDA
+    pub maker: UncheckedAccount<'info>,
+
     #[account(
         mut,
-        address = derive_cluster_pda!(mxe_account)
+        seeds = [b"InterChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+        has_one = maker,
     )]
-    pub cluster_account: Account<'info, Cluster>,
+    pub interchain_offer: Account<'info, InterchainOffer>,
+
     #[account(
         mut,
-        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+        seeds = [b"RelayerBond", interchain_offer.relayer.as_ref()],
+        bump = relayer_bond.bump,
     )]
-    pub pool_account: Account<'info, FeePool>,
+    pub relayer_bond: Account<'info, RelayerBond>,
+
     #[account(
-        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+        init_if_needed,
+        payer = admin,
+        space = 8,
+        seeds = [b"FeeTreasury"],
+        bump,
     )]
-    pub clock_account: Account<'info, ClockAccount>,
+    /// CHECK: Fee treasury PDA
+    pub fee_treasury: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
-    pub arcium_program: Program<'info, Arcium>,
 }
 
-// === ESCROW VAULT ACCOUNT CONTEXTS ===
+// === DISPUTE ACCOUNT CONTEXTS ===
 
 #[derive(Accounts)]
-#[instruction(offer_id: u64)]
-pub struct ExecuteIntrachainSwap<'info> {
+pub struct SetArbiterRegistry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
     #[account(
-        seeds = [b"IntraChainoffer", seller.key().as_ref(), &offer_id.to_le_bytes()],
-        bump = intrachain_offer.bump,
+        seeds = [b"ExchangeConfig"],
+        bump = exchange_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
     )]
-    pub intrachain_offer: Account<'info, IntraChainOffer>,
-    
-    #[account(mut)]
-    pub seller: Signer<'info>,
-    
+    pub exchange_config: Account<'info, ExchangeConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = ArbiterRegistry::SPACE,
+        seeds = [b"ArbiterRegistry"],
+        bump,
+    )]
+    pub arbiter_registry: Account<'info, ArbiterRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64, buyer: Pubkey)]
+pub struct OpenDispute<'info> {
     #[account(mut)]
-    pub buyer: Signer<'info>,
-    
+    pub opener: Signer<'info>,
+
+    /// CHECK: offer maker, only used to derive the offer PDA
+    pub maker: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"InterChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+        has_one = maker,
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+
     #[account(
-        mut,
-        seeds = [b"seller_vault", seller.key().as_ref(), &offer_id.to_le_bytes()],
-        bump,
+        seeds = [b"ArbiterRegistry"],
+        bump = arbiter_registry.bump,
     )]
-    /// CHECK: Escrow vault holding seller's token A
-    pub seller_vault: UncheckedAccount<'info>,
-    
+    pub arbiter_registry: Account<'info, ArbiterRegistry>,
+
     #[account(
-        mut,
-        seeds = [b"buyer_vault", buyer.key().as_ref(), &offer_id.to_le_bytes()],
+        seeds = [b"buyer_vault", buyer.as_ref(), &offer_id.to_le_bytes()],
         bump,
     )]
-    /// CHECK: Escrow vault holding buyer's token B
+    /// CHECK: Escrow vault holding the buyer's token B
     pub buyer_vault: UncheckedAccount<'info>,
-    
+
+    #[account(
+        init,
+        payer = opener,
+        space = Dispute::SPACE,
+        seeds = [b"Dispute", interchain_offer.key().as_ref()],
+        bump,
+    )]
+    pub dispute: Account<'info, Dispute>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 #[instruction(offer_id: u64)]
-pub struct ExecuteInterchainSwap<'info> {
+pub struct RuleDispute<'info> {
+    #[account(mut)]
+    pub arbiter: Signer<'info>,
+
     #[account(
-        seeds = [b"InterChainoffer", seller.key().as_ref(), &offer_id.to_le_bytes()],
-        bump = interchain_offer.bump,
+        seeds = [b"ArbiterRegistry"],
+        bump = arbiter_registry.bump,
+        constraint = arbiter_registry.is_arbiter(&arbiter.key()) @ ErrorCode::UnauthorizedArbiter,
     )]
-    pub interchain_offer: Account<'info, InterchainOffer>,
-    
+    pub arbiter_registry: Account<'info, ArbiterRegistry>,
+
     #[account(mut)]
-    pub seller: Signer<'info>,
-    
+    /// CHECK: receives the maker's share, checked by the dispute's `has_one`
+    pub maker: UncheckedAccount<'info>,
+
     #[account(mut)]
-    pub buyer: Signer<'info>,
-    
+    /// CHECK: receives the buyer's share, checked by the dispute's `has_one`
+    pub buyer: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: gets the dispute's rent back, checked by the dispute's `has_one`
+    pub opened_by: UncheckedAccount<'info>,
+
     #[account(
         mut,
-        seeds = [b"seller_vault", seller.key().as_ref(), &offer_id.to_le_bytes()],
+        seeds = [b"InterChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+        has_one = maker,
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+
+    #[account(
+        mut,
+        close = opened_by,
+        seeds = [b"Dispute", interchain_offer.key().as_ref()],
+        bump = dispute.bump,
+        has_one = maker,
+        has_one = buyer,
+        has_one = opened_by,
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    #[account(
+        mut,
+        seeds = [b"seller_vault", maker.key().as_ref(), &offer_id.to_le_bytes()],
         bump,
     )]
-    /// CHECK: Escrow vault holding seller's token A
+    /// CHECK: Escrow vault holding the maker's token A
     pub seller_vault: UncheckedAccount<'info>,
-    
+
     #[account(
         mut,
         seeds = [b"buyer_vault", buyer.key().as_ref(), &offer_id.to_le_bytes()],
         bump,
     )]
-    /// CHECK: Escrow vault holding buyer's token B
+    /// CHECK: Escrow vault holding the buyer's token B
     pub buyer_vault: UncheckedAccount<'info>,
-    
+
+    #[account(
+        mut,
+        seeds = [b"ChainConfig", &interchain_offer.chain_id.to_le_bytes()],
+        bump = chain_config.bump,
+    )]
+    pub chain_config: Account<'info, ChainConfig>,
+
+    #[account(
+        init,
+        payer = arbiter,
+        space = OutboundMessage::SPACE,
+        seeds = [
+            b"OutboundMessage".as_ref(),
+            &interchain_offer.chain_id.to_le_bytes(),
+            &chain_config.outbound_sequence.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub outbound_message: Account<'info, OutboundMessage>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 #[instruction(offer_id: u64)]
-pub struct DepositToSellerVault<'info> {
+pub struct ExpireDispute<'info> {
     #[account(mut)]
-    pub seller: Signer<'info>,
-    
+    /// CHECK: gets the bond and the dispute's rent back, checked by the
+    /// dispute's `has_one`
+    pub opened_by: UncheckedAccount<'info>,
+
+    /// CHECK: offer maker, only used to derive the offer PDA
+    pub maker: UncheckedAccount<'info>,
+
     #[account(
-        init_if_needed,
-        payer = seller,
-        space = 8,
-        seeds = [b"seller_vault", seller.key().as_ref(), &offer_id.to_le_bytes()],
-        bump,
+        mut,
+        seeds = [b"InterChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+        has_one = maker,
     )]
-    /// CHECK: Escrow vault PDA
-    pub seller_vault: UncheckedAccount<'info>,
-    
-    pub system_program: Program<'info, System>,
+    pub interchain_offer: Account<'info, InterchainOffer>,
+
+    #[account(
+        mut,
+        close = opened_by,
+        seeds = [b"Dispute", interchain_offer.key().as_ref()],
+        bump = dispute.bump,
+        has_one = opened_by,
+    )]
+    pub dispute: Account<'info, Dispute>,
 }
 
+// === ACCOUNT MIGRATION CONTEXT ===
+
 #[derive(Accounts)]
-#[instruction(offer_id: u64)]
-pub struct DepositToBuyerVault<'info> {
+pub struct MigrateAccount<'info> {
+    /// Exchange admin or the account's owner; pays for the larger account
     #[account(mut)]
-    pub buyer: Signer<'info>,
-    
+    pub authority: Signer<'info>,
+
+    #[account(mut, owner = crate::ID)]
+    /// CHECK: any program account, decoded by its discriminator
+    pub account: UncheckedAccount<'info>,
+
+    /// CHECK: maker of a version 0 offer, checked against the offer's address
+    pub maker: Option<UncheckedAccount<'info>>,
+
     #[account(
-        init_if_needed,
-        payer = buyer,
-        space = 8,
-        seeds = [b"buyer_vault", buyer.key().as_ref(), &offer_id.to_le_bytes()],
+        seeds = [b"ExchangeConfig"],
         bump,
     )]
-    /// CHECK: Escrow vault PDA
-    pub buyer_vault: UncheckedAccount<'info>,
-    
+    /// CHECK: read in any layout; may not exist yet
+    pub exchange_config: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -1229,6 +5501,17 @@ pub struct AddTogetherCallback<'info> {
     #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
     /// CHECK: instructions_sysvar, checked by the account constraint
     pub instructions_sysvar: AccountInfo<'info>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::AddTogether @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
 }
 
 #[callback_accounts("relay_offer_clone")]
@@ -1242,6 +5525,20 @@ pub struct RelayOfferCloneCallback<'info> {
     #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
     /// CHECK: instructions_sysvar, checked by the account constraint
     pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::RelayOfferClone @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == interchain_offer.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
 }
 
 #[callback_accounts("confidential_deposit_native")]
@@ -1255,6 +5552,17 @@ pub struct ConfidentialDepositNativeCallback<'info> {
     #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
     /// CHECK: instructions_sysvar, checked by the account constraint
     pub instructions_sysvar: AccountInfo<'info>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::ConfidentialDepositNative @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
 }
 
 #[callback_accounts("interchain_origin_evm_deposit_seller_spl")]
@@ -1262,71 +5570,303 @@ pub struct ConfidentialDepositNativeCallback<'info> {
 pub struct InterchainOriginEvmDepositSellerSplCallback<'info> {
     pub arcium_program: Program<'info, Arcium>,
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INTERCHAIN_ORIGIN_EVM_DEPOSIT_SELLER_SPL)
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INTERCHAIN_ORIGIN_EVM_DEPOSIT_SELLER_SPL)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::InterchainOriginEvmDepositSellerSpl @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == interchain_offer.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[callback_accounts("finalize_interchain_origin_evm_offer")]
+#[derive(Accounts)]
+pub struct FinalizeInterchainOriginEvmOfferCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTERCHAIN_ORIGIN_EVM_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub interchain_offer: Box<Account<'info, InterchainOffer>>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::FinalizeInterchainOriginEvmOffer @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == interchain_offer.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[callback_accounts("deposit_seller_native")]
+#[derive(Accounts)]
+pub struct DepositSellerNativeCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_NATIVE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub intrachain_offer: Account<'info, IntraChainOffer>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::DepositSellerNative @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == intrachain_offer.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[callback_accounts("deposit_seller_spl")]
+#[derive(Accounts)]
+pub struct DepositSellerSplCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_SPL)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub intrachain_offer: Account<'info, IntraChainOffer>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::DepositSellerSpl @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == intrachain_offer.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[callback_accounts("finalize_intrachain_offer")]
+#[derive(Accounts)]
+pub struct FinalizeIntrachainOfferCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTRACHAIN_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub intrachain_offer: Box<Account<'info, IntraChainOffer>>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::FinalizeIntrachainOffer @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == intrachain_offer.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+
+
+#[callback_accounts("store_evm_payout_address")]
+#[derive(Accounts)]
+pub struct StoreEvmPayoutAddressCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_STORE_EVM_PAYOUT_ADDRESS)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub solana_origin_offer: Account<'info, SolanaOriginOffer>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::StoreEvmPayoutAddress @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == solana_origin_offer.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[callback_accounts("reveal_evm_payout_address")]
+#[derive(Accounts)]
+pub struct RevealEvmPayoutAddressCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_EVM_PAYOUT_ADDRESS)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub solana_origin_offer: Box<Account<'info, SolanaOriginOffer>>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::RevealEvmPayoutAddress @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == solana_origin_offer.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[init_computation_definition_accounts("add_together", payer)]
+#[derive(Accounts)]
+pub struct InitAddTogetherCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("relay_offer_clone", payer)]
+#[derive(Accounts)]
+pub struct InitRelayOfferCloneCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
     )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: instructions_sysvar, checked by the account constraint
-    pub instructions_sysvar: AccountInfo<'info>,
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
 }
 
-#[callback_accounts("finalize_interchain_origin_evm_offer")]
+#[init_computation_definition_accounts("confidential_deposit_native", payer)]
 #[derive(Accounts)]
-pub struct FinalizeInterchainOriginEvmOfferCallback<'info> {
-    pub arcium_program: Program<'info, Arcium>,
+pub struct InitConfidentialDepositNativeCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTERCHAIN_ORIGIN_EVM_OFFER)
+        mut,
+        address = derive_mxe_pda!()
     )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: instructions_sysvar, checked by the account constraint
-    pub instructions_sysvar: AccountInfo<'info>,
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
 }
 
-#[callback_accounts("deposit_seller_native")]
+#[init_computation_definition_accounts("interchain_origin_evm_deposit_seller_spl", payer)]
 #[derive(Accounts)]
-pub struct DepositSellerNativeCallback<'info> {
-    pub arcium_program: Program<'info, Arcium>,
+pub struct InitInterchainOriginEvmDepositSellerSplCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_NATIVE)
+        mut,
+        address = derive_mxe_pda!()
     )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: instructions_sysvar, checked by the account constraint
-    pub instructions_sysvar: AccountInfo<'info>,
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
 }
 
-#[callback_accounts("deposit_seller_spl")]
+#[init_computation_definition_accounts("finalize_interchain_origin_evm_offer", payer)]
 #[derive(Accounts)]
-pub struct DepositSellerSplCallback<'info> {
-    pub arcium_program: Program<'info, Arcium>,
+pub struct InitFinalizeInterchainOriginEvmOfferCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_SPL)
+        mut,
+        address = derive_mxe_pda!()
     )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: instructions_sysvar, checked by the account constraint
-    pub instructions_sysvar: AccountInfo<'info>,
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
 }
 
-#[callback_accounts("finalize_intrachain_offer")]
+#[init_computation_definition_accounts("deposit_seller_native", payer)]
 #[derive(Accounts)]
-pub struct FinalizeIntrachainOfferCallback<'info> {
-    pub arcium_program: Program<'info, Arcium>,
+pub struct InitDepositSellerNativeCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTRACHAIN_OFFER)
+        mut,
+        address = derive_mxe_pda!()
     )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: instructions_sysvar, checked by the account constraint
-    pub instructions_sysvar: AccountInfo<'info>,
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
 }
 
-
-
-#[init_computation_definition_accounts("add_together", payer)]
+#[init_computation_definition_accounts("deposit_seller_spl", payer)]
 #[derive(Accounts)]
-pub struct InitAddTogetherCompDef<'info> {
+pub struct InitDepositSellerSplCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(
@@ -1342,9 +5882,9 @@ pub struct InitAddTogetherCompDef<'info> {
     pub system_program: Program<'info, System>,
 }
 
-#[init_computation_definition_accounts("relay_offer_clone", payer)]
+#[init_computation_definition_accounts("finalize_intrachain_offer", payer)]
 #[derive(Accounts)]
-pub struct InitRelayOfferCloneCompDef<'info> {
+pub struct InitFinalizeIntrachainOfferCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(
@@ -1360,9 +5900,9 @@ pub struct InitRelayOfferCloneCompDef<'info> {
     pub system_program: Program<'info, System>,
 }
 
-#[init_computation_definition_accounts("confidential_deposit_native", payer)]
+#[init_computation_definition_accounts("store_evm_payout_address", payer)]
 #[derive(Accounts)]
-pub struct InitConfidentialDepositNativeCompDef<'info> {
+pub struct InitStoreEvmPayoutAddressCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(
@@ -1378,9 +5918,9 @@ pub struct InitConfidentialDepositNativeCompDef<'info> {
     pub system_program: Program<'info, System>,
 }
 
-#[init_computation_definition_accounts("interchain_origin_evm_deposit_seller_spl", payer)]
+#[init_computation_definition_accounts("reveal_evm_payout_address", payer)]
 #[derive(Accounts)]
-pub struct InitInterchainOriginEvmDepositSellerSplCompDef<'info> {
+pub struct InitRevealEvmPayoutAddressCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(
@@ -1396,121 +5936,512 @@ pub struct InitInterchainOriginEvmDepositSellerSplCompDef<'info> {
     pub system_program: Program<'info, System>,
 }
 
-#[init_computation_definition_accounts("finalize_interchain_origin_evm_offer", payer)]
-#[derive(Accounts)]
-pub struct InitFinalizeInterchainOriginEvmOfferCompDef<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        mut,
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Box<Account<'info, MXEAccount>>,
-    #[account(mut)]
-    /// CHECK: comp_def_account, checked by arcium program.
-    /// Can't check it here as it's not initialized yet.
-    pub comp_def_account: UncheckedAccount<'info>,
-    pub arcium_program: Program<'info, Arcium>,
-    pub system_program: Program<'info, System>,
+
+/// Bumped whenever the layout of an event changes so consumers can branch on it
+pub const EVENT_SCHEMA_VERSION: u8 = 1;
+
+/// Leading field of every event; correlates it with the offer and
+/// computation it belongs to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EventHeader {
+    pub schema_version: u8,
+    pub offer_kind: OfferKind,
+    /// 0 for events that are not about an offer
+    pub offer_id: u64,
+    /// Offer PDA, `Pubkey::default()` when the event has none
+    pub offer: Pubkey,
+    pub maker: Pubkey,
+    /// Computation the event queued or came from, 0 when none
+    pub computation_offset: u64,
+    /// Offer status before the instruction, `None` when it created the offer
+    /// or the event is not about an offer
+    pub status_before: Option<OfferStatus>,
+    pub status_after: Option<OfferStatus>,
+    pub slot: u64,
+    pub unix_timestamp: i64,
+}
+
+impl EventHeader {
+    fn new(
+        offer_kind: OfferKind,
+        offer_id: u64,
+        offer: Pubkey,
+        maker: Pubkey,
+        computation_offset: u64,
+        status_before: Option<OfferStatus>,
+        status_after: Option<OfferStatus>,
+    ) -> Result<Self> {
+        let clock = Clock::get()?;
+        Ok(Self {
+            schema_version: EVENT_SCHEMA_VERSION,
+            offer_kind,
+            offer_id,
+            offer,
+            maker,
+            computation_offset,
+            status_before,
+            status_after,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        })
+    }
+
+    /// Header for an event that is not about an offer
+    fn standalone(computation_offset: u64) -> Result<Self> {
+        Self::new(
+            OfferKind::Standalone,
+            0,
+            Pubkey::default(),
+            Pubkey::default(),
+            computation_offset,
+            None,
+            None,
+        )
+    }
+}
+
+/// Offer accounts events can be correlated with
+trait OfferAccount {
+    const KIND: OfferKind;
+
+    fn offer_id(&self) -> u64;
+    fn offer_maker(&self) -> Pubkey;
+    fn offer_status(&self) -> OfferStatus;
+    fn offer_computation_offset(&self) -> u64;
+
+    /// Header for an event about this offer, whose status moved from
+    /// `status_before` to its current one
+    fn event_header(&self, key: Pubkey, status_before: Option<OfferStatus>) -> Result<EventHeader> {
+        EventHeader::new(
+            Self::KIND,
+            self.offer_id(),
+            key,
+            self.offer_maker(),
+            self.offer_computation_offset(),
+            status_before,
+            Some(self.offer_status()),
+        )
+    }
+
+    /// Undoes what `operation`, queued for this offer, left pending once it
+    /// ends without a result, whether the cluster aborted it or it went
+    /// stale; returns the offer's status while it was queued
+    fn abort_computation(&mut self, operation: ComputationOperation) -> Result<OfferStatus>;
+}
+
+/// An offer whose seller verification can be re-queued once it aborted
+trait RetryableOffer: OfferAccount {
+    fn offer_deadline(&self) -> i64;
+
+    /// Moves the offer back to pending under `computation_offset`
+    fn requeue(&mut self, computation_offset: u64);
+}
+
+/// Moves an offer a computation left pending to `to`; returns `Pending`
+fn leave_pending(status: &mut OfferStatus, to: OfferStatus) -> Result<OfferStatus> {
+    require!(*status == OfferStatus::Pending, ErrorCode::OfferNotPending);
+    *status = to;
+    Ok(OfferStatus::Pending)
+}
+
+impl OfferAccount for InterchainOffer {
+    const KIND: OfferKind = OfferKind::Interchain;
+
+    fn offer_id(&self) -> u64 {
+        self.id
+    }
+    fn offer_maker(&self) -> Pubkey {
+        self.maker
+    }
+    fn offer_status(&self) -> OfferStatus {
+        self.status
+    }
+    fn offer_computation_offset(&self) -> u64 {
+        self.computation_offset
+    }
+    fn abort_computation(&mut self, operation: ComputationOperation) -> Result<OfferStatus> {
+        match operation {
+            // The offer is left as it was, for the buyer to finalize again
+            // if it is still open
+            ComputationOperation::FinalizeInterchainOriginEvmOffer => Ok(self.status),
+            _ => leave_pending(&mut self.status, OfferStatus::VerificationFailed),
+        }
+    }
+}
+
+impl OfferAccount for IntraChainOffer {
+    const KIND: OfferKind = OfferKind::Intrachain;
+
+    fn offer_id(&self) -> u64 {
+        self.id
+    }
+    fn offer_maker(&self) -> Pubkey {
+        self.maker
+    }
+    fn offer_status(&self) -> OfferStatus {
+        self.status
+    }
+    fn offer_computation_offset(&self) -> u64 {
+        self.computation_offset
+    }
+    fn abort_computation(&mut self, operation: ComputationOperation) -> Result<OfferStatus> {
+        match operation {
+            // The offer is left as it was, for the buyer to finalize again
+            // if it is still open
+            ComputationOperation::FinalizeIntrachainOffer => Ok(self.status),
+            _ => leave_pending(&mut self.status, OfferStatus::VerificationFailed),
+        }
+    }
+}
+
+impl OfferAccount for SolanaOriginOffer {
+    const KIND: OfferKind = OfferKind::SolanaOrigin;
+
+    fn offer_id(&self) -> u64 {
+        self.id
+    }
+    fn offer_maker(&self) -> Pubkey {
+        self.maker
+    }
+    fn offer_status(&self) -> OfferStatus {
+        self.status
+    }
+    fn offer_computation_offset(&self) -> u64 {
+        self.computation_offset
+    }
+    fn abort_computation(&mut self, operation: ComputationOperation) -> Result<OfferStatus> {
+        match operation {
+            // Nothing was revealed; the offer is open to takers again
+            ComputationOperation::RevealEvmPayoutAddress => {
+                let status_before = leave_pending(&mut self.status, OfferStatus::Open)?;
+                self.release_reservation();
+                Ok(status_before)
+            }
+            _ => leave_pending(&mut self.status, OfferStatus::VerificationFailed),
+        }
+    }
+}
+
+impl RetryableOffer for InterchainOffer {
+    fn offer_deadline(&self) -> i64 {
+        self.deadline
+    }
+    fn requeue(&mut self, computation_offset: u64) {
+        self.status = OfferStatus::Pending;
+        self.computation_offset = computation_offset;
+    }
+}
+
+impl RetryableOffer for IntraChainOffer {
+    fn offer_deadline(&self) -> i64 {
+        self.deadline
+    }
+    fn requeue(&mut self, computation_offset: u64) {
+        self.status = OfferStatus::Pending;
+        self.computation_offset = computation_offset;
+    }
+}
+
+impl RetryableOffer for SolanaOriginOffer {
+    fn offer_deadline(&self) -> i64 {
+        self.deadline
+    }
+    fn requeue(&mut self, computation_offset: u64) {
+        self.status = OfferStatus::Pending;
+        self.computation_offset = computation_offset;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VaultSide {
+    Seller,
+    Buyer,
+}
+
+#[event]
+pub struct SumEvent {
+    pub header: EventHeader,
+    pub sum: [u8; 32],
+    pub nonce: [u8; 16],
+}
+
+#[event]
+pub struct InterchainOfferCreatedEvent {
+    pub header: EventHeader,
+    pub chain_id: u64,
+    pub token_a_offered_amount: u64,
+    /// Full-width EVM amount, big-endian u256
+    pub token_b_wanted_amount: [u8; 32],
+    pub token_mapping: Pubkey,
+    /// `Pubkey::default()` when the maker deposited directly
+    pub relayer: Pubkey,
+    pub deadline: i64,
+}
+
+#[event]
+pub struct RelayOfferClonedEvent {
+    pub header: EventHeader,
+    pub chain_id: u64,
+    pub relayer: Pubkey,
+}
+
+#[event]
+pub struct InterchainOriginEvmDepositSellerSplEvent {
+    pub header: EventHeader,
+    pub chain_id: u64,
+    pub token_a_offered_amount: u64,
+}
+
+#[event]
+pub struct FinalizeInterchainOriginEvmOfferEvent {
+    pub header: EventHeader,
+}
+
+#[event]
+pub struct InterchainSwapExecutedEvent {
+    pub header: EventHeader,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    /// Paid to the buyer, net of `fee`
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct IntrachainOfferCreatedEvent {
+    pub header: EventHeader,
+    pub token_a_offered_amount: u64,
+    pub token_b_wanted_amount: u64,
+    pub is_taker_native: bool,
+    pub deadline: i64,
+}
+
+#[event]
+pub struct DepositSellerNativeEvent {
+    pub header: EventHeader,
+    pub token_a_offered_amount: u64,
+    pub token_b_wanted_amount: u64,
+}
+
+#[event]
+pub struct DepositSellerSplEvent {
+    pub header: EventHeader,
+    pub token_a_offered_amount: u64,
+    pub token_b_wanted_amount: u64,
+}
+
+#[event]
+pub struct FinalizeIntrachainOfferEvent {
+    pub header: EventHeader,
+}
+
+#[event]
+pub struct IntrachainSwapExecutedEvent {
+    pub header: EventHeader,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+}
+
+#[event]
+pub struct ConfidentialDepositNativeEvent {
+    pub header: EventHeader,
+    pub processed_amount: [u8; 32],
+    pub nonce: [u8; 16],
+}
+
+#[event]
+pub struct VaultDepositedEvent {
+    pub header: EventHeader,
+    pub vault: Pubkey,
+    pub side: VaultSide,
+    pub amount: u64,
+}
+
+#[event]
+pub struct OfferCancelledEvent {
+    pub header: EventHeader,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct OfferRefundedEvent {
+    pub header: EventHeader,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct BuyerVaultRefundedEvent {
+    pub header: EventHeader,
+    pub buyer: Pubkey,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct OutboundMessagePostedEvent {
+    pub header: EventHeader,
+    pub chain_id: u64,
+    pub sequence: u64,
+    pub kind: OutboundMessageKind,
+    pub message: Pubkey,
+    /// ABI-encoded payload, identical to `OutboundMessage::payload`
+    pub payload: Vec<u8>,
+}
+
+#[event]
+pub struct SolanaOriginOfferCreatedEvent {
+    pub header: EventHeader,
+    pub chain_id: u64,
+    pub token_a_offered_amount: u64,
+    /// `None` for native SOL escrow
+    pub escrow_mint: Option<Pubkey>,
+    pub evm_wanted_amount: [u8; 32],
+    pub deadline: i64,
+}
+
+#[event]
+pub struct EvmPayoutAddressStoredEvent {
+    pub header: EventHeader,
 }
 
-#[init_computation_definition_accounts("deposit_seller_native", payer)]
-#[derive(Accounts)]
-pub struct InitDepositSellerNativeCompDef<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        mut,
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Box<Account<'info, MXEAccount>>,
-    #[account(mut)]
-    /// CHECK: comp_def_account, checked by arcium program.
-    /// Can't check it here as it's not initialized yet.
-    pub comp_def_account: UncheckedAccount<'info>,
-    pub arcium_program: Program<'info, Arcium>,
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct SolanaOriginOfferReservedEvent {
+    pub header: EventHeader,
+    pub taker: Pubkey,
+    pub taker_evm_address: [u8; 20],
+    pub reserved_until: i64,
 }
 
-#[init_computation_definition_accounts("deposit_seller_spl", payer)]
-#[derive(Accounts)]
-pub struct InitDepositSellerSplCompDef<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        mut,
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Box<Account<'info, MXEAccount>>,
-    #[account(mut)]
-    /// CHECK: comp_def_account, checked by arcium program.
-    /// Can't check it here as it's not initialized yet.
-    pub comp_def_account: UncheckedAccount<'info>,
-    pub arcium_program: Program<'info, Arcium>,
-    pub system_program: Program<'info, System>,
+/// The maker's EVM payout address, encrypted to the x25519 key the taker
+/// reserved the offer with
+#[event]
+pub struct EvmPayoutAddressRevealedEvent {
+    pub header: EventHeader,
+    pub taker: Pubkey,
+    pub encryption_key: [u8; 32],
+    pub nonce: u128,
+    pub ciphertexts: [[u8; 32]; 2],
+    pub reserved_until: i64,
 }
 
-#[init_computation_definition_accounts("finalize_intrachain_offer", payer)]
-#[derive(Accounts)]
-pub struct InitFinalizeIntrachainOfferCompDef<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        mut,
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Box<Account<'info, MXEAccount>>,
-    #[account(mut)]
-    /// CHECK: comp_def_account, checked by arcium program.
-    /// Can't check it here as it's not initialized yet.
-    pub comp_def_account: UncheckedAccount<'info>,
-    pub arcium_program: Program<'info, Arcium>,
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct SolanaOriginOfferSettledEvent {
+    pub header: EventHeader,
+    pub taker: Pubkey,
+    pub token_a_amount: u64,
+    pub evm_tx_hash: [u8; 32],
+    pub evm_paid_amount: [u8; 32],
 }
 
+#[event]
+pub struct SolanaOriginOfferProvenEvent {
+    pub header: EventHeader,
+    pub taker: Pubkey,
+    pub token_a_amount: u64,
+    pub block_number: u64,
+    pub block_hash: [u8; 32],
+    pub tx_index: u64,
+    pub log_index: u64,
+    pub evm_paid_amount: [u8; 32],
+}
 
 #[event]
-pub struct SumEvent {
-    pub sum: [u8; 32],
-    pub nonce: [u8; 16],
+pub struct ComputationAbortedEvent {
+    pub header: EventHeader,
+    /// Identifies the circuit whose computation aborted
+    pub comp_def_offset: u32,
 }
 
 #[event]
-pub struct RelayOfferClonedEvent {
-    pub acknowledged: u8,
+pub struct ComputationRetriedEvent {
+    pub header: EventHeader,
+    pub comp_def_offset: u32,
 }
 
 #[event]
-pub struct ConfidentialDepositNativeEvent {
-    pub processed_amount: [u8; 32],
-    pub nonce: [u8; 16],
+pub struct ComputationStaleEvent {
+    pub header: EventHeader,
+    pub comp_def_offset: u32,
+    pub queued_at_slot: u64,
+    pub marked_by: Pubkey,
+    /// Lamports returned to the requester from their escrow
+    pub refunded: u64,
 }
 
 #[event]
-pub struct InterchainOriginEvmDepositSellerSplEvent {
-    pub acknowledged: u8,
+pub struct RelayerBondUpdatedEvent {
+    pub header: EventHeader,
+    pub relayer: Pubkey,
+    pub bonded: u64,
+    pub unbonding: u64,
+    pub unbonding_started_at: i64,
+    pub fees_earned: u64,
 }
 
 #[event]
-pub struct FinalizeInterchainOriginEvmOfferEvent {
-    pub acknowledged: u8,
+pub struct RelayerSlashedEvent {
+    pub header: EventHeader,
+    pub relayer: Pubkey,
+    pub amount: u64,
+    pub remaining_bond: u64,
 }
 
 #[event]
-pub struct DepositSellerNativeEvent {
-    pub acknowledged: u8,
+pub struct DisputeOpenedEvent {
+    pub header: EventHeader,
+    pub dispute: Pubkey,
+    pub opened_by: Pubkey,
+    pub buyer: Pubkey,
+    pub bond: u64,
+    pub ruling_deadline: i64,
 }
 
 #[event]
-pub struct DepositSellerSplEvent {
-    pub acknowledged: u8,
+pub struct DisputeRuledEvent {
+    pub header: EventHeader,
+    pub dispute: Pubkey,
+    pub arbiter: Pubkey,
+    pub maker_amount: u64,
+    pub buyer_amount: u64,
+    pub bond_recipient: Pubkey,
+    pub bond: u64,
 }
 
 #[event]
-pub struct FinalizeIntrachainOfferEvent {
-    pub acknowledged: u8,
+pub struct DisputeExpiredEvent {
+    pub header: EventHeader,
+    pub dispute: Pubkey,
+    pub bond_refunded: u64,
+}
+
+#[event]
+pub struct ProtocolFeeChargedEvent {
+    pub header: EventHeader,
+    pub fee: u64,
+    pub relayer: Pubkey,
+    pub relayer_share: u64,
+}
+
+#[event]
+pub struct EvmBlockHeaderPostedEvent {
+    pub header: EventHeader,
+    pub chain_id: u64,
+    pub block_number: u64,
+    pub block_hash: [u8; 32],
+    pub receipts_root: [u8; 32],
+    pub relayer: Pubkey,
 }
 
+#[event]
+pub struct AccountMigratedEvent {
+    pub header: EventHeader,
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub migrated_by: Pubkey,
+}
 
 #[error_code]
 pub enum ErrorCode {
@@ -1518,26 +6449,1074 @@ pub enum ErrorCode {
     AbortedComputation,
     #[msg("Cluster not set")]
     ClusterNotSet,
+    #[msg("Signer is not the exchange admin")]
+    Unauthorized,
+    #[msg("Chain is disabled")]
+    ChainDisabled,
+    #[msg("Signer is not a trusted relayer for this chain")]
+    UnauthorizedRelayer,
+    #[msg("Deadline is outside the chain's allowed window")]
+    DeadlineOutOfRange,
+    #[msg("Minimum deadline must be non-negative and not exceed the maximum")]
+    InvalidDeadlineBounds,
+    #[msg("Too many relayers for a chain config")]
+    TooManyRelayers,
+    #[msg("Too many token mappings for a chain config")]
+    TooManyTokens,
+    #[msg("Token is not allowed on this chain")]
+    TokenNotAllowed,
+    #[msg("Token mapping does not belong to this offer or chain")]
+    TokenMappingMismatch,
+    #[msg("Token has no Solana mint to settle in")]
+    NoSolanaMint,
+    #[msg("Amount cannot be represented exactly in the target decimals")]
+    AmountNotRepresentable,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Amount does not fit in a u64 on the Solana leg")]
+    AmountExceedsU64,
+    #[msg("Offer is not open")]
+    OfferNotOpen,
+    #[msg("Offer deadline has not passed yet")]
+    DeadlineNotReached,
+    #[msg("Offer deadline has passed")]
+    OfferExpired,
+    #[msg("Payout does not match the offer's payout commitment")]
+    PayoutMismatch,
+    #[msg("EVM payment is below the wanted amount")]
+    InsufficientPayment,
+    #[msg("Escrow accounts do not match the offer")]
+    EscrowMismatch,
+    #[msg("Malformed RLP encoding")]
+    InvalidRlp,
+    #[msg("Malformed EVM block header")]
+    InvalidBlockHeader,
+    #[msg("Receipt proof does not verify against the block's receipts root")]
+    InvalidReceiptProof,
+    #[msg("Receipt is malformed or the transaction failed")]
+    InvalidReceipt,
+    #[msg("No ERC-20 Transfer log at the given index")]
+    TransferLogNotFound,
+    #[msg("Transfer was not sent from the reserving taker's EVM address")]
+    TransferSenderMismatch,
+    #[msg("Transfer happened before the offer was reserved")]
+    TransferBeforeReservation,
+    #[msg("Offer is reserved by another taker")]
+    OfferReserved,
+    #[msg("Offer has no reservation for this taker")]
+    OfferNotReserved,
+    #[msg("Protocol fee or relayer parameters out of range")]
+    InvalidProtocolParams,
+    #[msg("Relayer bond is below the required minimum")]
+    InsufficientRelayerBond,
+    #[msg("Relayer bond does not belong to the offer's relayer")]
+    RelayerBondMismatch,
+    #[msg("Nothing to withdraw")]
+    NothingToWithdraw,
+    #[msg("Unbonding period has not elapsed")]
+    UnbondingNotElapsed,
+    #[msg("Offer was relayed fraudulently and its relayer slashed")]
+    OfferSlashed,
+    #[msg("Offer's computation has not aborted")]
+    OfferNotRetryable,
+    #[msg("Pending computation does not match this callback")]
+    PendingComputationMismatch,
+    #[msg("Computation timeout must be at least one slot")]
+    InvalidComputationTimeout,
+    #[msg("Computation has not timed out yet")]
+    ComputationNotStale,
+    #[msg("Offer is not awaiting a computation")]
+    OfferNotPending,
+    #[msg("Account layout version is not supported")]
+    UnsupportedAccountLayout,
+    #[msg("Account already has the current layout")]
+    AccountUpToDate,
+    #[msg("Each basket side needs 1 to MAX_BASKET_LEGS legs of distinct mints and non-zero amounts")]
+    InvalidBasketLegs,
+    #[msg("No such leg for this depositor, or its asset does not match")]
+    LegMismatch,
+    #[msg("Leg is already fully escrowed")]
+    LegFunded,
+    #[msg("Leg is not fully escrowed")]
+    LegUnfunded,
+    #[msg("Taker's identity has not been verified for this offer")]
+    TakerNotVerified,
+    #[msg("RFQ needs a non-zero size, distinct assets and a quote window closing before settlement")]
+    InvalidRfq,
+    #[msg("RFQ no longer takes quotes")]
+    QuoteWindowClosed,
+    #[msg("RFQ still takes quotes")]
+    QuoteWindowOpen,
+    #[msg("RFQ already has MAX_RFQ_QUOTES quotes")]
+    TooManyQuotes,
+    #[msg("Maker already quoted this RFQ, or is its taker")]
+    AlreadyQuoted,
+    #[msg("RFQ has no quotes")]
+    NoQuotes,
+    #[msg("RFQ has not been awarded")]
+    RfqNotAwarded,
+    #[msg("RFQ has already been awarded")]
+    RfqAlreadyAwarded,
+    #[msg("Counter-offer needs a size up to the offer's, a non-zero price and a deadline before the offer's")]
+    InvalidCounterOffer,
+    #[msg("Too many arbiters for the registry")]
+    TooManyArbiters,
+    #[msg("Dispute window must be positive")]
+    InvalidDisputeParams,
+    #[msg("Only the offer's maker or buyer can open a dispute")]
+    NotADisputeParty,
+    #[msg("Signer is not a registered arbiter")]
+    UnauthorizedArbiter,
+    #[msg("Dispute is not open")]
+    DisputeNotOpen,
+    #[msg("The dispute's ruling window has elapsed")]
+    DisputeWindowElapsed,
+    #[msg("Ruling awards more than the whole escrow")]
+    InvalidRuling,
+    #[msg("Fiat offer needs a non-zero amount and price, positive payment and release windows, and a buyer other than the seller")]
+    InvalidFiatOffer,
+    #[msg("Fiat offer is not at the stage this needs")]
+    FiatStageMismatch,
+    #[msg("Signer is not the buyer who locked the fiat offer")]
+    NotFiatBuyer,
+    #[msg("Identities are only exchanged for a settled intrachain or fiat offer")]
+    IdentityExchangeUnsupported,
+    #[msg("Signer is not the seller or the buyer of the trade")]
+    NotTradeParty,
+    #[msg("This party already shared their identity")]
+    IdentityAlreadyShared,
+    #[msg("Both parties have to share their identity first")]
+    IdentitiesNotShared,
+    #[msg("Price must be positive with an exponent of at most 18")]
+    InvalidPrice,
+    #[msg("Price bands guard intrachain and interchain offers only")]
+    PriceBandUnsupported,
+    #[msg("Price band needs a positive staleness limit")]
+    InvalidPriceBand,
+    #[msg("Price feed is not the one the offer's price band uses")]
+    PriceFeedMismatch,
+    #[msg("Price feed has not been updated recently enough")]
+    StalePrice,
+    #[msg("Offer's price is outside its band around the feed's price")]
+    PriceOutsideBand,
+    #[msg("Price triggers hold back intrachain and interchain offers only")]
+    PriceTriggerUnsupported,
+    #[msg("Price trigger is being computed or has already fired")]
+    PriceTriggerLocked,
+    #[msg("Price feed changed its exponent since the trigger was set")]
+    TriggerExponentChanged,
+    #[msg("This price was already checked against the trigger")]
+    PriceAlreadyChecked,
+    #[msg("Offer is dormant until its price trigger fires")]
+    OfferDormant,
+    #[msg("Lamport vaults only settle tokens mapped to native SOL")]
+    MintNotNative,
+    #[msg("Native SOL mappings must use 9 decimals")]
+    InvalidNativeMapping,
+    #[msg("Block header does not extend the posted header of its parent")]
+    BlockHeaderNotLinked,
+    #[msg("Block does not yet have the chain's finality depth of linked headers")]
+    BlockNotFinal,
+    #[msg("Vault deposits name exactly one intrachain or interchain offer")]
+    VaultOfferMissing,
+    #[msg("Version 0 offers migrate with the maker their address was derived from")]
+    LegacyOfferMakerMismatch,
+    #[msg("Offer can still settle with the buyer's escrow")]
+    OfferStillOpen,
 }
 
 // PDA account structures for on-chain state (matching original Anchor program)
 #[account]
+#[derive(InitSpace)]
 pub struct IntraChainOffer {
+    pub layout_version: u8,
     pub id: u64,
     pub token_a_offered_amount: u64,
     pub token_b_wanted_amount: u64,
     pub is_taker_native: bool,
     pub deadline: i64,
+    pub maker: Pubkey,
+    /// Buyer whose identity a finalize computation verified, or whose
+    /// counter-offer the maker accepted
+    pub buyer: Option<Pubkey>,
+    pub status: OfferStatus,
+    /// Offset of the latest MPC computation queued for this offer
+    pub computation_offset: u64,
     pub bump: u8,
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct InterchainOffer {
+    pub layout_version: u8,
     pub id: u64,
     pub token_a_offered_amount: u64,
-    pub token_b_wanted_amount: u64,
+    /// EVM-leg amount as a big-endian u256, in the mapped EVM token's decimals
+    pub token_b_wanted_amount: [u8; 32],
     pub is_taker_native: bool,
     pub chain_id: u64,
     pub deadline: i64,
+    /// `TokenMapping` of the EVM token that token B is denominated in
+    pub token_mapping: Pubkey,
+    /// Solana account that created the offer and owns its seller vault
+    pub maker: Pubkey,
+    /// Buyer whose identity a finalize computation verified; the only one
+    /// besides the maker who can dispute the offer
+    pub buyer: Option<Pubkey>,
+    /// Bonded relayer that mirrored the offer, default when none did
+    pub relayer: Pubkey,
+    /// Set once the relayer was slashed for this offer; it can no longer settle
+    pub slashed: bool,
+    pub status: OfferStatus,
+    /// Offset of the latest MPC computation queued for this offer
+    pub computation_offset: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum OfferStatus {
+    /// Waiting for the MPC callback that completes the offer
+    Pending,
+    Open,
+    Settled,
+    Refunded,
+    Cancelled,
+    /// The MPC computation completing the offer aborted; retry or refund it
+    VerificationFailed,
+    /// Escrow frozen by a `Dispute` until an arbiter rules or it expires
+    Disputed,
+}
+
+/// Which kind of offer a computation was queued for
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum OfferKind {
+    /// Not tied to an offer account
+    Standalone,
+    Interchain,
+    Intrachain,
+    SolanaOrigin,
+    Basket,
+    Rfq,
+    /// A `CounterOffer` on an intrachain offer
+    CounterOffer,
+    /// A P2P `FiatOffer` paid off-chain
+    Fiat,
+    /// An `IdentityExchange` after a settled trade
+    IdentityExchange,
+    /// The `PriceTrigger` holding back a conditional offer
+    PriceTrigger,
+}
+
+/// Circuit a queued computation runs
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum ComputationOperation {
+    AddTogether,
+    RelayOfferClone,
+    ConfidentialDepositNative,
+    InterchainOriginEvmDepositSellerSpl,
+    FinalizeInterchainOriginEvmOffer,
+    DepositSellerNative,
+    DepositSellerSpl,
+    FinalizeIntrachainOffer,
+    StoreEvmPayoutAddress,
+    CreateBasketOffer,
+    FinalizeBasketOffer,
+    RankRfqQuote,
+    SelectRfqQuote,
+    ProposeCounterOffer,
+    StoreFiatPaymentDetails,
+    RevealFiatPaymentDetails,
+    StoreTradeIdentity,
+    RevealTradeIdentities,
+    StorePriceTrigger,
+    CheckPriceTrigger,
+    RevealEvmPayoutAddress,
+}
+
+/// Links a queued computation offset to what it was queued for; exists
+/// until the computation's callback runs
+#[account]
+#[derive(InitSpace)]
+pub struct PendingComputation {
+    pub layout_version: u8,
+    pub computation_offset: u64,
+    pub offer_kind: OfferKind,
+    /// 0 for standalone computations
+    pub offer_id: u64,
+    /// Offer PDA, `Pubkey::default()` for standalone computations
+    pub offer: Pubkey,
+    pub operation: ComputationOperation,
+    /// Paid for the account and gets its rent back when it is closed
+    pub requester: Pubkey,
+    pub queued_at_slot: u64,
+    pub bump: u8,
+}
+
+impl PendingComputation {
+    #[allow(clippy::too_many_arguments)]
+    pub fn record(
+        &mut self,
+        computation_offset: u64,
+        offer_kind: OfferKind,
+        offer_id: u64,
+        offer: Pubkey,
+        operation: ComputationOperation,
+        requester: Pubkey,
+        bump: u8,
+    ) -> Result<()> {
+        self.computation_offset = computation_offset;
+        self.offer_kind = offer_kind;
+        self.offer_id = offer_id;
+        self.offer = offer;
+        self.operation = operation;
+        self.requester = requester;
+        self.queued_at_slot = Clock::get()?.slot;
+        self.layout_version = Self::LAYOUT_VERSION;
+        self.bump = bump;
+        Ok(())
+    }
+
+    /// Header for events of callbacks that have no offer account to read
+    fn event_header(&self) -> Result<EventHeader> {
+        EventHeader::new(
+            self.offer_kind,
+            self.offer_id,
+            self.offer,
+            Pubkey::default(),
+            self.computation_offset,
+            None,
+            None,
+        )
+    }
+}
+
+impl ComputationOperation {
+    pub fn comp_def_offset(self) -> u32 {
+        match self {
+            Self::AddTogether => COMP_DEF_OFFSET_ADD_TOGETHER,
+            Self::RelayOfferClone => COMP_DEF_OFFSET_RELAY_OFFER_CLONE,
+            Self::ConfidentialDepositNative => COMP_DEF_OFFSET_CONFIDENTIAL_DEPOSIT_NATIVE,
+            Self::InterchainOriginEvmDepositSellerSpl => {
+                COMP_DEF_OFFSET_INTERCHAIN_ORIGIN_EVM_DEPOSIT_SELLER_SPL
+            }
+            Self::FinalizeInterchainOriginEvmOffer => {
+                COMP_DEF_OFFSET_FINALIZE_INTERCHAIN_ORIGIN_EVM_OFFER
+            }
+            Self::DepositSellerNative => COMP_DEF_OFFSET_DEPOSIT_SELLER_NATIVE,
+            Self::DepositSellerSpl => COMP_DEF_OFFSET_DEPOSIT_SELLER_SPL,
+            Self::FinalizeIntrachainOffer => COMP_DEF_OFFSET_FINALIZE_INTRACHAIN_OFFER,
+            Self::StoreEvmPayoutAddress => COMP_DEF_OFFSET_STORE_EVM_PAYOUT_ADDRESS,
+            Self::CreateBasketOffer => COMP_DEF_OFFSET_CREATE_BASKET_OFFER,
+            Self::FinalizeBasketOffer => COMP_DEF_OFFSET_FINALIZE_BASKET_OFFER,
+            Self::RankRfqQuote => COMP_DEF_OFFSET_RANK_RFQ_QUOTE,
+            Self::SelectRfqQuote => COMP_DEF_OFFSET_SELECT_RFQ_QUOTE,
+            Self::ProposeCounterOffer => COMP_DEF_OFFSET_PROPOSE_COUNTER_OFFER,
+            Self::StoreFiatPaymentDetails => COMP_DEF_OFFSET_STORE_FIAT_PAYMENT_DETAILS,
+            Self::RevealFiatPaymentDetails => COMP_DEF_OFFSET_REVEAL_FIAT_PAYMENT_DETAILS,
+            Self::StoreTradeIdentity => COMP_DEF_OFFSET_STORE_TRADE_IDENTITY,
+            Self::RevealTradeIdentities => COMP_DEF_OFFSET_REVEAL_TRADE_IDENTITIES,
+            Self::StorePriceTrigger => COMP_DEF_OFFSET_STORE_PRICE_TRIGGER,
+            Self::CheckPriceTrigger => COMP_DEF_OFFSET_CHECK_PRICE_TRIGGER,
+            Self::RevealEvmPayoutAddress => COMP_DEF_OFFSET_REVEAL_EVM_PAYOUT_ADDRESS,
+        }
+    }
+}
+
+/// Per comp def override of `DEFAULT_COMPUTATION_TIMEOUT_SLOTS`
+#[account]
+#[derive(InitSpace)]
+pub struct ComputationTimeout {
+    pub layout_version: u8,
+    pub comp_def_offset: u32,
+    pub timeout_slots: u64,
+    pub bump: u8,
+}
+
+/// Only offers whose computation aborted, and that have not expired, can be re-queued
+fn check_retryable(status: OfferStatus, deadline: i64) -> Result<()> {
+    require!(status == OfferStatus::VerificationFailed, ErrorCode::OfferNotRetryable);
+    require!(Clock::get()?.unix_timestamp <= deadline, ErrorCode::OfferExpired);
+    Ok(())
+}
+
+/// Undoes what `operation` left pending on `offer`, for a callback whose
+/// computation aborted, and emits its `ComputationAbortedEvent`
+fn abort_offer_computation<T>(offer: &mut Account<T>, operation: ComputationOperation) -> Result<()>
+where
+    T: OfferAccount + AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    let status_before = offer.abort_computation(operation)?;
+    emit_computation_aborted(
+        offer.event_header(offer.key(), Some(status_before))?,
+        operation.comp_def_offset(),
+    );
+    Ok(())
+}
+
+/// Undoes what a stale `operation` left pending on `offer`, which has to be
+/// the account it was queued for; returns the header of the stale event
+fn abort_stale<T>(
+    offer: &mut Option<Box<Account<T>>>,
+    operation: ComputationOperation,
+) -> Result<EventHeader>
+where
+    T: OfferAccount + AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    let offer = offer.as_mut().ok_or(ErrorCode::PendingComputationMismatch)?;
+    let status_before = offer.abort_computation(operation)?;
+    offer.event_header(offer.key(), Some(status_before))
+}
+
+fn emit_computation_aborted(header: EventHeader, comp_def_offset: u32) {
+    msg!(
+        "⚠️ Computation {} for offer {} aborted",
+        header.computation_offset,
+        header.offer_id
+    );
+    emit!(ComputationAbortedEvent {
+        header,
+        comp_def_offset,
+    });
+}
+
+/// Re-queues `operation` for `offer`, whose computation aborted and which
+/// has not expired: records it in `pending` for `requester`, moves the
+/// offer back to pending and emits its `ComputationRetriedEvent`. Returns
+/// the accounts its callback writes.
+fn retry_offer_computation<T>(
+    offer: &mut Account<T>,
+    pending: &mut Account<PendingComputation>,
+    pending_bump: u8,
+    requester: Pubkey,
+    offer_id: u64,
+    operation: ComputationOperation,
+    computation_offset: u64,
+) -> Result<[CallbackAccount; 3]>
+where
+    T: RetryableOffer + AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    check_retryable(offer.offer_status(), offer.offer_deadline())?;
+    pending.record(
+        computation_offset,
+        T::KIND,
+        offer_id,
+        offer.key(),
+        operation,
+        requester,
+        pending_bump,
+    )?;
+    offer.requeue(computation_offset);
+    emit_computation_retried(
+        offer.event_header(offer.key(), Some(OfferStatus::VerificationFailed))?,
+        operation.comp_def_offset(),
+    );
+    Ok([offer.key(), pending.key(), requester].map(|pubkey| CallbackAccount {
+        pubkey,
+        is_writable: true,
+    }))
+}
+
+fn emit_computation_retried(header: EventHeader, comp_def_offset: u32) {
+    msg!(
+        "🔁 Computation for offer {} re-queued as {}",
+        header.offer_id,
+        header.computation_offset
+    );
+    emit!(ComputationRetriedEvent {
+        header,
+        comp_def_offset,
+    });
+}
+
+/// Global exchange settings; `admin` manages the chain registry
+#[account]
+#[derive(InitSpace)]
+pub struct ExchangeConfig {
+    pub layout_version: u8,
+    pub admin: Pubkey,
+    /// Fee taken from token A on settlement
+    pub protocol_fee_bps: u16,
+    /// Portion of the protocol fee paid to the offer's relayer
+    pub relayer_fee_share_bps: u16,
+    /// Stake required to relay offers, in lamports
+    pub min_relayer_bond: u64,
+    /// Delay between starting to unbond and withdrawing the stake
+    pub relayer_unbonding_secs: i64,
+    pub bump: u8,
+}
+
+/// Stake posted by a relayer; slashable while bonded or unbonding
+#[account]
+#[derive(InitSpace)]
+pub struct RelayerBond {
+    pub layout_version: u8,
+    pub relayer: Pubkey,
+    pub bonded: u64,
+    pub unbonding: u64,
+    pub unbonding_started_at: i64,
+    /// Unclaimed share of protocol fees, held in this account
+    pub fees_earned: u64,
+    pub total_slashed: u64,
+    pub bump: u8,
+}
+
+/// Move up to `amount` of a relayer's stake, bonded first, to `treasury`
+fn slash_bond<'info>(
+    bond: &mut Account<'info, RelayerBond>,
+    treasury: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<u64> {
+    let from_bonded = amount.min(bond.bonded);
+    let from_unbonding = (amount - from_bonded).min(bond.unbonding);
+    let slashed = from_bonded + from_unbonding;
+    require!(slashed > 0, ErrorCode::InsufficientRelayerBond);
+
+    bond.bonded -= from_bonded;
+    bond.unbonding -= from_unbonding;
+    bond.total_slashed = bond.total_slashed.saturating_add(slashed);
+
+    **bond.to_account_info().try_borrow_mut_lamports()? -= slashed;
+    **treasury.try_borrow_mut_lamports()? += slashed;
+    Ok(slashed)
+}
+
+/// Arbiters allowed to rule on disputed interchain and fiat offers and the
+/// dispute rules; `admin` manages it
+#[account]
+#[derive(InitSpace)]
+pub struct ArbiterRegistry {
+    pub layout_version: u8,
+    #[max_len(MAX_ARBITERS)]
+    pub arbiters: Vec<Pubkey>,
+    /// Lamports a party posts to open a dispute
+    pub dispute_bond: u64,
+    /// Time arbiters have to rule once a dispute is opened
+    pub dispute_window_secs: i64,
+    pub bump: u8,
+}
+
+impl ArbiterRegistry {
+    pub fn is_arbiter(&self, key: &Pubkey) -> bool {
+        self.arbiters.contains(key)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum DisputeStatus {
+    Open,
+    Ruled,
+    /// No arbiter ruled in time; the bond went back to the opener
+    Expired,
+}
+
+/// A contested `InterchainOffer` or escalated `FiatOffer`, one per offer.
+/// Holds the opener's bond until it is ruled or expires, and is kept
+/// afterwards as a record.
+#[account]
+#[derive(InitSpace)]
+pub struct Dispute {
+    pub layout_version: u8,
+    /// The disputed `InterchainOffer` or `FiatOffer`
+    pub offer: Pubkey,
+    pub offer_id: u64,
+    pub maker: Pubkey,
+    /// Owner of the buyer vault whose escrow is frozen, or the buyer who
+    /// locked the fiat offer
+    pub buyer: Pubkey,
+    /// The maker or the buyer, whoever posted the bond
+    pub opened_by: Pubkey,
+    pub bond: u64,
+    pub opened_at: i64,
+    /// Last timestamp an arbiter can rule at
+    pub ruling_deadline: i64,
+    /// Arbiter that ruled, default until then
+    pub arbiter: Pubkey,
+    /// Share of the escrow awarded to the maker
+    pub maker_share_bps: u16,
+    pub status: DisputeStatus,
+    pub bump: u8,
+}
+
+fn emit_bond_updated(bond: &RelayerBond) -> Result<()> {
+    emit!(RelayerBondUpdatedEvent {
+        header: EventHeader::standalone(0)?,
+        relayer: bond.relayer,
+        bonded: bond.bonded,
+        unbonding: bond.unbonding,
+        unbonding_started_at: bond.unbonding_started_at,
+        fees_earned: bond.fees_earned,
+    });
+    Ok(())
+}
+
+/// `amount * bps / 10_000`, rounded down
+fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    let value = u128::from(amount) * u128::from(bps) / u128::from(BPS_DENOMINATOR);
+    Ok(u64::try_from(value).map_err(|_| ErrorCode::MathOverflow)?)
+}
+
+/// Per-chain parameters for a supported external (EVM) chain
+#[account]
+#[derive(InitSpace)]
+pub struct ChainConfig {
+    pub layout_version: u8,
+    pub chain_id: u64,
+    pub enabled: bool,
+    /// Confirmations required on the external chain before an event is final
+    pub finality_blocks: u64,
+    /// Allowed offer lifetime window, in seconds from now
+    pub min_deadline_secs: i64,
+    pub max_deadline_secs: i64,
+    /// Trusted relayer/guardian set for this chain
+    #[max_len(MAX_CHAIN_RELAYERS)]
+    pub relayers: Vec<Pubkey>,
+    /// EVM token addresses that may be traded from this chain
+    #[max_len(MAX_CHAIN_TOKENS)]
+    pub allowed_tokens: Vec<[u8; 20]>,
+    /// Sequence number of the next outbound message to this chain
+    pub outbound_sequence: u64,
+    pub bump: u8,
+}
+
+impl ChainConfig {
+    pub fn is_relayer(&self, key: &Pubkey) -> bool {
+        self.relayers.contains(key)
+    }
+
+    pub fn validate_deadline(&self, deadline: i64, now: i64) -> Result<()> {
+        let lifetime = deadline.checked_sub(now).ok_or(ErrorCode::DeadlineOutOfRange)?;
+        require!(
+            lifetime >= self.min_deadline_secs && lifetime <= self.max_deadline_secs,
+            ErrorCode::DeadlineOutOfRange
+        );
+        Ok(())
+    }
+
+    pub fn validate_token_mapping(&self, mapping: &TokenMapping) -> Result<()> {
+        require!(mapping.chain_id == self.chain_id, ErrorCode::TokenMappingMismatch);
+        require!(
+            self.allowed_tokens.contains(&mapping.evm_token),
+            ErrorCode::TokenNotAllowed
+        );
+        Ok(())
+    }
+}
+
+/// Maps (chain_id, EVM token address) to a canonical asset and its Solana mint
+#[account]
+#[derive(InitSpace)]
+pub struct TokenMapping {
+    pub layout_version: u8,
+    pub chain_id: u64,
+    pub evm_token: [u8; 20],
+    /// Canonical asset id shared by every representation of the same asset
+    pub asset_id: u64,
+    pub evm_decimals: u8,
+    pub solana_mint: Option<Pubkey>,
+    pub solana_decimals: u8,
+    pub bump: u8,
+}
+
+impl TokenMapping {
+    /// Convert a big-endian u256 amount in the EVM token's decimals to the
+    /// mapped Solana mint's decimals, which must fit in a u64
+    pub fn to_solana_amount(&self, evm_amount: &[u8; 32]) -> Result<u64> {
+        require!(self.solana_mint.is_some(), ErrorCode::NoSolanaMint);
+        let normalized = normalize_amount(
+            U256::from_be_bytes(evm_amount),
+            self.evm_decimals,
+            self.solana_decimals,
+        )?;
+        Ok(normalized.to_u64().ok_or(ErrorCode::AmountExceedsU64)?)
+    }
+
+    /// `to_solana_amount` for the lamport escrow vaults, which only settle
+    /// tokens mapped to native SOL
+    pub fn to_lamports(&self, evm_amount: &[u8; 32]) -> Result<u64> {
+        require!(
+            self.solana_mint == Some(token::spl_token::native_mint::ID),
+            ErrorCode::MintNotNative
+        );
+        self.to_solana_amount(evm_amount)
+    }
+}
+
+/// Rescale `amount` between decimal precisions, rejecting overflow and lost precision
+pub fn normalize_amount(amount: U256, from_decimals: u8, to_decimals: u8) -> Result<U256> {
+    let mut scaled = amount;
+    if from_decimals <= to_decimals {
+        for _ in from_decimals..to_decimals {
+            scaled = scaled.checked_mul_u64(10).ok_or(ErrorCode::MathOverflow)?;
+        }
+    } else {
+        for _ in to_decimals..from_decimals {
+            let (quotient, remainder) = scaled.div_rem_u64(10).ok_or(ErrorCode::MathOverflow)?;
+            require!(remainder == 0, ErrorCode::AmountNotRepresentable);
+            scaled = quotient;
+        }
+    }
+    Ok(scaled)
+}
+
+/// Version of the outbound message layout; bump on any payload change
+pub const OUTBOUND_MESSAGE_VERSION: u8 = 1;
+/// ABI-encoded payload size: eight 32-byte words
+const OUTBOUND_PAYLOAD_LEN: usize = 8 * 32;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum OutboundMessageKind {
+    Settle,
+    Refund,
+    Cancel,
+    /// An arbiter split the escrow of a disputed offer
+    Ruling,
+}
+
+/// Message for the EVM side of an interchain offer, one per (chain, sequence)
+///
+/// A relayer delivers `payload` to the destination chain, which must accept
+/// each `(chain_id, sequence)` once. The payload is the ABI encoding of
+/// `(uint8 version, uint8 kind, bytes32 emitter, uint64 chainId,
+/// uint64 sequence, uint64 offerId, uint256 amount, bytes32 payoutCommitment)`.
+#[account]
+#[derive(InitSpace)]
+pub struct OutboundMessage {
+    pub layout_version: u8,
+    pub version: u8,
+    pub kind: OutboundMessageKind,
+    /// Destination chain
+    pub chain_id: u64,
+    pub sequence: u64,
+    pub offer_id: u64,
+    /// Big-endian u256 EVM-leg amount
+    pub amount: [u8; 32],
+    /// Commitment to the EVM payout recipient, zero when returning to origin
+    pub payout_commitment: [u8; 32],
+    pub posted_slot: u64,
+    #[max_len(OUTBOUND_PAYLOAD_LEN)]
+    pub payload: Vec<u8>,
+    pub bump: u8,
+}
+
+impl OutboundMessage {
+    pub const PAYLOAD_LEN: usize = OUTBOUND_PAYLOAD_LEN;
+
+    pub fn abi_encode(&self) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(Self::PAYLOAD_LEN);
+        payload.extend_from_slice(&abi_word_u64(u64::from(self.version)));
+        payload.extend_from_slice(&abi_word_u64(self.kind as u64));
+        payload.extend_from_slice(crate::ID.as_ref());
+        payload.extend_from_slice(&abi_word_u64(self.chain_id));
+        payload.extend_from_slice(&abi_word_u64(self.sequence));
+        payload.extend_from_slice(&abi_word_u64(self.offer_id));
+        payload.extend_from_slice(&self.amount);
+        payload.extend_from_slice(&self.payout_commitment);
+        payload
+    }
+}
+
+/// Left-pad a u64 into a 32-byte ABI word
+fn abi_word_u64(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Fill a freshly initialised outbound message for `offer` and advance the chain's sequence
+fn post_outbound_message(
+    chain_config: &mut ChainConfig,
+    message: &mut Account<OutboundMessage>,
+    bump: u8,
+    kind: OutboundMessageKind,
+    offer: &Account<InterchainOffer>,
+    payout_commitment: [u8; 32],
+) -> Result<()> {
+    message.version = OUTBOUND_MESSAGE_VERSION;
+    message.kind = kind;
+    message.chain_id = offer.chain_id;
+    message.sequence = chain_config.outbound_sequence;
+    message.offer_id = offer.id;
+    message.amount = offer.token_b_wanted_amount;
+    message.payout_commitment = payout_commitment;
+    message.posted_slot = Clock::get()?.slot;
+    message.payload = message.abi_encode();
+    message.layout_version = OutboundMessage::LAYOUT_VERSION;
+    message.bump = bump;
+
+    chain_config.outbound_sequence = chain_config
+        .outbound_sequence
+        .checked_add(1)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    emit!(OutboundMessagePostedEvent {
+        header: offer.event_header(offer.key(), Some(offer.status))?,
+        chain_id: message.chain_id,
+        sequence: message.sequence,
+        kind,
+        message: message.key(),
+        payload: message.payload.clone(),
+    });
+    Ok(())
+}
+
+/// Return everything above the rent-exempt minimum in a program-owned vault; returns the amount moved
+fn refund_vault<'info>(vault: &AccountInfo<'info>, to: &AccountInfo<'info>) -> Result<u64> {
+    if vault.owner != &crate::ID {
+        // Vault was never funded
+        return Ok(0);
+    }
+    let rent_exempt = Rent::get()?.minimum_balance(vault.data_len());
+    let refundable = vault.lamports().saturating_sub(rent_exempt);
+    if refundable > 0 {
+        **vault.try_borrow_mut_lamports()? -= refundable;
+        **to.try_borrow_mut_lamports()? += refundable;
+    }
+    Ok(refundable)
+}
+
+/// Offer posted on Solana whose taker pays on an EVM chain
+#[account]
+#[derive(InitSpace)]
+pub struct SolanaOriginOffer {
+    pub layout_version: u8,
+    pub id: u64,
+    pub maker: Pubkey,
+    /// EVM chain the taker pays on
+    pub chain_id: u64,
+    /// `TokenMapping` of the EVM token the maker wants
+    pub token_mapping: Pubkey,
+    /// Escrowed Solana-side amount
+    pub token_a_offered_amount: u64,
+    /// SPL escrow token account, `None` when SOL is escrowed in this account
+    pub escrow_token_account: Option<Pubkey>,
+    /// EVM-leg amount as a big-endian u256
+    pub evm_wanted_amount: [u8; 32],
+    /// keccak256(payout address || salt); lets a payment be matched without revealing the address
+    pub payout_commitment: [u8; 32],
+    /// Maker's EVM payout address, encrypted to the MXE
+    pub encrypted_payout_address: [[u8; 32]; 2],
+    pub payout_address_nonce: u128,
+    /// Solana account paid out on settlement, set by `reserve_solana_origin_offer`
+    pub taker: Option<Pubkey>,
+    /// EVM address the reserving taker pays from
+    pub taker_evm_address: [u8; 20],
+    pub reserved_at: i64,
+    pub deadline: i64,
+    pub status: OfferStatus,
+    /// Offset of the latest MPC computation queued for this offer
+    pub computation_offset: u64,
+    pub bump: u8,
+}
+
+impl SolanaOriginOffer {
+    pub fn is_reserved(&self, now: i64) -> bool {
+        self.taker.is_some() && now < self.reserved_at.saturating_add(RESERVATION_WINDOW_SECS)
+    }
+
+    pub fn release_reservation(&mut self) {
+        self.taker = None;
+        self.taker_evm_address = [0; 20];
+        self.reserved_at = 0;
+    }
+}
+
+/// Marker for an EVM transaction or log already used to settle an offer
+#[account]
+#[derive(InitSpace)]
+pub struct ProcessedEvmTx {
+    pub layout_version: u8,
     pub bump: u8,
-}
\ No newline at end of file
+}
+
+/// EVM block header posted by a relayer, keyed by (chain_id, number)
+#[account]
+#[derive(InitSpace)]
+pub struct EvmBlockHeader {
+    pub layout_version: u8,
+    pub chain_id: u64,
+    pub number: u64,
+    pub block_hash: [u8; 32],
+    pub receipts_root: [u8; 32],
+    pub timestamp: u64,
+    /// First block of the run of parent-linked headers this one ends
+    pub linked_from: u64,
+    pub relayer: Pubkey,
+    pub bump: u8,
+}
+
+/// Pay out a Solana-origin offer's escrow, either lamports held by the offer
+/// PDA or tokens in its escrow token account
+fn release_solana_origin_escrow<'info>(
+    offer: &Account<'info, SolanaOriginOffer>,
+    recipient: &AccountInfo<'info>,
+    escrow_token_account: &Option<Account<'info, TokenAccount>>,
+    recipient_token_account: &Option<Account<'info, TokenAccount>>,
+    token_program: &Option<Program<'info, Token>>,
+) -> Result<()> {
+    let amount = offer.token_a_offered_amount;
+    match offer.escrow_token_account {
+        None => {
+            **offer.to_account_info().try_borrow_mut_lamports()? -= amount;
+            **recipient.try_borrow_mut_lamports()? += amount;
+        }
+        Some(expected_escrow) => {
+            let (Some(escrow), Some(destination), Some(token_program)) =
+                (escrow_token_account, recipient_token_account, token_program)
+            else {
+                return Err(ErrorCode::EscrowMismatch.into());
+            };
+            require_keys_eq!(escrow.key(), expected_escrow, ErrorCode::EscrowMismatch);
+            require_keys_eq!(destination.owner, recipient.key(), ErrorCode::EscrowMismatch);
+            require_keys_eq!(destination.mint, escrow.mint, ErrorCode::EscrowMismatch);
+
+            let id_bytes = offer.id.to_le_bytes();
+            let signer_seeds: &[&[&[u8]]] = &[&[
+                b"SolanaOriginOffer",
+                offer.maker.as_ref(),
+                &id_bytes,
+                &[offer.bump],
+            ]];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    SplTransfer {
+                        from: escrow.to_account_info(),
+                        to: destination.to_account_info(),
+                        authority: offer.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                amount,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Lamports a program-owned vault holds above its rent-exempt minimum
+fn vault_lamports(vault: &AccountInfo) -> Result<u64> {
+    let rent_exempt = Rent::get()?.minimum_balance(vault.data_len());
+    Ok(vault.lamports().saturating_sub(rent_exempt))
+}
+
+/// An escrowed leg about to move out of its vault
+struct LegRelease<'a, 'info> {
+    vault: &'a AccountInfo<'info>,
+    destination: &'a AccountInfo<'info>,
+    leg: BasketLeg,
+}
+
+/// Checks that `accounts` hold a fully escrowed (vault, destination) pair
+/// for every one of `legs`, the vault of leg `i` being `vault_address(i)`
+/// and the destination paying `recipient`
+fn leg_releases<'a, 'info>(
+    legs: &[BasketLeg],
+    accounts: &'a [AccountInfo<'info>],
+    recipient: &Pubkey,
+    vault_address: impl Fn(u8) -> Pubkey,
+) -> Result<Vec<LegRelease<'a, 'info>>> {
+    let mut releases = Vec::with_capacity(accounts.len() / 2);
+    for (leg_index, (leg, pair)) in legs.iter().zip(accounts.chunks_exact(2)).enumerate() {
+        let (vault, destination) = (&pair[0], &pair[1]);
+        require_keys_eq!(vault.key(), vault_address(leg_index as u8), ErrorCode::EscrowMismatch);
+        match leg.mint {
+            None => {
+                require_keys_eq!(destination.key(), *recipient, ErrorCode::EscrowMismatch);
+                require!(
+                    vault.owner == &crate::ID && vault_lamports(vault)? >= leg.amount,
+                    ErrorCode::LegUnfunded
+                );
+            }
+            Some(mint) => {
+                require!(vault.owner == &token::ID, ErrorCode::LegUnfunded);
+                let escrow = TokenAccount::try_deserialize(&mut &vault.try_borrow_data()?[..])?;
+                require!(escrow.amount >= leg.amount, ErrorCode::LegUnfunded);
+                require!(destination.owner == &token::ID, ErrorCode::EscrowMismatch);
+                let destination_account =
+                    TokenAccount::try_deserialize(&mut &destination.try_borrow_data()?[..])?;
+                require_keys_eq!(destination_account.owner, *recipient, ErrorCode::EscrowMismatch);
+                require_keys_eq!(destination_account.mint, mint, ErrorCode::EscrowMismatch);
+            }
+        }
+        releases.push(LegRelease {
+            vault,
+            destination,
+            leg: *leg,
+        });
+    }
+    Ok(releases)
+}
+
+/// Move `amount` of a leg out of its vault, lamports directly and tokens
+/// signed for by `authority`, the PDA owning the token vault
+fn release_leg<'info>(
+    authority: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    vault: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    mint: Option<Pubkey>,
+    amount: u64,
+    token_program: Option<&Program<'info, Token>>,
+) -> Result<()> {
+    if mint.is_none() {
+        **vault.try_borrow_mut_lamports()? -= amount;
+        **destination.try_borrow_mut_lamports()? += amount;
+        return Ok(());
+    }
+    let token_program = token_program.ok_or(ErrorCode::EscrowMismatch)?;
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            SplTransfer {
+                from: vault.clone(),
+                to: destination.clone(),
+                authority: authority.clone(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )
+}
+
+/// Refund whatever a leg's vault holds to `depositor`, who funded it:
+/// lamports directly, tokens to `destination`, their account for the
+/// leg's mint, signed for by `authority`; returns the amount refunded
+fn refund_leg<'info>(
+    authority: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    vault: &AccountInfo<'info>,
+    depositor: &AccountInfo<'info>,
+    destination: Option<&Account<'info, TokenAccount>>,
+    mint: Option<Pubkey>,
+    token_program: Option<&Program<'info, Token>>,
+) -> Result<u64> {
+    let refunded = match mint {
+        None => refund_vault(vault, depositor)?,
+        Some(mint) => {
+            let destination = destination.ok_or(ErrorCode::EscrowMismatch)?;
+            require_keys_eq!(destination.owner, depositor.key(), ErrorCode::EscrowMismatch);
+            require_keys_eq!(destination.mint, mint, ErrorCode::EscrowMismatch);
+            let held = if vault.owner == &token::ID {
+                TokenAccount::try_deserialize(&mut &vault.try_borrow_data()?[..])?.amount
+            } else {
+                // Vault was never funded
+                0
+            };
+            if held > 0 {
+                release_leg(
+                    authority,
+                    signer_seeds,
+                    vault,
+                    &destination.to_account_info(),
+                    Some(mint),
+                    held,
+                    token_program,
+                )?;
+            }
+            held
+        }
+    };
+    require!(refunded > 0, ErrorCode::NothingToWithdraw);
+    Ok(refunded)
+}
+
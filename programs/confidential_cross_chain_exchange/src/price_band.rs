@@ -0,0 +1,256 @@
+//! Price feeds and price bands.
+//!
+//! A publisher keeps a program-owned price feed current, a local stand-in
+//! for a Pyth price account. A maker can bind an offer to a feed with a
+//! band, and the offer then only fills close enough to the feed's price.
+
+use anchor_lang::prelude::*;
+
+use crate::versioning::VersionedAccount;
+use crate::{
+    BPS_DENOMINATOR,
+    ErrorCode,
+    EventHeader,
+    InterchainOffer,
+    IntraChainOffer,
+    OfferAccount,
+    OfferKind,
+    OfferStatus,
+};
+
+/// Largest power of ten a price feed may scale its price by
+const MAX_PRICE_EXPONENT: u32 = 18;
+
+/// Program-owned price feed, a local stand-in for a Pyth price account.
+/// Token B per token A, in base units, is `price * 10^expo`.
+#[account]
+#[derive(InitSpace)]
+pub struct PriceFeed {
+    pub layout_version: u8,
+    pub publisher: Pubkey,
+    pub feed_id: u64,
+    pub price: i64,
+    pub expo: i32,
+    pub publish_time: i64,
+    pub bump: u8,
+}
+
+/// How far from a price feed an offer may still fill
+#[account]
+#[derive(InitSpace)]
+pub struct PriceBand {
+    pub layout_version: u8,
+    pub offer: Pubkey,
+    pub offer_kind: OfferKind,
+    pub maker: Pubkey,
+    pub price_feed: Pubkey,
+    pub max_deviation_bps: u16,
+    pub max_staleness_secs: i64,
+    pub bump: u8,
+}
+
+impl PriceBand {
+    /// Rejects a fill of `token_a_amount` for `token_b_amount` when `feed`
+    /// is stale or the fill's price strays from it by more than the band
+    fn check(&self, feed: &PriceFeed, token_a_amount: u64, token_b_amount: u64) -> Result<()> {
+        let age = Clock::get()?.unix_timestamp.saturating_sub(feed.publish_time);
+        require!(age <= self.max_staleness_secs, ErrorCode::StalePrice);
+
+        // Compare token_b_amount / token_a_amount with price * 10^expo, both
+        // scaled by token_a_amount * 10^-expo to stay in integers
+        let price = u128::try_from(feed.price).map_err(|_| ErrorCode::InvalidPrice)?;
+        let scale = 10u128
+            .checked_pow(feed.expo.unsigned_abs())
+            .ok_or(ErrorCode::MathOverflow)?;
+        let (fill, market) = if feed.expo < 0 {
+            (
+                u128::from(token_b_amount).checked_mul(scale),
+                price.checked_mul(u128::from(token_a_amount)),
+            )
+        } else {
+            (
+                Some(u128::from(token_b_amount)),
+                price
+                    .checked_mul(u128::from(token_a_amount))
+                    .and_then(|market| market.checked_mul(scale)),
+            )
+        };
+        let (fill, market) = fill.zip(market).ok_or(ErrorCode::MathOverflow)?;
+        let deviation = fill
+            .abs_diff(market)
+            .checked_mul(u128::from(BPS_DENOMINATOR))
+            .ok_or(ErrorCode::MathOverflow)?;
+        let allowed = market
+            .checked_mul(u128::from(self.max_deviation_bps))
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(deviation <= allowed, ErrorCode::PriceOutsideBand);
+        Ok(())
+    }
+}
+
+/// Checks a fill against the offer's price band, if `price_band` holds one
+pub(crate) fn enforce_price_band(
+    price_band: &AccountInfo,
+    price_feed: Option<&Account<PriceFeed>>,
+    token_a_amount: u64,
+    token_b_amount: u64,
+) -> Result<()> {
+    if price_band.data_is_empty() {
+        return Ok(());
+    }
+    let band = PriceBand::try_deserialize(&mut &price_band.data.borrow()[..])?;
+    let feed = price_feed.ok_or(ErrorCode::PriceFeedMismatch)?;
+    require_keys_eq!(feed.key(), band.price_feed, ErrorCode::PriceFeedMismatch);
+    band.check(feed, token_a_amount, token_b_amount)
+}
+
+// === INSTRUCTIONS ===
+
+pub(crate) fn publish_price(
+    ctx: Context<PublishPrice>,
+    feed_id: u64,
+    price: i64,
+    expo: i32,
+) -> Result<()> {
+    require!(
+        price > 0 && expo.unsigned_abs() <= MAX_PRICE_EXPONENT,
+        ErrorCode::InvalidPrice
+    );
+
+    let feed = &mut ctx.accounts.price_feed;
+    feed.layout_version = PriceFeed::LAYOUT_VERSION;
+    feed.publisher = ctx.accounts.publisher.key();
+    feed.feed_id = feed_id;
+    feed.price = price;
+    feed.expo = expo;
+    feed.publish_time = Clock::get()?.unix_timestamp;
+    feed.bump = ctx.bumps.price_feed;
+
+    msg!("📈 Feed {} priced at {}e{}", feed_id, price, expo);
+    emit!(PricePublishedEvent {
+        header: EventHeader::standalone(0)?,
+        price_feed: feed.key(),
+        publisher: feed.publisher,
+        feed_id,
+        price,
+        expo,
+    });
+    Ok(())
+}
+
+pub(crate) fn set_price_band(
+    ctx: Context<SetPriceBand>,
+    offer: Pubkey,
+    max_deviation_bps: u16,
+    max_staleness_secs: i64,
+) -> Result<()> {
+    require!(max_staleness_secs > 0, ErrorCode::InvalidPriceBand);
+
+    let maker = ctx.accounts.maker.key();
+    let (offer_kind, offer_maker, header) =
+        match (&ctx.accounts.intrachain_offer, &ctx.accounts.interchain_offer) {
+            (Some(guarded), None) => {
+                require!(
+                    matches!(guarded.status, OfferStatus::Open | OfferStatus::Pending),
+                    ErrorCode::OfferNotOpen
+                );
+                let header = guarded.event_header(offer, Some(guarded.status))?;
+                (OfferKind::Intrachain, guarded.maker, header)
+            }
+            (None, Some(guarded)) => {
+                require!(
+                    matches!(guarded.status, OfferStatus::Open | OfferStatus::Pending),
+                    ErrorCode::OfferNotOpen
+                );
+                let header = guarded.event_header(offer, Some(guarded.status))?;
+                (OfferKind::Interchain, guarded.maker, header)
+            }
+            _ => return err!(ErrorCode::PriceBandUnsupported),
+        };
+    require_keys_eq!(offer_maker, maker, ErrorCode::Unauthorized);
+
+    let band = &mut ctx.accounts.price_band;
+    band.layout_version = PriceBand::LAYOUT_VERSION;
+    band.offer = offer;
+    band.offer_kind = offer_kind;
+    band.maker = maker;
+    band.price_feed = ctx.accounts.price_feed.key();
+    band.max_deviation_bps = max_deviation_bps;
+    band.max_staleness_secs = max_staleness_secs;
+    band.bump = ctx.bumps.price_band;
+
+    msg!("📏 Offer {} fills within {} bps of its feed", offer, max_deviation_bps);
+    emit!(PriceBandSetEvent {
+        header,
+        price_feed: band.price_feed,
+        max_deviation_bps,
+        max_staleness_secs,
+    });
+    Ok(())
+}
+
+// === ACCOUNT CONTEXTS ===
+
+#[derive(Accounts)]
+#[instruction(feed_id: u64)]
+pub struct PublishPrice<'info> {
+    #[account(mut)]
+    pub publisher: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = publisher,
+        space = PriceFeed::SPACE,
+        seeds = [b"PriceFeed", publisher.key().as_ref(), &feed_id.to_le_bytes()],
+        bump,
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer: Pubkey)]
+pub struct SetPriceBand<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// The guarded offer, exactly one of these
+    #[account(address = offer @ ErrorCode::PriceBandUnsupported)]
+    pub intrachain_offer: Option<Box<Account<'info, IntraChainOffer>>>,
+    #[account(address = offer @ ErrorCode::PriceBandUnsupported)]
+    pub interchain_offer: Option<Box<Account<'info, InterchainOffer>>>,
+
+    pub price_feed: Account<'info, PriceFeed>,
+
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = PriceBand::SPACE,
+        seeds = [b"PriceBand", offer.as_ref()],
+        bump,
+    )]
+    pub price_band: Account<'info, PriceBand>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// === EVENTS ===
+
+#[event]
+pub struct PricePublishedEvent {
+    pub header: EventHeader,
+    pub price_feed: Pubkey,
+    pub publisher: Pubkey,
+    pub feed_id: u64,
+    pub price: i64,
+    pub expo: i32,
+}
+
+#[event]
+pub struct PriceBandSetEvent {
+    pub header: EventHeader,
+    pub price_feed: Pubkey,
+    pub max_deviation_bps: u16,
+    pub max_staleness_secs: i64,
+}
@@ -0,0 +1,580 @@
+//! Price triggers.
+//!
+//! A maker can hold an offer back until a price feed crosses a trigger
+//! price. The trigger is stored encrypted to the MXE and only compared with
+//! the feed in MPC, so nobody learns it before the offer wakes up.
+
+use anchor_lang::prelude::*;
+use arcium_anchor::prelude::*;
+use arcium_client::idl::arcium::types::CallbackAccount;
+
+use crate::versioning::VersionedAccount;
+use crate::{
+    COMP_DEF_OFFSET_CHECK_PRICE_TRIGGER,
+    COMP_DEF_OFFSET_STORE_PRICE_TRIGGER,
+    ComputationOperation,
+    ErrorCode,
+    EventHeader,
+    ID,
+    ID_CONST,
+    InterchainOffer,
+    IntraChainOffer,
+    OfferAccount,
+    OfferKind,
+    OfferStatus,
+    PendingComputation,
+    PriceFeed,
+    SignerAccount,
+    abort_offer_computation,
+    leave_pending,
+};
+
+/// Where `PriceTrigger::trigger` starts, after the discriminator and the
+/// layout version; `check_price_trigger` reads it from there
+const PRICE_TRIGGER_OFFSET: u32 = 8 + 1;
+
+/// Trigger price holding back a conditional offer. The trigger is stored
+/// encrypted to the MXE and only ever compared with a feed's price in MPC.
+#[account]
+#[derive(InitSpace)]
+pub struct PriceTrigger {
+    pub layout_version: u8,
+    /// Threshold and side encrypted to the MXE; must stay right after
+    /// `layout_version`, `check_price_trigger` reads it at
+    /// `PRICE_TRIGGER_OFFSET`
+    pub trigger: [[u8; 32]; 2],
+    pub trigger_nonce: u128,
+    pub offer: Pubkey,
+    pub offer_kind: OfferKind,
+    pub offer_id: u64,
+    pub maker: Pubkey,
+    pub price_feed: Pubkey,
+    /// The feed's exponent when the trigger was set, which the threshold
+    /// is expressed in
+    pub expo: i32,
+    /// Latest feed price submitted for a check
+    pub checked_price: i64,
+    pub checked_publish_time: i64,
+    /// `Pending` while a computation runs, `Open` while armed, `Settled`
+    /// once fired, `VerificationFailed` if storing the trigger aborted
+    pub status: OfferStatus,
+    /// Offset of the latest MPC computation queued for this trigger
+    pub computation_offset: u64,
+    pub bump: u8,
+}
+
+impl OfferAccount for PriceTrigger {
+    const KIND: OfferKind = OfferKind::PriceTrigger;
+
+    fn offer_id(&self) -> u64 {
+        self.offer_id
+    }
+    fn offer_maker(&self) -> Pubkey {
+        self.maker
+    }
+    fn offer_status(&self) -> OfferStatus {
+        self.status
+    }
+    fn offer_computation_offset(&self) -> u64 {
+        self.computation_offset
+    }
+    fn abort_computation(&mut self, operation: ComputationOperation) -> Result<OfferStatus> {
+        match operation {
+            // The offer stays dormant until the maker sets the trigger again
+            ComputationOperation::StorePriceTrigger => {
+                leave_pending(&mut self.status, OfferStatus::VerificationFailed)
+            }
+            // An unchecked price leaves the trigger armed
+            _ => leave_pending(&mut self.status, OfferStatus::Open),
+        }
+    }
+}
+
+/// Holds back a fill while `price_trigger` holds a trigger that has not
+/// fired
+pub(crate) fn enforce_price_trigger(price_trigger: &AccountInfo) -> Result<()> {
+    if price_trigger.data_is_empty() {
+        return Ok(());
+    }
+    let trigger = PriceTrigger::try_deserialize(&mut &price_trigger.data.borrow()[..])?;
+    require!(trigger.status == OfferStatus::Settled, ErrorCode::OfferDormant);
+    Ok(())
+}
+
+// === INSTRUCTIONS ===
+
+pub(crate) fn set_price_trigger(
+    ctx: Context<SetPriceTrigger>,
+    offer: Pubkey,
+    // Confidential trigger
+    ciphertext_trigger: [[u8; 32]; 2],
+    // Arcium handshake
+    pub_key: [u8; 32],
+    nonce: u128,
+    mxe_nonce: u128,
+    computation_offset: u64,
+) -> Result<()> {
+    let maker = ctx.accounts.payer.key();
+    let (offer_kind, offer_id, offer_maker, status) =
+        match (&ctx.accounts.intrachain_offer, &ctx.accounts.interchain_offer) {
+            (Some(held), None) => (OfferKind::Intrachain, held.id, held.maker, held.status),
+            (None, Some(held)) => (OfferKind::Interchain, held.id, held.maker, held.status),
+            _ => return err!(ErrorCode::PriceTriggerUnsupported),
+        };
+    require!(
+        matches!(status, OfferStatus::Open | OfferStatus::Pending),
+        ErrorCode::OfferNotOpen
+    );
+    require_keys_eq!(offer_maker, maker, ErrorCode::Unauthorized);
+    // A new trigger is still zeroed; a stored or failed one may be replaced
+    let trigger = &ctx.accounts.price_trigger;
+    require!(
+        trigger.layout_version == 0
+            || matches!(trigger.status, OfferStatus::Open | OfferStatus::VerificationFailed),
+        ErrorCode::PriceTriggerLocked
+    );
+
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+    ctx.accounts.pending_computation.record(
+        computation_offset,
+        OfferKind::PriceTrigger,
+        offer_id,
+        trigger.key(),
+        ComputationOperation::StorePriceTrigger,
+        maker,
+        ctx.bumps.pending_computation,
+    )?;
+
+    let expo = ctx.accounts.price_feed.expo;
+    let trigger = &mut ctx.accounts.price_trigger;
+    trigger.layout_version = PriceTrigger::LAYOUT_VERSION;
+    trigger.offer = offer;
+    trigger.offer_kind = offer_kind;
+    trigger.offer_id = offer_id;
+    trigger.maker = maker;
+    trigger.price_feed = ctx.accounts.price_feed.key();
+    trigger.expo = expo;
+    trigger.checked_price = 0;
+    trigger.checked_publish_time = 0;
+    trigger.status = OfferStatus::Pending;
+    trigger.computation_offset = computation_offset;
+    trigger.bump = ctx.bumps.price_trigger;
+
+    let args = vec![
+        Argument::ArcisPubkey(pub_key),
+        Argument::PlaintextU128(nonce),
+        Argument::EncryptedU64(ciphertext_trigger[0]),
+        Argument::EncryptedBool(ciphertext_trigger[1]),
+        Argument::PlaintextU128(mxe_nonce),
+    ];
+    // The callback writes the encrypted trigger back to its account
+    let callback_accounts = [
+        CallbackAccount {
+            pubkey: ctx.accounts.price_trigger.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.pending_computation.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.payer.key(),
+            is_writable: true,
+        },
+    ];
+    queue_computation(
+        ctx.accounts,
+        computation_offset,
+        args,
+        None,
+        vec![StorePriceTriggerCallback::callback_ix(&callback_accounts)],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn check_price_trigger(
+    ctx: Context<CheckPriceTrigger>,
+    computation_offset: u64,
+) -> Result<()> {
+    let trigger = &ctx.accounts.price_trigger;
+    let feed = &ctx.accounts.price_feed;
+    require!(trigger.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    require!(feed.expo == trigger.expo, ErrorCode::TriggerExponentChanged);
+    require!(
+        feed.publish_time > trigger.checked_publish_time,
+        ErrorCode::PriceAlreadyChecked
+    );
+    let price = u64::try_from(feed.price).map_err(|_| ErrorCode::InvalidPrice)?;
+
+    let cranker = ctx.accounts.payer.key();
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+    ctx.accounts.pending_computation.record(
+        computation_offset,
+        OfferKind::PriceTrigger,
+        trigger.offer_id,
+        trigger.key(),
+        ComputationOperation::CheckPriceTrigger,
+        cranker,
+        ctx.bumps.pending_computation,
+    )?;
+
+    let (feed_price, publish_time) = (feed.price, feed.publish_time);
+    let trigger = &mut ctx.accounts.price_trigger;
+    trigger.checked_price = feed_price;
+    trigger.checked_publish_time = publish_time;
+    trigger.status = OfferStatus::Pending;
+    trigger.computation_offset = computation_offset;
+
+    let args = vec![
+        Argument::PlaintextU128(trigger.trigger_nonce),
+        Argument::Account(trigger.key(), PRICE_TRIGGER_OFFSET, 32 * 2),
+        Argument::PlaintextU64(price),
+    ];
+    let callback_accounts = [
+        CallbackAccount {
+            pubkey: ctx.accounts.price_trigger.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.pending_computation.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.payer.key(),
+            is_writable: true,
+        },
+    ];
+    queue_computation(
+        ctx.accounts,
+        computation_offset,
+        args,
+        None,
+        vec![CheckPriceTriggerCallback::callback_ix(&callback_accounts)],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn store_price_trigger_callback(
+    ctx: Context<StorePriceTriggerCallback>,
+    output: ComputationOutputs<StorePriceTriggerOutput>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.price_trigger.status == OfferStatus::Pending,
+        ErrorCode::OfferNotPending
+    );
+    let o = match output {
+        ComputationOutputs::Success(StorePriceTriggerOutput { field_0 }) => field_0,
+        _ => {
+            let operation = ComputationOperation::StorePriceTrigger;
+            return abort_offer_computation(&mut ctx.accounts.price_trigger, operation);
+        }
+    };
+
+    let trigger = &mut ctx.accounts.price_trigger;
+    trigger.trigger = o.ciphertexts;
+    trigger.trigger_nonce = o.nonce;
+    trigger.status = OfferStatus::Open;
+    emit!(PriceTriggerArmedEvent {
+        header: trigger.event_header(trigger.key(), Some(OfferStatus::Pending))?,
+        price_feed: trigger.price_feed,
+        expo: trigger.expo,
+    });
+    Ok(())
+}
+
+pub(crate) fn check_price_trigger_callback(
+    ctx: Context<CheckPriceTriggerCallback>,
+    output: ComputationOutputs<CheckPriceTriggerOutput>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.price_trigger.status == OfferStatus::Pending,
+        ErrorCode::OfferNotPending
+    );
+    let crossed = match output {
+        ComputationOutputs::Success(CheckPriceTriggerOutput { field_0 }) => field_0,
+        _ => {
+            let operation = ComputationOperation::CheckPriceTrigger;
+            return abort_offer_computation(&mut ctx.accounts.price_trigger, operation);
+        }
+    };
+
+    let trigger = &mut ctx.accounts.price_trigger;
+    trigger.status = if crossed { OfferStatus::Settled } else { OfferStatus::Open };
+    if crossed {
+        msg!("🎯 Offer {} activated at price {}", trigger.offer_id, trigger.checked_price);
+    }
+    emit!(PriceTriggerCheckedEvent {
+        header: trigger.event_header(trigger.key(), Some(OfferStatus::Pending))?,
+        price: trigger.checked_price,
+        publish_time: trigger.checked_publish_time,
+        fired: crossed,
+    });
+    Ok(())
+}
+
+// === ACCOUNT CONTEXTS ===
+
+#[queue_computation_accounts("store_price_trigger", payer)]
+#[derive(Accounts)]
+#[instruction(offer: Pubkey, ciphertext_trigger: [[u8; 32]; 2], pub_key: [u8; 32], nonce: u128, mxe_nonce: u128, computation_offset: u64)]
+pub struct SetPriceTrigger<'info> {
+    /// The offer's maker
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The held back offer, exactly one of these
+    #[account(address = offer @ ErrorCode::PriceTriggerUnsupported)]
+    pub intrachain_offer: Option<Box<Account<'info, IntraChainOffer>>>,
+    #[account(address = offer @ ErrorCode::PriceTriggerUnsupported)]
+    pub interchain_offer: Option<Box<Account<'info, InterchainOffer>>>,
+    pub price_feed: Box<Account<'info, PriceFeed>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = PriceTrigger::SPACE,
+        seeds = [b"PriceTrigger", offer.as_ref()],
+        bump,
+    )]
+    pub price_trigger: Box<Account<'info, PriceTrigger>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_STORE_PRICE_TRIGGER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("check_price_trigger", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct CheckPriceTrigger<'info> {
+    /// Anyone may crank a trigger
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"PriceTrigger", price_trigger.offer.as_ref()],
+        bump = price_trigger.bump,
+    )]
+    pub price_trigger: Box<Account<'info, PriceTrigger>>,
+    #[account(address = price_trigger.price_feed @ ErrorCode::PriceFeedMismatch)]
+    pub price_feed: Box<Account<'info, PriceFeed>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CHECK_PRICE_TRIGGER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("store_price_trigger")]
+#[derive(Accounts)]
+pub struct StorePriceTriggerCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_STORE_PRICE_TRIGGER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub price_trigger: Box<Account<'info, PriceTrigger>>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::StorePriceTrigger @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == price_trigger.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[callback_accounts("check_price_trigger")]
+#[derive(Accounts)]
+pub struct CheckPriceTriggerCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CHECK_PRICE_TRIGGER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub price_trigger: Box<Account<'info, PriceTrigger>>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::CheckPriceTrigger @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == price_trigger.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[init_computation_definition_accounts("store_price_trigger", payer)]
+#[derive(Accounts)]
+pub struct InitStorePriceTriggerCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("check_price_trigger", payer)]
+#[derive(Accounts)]
+pub struct InitCheckPriceTriggerCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+// === EVENTS ===
+
+#[event]
+pub struct PriceTriggerArmedEvent {
+    pub header: EventHeader,
+    pub price_feed: Pubkey,
+    pub expo: i32,
+}
+
+#[event]
+pub struct PriceTriggerCheckedEvent {
+    pub header: EventHeader,
+    pub price: i64,
+    pub publish_time: i64,
+    pub fired: bool,
+}
@@ -0,0 +1,970 @@
+//! Requests for quote.
+//!
+//! A taker asks makers to quote a price for a size of the base asset. MPC
+//! ranks each quote against the best one so far, so only the winning price
+//! is ever revealed. The winner and the taker then escrow their legs in the
+//! RFQ's `RfqVault` PDAs and swap them.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer as SplTransfer};
+use arcium_anchor::prelude::*;
+use arcium_client::idl::arcium::types::CallbackAccount;
+
+use crate::versioning::VersionedAccount;
+use crate::{
+    BasketLeg,
+    COMP_DEF_OFFSET_RANK_RFQ_QUOTE,
+    COMP_DEF_OFFSET_SELECT_RFQ_QUOTE,
+    ComputationOperation,
+    ErrorCode,
+    EventHeader,
+    ID,
+    ID_CONST,
+    OfferAccount,
+    OfferKind,
+    OfferStatus,
+    PendingComputation,
+    SignerAccount,
+    VaultDepositedEvent,
+    VaultSide,
+    abort_offer_computation,
+    leave_pending,
+    leg_releases,
+    refund_leg,
+    release_leg,
+    vault_lamports,
+};
+
+/// Most quotes an RFQ takes
+pub const MAX_RFQ_QUOTES: usize = 8;
+
+/// Where `RfqRequest::best_quote` starts, after the discriminator and the
+/// layout version; the RFQ circuits read it from there
+const RFQ_BEST_QUOTE_OFFSET: u32 = 8 + 1;
+
+/// Request for quote: makers quote a price for `size` of the base asset,
+/// ranked in MPC so only the winning price is ever revealed. The winner
+/// escrows the base asset and the taker the quote asset, each in the
+/// `RfqVault` PDA of the RFQ and the side, until they are swapped.
+#[account]
+#[derive(InitSpace)]
+pub struct RfqRequest {
+    pub layout_version: u8,
+    /// Best quote so far (price, maker identity hash, maker index),
+    /// encrypted to the MXE; must stay right after `layout_version`, the
+    /// circuits read it at `RFQ_BEST_QUOTE_OFFSET`
+    pub best_quote: [[u8; 32]; 3],
+    pub best_quote_nonce: u128,
+    pub id: u64,
+    pub taker: Pubkey,
+    /// Asset the taker receives, `None` for SOL
+    pub base_mint: Option<Pubkey>,
+    /// Asset the taker pays in, `None` for SOL
+    pub quote_mint: Option<Pubkey>,
+    pub size: u64,
+    /// Last timestamp quotes are taken at
+    pub quote_deadline: i64,
+    /// Last timestamp the awarded RFQ can settle at
+    pub settle_deadline: i64,
+    /// Makers in the order their quotes were ranked
+    #[max_len(MAX_RFQ_QUOTES)]
+    pub makers: Vec<Pubkey>,
+    pub winner: Option<Pubkey>,
+    /// Winning price in the quote asset, 0 until awarded
+    pub price: u64,
+    pub status: OfferStatus,
+    /// Offset of the latest MPC computation queued for this RFQ
+    pub computation_offset: u64,
+    pub bump: u8,
+}
+
+impl RfqRequest {
+    /// The winner escrows the base leg and the taker the quote leg
+    pub fn leg(&self, side: VaultSide) -> BasketLeg {
+        match side {
+            VaultSide::Seller => BasketLeg {
+                mint: self.base_mint,
+                amount: self.size,
+            },
+            VaultSide::Buyer => BasketLeg {
+                mint: self.quote_mint,
+                amount: self.price,
+            },
+        }
+    }
+
+    pub fn depositor(&self, side: VaultSide) -> Option<Pubkey> {
+        match side {
+            VaultSide::Seller => self.winner,
+            VaultSide::Buyer => Some(self.taker),
+        }
+    }
+
+    /// Leg `depositor` may escrow on `side` once the RFQ is awarded
+    fn depositable_leg(&self, side: VaultSide, depositor: &Pubkey) -> Result<BasketLeg> {
+        require!(self.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+        require!(self.winner.is_some(), ErrorCode::RfqNotAwarded);
+        require!(Clock::get()?.unix_timestamp <= self.settle_deadline, ErrorCode::OfferExpired);
+        require!(self.depositor(side) == Some(*depositor), ErrorCode::LegMismatch);
+        Ok(self.leg(side))
+    }
+}
+
+impl OfferAccount for RfqRequest {
+    const KIND: OfferKind = OfferKind::Rfq;
+
+    fn offer_id(&self) -> u64 {
+        self.id
+    }
+    fn offer_maker(&self) -> Pubkey {
+        self.taker
+    }
+    fn offer_status(&self) -> OfferStatus {
+        self.status
+    }
+    fn offer_computation_offset(&self) -> u64 {
+        self.computation_offset
+    }
+    fn abort_computation(&mut self, operation: ComputationOperation) -> Result<OfferStatus> {
+        let status_before = leave_pending(&mut self.status, OfferStatus::Open)?;
+        // An unranked quote is dropped, for its maker to quote again; an
+        // unmade selection can be requested again
+        if operation == ComputationOperation::RankRfqQuote {
+            self.makers.pop();
+        }
+        Ok(status_before)
+    }
+}
+
+pub fn rfq_vault_address(rfq: &Pubkey, side: VaultSide) -> Pubkey {
+    Pubkey::find_program_address(&[b"RfqVault", rfq.as_ref(), &[side as u8]], &crate::ID).0
+}
+
+// === INSTRUCTIONS ===
+
+pub(crate) fn create_rfq(
+    ctx: Context<CreateRfq>,
+    id: u64,
+    base_mint: Option<Pubkey>,
+    quote_mint: Option<Pubkey>,
+    size: u64,
+    quote_deadline: i64,
+    settle_deadline: i64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        size > 0 && base_mint != quote_mint && now < quote_deadline && quote_deadline < settle_deadline,
+        ErrorCode::InvalidRfq
+    );
+
+    let rfq = &mut ctx.accounts.rfq;
+    rfq.best_quote = [[0; 32]; 3];
+    rfq.best_quote_nonce = 0;
+    rfq.id = id;
+    rfq.taker = ctx.accounts.taker.key();
+    rfq.base_mint = base_mint;
+    rfq.quote_mint = quote_mint;
+    rfq.size = size;
+    rfq.quote_deadline = quote_deadline;
+    rfq.settle_deadline = settle_deadline;
+    rfq.makers = Vec::new();
+    rfq.winner = None;
+    rfq.price = 0;
+    rfq.status = OfferStatus::Open;
+    rfq.computation_offset = 0;
+    rfq.layout_version = RfqRequest::LAYOUT_VERSION;
+    rfq.bump = ctx.bumps.rfq;
+
+    emit!(RfqCreatedEvent {
+        header: rfq.event_header(rfq.key(), None)?,
+        base_mint,
+        quote_mint,
+        size,
+        quote_deadline,
+        settle_deadline,
+    });
+    Ok(())
+}
+
+pub(crate) fn submit_rfq_quote(
+    ctx: Context<SubmitRfqQuote>,
+    rfq_id: u64,
+    // Confidential quote
+    ciphertext_price: [u8; 32],
+    ciphertext_maker_identity_hash: [u8; 32],
+    // Arcium handshake
+    pub_key: [u8; 32],
+    nonce: u128,
+    computation_offset: u64,
+) -> Result<()> {
+    let rfq = &ctx.accounts.rfq;
+    let maker = ctx.accounts.payer.key();
+    require!(rfq.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    require!(
+        Clock::get()?.unix_timestamp <= rfq.quote_deadline,
+        ErrorCode::QuoteWindowClosed
+    );
+    require!(maker != rfq.taker && !rfq.makers.contains(&maker), ErrorCode::AlreadyQuoted);
+    require!(rfq.makers.len() < MAX_RFQ_QUOTES, ErrorCode::TooManyQuotes);
+    let quote_index = rfq.makers.len() as u8;
+
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+    ctx.accounts.pending_computation.record(
+        computation_offset,
+        OfferKind::Rfq,
+        rfq_id,
+        ctx.accounts.rfq.key(),
+        ComputationOperation::RankRfqQuote,
+        maker,
+        ctx.bumps.pending_computation,
+    )?;
+
+    let rfq = &mut ctx.accounts.rfq;
+    rfq.makers.push(maker);
+    rfq.status = OfferStatus::Pending;
+    rfq.computation_offset = computation_offset;
+
+    let args = vec![
+        Argument::ArcisPubkey(pub_key),
+        Argument::PlaintextU128(nonce),
+        Argument::EncryptedU64(ciphertext_price),
+        Argument::EncryptedU64(ciphertext_maker_identity_hash),
+        Argument::PlaintextU128(rfq.best_quote_nonce),
+        Argument::Account(rfq.key(), RFQ_BEST_QUOTE_OFFSET, 32 * 3),
+        Argument::PlaintextU8(quote_index),
+    ];
+    let callback_accounts = [
+        CallbackAccount {
+            pubkey: ctx.accounts.rfq.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.pending_computation.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.payer.key(),
+            is_writable: true,
+        },
+    ];
+    queue_computation(
+        ctx.accounts,
+        computation_offset,
+        args,
+        None,
+        vec![RankRfqQuoteCallback::callback_ix(&callback_accounts)],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn select_rfq_quote(
+    ctx: Context<SelectRfqQuote>,
+    rfq_id: u64,
+    computation_offset: u64,
+) -> Result<()> {
+    let rfq = &ctx.accounts.rfq;
+    let now = Clock::get()?.unix_timestamp;
+    require!(rfq.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    require!(rfq.winner.is_none(), ErrorCode::RfqAlreadyAwarded);
+    require!(!rfq.makers.is_empty(), ErrorCode::NoQuotes);
+    require!(
+        now > rfq.quote_deadline || rfq.makers.len() == MAX_RFQ_QUOTES,
+        ErrorCode::QuoteWindowOpen
+    );
+    require!(now <= rfq.settle_deadline, ErrorCode::OfferExpired);
+
+    ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+    ctx.accounts.pending_computation.record(
+        computation_offset,
+        OfferKind::Rfq,
+        rfq_id,
+        ctx.accounts.rfq.key(),
+        ComputationOperation::SelectRfqQuote,
+        ctx.accounts.payer.key(),
+        ctx.bumps.pending_computation,
+    )?;
+
+    let rfq = &mut ctx.accounts.rfq;
+    rfq.status = OfferStatus::Pending;
+    rfq.computation_offset = computation_offset;
+
+    let args = vec![
+        Argument::PlaintextU128(rfq.best_quote_nonce),
+        Argument::Account(rfq.key(), RFQ_BEST_QUOTE_OFFSET, 32 * 3),
+    ];
+    let callback_accounts = [
+        CallbackAccount {
+            pubkey: ctx.accounts.rfq.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.pending_computation.key(),
+            is_writable: true,
+        },
+        CallbackAccount {
+            pubkey: ctx.accounts.payer.key(),
+            is_writable: true,
+        },
+    ];
+    queue_computation(
+        ctx.accounts,
+        computation_offset,
+        args,
+        None,
+        vec![SelectRfqQuoteCallback::callback_ix(&callback_accounts)],
+    )?;
+    Ok(())
+}
+
+pub(crate) fn cancel_rfq(ctx: Context<CancelRfq>, rfq_id: u64) -> Result<()> {
+    let rfq = &mut ctx.accounts.rfq;
+    require!(rfq.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    require!(rfq.winner.is_none(), ErrorCode::RfqAlreadyAwarded);
+
+    rfq.status = OfferStatus::Cancelled;
+    msg!("🚫 RFQ {} cancelled", rfq_id);
+    emit!(RfqCancelledEvent {
+        header: rfq.event_header(rfq.key(), Some(OfferStatus::Open))?,
+    });
+    Ok(())
+}
+
+pub(crate) fn deposit_rfq_leg_native(
+    ctx: Context<DepositRfqLegNative>,
+    rfq_id: u64,
+    side: VaultSide,
+) -> Result<()> {
+    let rfq = &ctx.accounts.rfq;
+    let leg = rfq.depositable_leg(side, &ctx.accounts.depositor.key())?;
+    require!(leg.mint.is_none(), ErrorCode::LegMismatch);
+
+    let vault = ctx.accounts.leg_vault.to_account_info();
+    let missing = leg.amount.saturating_sub(vault_lamports(&vault)?);
+    require!(missing > 0, ErrorCode::LegFunded);
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.depositor.to_account_info(),
+                to: vault,
+            },
+        ),
+        missing,
+    )?;
+
+    msg!("💰 RFQ {} {:?} leg funded with {} lamports", rfq_id, side, missing);
+    emit!(VaultDepositedEvent {
+        header: rfq.event_header(rfq.key(), Some(rfq.status))?,
+        vault: ctx.accounts.leg_vault.key(),
+        side,
+        amount: missing,
+    });
+    Ok(())
+}
+
+pub(crate) fn deposit_rfq_leg_spl(
+    ctx: Context<DepositRfqLegSpl>,
+    rfq_id: u64,
+    side: VaultSide,
+) -> Result<()> {
+    let rfq = &ctx.accounts.rfq;
+    let leg = rfq.depositable_leg(side, &ctx.accounts.depositor.key())?;
+    require!(leg.mint == Some(ctx.accounts.mint.key()), ErrorCode::LegMismatch);
+
+    let missing = leg.amount.saturating_sub(ctx.accounts.leg_vault.amount);
+    require!(missing > 0, ErrorCode::LegFunded);
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            SplTransfer {
+                from: ctx.accounts.depositor_token_account.to_account_info(),
+                to: ctx.accounts.leg_vault.to_account_info(),
+                authority: ctx.accounts.depositor.to_account_info(),
+            },
+        ),
+        missing,
+    )?;
+
+    msg!("💰 RFQ {} {:?} leg funded with {} tokens", rfq_id, side, missing);
+    emit!(VaultDepositedEvent {
+        header: rfq.event_header(rfq.key(), Some(rfq.status))?,
+        vault: ctx.accounts.leg_vault.key(),
+        side,
+        amount: missing,
+    });
+    Ok(())
+}
+
+pub(crate) fn settle_rfq<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SettleRfq<'info>>,
+    rfq_id: u64,
+) -> Result<()> {
+    let rfq = &ctx.accounts.rfq;
+    require!(rfq.status == OfferStatus::Open, ErrorCode::OfferNotOpen);
+    let winner = rfq.winner.ok_or(ErrorCode::RfqNotAwarded)?;
+    require!(Clock::get()?.unix_timestamp <= rfq.settle_deadline, ErrorCode::OfferExpired);
+    require!(ctx.remaining_accounts.len() == 4, ErrorCode::EscrowMismatch);
+    let (base_accounts, quote_accounts) = ctx.remaining_accounts.split_at(2);
+
+    let rfq_key = rfq.key();
+    let base = leg_releases(&[rfq.leg(VaultSide::Seller)], base_accounts, &rfq.taker, |_| {
+        rfq_vault_address(&rfq_key, VaultSide::Seller)
+    })?;
+    let quote = leg_releases(&[rfq.leg(VaultSide::Buyer)], quote_accounts, &winner, |_| {
+        rfq_vault_address(&rfq_key, VaultSide::Buyer)
+    })?;
+
+    msg!("🔄 Settling RFQ {}", rfq_id);
+    let id_bytes = rfq.id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[b"Rfq", rfq.taker.as_ref(), &id_bytes, &[rfq.bump]]];
+    for release in base.iter().chain(&quote) {
+        release_leg(
+            &rfq.to_account_info(),
+            signer_seeds,
+            release.vault,
+            release.destination,
+            release.leg.mint,
+            release.leg.amount,
+            Some(&ctx.accounts.token_program),
+        )?;
+    }
+
+    let rfq = &mut ctx.accounts.rfq;
+    rfq.status = OfferStatus::Settled;
+    emit!(RfqSettledEvent {
+        header: rfq.event_header(rfq.key(), Some(OfferStatus::Open))?,
+        taker: rfq.taker,
+        winner,
+        size: rfq.size,
+        price: rfq.price,
+    });
+    msg!("✅ RFQ settled successfully");
+    Ok(())
+}
+
+pub(crate) fn refund_rfq_leg(
+    ctx: Context<RefundRfqLeg>,
+    rfq_id: u64,
+    side: VaultSide,
+) -> Result<()> {
+    let rfq = &ctx.accounts.rfq;
+    let status_before = rfq.status;
+    let live = matches!(rfq.status, OfferStatus::Pending | OfferStatus::Open);
+    if live {
+        require!(
+            Clock::get()?.unix_timestamp > rfq.settle_deadline,
+            ErrorCode::DeadlineNotReached
+        );
+    }
+    let depositor = ctx.accounts.depositor.to_account_info();
+    require!(rfq.depositor(side) == Some(depositor.key()), ErrorCode::EscrowMismatch);
+
+    let vault = ctx.accounts.leg_vault.to_account_info();
+    let id_bytes = rfq.id.to_le_bytes();
+    let refunded = refund_leg(
+        &rfq.to_account_info(),
+        &[&[b"Rfq", rfq.taker.as_ref(), &id_bytes, &[rfq.bump]]],
+        &vault,
+        &depositor,
+        ctx.accounts.depositor_token_account.as_deref(),
+        rfq.leg(side).mint,
+        ctx.accounts.token_program.as_ref(),
+    )?;
+
+    let rfq = &mut ctx.accounts.rfq;
+    if live {
+        rfq.status = OfferStatus::Refunded;
+    }
+    msg!("↩️ RFQ {} {:?} leg refunded {}", rfq_id, side, refunded);
+    emit!(RfqLegRefundedEvent {
+        header: rfq.event_header(rfq.key(), Some(status_before))?,
+        depositor: depositor.key(),
+        side,
+        vault: vault.key(),
+        refunded,
+    });
+    Ok(())
+}
+
+pub(crate) fn rank_rfq_quote_callback(
+    ctx: Context<RankRfqQuoteCallback>,
+    output: ComputationOutputs<RankRfqQuoteOutput>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.rfq.status == OfferStatus::Pending,
+        ErrorCode::OfferNotPending
+    );
+    let o = match output {
+        ComputationOutputs::Success(RankRfqQuoteOutput { field_0 }) => field_0,
+        _ => {
+            let operation = ComputationOperation::RankRfqQuote;
+            return abort_offer_computation(&mut ctx.accounts.rfq, operation);
+        }
+    };
+
+    // Only the MXE can tell which quote is ahead
+    let rfq = &mut ctx.accounts.rfq;
+    rfq.best_quote = o.ciphertexts;
+    rfq.best_quote_nonce = o.nonce;
+    rfq.status = OfferStatus::Open;
+    emit!(RfqQuoteRankedEvent {
+        header: rfq.event_header(rfq.key(), Some(OfferStatus::Pending))?,
+        maker: ctx.accounts.pending_computation.requester,
+        quote_index: (rfq.makers.len() - 1) as u8,
+    });
+    Ok(())
+}
+
+pub(crate) fn select_rfq_quote_callback(
+    ctx: Context<SelectRfqQuoteCallback>,
+    output: ComputationOutputs<SelectRfqQuoteOutput>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.rfq.status == OfferStatus::Pending,
+        ErrorCode::OfferNotPending
+    );
+    let (price, quote_index) = match output {
+        ComputationOutputs::Success(SelectRfqQuoteOutput {
+            field_0: SelectRfqQuoteOutputStruct0 {
+                field_0: price,
+                field_1: quote_index,
+            },
+        }) => (price, quote_index),
+        _ => {
+            let operation = ComputationOperation::SelectRfqQuote;
+            return abort_offer_computation(&mut ctx.accounts.rfq, operation);
+        }
+    };
+
+    let rfq = &mut ctx.accounts.rfq;
+    let winner = *rfq
+        .makers
+        .get(usize::from(quote_index))
+        .ok_or(ErrorCode::PendingComputationMismatch)?;
+    rfq.winner = Some(winner);
+    rfq.price = price;
+    rfq.status = OfferStatus::Open;
+    emit!(RfqAwardedEvent {
+        header: rfq.event_header(rfq.key(), Some(OfferStatus::Pending))?,
+        winner,
+        price,
+    });
+    Ok(())
+}
+
+// === ACCOUNT CONTEXTS ===
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CreateRfq<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(
+        init,
+        payer = taker,
+        space = RfqRequest::SPACE,
+        seeds = [b"Rfq", taker.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub rfq: Box<Account<'info, RfqRequest>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[queue_computation_accounts("rank_rfq_quote", payer)]
+#[derive(Accounts)]
+#[instruction(rfq_id: u64, ciphertext_price: [u8; 32], ciphertext_maker_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct SubmitRfqQuote<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"Rfq", rfq.taker.as_ref(), &rfq_id.to_le_bytes()],
+        bump = rfq.bump,
+    )]
+    pub rfq: Box<Account<'info, RfqRequest>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_RANK_RFQ_QUOTE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("select_rfq_quote", payer)]
+#[derive(Accounts)]
+#[instruction(rfq_id: u64, computation_offset: u64)]
+pub struct SelectRfqQuote<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"Rfq", rfq.taker.as_ref(), &rfq_id.to_le_bytes()],
+        bump = rfq.bump,
+    )]
+    pub rfq: Box<Account<'info, RfqRequest>>,
+    #[account(
+        init,
+        payer = payer,
+        space = PendingComputation::SPACE,
+        seeds = [b"PendingComputation".as_ref(), &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub pending_computation: Box<Account<'info, PendingComputation>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_SELECT_RFQ_QUOTE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[derive(Accounts)]
+#[instruction(rfq_id: u64)]
+pub struct CancelRfq<'info> {
+    pub taker: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"Rfq", taker.key().as_ref(), &rfq_id.to_le_bytes()],
+        bump = rfq.bump,
+        has_one = taker,
+    )]
+    pub rfq: Box<Account<'info, RfqRequest>>,
+}
+
+#[derive(Accounts)]
+#[instruction(rfq_id: u64, side: VaultSide)]
+pub struct DepositRfqLegNative<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        seeds = [b"Rfq", rfq.taker.as_ref(), &rfq_id.to_le_bytes()],
+        bump = rfq.bump,
+    )]
+    pub rfq: Box<Account<'info, RfqRequest>>,
+
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8,
+        seeds = [b"RfqVault", rfq.key().as_ref(), &[side as u8]],
+        bump,
+    )]
+    /// CHECK: Escrow vault PDA holding the leg's lamports
+    pub leg_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(rfq_id: u64, side: VaultSide)]
+pub struct DepositRfqLegSpl<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        seeds = [b"Rfq", rfq.taker.as_ref(), &rfq_id.to_le_bytes()],
+        bump = rfq.bump,
+    )]
+    pub rfq: Box<Account<'info, RfqRequest>>,
+
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = depositor,
+    )]
+    pub depositor_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [b"RfqVault", rfq.key().as_ref(), &[side as u8]],
+        bump,
+        token::mint = mint,
+        token::authority = rfq,
+    )]
+    pub leg_vault: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(rfq_id: u64)]
+pub struct SettleRfq<'info> {
+    #[account(
+        mut,
+        seeds = [b"Rfq", rfq.taker.as_ref(), &rfq_id.to_le_bytes()],
+        bump = rfq.bump,
+    )]
+    pub rfq: Box<Account<'info, RfqRequest>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(rfq_id: u64, side: VaultSide)]
+pub struct RefundRfqLeg<'info> {
+    #[account(mut)]
+    /// CHECK: refund destination, checked against the side's depositor
+    pub depositor: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"Rfq", rfq.taker.as_ref(), &rfq_id.to_le_bytes()],
+        bump = rfq.bump,
+    )]
+    pub rfq: Box<Account<'info, RfqRequest>>,
+
+    #[account(
+        mut,
+        seeds = [b"RfqVault", rfq.key().as_ref(), &[side as u8]],
+        bump,
+    )]
+    /// CHECK: the leg's escrow vault, holding lamports or, for SPL legs, a token account
+    pub leg_vault: UncheckedAccount<'info>,
+
+    /// Receives an SPL leg
+    #[account(mut)]
+    pub depositor_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+#[callback_accounts("rank_rfq_quote")]
+#[derive(Accounts)]
+pub struct RankRfqQuoteCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_RANK_RFQ_QUOTE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub rfq: Box<Account<'info, RfqRequest>>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::RankRfqQuote @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == rfq.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[callback_accounts("select_rfq_quote")]
+#[derive(Accounts)]
+pub struct SelectRfqQuoteCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_SELECT_RFQ_QUOTE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub rfq: Box<Account<'info, RfqRequest>>,
+    /// Consumed here, so a computation's callback only ever runs once
+    #[account(
+        mut,
+        close = requester,
+        has_one = requester,
+        constraint = pending_computation.operation == ComputationOperation::SelectRfqQuote @ ErrorCode::PendingComputationMismatch,
+        constraint = pending_computation.offer == rfq.key() @ ErrorCode::PendingComputationMismatch,
+    )]
+    pub pending_computation: Account<'info, PendingComputation>,
+    #[account(mut)]
+    /// CHECK: receives the pending computation's rent, checked by `has_one`
+    pub requester: UncheckedAccount<'info>,
+}
+
+#[init_computation_definition_accounts("rank_rfq_quote", payer)]
+#[derive(Accounts)]
+pub struct InitRankRfqQuoteCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("select_rfq_quote", payer)]
+#[derive(Accounts)]
+pub struct InitSelectRfqQuoteCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+// === EVENTS ===
+
+#[event]
+pub struct RfqCreatedEvent {
+    pub header: EventHeader,
+    pub base_mint: Option<Pubkey>,
+    pub quote_mint: Option<Pubkey>,
+    pub size: u64,
+    pub quote_deadline: i64,
+    pub settle_deadline: i64,
+}
+
+/// A quote was ranked against the best so far; how it compared stays secret
+#[event]
+pub struct RfqQuoteRankedEvent {
+    pub header: EventHeader,
+    pub maker: Pubkey,
+    pub quote_index: u8,
+}
+
+#[event]
+pub struct RfqAwardedEvent {
+    pub header: EventHeader,
+    pub winner: Pubkey,
+    pub price: u64,
+}
+
+#[event]
+pub struct RfqCancelledEvent {
+    pub header: EventHeader,
+}
+
+#[event]
+pub struct RfqSettledEvent {
+    pub header: EventHeader,
+    pub taker: Pubkey,
+    pub winner: Pubkey,
+    pub size: u64,
+    pub price: u64,
+}
+
+#[event]
+pub struct RfqLegRefundedEvent {
+    pub header: EventHeader,
+    pub depositor: Pubkey,
+    pub side: VaultSide,
+    pub vault: Pubkey,
+    pub refunded: u64,
+}
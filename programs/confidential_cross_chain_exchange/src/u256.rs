@@ -0,0 +1,179 @@
+//! Minimal unsigned 256-bit integer for EVM-side amounts.
+//!
+//! On-chain accounts, instruction args and events carry EVM amounts as
+//! `[u8; 32]` big-endian (the ABI `uint256` encoding); this type is only used
+//! to do checked arithmetic on them.
+
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct U256 {
+    /// Little-endian 64-bit limbs
+    limbs: [u64; 4],
+}
+
+impl U256 {
+    pub const ZERO: U256 = U256 { limbs: [0; 4] };
+
+    pub fn from_u64(value: u64) -> Self {
+        U256 { limbs: [value, 0, 0, 0] }
+    }
+
+    pub fn from_be_bytes(bytes: &[u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let start = 32 - (i + 1) * 8;
+            let mut chunk = [0u8; 8];
+            chunk.copy_from_slice(&bytes[start..start + 8]);
+            *limb = u64::from_be_bytes(chunk);
+        }
+        U256 { limbs }
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, limb) in self.limbs.iter().enumerate() {
+            let start = 32 - (i + 1) * 8;
+            bytes[start..start + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.iter().all(|&limb| limb == 0)
+    }
+
+    /// `None` when the value does not fit in a u64
+    pub fn to_u64(&self) -> Option<u64> {
+        if self.limbs[1..].iter().any(|&limb| limb != 0) {
+            return None;
+        }
+        Some(self.limbs[0])
+    }
+
+    pub fn checked_mul_u64(&self, rhs: u64) -> Option<Self> {
+        let mut limbs = [0u64; 4];
+        let mut carry = 0u128;
+        for (i, limb) in self.limbs.iter().enumerate() {
+            let product = u128::from(*limb) * u128::from(rhs) + carry;
+            limbs[i] = product as u64;
+            carry = product >> 64;
+        }
+        if carry != 0 {
+            return None;
+        }
+        Some(U256 { limbs })
+    }
+
+    /// Returns `(quotient, remainder)`; `None` on division by zero
+    pub fn div_rem_u64(&self, rhs: u64) -> Option<(Self, u64)> {
+        if rhs == 0 {
+            return None;
+        }
+        let mut limbs = [0u64; 4];
+        let mut rem = 0u128;
+        for i in (0..4).rev() {
+            let dividend = (rem << 64) | u128::from(self.limbs[i]);
+            limbs[i] = (dividend / u128::from(rhs)) as u64;
+            rem = dividend % u128::from(rhs);
+        }
+        Some((U256 { limbs }, rem as u64))
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs.iter().rev().cmp(other.limbs.iter().rev())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX: U256 = U256 { limbs: [u64::MAX; 4] };
+
+    /// `2^(64 * limb)`, the lowest value in `limb`
+    fn limb_base(limb: usize) -> U256 {
+        let mut limbs = [0; 4];
+        limbs[limb] = 1;
+        U256 { limbs }
+    }
+
+    #[test]
+    fn round_trips_big_endian_bytes() {
+        let bytes: [u8; 32] = core::array::from_fn(|i| i as u8 + 1);
+        let value = U256::from_be_bytes(&bytes);
+        assert_eq!(value.to_be_bytes(), bytes);
+        // The last byte is the least significant
+        assert_eq!(value.limbs[0], u64::from_be_bytes(bytes[24..].try_into().unwrap()));
+        assert_eq!(MAX.to_be_bytes(), [0xff; 32]);
+        assert_eq!(U256::from_u64(0x0102).to_be_bytes()[30..], [0x01, 0x02]);
+    }
+
+    #[test]
+    fn converts_to_u64_only_when_it_fits() {
+        assert_eq!(U256::ZERO.to_u64(), Some(0));
+        assert_eq!(U256::from_u64(u64::MAX).to_u64(), Some(u64::MAX));
+        for limb in 1..4 {
+            assert_eq!(limb_base(limb).to_u64(), None);
+        }
+        assert_eq!(MAX.to_u64(), None);
+    }
+
+    #[test]
+    fn multiplies_with_carries_across_limbs() {
+        let max_u64 = U256::from_u64(u64::MAX);
+        // (2^64 - 1)^2 = 2^128 - 2^65 + 1
+        let square = max_u64.checked_mul_u64(u64::MAX).unwrap();
+        assert_eq!(square.limbs, [1, u64::MAX - 1, 0, 0]);
+        assert_eq!(limb_base(2).checked_mul_u64(u64::MAX).unwrap().limbs, [0, 0, u64::MAX, 0]);
+        assert_eq!(MAX.checked_mul_u64(1), Some(MAX));
+        assert_eq!(MAX.checked_mul_u64(0), Some(U256::ZERO));
+    }
+
+    #[test]
+    fn rejects_multiplication_overflow() {
+        assert_eq!(MAX.checked_mul_u64(2), None);
+        // The top limb may fill up, but not carry out
+        let top = limb_base(3).checked_mul_u64(u64::MAX).unwrap();
+        assert_eq!(top.limbs, [0, 0, 0, u64::MAX]);
+        assert_eq!(top.checked_mul_u64(2), None);
+        let top_half = U256 { limbs: [0, 0, 0, 1 << 63] };
+        assert_eq!(top_half.checked_mul_u64(2), None);
+    }
+
+    #[test]
+    fn divides_with_remainders_across_limbs() {
+        assert_eq!(MAX.div_rem_u64(1), Some((MAX, 0)));
+        let (quotient, remainder) = MAX.div_rem_u64(u64::MAX).unwrap();
+        // (2^256 - 1) / (2^64 - 1) = 2^192 + 2^128 + 2^64 + 1
+        assert_eq!((quotient.limbs, remainder), ([1; 4], 0));
+        let (quotient, remainder) = limb_base(1).div_rem_u64(10).unwrap();
+        assert_eq!((quotient.limbs, remainder), ([u64::MAX / 10, 0, 0, 0], 6));
+        assert_eq!(U256::from_u64(7).div_rem_u64(u64::MAX), Some((U256::ZERO, 7)));
+        assert_eq!(MAX.div_rem_u64(0), None);
+    }
+
+    #[test]
+    fn multiplication_and_division_invert() {
+        let value = U256::from_be_bytes(&[0x5a; 32]).div_rem_u64(1_000_000_007).unwrap().0;
+        let product = value.checked_mul_u64(1_000_000_007).unwrap();
+        assert_eq!(product.div_rem_u64(1_000_000_007), Some((value, 0)));
+    }
+
+    #[test]
+    fn orders_by_the_most_significant_limb() {
+        assert!(limb_base(1) > U256::from_u64(u64::MAX));
+        assert!(limb_base(3) > U256 { limbs: [u64::MAX, u64::MAX, u64::MAX, 0] });
+        assert!(U256::ZERO < U256::from_u64(1));
+        assert_eq!(MAX.cmp(&MAX), Ordering::Equal);
+        assert!(MAX > limb_base(3));
+    }
+}
@@ -0,0 +1,648 @@
+//! Layout versions of the program's accounts.
+//!
+//! Every account starts, right after its discriminator, with a
+//! `layout_version` byte, and is sized from its `InitSpace` derive.
+//! Only the two offer accounts of the first program predate the byte. They
+//! are version 0, recognised by their length, and [`IntraChainOfferV0`] and
+//! [`InterchainOfferV0`] keep their layouts. [`IntraChainOfferV1`] and
+//! [`InterchainOfferV1`] are the offers before they recorded their buyer.
+//!
+//! Instructions and [`decode_current`] only read the current layout, so
+//! accounts left over from an older program have to be upgraded with
+//! `migrate_account` before they can be used again.
+
+use anchor_lang::{prelude::*, Discriminator};
+
+use crate::{
+    ArbiterRegistry,
+    BasketOffer,
+    ChainConfig,
+    ComputationTimeout,
+    CounterOffer,
+    Dispute,
+    ErrorCode,
+    EvmBlockHeader,
+    ExchangeConfig,
+    FiatOffer,
+    IdentityExchange,
+    InterchainOffer,
+    IntraChainOffer,
+    OfferStatus,
+    OutboundMessage,
+    PendingComputation,
+    PriceBand,
+    PriceFeed,
+    PriceTrigger,
+    ProcessedEvmTx,
+    RelayerBond,
+    RfqRequest,
+    SolanaOriginOffer,
+    TokenMapping,
+};
+
+/// An account type whose layout is versioned
+pub trait VersionedAccount:
+    AccountSerialize + AccountDeserialize + AnchorDeserialize + Discriminator + Space
+{
+    /// Version written by this program; bump it whenever the layout changes
+    const LAYOUT_VERSION: u8;
+    /// Bytes allocated for the current layout
+    const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    fn set_layout_version(&mut self, version: u8);
+
+    /// Who may migrate the account besides the exchange admin
+    fn migration_authority(&self) -> Option<Pubkey>;
+
+    /// Maps `data`, the account at `address`, to the current layout if it
+    /// is in an older one, with the version it was in; `None` when it is not,
+    /// or the type never had one
+    fn decode_legacy(
+        _data: &[u8],
+        _address: &Pubkey,
+        _maker: Option<Pubkey>,
+    ) -> Option<Result<(u8, Self)>> {
+        None
+    }
+}
+
+/// `IntraChainOffer` as the first program wrote it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct IntraChainOfferV0 {
+    pub id: u64,
+    pub token_a_offered_amount: u64,
+    pub token_b_wanted_amount: u64,
+    pub is_taker_native: bool,
+    pub deadline: i64,
+    pub bump: u8,
+}
+
+/// `InterchainOffer` as the first program wrote it, with token B in
+/// lamports
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct InterchainOfferV0 {
+    pub id: u64,
+    pub token_a_offered_amount: u64,
+    pub token_b_wanted_amount: u64,
+    pub is_taker_native: bool,
+    pub chain_id: u64,
+    pub deadline: i64,
+    pub bump: u8,
+}
+
+/// A version 0 offer layout. Those offers did not record their maker, so it
+/// is given when migrating and checked against the offer's address.
+pub trait LegacyOffer: AnchorDeserialize {
+    type Current: VersionedAccount;
+    /// Bytes the first program allocated, discriminator included
+    const SPACE: usize;
+    /// Seed prefix of the offer's address, followed by the maker and the id
+    const SEED: &'static [u8];
+
+    fn id(&self) -> u64;
+    fn bump(&self) -> u8;
+
+    /// The offer in the current layout, still at version 0
+    fn upgrade(self, maker: Pubkey) -> Self::Current;
+}
+
+impl LegacyOffer for IntraChainOfferV0 {
+    type Current = IntraChainOffer;
+    const SPACE: usize = 8 + 8 + 8 + 8 + 1 + 8 + 1;
+    const SEED: &'static [u8] = b"IntraChainoffer";
+
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn bump(&self) -> u8 {
+        self.bump
+    }
+
+    /// The first program kept no status; whatever its vaults still hold
+    /// decides what the offer can settle
+    fn upgrade(self, maker: Pubkey) -> IntraChainOffer {
+        IntraChainOffer {
+            layout_version: 0,
+            id: self.id,
+            token_a_offered_amount: self.token_a_offered_amount,
+            token_b_wanted_amount: self.token_b_wanted_amount,
+            is_taker_native: self.is_taker_native,
+            deadline: self.deadline,
+            maker,
+            buyer: None,
+            status: OfferStatus::Open,
+            computation_offset: 0,
+            bump: self.bump,
+        }
+    }
+}
+
+impl LegacyOffer for InterchainOfferV0 {
+    type Current = InterchainOffer;
+    const SPACE: usize = 8 + 8 + 8 + 8 + 1 + 8 + 8 + 1;
+    const SEED: &'static [u8] = b"InterChainoffer";
+
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn bump(&self) -> u8 {
+        self.bump
+    }
+
+    /// Token B widens to a u256. No EVM token was mapped then, so the offer
+    /// can be cancelled or refunded but no longer settles.
+    fn upgrade(self, maker: Pubkey) -> InterchainOffer {
+        let mut token_b_wanted_amount = [0; 32];
+        token_b_wanted_amount[24..].copy_from_slice(&self.token_b_wanted_amount.to_be_bytes());
+        InterchainOffer {
+            layout_version: 0,
+            id: self.id,
+            token_a_offered_amount: self.token_a_offered_amount,
+            token_b_wanted_amount,
+            is_taker_native: self.is_taker_native,
+            chain_id: self.chain_id,
+            deadline: self.deadline,
+            token_mapping: Pubkey::default(),
+            maker,
+            buyer: None,
+            relayer: Pubkey::default(),
+            slashed: false,
+            status: OfferStatus::Open,
+            computation_offset: 0,
+            bump: self.bump,
+        }
+    }
+}
+
+/// Maps `data`, the account at `address`, from the version 0 layout `L`
+/// if it has that size; fails unless `maker` derives `address`
+fn decode_legacy_offer<L: LegacyOffer>(
+    data: &[u8],
+    address: &Pubkey,
+    maker: Option<Pubkey>,
+) -> Option<Result<(u8, L::Current)>> {
+    (data.len() == L::SPACE).then(|| {
+        let legacy: L = deserialize(&data[8..])?;
+        let maker = maker.ok_or(ErrorCode::LegacyOfferMakerMismatch)?;
+        let derived = Pubkey::create_program_address(
+            &[L::SEED, maker.as_ref(), &legacy.id().to_le_bytes(), &[legacy.bump()]],
+            &crate::ID,
+        )
+        .map_err(|_| ErrorCode::LegacyOfferMakerMismatch)?;
+        require_keys_eq!(derived, *address, ErrorCode::LegacyOfferMakerMismatch);
+        Ok((0, legacy.upgrade(maker)))
+    })
+}
+
+/// `IntraChainOffer` at version 1, before it recorded its buyer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct IntraChainOfferV1 {
+    pub layout_version: u8,
+    pub id: u64,
+    pub token_a_offered_amount: u64,
+    pub token_b_wanted_amount: u64,
+    pub is_taker_native: bool,
+    pub deadline: i64,
+    pub maker: Pubkey,
+    pub status: OfferStatus,
+    pub computation_offset: u64,
+    pub bump: u8,
+}
+
+impl IntraChainOfferV1 {
+    /// Bytes allocated for version 1, discriminator included
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    /// Version 1 offers never recorded a buyer; the next finalize does
+    fn upgrade(self) -> IntraChainOffer {
+        IntraChainOffer {
+            layout_version: self.layout_version,
+            id: self.id,
+            token_a_offered_amount: self.token_a_offered_amount,
+            token_b_wanted_amount: self.token_b_wanted_amount,
+            is_taker_native: self.is_taker_native,
+            deadline: self.deadline,
+            maker: self.maker,
+            buyer: None,
+            status: self.status,
+            computation_offset: self.computation_offset,
+            bump: self.bump,
+        }
+    }
+}
+
+/// Maps `data`, the intrachain offer at `address`, from its version 0 or 1
+/// layout
+fn decode_legacy_intrachain_offer(
+    data: &[u8],
+    address: &Pubkey,
+    maker: Option<Pubkey>,
+) -> Option<Result<(u8, IntraChainOffer)>> {
+    decode_legacy_offer::<IntraChainOfferV0>(data, address, maker).or_else(|| {
+        (data.len() == IntraChainOfferV1::SPACE).then(|| {
+            let legacy: IntraChainOfferV1 = deserialize(&data[8..])?;
+            Ok((1, legacy.upgrade()))
+        })
+    })
+}
+
+/// `InterchainOffer` at version 1, before it recorded its buyer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct InterchainOfferV1 {
+    pub layout_version: u8,
+    pub id: u64,
+    pub token_a_offered_amount: u64,
+    pub token_b_wanted_amount: [u8; 32],
+    pub is_taker_native: bool,
+    pub chain_id: u64,
+    pub deadline: i64,
+    pub token_mapping: Pubkey,
+    pub maker: Pubkey,
+    pub relayer: Pubkey,
+    pub slashed: bool,
+    pub status: OfferStatus,
+    pub computation_offset: u64,
+    pub bump: u8,
+}
+
+impl InterchainOfferV1 {
+    /// Bytes allocated for version 1, discriminator included
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    /// Version 1 offers never recorded a buyer; the next finalize does
+    fn upgrade(self) -> InterchainOffer {
+        InterchainOffer {
+            layout_version: self.layout_version,
+            id: self.id,
+            token_a_offered_amount: self.token_a_offered_amount,
+            token_b_wanted_amount: self.token_b_wanted_amount,
+            is_taker_native: self.is_taker_native,
+            chain_id: self.chain_id,
+            deadline: self.deadline,
+            token_mapping: self.token_mapping,
+            maker: self.maker,
+            buyer: None,
+            relayer: self.relayer,
+            slashed: self.slashed,
+            status: self.status,
+            computation_offset: self.computation_offset,
+            bump: self.bump,
+        }
+    }
+}
+
+/// Maps `data`, the interchain offer at `address`, from its version 0 or 1
+/// layout
+fn decode_legacy_interchain_offer(
+    data: &[u8],
+    address: &Pubkey,
+    maker: Option<Pubkey>,
+) -> Option<Result<(u8, InterchainOffer)>> {
+    decode_legacy_offer::<InterchainOfferV0>(data, address, maker).or_else(|| {
+        (data.len() == InterchainOfferV1::SPACE).then(|| {
+            let legacy: InterchainOfferV1 = deserialize(&data[8..])?;
+            Ok((1, legacy.upgrade()))
+        })
+    })
+}
+
+fn strip_discriminator<T: VersionedAccount>(data: &[u8]) -> Result<&[u8]> {
+    Ok(data
+        .strip_prefix(T::DISCRIMINATOR)
+        .ok_or(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch)?)
+}
+
+/// Decodes `data` in the current layout of `T`
+pub fn decode_current<T: VersionedAccount>(data: &[u8]) -> Result<T> {
+    let body = strip_discriminator::<T>(data)?;
+    let version = *body
+        .first()
+        .ok_or(anchor_lang::error::ErrorCode::AccountDidNotDeserialize)?;
+    require!(version == T::LAYOUT_VERSION, ErrorCode::UnsupportedAccountLayout);
+    deserialize(body)
+}
+
+/// Decodes `data`, the account at `address`, in any supported layout of
+/// `T`, with the version it was in; `maker` is needed for version 0 offers
+pub fn decode_with_version<T: VersionedAccount>(
+    data: &[u8],
+    address: &Pubkey,
+    maker: Option<Pubkey>,
+) -> Result<(u8, T)> {
+    strip_discriminator::<T>(data)?;
+    match T::decode_legacy(data, address, maker) {
+        Some(legacy) => legacy,
+        None => Ok((T::LAYOUT_VERSION, decode_current(data)?)),
+    }
+}
+
+fn deserialize<T: AnchorDeserialize>(mut body: &[u8]) -> Result<T> {
+    T::deserialize(&mut body)
+        .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into())
+}
+
+/// Rewrites `account` in the current layout of `T`, growing it to
+/// `T::SPACE` with `authority` paying the extra rent; returns the version
+/// it was in
+fn upgrade<'info, T: VersionedAccount>(
+    account: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    admin: Option<Pubkey>,
+    maker: Option<Pubkey>,
+    system_program: &AccountInfo<'info>,
+) -> Result<u8> {
+    let (from_version, mut state) =
+        decode_with_version::<T>(&account.try_borrow_data()?, account.key, maker)?;
+    require!(from_version < T::LAYOUT_VERSION, ErrorCode::AccountUpToDate);
+    require!(
+        admin == Some(authority.key()) || state.migration_authority() == Some(authority.key()),
+        ErrorCode::Unauthorized
+    );
+    state.set_layout_version(T::LAYOUT_VERSION);
+
+    let top_up = Rent::get()?
+        .minimum_balance(T::SPACE)
+        .saturating_sub(account.lamports());
+    if top_up > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: authority.clone(),
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    account.resize(T::SPACE)?;
+
+    let mut data = account.try_borrow_mut_data()?;
+    let mut writer = &mut data[..];
+    state.try_serialize(&mut writer)?;
+    writer.fill(0);
+    Ok(from_version)
+}
+
+macro_rules! versioned_accounts {
+    ($(
+        $account:ident: $version:literal, |$state:ident| $authority:expr $(, legacy = $legacy:expr)?;
+    )*) => {
+        $(
+            impl VersionedAccount for $account {
+                const LAYOUT_VERSION: u8 = $version;
+
+                fn set_layout_version(&mut self, version: u8) {
+                    self.layout_version = version;
+                }
+
+                fn migration_authority(&self) -> Option<Pubkey> {
+                    let $state = self;
+                    $authority
+                }
+
+                $(
+                    fn decode_legacy(
+                        data: &[u8],
+                        address: &Pubkey,
+                        maker: Option<Pubkey>,
+                    ) -> Option<Result<(u8, Self)>> {
+                        $legacy(data, address, maker)
+                    }
+                )?
+            }
+        )*
+
+        /// Upgrades whichever versioned account `account` holds; returns the
+        /// version it was in and the one it is in now
+        pub fn migrate<'info>(
+            account: &AccountInfo<'info>,
+            authority: &AccountInfo<'info>,
+            admin: Option<Pubkey>,
+            maker: Option<Pubkey>,
+            system_program: &AccountInfo<'info>,
+        ) -> Result<(u8, u8)> {
+            let discriminator = account.try_borrow_data()?.get(..8).map(<[u8]>::to_vec);
+            $(
+                if discriminator.as_deref() == Some($account::DISCRIMINATOR) {
+                    let from =
+                        upgrade::<$account>(account, authority, admin, maker, system_program)?;
+                    return Ok((from, $account::LAYOUT_VERSION));
+                }
+            )*
+            err!(ErrorCode::UnsupportedAccountLayout)
+        }
+    };
+}
+
+versioned_accounts! {
+    IntraChainOffer: 2, |offer| Some(offer.maker),
+        legacy = decode_legacy_intrachain_offer;
+    InterchainOffer: 2, |offer| Some(offer.maker),
+        legacy = decode_legacy_interchain_offer;
+    SolanaOriginOffer: 1, |offer| Some(offer.maker);
+    BasketOffer: 1, |offer| Some(offer.maker);
+    RfqRequest: 1, |rfq| Some(rfq.taker);
+    CounterOffer: 1, |counter| Some(counter.proposer);
+    FiatOffer: 1, |offer| Some(offer.maker);
+    IdentityExchange: 1, |exchange| Some(exchange.seller);
+    PriceBand: 1, |band| Some(band.maker);
+    PriceFeed: 1, |feed| Some(feed.publisher);
+    PriceTrigger: 1, |trigger| Some(trigger.maker);
+    Dispute: 1, |dispute| Some(dispute.opened_by);
+    PendingComputation: 1, |pending| Some(pending.requester);
+    RelayerBond: 1, |bond| Some(bond.relayer);
+    EvmBlockHeader: 1, |header| Some(header.relayer);
+    ComputationTimeout: 1, |_timeout| None;
+    ExchangeConfig: 1, |_config| None;
+    ChainConfig: 1, |_config| None;
+    ArbiterRegistry: 1, |_registry| None;
+    TokenMapping: 1, |_mapping| None;
+    OutboundMessage: 1, |_message| None;
+    ProcessedEvmTx: 1, |_marker| None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAKER: Pubkey = Pubkey::new_from_array([3; 32]);
+
+    fn offer(layout_version: u8) -> IntraChainOffer {
+        IntraChainOffer {
+            layout_version,
+            id: 7,
+            token_a_offered_amount: 1_000,
+            token_b_wanted_amount: 2_000,
+            is_taker_native: true,
+            deadline: 1_800_000_000,
+            maker: MAKER,
+            buyer: Some(Pubkey::new_from_array([4; 32])),
+            status: OfferStatus::Open,
+            computation_offset: 11,
+            bump: 254,
+        }
+    }
+
+    /// The first program's `IntraChainOffer` with id 7, field by field
+    fn v0_intrachain_bytes(bump: u8) -> Vec<u8> {
+        [
+            IntraChainOffer::DISCRIMINATOR,
+            &7u64.to_le_bytes(),
+            &1_000u64.to_le_bytes(),
+            &2_000u64.to_le_bytes(),
+            &[1],
+            &1_800_000_000i64.to_le_bytes(),
+            &[bump],
+        ]
+        .concat()
+    }
+
+    fn offer_address(seed: &[u8], maker: &Pubkey, id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[seed, maker.as_ref(), &id.to_le_bytes()], &crate::ID)
+    }
+
+    #[test]
+    fn v0_layouts_match_the_first_program() {
+        // The `space` of the first program's `init` constraints
+        assert_eq!(IntraChainOfferV0::SPACE, 8 + 8 + 8 + 8 + 1 + 8 + 1);
+        assert_eq!(InterchainOfferV0::SPACE, 8 + 8 + 8 + 8 + 1 + 8 + 8 + 1);
+        assert_eq!(v0_intrachain_bytes(0).len(), IntraChainOfferV0::SPACE);
+    }
+
+    #[test]
+    fn maps_v0_offers_onto_the_current_layouts() {
+        let (address, bump) = offer_address(b"IntraChainoffer", &MAKER, 7);
+        let data = v0_intrachain_bytes(bump);
+        let (version, decoded) =
+            decode_with_version::<IntraChainOffer>(&data, &address, Some(MAKER)).unwrap();
+        assert_eq!(version, 0);
+        assert_eq!((decoded.layout_version, decoded.id, decoded.bump), (0, 7, bump));
+        assert_eq!(decoded.token_b_wanted_amount, 2_000);
+        assert_eq!(decoded.deadline, 1_800_000_000);
+        assert_eq!((decoded.maker, decoded.status), (MAKER, OfferStatus::Open));
+        assert_eq!(decoded.buyer, None);
+        assert_eq!(decoded.migration_authority(), Some(MAKER));
+
+        // The maker is only trusted if it derives the offer's address
+        let stranger = Pubkey::new_from_array([4; 32]);
+        assert!(decode_with_version::<IntraChainOffer>(&data, &address, Some(stranger)).is_err());
+        assert!(decode_with_version::<IntraChainOffer>(&data, &address, None).is_err());
+
+        let (address, bump) = offer_address(b"InterChainoffer", &MAKER, 9);
+        let data = [
+            InterchainOffer::DISCRIMINATOR,
+            &9u64.to_le_bytes(),
+            &1_000u64.to_le_bytes(),
+            &2_000u64.to_le_bytes(),
+            &[0],
+            &5u64.to_le_bytes(),
+            &1_800_000_000i64.to_le_bytes(),
+            &[bump],
+        ]
+        .concat();
+        assert_eq!(data.len(), InterchainOfferV0::SPACE);
+        let (version, decoded) =
+            decode_with_version::<InterchainOffer>(&data, &address, Some(MAKER)).unwrap();
+        assert_eq!(version, 0);
+        assert_eq!((decoded.id, decoded.chain_id, decoded.bump), (9, 5, bump));
+        assert_eq!(decoded.token_b_wanted_amount[24..], 2_000u64.to_be_bytes());
+        assert_eq!(decoded.token_b_wanted_amount[..24], [0; 24]);
+        assert!(!decoded.is_taker_native);
+        assert_eq!(decoded.token_mapping, Pubkey::default());
+        assert_eq!((decoded.maker, decoded.status), (MAKER, OfferStatus::Open));
+        assert_eq!(decoded.buyer, None);
+    }
+
+    #[test]
+    fn maps_v1_intrachain_offers_without_a_buyer() {
+        let v1 = IntraChainOfferV1 {
+            layout_version: 1,
+            id: 7,
+            token_a_offered_amount: 1_000,
+            token_b_wanted_amount: 2_000,
+            is_taker_native: true,
+            deadline: 1_800_000_000,
+            maker: MAKER,
+            status: OfferStatus::Settled,
+            computation_offset: 11,
+            bump: 254,
+        };
+        let mut data = IntraChainOffer::DISCRIMINATOR.to_vec();
+        v1.serialize(&mut data).unwrap();
+        // The recorded buyer is the only field version 2 added
+        assert_eq!(data.len(), IntraChainOffer::SPACE - (1 + 32));
+        assert_eq!(data.len(), IntraChainOfferV1::SPACE);
+        assert!(decode_current::<IntraChainOffer>(&data).is_err());
+
+        let (version, decoded) =
+            decode_with_version::<IntraChainOffer>(&data, &Pubkey::default(), None).unwrap();
+        assert_eq!((version, decoded.layout_version, decoded.buyer), (1, 1, None));
+        assert_eq!((decoded.id, decoded.deadline), (7, 1_800_000_000));
+        assert_eq!((decoded.token_a_offered_amount, decoded.token_b_wanted_amount), (1_000, 2_000));
+        assert_eq!((decoded.maker, decoded.status), (MAKER, OfferStatus::Settled));
+        assert_eq!((decoded.computation_offset, decoded.bump), (11, 254));
+    }
+
+    #[test]
+    fn maps_v1_interchain_offers_without_a_buyer() {
+        let v1 = InterchainOfferV1 {
+            layout_version: 1,
+            id: 9,
+            token_a_offered_amount: 1_000,
+            token_b_wanted_amount: [7; 32],
+            is_taker_native: true,
+            chain_id: 5,
+            deadline: 1_800_000_000,
+            token_mapping: Pubkey::new_from_array([5; 32]),
+            maker: MAKER,
+            relayer: Pubkey::new_from_array([6; 32]),
+            slashed: true,
+            status: OfferStatus::Disputed,
+            computation_offset: 11,
+            bump: 254,
+        };
+        let mut data = InterchainOffer::DISCRIMINATOR.to_vec();
+        v1.serialize(&mut data).unwrap();
+        // The recorded buyer is the only field version 2 added
+        assert_eq!(data.len(), InterchainOffer::SPACE - (1 + 32));
+        assert_eq!(data.len(), InterchainOfferV1::SPACE);
+        assert!(decode_current::<InterchainOffer>(&data).is_err());
+
+        let (version, decoded) =
+            decode_with_version::<InterchainOffer>(&data, &Pubkey::default(), None).unwrap();
+        assert_eq!((version, decoded.layout_version, decoded.buyer), (1, 1, None));
+        assert_eq!((decoded.id, decoded.chain_id, decoded.deadline), (9, 5, 1_800_000_000));
+        assert_eq!(decoded.token_b_wanted_amount, [7; 32]);
+        assert_eq!((decoded.token_mapping, decoded.maker), (v1.token_mapping, MAKER));
+        assert_eq!((decoded.relayer, decoded.slashed), (v1.relayer, true));
+        assert_eq!((decoded.status, decoded.computation_offset), (OfferStatus::Disputed, 11));
+    }
+
+    #[test]
+    fn decodes_the_current_layout() {
+        let current = offer(2);
+        let mut data = Vec::new();
+        current.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), IntraChainOffer::SPACE);
+        let (version, decoded) =
+            decode_with_version::<IntraChainOffer>(&data, &Pubkey::default(), None).unwrap();
+        assert_eq!((version, decoded.maker, decoded.buyer), (2, current.maker, current.buyer));
+        assert_eq!(decode_current::<IntraChainOffer>(&data).unwrap().computation_offset, 11);
+    }
+
+    #[test]
+    fn rejects_unknown_versions_and_other_accounts() {
+        let mut data = Vec::new();
+        offer(2).try_serialize(&mut data).unwrap();
+        data[8] = 3;
+        assert!(decode_current::<IntraChainOffer>(&data).is_err());
+        data[8] = 2;
+        assert!(decode_current::<InterchainOffer>(&data).is_err());
+        assert!(decode_current::<IntraChainOffer>(&data[..8]).is_err());
+        // Version 0 offers have to be migrated first
+        assert!(decode_current::<IntraChainOffer>(&v0_intrachain_bytes(254)).is_err());
+    }
+}
@@ -71,6 +71,9 @@ describe("ConfidentialCrossChainExchange", () => {
     return first8.readBigUInt64LE();
   };
 
+  // EVM-leg amounts are passed as big-endian uint256 bytes
+  const toU256Be = (n: anchor.BN) => Array.from(n.toArrayLike(Buffer, "be", 32));
+
   const toHexU64 = (n: bigint | anchor.BN) => {
     if (anchor.BN.isBN(n)) {
       return '0x' + (n as anchor.BN).toArrayLike(Buffer, 'le', 8).toString('hex');
@@ -97,6 +100,111 @@ describe("ConfidentialCrossChainExchange", () => {
     return PublicKey.findProgramAddressSync([seed], programId)[0];
   };
 
+  const deriveExchangeConfigPda = (programId: PublicKey) => {
+    return PublicKey.findProgramAddressSync([Buffer.from("ExchangeConfig")], programId)[0];
+  };
+
+  const deriveChainConfigPda = (programId: PublicKey, chainId: anchor.BN) => {
+    const idLe = Buffer.from(chainId.toArrayLike(Buffer, "le", 8));
+    return PublicKey.findProgramAddressSync([Buffer.from("ChainConfig"), idLe], programId)[0];
+  };
+
+  // USDC on Ethereum mainnet, mapped to wrapped SOL's mint for the local tests
+  const TEST_EVM_TOKEN = Buffer.from("a0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", "hex");
+  const NATIVE_MINT = new PublicKey("So11111111111111111111111111111111111111112");
+
+  const deriveOutboundMessagePda = (programId: PublicKey, chainId: anchor.BN, sequence: anchor.BN) => {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("OutboundMessage"),
+        chainId.toArrayLike(Buffer, "le", 8),
+        sequence.toArrayLike(Buffer, "le", 8),
+      ],
+      programId
+    )[0];
+  };
+
+  const deriveTokenMappingPda = (programId: PublicKey, chainId: anchor.BN, evmToken: Buffer) => {
+    const idLe = Buffer.from(chainId.toArrayLike(Buffer, "le", 8));
+    return PublicKey.findProgramAddressSync([Buffer.from("TokenMapping"), idLe, evmToken], programId)[0];
+  };
+
+  // Registers `chainId` with the local wallet as admin and sole trusted relayer
+  const ensureChainConfig = async (chainId: anchor.BN, enabled: boolean = true) => {
+    const admin = (provider.wallet as any).payer.publicKey;
+    const exchangeConfig = deriveExchangeConfigPda(program.programId);
+    if (!(await provider.connection.getAccountInfo(exchangeConfig))) {
+      await program.methods
+        .initializeExchangeConfig()
+        .accountsPartial({ admin, exchangeConfig })
+        .rpc({ commitment: "confirmed" });
+    }
+
+    const chainConfig = deriveChainConfigPda(program.programId, chainId);
+    await program.methods
+      .setChainConfig(
+        chainId,
+        enabled,
+        new anchor.BN(12),
+        new anchor.BN(60),
+        new anchor.BN(7 * 24 * 3600),
+        [admin],
+        [Array.from(TEST_EVM_TOKEN)]
+      )
+      .accountsPartial({ admin, exchangeConfig, chainConfig })
+      .rpc({ commitment: "confirmed" });
+    return chainConfig;
+  };
+
+  const deriveRelayerBondPda = (programId: PublicKey, relayer: PublicKey) => {
+    return PublicKey.findProgramAddressSync([Buffer.from("RelayerBond"), relayer.toBuffer()], programId)[0];
+  };
+
+  const deriveFeeTreasuryPda = (programId: PublicKey) => {
+    return PublicKey.findProgramAddressSync([Buffer.from("FeeTreasury")], programId)[0];
+  };
+
+  const derivePendingComputationPda = (programId: PublicKey, computationOffset: anchor.BN) => {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("PendingComputation"), computationOffset.toArrayLike(Buffer, "le", 8)],
+      programId
+    )[0];
+  };
+
+  // Stakes `amount` lamports in the local wallet's relayer bond
+  const bondRelayer = async (amount: anchor.BN = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL)) => {
+    const relayer = (provider.wallet as any).payer.publicKey;
+    const relayerBond = deriveRelayerBondPda(program.programId, relayer);
+    await program.methods
+      .bondRelayer(amount)
+      .accountsPartial({ relayer, relayerBond })
+      .rpc({ commitment: "confirmed" });
+    return relayerBond;
+  };
+
+  // Maps TEST_EVM_TOKEN on `chainId` (6 decimals) to wrapped SOL (9 decimals)
+  const ensureTokenMapping = async (chainId: anchor.BN) => {
+    const admin = (provider.wallet as any).payer.publicKey;
+    const tokenMapping = deriveTokenMappingPda(program.programId, chainId, TEST_EVM_TOKEN);
+    await program.methods
+      .setTokenMapping(
+        chainId,
+        Array.from(TEST_EVM_TOKEN),
+        new anchor.BN(1),
+        6,
+        NATIVE_MINT,
+        9
+      )
+      .accountsPartial({
+        admin,
+        exchangeConfig: deriveExchangeConfigPda(program.programId),
+        chainConfig: deriveChainConfigPda(program.programId, chainId),
+        tokenMapping,
+      })
+      .rpc({ commitment: "confirmed" });
+    return tokenMapping;
+  };
+
   it("Is initialized!", async () => {
     const owner = readKpJson(`${os.homedir()}/.config/solana/id.json`);
 
@@ -157,10 +265,16 @@ describe("ConfidentialCrossChainExchange", () => {
           program.programId,
           Buffer.from(getCompDefAccOffset("add_together")).readUInt32LE()
         ),
+        pendingComputation: derivePendingComputationPda(program.programId, computationOffset),
       })
       .rpc({ skipPreflight: true, commitment: "confirmed" });
     console.log("Queue sig is ", queueSig);
 
+    const pendingComputation = derivePendingComputationPda(program.programId, computationOffset);
+    const pending = await program.account.pendingComputation.fetch(pendingComputation);
+    expect(pending.computationOffset.toString()).to.equal(computationOffset.toString());
+    expect(pending.operation).to.deep.equal({ addTogether: {} });
+
     const finalizeSig = await awaitComputationFinalization(
       provider as anchor.AnchorProvider,
       computationOffset,
@@ -170,6 +284,8 @@ describe("ConfidentialCrossChainExchange", () => {
     console.log("Finalize sig is ", finalizeSig);
 
     const sumEvent = await sumEventPromise;
+    // The callback consumes the pending computation
+    expect(await provider.connection.getAccountInfo(pendingComputation, "confirmed")).to.equal(null);
     const decrypted = (cipher as any).decrypt(
       [sumEvent.sum] as any,
       sumEvent.nonce as any
@@ -207,7 +323,7 @@ describe("ConfidentialCrossChainExchange", () => {
 
     // Public offer metadata
     const id = new anchor.BN(12345);
-    const tokenBWanted = new anchor.BN(3_000_000_000); // 3000 USDC (assuming 6-9 decimals in test)
+    const tokenBWanted = new anchor.BN("3000000000000000000000"); // 3000 tokens at 18 decimals, beyond u64 range
     const tokenAOffered = new anchor.BN(10_000_000_000); // 10 SOL in lamports for example
     const isTakerNative = true;
     const chainId = new anchor.BN(1);
@@ -218,7 +334,12 @@ describe("ConfidentialCrossChainExchange", () => {
     const idNonce = randomBytes(16);
     const idCiphertext = cipher.encrypt([extSellerHashU64], idNonce);
 
+    const chainConfig = await ensureChainConfig(chainId);
+    const tokenMapping = await ensureTokenMapping(chainId);
+    const relayerBond = await bondRelayer();
+
     const relayEventPromise = awaitEvent("relayOfferClonedEvent");
+    const createdEventPromise = awaitEvent("interchainOfferCreatedEvent");
     const computationOffset = new anchor.BN(randomBytes(8), "hex");
 
     const interchainOffer = deriveInterchainOfferPda(
@@ -241,7 +362,7 @@ describe("ConfidentialCrossChainExchange", () => {
     const queueSig = await program.methods
       .relayOfferClone(
         id,
-        tokenBWanted,
+        toU256Be(tokenBWanted),
         tokenAOffered,
         isTakerNative,
         chainId,
@@ -254,6 +375,10 @@ describe("ConfidentialCrossChainExchange", () => {
       .accounts({
         payer: (provider.wallet as any).payer.publicKey,
         interchainOffer,
+        chainConfig,
+        tokenMapping,
+        exchangeConfig: deriveExchangeConfigPda(program.programId),
+        relayerBond,
         signPdaAccount: getSignPdaAccAddress(program.programId),
         computationAccount: getComputationAccAddress(
           program.programId,
@@ -281,7 +406,14 @@ describe("ConfidentialCrossChainExchange", () => {
 
     const relayEvent = await relayEventPromise as any;
     console.log('RelayOfferClonedEvent:', relayEvent);
-    expect(relayEvent.acknowledged).to.equal(1);
+    expect(relayEvent.header.schemaVersion).to.equal(1);
+    expect(relayEvent.header.offerId.toString()).to.equal(id.toString());
+    expect(relayEvent.header.computationOffset.toString()).to.equal(computationOffset.toString());
+    expect(relayEvent.header.statusBefore).to.deep.equal({ pending: {} });
+    expect(relayEvent.header.statusAfter).to.deep.equal({ open: {} });
+
+    const createdEvent = await createdEventPromise as any;
+    expect(new anchor.BN(createdEvent.tokenBWantedAmount, "be").toString()).to.equal(tokenBWanted.toString());
   });
 
   it("Confidential deposit native works!", async () => {
@@ -411,6 +543,9 @@ describe("ConfidentialCrossChainExchange", () => {
     const nonce = randomBytes(16);
     const sellerCiphertext = cipher.encrypt([sellerHashU64], nonce);
 
+    const chainConfig = await ensureChainConfig(chainId);
+    const tokenMapping = await ensureTokenMapping(chainId);
+
     const depositEventPromise = awaitEvent("interchainOriginEvmDepositSellerSplEvent");
     const computationOffset = new anchor.BN(randomBytes(8), "hex");
 
@@ -434,7 +569,7 @@ describe("ConfidentialCrossChainExchange", () => {
     const queueSig = await program.methods
       .interchainOriginEvmDepositSellerSpl(
         id,
-        tokenBWanted,
+        toU256Be(tokenBWanted),
         tokenAOffered,
         isTakerNative,
         chainId,
@@ -447,6 +582,8 @@ describe("ConfidentialCrossChainExchange", () => {
       .accounts({
         payer: (provider.wallet as any).payer.publicKey,
         interchainOffer,
+        chainConfig,
+        tokenMapping,
         signPdaAccount: getSignPdaAccAddress(program.programId),
         computationAccount: getComputationAccAddress(
           program.programId,
@@ -474,15 +611,202 @@ describe("ConfidentialCrossChainExchange", () => {
 
     const depositEvent = await depositEventPromise as any;
     console.log('InterchainOriginEvmDepositSellerSplEvent:', depositEvent);
-    expect(depositEvent.acknowledged).to.equal(1);
+    expect(depositEvent.header.offerId.toString()).to.equal(id.toString());
+    expect(depositEvent.header.statusAfter).to.deep.equal({ open: {} });
 
     // Verify PDA state stored for interchain offer
     const fetched = await (program.account as any).interchainOffer.fetch(interchainOffer);
     console.log('Fetched InterchainOffer PDA state:', fetched);
     expect(fetched.id.toString()).to.equal(id.toString());
-    expect(fetched.tokenBWantedAmount.toString()).to.equal(tokenBWanted.toString());
+    expect(new anchor.BN(fetched.tokenBWantedAmount, "be").toString()).to.equal(tokenBWanted.toString());
     expect(fetched.tokenAOfferedAmount.toString()).to.equal(tokenAOffered.toString());
     expect(Boolean(fetched.isTakerNative)).to.equal(isTakerNative);
+    expect(fetched.tokenMapping.toBase58()).to.equal(tokenMapping.toBase58());
+    // Opened by the successful callback, which records the computation it came from
+    expect(fetched.status).to.have.property("open");
+    expect(fetched.computationOffset.toString()).to.equal(computationOffset.toString());
+  });
+
+  it("Cancel interchain offer posts an outbound message", async () => {
+    const maker = (provider.wallet as any).payer.publicKey;
+    const id = new anchor.BN(23456);
+    const chainId = new anchor.BN(1);
+    const interchainOffer = deriveInterchainOfferPda(program.programId, maker, id);
+    const chainConfig = deriveChainConfigPda(program.programId, chainId);
+    const sellerVault = PublicKey.findProgramAddressSync(
+      [Buffer.from("seller_vault"), maker.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+    const sequence = (await (program.account as any).chainConfig.fetch(chainConfig)).outboundSequence;
+    const outboundMessage = deriveOutboundMessagePda(program.programId, chainId, sequence);
+
+    const postedEventPromise = awaitEvent("outboundMessagePostedEvent");
+    await program.methods
+      .cancelInterchainOffer(id)
+      .accountsPartial({
+        maker,
+        interchainOffer,
+        sellerVault,
+        chainConfig,
+        outboundMessage,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const offer = await (program.account as any).interchainOffer.fetch(interchainOffer);
+    expect(offer.status).to.have.property("cancelled");
+
+    const message = await (program.account as any).outboundMessage.fetch(outboundMessage);
+    expect(message.version).to.equal(1);
+    expect(message.kind).to.have.property("cancel");
+    expect(message.sequence.toString()).to.equal(sequence.toString());
+    expect(message.offerId.toString()).to.equal(id.toString());
+    expect(Buffer.from(message.payload).length).to.equal(8 * 32);
+    // word 4 is the sequence, word 5 the offer id
+    expect(new anchor.BN(Buffer.from(message.payload).subarray(4 * 32, 5 * 32), "be").toString())
+      .to.equal(sequence.toString());
+    expect(new anchor.BN(Buffer.from(message.payload).subarray(5 * 32, 6 * 32), "be").toString())
+      .to.equal(id.toString());
+
+    const postedEvent = await postedEventPromise as any;
+    expect(postedEvent.message.toBase58()).to.equal(outboundMessage.toBase58());
+
+    const updatedConfig = await (program.account as any).chainConfig.fetch(chainConfig);
+    expect(updatedConfig.outboundSequence.toString()).to.equal(sequence.addn(1).toString());
+  });
+
+  it("Relay offer clone rejects a disabled chain", async () => {
+    const chainId = new anchor.BN(56);
+    const chainConfig = await ensureChainConfig(chainId, false);
+    const tokenMapping = await ensureTokenMapping(chainId);
+
+    const id = new anchor.BN(34567);
+    const computationOffset = new anchor.BN(randomBytes(8), "hex");
+    const interchainOffer = deriveInterchainOfferPda(
+      program.programId,
+      (provider.wallet as any).payer.publicKey,
+      id
+    );
+
+    let error: any = null;
+    try {
+      await program.methods
+        .relayOfferClone(
+          id,
+          toU256Be(new anchor.BN(3_000_000_000)),
+          new anchor.BN(10_000_000_000),
+          true,
+          chainId,
+          new anchor.BN(Math.floor(Date.now() / 1000) + 600),
+          Array.from(randomBytes(32)),
+          Array.from(randomBytes(32)),
+          new anchor.BN(deserializeLE(randomBytes(16)).toString()),
+          computationOffset
+        )
+        .accounts({
+          payer: (provider.wallet as any).payer.publicKey,
+          interchainOffer,
+          chainConfig,
+          tokenMapping,
+          exchangeConfig: deriveExchangeConfigPda(program.programId),
+          relayerBond: deriveRelayerBondPda(program.programId, (provider.wallet as any).payer.publicKey),
+          signPdaAccount: getSignPdaAccAddress(program.programId),
+          computationAccount: getComputationAccAddress(
+            program.programId,
+            computationOffset
+          ),
+          clusterAccount: arciumEnv.arciumClusterPubkey,
+          mxeAccount: getMXEAccAddress(program.programId),
+          mempoolAccount: getMempoolAccAddress(program.programId),
+          executingPool: getExecutingPoolAccAddress(program.programId),
+          compDefAccount: getCompDefAccAddress(
+            program.programId,
+            Buffer.from(getCompDefAccOffset("relay_offer_clone")).readUInt32LE()
+          ),
+        } as any)
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error, "relay on a disabled chain should fail").to.not.equal(null);
+    expect(String(error)).to.include("ChainDisabled");
+  });
+
+  it("Relayer bond is slashed for a fraudulent relayed offer", async () => {
+    const admin = (provider.wallet as any).payer.publicKey;
+    const exchangeConfig = deriveExchangeConfigPda(program.programId);
+    const relayerBond = deriveRelayerBondPda(program.programId, admin);
+    const feeTreasury = deriveFeeTreasuryPda(program.programId);
+    const halfSol = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 2);
+    const quarterSol = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 4);
+
+    // 0.3% fee, 20% of it to relayers, 0.5 SOL minimum bond, no unbonding delay
+    await program.methods
+      .setProtocolParams(30, 2_000, halfSol, new anchor.BN(0))
+      .accountsPartial({ admin, exchangeConfig })
+      .rpc({ commitment: "confirmed" });
+
+    // Offer 12345 was relayed by the local wallet in "Relay offer clone works!"
+    const id = new anchor.BN(12345);
+    const interchainOffer = deriveInterchainOfferPda(program.programId, admin, id);
+    let offer = await (program.account as any).interchainOffer.fetch(interchainOffer);
+    expect(offer.relayer.toBase58()).to.equal(admin.toBase58());
+    const bondBefore = await (program.account as any).relayerBond.fetch(relayerBond);
+
+    const slashedEventPromise = awaitEvent("relayerSlashedEvent");
+    await program.methods
+      .slashRelayer(id, quarterSol)
+      .accountsPartial({
+        admin,
+        exchangeConfig,
+        maker: admin,
+        interchainOffer,
+        relayerBond,
+        feeTreasury,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const slashedEvent = await slashedEventPromise as any;
+    expect(slashedEvent.amount.toString()).to.equal(quarterSol.toString());
+    offer = await (program.account as any).interchainOffer.fetch(interchainOffer);
+    expect(offer.slashed).to.equal(true);
+    const bondAfter = await (program.account as any).relayerBond.fetch(relayerBond);
+    expect(bondAfter.bonded.toString()).to.equal(bondBefore.bonded.sub(quarterSol).toString());
+    expect(bondAfter.totalSlashed.toString()).to.equal(quarterSol.toString());
+
+    // The same offer cannot be slashed twice
+    let error: any = null;
+    try {
+      await program.methods
+        .slashRelayer(id, quarterSol)
+        .accountsPartial({
+          admin,
+          exchangeConfig,
+          maker: admin,
+          interchainOffer,
+          relayerBond,
+          feeTreasury,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error, "second slash for the same offer should fail").to.not.equal(null);
+    expect(String(error)).to.include("OfferSlashed");
+
+    // Unbond part of the remaining stake and withdraw it
+    await program.methods
+      .beginRelayerUnbonding(quarterSol)
+      .accountsPartial({ relayer: admin, relayerBond })
+      .rpc({ commitment: "confirmed" });
+    await program.methods
+      .withdrawRelayerBond()
+      .accountsPartial({ relayer: admin, relayerBond, exchangeConfig })
+      .rpc({ commitment: "confirmed" });
+
+    const bondFinal = await (program.account as any).relayerBond.fetch(relayerBond);
+    expect(bondFinal.unbonding.toString()).to.equal("0");
+    expect(bondFinal.bonded.toString()).to.equal(bondAfter.bonded.sub(quarterSol).toString());
   });
 
   it("Finalize interchain origin EVM offer works!", async () => {
@@ -533,6 +857,8 @@ describe("ConfidentialCrossChainExchange", () => {
       )
       .accountsPartial({
         payer: provider.wallet.publicKey,
+        // Offered by the local wallet earlier in this suite
+        interchainOffer: deriveInterchainOfferPda(program.programId, provider.wallet.publicKey, id),
         signPdaAccount: getSignPdaAccAddress(program.programId),
         computationAccount: getComputationAccAddress(
           program.programId,
@@ -560,7 +886,8 @@ describe("ConfidentialCrossChainExchange", () => {
 
     const finalizeEvent = await finalizeEventPromise as any;
     console.log('FinalizeInterchainOriginEvmOfferEvent:', finalizeEvent);
-    expect(finalizeEvent.acknowledged).to.equal(1);
+    expect(finalizeEvent.header.offerId.toString()).to.equal(id.toString());
+    expect(finalizeEvent.header.computationOffset.toString()).to.equal(computationOffset.toString());
   });
 
   it("Deposit seller native works!", async () => {
@@ -663,7 +990,8 @@ describe("ConfidentialCrossChainExchange", () => {
 
     const depositEvent = await depositEventPromise as any;
     console.log('DepositSellerNativeEvent:', depositEvent);
-    expect(depositEvent.acknowledged).to.equal(1);
+    expect(depositEvent.header.offerId.toString()).to.equal(id.toString());
+    expect(depositEvent.header.statusAfter).to.deep.equal({ open: {} });
   });
 
   it("Deposit seller SPL works!", async () => {
@@ -765,7 +1093,8 @@ describe("ConfidentialCrossChainExchange", () => {
 
     const depositEvent = await depositEventPromise as any;
     console.log('DepositSellerSplEvent:', depositEvent);
-    expect(depositEvent.acknowledged).to.equal(1);
+    expect(depositEvent.header.offerId.toString()).to.equal(id.toString());
+    expect(depositEvent.header.statusAfter).to.deep.equal({ open: {} });
   });
 
   it("Finalize intrachain offer works!", async () => {
@@ -811,6 +1140,8 @@ describe("ConfidentialCrossChainExchange", () => {
       )
       .accountsPartial({
         payer: provider.wallet.publicKey,
+        // Offered by the local wallet earlier in this suite
+        intrachainOffer: deriveIntrachainOfferPda(program.programId, provider.wallet.publicKey, id),
         signPdaAccount: getSignPdaAccAddress(program.programId),
         computationAccount: getComputationAccAddress(
           program.programId,
@@ -838,7 +1169,8 @@ describe("ConfidentialCrossChainExchange", () => {
 
     const finalizeEvent = await finalizeEventPromise as any;
     console.log('FinalizeIntrachainOfferEvent:', finalizeEvent);
-    expect(finalizeEvent.acknowledged).to.equal(1);
+    expect(finalizeEvent.header.offerId.toString()).to.equal(id.toString());
+    expect(finalizeEvent.header.computationOffset.toString()).to.equal(computationOffset.toString());
   });
 
   it("Complete intrachain swap with escrow & asset transfers works!", async () => {
@@ -978,6 +1310,8 @@ describe("ConfidentialCrossChainExchange", () => {
       .depositToSellerVault(offerId, tokenAAmount)
       .accountsPartial({
         seller: seller.publicKey,
+        intrachainOffer,
+        interchainOffer: null,
         sellerVault: sellerVault,
       })
       .signers([seller])
@@ -1008,6 +1342,7 @@ describe("ConfidentialCrossChainExchange", () => {
       )
       .accountsPartial({
         payer: buyer.publicKey,
+        intrachainOffer,
         signPdaAccount: getSignPdaAccAddress(program.programId),
         computationAccount: getComputationAccAddress(
           program.programId,
@@ -1053,6 +1388,9 @@ describe("ConfidentialCrossChainExchange", () => {
       .depositToBuyerVault(offerId, tokenBAmount)
       .accountsPartial({
         buyer: buyer.publicKey,
+        maker: seller.publicKey,
+        intrachainOffer,
+        interchainOffer: null,
         buyerVault: buyerVault,
       })
       .signers([buyer])
@@ -1121,6 +1459,305 @@ describe("ConfidentialCrossChainExchange", () => {
     console.log("╚══════════════════════════════════════════════════════════════╝\n");
   });
 
+  it("Solana-origin offer escrows SOL and settles on relayer attestation", async () => {
+    const owner = readKpJson(`${os.homedir()}/.config/solana/id.json`);
+    const maker = (provider.wallet as any).payer;
+    const taker = anchor.web3.Keypair.generate();
+
+    console.log("Initializing store EVM payout address computation definition");
+    await initStoreEvmPayoutAddressCompDef(program, owner, false, false);
+    console.log("Initializing reveal EVM payout address computation definition");
+    await initRevealEvmPayoutAddressCompDef(program, owner, false, false);
+
+    // The taker pays for its reservation
+    const airdropSig = await provider.connection.requestAirdrop(
+      taker.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig, "confirmed");
+
+    const chainId = new anchor.BN(1);
+    const chainConfig = await ensureChainConfig(chainId);
+    const tokenMapping = await ensureTokenMapping(chainId);
+
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
+    );
+    const privateKey = x25519.utils.randomSecretKey();
+    const publicKey = x25519.getPublicKey(privateKey);
+    const cipher = new RescueCipher(x25519.getSharedSecret(privateKey, mxePublicKey));
+
+    // Maker's EVM payout address, split into its first 16 and last 4 bytes
+    const payoutAddress = randomBytes(20);
+    const payoutHi = BigInt("0x" + payoutAddress.subarray(0, 16).toString("hex"));
+    const payoutLo = BigInt("0x" + payoutAddress.subarray(16).toString("hex"));
+    const nonce = randomBytes(16);
+    const payoutCiphertext = cipher.encrypt([payoutHi, payoutLo], nonce);
+    // Opaque to the program here; relayers attest payment against it
+    const payoutCommitment = createHash("sha256")
+      .update(Buffer.concat([payoutAddress, randomBytes(32)]))
+      .digest();
+
+    const id = new anchor.BN(45678);
+    const tokenAOffered = new anchor.BN(2 * anchor.web3.LAMPORTS_PER_SOL);
+    const evmWanted = new anchor.BN("500000000"); // 500 USDC at 6 decimals
+    const deadline = new anchor.BN(Math.floor(Date.now() / 1000) + 600);
+    const computationOffset = new anchor.BN(randomBytes(8), "hex");
+    const solanaOriginOffer = PublicKey.findProgramAddressSync(
+      [Buffer.from("SolanaOriginOffer"), maker.publicKey.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+    const storedEventPromise = awaitEvent("evmPayoutAddressStoredEvent");
+    await program.methods
+      .createSolanaOriginOfferNative(
+        id,
+        chainId,
+        tokenAOffered,
+        toU256Be(evmWanted),
+        deadline,
+        Array.from(payoutCommitment),
+        Array.from(payoutCiphertext[0]),
+        Array.from(payoutCiphertext[1]),
+        Array.from(publicKey),
+        new anchor.BN(deserializeLE(nonce).toString()),
+        new anchor.BN(deserializeLE(randomBytes(16)).toString()),
+        computationOffset
+      )
+      .accountsPartial({
+        payer: maker.publicKey,
+        solanaOriginOffer,
+        chainConfig,
+        tokenMapping,
+        signPdaAccount: getSignPdaAccAddress(program.programId),
+        computationAccount: getComputationAccAddress(
+          program.programId,
+          computationOffset
+        ),
+        clusterAccount: arciumEnv.arciumClusterPubkey,
+        mxeAccount: getMXEAccAddress(program.programId),
+        mempoolAccount: getMempoolAccAddress(program.programId),
+        executingPool: getExecutingPoolAccAddress(program.programId),
+        compDefAccount: getCompDefAccAddress(
+          program.programId,
+          Buffer.from(getCompDefAccOffset("store_evm_payout_address")).readUInt32LE()
+        ),
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    await awaitComputationFinalization(
+      provider as anchor.AnchorProvider,
+      computationOffset,
+      program.programId,
+      "confirmed"
+    );
+    const storedEvent = await storedEventPromise as any;
+    expect(storedEvent.header.offerId.toString()).to.equal(id.toString());
+
+    let offer = await (program.account as any).solanaOriginOffer.fetch(solanaOriginOffer);
+    expect(offer.status).to.have.property("open");
+    expect(Buffer.from(offer.encryptedPayoutAddress[0]).equals(Buffer.alloc(32))).to.equal(false);
+
+    // The taker reserves the offer, and only it can read the payout address
+    const takerPrivateKey = x25519.utils.randomSecretKey();
+    const takerCipher = new RescueCipher(
+      x25519.getSharedSecret(takerPrivateKey, mxePublicKey)
+    );
+    const takerEvmAddress = randomBytes(20);
+    const reserveOffset = new anchor.BN(randomBytes(8), "hex");
+    const revealedEventPromise = awaitEvent("evmPayoutAddressRevealedEvent");
+    await program.methods
+      .reserveSolanaOriginOffer(
+        id,
+        Array.from(takerEvmAddress),
+        Array.from(x25519.getPublicKey(takerPrivateKey)),
+        new anchor.BN(deserializeLE(randomBytes(16)).toString()),
+        reserveOffset
+      )
+      .accountsPartial({
+        payer: taker.publicKey,
+        maker: maker.publicKey,
+        solanaOriginOffer,
+        signPdaAccount: getSignPdaAccAddress(program.programId),
+        computationAccount: getComputationAccAddress(
+          program.programId,
+          reserveOffset
+        ),
+        clusterAccount: arciumEnv.arciumClusterPubkey,
+        mxeAccount: getMXEAccAddress(program.programId),
+        mempoolAccount: getMempoolAccAddress(program.programId),
+        executingPool: getExecutingPoolAccAddress(program.programId),
+        compDefAccount: getCompDefAccAddress(
+          program.programId,
+          Buffer.from(getCompDefAccOffset("reveal_evm_payout_address")).readUInt32LE()
+        ),
+      })
+      .signers([taker])
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    await awaitComputationFinalization(
+      provider as anchor.AnchorProvider,
+      reserveOffset,
+      program.programId,
+      "confirmed"
+    );
+    const revealedEvent = await revealedEventPromise as any;
+    expect(revealedEvent.taker.toString()).to.equal(taker.publicKey.toString());
+    const revealed = takerCipher.decrypt(
+      revealedEvent.ciphertexts,
+      new Uint8Array(revealedEvent.nonce.toArrayLike(Buffer, "le", 16))
+    );
+    expect(revealed).to.deep.equal([payoutHi, payoutLo]);
+
+    // The local wallet is the chain's trusted relayer and attests the EVM payment
+    const takerBalanceBefore = await provider.connection.getBalance(taker.publicKey);
+    const evmTxHash = randomBytes(32);
+    const processedEvmTx = PublicKey.findProgramAddressSync(
+      [Buffer.from("ProcessedEvmTx"), chainId.toArrayLike(Buffer, "le", 8), evmTxHash],
+      program.programId
+    )[0];
+    await program.methods
+      .settleSolanaOriginOffer(
+        id,
+        Array.from(evmTxHash),
+        Array.from(takerEvmAddress),
+        toU256Be(evmWanted),
+        Array.from(payoutCommitment)
+      )
+      .accountsPartial({
+        relayer: maker.publicKey,
+        maker: maker.publicKey,
+        solanaOriginOffer,
+        chainConfig,
+        processedEvmTx,
+        taker: taker.publicKey,
+        escrowTokenAccount: null,
+        takerTokenAccount: null,
+        tokenProgram: null,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    offer = await (program.account as any).solanaOriginOffer.fetch(solanaOriginOffer);
+    expect(offer.status).to.have.property("settled");
+    expect(await provider.connection.getBalance(taker.publicKey)).to.equal(
+      takerBalanceBefore + tokenAOffered.toNumber()
+    );
+  });
+
+  it("Relayer posts an EVM block header for receipt proofs", async () => {
+    const relayer = (provider.wallet as any).payer;
+    const chainId = new anchor.BN(1);
+    const chainConfig = await ensureChainConfig(chainId);
+
+    // Same recorded header as the program's receipt-proof fixtures
+    const headerRlp = Buffer.from(
+        "f90204a0ff483e972a04a9a62bb4b7d04ae403c615604e4090521ecc5bb7af67f71be09ca01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142" +
+        "fd40d49347943333333333333333333333333333333333333333a069e39af32bd0cc2d5f8ad822a3afcd7fe8d7211e4ca7c42654cdbda7a9b74516a0306ee5f7" +
+        "9df3868527ca0e28dabeabb1269f92497c02721a269672b6ee362b2ca0d9610e5b64e001c617ca01bd23109ad53ee914b180323e0e7e3368c215d835f1b90100" +
+        "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000" +
+        "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000" +
+        "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000" +
+        "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000" +
+        "80840121eac08401c9c380830324b0846553f1008766697874757265a0ae8df219bf308945ea5dfc66cefa89433ee04132e9e17361a03ae901cf9a547c880000" +
+        "00000000000007",
+      "hex"
+    );
+    const blockNumber = new anchor.BN(19_000_000);
+    const deriveHeader = (number: anchor.BN) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("EvmBlockHeader"),
+          chainId.toArrayLike(Buffer, "le", 8),
+          number.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+    const evmBlockHeader = deriveHeader(blockNumber);
+
+    const postedEventPromise = awaitEvent("evmBlockHeaderPostedEvent");
+    await program.methods
+      .postEvmBlockHeader(chainId, blockNumber, headerRlp)
+      .accountsPartial({
+        relayer: relayer.publicKey,
+        chainConfig,
+        evmBlockHeader,
+        // Not posted, so this header starts a new linked run
+        parentBlockHeader: deriveHeader(blockNumber.subn(1)),
+      })
+      .rpc({ commitment: "confirmed" });
+    const postedEvent = await postedEventPromise as any;
+    expect(postedEvent.blockNumber.toString()).to.equal(blockNumber.toString());
+
+    const header = await (program.account as any).evmBlockHeader.fetch(evmBlockHeader);
+    expect(Buffer.from(header.blockHash).toString("hex")).to.equal(
+      "1e214c77eaed6ecb8bb0613b6233b7d32667089f991d32eae4c950fc54c2e85e"
+    );
+    expect(Buffer.from(header.receiptsRoot).toString("hex")).to.equal(
+      "d9610e5b64e001c617ca01bd23109ad53ee914b180323e0e7e3368c215d835f1"
+    );
+    expect(header.linkedFrom.toString()).to.equal(blockNumber.toString());
+
+    // A header whose number does not match the PDA's block is rejected
+    const wrongBlock = new anchor.BN(19_000_001);
+    let error: any = null;
+    try {
+      await program.methods
+        .postEvmBlockHeader(chainId, wrongBlock, headerRlp)
+        .accountsPartial({
+          relayer: relayer.publicKey,
+          chainConfig,
+          evmBlockHeader: deriveHeader(wrongBlock),
+          parentBlockHeader: evmBlockHeader,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error, "header for a different block should fail").to.not.equal(null);
+    expect(String(error)).to.include("InvalidBlockHeader");
+  });
+
+  it("Admin sets a per comp def computation timeout", async () => {
+    const admin = (provider.wallet as any).payer.publicKey;
+    await ensureChainConfig(new anchor.BN(1));
+    const compDefOffset = Buffer.from(getCompDefAccOffset("finalize_intrachain_offer")).readUInt32LE();
+    const offsetBytes = Buffer.alloc(4);
+    offsetBytes.writeUInt32LE(compDefOffset);
+    const computationTimeout = PublicKey.findProgramAddressSync(
+      [Buffer.from("ComputationTimeout"), offsetBytes],
+      program.programId
+    )[0];
+
+    await program.methods
+      .setComputationTimeout(compDefOffset, new anchor.BN(3_000))
+      .accountsPartial({
+        admin,
+        exchangeConfig: deriveExchangeConfigPda(program.programId),
+        computationTimeout,
+      })
+      .rpc({ commitment: "confirmed" });
+    const timeout = await (program.account as any).computationTimeout.fetch(computationTimeout);
+    expect(timeout.compDefOffset).to.equal(compDefOffset);
+    expect(timeout.timeoutSlots.toNumber()).to.equal(3_000);
+
+    let error: any = null;
+    try {
+      await program.methods
+        .setComputationTimeout(compDefOffset, new anchor.BN(0))
+        .accountsPartial({
+          admin,
+          exchangeConfig: deriveExchangeConfigPda(program.programId),
+          computationTimeout,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error, "a zero timeout should fail").to.not.equal(null);
+    expect(String(error)).to.include("InvalidComputationTimeout");
+  });
+
   async function initAddTogetherCompDef(
     program: Program<ConfidentialCrossChainExchange>,
     owner: anchor.web3.Keypair,
@@ -1658,6 +2295,134 @@ describe("ConfidentialCrossChainExchange", () => {
     }
     return sig;
   }
+
+  async function initStoreEvmPayoutAddressCompDef(
+    program: Program<ConfidentialCrossChainExchange>,
+    owner: anchor.web3.Keypair,
+    uploadRawCircuit: boolean,
+    offchainSource: boolean
+  ): Promise<string> {
+    const baseSeedCompDefAcc = getArciumAccountBaseSeed(
+      "ComputationDefinitionAccount"
+    );
+    const offset = getCompDefAccOffset("store_evm_payout_address");
+
+    const compDefPDA = PublicKey.findProgramAddressSync(
+      [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
+      getArciumProgAddress()
+    )[0];
+
+    console.log("Comp def pda is ", compDefPDA);
+
+    const sig = await program.methods
+      .initStoreEvmPayoutAddressCompDef()
+      .accounts({
+        compDefAccount: compDefPDA,
+        payer: owner.publicKey,
+        mxeAccount: getMXEAccAddress(program.programId),
+      })
+      .signers([owner])
+      .rpc({
+        commitment: "confirmed",
+      });
+    console.log(
+      "Init store EVM payout address computation definition transaction",
+      sig
+    );
+
+    if (uploadRawCircuit) {
+      const rawCircuit = fs.readFileSync(
+        "build/store_evm_payout_address.arcis"
+      );
+
+      await uploadCircuit(
+        provider as anchor.AnchorProvider,
+        "store_evm_payout_address",
+        program.programId,
+        rawCircuit,
+        true
+      );
+    } else if (!offchainSource) {
+      const finalizeTx = await buildFinalizeCompDefTx(
+        provider as anchor.AnchorProvider,
+        Buffer.from(offset).readUInt32LE(),
+        program.programId
+      );
+
+      const latestBlockhash = await provider.connection.getLatestBlockhash();
+      finalizeTx.recentBlockhash = latestBlockhash.blockhash;
+      finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+
+      finalizeTx.sign(owner);
+
+      await provider.sendAndConfirm(finalizeTx);
+    }
+    return sig;
+  }
+
+  async function initRevealEvmPayoutAddressCompDef(
+    program: Program<ConfidentialCrossChainExchange>,
+    owner: anchor.web3.Keypair,
+    uploadRawCircuit: boolean,
+    offchainSource: boolean
+  ): Promise<string> {
+    const baseSeedCompDefAcc = getArciumAccountBaseSeed(
+      "ComputationDefinitionAccount"
+    );
+    const offset = getCompDefAccOffset("reveal_evm_payout_address");
+
+    const compDefPDA = PublicKey.findProgramAddressSync(
+      [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
+      getArciumProgAddress()
+    )[0];
+
+    console.log("Comp def pda is ", compDefPDA);
+
+    const sig = await program.methods
+      .initRevealEvmPayoutAddressCompDef()
+      .accounts({
+        compDefAccount: compDefPDA,
+        payer: owner.publicKey,
+        mxeAccount: getMXEAccAddress(program.programId),
+      })
+      .signers([owner])
+      .rpc({
+        commitment: "confirmed",
+      });
+    console.log(
+      "Init reveal EVM payout address computation definition transaction",
+      sig
+    );
+
+    if (uploadRawCircuit) {
+      const rawCircuit = fs.readFileSync(
+        "build/reveal_evm_payout_address.arcis"
+      );
+
+      await uploadCircuit(
+        provider as anchor.AnchorProvider,
+        "reveal_evm_payout_address",
+        program.programId,
+        rawCircuit,
+        true
+      );
+    } else if (!offchainSource) {
+      const finalizeTx = await buildFinalizeCompDefTx(
+        provider as anchor.AnchorProvider,
+        Buffer.from(offset).readUInt32LE(),
+        program.programId
+      );
+
+      const latestBlockhash = await provider.connection.getLatestBlockhash();
+      finalizeTx.recentBlockhash = latestBlockhash.blockhash;
+      finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+
+      finalizeTx.sign(owner);
+
+      await provider.sendAndConfirm(finalizeTx);
+    }
+    return sig;
+  }
 });
 
 async function getMXEPublicKeyWithRetry(